@@ -103,6 +103,21 @@ export const enum TextPosition {
   TopLeft = 'TopLeft',
   Inside = 'Inside'
 }
+/** Code of an error thrown by the bindings, available as `err.code` */
+export const enum ErrorCode {
+  ElementNotFound = 'ELEMENT_NOT_FOUND',
+  OperationTimedOut = 'OPERATION_TIMED_OUT',
+  PermissionDenied = 'PERMISSION_DENIED',
+  PlatformError = 'PLATFORM_ERROR',
+  UnsupportedOperation = 'UNSUPPORTED_OPERATION',
+  UnsupportedPlatform = 'UNSUPPORTED_PLATFORM',
+  InvalidArgument = 'INVALID_ARGUMENT',
+  InternalError = 'INTERNAL_ERROR',
+  InvalidSelector = 'INVALID_SELECTOR',
+  ElementObscured = 'ELEMENT_OBSCURED',
+  TreeChanged = 'TREE_CHANGED',
+  UiAutomationApiError = 'UI_AUTOMATION_API_ERROR'
+}
 export interface FontStyle {
   size: number
   bold: boolean
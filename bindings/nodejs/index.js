@@ -310,7 +310,7 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { Desktop, Element, Locator, Selector, PropertyLoadingMode, TextPosition, HighlightHandle } = nativeBinding
+const { Desktop, Element, Locator, Selector, PropertyLoadingMode, TextPosition, ErrorCode, HighlightHandle } = nativeBinding
 
 module.exports.Desktop = Desktop
 module.exports.Element = Element
@@ -318,4 +318,5 @@ module.exports.Locator = Locator
 module.exports.Selector = Selector
 module.exports.PropertyLoadingMode = PropertyLoadingMode
 module.exports.TextPosition = TextPosition
+module.exports.ErrorCode = ErrorCode
 module.exports.HighlightHandle = HighlightHandle
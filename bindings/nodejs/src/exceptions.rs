@@ -1,40 +1,250 @@
+//! Mapping of Terminator errors to NAPI errors.
+//!
+//! A NAPI error only carries a message, so the message holds everything: a code prefix
+//! and the human-readable text (`ELEMENT_NOT_FOUND: ...`), followed by a line starting
+//! with [`DETAILS_SEPARATOR`] and a JSON object with `code`, `message` and, for locator
+//! errors, `selector`. UI Automation API errors also have `operation`, `retryable` and,
+//! when the call failed with one, the numeric `hresult`. `wrapper.js` strips that line
+//! and exposes it as `err.code` and `err.details` on the error class it throws.
+
 use napi::{self, Status};
+use napi_derive::napi;
+use serde_json::{json, Map, Value};
 use terminator::errors::AutomationError;
 
+/// Starts the line of the error message that holds the JSON details
+pub const DETAILS_SEPARATOR: &str = "\nTERMINATOR_ERROR_DETAILS: ";
+
+/// Code of an error thrown by the bindings, available as `err.code`
+#[napi(string_enum = "SCREAMING_SNAKE_CASE")]
+#[derive(Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    ElementNotFound,
+    OperationTimedOut,
+    PermissionDenied,
+    PlatformError,
+    UnsupportedOperation,
+    UnsupportedPlatform,
+    InvalidArgument,
+    InternalError,
+    InvalidSelector,
+    ElementObscured,
+    TreeChanged,
+    UiAutomationApiError,
+}
+
+impl ErrorCode {
+    /// The code as it appears in `err.code` and as the message prefix
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::ElementNotFound => "ELEMENT_NOT_FOUND",
+            ErrorCode::OperationTimedOut => "OPERATION_TIMED_OUT",
+            ErrorCode::PermissionDenied => "PERMISSION_DENIED",
+            ErrorCode::PlatformError => "PLATFORM_ERROR",
+            ErrorCode::UnsupportedOperation => "UNSUPPORTED_OPERATION",
+            ErrorCode::UnsupportedPlatform => "UNSUPPORTED_PLATFORM",
+            ErrorCode::InvalidArgument => "INVALID_ARGUMENT",
+            ErrorCode::InternalError => "INTERNAL_ERROR",
+            ErrorCode::InvalidSelector => "INVALID_SELECTOR",
+            ErrorCode::ElementObscured => "ELEMENT_OBSCURED",
+            ErrorCode::TreeChanged => "TREE_CHANGED",
+            ErrorCode::UiAutomationApiError => "UI_AUTOMATION_API_ERROR",
+        }
+    }
+}
+
 /// Map Terminator errors to NAPI errors
 pub fn map_error(err: AutomationError) -> napi::Error {
-    match err {
+    to_napi_error(err, None)
+}
+
+/// Map an error from finding elements with `selector`, which is kept in the details
+pub fn map_selector_error(err: AutomationError, selector: &str) -> napi::Error {
+    to_napi_error(err, Some(selector))
+}
+
+fn to_napi_error(err: AutomationError, selector: Option<&str>) -> napi::Error {
+    let mut details = Map::new();
+    let (status, code, message) = match err {
         AutomationError::ElementNotFound(msg) => {
-            napi::Error::new(Status::InvalidArg, format!("ELEMENT_NOT_FOUND: {msg}"))
+            (Status::InvalidArg, ErrorCode::ElementNotFound, msg)
         }
-        AutomationError::Timeout(msg) => napi::Error::new(
-            Status::GenericFailure,
-            format!("OPERATION_TIMED_OUT: {msg}"),
-        ),
-        AutomationError::PermissionDenied(msg) => {
-            napi::Error::new(Status::GenericFailure, format!("PERMISSION_DENIED: {msg}"))
+        AutomationError::Timeout(msg) => {
+            (Status::GenericFailure, ErrorCode::OperationTimedOut, msg)
         }
-        AutomationError::PlatformError(e) => {
-            napi::Error::new(Status::GenericFailure, format!("PLATFORM_ERROR: {e}"))
+        AutomationError::PermissionDenied(msg) => {
+            (Status::GenericFailure, ErrorCode::PermissionDenied, msg)
         }
+        AutomationError::PlatformError(e) => (Status::GenericFailure, ErrorCode::PlatformError, e),
         AutomationError::UnsupportedOperation(msg) => {
-            napi::Error::new(Status::InvalidArg, format!("UNSUPPORTED_OPERATION: {msg}"))
+            (Status::InvalidArg, ErrorCode::UnsupportedOperation, msg)
         }
         AutomationError::UnsupportedPlatform(msg) => {
-            napi::Error::new(Status::InvalidArg, format!("UNSUPPORTED_PLATFORM: {msg}"))
-        }
-        AutomationError::InvalidArgument(e) => {
-            napi::Error::new(Status::InvalidArg, format!("INVALID_ARGUMENT: {e}"))
+            (Status::InvalidArg, ErrorCode::UnsupportedPlatform, msg)
         }
-        AutomationError::Internal(e) => {
-            napi::Error::new(Status::GenericFailure, format!("INTERNAL_ERROR: {e}"))
+        AutomationError::InvalidArgument(e) => (Status::InvalidArg, ErrorCode::InvalidArgument, e),
+        AutomationError::Internal(e) => (Status::GenericFailure, ErrorCode::InternalError, e),
+        AutomationError::InvalidSelector(e) => (Status::InvalidArg, ErrorCode::InvalidSelector, e),
+        AutomationError::ElementObscured(e) => {
+            (Status::GenericFailure, ErrorCode::ElementObscured, e)
         }
-        AutomationError::InvalidSelector(e) => {
-            napi::Error::new(Status::InvalidArg, format!("INVALID_SELECTOR: {e}"))
+        AutomationError::TreeChanged(e) => (Status::GenericFailure, ErrorCode::TreeChanged, e),
+        AutomationError::UIAutomationAPIError {
+            message,
+            com_error,
+            operation,
+            is_retryable,
+        } => {
+            let hresult = com_error
+                .map(|c| format!(" hresult=0x{:08X}", c as u32))
+                .unwrap_or_default();
+            if let Some(c) = com_error {
+                details.insert("hresult".to_string(), json!(c as u32));
+            }
+            details.insert("operation".to_string(), json!(operation));
+            details.insert("retryable".to_string(), json!(is_retryable));
+            (
+                Status::GenericFailure,
+                ErrorCode::UiAutomationApiError,
+                format!("{message} (operation={operation}{hresult} retryable={is_retryable})"),
+            )
         }
-        AutomationError::UIAutomationAPIError { message, .. } => napi::Error::new(
-            Status::GenericFailure,
-            format!("UI_AUTOMATION_API_ERROR: {message}"),
+    };
+
+    let code = code.as_str();
+    details.insert("code".to_string(), json!(code));
+    details.insert("message".to_string(), json!(message));
+    if let Some(selector) = selector {
+        details.insert("selector".to_string(), json!(selector));
+    }
+    napi::Error::new(
+        status,
+        format!(
+            "{code}: {message}{DETAILS_SEPARATOR}{}",
+            Value::Object(details)
         ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use terminator::errors::hresult::UIA_E_ELEMENTNOTAVAILABLE;
+
+    /// The message before the details line, and the details
+    fn split(err: &napi::Error) -> (&str, Value) {
+        let (message, details) = err
+            .reason
+            .split_once(DETAILS_SEPARATOR)
+            .expect("details line");
+        (message, serde_json::from_str(details).unwrap())
+    }
+
+    #[test]
+    fn test_tree_changed_is_not_flattened_into_internal() {
+        let err = map_error(AutomationError::TreeChanged(
+            "Element left the UI tree".to_string(),
+        ));
+        assert_eq!(err.status, Status::GenericFailure);
+        assert_eq!(split(&err).0, "TREE_CHANGED: Element left the UI tree");
+
+        let err = map_error(AutomationError::Internal("poisoned lock".to_string()));
+        assert_eq!(split(&err).0, "INTERNAL_ERROR: poisoned lock");
+    }
+
+    #[test]
+    fn test_selector_errors_carry_structured_details() {
+        let err = map_selector_error(
+            AutomationError::ElementNotFound("Name: 'Submit', Err: not found".to_string()),
+            "role:Button|name:Submit",
+        );
+        assert_eq!(err.status, Status::InvalidArg);
+
+        let (message, details) = split(&err);
+        // The human-readable message is unchanged
+        assert_eq!(message, "ELEMENT_NOT_FOUND: Name: 'Submit', Err: not found");
+        assert_eq!(
+            details,
+            json!({
+                "code": "ELEMENT_NOT_FOUND",
+                "message": "Name: 'Submit', Err: not found",
+                "selector": "role:Button|name:Submit"
+            })
+        );
+
+        // Errors that did not come from a locator have no selector
+        let (_, details) = split(&map_error(AutomationError::Timeout("slow".to_string())));
+        assert_eq!(
+            details,
+            json!({"code": "OPERATION_TIMED_OUT", "message": "slow"})
+        );
+    }
+
+    #[test]
+    fn test_ui_automation_api_error_keeps_its_hresult() {
+        let err = map_error(AutomationError::UIAutomationAPIError {
+            message: "Failed to get pattern".to_string(),
+            com_error: Some(UIA_E_ELEMENTNOTAVAILABLE),
+            operation: "get_pattern".to_string(),
+            is_retryable: true,
+        });
+
+        let (message, details) = split(&err);
+        assert_eq!(
+            message,
+            "UI_AUTOMATION_API_ERROR: Failed to get pattern (operation=get_pattern hresult=0x80040201 retryable=true)"
+        );
+        assert_eq!(details["hresult"], json!(0x80040201_u32));
+        assert_eq!(
+            details["hresult"].as_u64().map(|c| c as u32 as i32),
+            Some(UIA_E_ELEMENTNOTAVAILABLE)
+        );
+        assert_eq!(details["operation"], "get_pattern");
+        assert_eq!(details["retryable"], true);
+
+        let (_, details) = split(&map_error(AutomationError::UIAutomationAPIError {
+            message: "call failed".to_string(),
+            com_error: None,
+            operation: "find_first".to_string(),
+            is_retryable: false,
+        }));
+        assert!(details.get("hresult").is_none());
+    }
+
+    #[test]
+    fn test_message_prefix_and_details_use_the_error_code() {
+        let errors = [
+            (
+                AutomationError::ElementNotFound("x".to_string()),
+                ErrorCode::ElementNotFound,
+            ),
+            (
+                AutomationError::Timeout("x".to_string()),
+                ErrorCode::OperationTimedOut,
+            ),
+            (
+                AutomationError::InvalidSelector("x".to_string()),
+                ErrorCode::InvalidSelector,
+            ),
+            (
+                AutomationError::TreeChanged("x".to_string()),
+                ErrorCode::TreeChanged,
+            ),
+            (
+                AutomationError::UIAutomationAPIError {
+                    message: "x".to_string(),
+                    com_error: None,
+                    operation: "x".to_string(),
+                    is_retryable: false,
+                },
+                ErrorCode::UiAutomationApiError,
+            ),
+        ];
+        for (error, code) in errors {
+            let err = map_error(error);
+            let (message, details) = split(&err);
+            assert!(message.starts_with(&format!("{}: ", code.as_str())));
+            assert_eq!(details["code"], code.as_str());
+        }
     }
 }
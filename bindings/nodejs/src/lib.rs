@@ -17,4 +17,4 @@ pub use types::{
 };
 
 // Error handling - see exceptions.rs for detailed architecture
-pub use exceptions::map_error;
+pub use exceptions::{map_error, map_selector_error};
@@ -1,7 +1,7 @@
 use napi_derive::napi;
 use terminator::Locator as TerminatorLocator;
 
-use crate::map_error;
+use crate::map_selector_error;
 use crate::Element;
 use crate::Selector;
 use napi::bindgen_prelude::Either;
@@ -35,7 +35,7 @@ impl Locator {
             .first(None)
             .await
             .map(Element::from)
-            .map_err(map_error)
+            .map_err(|e| map_selector_error(e, &self.inner.selector_string()))
     }
 
     /// (async) Get all matching elements.
@@ -56,7 +56,7 @@ impl Locator {
             .all(timeout, depth)
             .await
             .map(|els| els.into_iter().map(Element::from).collect())
-            .map_err(map_error)
+            .map_err(|e| map_selector_error(e, &self.inner.selector_string()))
     }
 
     /// (async) Wait for the first matching element.
@@ -71,7 +71,7 @@ impl Locator {
             .wait(timeout)
             .await
             .map(Element::from)
-            .map_err(map_error)
+            .map_err(|e| map_selector_error(e, &self.inner.selector_string()))
     }
 
     /// Set a default timeout for this locator.
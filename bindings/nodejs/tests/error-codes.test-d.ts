@@ -0,0 +1,34 @@
+/**
+ * Type-level test for the ErrorCode enum, checked with `npm run test:types`.
+ * It fails to compile if a code is added, removed or renamed without updating it.
+ */
+import { ErrorCode, ErrorDetails, ElementNotFoundError, TreeChangedError } from '../wrapper';
+
+type Equals<A, B> = (<T>() => T extends A ? 1 : 2) extends (<T>() => T extends B ? 1 : 2) ? true : false;
+type Assert<T extends true> = T;
+
+// The enum values are exactly the message prefixes thrown by the native module
+export type AllCodes = Assert<Equals<`${ErrorCode}`,
+    | 'ELEMENT_NOT_FOUND'
+    | 'OPERATION_TIMED_OUT'
+    | 'PERMISSION_DENIED'
+    | 'PLATFORM_ERROR'
+    | 'UNSUPPORTED_OPERATION'
+    | 'UNSUPPORTED_PLATFORM'
+    | 'INVALID_ARGUMENT'
+    | 'INTERNAL_ERROR'
+    | 'INVALID_SELECTOR'
+    | 'ELEMENT_OBSCURED'
+    | 'TREE_CHANGED'
+    | 'UI_AUTOMATION_API_ERROR'
+>>;
+export type ElementNotFoundCode = Assert<Equals<`${ErrorCode.ElementNotFound}`, 'ELEMENT_NOT_FOUND'>>;
+export type TreeChangedCode = Assert<Equals<`${ErrorCode.TreeChanged}`, 'TREE_CHANGED'>>;
+
+// err.code and err.details.code are typed as ErrorCode, not string
+export type DetailsCode = Assert<Equals<ErrorDetails['code'], ErrorCode>>;
+export type ElementNotFoundErrorCode = Assert<Equals<ElementNotFoundError['code'], ErrorCode | undefined>>;
+export type TreeChangedErrorDetails = Assert<Equals<TreeChangedError['details'], ErrorDetails | undefined>>;
+
+// A handler keyed by every code must cover them all
+export type HandlerTable = { [Code in ErrorCode]: (details: ErrorDetails) => void };
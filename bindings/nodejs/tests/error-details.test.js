@@ -0,0 +1,55 @@
+const { Desktop, ErrorCode, TimeoutError } = require("../wrapper.js");
+
+/**
+ * Test for the structured fields (err.code, err.details) on errors thrown by the bindings
+ */
+async function testLocatorErrorDetails() {
+  console.log("🧾 Testing structured error details...");
+
+  try {
+    const desktop = new Desktop();
+    const selector = "role:button|name:Terminator Missing Button 7f3c";
+
+    try {
+      await desktop.locator(selector).timeout(500).first();
+      throw new Error("Expected the locator to fail");
+    } catch (err) {
+      if (!(err instanceof TimeoutError)) {
+        throw err;
+      }
+      if (err.code !== ErrorCode.OperationTimedOut || err.code !== "OPERATION_TIMED_OUT") {
+        throw new Error(`Unexpected err.code: ${err.code}`);
+      }
+      if (!err.details || err.details.code !== err.code) {
+        throw new Error(`Missing err.details: ${JSON.stringify(err.details)}`);
+      }
+      if (!err.details.selector || !err.details.selector.includes("Terminator Missing Button 7f3c")) {
+        throw new Error(`err.details.selector lacks the selector: ${err.details.selector}`);
+      }
+      if (err.details.message !== err.message) {
+        throw new Error("err.details.message differs from err.message");
+      }
+      if (err.message.includes("TERMINATOR_ERROR_DETAILS")) {
+        throw new Error("The details line was left in err.message");
+      }
+      console.log(`✅ ${err.code} with selector ${err.details.selector}`);
+    }
+
+    return true;
+  } catch (error) {
+    console.error("❌ Error details test failed:", error.message);
+    return false;
+  }
+}
+
+// Run tests if this file is executed directly
+if (require.main === module) {
+  testLocatorErrorDetails()
+    .then((passed) => process.exit(passed ? 0 : 1))
+    .catch((error) => {
+      console.error("💥 Test runner crashed:", error);
+      process.exit(1);
+    });
+}
+
+module.exports = { testLocatorErrorDetails };
@@ -1,42 +1,89 @@
 // Re-export all types and interfaces from the original declaration file
 export * from './index.d';
+import { ErrorCode } from './index.d';
+
+/** Structured details attached to every error thrown by the bindings as `err.details`. */
+export interface ErrorDetails {
+    /** Error code, e.g. `ErrorCode.ElementNotFound`; also available as `err.code`. */
+    code: ErrorCode;
+    /** Human-readable message, without the code prefix. */
+    message: string;
+    /** The selector that was being searched for, for errors thrown by a locator. */
+    selector?: string;
+    /** HRESULT of a failed Windows UI Automation call, e.g. `0x80040201`. */
+    hresult?: number;
+    /** UI Automation operation that failed. */
+    operation?: string;
+    /** Whether the failed UI Automation call may succeed if retried. */
+    retryable?: boolean;
+}
 
 /** Thrown when an element is not found. */
 export class ElementNotFoundError extends Error {
+    code?: ErrorCode;
+    details?: ErrorDetails;
     constructor(message: string);
 }
 
 /** Thrown when an operation times out. */
 export class TimeoutError extends Error {
+    code?: ErrorCode;
+    details?: ErrorDetails;
     constructor(message: string);
 }
 
 /** Thrown when permission is denied. */
 export class PermissionDeniedError extends Error {
+    code?: ErrorCode;
+    details?: ErrorDetails;
     constructor(message: string);
 }
 
 /** Thrown for platform-specific errors. */
 export class PlatformError extends Error {
+    code?: ErrorCode;
+    details?: ErrorDetails;
     constructor(message: string);
 }
 
 /** Thrown for unsupported operations. */
 export class UnsupportedOperationError extends Error {
+    code?: ErrorCode;
+    details?: ErrorDetails;
     constructor(message: string);
 }
 
 /** Thrown for unsupported platforms. */
 export class UnsupportedPlatformError extends Error {
+    code?: ErrorCode;
+    details?: ErrorDetails;
     constructor(message: string);
 }
 
 /** Thrown for invalid arguments. */
 export class InvalidArgumentError extends Error {
+    code?: ErrorCode;
+    details?: ErrorDetails;
     constructor(message: string);
 }
 
 /** Thrown for internal errors. */
 export class InternalError extends Error {
+    code?: ErrorCode;
+    details?: ErrorDetails;
+    constructor(message: string);
+}
+
+/** Thrown when another window covers the point an action would click. */
+export class ElementObscuredError extends Error {
+    code?: ErrorCode;
+    details?: ErrorDetails;
+    constructor(message: string);
+}
+
+/** Thrown when the UI changed under a found element; find it again and retry. */
+export class TreeChangedError extends Error {
+    code?: ErrorCode;
+    details?: ErrorDetails;
     constructor(message: string);
 } 
\ No newline at end of file
@@ -114,11 +114,51 @@ class InternalError extends Error {
     }
 }
 
+class ElementObscuredError extends Error {
+    constructor(message) {
+        super(message);
+        this.name = 'ElementObscuredError';
+    }
+}
+
+class TreeChangedError extends Error {
+    constructor(message) {
+        super(message);
+        this.name = 'TreeChangedError';
+    }
+}
+
+// Native error messages end with a line holding JSON details (see src/exceptions.rs)
+const DETAILS_SEPARATOR = '\nTERMINATOR_ERROR_DETAILS: ';
+
 // Error mapping function
 function mapNativeError(error) {
     if (!error.message) return error;
-    
-    const message = error.message;
+
+    let message = error.message;
+    let details;
+    const separator = message.lastIndexOf(DETAILS_SEPARATOR);
+    if (separator !== -1) {
+        try {
+            details = JSON.parse(message.slice(separator + DETAILS_SEPARATOR.length));
+            message = message.slice(0, separator);
+        } catch (_) {
+            // Not a details line; keep the whole message
+        }
+    }
+
+    const mapped = errorFromMessage(message, error);
+    if (details) {
+        if (mapped === error) {
+            error.message = message;
+        }
+        mapped.code = details.code;
+        mapped.details = details;
+    }
+    return mapped;
+}
+
+function errorFromMessage(message, error) {
     if (message.startsWith('ELEMENT_NOT_FOUND:')) {
         return new ElementNotFoundError(message.replace('ELEMENT_NOT_FOUND:', '').trim());
     }
@@ -143,6 +183,12 @@ function mapNativeError(error) {
     if (message.startsWith('INTERNAL_ERROR:')) {
         return new InternalError(message.replace('INTERNAL_ERROR:', '').trim());
     }
+    if (message.startsWith('ELEMENT_OBSCURED:')) {
+        return new ElementObscuredError(message.replace('ELEMENT_OBSCURED:', '').trim());
+    }
+    if (message.startsWith('TREE_CHANGED:')) {
+        return new TreeChangedError(message.replace('TREE_CHANGED:', '').trim());
+    }
     return error;
 }
 
@@ -158,6 +204,7 @@ module.exports = {
     Element,
     Locator,
     Selector,
+    ErrorCode: native.ErrorCode,
     // Export error classes
     ElementNotFoundError,
     TimeoutError,
@@ -166,5 +213,7 @@ module.exports = {
     UnsupportedOperationError,
     UnsupportedPlatformError,
     InvalidArgumentError,
-    InternalError
+    InternalError,
+    ElementObscuredError,
+    TreeChangedError
 }; 
\ No newline at end of file
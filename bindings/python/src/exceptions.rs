@@ -34,6 +34,16 @@ create_exception!(
     InvalidSelectorError,
     pyo3::exceptions::PyRuntimeError
 );
+create_exception!(
+    terminator,
+    ElementObscuredError,
+    pyo3::exceptions::PyRuntimeError
+);
+create_exception!(
+    terminator,
+    TreeChangedError,
+    pyo3::exceptions::PyRuntimeError
+);
 
 use ::terminator_core::errors::AutomationError;
 
@@ -50,6 +60,8 @@ pub fn automation_error_to_pyerr(e: AutomationError) -> pyo3::PyErr {
         AutomationError::InvalidArgument(_) => InvalidArgumentError::new_err(msg),
         AutomationError::Internal(_) => InternalError::new_err(msg),
         AutomationError::InvalidSelector(_) => InvalidSelectorError::new_err(msg),
+        AutomationError::ElementObscured(_) => ElementObscuredError::new_err(msg),
+        AutomationError::TreeChanged(_) => TreeChangedError::new_err(msg),
         AutomationError::UIAutomationAPIError { .. } => PlatformError::new_err(msg),
     }
 }
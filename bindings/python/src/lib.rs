@@ -60,6 +60,11 @@ fn terminator(_py: Python, m: &Bound<'_, PyModule>) -> PyResult<()> {
         _py.get_type::<InvalidArgumentError>(),
     )?;
     m.add("InternalError", _py.get_type::<InternalError>())?;
+    m.add(
+        "ElementObscuredError",
+        _py.get_type::<ElementObscuredError>(),
+    )?;
+    m.add("TreeChangedError", _py.get_type::<TreeChangedError>())?;
     Ok(())
 }
 
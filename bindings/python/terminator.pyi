@@ -879,6 +879,8 @@ class UINode:
 
 class ElementNotFoundError(RuntimeError): ...
 
+class ElementObscuredError(RuntimeError): ...
+
 class InternalError(RuntimeError): ...
 
 class InvalidArgumentError(RuntimeError): ...
@@ -889,6 +891,8 @@ class PlatformError(RuntimeError): ...
 
 class TimeoutError(RuntimeError): ...
 
+class TreeChangedError(RuntimeError): ...
+
 class UnsupportedOperationError(RuntimeError): ...
 
 class UnsupportedPlatformError(RuntimeError): ...
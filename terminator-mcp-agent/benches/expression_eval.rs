@@ -0,0 +1,59 @@
+//! Compares step-condition evaluation by tree walk against the indexed context.
+//!
+//! Run with `cargo bench -p terminator-mcp-agent --bench expression_eval`. The indexed
+//! numbers include creating the context, as the sequence runner creates one per step.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde_json::{json, Value};
+use terminator_mcp_agent::expression_eval::{evaluate, evaluate_indexed, IndexedContext};
+
+/// `width` variables, each nested `depth` levels deep
+fn nested_variables(width: usize, depth: usize) -> Value {
+    let mut vars = serde_json::Map::new();
+    for i in 0..width {
+        let mut leaf = json!(i);
+        for level in (1..depth).rev() {
+            leaf = json!({ format!("l{level}"): leaf });
+        }
+        vars.insert(format!("v{i}"), leaf);
+    }
+    Value::Object(vars)
+}
+
+fn condition(width: usize, depth: usize) -> String {
+    let last = width - 1;
+    let path: Vec<String> = std::iter::once(format!("v{last}"))
+        .chain((1..depth).map(|level| format!("l{level}")))
+        .collect();
+    format!("{} == '{last}' && !env.missing", path.join("."))
+}
+
+fn step_conditions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("step_conditions");
+    for width in [10, 1000] {
+        let vars = nested_variables(width, 10);
+        let expr = condition(width, 10);
+        for evaluations in [1, 100] {
+            let id = format!("{width}vars/{evaluations}evals");
+            group.bench_with_input(BenchmarkId::new("tree_walk", &id), &vars, |b, vars| {
+                b.iter(|| {
+                    for _ in 0..evaluations {
+                        black_box(evaluate(black_box(&expr), vars));
+                    }
+                })
+            });
+            group.bench_with_input(BenchmarkId::new("indexed", &id), &vars, |b, vars| {
+                b.iter(|| {
+                    let context = IndexedContext::new(vars);
+                    for _ in 0..evaluations {
+                        black_box(evaluate_indexed(black_box(&expr), &context));
+                    }
+                })
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, step_conditions);
+criterion_main!(benches);
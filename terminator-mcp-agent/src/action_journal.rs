@@ -0,0 +1,198 @@
+//! Journal of recent actions, used by the `undo` tool.
+//!
+//! State-changing tools record what they did together with an inverse: the previous
+//! toggle/selection/range/value state of the element, or an application-level undo
+//! (Ctrl+Z) for typing. `undo(n)` replays the inverses of the last `n` actions,
+//! newest first. Actions without an inverse (clicks, closing windows, ...) are
+//! reported as skipped.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::time::Duration;
+use terminator::{AutomationError, Desktop};
+
+/// Number of actions kept in the journal
+pub const DEFAULT_JOURNAL_CAPACITY: usize = 100;
+
+/// How to reverse one action
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Inverse {
+    SetToggled {
+        selector: String,
+        state: bool,
+    },
+    SetSelected {
+        selector: String,
+        state: bool,
+    },
+    SetRangeValue {
+        selector: String,
+        value: f64,
+    },
+    SetValue {
+        selector: String,
+        value: String,
+    },
+    /// Ask the application to undo (Ctrl+Z), on the element or on whatever has focus
+    AppUndo {
+        selector: Option<String>,
+    },
+}
+
+/// One recorded action
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JournalEntry {
+    pub tool_name: String,
+    pub selector: Option<String>,
+    pub inverse: Option<Inverse>,
+}
+
+impl JournalEntry {
+    pub fn reversible(tool_name: &str, selector: Option<&str>, inverse: Inverse) -> Self {
+        Self {
+            tool_name: tool_name.to_string(),
+            selector: selector.map(str::to_string),
+            inverse: Some(inverse),
+        }
+    }
+
+    pub fn irreversible(tool_name: &str, selector: Option<&str>) -> Self {
+        Self {
+            tool_name: tool_name.to_string(),
+            selector: selector.map(str::to_string),
+            inverse: None,
+        }
+    }
+}
+
+/// Bounded list of recent actions, oldest first
+#[derive(Debug)]
+pub struct ActionJournal {
+    entries: VecDeque<JournalEntry>,
+    capacity: usize,
+}
+
+impl Default for ActionJournal {
+    fn default() -> Self {
+        Self::new(DEFAULT_JOURNAL_CAPACITY)
+    }
+}
+
+impl ActionJournal {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Records an action, forgetting the oldest one when the journal is full.
+    pub fn record(&mut self, entry: JournalEntry) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Removes and returns up to `n` of the most recent actions, newest first.
+    pub fn take_last(&mut self, n: usize) -> Vec<JournalEntry> {
+        let n = n.min(self.entries.len());
+        let mut taken: Vec<JournalEntry> = self.entries.drain(self.entries.len() - n..).collect();
+        taken.reverse();
+        taken
+    }
+}
+
+/// Applies inverses to the desktop
+pub trait UndoBackend {
+    fn apply(&self, inverse: &Inverse) -> impl Future<Output = Result<(), String>> + Send;
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UndoFailure {
+    pub entry: JournalEntry,
+    pub error: String,
+}
+
+/// What `undo` did with each action, newest first
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UndoReport {
+    pub undone: Vec<JournalEntry>,
+    pub skipped: Vec<JournalEntry>,
+    pub failed: Vec<UndoFailure>,
+}
+
+/// Replays the inverses of `entries` in order (pass them newest first, as returned by
+/// [`ActionJournal::take_last`]). A failed inverse does not stop the remaining ones.
+pub async fn undo_entries<B: UndoBackend>(entries: Vec<JournalEntry>, backend: &B) -> UndoReport {
+    let mut report = UndoReport::default();
+    for entry in entries {
+        let Some(inverse) = &entry.inverse else {
+            report.skipped.push(entry);
+            continue;
+        };
+        match backend.apply(inverse).await {
+            Ok(()) => report.undone.push(entry),
+            Err(error) => report.failed.push(UndoFailure { entry, error }),
+        }
+    }
+    report
+}
+
+/// Applies inverses by locating elements on the live desktop
+pub struct DesktopUndo<'a> {
+    pub desktop: &'a Desktop,
+    pub timeout: Duration,
+}
+
+impl DesktopUndo<'_> {
+    async fn find(&self, selector: &str) -> Result<terminator::UIElement, AutomationError> {
+        self.desktop
+            .locator(terminator::Selector::from(selector))
+            .first(Some(self.timeout))
+            .await
+    }
+}
+
+impl UndoBackend for DesktopUndo<'_> {
+    async fn apply(&self, inverse: &Inverse) -> Result<(), String> {
+        let result = match inverse {
+            Inverse::SetToggled { selector, state } => self
+                .find(selector)
+                .await
+                .and_then(|e| e.set_toggled(*state)),
+            Inverse::SetSelected { selector, state } => self
+                .find(selector)
+                .await
+                .and_then(|e| e.set_selected(*state)),
+            Inverse::SetRangeValue { selector, value } => self
+                .find(selector)
+                .await
+                .and_then(|e| e.set_range_value(*value)),
+            Inverse::SetValue { selector, value } => {
+                self.find(selector).await.and_then(|e| e.set_value(value))
+            }
+            Inverse::AppUndo {
+                selector: Some(selector),
+            } => self
+                .find(selector)
+                .await
+                .and_then(|e| e.press_key("{Ctrl}z")),
+            Inverse::AppUndo { selector: None } => self
+                .desktop
+                .focused_element()
+                .and_then(|e| e.press_key("{Ctrl}z")),
+        };
+        result.map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,102 @@
+//! What this agent build supports, reported by the `capabilities` tool.
+//!
+//! Clients call it once after connecting and adapt (hide tools, skip OCR, pick a
+//! selector syntax) instead of probing with calls that may fail. Everything except
+//! the tool list is fixed at compile time.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use terminator::Selector;
+
+/// Transports the server binary can be started with (`--transport`)
+pub const TRANSPORTS: &[&str] = &["stdio", "sse", "http"];
+
+/// Selector syntaxes understood by `terminator::Selector`
+pub const SELECTOR_TYPES: &[&str] = &[
+    "role",
+    "name",
+    "id",
+    "text",
+    "nativeid",
+    "classname",
+    "attr",
+    "visible",
+    "rightof",
+    "leftof",
+    "above",
+    "below",
+    "near",
+    "has",
+    "nth",
+    "path",
+    "chain",
+    "state",
+];
+
+/// Selector syntaxes that depend on optional features of `terminator`
+pub const FEATURE_SELECTOR_TYPES: &[&str] = &["ocr", "image"];
+
+/// Cargo features of this crate that were enabled at build time
+pub fn enabled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "telemetry") {
+        features.push("telemetry");
+    }
+    if cfg!(feature = "embedded-python") {
+        features.push("embedded-python");
+    }
+    features
+}
+
+/// Whether the platform OCR engine is compiled in for this target
+pub const OCR_AVAILABLE: bool = terminator::ocr::OCR_ENABLED
+    && cfg!(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux"
+    ));
+
+/// Feature-dependent selector syntaxes this build rejects, with the parser's reason
+pub fn unavailable_selector_types() -> BTreeMap<&'static str, String> {
+    FEATURE_SELECTOR_TYPES
+        .iter()
+        .filter_map(
+            |kind| match Selector::from(format!("{kind}:probe").as_str()) {
+                Selector::Unsupported(reason) => Some((*kind, reason)),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+/// The result of the capabilities handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    pub version: &'static str,
+    pub features: Vec<&'static str>,
+    pub ocr_available: bool,
+    pub transports: Vec<&'static str>,
+    /// Response encodings a client can opt in to (see [`crate::compression`])
+    pub compression: Vec<&'static str>,
+    pub selector_types: Vec<&'static str>,
+    /// Selector syntaxes that fail with `UnsupportedOperation` in this build
+    pub unavailable_selector_types: BTreeMap<&'static str, String>,
+    pub tools: Vec<String>,
+}
+
+impl Capabilities {
+    /// Capabilities of the running build, advertising `tools` (sorted by name).
+    pub fn current(mut tools: Vec<String>) -> Self {
+        tools.sort();
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            features: enabled_features(),
+            ocr_available: OCR_AVAILABLE,
+            transports: TRANSPORTS.to_vec(),
+            compression: vec![crate::compression::GZIP],
+            selector_types: SELECTOR_TYPES.to_vec(),
+            unavailable_selector_types: unavailable_selector_types(),
+            tools,
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Reading and writing clipboard text for the `clipboard_get` and `clipboard_set` tools.
+//!
+//! The system clipboard is reached through `arboard`. A read fails with
+//! [`ClipboardError::Empty`] when the clipboard holds nothing, and with
+//! [`ClipboardError::NotText`] when it holds an image or text that is not valid UTF-8.
+//! Content in any other format cannot be told apart from an empty clipboard and is
+//! reported as empty. Access goes through a small trait so the error mapping can be
+//! tested without a desktop.
+
+use arboard::Error as ArboardError;
+
+/// The clipboard operations the tools need
+pub trait ClipboardAccess {
+    fn get_text(&mut self) -> Result<String, ArboardError>;
+    /// Whether the clipboard currently holds an image
+    fn has_image(&mut self) -> bool;
+    fn set_text(&mut self, text: &str) -> Result<(), ArboardError>;
+}
+
+impl ClipboardAccess for arboard::Clipboard {
+    fn get_text(&mut self) -> Result<String, ArboardError> {
+        arboard::Clipboard::get_text(self)
+    }
+
+    fn has_image(&mut self) -> bool {
+        self.get_image().is_ok()
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ArboardError> {
+        arboard::Clipboard::set_text(self, text)
+    }
+}
+
+/// Why the clipboard could not be read or written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardError {
+    /// The clipboard holds nothing, or only formats that cannot be read
+    Empty,
+    /// The clipboard holds content that is not UTF-8 text, such as an image
+    NotText { content: String },
+    /// The clipboard could not be opened, e.g. another application holds it
+    Unavailable(String),
+}
+
+impl ClipboardError {
+    /// A stable name for the error, for clients to branch on
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ClipboardError::Empty => "clipboard_empty",
+            ClipboardError::NotText { .. } => "clipboard_not_text",
+            ClipboardError::Unavailable(_) => "clipboard_unavailable",
+        }
+    }
+}
+
+impl std::fmt::Display for ClipboardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClipboardError::Empty => write!(f, "The clipboard is empty"),
+            ClipboardError::NotText { content } => {
+                write!(f, "The clipboard holds {content}, not text")
+            }
+            ClipboardError::Unavailable(reason) => {
+                write!(f, "The clipboard is not available: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ClipboardError {}
+
+/// Opens the system clipboard
+pub fn system_clipboard() -> Result<arboard::Clipboard, ClipboardError> {
+    arboard::Clipboard::new().map_err(|e| ClipboardError::Unavailable(e.to_string()))
+}
+
+/// The clipboard's text, which is never empty
+pub fn read_text(clipboard: &mut impl ClipboardAccess) -> Result<String, ClipboardError> {
+    match clipboard.get_text() {
+        Ok(text) if text.is_empty() => Err(ClipboardError::Empty),
+        Ok(text) => Ok(text),
+        Err(ArboardError::ContentNotAvailable) if clipboard.has_image() => {
+            Err(ClipboardError::NotText {
+                content: "an image".to_string(),
+            })
+        }
+        Err(ArboardError::ContentNotAvailable) => Err(ClipboardError::Empty),
+        Err(ArboardError::ConversionFailure) => Err(ClipboardError::NotText {
+            content: "text that is not valid UTF-8".to_string(),
+        }),
+        Err(e) => Err(ClipboardError::Unavailable(e.to_string())),
+    }
+}
+
+/// Replaces the clipboard's content with `text`
+pub fn write_text(clipboard: &mut impl ClipboardAccess, text: &str) -> Result<(), ClipboardError> {
+    clipboard
+        .set_text(text)
+        .map_err(|e| ClipboardError::Unavailable(e.to_string()))
+}
@@ -0,0 +1,140 @@
+//! Opt-in gzip compression of large tool responses.
+//!
+//! UI trees and other large results waste bandwidth over the SSE and HTTP transports.
+//! Compression is negotiated in the MCP handshake through an experimental capability:
+//! the server advertises
+//!
+//! ```json
+//! { "experimental": { "compression": { "encodings": ["gzip"], "threshold": 65536 } } }
+//! ```
+//!
+//! and a client opts in by sending `"compression": { "encodings": ["gzip"] }` in its own
+//! experimental capabilities, optionally with a smaller or larger `threshold` in bytes.
+//! For opted-in sessions, text content above the threshold is replaced by an envelope:
+//!
+//! ```json
+//! { "content_encoding": "gzip", "original_size": 1048576, "data": "<base64 gzip>" }
+//! ```
+//!
+//! Content that does not get smaller is sent unchanged. Clients that do not opt in
+//! always receive plain responses.
+
+use base64::{engine::general_purpose, Engine as _};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rmcp::model::{CallToolResult, JsonObject, RawContent};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+/// Name of the experimental capability used for negotiation
+pub const COMPRESSION_CAPABILITY: &str = "compression";
+
+/// The only encoding supported so far
+pub const GZIP: &str = "gzip";
+
+/// Responses smaller than this many bytes are never compressed by default
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 64 * 1024;
+
+/// Compression agreed with one client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionSettings {
+    /// Minimum size in bytes of a text content before it is compressed
+    pub threshold: usize,
+}
+
+impl Default for CompressionSettings {
+    fn default() -> Self {
+        Self {
+            threshold: DEFAULT_COMPRESSION_THRESHOLD,
+        }
+    }
+}
+
+/// The capability the server advertises in its experimental capabilities.
+pub fn server_capability() -> (String, JsonObject) {
+    let capability = json!({
+        "encodings": [GZIP],
+        "threshold": DEFAULT_COMPRESSION_THRESHOLD,
+    });
+    let Value::Object(capability) = capability else {
+        unreachable!("json! object literal")
+    };
+    (COMPRESSION_CAPABILITY.to_string(), capability)
+}
+
+/// Reads a client's experimental capabilities and returns the settings to use, or
+/// `None` when the client did not opt in to gzip.
+pub fn negotiate(
+    client_experimental: Option<&BTreeMap<String, JsonObject>>,
+) -> Option<CompressionSettings> {
+    let capability = client_experimental?.get(COMPRESSION_CAPABILITY)?;
+    let accepts_gzip = capability
+        .get("encodings")
+        .and_then(Value::as_array)
+        .is_some_and(|encodings| encodings.iter().any(|e| e.as_str() == Some(GZIP)));
+    if !accepts_gzip {
+        return None;
+    }
+    let threshold = capability
+        .get("threshold")
+        .and_then(Value::as_u64)
+        .map(|t| t as usize)
+        .unwrap_or(DEFAULT_COMPRESSION_THRESHOLD);
+    Some(CompressionSettings { threshold })
+}
+
+/// Compresses `text` into an envelope when it is at least `threshold` bytes and the
+/// envelope is smaller than the original.
+pub fn compress_text(text: &str, settings: &CompressionSettings) -> Option<String> {
+    if text.len() < settings.threshold {
+        return None;
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(text.as_bytes()).ok()?;
+    let compressed = encoder.finish().ok()?;
+
+    let envelope = json!({
+        "content_encoding": GZIP,
+        "original_size": text.len(),
+        "data": general_purpose::STANDARD.encode(compressed),
+    })
+    .to_string();
+    (envelope.len() < text.len()).then_some(envelope)
+}
+
+/// Restores the original text from an envelope made by [`compress_text`]. Returns
+/// `None` when `text` is not an envelope.
+pub fn decompress_text(text: &str) -> Option<Result<String, String>> {
+    let envelope: Value = serde_json::from_str(text).ok()?;
+    if envelope.get("content_encoding").and_then(Value::as_str) != Some(GZIP) {
+        return None;
+    }
+    let decode = || -> Result<String, String> {
+        let data = envelope
+            .get("data")
+            .and_then(Value::as_str)
+            .ok_or("compressed envelope has no data")?;
+        let compressed = general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| format!("invalid base64 in compressed envelope: {e}"))?;
+        let mut decompressed = String::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut decompressed)
+            .map_err(|e| format!("invalid gzip data in compressed envelope: {e}"))?;
+        Ok(decompressed)
+    };
+    Some(decode())
+}
+
+/// Replaces large text contents of a tool result with compressed envelopes.
+pub fn compress_result(result: &mut CallToolResult, settings: &CompressionSettings) {
+    for content in result.content.iter_mut() {
+        if let RawContent::Text(text_content) = &mut content.raw {
+            if let Some(envelope) = compress_text(&text_content.text, settings) {
+                text_content.text = envelope;
+            }
+        }
+    }
+}
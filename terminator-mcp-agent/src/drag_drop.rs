@@ -0,0 +1,201 @@
+//! Press-move-release gestures for the `drag_and_drop` tool.
+//!
+//! The button is pressed on the source point, the pointer is moved to the target in
+//! `steps` equal increments, and the button is released there. Many apps only start a
+//! drag after they see movement events while the button is down, so a single jump from
+//! source to target is not enough. Input goes through [`InputSink`] so the gesture can be
+//! tested without a desktop.
+
+use crate::monitors::{to_desktop, MonitorError};
+use serde::Serialize;
+use std::time::Duration;
+use terminator::{AutomationError, Monitor, UIElement};
+
+/// Default number of moves between pressing and releasing the button
+pub const DEFAULT_STEPS: u32 = 10;
+
+/// Default pause after the press and after each move
+pub const DEFAULT_STEP_DELAY: Duration = Duration::from_millis(10);
+
+/// The mouse operations a drag is made of, in screen coordinates
+pub trait InputSink {
+    fn press(&self, x: f64, y: f64) -> Result<(), AutomationError>;
+    fn move_to(&self, x: f64, y: f64) -> Result<(), AutomationError>;
+    fn release(&self) -> Result<(), AutomationError>;
+}
+
+impl InputSink for UIElement {
+    fn press(&self, x: f64, y: f64) -> Result<(), AutomationError> {
+        self.mouse_click_and_hold(x, y)
+    }
+
+    fn move_to(&self, x: f64, y: f64) -> Result<(), AutomationError> {
+        self.mouse_move(x, y)
+    }
+
+    fn release(&self) -> Result<(), AutomationError> {
+        self.mouse_release()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    /// The center of a `(x, y, width, height)` bounding box
+    pub fn center_of(bounds: (f64, f64, f64, f64)) -> Self {
+        let (x, y, width, height) = bounds;
+        Self {
+            x: x + width / 2.0,
+            y: y + height / 2.0,
+        }
+    }
+}
+
+/// One end of a drag, as given in the tool arguments
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DragEndpoint<'a> {
+    Selector(&'a str),
+    Point(Point),
+}
+
+impl<'a> DragEndpoint<'a> {
+    /// Reads the `<name>_selector` or `<name>_x` and `<name>_y` arguments; exactly one of
+    /// the two forms must be given.
+    pub fn from_args(
+        name: &str,
+        selector: Option<&'a str>,
+        x: Option<f64>,
+        y: Option<f64>,
+    ) -> Result<Self, String> {
+        match (selector, x, y) {
+            (Some(selector), None, None) => Ok(DragEndpoint::Selector(selector)),
+            (None, Some(x), Some(y)) => Ok(DragEndpoint::Point(Point { x, y })),
+            (None, None, None) => Err(format!(
+                "Either {name}_selector or both {name}_x and {name}_y are required"
+            )),
+            (Some(_), _, _) => Err(format!(
+                "Give either {name}_selector or {name}_x and {name}_y, not both"
+            )),
+            (None, _, _) => Err(format!("{name}_x and {name}_y must be given together")),
+        }
+    }
+
+    /// Converts coordinates relative to monitor `index` into desktop coordinates;
+    /// selectors are left as they are
+    pub fn on_monitor(self, monitor: &Monitor, index: usize) -> Result<Self, MonitorError> {
+        match self {
+            DragEndpoint::Point(point) => {
+                let (x, y) = to_desktop(monitor, index, point.x, point.y)?;
+                Ok(DragEndpoint::Point(Point { x, y }))
+            }
+            selector => Ok(selector),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DragOptions {
+    /// Moves between press and release; 0 is treated as 1
+    pub steps: u32,
+    pub step_delay: Duration,
+}
+
+impl Default for DragOptions {
+    fn default() -> Self {
+        Self {
+            steps: DEFAULT_STEPS,
+            step_delay: DEFAULT_STEP_DELAY,
+        }
+    }
+}
+
+/// Why a drag failed
+#[derive(Debug)]
+pub enum DragError {
+    /// The button could not be pressed on the source
+    PressFailed(AutomationError),
+    /// The pointer could not be moved; the button was released again
+    MoveFailed {
+        step: u32,
+        steps: u32,
+        error: AutomationError,
+    },
+    /// The button could not be released on the target
+    ReleaseFailed(AutomationError),
+}
+
+impl DragError {
+    /// A stable name for the error, for clients to branch on
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DragError::PressFailed(_) => "drag_press_failed",
+            DragError::MoveFailed { .. } => "drag_move_failed",
+            DragError::ReleaseFailed(_) => "drag_release_failed",
+        }
+    }
+}
+
+impl std::fmt::Display for DragError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DragError::PressFailed(e) => write!(f, "Failed to press the mouse on the source: {e}"),
+            DragError::MoveFailed { step, steps, error } => {
+                write!(
+                    f,
+                    "Failed to move the mouse (step {step} of {steps}): {error}"
+                )
+            }
+            DragError::ReleaseFailed(e) => {
+                write!(f, "Failed to release the mouse on the target: {e}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DragError {}
+
+/// The points the pointer moves through after the press, ending exactly on `to`
+pub fn drag_path(from: Point, to: Point, steps: u32) -> Vec<Point> {
+    let steps = steps.max(1);
+    (1..=steps)
+        .map(|step| {
+            let t = step as f64 / steps as f64;
+            Point {
+                x: from.x + (to.x - from.x) * t,
+                y: from.y + (to.y - from.y) * t,
+            }
+        })
+        .collect()
+}
+
+/// Drags from `from` to `to` with `sink`. If a move fails the button is released so it is
+/// not left held down.
+pub async fn drag<S: InputSink>(
+    sink: &S,
+    from: Point,
+    to: Point,
+    options: &DragOptions,
+) -> Result<(), DragError> {
+    sink.press(from.x, from.y).map_err(DragError::PressFailed)?;
+    tokio::time::sleep(options.step_delay).await;
+
+    let path = drag_path(from, to, options.steps);
+    let steps = path.len() as u32;
+    for (index, point) in path.into_iter().enumerate() {
+        if let Err(error) = sink.move_to(point.x, point.y) {
+            let _ = sink.release();
+            return Err(DragError::MoveFailed {
+                step: index as u32 + 1,
+                steps,
+                error,
+            });
+        }
+        tokio::time::sleep(options.step_delay).await;
+    }
+
+    sink.release().map_err(DragError::ReleaseFailed)
+}
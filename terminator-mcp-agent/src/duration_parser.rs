@@ -1,51 +1,116 @@
 use anyhow::{anyhow, Result};
+use std::time::Duration;
+
+/// The longest duration accepted, 100 years. Anything longer is almost certainly a typo,
+/// and far beyond what a timer can wait for.
+pub const MAX_DURATION_MS: u64 = 100 * 365 * 86_400_000;
 
 /// Parse human-readable duration strings into milliseconds
-/// Supports formats like: "1s", "500ms", "2m", "1.5s", "30", "2h"
+/// Supports formats like: "1s", "500ms", "2m", "1.5s", "30", "2h", "1.5d", "2w"
+/// and compound durations such as "1h30m" or "1h 30m 15s", whose components are summed.
+/// Every unit accepts a decimal value. Durations longer than [`MAX_DURATION_MS`] are an error.
+/// Sub-millisecond parts are truncated; use [`parse_std_duration`] to keep them.
 pub fn parse_duration(input: &str) -> Result<u64> {
+    parse_std_duration(input).map(|duration| duration.as_millis() as u64)
+}
+
+/// Like [`parse_duration`], but keeps microsecond precision, so "500us" or "0.5ms"
+/// is half a millisecond rather than zero.
+pub fn parse_std_duration(input: &str) -> Result<Duration> {
     let input = input.trim();
 
     // Try to parse as plain number first (assumes milliseconds)
     if let Ok(ms) = input.parse::<u64>() {
-        return Ok(ms);
+        return checked(ms as f64 * 1000.0, input);
     }
 
-    // Parse with units
-    let (number_part, unit_part) = split_number_and_unit(input)?;
-    let value: f64 = number_part
-        .parse()
-        .map_err(|_| anyhow!("Invalid number in duration: {}", number_part))?;
+    let components = split_components(input)?;
+    let compound = components.len() > 1;
+    let mut seen_units = Vec::new();
+    let mut total = 0.0;
+    for (number_part, unit_part) in components {
+        let value: f64 = number_part
+            .parse()
+            .map_err(|_| anyhow!("Invalid number in duration: {}", number_part))?;
+        // Checked per component, so "1h-30m" cannot hide a negative part in the sum
+        if !value.is_finite() || value < 0.0 {
+            return Err(anyhow!("Invalid duration: {}", input));
+        }
 
-    let multiplier = match unit_part {
-        "ms" | "milliseconds" | "millisecond" => 1.0,
-        "s" | "sec" | "secs" | "second" | "seconds" => 1000.0,
-        "m" | "min" | "mins" | "minute" | "minutes" => 60_000.0,
-        "h" | "hr" | "hrs" | "hour" | "hours" => 3_600_000.0,
-        "" => 1.0, // Default to milliseconds if no unit
-        _ => return Err(anyhow!("Unknown time unit: {}", unit_part)),
-    };
+        let (unit, multiplier) = match unit_part {
+            // Multipliers are in microseconds
+            "us" | "µs" | "microseconds" | "microsecond" => ("us", 1.0),
+            "ms" | "milliseconds" | "millisecond" => ("ms", 1000.0),
+            "s" | "sec" | "secs" | "second" | "seconds" => ("s", 1_000_000.0),
+            "m" | "min" | "mins" | "minute" | "minutes" => ("m", 60_000_000.0),
+            "h" | "hr" | "hrs" | "hour" | "hours" => ("h", 3_600_000_000.0),
+            "d" | "day" | "days" => ("d", 86_400_000_000.0),
+            "w" | "wk" | "wks" | "week" | "weeks" => ("w", 604_800_000_000.0),
+            // A bare number is milliseconds, but only on its own: in "1h30" the unit is missing
+            "" if !compound => ("ms", 1000.0),
+            "" => {
+                return Err(anyhow!(
+                    "Missing time unit after {} in duration: {}",
+                    number_part,
+                    input
+                ))
+            }
+            _ => return Err(anyhow!("Unknown time unit: {}", unit_part)),
+        };
+        if seen_units.contains(&unit) {
+            return Err(anyhow!(
+                "Duplicate time unit '{}' in duration: {}",
+                unit_part,
+                input
+            ));
+        }
+        seen_units.push(unit);
+        total += value * multiplier;
+    }
 
-    Ok((value * multiplier) as u64)
+    checked(total, input)
 }
 
-fn split_number_and_unit(input: &str) -> Result<(&str, &str)> {
-    // Find where the numeric part ends
-    let mut split_pos = input.len();
-    for (i, ch) in input.char_indices() {
-        if ch.is_alphabetic() {
-            split_pos = i;
-            break;
-        }
+fn checked(us: f64, input: &str) -> Result<Duration> {
+    if !us.is_finite() || us < 0.0 {
+        return Err(anyhow!("Invalid duration: {}", input));
+    }
+    if us > MAX_DURATION_MS as f64 * 1000.0 {
+        return Err(anyhow!(
+            "Duration is too long: {} (the limit is 100 years)",
+            input
+        ));
     }
+    // Rounded, so that decimal values such as "0.3ms" are not a microsecond short
+    Ok(Duration::from_micros(us.round() as u64))
+}
+
+/// Splits a duration into its number and unit pairs, left to right.
+/// Whitespace may separate components, and a number from its unit.
+fn split_components(input: &str) -> Result<Vec<(&str, &str)>> {
+    let mut components = Vec::new();
+    let mut rest = input.trim_start();
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|ch: char| ch.is_alphabetic() || ch.is_whitespace())
+            .unwrap_or(rest.len());
+        let number_part = &rest[..number_end];
+        if number_part.is_empty() {
+            return Err(anyhow!("No numeric value in duration: {}", input));
+        }
+        rest = rest[number_end..].trim_start();
 
-    let number_part = &input[..split_pos];
-    let unit_part = &input[split_pos..];
+        let unit_end = rest
+            .find(|ch: char| !ch.is_alphabetic())
+            .unwrap_or(rest.len());
+        components.push((number_part, &rest[..unit_end]));
+        rest = rest[unit_end..].trim_start();
+    }
 
-    if number_part.is_empty() {
+    if components.is_empty() {
         return Err(anyhow!("No numeric value in duration: {}", input));
     }
-
-    Ok((number_part, unit_part))
+    Ok(components)
 }
 
 #[cfg(test)]
@@ -80,6 +145,102 @@ mod tests {
         assert_eq!(parse_duration("0.5h").unwrap(), 1800000);
     }
 
+    #[test]
+    fn test_parse_duration_fractional_hours() {
+        assert_eq!(parse_duration("1.5h").unwrap(), 5_400_000);
+        assert_eq!(parse_duration("0.25hours").unwrap(), 900_000);
+        assert_eq!(parse_duration("1.5d").unwrap(), 129_600_000);
+    }
+
+    #[test]
+    fn test_parse_duration_weeks() {
+        assert_eq!(parse_duration("1w").unwrap(), 604_800_000);
+        assert_eq!(parse_duration("2weeks").unwrap(), 1_209_600_000);
+        assert_eq!(parse_duration("0.5w").unwrap(), 302_400_000);
+    }
+
+    #[test]
+    fn test_parse_duration_overflow_is_an_error() {
+        assert!(parse_duration("99999999w").is_err());
+        assert!(parse_duration("99999999999999999999").is_err());
+        assert!(parse_duration(&format!("{}1h", "9".repeat(400))).is_err());
+        assert!(parse_duration("-1s").is_err());
+        assert_eq!(parse_duration("5200w").unwrap(), 5200 * 604_800_000);
+    }
+
+    #[test]
+    fn test_parse_duration_compound() {
+        assert_eq!(parse_duration("1h30m").unwrap(), 5_400_000);
+        assert_eq!(parse_duration("2m15s").unwrap(), 135_000);
+        assert_eq!(parse_duration("1h30m15s").unwrap(), 5_415_000);
+        assert_eq!(parse_duration("1s500ms").unwrap(), 1500);
+        assert_eq!(parse_duration("1.5h10m").unwrap(), 6_000_000);
+    }
+
+    #[test]
+    fn test_parse_duration_compound_with_whitespace() {
+        assert_eq!(parse_duration("1h 30m").unwrap(), 5_400_000);
+        assert_eq!(parse_duration(" 1h 30m 15s ").unwrap(), 5_415_000);
+        assert_eq!(parse_duration("1 hour 30 minutes").unwrap(), 5_400_000);
+        assert_eq!(parse_duration("2 w").unwrap(), 1_209_600_000);
+    }
+
+    #[test]
+    fn test_parse_duration_compound_errors() {
+        let duplicate = parse_duration("1m30min").unwrap_err().to_string();
+        assert!(duplicate.contains("Duplicate time unit"), "{duplicate}");
+        assert!(parse_duration("1h1h").is_err());
+
+        let unknown = parse_duration("1h30x").unwrap_err().to_string();
+        assert_eq!(unknown, "Unknown time unit: x");
+
+        let missing = parse_duration("1h30").unwrap_err().to_string();
+        assert!(missing.contains("Missing time unit after 30"), "{missing}");
+        assert!(parse_duration("1h-30m").is_err());
+        assert!(parse_duration("h30m").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration_sub_millisecond() {
+        assert_eq!(
+            parse_std_duration("250ms").unwrap(),
+            Duration::from_millis(250)
+        );
+        assert_eq!(
+            parse_std_duration("500us").unwrap(),
+            Duration::from_micros(500)
+        );
+        assert_eq!(
+            parse_std_duration("0.5ms").unwrap(),
+            Duration::from_micros(500)
+        );
+        assert_eq!(
+            parse_std_duration("0.3ms").unwrap(),
+            Duration::from_micros(300)
+        );
+        assert_eq!(
+            parse_std_duration("1ms250us").unwrap(),
+            Duration::from_micros(1250)
+        );
+        assert_eq!(parse_std_duration("0ms").unwrap(), Duration::ZERO);
+        assert_eq!(parse_std_duration("0").unwrap(), Duration::ZERO);
+        assert_ne!(
+            parse_std_duration("250ms").unwrap(),
+            parse_std_duration("1s").unwrap()
+        );
+
+        // Truncated to whole milliseconds by parse_duration
+        assert_eq!(parse_duration("500us").unwrap(), 0);
+        assert_eq!(parse_duration("1500us").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_parse_duration_ms_is_not_minutes_and_seconds() {
+        assert_eq!(parse_duration("1ms").unwrap(), 1);
+        assert_eq!(parse_duration("1m1s").unwrap(), 61_000);
+        assert!(parse_duration("1us1ms1us").is_err());
+    }
+
     #[test]
     fn test_parse_duration_errors() {
         assert!(parse_duration("abc").is_err());
@@ -0,0 +1,127 @@
+//! Acting on an element captured by an earlier step.
+//!
+//! A step with `element_ref: <step id>` targets the element that step returned (e.g. the
+//! `element` of a `find_by_identity` or `validate_element` result) instead of resolving
+//! its own selector. The captured element is found again by its identity key within its
+//! application, and the step's `selector` is replaced with the live element's id, so an
+//! ambiguous selector cannot pick a different element.
+//!
+//! A ref fails when the step has no stored result, the result holds no element with an
+//! identity key, or the element is gone from the UI (stale).
+
+use serde_json::Value;
+use terminator::AutomationError;
+
+/// Selector arguments replaced by the resolved element
+const SELECTOR_KEYS: &[&str] = &["selector", "alternative_selectors", "fallback_selectors"];
+
+/// What a step captured about an element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedElement {
+    pub identity_key: String,
+    pub process_id: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElementRefError {
+    /// No result is stored for the referenced step
+    UnknownStep(String),
+    /// The step's result does not describe an element with an identity key
+    NoElement(String),
+    /// The captured element could not be found again
+    Stale {
+        reference: String,
+        identity_key: String,
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for ElementRefError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ElementRefError::UnknownStep(reference) => write!(
+                f,
+                "element_ref '{reference}' does not name an earlier step with a stored result"
+            ),
+            ElementRefError::NoElement(reference) => write!(
+                f,
+                "the result of step '{reference}' does not contain an element with an identity key"
+            ),
+            ElementRefError::Stale {
+                reference,
+                identity_key,
+                reason,
+            } => write!(
+                f,
+                "element_ref '{reference}' is stale: element '{identity_key}' is no longer in the UI ({reason})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ElementRefError {}
+
+/// Reads the element captured by step `reference` from the execution context.
+///
+/// The step's result is stored as `env.<reference>_result`; the first content item
+/// with an `element` (or that is itself an element) carrying an identity key is used.
+pub fn captured_element(
+    context: &Value,
+    reference: &str,
+) -> Result<CapturedElement, ElementRefError> {
+    let result = context
+        .get("env")
+        .and_then(|env| env.get(format!("{reference}_result")))
+        .filter(|result| !result.is_null())
+        .ok_or_else(|| ElementRefError::UnknownStep(reference.to_string()))?;
+
+    let items: Vec<&Value> = match result {
+        Value::Array(items) => items.iter().collect(),
+        other => vec![other],
+    };
+    items
+        .into_iter()
+        .flat_map(|item| [item.get("element"), Some(item)])
+        .flatten()
+        .find_map(|candidate| {
+            let identity_key = candidate.get("identity_key")?.as_str()?;
+            Some(CapturedElement {
+                identity_key: identity_key.to_string(),
+                process_id: candidate
+                    .get("process_id")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(0) as u32,
+            })
+        })
+        .ok_or_else(|| ElementRefError::NoElement(reference.to_string()))
+}
+
+/// Resolves step `reference` to a selector for the live element.
+///
+/// `find` looks the captured element up in the UI and returns its current id.
+pub fn resolve<F>(context: &Value, reference: &str, find: F) -> Result<String, ElementRefError>
+where
+    F: FnOnce(&CapturedElement) -> Result<String, AutomationError>,
+{
+    let captured = captured_element(context, reference)?;
+    find(&captured)
+        .map(|id| format!("#{id}"))
+        .map_err(|e| ElementRefError::Stale {
+            reference: reference.to_string(),
+            identity_key: captured.identity_key.clone(),
+            reason: e.to_string(),
+        })
+}
+
+/// Points a tool call's arguments at `selector`, dropping any selector fallbacks.
+pub fn apply_selector(arguments: &mut Value, selector: &str) {
+    if !arguments.is_object() {
+        *arguments = Value::Object(serde_json::Map::new());
+    }
+    if let Some(arguments) = arguments.as_object_mut() {
+        for key in SELECTOR_KEYS {
+            arguments.remove(*key);
+        }
+        arguments.insert("selector".to_string(), Value::String(selector.to_string()));
+    }
+}
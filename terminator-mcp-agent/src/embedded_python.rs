@@ -0,0 +1,125 @@
+//! Python engine scripts run in-process, behind the `embedded-python` feature.
+//!
+//! A script that does not get `desktop` needs nothing from terminator.py, so with this
+//! feature it runs in a CPython interpreter embedded through pyo3 instead of a `python`
+//! subprocess, skipping the interpreter start and the bindings install on every step.
+//! The contract is the subprocess one: `env` and `variables` are defined by the prelude
+//! `run_command` puts before the script, the script is the body of an `async def` with
+//! `sleep(ms)` and `log(..., level='log')`, and the value it returns comes back as JSON
+//! along with the lines it logged. The JSON literals `true`, `false` and `null` can be
+//! used for `True`, `False` and `None`.
+//!
+//! Scripts get the language version, standard library and installed packages of the
+//! Python the agent was built against; `import terminator` is not available. What they
+//! print is collected rather than written to stdout, which carries the MCP transport,
+//! and is also mirrored to the agent's log. Scripts share
+//! the one interpreter, so module-level state such as imported modules carries over
+//! between runs. A running script cannot be stopped part-way, so it runs to completion
+//! even when the call awaiting it is dropped; scripts with an explicit `timeout` run in a
+//! `python` process instead, which is killed at the limit.
+
+use crate::scripting_engine::{with_logs, ScriptLogLine, LOG_HELPER_PY};
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use rmcp::ErrorData as McpError;
+use serde_json::json;
+use std::ffi::CString;
+use tracing::info;
+
+/// Defines the helpers and runs `__user_main__`, the user script, collecting what it prints
+const RUNNER: &str = r#"
+import asyncio, builtins, io, json
+
+true, false, null = True, False, None
+__stdout__ = io.StringIO()
+__result__ = json.dumps(asyncio.run(__user_main__()))
+"#;
+
+/// Turns `script` into the body of `async def __user_main__()`, with the helpers in scope
+fn user_main(script: &str) -> String {
+    let mut source = String::from(
+        r#"async def __user_main__():
+    def print(*args, file=None, **kwargs):
+        builtins.print(*args, file=file or __stdout__, **kwargs)
+    async def sleep(ms):
+        await asyncio.sleep(ms / 1000.0)
+"#,
+    );
+    source.push_str(LOG_HELPER_PY);
+    for line in script.lines() {
+        source.push_str("    ");
+        source.push_str(line);
+        source.push('\n');
+    }
+    source
+}
+
+/// Runs `script` in the embedded interpreter and returns `{result, logs}`, as the
+/// subprocess engine does
+pub async fn run_python_embedded(script: String) -> Result<serde_json::Value, McpError> {
+    info!(
+        "[Python embedded] Running script ({} bytes) in the embedded interpreter",
+        script.len()
+    );
+    tokio::task::spawn_blocking(move || Python::with_gil(|py| execute(py, &script)))
+        .await
+        .map_err(|e| {
+            McpError::internal_error(
+                "Python execution failed",
+                Some(json!({"error": e.to_string()})),
+            )
+        })?
+}
+
+fn execute(py: Python<'_>, script: &str) -> Result<serde_json::Value, McpError> {
+    let source = CString::new(format!("{}\n{RUNNER}", user_main(script))).map_err(|_| {
+        McpError::invalid_params("Python scripts cannot contain NUL characters", None)
+    })?;
+    let globals = PyDict::new(py);
+    let outcome = py.run(&source, Some(&globals), None);
+
+    let mut logs = Vec::new();
+    if let Ok(Some(stdout)) = globals.get_item("__stdout__") {
+        if let Ok(output) = stdout
+            .call_method0("getvalue")
+            .and_then(|v| v.extract::<String>())
+        {
+            for line in output.lines() {
+                info!("[Python embedded] {}", line);
+                logs.push(ScriptLogLine::from_stdout(line));
+            }
+        }
+    }
+    outcome.map_err(|e| python_error(py, e, &logs))?;
+
+    let result: String = globals
+        .get_item("__result__")
+        .ok()
+        .flatten()
+        .and_then(|r| r.extract().ok())
+        .ok_or_else(|| McpError::internal_error("No result received from Python", None))?;
+    let result: serde_json::Value = serde_json::from_str(&result).map_err(|e| {
+        McpError::internal_error(
+            "Failed to parse the Python result",
+            Some(json!({"error": e.to_string()})),
+        )
+    })?;
+    Ok(json!({"result": result, "logs": logs}))
+}
+
+/// A Python exception as the same error the subprocess engine reports
+fn python_error(py: Python<'_>, error: PyErr, logs: &[ScriptLogLine]) -> McpError {
+    let stack = error
+        .traceback(py)
+        .and_then(|traceback| traceback.format().ok());
+    McpError::internal_error(
+        "Python execution error",
+        Some(with_logs(
+            json!({
+                "message": error.to_string(),
+                "stack": stack,
+            }),
+            logs,
+        )),
+    )
+}
@@ -1,4 +1,11 @@
+use regex::Regex;
 use serde_json::Value;
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Mutex, OnceLock};
 use tracing::warn;
 
 /// Normalizes an expression by replacing smart quotes and other Unicode characters
@@ -15,49 +22,641 @@ fn normalize_expression(expr: &str) -> String {
         .to_string()
 }
 
-// Helper to get a value from the variables JSON.
+// Helper to get a value from the variables JSON. Array elements are reached by index,
+// as `rows.0.name` or `rows[0].name`; an index past the end is undefined.
 pub fn get_value<'a>(path: &str, variables: &'a Value) -> Option<&'a Value> {
-    // Support dot notation for nested access
-    if !path.contains('.') {
-        return variables.get(path); // Fast path for simple keys
+    if !path.contains('.') && !path.contains('[') {
+        return child(variables, path); // Fast path for simple keys
     }
 
+    // Support dot notation for nested access
+    let path = dotted_path(path)?;
     let mut current = variables;
     for part in path.split('.') {
-        current = current.get(part)?;
+        current = child(current, part)?;
     }
     Some(current)
 }
 
-// Main evaluation function.
+// A field of an object, or an element of an array by its index
+fn child<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    match value {
+        Value::Array(items) => items.get(array_index(key)?),
+        _ => value.get(key),
+    }
+}
+
+// An array index written in plain decimal, so "2" but not "02" or "+2"
+fn array_index(key: &str) -> Option<usize> {
+    let canonical =
+        key.bytes().all(|b| b.is_ascii_digit()) && (key == "0" || !key.starts_with('0'));
+    canonical.then(|| key.parse().ok()).flatten()
+}
+
+// `rows[2].name` as `rows.2.name`, the form paths are walked in.
+// None when a bracket does not hold an index.
+fn dotted_path(path: &str) -> Option<Cow<'_, str>> {
+    if !path.contains('[') && !path.contains(']') {
+        return Some(Cow::Borrowed(path));
+    }
+    let mut dotted = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(open) = rest.find('[') {
+        dotted.push_str(&rest[..open]);
+        let (index, after) = rest[open + 1..].split_once(']')?;
+        array_index(index)?;
+        if !dotted.is_empty() {
+            dotted.push('.');
+        }
+        dotted.push_str(index);
+        rest = after;
+    }
+    if rest.contains(']') {
+        return None;
+    }
+    dotted.push_str(rest);
+    Some(Cow::Owned(dotted))
+}
+
+/// A variables object whose path lookups are memoized.
+///
+/// Each path is resolved by walking the JSON tree the first time it is looked up;
+/// later lookups of it are a single hash probe. Nothing is walked up front, so a
+/// context is cheap to create for a single evaluation and pays off when the same
+/// variables are evaluated against many times, e.g. a step's `if` and `precondition`,
+/// or every step of a plan. See `benches/expression_eval.rs`.
+#[derive(Debug)]
+pub struct IndexedContext<'a> {
+    root: &'a Value,
+    paths: RefCell<HashMap<String, Option<&'a Value>>>,
+}
+
+impl<'a> IndexedContext<'a> {
+    pub fn new(variables: &'a Value) -> Self {
+        Self {
+            root: variables,
+            paths: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Same result as [`get_value`] on the indexed variables.
+    pub fn get(&self, path: &str) -> Option<&'a Value> {
+        if let Some(found) = self.paths.borrow().get(path) {
+            return *found;
+        }
+        let found = get_value(path, self.root);
+        self.paths.borrow_mut().insert(path.to_string(), found);
+        found
+    }
+
+    /// The variables the index was built from
+    pub fn root(&self) -> &'a Value {
+        self.root
+    }
+
+    /// Number of distinct paths looked up so far
+    pub fn len(&self) -> usize {
+        self.paths.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.borrow().is_empty()
+    }
+}
+
+// The index as `Vars` sees it. Results borrow from the lookup itself, which keeps
+// `Vars` covariant even though the context's cache is not.
+trait PathLookup {
+    fn lookup(&self, path: &str) -> Option<&Value>;
+}
+
+impl PathLookup for IndexedContext<'_> {
+    fn lookup(&self, path: &str) -> Option<&Value> {
+        self.get(path)
+    }
+}
+
+// Recursion budget of one evaluation. Once exceeded the whole expression fails, rather
+// than the innermost level alone, which a surrounding negation would turn into `true`.
+// It also keeps the first other failure, for evaluate_checked.
+struct DepthLimit {
+    max: usize,
+    exceeded: Cell<bool>,
+    failure: RefCell<Option<EvalError>>,
+}
+
+impl DepthLimit {
+    fn new(max: usize) -> Self {
+        Self {
+            max,
+            exceeded: Cell::new(false),
+            failure: RefCell::new(None),
+        }
+    }
+
+    fn exceeded(&self) -> bool {
+        self.exceeded.get()
+    }
+
+    fn error(&self) -> String {
+        format!(
+            "expression is nested deeper than the limit of {} levels",
+            self.max
+        )
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if self.exceeded() {
+            Err(self.error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+// Where evaluation reads variables from: a tree walk over the raw JSON, or the index.
+// Inside filter() the current row is an inner scope that shadows both. It also carries
+// the current nesting depth, so every recursive call site can check the limit.
+#[derive(Clone, Copy)]
+struct Vars<'a> {
+    root: &'a Value,
+    index: Option<&'a dyn PathLookup>,
+    scope: Option<&'a Value>,
+    depth: usize,
+    limit: &'a DepthLimit,
+    strict_equality: bool,
+    unit_suffixes: &'static [&'static str],
+}
+
+impl<'a> Vars<'a> {
+    fn tree(root: &'a Value, limit: &'a DepthLimit) -> Self {
+        Self {
+            root,
+            index: None,
+            scope: None,
+            depth: 0,
+            limit,
+            strict_equality: strict_equality_enabled(),
+            unit_suffixes: DEFAULT_UNIT_SUFFIXES,
+        }
+    }
+
+    fn with_options(root: &'a Value, limit: &'a DepthLimit, options: &EvalOptions) -> Self {
+        Self {
+            strict_equality: options.strict_equality,
+            unit_suffixes: options.unit_suffixes,
+            ..Self::tree(root, limit)
+        }
+    }
+
+    fn indexed(context: &'a IndexedContext, limit: &'a DepthLimit) -> Self {
+        Self {
+            root: context.root,
+            index: Some(context),
+            scope: None,
+            depth: 0,
+            limit,
+            strict_equality: strict_equality_enabled(),
+            unit_suffixes: DEFAULT_UNIT_SUFFIXES,
+        }
+    }
+
+    // Levels of nesting still allowed below this one
+    fn levels_left(&self) -> usize {
+        self.limit.max.saturating_sub(self.depth)
+    }
+
+    // One level deeper, or None once past the limit, which fails the whole evaluation.
+    fn descend(self) -> Option<Self> {
+        if self.limit.exceeded() || self.depth >= self.limit.max {
+            self.limit.exceeded.set(true);
+            return None;
+        }
+        Some(Self {
+            depth: self.depth + 1,
+            ..self
+        })
+    }
+
+    // Records that `expr` could not be evaluated. Lenient callers only see the warning
+    // and a `false`; evaluate_checked reports the first failure.
+    fn fail(&self, expr: &str, error: EvalError) {
+        if self.limit.exceeded() {
+            return;
+        }
+        warn!(
+            "Could not evaluate '{}': {}. Defaulting to false.",
+            expr, error
+        );
+        self.limit.failure.borrow_mut().get_or_insert(error);
+    }
+
+    // The same variables with `row` as the innermost scope: its fields resolve first,
+    // and `item` names the row itself.
+    fn with_scope(self, row: &'a Value) -> Self {
+        Self {
+            scope: Some(row),
+            ..self
+        }
+    }
+
+    fn get(&self, path: &str) -> Option<&'a Value> {
+        if let Some(row) = self.scope {
+            if path == "item" {
+                return Some(row);
+            }
+            let scoped = match path.strip_prefix("item.") {
+                Some(rest) => get_value(rest, row),
+                None if path.starts_with("item[") => get_value(&path["item".len()..], row),
+                None => get_value(path, row),
+            };
+            if scoped.is_some() {
+                return scoped;
+            }
+        }
+        match self.index {
+            Some(index) => index.lookup(path),
+            None => get_value(path, self.root),
+        }
+    }
+}
+
+/// Default for [`EvalOptions::max_depth`]
+pub const DEFAULT_MAX_DEPTH: usize = 100;
+
+/// Default for [`EvalOptions::unit_suffixes`]: lengths, percentages and durations
+pub const DEFAULT_UNIT_SUFFIXES: &[&str] = &["px", "pt", "em", "rem", "%", "ms", "s", "min", "h"];
+
+/// Set to `true` to make strict equality the process-wide default
+pub const STRICT_EQUALITY_ENV: &str = "TERMINATOR_STRICT_EQUALITY";
+
+// 0 = follow the environment, 1 = forced off, 2 = forced on
+static STRICT_EQUALITY_OVERRIDE: AtomicU8 = AtomicU8::new(0);
+
+/// Overrides the default equality mode for this process, regardless of the environment.
+///
+/// Applies to every evaluation that does not pass its own [`EvalOptions`], including
+/// workflow conditions and `{{...}}` placeholders.
+pub fn set_strict_equality(enabled: bool) {
+    STRICT_EQUALITY_OVERRIDE.store(if enabled { 2 } else { 1 }, Ordering::Relaxed);
+}
+
+/// Whether `==` and `!=` compare numbers and numeric strings strictly by default.
+pub fn strict_equality_enabled() -> bool {
+    static FROM_ENV: OnceLock<bool> = OnceLock::new();
+    match STRICT_EQUALITY_OVERRIDE.load(Ordering::Relaxed) {
+        1 => false,
+        2 => true,
+        _ => *FROM_ENV.get_or_init(|| {
+            std::env::var(STRICT_EQUALITY_ENV)
+                .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                .unwrap_or(false)
+        }),
+    }
+}
+
+/// Options for [`evaluate_with_options`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvalOptions {
+    /// Rewrite smart quotes, backticks and Unicode spaces to their ASCII equivalents
+    /// before evaluating. Disable it when literals legitimately contain those characters.
+    pub normalize: bool,
+    /// How deeply negations, `&&`/`||` chains, `??` chains and function calls may nest.
+    /// Deeper expressions are rejected instead of overflowing the stack.
+    pub max_depth: usize,
+    /// Make `==` and `!=` tell numbers from strings, so `"42" == 42` is false.
+    /// Defaults to [`strict_equality_enabled`]; lenient unless configured otherwise.
+    pub strict_equality: bool,
+    /// Unit suffixes accepted on numeric strings by `>`, `>=`, `<` and `<=`, so
+    /// `width > '100px'` compares 100. Units are not converted: two sides with different
+    /// units, or a suffix not listed here, make the comparison false.
+    pub unit_suffixes: &'static [&'static str],
+}
+
+impl Default for EvalOptions {
+    fn default() -> Self {
+        Self {
+            normalize: true,
+            max_depth: DEFAULT_MAX_DEPTH,
+            strict_equality: strict_equality_enabled(),
+            unit_suffixes: DEFAULT_UNIT_SUFFIXES,
+        }
+    }
+}
+
+/// Why an expression could not be evaluated, as reported by [`evaluate_checked`].
+///
+/// Each variant carries a message naming the offending part of the expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    /// A call to a function the evaluator does not know, e.g. `lenght(items)`
+    UnknownFunction(String),
+    /// A variable that is not defined, e.g. `env.missing == 'x'`
+    VariableNotFound(String),
+    /// Syntax the evaluator cannot parse, a wrong number of arguments, or nesting
+    /// deeper than the depth limit
+    Malformed(String),
+    /// A value of the wrong type for the operation, e.g. `len(42)`
+    TypeMismatch(String),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFunction(message)
+            | Self::VariableNotFound(message)
+            | Self::Malformed(message)
+            | Self::TypeMismatch(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+// Main evaluation function. Anything it cannot evaluate is logged and counts as false;
+// use `evaluate_checked` to find out why.
 pub fn evaluate(expression: &str, variables: &Value) -> bool {
-    // Normalize the expression to handle smart quotes and other Unicode characters
-    let normalized = normalize_expression(expression);
-    evaluate_internal(&normalized, variables)
+    evaluate_with_options(expression, variables, &EvalOptions::default())
+}
+
+/// Like [`evaluate`], but reports why an expression could not be evaluated instead of
+/// treating it as `false`.
+///
+/// Every part of the expression that is evaluated must succeed, so an undefined
+/// variable is an error even where `||` would have recovered from it.
+pub fn evaluate_checked(expression: &str, variables: &Value) -> Result<bool, EvalError> {
+    let options = EvalOptions::default();
+    let limit = DepthLimit::new(options.max_depth);
+    let result = evaluate_in(expression, Vars::tree(variables, &limit), &options)
+        .map_err(EvalError::Malformed)?;
+    match limit.failure.into_inner() {
+        Some(error) => Err(error),
+        None => Ok(result),
+    }
+}
+
+/// Evaluates an expression with explicit options.
+///
+/// An expression nested deeper than `options.max_depth` evaluates to `false`.
+pub fn evaluate_with_options(expression: &str, variables: &Value, options: &EvalOptions) -> bool {
+    try_evaluate_with_options(expression, variables, options).unwrap_or_else(lenient)
+}
+
+/// Like [`evaluate_with_options`], but an expression nested deeper than
+/// `options.max_depth` is an error rather than `false`.
+pub fn try_evaluate_with_options(
+    expression: &str,
+    variables: &Value,
+    options: &EvalOptions,
+) -> Result<bool, String> {
+    let limit = DepthLimit::new(options.max_depth);
+    evaluate_in(
+        expression,
+        Vars::with_options(variables, &limit, options),
+        options,
+    )
+}
+
+/// Evaluates an expression against pre-indexed variables.
+///
+/// Gives the same result as [`evaluate`] on `context.root()`.
+pub fn evaluate_indexed(expression: &str, context: &IndexedContext) -> bool {
+    let options = EvalOptions::default();
+    let limit = DepthLimit::new(options.max_depth);
+    evaluate_in(expression, Vars::indexed(context, &limit), &options).unwrap_or_else(lenient)
+}
+
+/// A condition parsed once and evaluated many times, e.g. the `repeat_until` of a loop.
+///
+/// Compiling rejects what is wrong regardless of the variables: unbalanced parentheses,
+/// nesting deeper than the limit and calls to unknown functions. Evaluating gives the
+/// same result as [`evaluate_with_options`] with the options it was compiled with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    source: String,
+    root: Node,
+    options: EvalOptions,
+}
+
+impl Expression {
+    /// Compiles an expression with the default [`EvalOptions`].
+    pub fn compile(expression: &str) -> Result<Self, EvalError> {
+        Self::compile_with_options(expression, &EvalOptions::default())
+    }
+
+    pub fn compile_with_options(
+        expression: &str,
+        options: &EvalOptions,
+    ) -> Result<Self, EvalError> {
+        let source = if options.normalize {
+            normalize_expression(expression)
+        } else {
+            expression.trim().to_string()
+        };
+        let root = Node::parse(&source, options.max_depth)?;
+        root.check(options.max_depth)?;
+        Ok(Self {
+            source,
+            root,
+            options: *options,
+        })
+    }
+
+    /// The expression as it is evaluated, i.e. after normalization
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Evaluates the expression; anything it cannot evaluate counts as false.
+    pub fn evaluate(&self, variables: &Value) -> bool {
+        let limit = DepthLimit::new(self.options.max_depth);
+        let result = self
+            .root
+            .evaluate(Vars::with_options(variables, &limit, &self.options));
+        limit.check().map(|()| result).unwrap_or_else(lenient)
+    }
+
+    /// Like [`evaluate_checked`], for a compiled expression.
+    pub fn evaluate_checked(&self, variables: &Value) -> Result<bool, EvalError> {
+        let limit = DepthLimit::new(self.options.max_depth);
+        let result = self
+            .root
+            .evaluate(Vars::with_options(variables, &limit, &self.options));
+        limit.check().map_err(EvalError::Malformed)?;
+        match limit.failure.into_inner() {
+            Some(error) => Err(error),
+            None => Ok(result),
+        }
+    }
+}
+
+fn evaluate_in(
+    expression: &str,
+    variables: Vars<'_>,
+    options: &EvalOptions,
+) -> Result<bool, String> {
+    let result = if options.normalize {
+        // Normalize the expression to handle smart quotes and other Unicode characters
+        let normalized = normalize_expression(expression);
+        evaluate_internal(&normalized, variables)
+    } else {
+        evaluate_internal(expression.trim(), variables)
+    };
+    variables.limit.check()?;
+    Ok(result)
+}
+
+// Lenient callers treat an expression that is too deep like any other they cannot parse
+fn lenient(error: String) -> bool {
+    warn!("{}. Defaulting to false.", error);
+    false
+}
+
+/// The truthiness rules used when a value stands alone as a condition, and by `bool()`.
+///
+/// | value                   | truthy when                              |
+/// |-------------------------|------------------------------------------|
+/// | boolean                 | it is `true`                             |
+/// | string                  | non-empty and not `"false"` or `"0"`     |
+/// | number                  | it is non-zero (`0.5` is truthy)         |
+/// | null                    | never                                    |
+/// | array / object          | non-empty                                |
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Bool(b) => *b,
+        Value::String(s) => !s.is_empty() && s != "false" && s != "0",
+        Value::Number(n) => n.as_f64().is_some_and(|f| f != 0.0),
+        Value::Null => false,
+        Value::Array(arr) => !arr.is_empty(),
+        Value::Object(obj) => !obj.is_empty(),
+    }
 }
 
 // Internal evaluation function that works with normalized expressions
-fn evaluate_internal(expression: &str, variables: &Value) -> bool {
-    // Trim whitespace
-    let expr = expression.trim();
+fn evaluate_internal(expression: &str, variables: Vars<'_>) -> bool {
+    match Node::parse(expression, variables.levels_left()) {
+        Ok(node) => node.evaluate(variables),
+        Err(e) => {
+            if variables.descend().is_some() {
+                variables.fail(expression.trim(), e);
+            }
+            false
+        }
+    }
+}
+
+// The logical structure of a condition: `||`, `&&`, `!` and parentheses around atoms
+// (comparisons, function calls, variables), which are evaluated from their text. A
+// chain of `||` or `&&` is one node over all its operands and adds no nesting, however
+// long it is; every other node is one level. Past the depth limit the rest is left
+// unparsed, and reaching it fails the whole evaluation.
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Or(Vec<Node>),
+    And(Vec<Node>),
+    Not(Box<Node>),
+    Group(Box<Node>),
+    Atom(String),
+    TooDeep,
+}
 
-    // Handle negation operator
-    if let Some(inner_expr) = expr.strip_prefix('!') {
-        let inner_expr = inner_expr.trim();
-        return !evaluate_internal(inner_expr, variables);
+impl Node {
+    // Parses at most `levels` levels deep.
+    fn parse(expression: &str, levels: usize) -> Result<Self, EvalError> {
+        let expr = expression.trim();
+        if (expr.contains('(') || expr.contains(')')) && !balanced_parens(expr) {
+            return Err(EvalError::Malformed(format!(
+                "unbalanced parentheses in '{expr}'"
+            )));
+        }
+        Ok(Self::parse_balanced(expr, levels))
     }
 
-    // Handle logical operators (&&, ||) with proper precedence
-    if let Some(pos) = expr.find("&&") {
-        let left = &expr[..pos].trim();
-        let right = &expr[pos + 2..].trim();
-        return evaluate_internal(left, variables) && evaluate_internal(right, variables);
+    // Every part of a balanced expression split at the top level is balanced too.
+    fn parse_balanced(expression: &str, levels: usize) -> Self {
+        let expr = expression.trim();
+        let operands = |parts: Vec<&str>| {
+            parts
+                .into_iter()
+                .map(|part| Self::parse_balanced(part, levels))
+                .collect()
+        };
+
+        // Logical operators, as in JavaScript: `||` binds loosest, then `&&`, then `!`.
+        // Operators inside quotes or parentheses belong to their operand.
+        let alternatives = split_top_level(expr, "||");
+        if alternatives.len() > 1 {
+            return Node::Or(operands(alternatives));
+        }
+        let conjuncts = split_top_level(expr, "&&");
+        if conjuncts.len() > 1 {
+            return Node::And(operands(conjuncts));
+        }
+
+        let Some(levels) = levels.checked_sub(1) else {
+            return Node::TooDeep;
+        };
+
+        // Handle negation operator
+        if let Some(inner_expr) = expr.strip_prefix('!') {
+            return Node::Not(Box::new(Self::parse_balanced(inner_expr, levels)));
+        }
+
+        // A parenthesized condition, e.g. "(a || b)" in "(a || b) && c". The outer pair
+        // must enclose everything, which "(a) == (b)" does not.
+        if let Some(inner_expr) = expr
+            .strip_prefix('(')
+            .and_then(|rest| rest.strip_suffix(')'))
+            .filter(|inner| balanced_parens(inner))
+        {
+            return Node::Group(Box::new(Self::parse_balanced(inner_expr, levels)));
+        }
+
+        Node::Atom(expr.to_string())
+    }
+
+    // Chains evaluate their operands at their own level; every other node descends one.
+    fn evaluate(&self, variables: Vars<'_>) -> bool {
+        let descend = || variables.descend();
+        match self {
+            Node::Or(operands) => operands.iter().any(|node| node.evaluate(variables)),
+            Node::And(operands) => operands.iter().all(|node| node.evaluate(variables)),
+            Node::Not(inner) => descend().is_some_and(|variables| !inner.evaluate(variables)),
+            Node::Group(inner) => descend().is_some_and(|variables| inner.evaluate(variables)),
+            Node::Atom(expr) => descend().is_some_and(|variables| evaluate_atom(expr, variables)),
+            // Only reached past the limit, where descending records the failure
+            Node::TooDeep => descend().is_some(),
+        }
+    }
+
+    // What is wrong regardless of the variables: nesting past the limit, or a call
+    // to a function that does not exist.
+    fn check(&self, max_depth: usize) -> Result<(), EvalError> {
+        match self {
+            Node::Or(operands) | Node::And(operands) => {
+                operands.iter().try_for_each(|node| node.check(max_depth))
+            }
+            Node::Not(inner) | Node::Group(inner) => inner.check(max_depth),
+            Node::Atom(expr) => {
+                let operands = match split_comparison(expr) {
+                    Some((lhs, _, rhs)) => vec![lhs.trim(), rhs.trim()],
+                    None => vec![expr.as_str()],
+                };
+                match operands.into_iter().find_map(unknown_function) {
+                    Some(error) => Err(error),
+                    None => Ok(()),
+                }
+            }
+            Node::TooDeep => Err(EvalError::Malformed(DepthLimit::new(max_depth).error())),
+        }
     }
+}
 
-    if let Some(pos) = expr.find("||") {
-        let left = &expr[..pos].trim();
-        let right = &expr[pos + 2..].trim();
-        return evaluate_internal(left, variables) || evaluate_internal(right, variables);
+// A comparison, function call or variable: anything without a top-level `||`, `&&`,
+// `!` or enclosing parentheses.
+fn evaluate_atom(expr: &str, variables: Vars<'_>) -> bool {
+    if let Some(result) = parse_and_evaluate_membership(expr, variables) {
+        return result;
     }
 
     // Try parsing function-based expressions first, e.g., contains(vars, 'value')
@@ -65,6 +664,16 @@ fn evaluate_internal(expression: &str, variables: &Value) -> bool {
         return result;
     }
 
+    // Value functions used as conditions, e.g., bool(flag) or not(env.done)
+    match parse_and_evaluate_value_function(expr, variables) {
+        Some(Ok(value)) => return is_truthy(&value),
+        Some(Err(e)) => {
+            variables.fail(expr, e);
+            return false;
+        }
+        None => {}
+    }
+
     // Fallback to simple binary expressions, e.g., vars == 'value'
     if let Some(result) = parse_and_evaluate_binary_expression(expr, variables) {
         return result;
@@ -73,26 +682,114 @@ fn evaluate_internal(expression: &str, variables: &Value) -> bool {
     // Handle simple variable references (evaluate to their boolean truthiness)
     // This allows expressions like "env.troubleshooting" or "!env.troubleshooting"
     // where troubleshooting is a boolean
-    if let Some(value) = get_value(expr, variables) {
-        return match value {
-            Value::Bool(b) => *b,
-            Value::String(s) => !s.is_empty() && s != "false" && s != "0",
-            Value::Number(n) => n.as_i64().unwrap_or(0) != 0,
-            Value::Null => false,
-            Value::Array(arr) => !arr.is_empty(),
-            Value::Object(obj) => !obj.is_empty(),
-        };
+    if let Some(value) = variables.get(expr) {
+        return is_truthy(value);
     }
 
-    warn!(
-        "Could not parse expression: '{}'. Defaulting to false.",
-        expression
-    );
+    variables.fail(expr, diagnose(expr, variables));
     false
 }
 
+// Why an expression that none of the forms above accept could not be evaluated.
+fn diagnose(expr: &str, variables: Vars<'_>) -> EvalError {
+    if let Some((func_name, args)) = split_call(expr) {
+        match func_name {
+            "always" => {
+                return EvalError::Malformed("always expects no arguments".to_string());
+            }
+            "contains"
+            | "startsWith"
+            | "endsWith"
+            | "matches"
+            | "equalsIgnoreCase"
+            | "containsIgnoreCase"
+            | "startsWithIgnoreCase"
+            | "endsWithIgnoreCase" => {
+                if args.len() != 2 {
+                    return EvalError::Malformed(format!(
+                        "{func_name} expects 2 arguments, got {}",
+                        args.len()
+                    ));
+                }
+                if let Some(error) = diagnose_operand(args[0], variables) {
+                    return error;
+                }
+                if matches!(
+                    func_name,
+                    "startsWith" | "endsWith" | "startsWithIgnoreCase" | "endsWithIgnoreCase"
+                ) {
+                    return EvalError::TypeMismatch(format!(
+                        "{func_name}: '{}' is not a string",
+                        args[0]
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let operands = match split_comparison(expr) {
+        Some((lhs, _, rhs)) => vec![lhs.trim(), rhs.trim()],
+        None => vec![expr],
+    };
+    operands
+        .into_iter()
+        .find_map(|operand| diagnose_operand(operand, variables))
+        .unwrap_or_else(|| EvalError::Malformed(format!("could not parse '{expr}'")))
+}
+
+// An operand that is a call to an unknown function or an undefined variable
+fn diagnose_operand(operand: &str, variables: Vars<'_>) -> Option<EvalError> {
+    if split_call(operand).is_some() {
+        return unknown_function(operand);
+    }
+    (is_variable_path(operand) && variables.get(operand).is_none())
+        .then(|| EvalError::VariableNotFound(format!("'{operand}' is not defined")))
+}
+
+// An operand that calls a function the evaluator does not know
+fn unknown_function(operand: &str) -> Option<EvalError> {
+    let (func_name, _) = split_call(operand)?;
+    (!KNOWN_FUNCTIONS.contains(&func_name))
+        .then(|| EvalError::UnknownFunction(format!("unknown function '{func_name}'")))
+}
+
+// A call like "name(a, b)" that spans the whole expression
+fn split_call(expr: &str) -> Option<(&str, Vec<&str>)> {
+    let (func_name, args_str) = expr.split_once('(')?;
+    let func_name = func_name.trim();
+    let args_str = args_str
+        .strip_suffix(')')
+        .filter(|args| balanced_parens(args))?;
+    is_variable_path(func_name).then(|| (func_name, split_arguments(args_str)))
+}
+
+// Every function name the evaluator accepts, as a condition or a value
+const KNOWN_FUNCTIONS: &[&str] = &[
+    "always",
+    "contains",
+    "startsWith",
+    "endsWith",
+    "matches",
+    "equalsIgnoreCase",
+    "containsIgnoreCase",
+    "startsWithIgnoreCase",
+    "endsWithIgnoreCase",
+    "numberFormat",
+    "map",
+    "filter",
+    "len",
+    "get",
+    "coalesce",
+    "bool",
+    "not",
+    "isEmpty",
+    "isNull",
+    "between",
+];
+
 // Parses expressions like "contains(policy.product_types, 'FEX')"
-fn parse_and_evaluate_function(expr: &str, variables: &Value) -> Option<bool> {
+fn parse_and_evaluate_function(expr: &str, variables: Vars<'_>) -> Option<bool> {
     let (func_name, args_str) = expr.split_once('(')?;
     if !args_str.ends_with(')') {
         return None;
@@ -110,24 +807,102 @@ fn parse_and_evaluate_function(expr: &str, variables: &Value) -> Option<bool> {
         }
         _ => {
             // For other functions, we need exactly 2 arguments
-            let args: Vec<&str> = args_str.split(',').map(|s| s.trim()).collect();
+            let args = split_arguments(args_str);
             if args.len() != 2 {
                 return None;
             }
 
-            let val1 = get_value(args[0], variables)?;
-            let val2_str = args[1].trim_matches('\''); // Remove single quotes
+            let val1 = resolve_operand(args[0], variables)?;
+            let val1 = val1.as_ref();
+            let val2_str = unquote(args[1]);
 
             match func_name.trim() {
                 "contains" => Some(evaluate_contains(val1, val2_str)),
                 "startsWith" => Some(val1.as_str()?.starts_with(val2_str)),
                 "endsWith" => Some(val1.as_str()?.ends_with(val2_str)),
+                "matches" => Some(evaluate_matches(val1, val2_str, expr, variables)),
+                "equalsIgnoreCase" => Some(
+                    scalar_text(val1)
+                        .is_some_and(|text| text.to_lowercase() == val2_str.to_lowercase()),
+                ),
+                "containsIgnoreCase" => Some(evaluate_contains_ignore_case(val1, val2_str)),
+                "startsWithIgnoreCase" => Some(
+                    val1.as_str()?
+                        .to_lowercase()
+                        .starts_with(&val2_str.to_lowercase()),
+                ),
+                "endsWithIgnoreCase" => Some(
+                    val1.as_str()?
+                        .to_lowercase()
+                        .ends_with(&val2_str.to_lowercase()),
+                ),
                 _ => None,
             }
         }
     }
 }
 
+// matches(value, 'pattern'): whether the pattern matches anywhere in the value, so
+// anchor it with ^ and $ to match the whole value. Numbers and booleans are matched
+// as text; other values never match. An invalid pattern is reported and is false.
+fn evaluate_matches(value: &Value, pattern: &str, expr: &str, variables: Vars<'_>) -> bool {
+    let Some(text) = scalar_text(value) else {
+        return false;
+    };
+    match compiled_pattern(pattern) {
+        Ok(regex) => regex.is_match(&text),
+        Err(e) => {
+            variables.fail(
+                expr,
+                EvalError::Malformed(format!("invalid pattern '{pattern}': {e}")),
+            );
+            false
+        }
+    }
+}
+
+// A string, number or boolean as text; None for other values
+fn scalar_text(value: &Value) -> Option<Cow<'_, str>> {
+    match value {
+        Value::String(s) => Some(Cow::Borrowed(s.as_str())),
+        Value::Number(n) => Some(Cow::Owned(n.to_string())),
+        Value::Bool(b) => Some(Cow::Owned(b.to_string())),
+        _ => None,
+    }
+}
+
+// Compiles a matches() pattern once. Conditions in loops are evaluated over and over
+// with the same pattern; the cache is emptied if it ever grows past its limit.
+fn compiled_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    const MAX_CACHED_PATTERNS: usize = 256;
+    type Cache = HashMap<String, Result<Regex, regex::Error>>;
+    static PATTERNS: OnceLock<Mutex<Cache>> = OnceLock::new();
+
+    let Ok(mut patterns) = PATTERNS.get_or_init(Mutex::default).lock() else {
+        return Regex::new(pattern);
+    };
+    if let Some(compiled) = patterns.get(pattern) {
+        return compiled.clone();
+    }
+    if patterns.len() >= MAX_CACHED_PATTERNS {
+        patterns.clear();
+    }
+    let compiled = Regex::new(pattern);
+    patterns.insert(pattern.to_string(), compiled.clone());
+    compiled
+}
+
+// A string argument without its single or double quotes. Commas and parentheses
+// inside the quotes are part of the string.
+fn unquote(arg: &str) -> &str {
+    for q in ['\'', '"'] {
+        if let Some(inner) = arg.strip_prefix(q).and_then(|rest| rest.strip_suffix(q)) {
+            return inner;
+        }
+    }
+    arg
+}
+
 // Evaluates the 'contains' function for different types.
 fn evaluate_contains(collection: &Value, item: &str) -> bool {
     match collection {
@@ -137,33 +912,66 @@ fn evaluate_contains(collection: &Value, item: &str) -> bool {
     }
 }
 
-// Parses simple expressions like "variable == 'value'" or "variable == true"
-fn parse_and_evaluate_binary_expression(expr: &str, variables: &Value) -> Option<bool> {
-    let (var_path, op, raw_rhs) = if let Some(pos) = expr.find("==") {
+// 'contains' with both sides lowercased, Unicode-aware, so "ÄRGER" contains "ärg".
+fn evaluate_contains_ignore_case(collection: &Value, item: &str) -> bool {
+    let item = item.to_lowercase();
+    match collection {
+        Value::Array(arr) => arr
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|v| v.to_lowercase() == item),
+        Value::String(s) => s.to_lowercase().contains(&item),
+        _ => false,
+    }
+}
+
+// Splits a comparison into its left side, operator and right side
+fn split_comparison(expr: &str) -> Option<(&str, &'static str, &str)> {
+    let split = if let Some(pos) = expr.find("==") {
         (&expr[..pos], "==", &expr[pos + 2..])
     } else if let Some(pos) = expr.find("!=") {
         (&expr[..pos], "!=", &expr[pos + 2..])
+    } else if let Some(pos) = expr.find(">=") {
+        (&expr[..pos], ">=", &expr[pos + 2..])
+    } else if let Some(pos) = expr.find("<=") {
+        (&expr[..pos], "<=", &expr[pos + 2..])
+    } else if let Some(pos) = expr.find('>') {
+        (&expr[..pos], ">", &expr[pos + 1..])
+    } else if let Some(pos) = expr.find('<') {
+        (&expr[..pos], "<", &expr[pos + 1..])
     } else {
         return None;
     };
+    Some(split)
+}
+
+// Parses simple expressions like "variable == 'value'" or "variable == true"
+fn parse_and_evaluate_binary_expression(expr: &str, variables: Vars<'_>) -> Option<bool> {
+    let (var_path, op, raw_rhs) = split_comparison(expr)?;
 
     let var_path = var_path.trim();
     let raw_rhs = raw_rhs.trim();
 
-    let lhs = get_value(var_path, variables)?;
+    let lhs = match resolve_operand(var_path, variables) {
+        Some(lhs) => lhs,
+        // A bare number, e.g. "5 == 5.0"
+        None => Cow::Owned(Value::from(parse_number(var_path)?)),
+    };
+    let lhs = lhs.as_ref();
 
-    let are_equal = match raw_rhs {
-        "true" => lhs.as_bool() == Some(true),
-        "false" => lhs.as_bool() == Some(false),
-        _ if raw_rhs.starts_with('\'') && raw_rhs.ends_with('\'') => {
-            let rhs_str = raw_rhs.trim_matches('\'');
-            compare_values_smart(lhs, rhs_str)
-        }
-        _ if raw_rhs.starts_with('"') && raw_rhs.ends_with('"') => {
-            let rhs_str = raw_rhs.trim_matches('"');
-            compare_values_smart(lhs, rhs_str)
+    if !matches!(op, "==" | "!=") {
+        return compare_order(lhs, op, raw_rhs, variables);
+    }
+
+    let strict = variables.strict_equality;
+    let are_equal = match equals_literal(lhs, raw_rhs, strict) {
+        Some(are_equal) => are_equal,
+        None if is_variable_path(raw_rhs) => {
+            // An undefined RHS behaves like an undefined LHS
+            let rhs = variables.get(raw_rhs)?;
+            compare_variables(lhs, rhs, strict)
         }
-        _ => return None, // Invalid RHS
+        None => return None,
     };
 
     match op {
@@ -173,13 +981,761 @@ fn parse_and_evaluate_binary_expression(expr: &str, variables: &Value) -> Option
     }
 }
 
-// Smart comparison that handles type coercion between strings and booleans
-fn compare_values_smart(lhs: &Value, rhs_str: &str) -> bool {
+// Whether a value equals a literal: `true`, `false`, a quoted string or a bare number.
+// None when `literal` is none of those.
+fn equals_literal(lhs: &Value, literal: &str, strict: bool) -> Option<bool> {
+    let are_equal = match literal {
+        "true" => lhs.as_bool() == Some(true),
+        "false" => lhs.as_bool() == Some(false),
+        _ if literal.starts_with('\'') && literal.ends_with('\'') => {
+            let rhs_str = literal.trim_matches('\'');
+            compare_values_smart(lhs, rhs_str, strict)
+        }
+        _ if literal.starts_with('"') && literal.ends_with('"') => {
+            let rhs_str = literal.trim_matches('"');
+            compare_values_smart(lhs, rhs_str, strict)
+        }
+        _ => {
+            // A bare number, e.g. "count == 42"
+            let rhs = parse_number(literal)?;
+            match lhs {
+                Value::Number(n) => n.as_f64() == Some(rhs),
+                Value::String(_) if !strict => value_as_number(lhs) == Some(rhs),
+                _ => false,
+            }
+        }
+    };
+    Some(are_equal)
+}
+
+// A finite number written without quotes, e.g. "42", "-1.5" or "0.0"
+fn parse_number(s: &str) -> Option<f64> {
+    s.parse::<f64>().ok().filter(|n| n.is_finite())
+}
+
+// Membership, e.g. "status in ('active', 'pending')" or "code not in (404, 500)":
+// whether the value equals any literal in the list, with the coercions of `==`.
+// Returns None when the expression has no top-level `in`.
+fn parse_and_evaluate_membership(expr: &str, variables: Vars<'_>) -> Option<bool> {
+    let (pos, keyword, negate) = match find_top_level(expr, " not in ") {
+        Some(pos) => (pos, " not in ", true),
+        None => (find_top_level(expr, " in ")?, " in ", false),
+    };
+    let operand = expr[..pos].trim();
+    let list = expr[pos + keyword.len()..].trim();
+    let Some(items) = list
+        .strip_prefix('(')
+        .and_then(|rest| rest.strip_suffix(')'))
+        .filter(|inner| balanced_parens(inner))
+    else {
+        variables.fail(
+            expr,
+            EvalError::Malformed(format!(
+                "'{}' must be followed by a parenthesized list, got '{list}'",
+                keyword.trim()
+            )),
+        );
+        return Some(false);
+    };
+    let Some(value) = resolve_operand(operand, variables) else {
+        variables.fail(
+            expr,
+            diagnose_operand(operand, variables)
+                .unwrap_or_else(|| EvalError::Malformed(format!("could not evaluate '{operand}'"))),
+        );
+        return Some(false);
+    };
+
+    let mut found = false;
+    for item in split_arguments(items)
+        .into_iter()
+        .filter(|item| !item.is_empty())
+    {
+        match equals_literal(&value, item, variables.strict_equality) {
+            Some(equal) => found |= equal,
+            None => {
+                variables.fail(
+                    expr,
+                    EvalError::Malformed(format!("'{item}' in '{list}' is not a literal")),
+                );
+                return Some(false);
+            }
+        }
+    }
+    Some(found != negate)
+}
+
+// Numeric ordering (>, >=, <, <=). Both sides are coerced like numberFormat input,
+// optionally followed by a known unit; a side that is not a number makes the
+// comparison false, and so do two different units.
+fn compare_order(lhs: &Value, op: &str, raw_rhs: &str, variables: Vars<'_>) -> Option<bool> {
+    let rhs = resolve_argument(raw_rhs, variables)?;
+    Some(compare_numbers(lhs, op, &rhs, variables.unit_suffixes))
+}
+
+fn compare_numbers(lhs: &Value, op: &str, rhs: &Value, units: &[&'static str]) -> bool {
+    let (Some((a, a_unit)), Some((b, b_unit))) =
+        (number_with_unit(lhs, units), number_with_unit(rhs, units))
+    else {
+        return false;
+    };
+    if a_unit.is_some() && b_unit.is_some() && a_unit != b_unit {
+        return false;
+    }
+    match op {
+        ">" => a > b,
+        ">=" => a >= b,
+        "<" => a < b,
+        _ => a <= b,
+    }
+}
+
+// A dotted path of identifiers and array indices, e.g. "expected_total",
+// "env.totals.expected", "rows.0.name" or "rows[0].name"
+fn is_variable_path(s: &str) -> bool {
+    s.split('.').enumerate().all(|(i, part)| {
+        let (key, mut indices) = part.split_at(part.find('[').unwrap_or(part.len()));
+        let is_identifier = key
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        // Only a later segment may be a bare index, so "0.5" stays a number
+        if !is_identifier && (i == 0 || array_index(key).is_none()) {
+            return false;
+        }
+        while !indices.is_empty() {
+            match indices
+                .strip_prefix('[')
+                .and_then(|rest| rest.split_once(']'))
+            {
+                Some((index, rest)) if array_index(index).is_some() => indices = rest,
+                _ => return false,
+            }
+        }
+        true
+    })
+}
+
+// Compares two variable values, applying the same coercions as a literal RHS in both directions
+fn compare_variables(lhs: &Value, rhs: &Value, strict: bool) -> bool {
+    fn scalar_string(value: &Value) -> Option<String> {
+        match value {
+            Value::String(s) => Some(s.clone()),
+            Value::Bool(b) => Some(b.to_string()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }
+    }
+
+    match (lhs, rhs) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64() == b.as_f64(),
+        (Value::Number(_), Value::String(_)) | (Value::String(_), Value::Number(_)) if strict => {
+            false
+        }
+        _ => match (scalar_string(lhs), scalar_string(rhs)) {
+            (Some(lhs_str), Some(rhs_str)) => {
+                compare_values_smart(lhs, &rhs_str, strict)
+                    || compare_values_smart(rhs, &lhs_str, strict)
+            }
+            // Null, arrays and objects compare structurally
+            _ => lhs == rhs,
+        },
+    }
+}
+
+// Smart comparison that handles type coercion between strings and booleans.
+// When strict, a number never equals a string literal.
+fn compare_values_smart(lhs: &Value, rhs_str: &str, strict: bool) -> bool {
     match lhs {
         Value::String(s) => s == rhs_str,
         Value::Bool(true) => rhs_str == "true" || rhs_str == "1",
         Value::Bool(false) => rhs_str == "false" || rhs_str == "0",
+        Value::Number(_) if strict => false,
         Value::Number(n) => rhs_str.parse::<f64>().ok() == Some(n.as_f64().unwrap_or(0.0)),
         _ => false,
     }
 }
+
+// ---------------------------------------------------------------------------
+// Value layer
+//
+// `evaluate` always collapses to a boolean, which is what `if` conditions need.
+// Placeholders like `{{numberFormat(total, 2)}}` need the actual value instead,
+// so value-producing functions are evaluated here and anything else falls back to
+// the boolean evaluation.
+// ---------------------------------------------------------------------------
+
+/// Evaluates an expression to a JSON value.
+///
+/// Value functions (e.g. `numberFormat(x, 2)`) return their result; any other
+/// expression is evaluated as a condition and returned as `Value::Bool`.
+/// Returns an error when a value function receives invalid input.
+pub fn evaluate_value(expression: &str, variables: &Value) -> Result<Value, String> {
+    let limit = DepthLimit::new(DEFAULT_MAX_DEPTH);
+    evaluate_value_in(expression, Vars::tree(variables, &limit))
+}
+
+/// [`evaluate_value`] against pre-indexed variables.
+pub fn evaluate_value_indexed(expression: &str, context: &IndexedContext) -> Result<Value, String> {
+    let limit = DepthLimit::new(DEFAULT_MAX_DEPTH);
+    evaluate_value_in(expression, Vars::indexed(context, &limit))
+}
+
+fn evaluate_value_in(expression: &str, variables: Vars<'_>) -> Result<Value, String> {
+    let normalized = normalize_expression(expression);
+    let result = if let Some(result) = parse_and_evaluate_null_coalescing(&normalized, variables) {
+        result
+    } else if let Some(result) = parse_and_evaluate_value_function(&normalized, variables) {
+        result
+    } else {
+        Ok(Value::Bool(evaluate_internal(&normalized, variables)))
+    };
+    // Too deep is reported even where a nested level degraded to null or false
+    variables.limit.check()?;
+    result.map_err(|e| e.to_string())
+}
+
+// `a ?? b ?? c`: the first operand that is defined and not null. Right-associative,
+// so it reads as `a ?? (b ?? c)` and stops at the first present value. Unlike
+// coalesce(), falsy values such as 0, "" and false count as present and are returned.
+// Returns None when the expression has no `??` outside quotes and parentheses.
+fn parse_and_evaluate_null_coalescing(
+    expr: &str,
+    variables: Vars<'_>,
+) -> Option<Result<Value, EvalError>> {
+    let pos = find_top_level(expr, "??")?;
+    let Some(variables) = variables.descend() else {
+        return Some(Err(EvalError::Malformed(variables.limit.error())));
+    };
+    let (left, right) = (expr[..pos].trim(), expr[pos + 2..].trim());
+    if left.is_empty() || right.is_empty() {
+        return Some(Err(EvalError::Malformed(format!(
+            "'??' needs an operand on both sides in '{expr}'"
+        ))));
+    }
+    match resolve_coalesce_operand(left, variables) {
+        Ok(Some(value)) if !value.is_null() => Some(Ok(value)),
+        Ok(_) => Some(
+            parse_and_evaluate_null_coalescing(right, variables).unwrap_or_else(|| {
+                resolve_coalesce_operand(right, variables).map(Option::unwrap_or_default)
+            }),
+        ),
+        Err(e) => Some(Err(e)),
+    }
+}
+
+// An operand of `??`: a literal, a variable path (undefined is None), or a value
+// function call.
+fn resolve_coalesce_operand(arg: &str, variables: Vars<'_>) -> Result<Option<Value>, EvalError> {
+    if arg == "null" {
+        return Ok(None);
+    }
+    if let Some(value) = resolve_operand(arg, variables) {
+        return Ok(Some(value.into_owned()));
+    }
+    if let Some(value) = resolve_argument(arg, variables) {
+        return Ok(Some(value));
+    }
+    if is_variable_path(arg) {
+        return Ok(None);
+    }
+    Err(EvalError::Malformed(format!(
+        "'{arg}' is not a value and cannot be an operand of '??'"
+    )))
+}
+
+// coalesce(a, b, ...): the first truthy argument (see `is_truthy`), or null when none
+// is. Falsy values such as 0, "" and false are skipped; use `??` to keep them.
+fn evaluate_coalesce(args: &[&str], variables: Vars<'_>) -> Result<Value, EvalError> {
+    if args.iter().all(|arg| arg.is_empty()) {
+        return Err(EvalError::Malformed(
+            "coalesce expects at least 1 argument".to_string(),
+        ));
+    }
+    for arg in args {
+        if let Some(value) = resolve_coalesce_operand(arg, variables)? {
+            if is_truthy(&value) {
+                return Ok(value);
+            }
+        }
+    }
+    Ok(Value::Null)
+}
+
+// Byte offset of the first `needle` that is not inside quotes or parentheses.
+fn find_top_level(expr: &str, needle: &str) -> Option<usize> {
+    if !expr.contains(needle) {
+        return None;
+    }
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (i, ch) in expr.char_indices() {
+        match (quote, ch) {
+            (Some(q), _) if ch == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(ch),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, _) if depth == 0 && expr[i..].starts_with(needle) => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+// Splits `expr` at every top-level `needle`; a single part when there is none.
+fn split_top_level<'e>(expr: &'e str, needle: &str) -> Vec<&'e str> {
+    let mut parts = Vec::new();
+    let mut rest = expr;
+    while let Some(pos) = find_top_level(rest, needle) {
+        parts.push(&rest[..pos]);
+        rest = &rest[pos + needle.len()..];
+    }
+    parts.push(rest);
+    parts
+}
+
+// Whether the parentheses outside quotes are balanced, never closing more than
+// they opened.
+fn balanced_parens(expr: &str) -> bool {
+    let mut depth = 0usize;
+    let mut quote = None;
+    for ch in expr.chars() {
+        match (quote, ch) {
+            (Some(q), _) if ch == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(ch),
+            (None, '(') => depth += 1,
+            (None, ')') if depth == 0 => return false,
+            (None, ')') => depth -= 1,
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+// Parses value-producing calls like "numberFormat(total, 2)".
+// Returns None when the expression is not a known value function.
+fn parse_and_evaluate_value_function(
+    expr: &str,
+    variables: Vars<'_>,
+) -> Option<Result<Value, EvalError>> {
+    let (func_name, args_str) = expr.split_once('(')?;
+    let args_str = args_str.strip_suffix(')')?;
+    if !balanced_parens(args_str) {
+        // e.g. "bool(a) == bool(b)": the first call ends before the expression does
+        return None;
+    }
+    let Some(variables) = variables.descend() else {
+        return Some(Err(EvalError::Malformed(variables.limit.error())));
+    };
+    let args = split_arguments(args_str);
+
+    match func_name.trim() {
+        "numberFormat" => Some(evaluate_number_format(&args, variables)),
+        "map" => Some(evaluate_map(&args, variables)),
+        "filter" => Some(evaluate_filter(&args, variables)),
+        "len" => Some(evaluate_len(&args, variables)),
+        "get" => Some(evaluate_get(&args, variables)),
+        "coalesce" => Some(evaluate_coalesce(&args, variables)),
+        "bool" => Some(evaluate_bool(&args, variables, false)),
+        "not" => Some(evaluate_bool(&args, variables, true)),
+        "isEmpty" => Some(evaluate_is_empty(&args, variables, "isEmpty")),
+        "isNull" => Some(evaluate_is_empty(&args, variables, "isNull")),
+        "between" => Some(evaluate_between(&args, variables)),
+        _ => None,
+    }
+}
+
+// bool(x) applies `is_truthy` to a value, or evaluates x as a condition when it is
+// an expression such as "status == 'done'". not(x) negates the result.
+fn evaluate_bool(args: &[&str], variables: Vars<'_>, negate: bool) -> Result<Value, EvalError> {
+    let name = if negate { "not" } else { "bool" };
+    if args.len() != 1 || args[0].is_empty() {
+        return Err(EvalError::Malformed(format!(
+            "{name} expects 1 argument, got {}",
+            args.len()
+        )));
+    }
+    let arg = args[0];
+    let truthy = match resolve_value(arg, variables) {
+        Some(value) => is_truthy(&value),
+        None if is_variable_path(arg) => false, // undefined variable
+        None => evaluate_internal(arg, variables),
+    };
+    Ok(Value::Bool(truthy != negate))
+}
+
+// isEmpty(x) and isNull(x) check a variable, literal or value function result. Unlike
+// bool(), zero and `false` are values, not emptiness:
+//
+// | value                     | isEmpty | isNull |
+// |---------------------------|---------|--------|
+// | undefined variable        | true    | true   |
+// | null                      | true    | true   |
+// | "" / [] / {}              | true    | false  |
+// | other string/array/object | false   | false  |
+// | number (including 0)      | false   | false  |
+// | boolean (including false) | false   | false  |
+fn evaluate_is_empty(args: &[&str], variables: Vars<'_>, name: &str) -> Result<Value, EvalError> {
+    if args.len() != 1 || args[0].is_empty() {
+        return Err(EvalError::Malformed(format!(
+            "{name} expects 1 argument, got {}",
+            args.len()
+        )));
+    }
+    let arg = args[0];
+    let value = match resolve_value(arg, variables) {
+        Some(value) => value,
+        None if arg == "null" || is_variable_path(arg) => Value::Null,
+        None => {
+            return Err(EvalError::Malformed(format!(
+                "{name}: '{arg}' is not a variable or value"
+            )))
+        }
+    };
+    let result = match (&value, name) {
+        (Value::Null, _) => true,
+        (_, "isNull") => false,
+        (Value::String(s), _) => s.is_empty(),
+        (Value::Array(items), _) => items.is_empty(),
+        (Value::Object(map), _) => map.is_empty(),
+        _ => false,
+    };
+    Ok(Value::Bool(result))
+}
+
+// between(value, low, high): whether low <= value <= high, bounds included. Each
+// argument is a variable, a literal or a value function, compared like `>=` and `<=`
+// (numeric strings and unit suffixes included). An undefined or non-numeric value,
+// or bound, is not in range.
+fn evaluate_between(args: &[&str], variables: Vars<'_>) -> Result<Value, EvalError> {
+    if args.len() != 3 || args.iter().any(|arg| arg.is_empty()) {
+        return Err(EvalError::Malformed(format!(
+            "between expects 3 arguments (value, low, high), got {}",
+            args.len()
+        )));
+    }
+    let [Some(value), Some(low), Some(high)] = [0, 1, 2].map(|i| resolve_value(args[i], variables))
+    else {
+        return Ok(Value::Bool(false));
+    };
+    let units = variables.unit_suffixes;
+    Ok(Value::Bool(
+        compare_numbers(&value, ">=", &low, units) && compare_numbers(&value, "<=", &high, units),
+    ))
+}
+
+// Splits function arguments on commas that are not nested inside parentheses or quotes,
+// so calls like "contains(map(items, 'name'), 'Bob')" keep the inner call intact and
+// "matches(code, '^[0-9]{2,4}$')" keeps its pattern.
+fn split_arguments(args_str: &str) -> Vec<&str> {
+    let mut args = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, ch) in args_str.char_indices() {
+        match (quote, ch) {
+            (Some(q), _) if ch == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(ch),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => {
+                args.push(args_str[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    args.push(args_str[start..].trim());
+    args
+}
+
+// Resolves an operand that is either a variable path or a nested value function call.
+fn resolve_operand<'a>(arg: &str, variables: Vars<'a>) -> Option<Cow<'a, Value>> {
+    if let Some(value) = variables.get(arg) {
+        return Some(Cow::Borrowed(value));
+    }
+    match parse_and_evaluate_value_function(arg, variables)? {
+        Ok(value) => Some(Cow::Owned(value)),
+        Err(e) => {
+            variables.fail(arg, e);
+            None
+        }
+    }
+}
+
+// map(items, 'field'): the named field (dot paths allowed) of each array element.
+// Elements without the field map to null; non-array input maps to an empty array.
+fn evaluate_map(args: &[&str], variables: Vars<'_>) -> Result<Value, EvalError> {
+    if args.len() != 2 {
+        return Err(EvalError::Malformed(format!(
+            "map expects 2 arguments (items, 'field'), got {}",
+            args.len()
+        )));
+    }
+    let field = resolve_argument(args[1], variables)
+        .and_then(|v| v.as_str().map(str::to_string))
+        .ok_or_else(|| {
+            EvalError::TypeMismatch(format!("map: field must be a string, got '{}'", args[1]))
+        })?;
+
+    let projected = match resolve_operand(args[0], variables).as_deref() {
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| get_value(&field, item).cloned().unwrap_or(Value::Null))
+            .collect(),
+        _ => Vec::new(),
+    };
+    Ok(Value::Array(projected))
+}
+
+// filter(rows, 'amount > 100'): the rows for which the condition holds, unchanged and
+// in order. The condition is evaluated with each row as the innermost scope, so its
+// fields can be named directly (`amount`) or through `item` (`item.amount`, or `item`
+// alone for arrays of scalars); other variables stay visible. Non-array input filters
+// to an empty array.
+fn evaluate_filter(args: &[&str], variables: Vars<'_>) -> Result<Value, EvalError> {
+    if args.len() != 2 {
+        return Err(EvalError::Malformed(format!(
+            "filter expects 2 arguments (rows, 'condition'), got {}",
+            args.len()
+        )));
+    }
+    let condition = resolve_argument(args[1], variables)
+        .and_then(|v| v.as_str().map(str::to_string))
+        .ok_or_else(|| {
+            EvalError::TypeMismatch(format!(
+                "filter: condition must be a string, got '{}'",
+                args[1]
+            ))
+        })?;
+
+    // Parsed once for all rows
+    let condition = Node::parse(&condition, variables.levels_left())?;
+    let rows = resolve_operand(args[0], variables);
+    let matching = match rows.as_deref() {
+        Some(Value::Array(rows)) => rows
+            .iter()
+            .filter(|row| condition.evaluate(variables.with_scope(row)))
+            .cloned()
+            .collect(),
+        _ => Vec::new(),
+    };
+    Ok(Value::Array(matching))
+}
+
+// get(obj, 'a.b.c'): dynamic path lookup. The path may be a literal or a variable
+// holding the path string; a missing object or path yields null.
+fn evaluate_get(args: &[&str], variables: Vars<'_>) -> Result<Value, EvalError> {
+    if args.len() != 2 {
+        return Err(EvalError::Malformed(format!(
+            "get expects 2 arguments (object, 'path'), got {}",
+            args.len()
+        )));
+    }
+    let path = resolve_argument(args[1], variables)
+        .and_then(|v| v.as_str().map(str::to_string))
+        .ok_or_else(|| {
+            EvalError::TypeMismatch(format!("get: path must be a string, got '{}'", args[1]))
+        })?;
+
+    let found = resolve_operand(args[0], variables)
+        .and_then(|object| get_value(&path, object.as_ref()).cloned());
+    Ok(found.unwrap_or(Value::Null))
+}
+
+// len(x): number of elements of an array or object, or characters of a string.
+fn evaluate_len(args: &[&str], variables: Vars<'_>) -> Result<Value, EvalError> {
+    if args.len() != 1 || args[0].is_empty() {
+        return Err(EvalError::Malformed(format!(
+            "len expects 1 argument, got {}",
+            args.len()
+        )));
+    }
+    let value = resolve_operand(args[0], variables)
+        .ok_or_else(|| EvalError::VariableNotFound(format!("len: '{}' is not defined", args[0])))?;
+    let len = match value.as_ref() {
+        Value::Array(items) => items.len(),
+        Value::Object(map) => map.len(),
+        Value::String(s) => s.chars().count(),
+        other => {
+            return Err(EvalError::TypeMismatch(format!(
+                "len: expected an array, object or string but got {other}"
+            )))
+        }
+    };
+    Ok(Value::from(len))
+}
+
+// Resolves an argument that may be a variable, a value function call or a literal.
+fn resolve_value(arg: &str, variables: Vars<'_>) -> Option<Value> {
+    resolve_operand(arg, variables)
+        .map(Cow::into_owned)
+        .or_else(|| resolve_argument(arg, variables))
+}
+
+// Resolves a function argument: quoted string, numeric/boolean literal, or variable path.
+fn resolve_argument(arg: &str, variables: Vars<'_>) -> Option<Value> {
+    if arg.len() >= 2
+        && ((arg.starts_with('\'') && arg.ends_with('\''))
+            || (arg.starts_with('"') && arg.ends_with('"')))
+    {
+        return Some(Value::String(arg[1..arg.len() - 1].to_string()));
+    }
+    match arg {
+        "true" => return Some(Value::Bool(true)),
+        "false" => return Some(Value::Bool(false)),
+        _ => {}
+    }
+    if let Ok(n) = arg.parse::<f64>() {
+        return serde_json::Number::from_f64(n).map(Value::Number);
+    }
+    variables.get(arg).cloned()
+}
+
+// Coerces a value to f64, accepting numbers and numeric strings.
+fn value_as_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok().filter(|n| n.is_finite()),
+        _ => None,
+    }
+}
+
+// Like value_as_number, but a string may end in one of `units`, e.g. "100px" is
+// (100, Some("px")). Any other trailing text makes it not a number.
+fn number_with_unit(value: &Value, units: &[&'static str]) -> Option<(f64, Option<&'static str>)> {
+    if let Some(n) = value_as_number(value) {
+        return Some((n, None));
+    }
+    let s = value.as_str()?.trim();
+    // The longest match wins, so "ms" is not read as "m" + "s"
+    let unit = units
+        .iter()
+        .copied()
+        .filter(|unit| !unit.is_empty() && s.ends_with(unit))
+        .max_by_key(|unit| unit.len())?;
+    let n = s[..s.len() - unit.len()]
+        .trim_end()
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite())?;
+    Some((n, Some(unit)))
+}
+
+/// Thousands and decimal separators for a locale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberSeparators {
+    pub group: char,
+    pub decimal: char,
+}
+
+impl NumberSeparators {
+    /// Looks up separators for a BCP 47 style locale tag such as `en-US` or `de_DE`.
+    /// Unknown locales fall back to `en-US` conventions.
+    pub fn for_locale(locale: &str) -> Self {
+        let tag = locale.trim().replace('_', "-").to_lowercase();
+        let language = tag.split('-').next().unwrap_or_default();
+
+        match (tag.as_str(), language) {
+            ("de-ch", _) | ("it-ch", _) | ("fr-ch", _) => Self {
+                group: '\'',
+                decimal: '.',
+            },
+            (_, "de" | "es" | "it" | "nl" | "pt" | "id" | "tr" | "da" | "el" | "ro") => Self {
+                group: '.',
+                decimal: ',',
+            },
+            (_, "fr" | "ru" | "pl" | "cs" | "sv" | "nb" | "fi" | "uk" | "hu" | "sk") => Self {
+                group: '\u{00A0}',
+                decimal: ',',
+            },
+            _ => Self {
+                group: ',',
+                decimal: '.',
+            },
+        }
+    }
+}
+
+// The locale comes from an explicit third argument, otherwise from a `locale`
+// input or `env.locale`, defaulting to en-US.
+fn configured_locale<'a>(variables: Vars<'a>) -> Option<&'a str> {
+    variables
+        .get("locale")
+        .or_else(|| variables.get("env.locale"))
+        .and_then(|v| v.as_str())
+}
+
+fn evaluate_number_format(args: &[&str], variables: Vars<'_>) -> Result<Value, EvalError> {
+    if args.len() < 2 || args.len() > 3 {
+        return Err(EvalError::Malformed(format!(
+            "numberFormat expects 2 or 3 arguments (value, decimals[, locale]), got {}",
+            args.len()
+        )));
+    }
+
+    let raw = resolve_argument(args[0], variables).ok_or_else(|| {
+        EvalError::VariableNotFound(format!("numberFormat: '{}' is not defined", args[0]))
+    })?;
+    let number = value_as_number(&raw).ok_or_else(|| {
+        EvalError::TypeMismatch(format!("numberFormat: expected a number but got {raw}"))
+    })?;
+
+    let decimals = resolve_argument(args[1], variables)
+        .as_ref()
+        .and_then(value_as_number)
+        .filter(|d| *d >= 0.0 && d.fract() == 0.0 && *d <= 20.0)
+        .ok_or_else(|| {
+            EvalError::TypeMismatch(format!(
+                "numberFormat: decimals must be an integer between 0 and 20, got '{}'",
+                args[1]
+            ))
+        })? as usize;
+
+    let locale = match args.get(2) {
+        Some(arg) => resolve_argument(arg, variables)
+            .and_then(|v| v.as_str().map(str::to_string))
+            .ok_or_else(|| {
+                EvalError::TypeMismatch(format!("numberFormat: invalid locale '{arg}'"))
+            })?,
+        None => configured_locale(variables).unwrap_or("en-US").to_string(),
+    };
+
+    Ok(Value::String(format_number(
+        number,
+        decimals,
+        NumberSeparators::for_locale(&locale),
+    )))
+}
+
+/// Formats a number with a fixed number of decimals and grouped thousands.
+pub fn format_number(number: f64, decimals: usize, separators: NumberSeparators) -> String {
+    let fixed = format!("{:.*}", decimals, number.abs());
+    let (int_part, frac_part) = match fixed.split_once('.') {
+        Some((i, f)) => (i, Some(f)),
+        None => (fixed.as_str(), None),
+    };
+
+    let mut grouped = String::with_capacity(fixed.len() + int_part.len() / 3);
+    for (i, ch) in int_part.chars().enumerate() {
+        if i > 0 && (int_part.len() - i) % 3 == 0 {
+            grouped.push(separators.group);
+        }
+        grouped.push(ch);
+    }
+    if let Some(frac) = frac_part {
+        grouped.push(separators.decimal);
+        grouped.push_str(frac);
+    }
+
+    // Avoid "-0.00" when a small negative number rounds to zero
+    let is_zero = fixed.chars().all(|c| c == '0' || c == '.');
+    if number.is_sign_negative() && !is_zero {
+        grouped.insert(0, '-');
+    }
+    grouped
+}
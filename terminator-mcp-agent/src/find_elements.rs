@@ -0,0 +1,70 @@
+//! Every element matching a selector, behind the `find_elements` tool.
+//!
+//! Unlike the single-element tools, a selector that matches nothing is not an error: the
+//! result is an empty list, including when the search times out. Matches are in reading
+//! order and can be capped with a limit; `total` still counts every match so callers can
+//! tell the list was cut. Only the matches that are returned are described, so a low
+//! limit also saves reading the text of the rest.
+
+use serde::Serialize;
+use terminator::{AutomationError, ElementBounds, UIElement};
+
+/// Default time to wait for the first match, in milliseconds
+pub const DEFAULT_TIMEOUT_MS: u64 = 1000;
+
+/// One matching element
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ElementMatch {
+    pub role: String,
+    pub name: Option<String>,
+    /// The element's own text, without its children's
+    pub text: Option<String>,
+    pub bounds: Option<ElementBounds>,
+}
+
+/// Anything that can be described as an [`ElementMatch`]
+pub trait Describe {
+    fn describe(&self) -> ElementMatch;
+}
+
+impl Describe for UIElement {
+    fn describe(&self) -> ElementMatch {
+        let non_empty = |s: Option<String>| s.filter(|s| !s.is_empty());
+        ElementMatch {
+            role: self.role(),
+            name: non_empty(self.name()),
+            text: non_empty(self.text(0).ok()),
+            bounds: self.bounds().ok().map(ElementBounds::from),
+        }
+    }
+}
+
+/// The matches of one selector
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FoundElements {
+    /// Number of elements that matched, including any left out by the limit
+    pub total: usize,
+    /// Some matches were left out by the limit
+    pub truncated: bool,
+    pub elements: Vec<ElementMatch>,
+}
+
+/// Describes up to `limit` of the elements a search returned. Finding nothing, or timing
+/// out before anything appeared, gives an empty result; other errors are passed on.
+pub fn collect_matches<E: Describe>(
+    found: Result<Vec<E>, AutomationError>,
+    limit: Option<usize>,
+) -> Result<FoundElements, AutomationError> {
+    let elements = match found {
+        Ok(elements) => elements,
+        Err(AutomationError::ElementNotFound(_) | AutomationError::Timeout(_)) => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    let total = elements.len();
+    let kept = limit.map_or(total, |limit| limit.min(total));
+    Ok(FoundElements {
+        total,
+        truncated: kept < total,
+        elements: elements[..kept].iter().map(Describe::describe).collect(),
+    })
+}
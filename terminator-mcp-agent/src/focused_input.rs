@@ -0,0 +1,94 @@
+//! Input to whatever element currently has keyboard focus.
+//!
+//! Used by the `type_into_focused` and `press_keys` tools when focus is already in the
+//! right place (e.g. after tabbing) and no selector is needed. The focus lookup and the
+//! input calls go through small traits so the logic can be tested without a desktop.
+
+use terminator::{AutomationError, Desktop, UIElement};
+
+/// Something that can report the element with keyboard focus
+pub trait FocusProvider {
+    type Element: FocusedElement;
+
+    fn focused(&self) -> Result<Self::Element, AutomationError>;
+}
+
+/// The input operations performed on the focused element
+pub trait FocusedElement {
+    fn type_text(&self, text: &str, use_clipboard: bool) -> Result<(), AutomationError>;
+    fn press_key(&self, key: &str) -> Result<(), AutomationError>;
+}
+
+impl FocusProvider for Desktop {
+    type Element = UIElement;
+
+    fn focused(&self) -> Result<UIElement, AutomationError> {
+        self.focused_element()
+    }
+}
+
+impl FocusedElement for UIElement {
+    fn type_text(&self, text: &str, use_clipboard: bool) -> Result<(), AutomationError> {
+        UIElement::type_text(self, text, use_clipboard)
+    }
+
+    fn press_key(&self, key: &str) -> Result<(), AutomationError> {
+        UIElement::press_key(self, key)
+    }
+}
+
+/// Why input to the focused element failed
+#[derive(Debug)]
+pub enum FocusedInputError {
+    /// Nothing has keyboard focus, or the focused element could not be read
+    NoFocus(String),
+    /// The focused element was found but rejected the input
+    Input(AutomationError),
+}
+
+impl std::fmt::Display for FocusedInputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FocusedInputError::NoFocus(reason) => {
+                write!(f, "No element has keyboard focus: {reason}")
+            }
+            FocusedInputError::Input(e) => write!(f, "Input to the focused element failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for FocusedInputError {}
+
+fn focused<P: FocusProvider>(provider: &P) -> Result<P::Element, FocusedInputError> {
+    provider
+        .focused()
+        .map_err(|e| FocusedInputError::NoFocus(e.to_string()))
+}
+
+/// Types `text` into the focused element and returns that element.
+pub fn type_into_focused<P: FocusProvider>(
+    provider: &P,
+    text: &str,
+    use_clipboard: bool,
+) -> Result<P::Element, FocusedInputError> {
+    let element = focused(provider)?;
+    element
+        .type_text(text, use_clipboard)
+        .map_err(FocusedInputError::Input)?;
+    Ok(element)
+}
+
+/// Presses `keys` in order on the focused element and returns that element.
+///
+/// Focus is resolved once, so all keys go to the element that had focus when the call
+/// started. Stops at the first key that fails.
+pub fn press_keys<P: FocusProvider>(
+    provider: &P,
+    keys: &[String],
+) -> Result<P::Element, FocusedInputError> {
+    let element = focused(provider)?;
+    for key in keys {
+        element.press_key(key).map_err(FocusedInputError::Input)?;
+    }
+    Ok(element)
+}
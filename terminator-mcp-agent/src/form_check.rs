@@ -0,0 +1,123 @@
+//! Form completeness checks behind the `check_fields` tool.
+//!
+//! Each field is located by selector and its value is read live. A field counts as
+//! required when the platform marks it `IsRequiredForForm`; when the platform does not
+//! say, every listed field is treated as required. The form is complete when no
+//! required field is empty and every field could be found and read.
+
+use serde::{Deserialize, Serialize};
+use terminator::UIElement;
+
+/// What was read from one field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldReading {
+    /// No element matched the selector
+    NotFound(String),
+    /// The element exists but its value could not be read
+    Unreadable(String),
+    Found {
+        value: Option<String>,
+        /// `IsRequiredForForm`, when the platform exposes it
+        required: Option<bool>,
+    },
+}
+
+impl FieldReading {
+    /// Reads the field's current value and required flag.
+    pub fn read(element: &UIElement) -> Self {
+        match element.get_value() {
+            Ok(value) => FieldReading::Found {
+                value,
+                required: element.is_required_for_form().unwrap_or(None),
+            },
+            Err(e) => FieldReading::Unreadable(e.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldStatus {
+    Filled,
+    Empty,
+    NotFound,
+    Unreadable,
+}
+
+/// The verdict for one field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldReport {
+    pub selector: String,
+    pub status: FieldStatus,
+    pub required: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The verdict for the whole form
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FormReport {
+    pub complete: bool,
+    /// Required fields with no value
+    pub empty_required: Vec<String>,
+    /// Fields that could not be found or read
+    pub invalid: Vec<String>,
+    pub fields: Vec<FieldReport>,
+}
+
+/// Builds the report from each selector's reading, in the order given.
+pub fn check_fields(readings: Vec<(String, FieldReading)>) -> FormReport {
+    let fields: Vec<FieldReport> = readings
+        .into_iter()
+        .map(|(selector, reading)| match reading {
+            FieldReading::NotFound(error) => FieldReport {
+                selector,
+                status: FieldStatus::NotFound,
+                required: true,
+                value: None,
+                error: Some(error),
+            },
+            FieldReading::Unreadable(error) => FieldReport {
+                selector,
+                status: FieldStatus::Unreadable,
+                required: true,
+                value: None,
+                error: Some(error),
+            },
+            FieldReading::Found { value, required } => {
+                let filled = value.as_deref().is_some_and(|v| !v.trim().is_empty());
+                FieldReport {
+                    selector,
+                    status: if filled {
+                        FieldStatus::Filled
+                    } else {
+                        FieldStatus::Empty
+                    },
+                    required: required.unwrap_or(true),
+                    value,
+                    error: None,
+                }
+            }
+        })
+        .collect();
+
+    let empty_required: Vec<String> = fields
+        .iter()
+        .filter(|f| f.required && f.status == FieldStatus::Empty)
+        .map(|f| f.selector.clone())
+        .collect();
+    let invalid: Vec<String> = fields
+        .iter()
+        .filter(|f| matches!(f.status, FieldStatus::NotFound | FieldStatus::Unreadable))
+        .map(|f| f.selector.clone())
+        .collect();
+
+    FormReport {
+        complete: empty_required.is_empty() && invalid.is_empty(),
+        empty_required,
+        invalid,
+        fields,
+    }
+}
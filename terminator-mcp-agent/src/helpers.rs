@@ -1,10 +1,11 @@
-use crate::expression_eval;
+use crate::expression_eval::{self, IndexedContext};
 use crate::utils::ToolCall;
+use crate::workflow_events::VariableChange;
 use regex::Regex;
 use rmcp::ErrorData as McpError;
 use serde_json::{json, Value};
 use std::time::Duration;
-use terminator::{AutomationError, Desktop, Selector, UIElement}; // NEW: import expression evaluator
+use terminator::{AutomationError, Desktop, ElementInfo, ErrorCategory, Selector, UIElement}; // NEW: import expression evaluator
 
 /// Helper function to parse comma-separated alternative selectors into a Vec<String>
 pub fn parse_alternative_selectors(alternatives: Option<&str>) -> Vec<String> {
@@ -37,36 +38,146 @@ pub fn get_selectors_tried_all(
 }
 
 /// Builds a standardized JSON object with detailed information about a UIElement.
-/// This includes a suggested selector that prioritizes role|name over just the ID.
+/// The [`ElementInfo`] fields come first, followed by tool-specific extras such as a
+/// suggested selector that prioritizes role|name over just the ID.
 pub fn build_element_info(element: &UIElement) -> Value {
     let id = element.id().unwrap_or_default();
-    let role = element.role();
-    let name = element.name().unwrap_or_default();
+    let info = element.info();
+    let name = info.name.clone().unwrap_or_default();
 
-    let suggested_selector = if !name.is_empty() && role != "Unknown" {
-        format!("{}|{}", &role, &name)
+    let suggested_selector = if !name.is_empty() && info.role != "Unknown" {
+        format!("{}|{}", &info.role, &name)
     } else {
         format!("#{id}")
     };
 
-    json!({
-        "name": name,
-        "role": role,
-        "id": id,
-        "suggested_selector": suggested_selector,
-        "application": element.application_name(),
-        "window_title": element.window_title(),
-        "process_id": element.process_id().unwrap_or(0),
-        "is_focused": element.is_focused().unwrap_or(false),
-        "text": element.text(0).unwrap_or_default(),
-        "bounds": element.bounds().map(|b| json!({
-            "x": b.0, "y": b.1, "width": b.2, "height": b.3
-        })).unwrap_or(json!(null)),
-        "enabled": element.is_enabled().unwrap_or(false),
-        "is_selected": element.is_selected().unwrap_or(false),
-        "is_toggled": element.is_toggled().unwrap_or(false),
-        "keyboard_focusable": element.is_keyboard_focusable().unwrap_or(false),
-    })
+    element_info_with_extras(
+        &info,
+        json!({
+            "id": id,
+            "identity_key": element.identity_key(),
+            "suggested_selector": suggested_selector,
+            "application": element.application_name(),
+            "window_title": element.window_title(),
+            "process_id": element.process_id().unwrap_or(0),
+            "text": element.text(0).unwrap_or_default(),
+        }),
+    )
+}
+
+/// Serializes `info` and adds the fields of `extras` next to its own.
+///
+/// `ElementInfo` fields win over extras with the same name, so every tool result
+/// carries the shared shape unchanged.
+pub fn element_info_with_extras(info: &ElementInfo, extras: Value) -> Value {
+    let mut value = serde_json::to_value(info).unwrap_or_else(|_| json!({}));
+    if let (Some(object), Value::Object(extras)) = (value.as_object_mut(), extras) {
+        for (key, extra) in extras {
+            object.entry(key).or_insert(extra);
+        }
+    }
+    value
+}
+
+/// Converts a focused-input failure into an MCP error, merging `context` into the details.
+pub fn focused_input_error(
+    error: crate::focused_input::FocusedInputError,
+    context: Value,
+) -> McpError {
+    use crate::focused_input::FocusedInputError;
+
+    let mut details = json!({ "reason": error.to_string() });
+    if let (Some(details), Value::Object(context)) = (details.as_object_mut(), context) {
+        details.extend(context);
+    }
+    match error {
+        FocusedInputError::NoFocus(_) => {
+            details["suggestion"] = json!(
+                "Focus the target control first (e.g. click it or press {Tab}), or use a selector-based tool."
+            );
+            McpError::resource_not_found("No element has keyboard focus", Some(details))
+        }
+        FocusedInputError::Input(_) => {
+            McpError::internal_error("Failed to send input to the focused element", Some(details))
+        }
+    }
+}
+
+/// Converts a clipboard failure into an MCP error that names its kind
+pub fn clipboard_error(error: crate::clipboard::ClipboardError) -> McpError {
+    use crate::clipboard::ClipboardError;
+
+    let details = json!({ "error_type": error.kind(), "reason": error.to_string() });
+    match error {
+        ClipboardError::Empty => {
+            McpError::resource_not_found("The clipboard is empty", Some(details))
+        }
+        ClipboardError::NotText { .. } => {
+            McpError::resource_not_found("The clipboard does not hold text", Some(details))
+        }
+        ClipboardError::Unavailable(_) => {
+            McpError::internal_error("The clipboard is not available", Some(details))
+        }
+    }
+}
+
+/// Converts a failed drag into an MCP error that names the failing stage
+pub fn drag_error(error: crate::drag_drop::DragError) -> McpError {
+    McpError::internal_error(
+        error.to_string(),
+        Some(json!({ "error_type": error.kind(), "reason": error.to_string() })),
+    )
+}
+
+/// Converts a failure to scroll an element into view into an MCP error
+pub fn scroll_error(error: crate::scroll_view::ScrollError) -> McpError {
+    use crate::scroll_view::ScrollError;
+
+    let mut details = json!({ "error_type": error.kind(), "reason": error.to_string() });
+    if let ScrollError::ScrollFailed {
+        scrolls,
+        bounds,
+        viewport,
+        ..
+    } = &error
+    {
+        details["scrolls"] = json!(scrolls);
+        details["bounds"] = json!(bounds);
+        details["viewport"] = json!(viewport);
+    }
+    McpError::internal_error(error.to_string(), Some(details))
+}
+
+/// Converts a monitor lookup failure into an MCP error; a bad index or coordinates
+/// are invalid parameters
+pub fn monitor_error(error: crate::monitors::MonitorError) -> McpError {
+    use crate::monitors::MonitorError;
+
+    let details = json!({ "error_type": error.kind(), "reason": error.to_string() });
+    match error {
+        MonitorError::NotFound { .. } | MonitorError::OutsideMonitor { .. } => {
+            McpError::invalid_params(error.to_string(), Some(details))
+        }
+        MonitorError::NoMonitors => McpError::resource_not_found(error.to_string(), Some(details)),
+        MonitorError::List(_) => McpError::internal_error(error.to_string(), Some(details)),
+    }
+}
+
+/// Converts a failure to read a screen region into an MCP error; a region that is empty
+/// or off-screen is an invalid parameter
+pub fn ocr_region_error(error: crate::ocr_region::OcrRegionError) -> McpError {
+    use crate::ocr_region::OcrRegionError;
+
+    let mut details = json!({ "error_type": error.kind(), "reason": error.to_string() });
+    match &error {
+        OcrRegionError::EmptyRegion(region) | OcrRegionError::OffScreen(region) => {
+            details["region"] = json!(region);
+            McpError::invalid_params(error.to_string(), Some(details))
+        }
+        OcrRegionError::Capture(_) | OcrRegionError::Recognition(_) => {
+            McpError::internal_error(error.to_string(), Some(details))
+        }
+    }
 }
 
 /// Builds a standardized, actionable error when an element cannot be found.
@@ -76,6 +187,11 @@ pub fn build_element_not_found_error(
     fallback: Option<&str>,
     original_error: anyhow::Error,
 ) -> McpError {
+    // Errors other than automation errors here mean no selector matched
+    let error_category = original_error
+        .downcast_ref::<AutomationError>()
+        .map_or(ErrorCategory::User, AutomationError::category);
+
     // Check if the underlying error is UIAutomationAPIError
     if let Some(AutomationError::UIAutomationAPIError {
         message,
@@ -86,8 +202,10 @@ pub fn build_element_not_found_error(
     {
         let error_details = json!({
             "error_type": "ui_automation_api_failure",
+            "error_category": error_category,
             "message": format!("Windows UI Automation API failure: {}", message),
             "com_error": com_error,
+            "hresult": com_error.map(|c| format!("0x{:08X}", c as u32)),
             "operation": operation,
             "is_retryable": is_retryable,
             "selector": primary_selector,
@@ -101,9 +219,24 @@ pub fn build_element_not_found_error(
         return McpError::invalid_params("Windows UI Automation API failure", Some(error_details));
     }
 
+    if let Some(AutomationError::ElementObscured(message)) =
+        original_error.downcast_ref::<AutomationError>()
+    {
+        let error_details = json!({
+            "error_type": "ElementObscured",
+            "error_category": error_category,
+            "message": message,
+            "selector": primary_selector,
+            "suggestion": "Close or move the covering window, or activate the target window before clicking."
+        });
+
+        return McpError::invalid_params("Element obscured", Some(error_details));
+    }
+
     let selectors_tried = get_selectors_tried_all(primary_selector, alternatives, fallback);
     let error_payload = json!({
         "error_type": "ElementNotFound",
+        "error_category": error_category,
         "message": format!("The specified element could not be found after trying all selectors. Original error: {}", original_error),
         "selectors_tried": selectors_tried,
         "suggestions": [
@@ -178,13 +311,21 @@ pub fn substitute_variables(args: &mut Value, variables: &Value) {
                         || inner_str.contains("!=")
                         || inner_str.contains("contains")
                         || inner_str.contains("startsWith")
-                        || inner_str.contains("endsWith");
+                        || inner_str.contains("endsWith")
+                        || inner_str.contains("??");
 
                     if is_expression {
                         debug!("Evaluating expression: '{}'", inner_str);
-                        let eval_result = expression_eval::evaluate(inner_str, variables);
-                        debug!("Expression result: {}", eval_result);
-                        *args = Value::Bool(eval_result);
+                        match expression_eval::evaluate_value(inner_str, variables) {
+                            Ok(eval_result) => {
+                                debug!("Expression result: {}", eval_result);
+                                *args = eval_result;
+                            }
+                            Err(e) => {
+                                // Leave the placeholder so the failure is visible downstream
+                                tracing::warn!("Failed to evaluate '{}': {}", inner_str, e);
+                            }
+                        }
                     }
                     // If it's not a simple variable and not a recognized expression, leave it as is.
                     return;
@@ -232,13 +373,22 @@ pub fn substitute_variables(args: &mut Value, variables: &Value) {
                             || inner_str.contains("!=")
                             || inner_str.contains("contains")
                             || inner_str.contains("startsWith")
-                            || inner_str.contains("endsWith");
+                            || inner_str.contains("endsWith")
+                            || inner_str.contains("??");
 
                         if is_expression {
                             debug!("Evaluating partial expression: '{}'", inner_str);
-                            let bool_val = expression_eval::evaluate(inner_str, variables);
-                            debug!("Expression result: {}", bool_val);
-                            bool_val.to_string()
+                            match expression_eval::evaluate_value(inner_str, variables) {
+                                Ok(Value::String(s)) => s,
+                                Ok(val) => {
+                                    debug!("Expression result: {}", val);
+                                    val.to_string()
+                                }
+                                Err(e) => {
+                                    tracing::warn!("Failed to evaluate '{}': {}", inner_str, e);
+                                    caps.get(0).unwrap().as_str().to_string()
+                                }
+                            }
                         } else {
                             debug!("Unknown placeholder type: '{}'", inner_str);
                             // Not a known expression type, keep original placeholder.
@@ -257,6 +407,184 @@ pub fn substitute_variables(args: &mut Value, variables: &Value) {
     }
 }
 
+/// Outcome of the checks performed before a workflow step runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepGate {
+    /// The step should execute.
+    Run,
+    /// The step's `if` expression evaluated to false.
+    ConditionNotMet,
+    /// The step's `precondition` (its expected post-state) already holds, so running it
+    /// again would be redundant.
+    AlreadySatisfied,
+}
+
+/// Decides whether a step runs, based on its `if` gate and its `precondition`.
+///
+/// The `if` expression is checked first; `always()` always passes. A step whose
+/// precondition is already true is skipped as satisfied, which keeps re-runs idempotent.
+/// Both are evaluated against the same index of the step's variables.
+pub fn evaluate_step_gate(
+    if_expr: Option<&str>,
+    precondition: Option<&str>,
+    variables: &IndexedContext,
+) -> StepGate {
+    if let Some(cond) = if_expr {
+        if cond.trim() != "always()" && !expression_eval::evaluate_indexed(cond, variables) {
+            return StepGate::ConditionNotMet;
+        }
+    }
+    if let Some(pre) = precondition {
+        if expression_eval::evaluate_indexed(pre, variables) {
+            return StepGate::AlreadySatisfied;
+        }
+    }
+    StepGate::Run
+}
+
+/// Checks a step's `verify` post-condition after its tool succeeded.
+///
+/// Returns the failed result to report instead of `result` when the expression does not
+/// hold, so the step is retried and then handled like any other failed action. Steps
+/// that continue on error report the failure as `skipped`. When the expression could not
+/// be evaluated at all, the error says why.
+pub fn verify_step(
+    verify: &str,
+    result: &Value,
+    variables: &Value,
+    skippable: bool,
+) -> Option<Value> {
+    if expression_eval::evaluate(verify, variables) {
+        return None;
+    }
+    let mut failed = if result.is_object() {
+        result.clone()
+    } else {
+        json!({ "result": result })
+    };
+    failed["status"] = json!(if skippable { "skipped" } else { "error" });
+    failed["error"] = match expression_eval::evaluate_checked(verify, variables) {
+        Err(e) => json!(format!("Post-condition not met: {verify} ({e})")),
+        Ok(_) => json!(format!("Post-condition not met: {verify}")),
+    };
+    failed["verification_failed"] = json!(true);
+    Some(failed)
+}
+
+/// Merges a script step's outputs into `env`.
+///
+/// An output replaces an existing variable of the same name, since the step ran later.
+/// Within one result, fields the script returned directly win over its `set_env`.
+/// With a `namespace` (the step id of a step with `namespace_outputs`), the outputs are
+/// merged into the object `env.<namespace>` instead and top-level variables are left
+/// alone; a non-object value already stored under that name is replaced.
+///
+/// Returns the variables written, with their previous values, keyed `namespace.name`
+/// when namespaced.
+pub fn merge_step_outputs(
+    env: &mut serde_json::Map<String, Value>,
+    outputs: serde_json::Map<String, Value>,
+    namespace: Option<&str>,
+) -> Vec<VariableChange> {
+    let (target, prefix) = match namespace {
+        None => (env, String::new()),
+        Some(namespace) => {
+            let slot = env
+                .entry(namespace.to_string())
+                .or_insert_with(|| json!({}));
+            if !slot.is_object() {
+                *slot = json!({});
+            }
+            match slot.as_object_mut() {
+                Some(namespaced) => (namespaced, format!("{namespace}.")),
+                None => return Vec::new(),
+            }
+        }
+    };
+    outputs
+        .into_iter()
+        .map(|(name, value)| VariableChange {
+            key: format!("{prefix}{name}"),
+            old_value: target.insert(name, value.clone()),
+            new_value: value,
+        })
+        .collect()
+}
+
+/// Whether a tool or step result reports success: a `status` of `success`, a `success`
+/// of `true`, or neither field saying otherwise.
+pub fn step_succeeded(result: &Value) -> bool {
+    result["status"] == "success"
+        || result["success"] == true
+        || (result["status"].is_null() && result["success"] != false)
+}
+
+/// Collects the env updates a successful `run_command` or `execute_browser_script`
+/// result carries: explicit `set_env`/`env` objects first, then any other
+/// non-reserved fields of the script's result object.
+pub fn env_updates_from_result(tool_name: &str, result: &Value) -> serde_json::Map<String, Value> {
+    const RESERVED_KEYS: &[&str] = &["status", "error", "logs", "duration_ms", "set_env"];
+
+    let mut updates = serde_json::Map::new();
+    if !step_succeeded(result) {
+        return updates;
+    }
+    let mut merge = |update: Option<&Value>| {
+        if let Some(map) = update.and_then(|v| v.as_object()) {
+            updates.extend(map.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+    };
+    let content = result
+        .get("result")
+        .and_then(|r| r.get("content"))
+        .and_then(|c| c.as_array());
+
+    let tool_name = tool_name
+        .strip_prefix("mcp_terminator-mcp-agent_")
+        .unwrap_or(tool_name);
+    match tool_name {
+        "execute_browser_script" => {
+            let parsed = content
+                .and_then(|arr| arr.first())
+                .and_then(|item| item.get("result"))
+                .and_then(|r| r.as_str())
+                .and_then(|s| serde_json::from_str::<Value>(s).ok());
+            if let Some(parsed) = parsed {
+                merge(parsed.get("set_env"));
+                let fields: serde_json::Map<String, Value> = parsed
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .filter(|(k, _)| !RESERVED_KEYS.contains(&k.as_str()))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                merge(Some(&Value::Object(fields)));
+            }
+        }
+        "run_command" => {
+            for item in content.into_iter().flatten() {
+                if let Some(res) = item.get("result") {
+                    merge(res.get("set_env").or_else(|| res.get("env")));
+                }
+                merge(item.get("set_env").or_else(|| item.get("env")));
+            }
+            for item in content.into_iter().flatten() {
+                let fields: serde_json::Map<String, Value> = item
+                    .get("result")
+                    .and_then(|r| r.as_object())
+                    .into_iter()
+                    .flatten()
+                    .filter(|(k, _)| !RESERVED_KEYS.contains(&k.as_str()))
+                    .map(|(k, v)| (k.clone(), v.clone()))
+                    .collect();
+                merge(Some(&Value::Object(fields)));
+            }
+        }
+        _ => {}
+    }
+    updates
+}
+
 /// Waits for a detectable UI change after an action, like an element disappearing or focus shifting.
 /// This is more efficient than a fixed sleep, as it returns as soon as a change is detected.
 pub async fn wait_for_ui_change(
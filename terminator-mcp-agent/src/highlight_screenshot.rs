@@ -0,0 +1,206 @@
+//! Screenshots with an element outlined, behind the `capture_highlighted_screenshot` tool.
+//!
+//! The monitor holding most of the element is captured and a rectangle is drawn over the
+//! element's bounding box. Element bounds are in desktop coordinates, so on a
+//! multi-monitor setup they are shifted by the monitor's position; when the capture's
+//! size differs from the monitor's (display scaling), they are scaled to match. Parts of
+//! the element outside the captured monitor are left out of the rectangle and reported
+//! through `clipped`; an element entirely off-screen is an error.
+
+use image::{Rgba, RgbaImage};
+use serde::Serialize;
+use std::future::Future;
+use terminator::{Monitor, UIElement};
+
+/// Default rectangle thickness in pixels
+pub const DEFAULT_BORDER_WIDTH: u32 = 3;
+
+/// Default rectangle color, as a BGR code like `highlight_element` takes (red)
+pub const DEFAULT_COLOR_BGR: u32 = 0x0000FF;
+
+/// Anything with a bounding box in desktop coordinates
+pub trait BoundsSource {
+    /// `(x, y, width, height)`
+    fn bounds(&self) -> Result<(f64, f64, f64, f64), String>;
+}
+
+impl BoundsSource for UIElement {
+    fn bounds(&self) -> Result<(f64, f64, f64, f64), String> {
+        self.bounds().map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// A rectangle in the captured image, in pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HighlightStyle {
+    pub color_bgr: u32,
+    pub border_width: u32,
+}
+
+impl Default for HighlightStyle {
+    fn default() -> Self {
+        Self {
+            color_bgr: DEFAULT_COLOR_BGR,
+            border_width: DEFAULT_BORDER_WIDTH,
+        }
+    }
+}
+
+impl HighlightStyle {
+    fn rgba(&self) -> Rgba<u8> {
+        // 0x00BBGGRR, like a Windows COLORREF
+        let red = self.color_bgr as u8;
+        let green = (self.color_bgr >> 8) as u8;
+        let blue = (self.color_bgr >> 16) as u8;
+        Rgba([red, green, blue, 255])
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct HighlightedScreenshot {
+    pub image: RgbaImage,
+    /// The element's bounding box, in desktop coordinates
+    pub bounds: Rect,
+    pub monitor: Monitor,
+    /// The visible part of the element in the image, where the rectangle was drawn
+    pub highlight: PixelRect,
+    /// Part of the element lies outside the captured monitor
+    pub clipped: bool,
+}
+
+/// Captures the monitor showing most of `element` with `capture` and outlines the element
+pub async fn capture_highlighted<S, F, Fut>(
+    element: &S,
+    monitors: &[Monitor],
+    capture: F,
+    style: &HighlightStyle,
+) -> Result<HighlightedScreenshot, String>
+where
+    S: BoundsSource,
+    F: FnOnce(Monitor) -> Fut,
+    Fut: Future<Output = Result<RgbaImage, String>>,
+{
+    let (x, y, width, height) = element.bounds()?;
+    if !(width > 0.0 && height > 0.0) {
+        return Err(format!(
+            "Element has an empty bounding box ({x}, {y}, {width}x{height})"
+        ));
+    }
+    let bounds = Rect {
+        x,
+        y,
+        width,
+        height,
+    };
+    let monitor = monitor_showing(&bounds, monitors)
+        .ok_or_else(|| format!("Element is entirely off-screen at ({x}, {y}, {width}x{height})"))?
+        .clone();
+
+    let mut image = capture(monitor.clone()).await?;
+    let (highlight, clipped) = draw_highlight(&mut image, &monitor, &bounds, style)
+        .ok_or_else(|| "Element is not visible in the monitor capture".to_string())?;
+    Ok(HighlightedScreenshot {
+        image,
+        bounds,
+        monitor,
+        highlight,
+        clipped,
+    })
+}
+
+/// The monitor with the largest share of `bounds`, preferring the earlier one on ties
+pub fn monitor_showing<'a>(bounds: &Rect, monitors: &'a [Monitor]) -> Option<&'a Monitor> {
+    let mut best: Option<(&Monitor, f64)> = None;
+    for monitor in monitors {
+        let overlap_x = (bounds.x + bounds.width).min(monitor.x as f64 + monitor.width as f64)
+            - bounds.x.max(monitor.x as f64);
+        let overlap_y = (bounds.y + bounds.height).min(monitor.y as f64 + monitor.height as f64)
+            - bounds.y.max(monitor.y as f64);
+        if overlap_x <= 0.0 || overlap_y <= 0.0 {
+            continue;
+        }
+        let area = overlap_x * overlap_y;
+        if best.is_none_or(|(_, best_area)| area > best_area) {
+            best = Some((monitor, area));
+        }
+    }
+    best.map(|(monitor, _)| monitor)
+}
+
+/// Draws the outline of `bounds` onto a capture of `monitor`. Returns the visible part
+/// of the element in image pixels and whether any of it was cut off, or `None` when
+/// none of it is in the image.
+pub fn draw_highlight(
+    image: &mut RgbaImage,
+    monitor: &Monitor,
+    bounds: &Rect,
+    style: &HighlightStyle,
+) -> Option<(PixelRect, bool)> {
+    let (image_width, image_height) = image.dimensions();
+    let scale_x = if monitor.width > 0 {
+        image_width as f64 / monitor.width as f64
+    } else {
+        1.0
+    };
+    let scale_y = if monitor.height > 0 {
+        image_height as f64 / monitor.height as f64
+    } else {
+        1.0
+    };
+
+    // Element edges in image pixels, possibly outside the image
+    let left = ((bounds.x - monitor.x as f64) * scale_x).round() as i64;
+    let top = ((bounds.y - monitor.y as f64) * scale_y).round() as i64;
+    let right = ((bounds.x + bounds.width - monitor.x as f64) * scale_x).round() as i64;
+    let bottom = ((bounds.y + bounds.height - monitor.y as f64) * scale_y).round() as i64;
+
+    let visible_left = left.clamp(0, image_width as i64);
+    let visible_top = top.clamp(0, image_height as i64);
+    let visible_right = right.clamp(0, image_width as i64);
+    let visible_bottom = bottom.clamp(0, image_height as i64);
+    if visible_left >= visible_right || visible_top >= visible_bottom {
+        return None;
+    }
+    let clipped =
+        (visible_left, visible_top, visible_right, visible_bottom) != (left, top, right, bottom);
+
+    // Edges outside the image fall on pixels that are not drawn, so an element cut off
+    // by the screen edge gets an open rectangle
+    let border = style.border_width.max(1) as i64;
+    let color = style.rgba();
+    for py in visible_top..visible_bottom {
+        for px in visible_left..visible_right {
+            let on_border = px - left < border
+                || right - 1 - px < border
+                || py - top < border
+                || bottom - 1 - py < border;
+            if on_border {
+                image.put_pixel(px as u32, py as u32, color);
+            }
+        }
+    }
+
+    let highlight = PixelRect {
+        x: visible_left as u32,
+        y: visible_top as u32,
+        width: (visible_right - visible_left) as u32,
+        height: (visible_bottom - visible_top) as u32,
+    };
+    Some((highlight, clipped))
+}
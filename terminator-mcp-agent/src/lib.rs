@@ -1,20 +1,54 @@
+pub mod action_journal;
 pub mod cancellation;
+pub mod capabilities;
+pub mod clipboard;
+pub mod compression;
+pub mod drag_drop;
 pub mod duration_parser;
+pub mod element_ref;
+#[cfg(feature = "embedded-python")]
+pub mod embedded_python;
 pub mod expression_eval;
+pub mod find_elements;
+pub mod focused_input;
+pub mod form_check;
 pub mod helpers;
+pub mod highlight_screenshot;
 pub mod log_capture;
 pub mod mcp_converter;
 pub mod mcp_types;
+pub mod menu_reader;
+pub mod middleware;
+pub mod modal_watcher;
+pub mod monitors;
+pub mod ocr_region;
+pub mod on_error;
 pub mod output_parser;
+pub mod parallel_group;
 pub mod prompt;
+pub mod redaction;
+pub mod repeat_loop;
+pub mod retry_backoff;
+pub mod run_bundle;
+pub mod safe_mode;
 pub mod scripting_engine;
+pub mod scroll_view;
+pub mod selector_resolution;
 pub mod server;
 pub mod server_sequence;
 pub mod server_workflow_files;
+pub mod step_timeout;
 pub mod telemetry;
+pub mod tree_formatter;
 pub mod utils;
+pub mod variable_scope;
+pub mod variable_store;
+pub mod visual_assert;
+pub mod wait_predicate;
 pub mod workflow_converter;
 pub mod workflow_events;
+pub mod workflow_markdown;
+pub mod workflow_plan;
 
 // Re-export the extract_content_json function for testing
 pub use server::extract_content_json;
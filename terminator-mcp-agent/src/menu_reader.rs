@@ -0,0 +1,152 @@
+//! Reading a menu's item hierarchy behind the `read_menu` tool.
+//!
+//! Items are read from the accessibility tree without invoking them. A submenu whose
+//! items are already exposed is read as is; a collapsed one is expanded through its
+//! expand/collapse pattern, read, and collapsed again, so every menu ends in the state
+//! it started in. Containers between items (the `Menu` holding a submenu's items, groups)
+//! are not reported: their items become the children of the enclosing item.
+
+use serde::Serialize;
+use std::time::Duration;
+use terminator::UIElement;
+
+/// Default number of submenu levels read below the starting element
+pub const DEFAULT_MAX_DEPTH: usize = 5;
+
+/// How long an expanded submenu is given to populate its items
+const EXPAND_SETTLE: Duration = Duration::from_millis(150);
+
+/// An element of a menu, as seen by [`read_menu`]
+pub trait MenuNode: Sized {
+    fn name(&self) -> String;
+    fn role(&self) -> String;
+    fn is_enabled(&self) -> bool;
+    fn children(&self) -> Vec<Self>;
+    /// `None` when the element cannot be expanded
+    fn is_expanded(&self) -> Option<bool>;
+    fn set_expanded(&self, expanded: bool) -> Result<(), String>;
+}
+
+impl MenuNode for UIElement {
+    fn name(&self) -> String {
+        self.name().unwrap_or_default()
+    }
+
+    fn role(&self) -> String {
+        self.role()
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.is_enabled().unwrap_or(true)
+    }
+
+    fn children(&self) -> Vec<Self> {
+        self.children().unwrap_or_default()
+    }
+
+    fn is_expanded(&self) -> Option<bool> {
+        self.is_expanded().ok().flatten()
+    }
+
+    fn set_expanded(&self, expanded: bool) -> Result<(), String> {
+        self.set_expanded(expanded).map_err(|e| e.to_string())?;
+        if expanded {
+            std::thread::sleep(EXPAND_SETTLE);
+        }
+        Ok(())
+    }
+}
+
+/// One menu item and its submenu
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MenuItem {
+    pub name: String,
+    pub role: String,
+    pub enabled: bool,
+    /// The item opens a submenu, even when its items could not be read
+    pub has_submenu: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<MenuItem>,
+}
+
+/// The result of reading a menu
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MenuRead {
+    pub menu: MenuItem,
+    /// Number of submenus that had to be expanded to be read
+    pub expanded: usize,
+    /// Submenus that could not be expanded or collapsed again
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Reads the menu rooted at `root` down to `max_depth` submenu levels.
+pub fn read_menu<N: MenuNode>(root: &N, max_depth: usize) -> MenuRead {
+    let mut reader = Reader {
+        max_depth,
+        expanded: 0,
+        errors: Vec::new(),
+    };
+    let menu = reader.item(root, 0);
+    MenuRead {
+        menu,
+        expanded: reader.expanded,
+        errors: reader.errors,
+    }
+}
+
+fn is_menu_item(role: &str) -> bool {
+    role.to_ascii_lowercase()
+        .replace(['_', ' '], "")
+        .contains("menuitem")
+}
+
+struct Reader {
+    max_depth: usize,
+    expanded: usize,
+    errors: Vec<String>,
+}
+
+impl Reader {
+    fn item<N: MenuNode>(&mut self, node: &N, depth: usize) -> MenuItem {
+        let name = node.name();
+        let expandable = node.is_expanded();
+        let mut children = Vec::new();
+        if depth < self.max_depth {
+            let opened = expandable == Some(false) && node.children().is_empty();
+            if opened {
+                match node.set_expanded(true) {
+                    Ok(()) => self.expanded += 1,
+                    Err(e) => self.errors.push(format!("Failed to expand '{name}': {e}")),
+                }
+            }
+            for child in node.children() {
+                self.collect(&child, depth + 1, &mut children);
+            }
+            if opened && node.is_expanded() == Some(true) {
+                if let Err(e) = node.set_expanded(false) {
+                    self.errors
+                        .push(format!("Failed to collapse '{name}': {e}"));
+                }
+            }
+        }
+        MenuItem {
+            role: node.role(),
+            enabled: node.is_enabled(),
+            has_submenu: expandable.is_some() || !children.is_empty(),
+            name,
+            children,
+        }
+    }
+
+    // Adds the menu items at or below `node`, looking through containers
+    fn collect<N: MenuNode>(&mut self, node: &N, depth: usize, items: &mut Vec<MenuItem>) {
+        if is_menu_item(&node.role()) {
+            items.push(self.item(node, depth));
+        } else {
+            for child in node.children() {
+                self.collect(&child, depth, items);
+            }
+        }
+    }
+}
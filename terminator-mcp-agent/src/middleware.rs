@@ -0,0 +1,311 @@
+//! Middleware chain wrapped around every tool call.
+//!
+//! Each action goes through the chain before it is dispatched: every middleware's
+//! `before` hook runs in order, and any of them can short-circuit the call by returning
+//! an error, which becomes the call's result. Once the tool has run (or was blocked),
+//! the `after` hooks of the middlewares whose `before` ran are called in reverse order
+//! and may inspect or rewrite the result.
+//!
+//! The server's standard chain is logging, safe mode and latency tracking. Embedders can
+//! append their own middleware with `DesktopWrapper::with_middleware` to add screenshots,
+//! rate limiting, confirmation prompts and the like without touching the dispatcher.
+
+use crate::redaction::Redactor;
+use rmcp::model::CallToolResult;
+use rmcp::ErrorData as McpError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{File, OpenOptions};
+use std::future::Future;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tracing::{debug, warn};
+
+/// File the logging middleware appends JSON-lines tool records to, when set
+pub const TOOL_LOG_JSONL_ENV: &str = "TERMINATOR_TOOL_LOG_JSONL";
+
+/// A tool call going through the middleware chain
+#[derive(Debug, Clone)]
+pub struct Action<'a> {
+    pub tool_name: &'a str,
+    pub arguments: &'a Value,
+    /// `true` for steps dispatched from inside `execute_sequence`
+    pub nested: bool,
+    pub started: Instant,
+}
+
+impl<'a> Action<'a> {
+    pub fn new(tool_name: &'a str, arguments: &'a Value) -> Self {
+        Self {
+            tool_name,
+            arguments,
+            nested: false,
+            started: Instant::now(),
+        }
+    }
+
+    /// Marks the action as a step of a sequence rather than a direct client call.
+    pub fn nested(mut self) -> Self {
+        self.nested = true;
+        self
+    }
+}
+
+/// Hooks run around each dispatched action
+pub trait ActionMiddleware: Send + Sync {
+    /// Short name used in log messages
+    fn name(&self) -> &str;
+
+    /// Runs before the tool. Returning an error blocks the call.
+    fn before(&self, _action: &Action<'_>) -> Result<(), McpError> {
+        Ok(())
+    }
+
+    /// Runs after the tool, or after a later middleware blocked the call.
+    fn after(&self, _action: &Action<'_>, _result: &mut Result<CallToolResult, McpError>) {}
+}
+
+/// An ordered list of middleware
+#[derive(Clone, Default)]
+pub struct MiddlewareChain {
+    middlewares: Vec<Arc<dyn ActionMiddleware>>,
+}
+
+impl std::fmt::Debug for MiddlewareChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.middlewares.iter().map(|m| m.name()))
+            .finish()
+    }
+}
+
+impl MiddlewareChain {
+    /// The chain the server starts with: logging, safe mode, latency tracking
+    pub fn standard() -> Self {
+        Self::default()
+            .with(Arc::new(LoggingMiddleware::default()))
+            .with(Arc::new(SafeModeMiddleware))
+            .with(Arc::new(LatencyMiddleware))
+    }
+
+    /// Appends a middleware to the end of the chain.
+    pub fn with(mut self, middleware: Arc<dyn ActionMiddleware>) -> Self {
+        self.middlewares.push(middleware);
+        self
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.middlewares.iter().map(|m| m.name()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.middlewares.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.middlewares.is_empty()
+    }
+
+    /// Runs `call` wrapped in the chain's hooks.
+    pub async fn run<F, Fut>(&self, action: Action<'_>, call: F) -> Result<CallToolResult, McpError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<CallToolResult, McpError>>,
+    {
+        let mut entered = 0;
+        let mut blocked = None;
+        for middleware in &self.middlewares {
+            entered += 1;
+            if let Err(e) = middleware.before(&action) {
+                debug!(
+                    "middleware '{}' blocked '{}'",
+                    middleware.name(),
+                    action.tool_name
+                );
+                blocked = Some(e);
+                break;
+            }
+        }
+
+        let mut result = match blocked {
+            Some(e) => Err(e),
+            None => call().await,
+        };
+        for middleware in self.middlewares[..entered].iter().rev() {
+            middleware.after(&action, &mut result);
+        }
+        result
+    }
+}
+
+/// One tool call in the JSON-lines tool log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolLogRecord {
+    /// When the call finished, RFC 3339
+    pub timestamp: String,
+    pub tool: String,
+    /// The arguments, redacted
+    pub args: Value,
+    pub duration_ms: u64,
+    /// `success` or `error`
+    pub status: String,
+    /// `true` for steps dispatched from inside `execute_sequence`
+    pub nested: bool,
+}
+
+/// Appends tool log records to a file, one JSON object per line
+///
+/// Each record is written with a single `write_all` under a lock on a file opened in
+/// append mode, so lines from concurrent calls never interleave.
+#[derive(Debug)]
+pub struct JsonlSink {
+    file: Mutex<File>,
+}
+
+impl JsonlSink {
+    /// Opens `path` for appending, creating it if needed
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn write(&self, record: &ToolLogRecord) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        let mut file = self
+            .file
+            .lock()
+            .map_err(|_| io::Error::other("tool log file lock poisoned"))?;
+        file.write_all(&line)
+    }
+}
+
+/// Logs the start and outcome of every action
+///
+/// Arguments are logged with sensitive values masked, see [`crate::redaction`]. With a
+/// [`JsonlSink`], every call is also written as a [`ToolLogRecord`].
+pub struct LoggingMiddleware {
+    redactor: Redactor,
+    jsonl: Option<JsonlSink>,
+}
+
+impl Default for LoggingMiddleware {
+    /// Uses the process-wide redactor and the JSON-lines file in
+    /// `TERMINATOR_TOOL_LOG_JSONL`, if set
+    fn default() -> Self {
+        let middleware = Self::new(crate::redaction::global().clone());
+        match std::env::var(TOOL_LOG_JSONL_ENV) {
+            Ok(path) if !path.trim().is_empty() => match JsonlSink::open(path.trim()) {
+                Ok(sink) => middleware.with_jsonl(sink),
+                Err(e) => {
+                    warn!("Cannot open tool log '{}': {}", path, e);
+                    middleware
+                }
+            },
+            _ => middleware,
+        }
+    }
+}
+
+impl LoggingMiddleware {
+    pub fn new(redactor: Redactor) -> Self {
+        Self {
+            redactor,
+            jsonl: None,
+        }
+    }
+
+    /// Also writes every call to `sink`
+    pub fn with_jsonl(mut self, sink: JsonlSink) -> Self {
+        self.jsonl = Some(sink);
+        self
+    }
+
+    /// The arguments as they appear in the log
+    pub fn loggable_arguments(&self, action: &Action<'_>) -> Value {
+        self.redactor.redact(action.arguments)
+    }
+}
+
+impl ActionMiddleware for LoggingMiddleware {
+    fn name(&self) -> &str {
+        "logging"
+    }
+
+    fn before(&self, action: &Action<'_>) -> Result<(), McpError> {
+        debug!(
+            "tool call '{}' started (nested: {}) with arguments {}",
+            action.tool_name,
+            action.nested,
+            self.loggable_arguments(action)
+        );
+        Ok(())
+    }
+
+    fn after(&self, action: &Action<'_>, result: &mut Result<CallToolResult, McpError>) {
+        let elapsed_ms = action.started.elapsed().as_millis();
+        match result {
+            Ok(_) => debug!(
+                "tool call '{}' finished in {elapsed_ms}ms",
+                action.tool_name
+            ),
+            Err(e) => debug!(
+                "tool call '{}' failed after {elapsed_ms}ms: {}",
+                action.tool_name, e.message
+            ),
+        }
+
+        if let Some(sink) = &self.jsonl {
+            let failed = match result {
+                Ok(result) => result.is_error == Some(true),
+                Err(_) => true,
+            };
+            let record = ToolLogRecord {
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                tool: action.tool_name.to_string(),
+                args: self.loggable_arguments(action),
+                duration_ms: elapsed_ms as u64,
+                status: if failed { "error" } else { "success" }.to_string(),
+                nested: action.nested,
+            };
+            if let Err(e) = sink.write(&record) {
+                warn!("Failed to write tool log record: {}", e);
+            }
+        }
+    }
+}
+
+/// Rejects unconfirmed destructive actions, see [`crate::safe_mode`]
+pub struct SafeModeMiddleware;
+
+impl ActionMiddleware for SafeModeMiddleware {
+    fn name(&self) -> &str {
+        "safe_mode"
+    }
+
+    fn before(&self, action: &Action<'_>) -> Result<(), McpError> {
+        crate::safe_mode::global().check(action.tool_name, action.arguments)?;
+        Ok(())
+    }
+}
+
+/// Feeds client tool calls into the latency tracker
+///
+/// Sequence steps are skipped because their step spans already record them.
+pub struct LatencyMiddleware;
+
+impl ActionMiddleware for LatencyMiddleware {
+    fn name(&self) -> &str {
+        "latency"
+    }
+
+    fn after(&self, action: &Action<'_>, _result: &mut Result<CallToolResult, McpError>) {
+        if !action.nested {
+            crate::telemetry::record_tool_latency(action.tool_name, action.started.elapsed());
+        }
+    }
+}
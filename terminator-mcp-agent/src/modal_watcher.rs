@@ -0,0 +1,264 @@
+//! Background watcher that dismisses unexpected modal dialogs during a workflow run.
+//!
+//! Update prompts and error popups tend to appear at arbitrary points and derail a
+//! sequence. When a workflow declares `modal_watcher` patterns, the sequence runner spawns
+//! this watcher alongside the steps; it polls for dialogs whose title matches a pattern,
+//! clicks the first configured button that exists, and emits a `ModalDismissed` event.
+
+use crate::workflow_events::{ExecutionEvent, ExecutionEventEmitter};
+use regex::{Regex, RegexBuilder};
+use rmcp::{schemars, schemars::JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
+use terminator::{AutomationError, Desktop, UIElement};
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
+
+const DEFAULT_POLL_INTERVAL_MS: u64 = 500;
+const BUTTON_SEARCH_DEPTH: usize = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ModalPattern {
+    #[schemars(
+        description = "Regex matched case-insensitively against the dialog title, e.g. 'update available|error'"
+    )]
+    pub title: String,
+    #[schemars(
+        description = "Names of the buttons that dismiss the dialog, tried in order (e.g. ['Later', 'OK'])"
+    )]
+    pub buttons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ModalWatcherConfig {
+    #[schemars(description = "Dialog patterns to dismiss automatically while the workflow runs")]
+    pub patterns: Vec<ModalPattern>,
+    #[schemars(
+        description = "How often to look for dialogs. Supports human-readable durations like '500ms' or '1s'. Defaults to 500ms."
+    )]
+    pub poll_interval: Option<String>,
+}
+
+/// Abstraction over where dialogs come from, so the watcher can be driven by the live
+/// desktop in production and by a scripted source in tests.
+pub trait DialogSource: Send + Sync + 'static {
+    type Dialog: Send;
+
+    /// Returns the dialogs that are currently open
+    fn open_dialogs(&self) -> Result<Vec<Self::Dialog>, AutomationError>;
+
+    /// Returns the title used for pattern matching
+    fn dialog_title(&self, dialog: &Self::Dialog) -> String;
+
+    /// Clicks the named button inside the dialog. Returns `Ok(false)` when the dialog
+    /// has no button with that name.
+    fn click_button(&self, dialog: &Self::Dialog, button: &str) -> Result<bool, AutomationError>;
+}
+
+struct CompiledPattern {
+    title: Regex,
+    buttons: Vec<String>,
+}
+
+fn compile_patterns(patterns: &[ModalPattern]) -> Result<Vec<CompiledPattern>, String> {
+    patterns
+        .iter()
+        .map(|p| {
+            let title = RegexBuilder::new(&p.title)
+                .case_insensitive(true)
+                .build()
+                .map_err(|e| format!("Invalid modal title pattern '{}': {e}", p.title))?;
+            Ok(CompiledPattern {
+                title,
+                buttons: p.buttons.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Performs a single scan, dismissing every matching dialog.
+/// Returns the `(title, button)` pairs that were dismissed.
+fn dismiss_matching<S: DialogSource>(
+    source: &S,
+    patterns: &[CompiledPattern],
+) -> Vec<(String, String)> {
+    let dialogs = match source.open_dialogs() {
+        Ok(dialogs) => dialogs,
+        Err(e) => {
+            debug!("[modal_watcher] Failed to enumerate dialogs: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut dismissed = Vec::new();
+    for dialog in dialogs {
+        let title = source.dialog_title(&dialog);
+        let Some(pattern) = patterns.iter().find(|p| p.title.is_match(&title)) else {
+            continue;
+        };
+
+        for button in &pattern.buttons {
+            match source.click_button(&dialog, button) {
+                Ok(true) => {
+                    info!("[modal_watcher] Dismissed '{}' via '{}'", title, button);
+                    dismissed.push((title.clone(), button.clone()));
+                    break;
+                }
+                Ok(false) => continue,
+                Err(e) => {
+                    warn!(
+                        "[modal_watcher] Clicking '{}' on '{}' failed: {}",
+                        button, title, e
+                    );
+                }
+            }
+        }
+    }
+    dismissed
+}
+
+/// Handle to a running watcher. Dropping it stops the watcher.
+pub struct ModalWatcherHandle {
+    token: CancellationToken,
+    task: Option<JoinHandle<usize>>,
+}
+
+impl ModalWatcherHandle {
+    /// Stops the watcher and returns how many dialogs it dismissed
+    pub async fn stop(mut self) -> usize {
+        self.token.cancel();
+        match self.task.take() {
+            Some(task) => task.await.unwrap_or(0),
+            None => 0,
+        }
+    }
+}
+
+impl Drop for ModalWatcherHandle {
+    fn drop(&mut self) {
+        self.token.cancel();
+    }
+}
+
+/// Spawns a watcher scoped to `parent`: it stops when the handle is stopped/dropped or
+/// when the parent token (typically the request's cancellation token) is cancelled.
+pub fn spawn_modal_watcher<S: DialogSource>(
+    source: Arc<S>,
+    config: &ModalWatcherConfig,
+    emitter: ExecutionEventEmitter,
+    parent: &CancellationToken,
+) -> Result<ModalWatcherHandle, String> {
+    let patterns = Arc::new(compile_patterns(&config.patterns)?);
+    let poll_interval = match &config.poll_interval {
+        Some(s) => crate::duration_parser::parse_std_duration(s)
+            .map_err(|e| format!("Invalid modal_watcher poll_interval '{s}': {e}"))?,
+        None => Duration::from_millis(DEFAULT_POLL_INTERVAL_MS),
+    };
+    let poll_interval = poll_interval.max(Duration::from_micros(1));
+
+    let token = parent.child_token();
+    let task_token = token.clone();
+    let task = tokio::spawn(async move {
+        let mut total = 0usize;
+        loop {
+            tokio::select! {
+                _ = task_token.cancelled() => break,
+                _ = tokio::time::sleep(poll_interval) => {}
+            }
+
+            let source = source.clone();
+            let patterns = patterns.clone();
+            // Dialog enumeration hits the accessibility API, which blocks
+            let dismissed =
+                tokio::task::spawn_blocking(move || dismiss_matching(source.as_ref(), &patterns))
+                    .await
+                    .unwrap_or_default();
+
+            for (title, button) in dismissed {
+                total += 1;
+                emitter.emit(ExecutionEvent::ModalDismissed {
+                    title,
+                    button,
+                    timestamp: chrono::Utc::now().to_rfc3339(),
+                });
+            }
+        }
+        total
+    });
+
+    Ok(ModalWatcherHandle {
+        token,
+        task: Some(task),
+    })
+}
+
+/// Dialog source backed by the live desktop accessibility tree.
+pub struct DesktopDialogSource {
+    desktop: Arc<Desktop>,
+}
+
+impl DesktopDialogSource {
+    pub fn new(desktop: Arc<Desktop>) -> Self {
+        Self { desktop }
+    }
+}
+
+fn is_dialog_role(role: &str) -> bool {
+    matches!(
+        role.to_lowercase().as_str(),
+        "window" | "dialog" | "axsheet"
+    )
+}
+
+fn find_button(element: &UIElement, name: &str, depth: usize) -> Option<UIElement> {
+    let children = element.children().ok()?;
+    for child in children {
+        if child.role().to_lowercase().contains("button")
+            && child
+                .name()
+                .is_some_and(|n| n.trim().eq_ignore_ascii_case(name))
+        {
+            return Some(child);
+        }
+        if depth > 0 {
+            if let Some(found) = find_button(&child, name, depth - 1) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+impl DialogSource for DesktopDialogSource {
+    type Dialog = UIElement;
+
+    fn open_dialogs(&self) -> Result<Vec<UIElement>, AutomationError> {
+        let mut dialogs = Vec::new();
+        for window in self.desktop.root().children()? {
+            // Owned modal dialogs are usually exposed as child windows of their owner
+            if let Ok(children) = window.children() {
+                dialogs.extend(children.into_iter().filter(|c| is_dialog_role(&c.role())));
+            }
+            if is_dialog_role(&window.role()) {
+                dialogs.push(window);
+            }
+        }
+        Ok(dialogs)
+    }
+
+    fn dialog_title(&self, dialog: &UIElement) -> String {
+        dialog.name().unwrap_or_default()
+    }
+
+    fn click_button(&self, dialog: &UIElement, button: &str) -> Result<bool, AutomationError> {
+        match find_button(dialog, button, BUTTON_SEARCH_DEPTH) {
+            Some(element) => {
+                element.click()?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Monitor enumeration and monitor-relative coordinates.
+//!
+//! Monitors are numbered left to right, then top to bottom, so an index stays the same
+//! between calls as long as the display layout does. Tools that take coordinates accept
+//! an optional `monitor` index; the coordinates are then relative to that monitor's
+//! top-left corner, in the same units as its `width` and `height`, and must fall inside
+//! it. `scale_factor` and the logical size are reported so callers working in
+//! DPI-independent units can convert. Monitors come from a [`MonitorProvider`] so the
+//! logic can be tested without displays.
+
+use serde::Serialize;
+use std::future::Future;
+use terminator::{AutomationError, Desktop, Monitor, WorkAreaBounds};
+
+/// Something that can list the connected monitors
+pub trait MonitorProvider {
+    fn monitors(&self) -> impl Future<Output = Result<Vec<Monitor>, AutomationError>>;
+}
+
+impl MonitorProvider for Desktop {
+    fn monitors(&self) -> impl Future<Output = Result<Vec<Monitor>, AutomationError>> {
+        self.list_monitors()
+    }
+}
+
+/// A monitor as reported by `list_monitors`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MonitorInfo {
+    pub index: usize,
+    pub id: String,
+    pub name: String,
+    pub is_primary: bool,
+    /// Top-left corner and size in desktop coordinates
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    /// Size in DPI-independent units, `width` and `height` divided by `scale_factor`
+    pub logical_width: f64,
+    pub logical_height: f64,
+    pub work_area: Option<WorkAreaBounds>,
+}
+
+impl MonitorInfo {
+    fn new(index: usize, monitor: &Monitor) -> Self {
+        // A missing or bogus scale factor is treated as 100%
+        let scale = if monitor.scale_factor > 0.0 {
+            monitor.scale_factor
+        } else {
+            1.0
+        };
+        Self {
+            index,
+            id: monitor.id.clone(),
+            name: monitor.name.clone(),
+            is_primary: monitor.is_primary,
+            x: monitor.x,
+            y: monitor.y,
+            width: monitor.width,
+            height: monitor.height,
+            scale_factor: scale,
+            logical_width: monitor.width as f64 / scale,
+            logical_height: monitor.height as f64 / scale,
+            work_area: monitor.work_area,
+        }
+    }
+}
+
+/// Why a monitor could not be listed or used
+#[derive(Debug)]
+pub enum MonitorError {
+    /// The monitors could not be listed
+    List(AutomationError),
+    /// No monitors were found, e.g. on a headless machine without a virtual display
+    NoMonitors,
+    /// There is no monitor with this index
+    NotFound { index: usize, count: usize },
+    /// Monitor-relative coordinates fall outside the monitor
+    OutsideMonitor {
+        index: usize,
+        x: f64,
+        y: f64,
+        width: u32,
+        height: u32,
+    },
+}
+
+impl MonitorError {
+    /// A stable name for the error, for clients to branch on
+    pub fn kind(&self) -> &'static str {
+        match self {
+            MonitorError::List(_) => "monitor_list_failed",
+            MonitorError::NoMonitors => "no_monitors",
+            MonitorError::NotFound { .. } => "monitor_not_found",
+            MonitorError::OutsideMonitor { .. } => "coordinates_outside_monitor",
+        }
+    }
+}
+
+impl std::fmt::Display for MonitorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MonitorError::List(e) => write!(f, "Failed to list monitors: {e}"),
+            MonitorError::NoMonitors => write!(f, "No monitors were found"),
+            MonitorError::NotFound { index, count: 1 } => {
+                write!(f, "There is no monitor {index}; the only monitor is 0")
+            }
+            MonitorError::NotFound { index, count } => write!(
+                f,
+                "There is no monitor {index}; monitors are numbered 0 to {}",
+                count - 1
+            ),
+            MonitorError::OutsideMonitor {
+                index,
+                x,
+                y,
+                width,
+                height,
+            } => write!(
+                f,
+                "({x}, {y}) is outside monitor {index}, which is {width}x{height}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MonitorError {}
+
+/// The monitors in index order: left to right, then top to bottom
+pub async fn ordered_monitors<P: MonitorProvider>(
+    provider: &P,
+) -> Result<Vec<Monitor>, MonitorError> {
+    let mut monitors = provider.monitors().await.map_err(MonitorError::List)?;
+    if monitors.is_empty() {
+        return Err(MonitorError::NoMonitors);
+    }
+    monitors.sort_by_key(|m| (m.x, m.y));
+    Ok(monitors)
+}
+
+/// Every monitor with its index
+pub async fn list_monitors<P: MonitorProvider>(
+    provider: &P,
+) -> Result<Vec<MonitorInfo>, MonitorError> {
+    let monitors = ordered_monitors(provider).await?;
+    Ok(monitors
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| MonitorInfo::new(index, monitor))
+        .collect())
+}
+
+/// The monitor with `index` among monitors returned by [`ordered_monitors`]
+pub fn nth_monitor(monitors: &[Monitor], index: usize) -> Result<&Monitor, MonitorError> {
+    monitors.get(index).ok_or(MonitorError::NotFound {
+        index,
+        count: monitors.len(),
+    })
+}
+
+/// The monitor with `index`
+pub async fn monitor_at<P: MonitorProvider>(
+    provider: &P,
+    index: usize,
+) -> Result<Monitor, MonitorError> {
+    let monitors = ordered_monitors(provider).await?;
+    nth_monitor(&monitors, index).cloned()
+}
+
+/// Converts coordinates relative to monitor `index` into desktop coordinates
+pub fn to_desktop(
+    monitor: &Monitor,
+    index: usize,
+    x: f64,
+    y: f64,
+) -> Result<(f64, f64), MonitorError> {
+    let inside =
+        (0.0..monitor.width as f64).contains(&x) && (0.0..monitor.height as f64).contains(&y);
+    if !inside {
+        return Err(MonitorError::OutsideMonitor {
+            index,
+            x,
+            y,
+            width: monitor.width,
+            height: monitor.height,
+        });
+    }
+    Ok((monitor.x as f64 + x, monitor.y as f64 + y))
+}
@@ -0,0 +1,253 @@
+//! Reading text from part of the screen, behind the `ocr_region` tool.
+//!
+//! Apps that expose no accessibility tree can only be read from pixels. The region, in
+//! desktop coordinates, is cut out of a capture of the monitor showing most of it; when
+//! the capture's size differs from the monitor's (display scaling) the region is scaled
+//! to match, as in `capture_highlighted_screenshot`. Word boxes come back in desktop
+//! coordinates so they can be clicked. Parts of the region outside that monitor are left
+//! out and reported through `clipped`. Recognition goes through [`WordRecognizer`] so
+//! the mapping can be tested without an OCR engine.
+
+use crate::highlight_screenshot::{monitor_showing, Rect};
+use image::{DynamicImage, RgbaImage};
+use std::future::Future;
+use terminator::ocr::{OcrWord, WordRecognizer};
+use terminator::{AutomationError, Monitor};
+
+/// What to read, as given in the tool arguments
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RegionSource<'a> {
+    /// The bounding box of the element matching the selector
+    Selector(&'a str),
+    Rect(Rect),
+}
+
+impl<'a> RegionSource<'a> {
+    /// Reads the `selector` or `x`, `y`, `width` and `height` arguments; exactly one of the
+    /// two forms must be given.
+    pub fn from_args(
+        selector: Option<&'a str>,
+        x: Option<f64>,
+        y: Option<f64>,
+        width: Option<f64>,
+        height: Option<f64>,
+    ) -> Result<Self, String> {
+        match (selector, x, y, width, height) {
+            (Some(selector), None, None, None, None) => Ok(RegionSource::Selector(selector)),
+            (None, Some(x), Some(y), Some(width), Some(height)) => Ok(RegionSource::Rect(Rect {
+                x,
+                y,
+                width,
+                height,
+            })),
+            (None, None, None, None, None) => {
+                Err("Either selector or x, y, width and height are required".to_string())
+            }
+            (Some(_), ..) => {
+                Err("Give either selector or x, y, width and height, not both".to_string())
+            }
+            (None, ..) => Err("x, y, width and height must be given together".to_string()),
+        }
+    }
+}
+
+/// Text read from a region
+#[derive(Debug, Clone)]
+pub struct RegionText {
+    /// The requested region, in desktop coordinates
+    pub region: Rect,
+    pub monitor: Monitor,
+    /// Part of the region lies outside the captured monitor
+    pub clipped: bool,
+    /// The words joined with spaces, one line of text per line
+    pub text: String,
+    /// Words with their bounding boxes in desktop coordinates
+    pub words: Vec<OcrWord>,
+}
+
+/// Why a region could not be read
+#[derive(Debug)]
+pub enum OcrRegionError {
+    /// The region has no area
+    EmptyRegion(Rect),
+    /// No monitor shows any of the region
+    OffScreen(Rect),
+    /// The monitor could not be captured
+    Capture(String),
+    /// The OCR engine failed, or is not compiled in
+    Recognition(AutomationError),
+}
+
+impl OcrRegionError {
+    /// A stable name for the error, for clients to branch on
+    pub fn kind(&self) -> &'static str {
+        match self {
+            OcrRegionError::EmptyRegion(_) => "empty_region",
+            OcrRegionError::OffScreen(_) => "region_off_screen",
+            OcrRegionError::Capture(_) => "screen_capture_failed",
+            OcrRegionError::Recognition(AutomationError::UnsupportedOperation(_)) => {
+                "ocr_not_enabled"
+            }
+            OcrRegionError::Recognition(_) => "ocr_failed",
+        }
+    }
+}
+
+impl std::fmt::Display for OcrRegionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OcrRegionError::EmptyRegion(r) => write!(
+                f,
+                "Region ({}, {}, {}x{}) is empty",
+                r.x, r.y, r.width, r.height
+            ),
+            OcrRegionError::OffScreen(r) => write!(
+                f,
+                "Region ({}, {}, {}x{}) is not on any monitor",
+                r.x, r.y, r.width, r.height
+            ),
+            OcrRegionError::Capture(reason) => write!(f, "Failed to capture the screen: {reason}"),
+            OcrRegionError::Recognition(e) => write!(f, "OCR failed: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for OcrRegionError {}
+
+/// A region cut out of a monitor capture
+#[derive(Debug, Clone)]
+pub struct RegionCrop {
+    pub image: RgbaImage,
+    /// Desktop coordinates of the crop's top-left corner
+    pub x: f64,
+    pub y: f64,
+    /// Capture pixels per desktop unit
+    pub scale_x: f64,
+    pub scale_y: f64,
+    /// Part of the region lies outside the capture
+    pub clipped: bool,
+}
+
+impl RegionCrop {
+    /// Moves a word box from crop pixels to desktop coordinates
+    pub fn to_desktop(&self, word: OcrWord) -> OcrWord {
+        OcrWord {
+            x: self.x + word.x / self.scale_x,
+            y: self.y + word.y / self.scale_y,
+            width: word.width / self.scale_x,
+            height: word.height / self.scale_y,
+            ..word
+        }
+    }
+}
+
+/// Cuts `region` out of a capture of `monitor`, or `None` when none of it is in the image.
+/// Pixels the region only partly covers are included.
+pub fn crop_region(image: &RgbaImage, monitor: &Monitor, region: &Rect) -> Option<RegionCrop> {
+    let (image_width, image_height) = image.dimensions();
+    let scale_x = if monitor.width > 0 {
+        image_width as f64 / monitor.width as f64
+    } else {
+        1.0
+    };
+    let scale_y = if monitor.height > 0 {
+        image_height as f64 / monitor.height as f64
+    } else {
+        1.0
+    };
+
+    // Region edges in image pixels, possibly outside the image
+    let left = ((region.x - monitor.x as f64) * scale_x).floor() as i64;
+    let top = ((region.y - monitor.y as f64) * scale_y).floor() as i64;
+    let right = ((region.x + region.width - monitor.x as f64) * scale_x).ceil() as i64;
+    let bottom = ((region.y + region.height - monitor.y as f64) * scale_y).ceil() as i64;
+
+    let visible_left = left.clamp(0, image_width as i64);
+    let visible_top = top.clamp(0, image_height as i64);
+    let visible_right = right.clamp(0, image_width as i64);
+    let visible_bottom = bottom.clamp(0, image_height as i64);
+    if visible_left >= visible_right || visible_top >= visible_bottom {
+        return None;
+    }
+
+    let cropped = image::imageops::crop_imm(
+        image,
+        visible_left as u32,
+        visible_top as u32,
+        (visible_right - visible_left) as u32,
+        (visible_bottom - visible_top) as u32,
+    )
+    .to_image();
+    Some(RegionCrop {
+        image: cropped,
+        x: monitor.x as f64 + visible_left as f64 / scale_x,
+        y: monitor.y as f64 + visible_top as f64 / scale_y,
+        scale_x,
+        scale_y,
+        clipped: (visible_left, visible_top, visible_right, visible_bottom)
+            != (left, top, right, bottom),
+    })
+}
+
+/// The words as text: words on a line joined with spaces, lines with newlines
+pub fn line_text(words: &[OcrWord]) -> String {
+    let mut words: Vec<&OcrWord> = words.iter().collect();
+    words.sort_by_key(|word| word.line);
+
+    let mut text = String::new();
+    let mut line = None;
+    for word in words {
+        match line {
+            None => {}
+            Some(line) if line == word.line => text.push(' '),
+            Some(_) => text.push('\n'),
+        }
+        text.push_str(&word.text);
+        line = Some(word.line);
+    }
+    text
+}
+
+/// Captures the monitor showing most of `region` with `capture` and reads the words in
+/// the region with `recognizer`
+pub async fn ocr_region<R, F, Fut>(
+    recognizer: &R,
+    region: Rect,
+    monitors: &[Monitor],
+    capture: F,
+    locale: Option<&str>,
+) -> Result<RegionText, OcrRegionError>
+where
+    R: WordRecognizer,
+    F: FnOnce(Monitor) -> Fut,
+    Fut: Future<Output = Result<RgbaImage, String>>,
+{
+    if !(region.width > 0.0 && region.height > 0.0) {
+        return Err(OcrRegionError::EmptyRegion(region));
+    }
+    let monitor = monitor_showing(&region, monitors)
+        .ok_or(OcrRegionError::OffScreen(region))?
+        .clone();
+
+    let image = capture(monitor.clone())
+        .await
+        .map_err(OcrRegionError::Capture)?;
+    let mut crop =
+        crop_region(&image, &monitor, &region).ok_or(OcrRegionError::OffScreen(region))?;
+    let pixels = std::mem::take(&mut crop.image);
+    let words: Vec<OcrWord> = recognizer
+        .recognize_words(DynamicImage::ImageRgba8(pixels), locale)
+        .await
+        .map_err(OcrRegionError::Recognition)?
+        .into_iter()
+        .map(|word| crop.to_desktop(word))
+        .collect();
+
+    Ok(RegionText {
+        region,
+        monitor,
+        clipped: crop.clipped,
+        text: line_text(&words),
+        words,
+    })
+}
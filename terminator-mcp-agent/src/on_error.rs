@@ -0,0 +1,149 @@
+//! `on_error` handlers.
+//!
+//! A step's `on_error` lists handlers for when it still fails after its retries. The
+//! first handler whose `when` expression holds (or that has none) runs: it sets its
+//! `set_env` variables, runs its fallback `steps` in order, then resolves the failure
+//! with `then`. While handlers are chosen and run, the failure is available as
+//! `env.error` (`message`, `kind`, `category`, `step_id`, `tool_name`); it is removed
+//! afterwards. A fallback step that fails ends the handler and leaves the step failed.
+
+use crate::expression_eval;
+use crate::helpers::step_succeeded;
+use crate::utils::{SequenceStep, ToolCall};
+use rmcp::{schemars, schemars::JsonSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// Name of the env variable holding the failure while a handler runs
+pub const ERROR_VARIABLE: &str = "error";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ErrorHandler {
+    #[schemars(
+        description = "Expression selecting the failures this handler covers, e.g. \"env.error.kind == 'step_timeout'\". Handlers are tried in order; one without 'when' covers every failure."
+    )]
+    pub when: Option<String>,
+    #[schemars(
+        description = "Env variables to set, before any fallback steps run. Values may use {{...}} templates, e.g. {\"last_failure\": \"{{env.error.message}}\"}."
+    )]
+    pub set_env: Option<Map<String, Value>>,
+    #[schemars(description = "Fallback steps to run in order")]
+    pub steps: Option<Vec<ToolCall>>,
+    #[schemars(
+        description = "What happens once the handler has run: 'continue' treats the step as recovered, 'abort' stops the workflow (only always() steps still run), 'fail' (default) keeps the step failed so fallback_id and stop_on_error apply."
+    )]
+    pub then: Option<ErrorAction>,
+}
+
+/// How a handled failure is resolved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorAction {
+    Continue,
+    Abort,
+    #[default]
+    Fail,
+}
+
+impl ErrorAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorAction::Continue => "continue",
+            ErrorAction::Abort => "abort",
+            ErrorAction::Fail => "fail",
+        }
+    }
+}
+
+/// What made a step fail, as seen by its handlers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepError {
+    pub message: String,
+    /// `step_timeout`, `verification_failed` or `tool_error`
+    pub kind: String,
+    /// `user`, `transient` or `system`, when the tool reported one
+    pub category: Option<String>,
+}
+
+impl StepError {
+    /// Describes the failure in a failed step's result. For a group, the first failed
+    /// call's result is used.
+    pub fn from_result(result: &Value) -> Self {
+        let failed_call = result["results"].as_array().and_then(|calls| {
+            calls
+                .iter()
+                .find(|call| call["error"].is_string() && !step_succeeded(call))
+        });
+        let result = failed_call.unwrap_or(result);
+
+        let kind = if result["reason"] == "step_timeout" {
+            "step_timeout"
+        } else if result["verification_failed"] == true {
+            "verification_failed"
+        } else {
+            "tool_error"
+        };
+        let message = result["error"]
+            .as_str()
+            .map(str::to_string)
+            .or_else(|| {
+                result["group_name"]
+                    .as_str()
+                    .map(|group| format!("Group '{group}' failed"))
+            })
+            .unwrap_or_else(|| "Step failed".to_string());
+        Self {
+            message,
+            kind: kind.to_string(),
+            category: result["error_category"].as_str().map(str::to_string),
+        }
+    }
+
+    /// The value exposed to handlers as `env.error`
+    pub fn variable(&self, step: &SequenceStep) -> Value {
+        json!({
+            "message": self.message,
+            "kind": self.kind,
+            "category": self.category,
+            "step_id": step.id,
+            "tool_name": step.tool_name,
+        })
+    }
+}
+
+/// The first handler covering the failure, given variables that include `env.error`
+pub fn select_handler<'a>(
+    handlers: &'a [ErrorHandler],
+    variables: &Value,
+) -> Option<(usize, &'a ErrorHandler)> {
+    handlers.iter().enumerate().find(|(_, handler)| {
+        handler
+            .when
+            .as_deref()
+            .is_none_or(|when| expression_eval::evaluate(when, variables))
+    })
+}
+
+/// Checks the `when` expressions of a step's handlers before the workflow runs
+pub fn validate(step: &SequenceStep) -> Result<(), String> {
+    for handler in step.on_error.as_deref().unwrap_or_default() {
+        if let Some(when) = &handler.when {
+            expression_eval::Expression::compile(when)
+                .map_err(|e| format!("Invalid on_error when '{when}': {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Puts the failure into `env.error`, returning the value it replaced
+pub fn expose_error(env: &mut Map<String, Value>, error: Value) -> Option<Value> {
+    env.insert(ERROR_VARIABLE.to_string(), error)
+}
+
+/// Removes `env.error` once handling is over, restoring any earlier value
+pub fn restore_error(env: &mut Map<String, Value>, previous: Option<Value>) {
+    match previous {
+        Some(value) => env.insert(ERROR_VARIABLE.to_string(), value),
+        None => env.remove(ERROR_VARIABLE),
+    };
+}
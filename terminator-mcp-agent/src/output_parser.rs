@@ -1,7 +1,9 @@
 use crate::scripting_engine::execute_javascript_with_nodejs;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
+use serde_json_path::JsonPath;
+use std::fmt;
 
 /// JavaScript-based parser definition
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -20,6 +22,64 @@ pub struct OutputParserDefinition {
     /// Simplified alias for 'javascript_code' - inspired by GitHub Actions syntax
     /// Use this for inline JavaScript code instead of javascript_code
     pub run: Option<String>,
+    /// Optional: JSONPath such as `$.data.items[*].name`, selecting values from the
+    /// parser's result. Without JavaScript, it selects from the tool output instead.
+    /// The matches are returned as an array.
+    #[serde(default)]
+    pub extract_path: Option<String>,
+    /// Optional: the result is markdown holding a table, as LLM answers often are.
+    /// The first table is converted to an array of objects keyed by its header row.
+    /// Applied after `extract_path`; an array of strings has each one converted.
+    #[serde(default)]
+    pub markdown_table: bool,
+    /// Optional: JSON Schema the parser's result must match. A result that doesn't
+    /// fails the parser with a [`SchemaValidationError`] listing every violation.
+    #[serde(default)]
+    pub schema: Option<Value>,
+    /// Extra serialization of the parser's result. With `csv`, the returned array of
+    /// objects is also provided as CSV text in the output's `csv` field.
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+/// A single way in which a parser's result doesn't match its schema
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON Pointer to the offending value, such as `/0/price`; empty for the whole result
+    pub path: String,
+    pub message: String,
+}
+
+/// Returned when a parser's result doesn't match the definition's `schema`
+#[derive(Debug)]
+pub struct SchemaValidationError {
+    pub violations: Vec<SchemaViolation>,
+}
+
+impl fmt::Display for SchemaValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Parsed output does not match the schema:")?;
+        for violation in &self.violations {
+            let path = if violation.path.is_empty() {
+                "(root)"
+            } else {
+                &violation.path
+            };
+            write!(f, "\n  {}: {}", path, violation.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SchemaValidationError {}
+
+/// How the parser's result is provided, besides the JSON value itself
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Csv,
 }
 
 /// The main entry point for parsing tool output.
@@ -34,6 +94,10 @@ pub async fn run_output_parser(
             javascript_code: parser_def_val.as_str().map(|s| s.to_string()),
             javascript_file_path: None,
             run: None,
+            extract_path: None,
+            markdown_table: false,
+            schema: None,
+            format: OutputFormat::Json,
         }
     } else {
         serde_json::from_value(parser_def_val.clone()).map_err(|e| {
@@ -63,11 +127,22 @@ pub async fn run_output_parser(
                 "Cannot provide both inline JavaScript code ('javascript_code' or 'run') and 'javascript_file_path'. Please provide only one."
             ));
         }
-        (None, None) => {
+        (None, None) if parser_def.extract_path.is_none() => {
             return Err(anyhow::anyhow!(
-                "Must provide either 'javascript_code'/'run' (inline JavaScript) or 'javascript_file_path' (path to JavaScript file)."
+                "Must provide either 'javascript_code'/'run' (inline JavaScript), 'javascript_file_path' (path to JavaScript file) or 'extract_path' (JSONPath)."
             ));
         }
+        (None, None) => {
+            // Path-only parser: select straight from the tool output, no script needed
+            let path = parser_def.extract_path.as_deref().unwrap_or_default();
+            let mut result = serde_json::json!({ "result": extract_path(tool_output, path)? });
+            if parser_def.markdown_table {
+                result["result"] = rows_from_markdown(&result["result"])?;
+            }
+            check_schema(&result["result"], parser_def.schema.as_ref())?;
+            apply_format(&mut result, parser_def.format)?;
+            return Ok(Some(result));
+        }
     };
 
     let ui_tree =
@@ -117,13 +192,222 @@ pub async fn run_output_parser(
     };
 
     // Execute JavaScript code asynchronously
-    let result = execute_javascript_with_nodejs(full_script, None)
+    let mut result = execute_javascript_with_nodejs(full_script, None)
         .await
         .map_err(|e| anyhow::anyhow!("JavaScript execution failed: {}", e))?;
 
+    if let Some(path) = parser_def.extract_path.as_deref() {
+        result["result"] = extract_path(&result["result"], path)?;
+    }
+    if parser_def.markdown_table {
+        result["result"] = rows_from_markdown(&result["result"])?;
+    }
+    check_schema(&result["result"], parser_def.schema.as_ref())?;
+    apply_format(&mut result, parser_def.format)?;
+
     Ok(Some(result))
 }
 
+/// Adds the extra serialization requested by `format` to a parser output
+fn apply_format(result: &mut Value, format: OutputFormat) -> Result<()> {
+    if format == OutputFormat::Csv {
+        let csv = to_csv(&result["result"])?;
+        if let Some(obj) = result.as_object_mut() {
+            obj.insert("csv".to_string(), Value::String(csv));
+        }
+    }
+    Ok(())
+}
+
+/// Converts the markdown table in a string, or in each string of an array, to rows
+fn rows_from_markdown(result: &Value) -> Result<Value> {
+    let texts: Vec<&str> = match result {
+        Value::String(text) => vec![text],
+        Value::Array(items) => items.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if texts.is_empty() {
+        return Err(anyhow::anyhow!(
+            "markdown_table needs the parser's result to be a string, or an array of strings"
+        ));
+    }
+
+    let mut rows = Vec::new();
+    for text in texts {
+        let table = parse_markdown_table(text)
+            .ok_or_else(|| anyhow::anyhow!("No markdown table found in: {}", text))?;
+        rows.extend(table.into_iter().map(Value::Object));
+    }
+    Ok(Value::Array(rows))
+}
+
+/// Finds the first GitHub-flavored markdown table in `text` and returns its rows,
+/// each keyed by the header row's cells.
+///
+/// A table is a header row followed by a delimiter row such as `|---|:--:|`, with the
+/// same number of cells. Cells are trimmed and `\|` is a literal pipe. Rows with fewer
+/// cells than the header are padded with empty strings; extra cells are dropped.
+/// The table ends at the first blank line or line without a pipe.
+pub fn parse_markdown_table(text: &str) -> Option<Vec<Map<String, Value>>> {
+    let lines: Vec<&str> = text.lines().collect();
+    let start = lines.windows(2).position(|pair| {
+        let header = split_table_row(pair[0]);
+        let delimiter = split_table_row(pair[1]);
+        pair[0].contains('|')
+            && header.len() == delimiter.len()
+            && delimiter.iter().all(|cell| is_delimiter_cell(cell))
+    })?;
+
+    let header = split_table_row(lines[start]);
+    let rows = lines[start + 2..]
+        .iter()
+        .take_while(|line| !line.trim().is_empty() && line.contains('|'))
+        .map(|line| {
+            let mut cells = split_table_row(line).into_iter();
+            header
+                .iter()
+                .map(|key| {
+                    let cell = cells.next().unwrap_or_default();
+                    (key.clone(), Value::String(cell))
+                })
+                .collect()
+        })
+        .collect();
+    Some(rows)
+}
+
+/// Splits a table row on unescaped pipes, ignoring the optional outer ones
+fn split_table_row(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut chars = line.trim().chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' if chars.peek() == Some(&'|') => {
+                cell.push('|');
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut cell)),
+            _ => cell.push(ch),
+        }
+    }
+    cells.push(cell);
+
+    let line = line.trim();
+    if line.starts_with('|') {
+        cells.remove(0);
+    }
+    if line.len() > 1 && line.ends_with('|') && !line.ends_with("\\|") {
+        cells.pop();
+    }
+    cells.iter().map(|cell| cell.trim().to_string()).collect()
+}
+
+/// A delimiter row cell: at least one dash, with optional alignment colons
+fn is_delimiter_cell(cell: &str) -> bool {
+    let dashes = cell.strip_prefix(':').unwrap_or(cell);
+    let dashes = dashes.strip_suffix(':').unwrap_or(dashes);
+    !dashes.is_empty() && dashes.chars().all(|ch| ch == '-')
+}
+
+fn check_schema(result: &Value, schema: Option<&Value>) -> Result<()> {
+    let Some(schema) = schema else {
+        return Ok(());
+    };
+    let violations = validate_against_schema(result, schema)?;
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(SchemaValidationError { violations }.into())
+    }
+}
+
+/// Checks a value against a JSON Schema, returning every violation found.
+/// An empty list means the value is valid; an invalid schema is an error.
+pub fn validate_against_schema(value: &Value, schema: &Value) -> Result<Vec<SchemaViolation>> {
+    let validator = jsonschema::validator_for(schema)
+        .map_err(|e| anyhow::anyhow!("Invalid output schema: {}", e))?;
+    Ok(validator
+        .iter_errors(value)
+        .map(|error| SchemaViolation {
+            path: error.instance_path.to_string(),
+            message: error.to_string(),
+        })
+        .collect())
+}
+
+/// Selects the values matching a JSONPath query, such as `$.data.items[*].name`.
+///
+/// Matches are returned as an array, in document order; no match gives an empty array.
+pub fn extract_path(value: &Value, path: &str) -> Result<Value> {
+    let json_path =
+        JsonPath::parse(path).map_err(|e| anyhow::anyhow!("Invalid JSONPath '{}': {}", path, e))?;
+    Ok(Value::Array(
+        json_path.query(value).all().into_iter().cloned().collect(),
+    ))
+}
+
+/// Serializes an array of objects as CSV.
+///
+/// The header is the union of the rows' keys, in the order they first appear. A row
+/// without a key gets an empty cell, as does `null`. Nested arrays and objects are
+/// written as JSON. Cells containing commas, quotes or line breaks are quoted.
+pub fn to_csv(rows: &Value) -> Result<String> {
+    let rows = rows
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("CSV output needs the parser to return an array"))?;
+    let mut objects = Vec::with_capacity(rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        let object = row.as_object().ok_or_else(|| {
+            anyhow::anyhow!(
+                "CSV output needs an array of objects, but row {} is {}",
+                i,
+                row
+            )
+        })?;
+        objects.push(object);
+    }
+
+    let mut header: Vec<&str> = Vec::new();
+    for object in &objects {
+        for key in object.keys() {
+            if !header.contains(&key.as_str()) {
+                header.push(key);
+            }
+        }
+    }
+
+    let mut csv = String::new();
+    push_csv_line(&mut csv, header.iter().map(|key| key.to_string()));
+    for object in objects {
+        push_csv_line(
+            &mut csv,
+            header.iter().map(|key| match object.get(*key) {
+                None | Some(Value::Null) => String::new(),
+                Some(Value::String(text)) => text.clone(),
+                Some(other) => other.to_string(),
+            }),
+        );
+    }
+    Ok(csv)
+}
+
+fn push_csv_line(csv: &mut String, cells: impl Iterator<Item = String>) {
+    for (i, cell) in cells.enumerate() {
+        if i > 0 {
+            csv.push(',');
+        }
+        if cell.contains([',', '"', '\n', '\r']) {
+            csv.push('"');
+            csv.push_str(&cell.replace('"', "\"\""));
+            csv.push('"');
+        } else {
+            csv.push_str(&cell);
+        }
+    }
+    csv.push('\n');
+}
+
 /// Finds a UI tree in the tool output results
 fn find_ui_tree_in_results(tool_output: &Value, step_id: Option<&str>) -> Result<Option<Value>> {
     // Strategy 0: If step_id is specified, prefer UI tree from that specific step, but gracefully
@@ -297,6 +581,10 @@ mod tests {
                 .to_string(),
             ),
             javascript_file_path: None,
+            extract_path: None,
+            markdown_table: false,
+            schema: None,
+            format: OutputFormat::Json,
         };
         // Note: This test would require an async runtime to execute JavaScript
         // For now, we'll just verify the parser definition structure is correct
@@ -327,6 +615,10 @@ mod tests {
                 .to_string(),
             ),
             javascript_file_path: None,
+            extract_path: None,
+            markdown_table: false,
+            schema: None,
+            format: OutputFormat::Json,
         };
 
         // Verify parser definition structure
@@ -377,6 +669,10 @@ mod tests {
                 .to_string(),
             ),
             javascript_file_path: None,
+            extract_path: None,
+            markdown_table: false,
+            schema: None,
+            format: OutputFormat::Json,
         };
 
         // Verify parser definition structure
@@ -421,6 +717,10 @@ mod tests {
                 .to_string(),
             ),
             javascript_file_path: None,
+            extract_path: None,
+            markdown_table: false,
+            schema: None,
+            format: OutputFormat::Json,
         };
 
         json!({
@@ -453,6 +753,241 @@ mod tests {
         assert!(parser_def_json.get("javascript_code").is_some());
     }
 
+    #[test]
+    fn test_csv_header_is_the_union_of_keys() {
+        let rows = json!([
+            {"name": "Widget", "price": 9.5},
+            {"name": "Gadget", "sku": "G-1"},
+            {"price": 3, "in_stock": true, "name": null}
+        ]);
+
+        assert_eq!(
+            to_csv(&rows).unwrap(),
+            "name,price,sku,in_stock\n\
+             Widget,9.5,,\n\
+             Gadget,,G-1,\n\
+             ,3,,true\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_escapes_special_characters() {
+        let rows = json!([
+            {"name": "Bolt, M6", "note": "says \"hi\"", "tags": ["a", "b"]},
+            {"name": "Line\nbreak", "note": "plain", "tags": []}
+        ]);
+
+        assert_eq!(
+            to_csv(&rows).unwrap(),
+            "name,note,tags\n\
+             \"Bolt, M6\",\"says \"\"hi\"\"\",\"[\"\"a\"\",\"\"b\"\"]\"\n\
+             \"Line\nbreak\",plain,[]\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_needs_an_array_of_objects() {
+        assert!(to_csv(&json!({"name": "Widget"})).is_err());
+        assert!(to_csv(&json!([{"name": "Widget"}, "Gadget"])).is_err());
+        assert_eq!(to_csv(&json!([])).unwrap(), "\n");
+    }
+
+    #[test]
+    fn test_extract_path_returns_matches_as_an_array() {
+        let scraped = json!({
+            "data": {
+                "items": [
+                    {"name": "Widget", "price": 9.5},
+                    {"name": "Gadget", "price": 3},
+                    {"price": 1}
+                ]
+            }
+        });
+
+        assert_eq!(
+            extract_path(&scraped, "$.data.items[*].name").unwrap(),
+            json!(["Widget", "Gadget"])
+        );
+        assert_eq!(
+            extract_path(&scraped, "$.data.items[0]").unwrap(),
+            json!([{"name": "Widget", "price": 9.5}])
+        );
+        assert_eq!(extract_path(&scraped, "$.data.missing").unwrap(), json!([]));
+    }
+
+    #[test]
+    fn test_extract_path_rejects_invalid_paths() {
+        let error = extract_path(&json!({}), "$.data[").unwrap_err().to_string();
+        assert!(error.starts_with("Invalid JSONPath '$.data['"), "{error}");
+        assert!(extract_path(&json!({}), "data.items").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_extract_path_without_javascript_reads_the_tool_output() {
+        let tool_output = json!({
+            "results": [
+                {"step_id": "scrape", "result": {"rows": [{"sku": "A-1"}, {"sku": "B-2"}]}}
+            ]
+        });
+        let parser_def = json!({"extract_path": "$.results[0].result.rows[*].sku"});
+
+        let parsed = run_output_parser(&parser_def, &tool_output)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed["result"], json!(["A-1", "B-2"]));
+
+        let invalid = json!({"extract_path": "$.results["});
+        assert!(run_output_parser(&invalid, &tool_output).await.is_err());
+    }
+
+    #[test]
+    fn test_markdown_table_with_two_columns() {
+        let answer = "Here are the results:\n\
+            \n\
+            | Name   |  Price |\n\
+            |:-------|-------:|\n\
+            | Widget |   9.50 |\n\
+            |Gadget|3|\n\
+            \n\
+            Let me know if you need more.";
+
+        let rows = parse_markdown_table(answer).unwrap();
+        assert_eq!(
+            Value::Array(rows.into_iter().map(Value::Object).collect()),
+            json!([
+                {"Name": "Widget", "Price": "9.50"},
+                {"Name": "Gadget", "Price": "3"}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_markdown_table_with_escaped_pipe_in_a_cell() {
+        let table = "Command | Meaning\n\
+            --- | :---:\n\
+            `a \\| b` | pipe a into b\n\
+            `ls` | list files | extra\n\
+            `pwd` |\n\
+            `whoami`";
+
+        let rows = parse_markdown_table(table).unwrap();
+        assert_eq!(
+            Value::Array(rows.into_iter().map(Value::Object).collect()),
+            json!([
+                {"Command": "`a | b`", "Meaning": "pipe a into b"},
+                {"Command": "`ls`", "Meaning": "list files"},
+                {"Command": "`pwd`", "Meaning": ""}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_markdown_without_a_table() {
+        assert!(parse_markdown_table("Just | some text\nwith a pipe").is_none());
+        // The delimiter row must match the header's cell count
+        assert!(parse_markdown_table("| a | b |\n|---|\n| 1 | 2 |").is_none());
+        assert!(parse_markdown_table("").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_markdown_table_parser_option() {
+        let tool_output = json!({"answer": "| sku | qty |\n|-----|-----|\n| A-1 | 2 |"});
+        let parser_def = json!({"extract_path": "$.answer", "markdown_table": true});
+
+        let parsed = run_output_parser(&parser_def, &tool_output)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed["result"], json!([{"sku": "A-1", "qty": "2"}]));
+
+        let no_table = json!({"answer": "No results found."});
+        assert!(run_output_parser(&parser_def, &no_table).await.is_err());
+    }
+
+    fn product_schema() -> Value {
+        json!({
+            "type": "array",
+            "items": {
+                "type": "object",
+                "required": ["name", "price"],
+                "properties": {
+                    "name": {"type": "string"},
+                    "price": {"type": "number"}
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_schema_accepts_a_matching_document() {
+        let products = json!([
+            {"name": "Widget", "price": 9.5},
+            {"name": "Gadget", "price": 3}
+        ]);
+
+        assert_eq!(
+            validate_against_schema(&products, &product_schema()).unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_schema_lists_every_violation() {
+        let products = json!([
+            {"name": "Widget", "price": "9.50"},
+            {"name": "Gadget"}
+        ]);
+
+        let violations = validate_against_schema(&products, &product_schema()).unwrap();
+        assert_eq!(
+            violations,
+            vec![
+                SchemaViolation {
+                    path: "/0/price".to_string(),
+                    message: r#""9.50" is not of type "number""#.to_string(),
+                },
+                SchemaViolation {
+                    path: "/1".to_string(),
+                    message: r#""price" is a required property"#.to_string(),
+                },
+            ]
+        );
+
+        assert!(validate_against_schema(&products, &json!({"type": 12})).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_schema_violations_fail_the_parser() {
+        let tool_output = json!({"rows": [{"name": "Widget"}, {"name": 7}]});
+        let parser_def = json!({
+            "extract_path": "$.rows[*].name",
+            "schema": {"type": "array", "items": {"type": "string"}}
+        });
+
+        let error = run_output_parser(&parser_def, &tool_output)
+            .await
+            .unwrap_err();
+        let validation = error.downcast_ref::<SchemaValidationError>().unwrap();
+        assert_eq!(validation.violations.len(), 1);
+        assert_eq!(validation.violations[0].path, "/1");
+        assert!(
+            error.to_string().contains("\n  /1: 7 is not of type"),
+            "{error}"
+        );
+    }
+
+    #[test]
+    fn test_parser_definition_format() {
+        let parser_def: OutputParserDefinition =
+            serde_json::from_value(json!({"run": "return [];", "format": "csv"})).unwrap();
+        assert_eq!(parser_def.format, OutputFormat::Csv);
+
+        let parser_def: OutputParserDefinition =
+            serde_json::from_value(json!({"run": "return [];"})).unwrap();
+        assert_eq!(parser_def.format, OutputFormat::Json);
+    }
+
     #[test]
     fn test_parser_definition_serialization() {
         // Test the new clean syntax for JavaScript-based parsing
@@ -0,0 +1,89 @@
+//! Parallel groups.
+//!
+//! A group step with `parallel: true` starts all of its steps at once instead of one
+//! after another, for independent work such as reading two separate panels. Every step
+//! sees the variables as they were when the group started. Once all steps have ended,
+//! their outputs and `<id>_status` are merged into env in the group's step order, so
+//! the outcome does not depend on which step finished first.
+//!
+//! A failed step fails the group unless it sets `continue_on_error`. With `fail_fast`
+//! (the default) the first such failure cancels the steps still running; without it,
+//! the other steps run to completion. Cancelling the request cancels every running step.
+
+use crate::utils::SequenceStep;
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::future::Future;
+use tokio_util::sync::CancellationToken;
+
+/// The settings of a `parallel` group step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParallelGroup {
+    pub fail_fast: bool,
+}
+
+impl ParallelGroup {
+    /// Reads the parallel settings from a step. Returns `Ok(None)` for steps that are
+    /// not parallel groups.
+    pub fn from_step(step: &SequenceStep) -> Result<Option<Self>, String> {
+        if step.parallel != Some(true) {
+            return Ok(None);
+        }
+        if step.group_name.is_none() {
+            return Err("parallel is only supported on group steps".to_string());
+        }
+        if step.repeat_until.is_some() {
+            return Err("A parallel group cannot use repeat_until".to_string());
+        }
+        Ok(Some(Self {
+            fail_fast: step.fail_fast.unwrap_or(true),
+        }))
+    }
+}
+
+/// How a step of a parallel group ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChildOutcome<T> {
+    Finished(T),
+    /// Still running when the group was cancelled
+    Cancelled,
+}
+
+/// Runs `children` concurrently and returns their outcomes in the children's order.
+///
+/// `cancellation` should belong to this group alone (a child of the request's token),
+/// because with `fail_fast` the first output for which `is_failure` holds cancels it.
+/// Children still running once it is cancelled, for either reason, are dropped.
+pub async fn run_children<T, Fut>(
+    children: Vec<Fut>,
+    cancellation: &CancellationToken,
+    fail_fast: bool,
+    is_failure: impl Fn(&T) -> bool,
+) -> Vec<ChildOutcome<T>>
+where
+    Fut: Future<Output = T>,
+{
+    let mut outcomes: Vec<ChildOutcome<T>> =
+        children.iter().map(|_| ChildOutcome::Cancelled).collect();
+    let mut running: FuturesUnordered<_> = children
+        .into_iter()
+        .enumerate()
+        .map(|(index, child)| async move { (index, child.await) })
+        .collect();
+
+    loop {
+        tokio::select! {
+            biased;
+            _ = cancellation.cancelled() => break,
+            next = running.next() => {
+                let Some((index, output)) = next else {
+                    break;
+                };
+                if fail_fast && is_failure(&output) {
+                    cancellation.cancel();
+                }
+                outcomes[index] = ChildOutcome::Finished(output);
+            }
+        }
+    }
+    outcomes
+}
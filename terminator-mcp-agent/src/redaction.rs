@@ -0,0 +1,106 @@
+//! Redaction of sensitive tool arguments before they are logged.
+//!
+//! An argument is sensitive when its key equals one of the sensitive keys or contains one
+//! as a word, ignoring case: `password` covers `admin_password` and `newPassword`, while
+//! `ssn` does not cover `classname`. Sensitive values are replaced with `[REDACTED]` at any
+//! depth, including inside arrays. The defaults are `password`, `token`, `secret` and
+//! `ssn`; `TERMINATOR_REDACT_KEYS` replaces them with a comma-separated list. Typed text
+//! is passed as `text_to_type`, so add that key to keep everything typed out of the logs.
+
+use serde_json::Value;
+use std::sync::OnceLock;
+
+pub const REDACT_KEYS_ENV: &str = "TERMINATOR_REDACT_KEYS";
+
+/// Keys whose values are masked unless `TERMINATOR_REDACT_KEYS` says otherwise
+pub const DEFAULT_SENSITIVE_KEYS: &[&str] = &["password", "token", "secret", "ssn"];
+
+/// What a sensitive value is replaced with
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Masks the values of sensitive argument keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redactor {
+    pub sensitive_keys: Vec<String>,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(DEFAULT_SENSITIVE_KEYS.iter().copied())
+    }
+}
+
+// Splits a key into lowercase words at `_`, `-`, `.`, spaces and camelCase boundaries
+fn words(key: &str) -> Vec<String> {
+    let mut words = vec![String::new()];
+    let mut previous_lower = false;
+    for c in key.chars() {
+        if !c.is_alphanumeric() {
+            words.push(String::new());
+        } else {
+            if c.is_uppercase() && previous_lower {
+                words.push(String::new());
+            }
+            if let Some(word) = words.last_mut() {
+                word.extend(c.to_lowercase());
+            }
+        }
+        previous_lower = c.is_lowercase() || c.is_ascii_digit();
+    }
+    words.retain(|word| !word.is_empty());
+    words
+}
+
+impl Redactor {
+    pub fn new<S: Into<String>>(sensitive_keys: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            sensitive_keys: sensitive_keys
+                .into_iter()
+                .map(|key| key.into().trim().to_lowercase())
+                .filter(|key| !key.is_empty())
+                .collect(),
+        }
+    }
+
+    /// The default keys, or the list in `TERMINATOR_REDACT_KEYS` when it is set
+    pub fn from_env() -> Self {
+        match std::env::var(REDACT_KEYS_ENV) {
+            Ok(keys) => Self::new(keys.split(',')),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn is_sensitive(&self, key: &str) -> bool {
+        let lowered = key.to_lowercase();
+        let words = words(key);
+        self.sensitive_keys
+            .iter()
+            .any(|sensitive| *sensitive == lowered || words.contains(sensitive))
+    }
+
+    /// A copy of `value` with every sensitive value replaced by [`REDACTED`]
+    pub fn redact(&self, value: &Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| {
+                        let value = if self.is_sensitive(key) {
+                            Value::String(REDACTED.to_string())
+                        } else {
+                            self.redact(value)
+                        };
+                        (key.clone(), value)
+                    })
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.redact(v)).collect()),
+            other => other.clone(),
+        }
+    }
+}
+
+/// The process-wide redactor, read from the environment on first use
+pub fn global() -> &'static Redactor {
+    static REDACTOR: OnceLock<Redactor> = OnceLock::new();
+    REDACTOR.get_or_init(Redactor::from_env)
+}
@@ -0,0 +1,148 @@
+//! `repeat_until` loops.
+//!
+//! A group step with `repeat_until` re-runs its steps until the `expression_eval`
+//! condition holds. The condition is checked after each iteration, so the body always
+//! runs at least once. `max_iterations` and an optional `loop_timeout` stop loops whose
+//! condition never becomes true. The current iteration (starting at 1) is exposed as
+//! `env.<loop_variable>`, which defaults to `env.iteration`. The condition is compiled
+//! once per loop rather than parsed again on every iteration.
+
+use crate::expression_eval::{self, Expression};
+use crate::utils::SequenceStep;
+use serde_json::{json, Map, Value};
+use std::time::{Duration, Instant};
+
+/// Iteration cap used when a loop does not set `max_iterations`
+pub const DEFAULT_MAX_ITERATIONS: u32 = 100;
+
+/// Name of the loop variable when a loop does not set `loop_variable`
+pub const DEFAULT_LOOP_VARIABLE: &str = "iteration";
+
+/// The loop settings of a `repeat_until` step
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepeatUntil {
+    pub condition: String,
+    pub max_iterations: u32,
+    pub timeout: Option<Duration>,
+    pub variable: String,
+}
+
+impl RepeatUntil {
+    /// Reads the loop settings from a step. Returns `Ok(None)` for steps without `repeat_until`.
+    pub fn from_step(step: &SequenceStep) -> Result<Option<Self>, String> {
+        let Some(condition) = &step.repeat_until else {
+            return Ok(None);
+        };
+        if step.group_name.is_none() {
+            return Err("repeat_until is only supported on group steps".to_string());
+        }
+        Expression::compile(condition)
+            .map_err(|e| format!("Invalid repeat_until '{condition}': {e}"))?;
+        let max_iterations = step.max_iterations.unwrap_or(DEFAULT_MAX_ITERATIONS);
+        if max_iterations == 0 {
+            return Err("max_iterations must be at least 1".to_string());
+        }
+        let timeout = step
+            .loop_timeout
+            .as_deref()
+            .map(|t| {
+                crate::duration_parser::parse_std_duration(t)
+                    .map_err(|e| format!("Invalid loop_timeout '{t}': {e}"))
+            })
+            .transpose()?;
+        Ok(Some(Self {
+            condition: condition.clone(),
+            max_iterations,
+            timeout,
+            variable: step
+                .loop_variable
+                .clone()
+                .unwrap_or_else(|| DEFAULT_LOOP_VARIABLE.to_string()),
+        }))
+    }
+}
+
+/// Why a loop stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopExit {
+    ConditionMet,
+    MaxIterations,
+    TimedOut,
+}
+
+impl LoopExit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoopExit::ConditionMet => "condition_met",
+            LoopExit::MaxIterations => "max_iterations",
+            LoopExit::TimedOut => "timeout",
+        }
+    }
+
+    /// Whether the loop ended because a safeguard tripped rather than its condition
+    pub fn is_guard(&self) -> bool {
+        !matches!(self, LoopExit::ConditionMet)
+    }
+}
+
+/// Tracks one run of a loop
+#[derive(Debug)]
+pub struct LoopGuard<'a> {
+    config: &'a RepeatUntil,
+    /// None when the condition does not compile; it is then evaluated as text
+    condition: Option<Expression>,
+    iteration: u32,
+    started: Instant,
+}
+
+impl<'a> LoopGuard<'a> {
+    pub fn new(config: &'a RepeatUntil) -> Self {
+        Self {
+            config,
+            condition: Expression::compile(&config.condition).ok(),
+            iteration: 0,
+            started: Instant::now(),
+        }
+    }
+
+    /// Number of iterations started so far
+    pub fn iteration(&self) -> u32 {
+        self.iteration
+    }
+
+    /// Starts the next iteration and records it as the loop variable in `context["env"]`.
+    pub fn start_iteration(&mut self, context: &mut Map<String, Value>) -> u32 {
+        self.iteration += 1;
+        if !context.get("env").is_some_and(|env| env.is_object()) {
+            context.insert("env".to_string(), json!({}));
+        }
+        if let Some(env) = context.get_mut("env").and_then(|env| env.as_object_mut()) {
+            env.insert(self.config.variable.clone(), json!(self.iteration));
+        }
+        self.iteration
+    }
+
+    /// Decides whether the loop stops after the iteration that just ran.
+    ///
+    /// The condition takes priority, so a loop whose last allowed iteration meets it
+    /// still reports `ConditionMet`.
+    pub fn after_iteration(&self, variables: &Value) -> Option<LoopExit> {
+        let condition_met = match &self.condition {
+            Some(condition) => condition.evaluate(variables),
+            None => expression_eval::evaluate(&self.config.condition, variables),
+        };
+        if condition_met {
+            Some(LoopExit::ConditionMet)
+        } else if self.iteration >= self.config.max_iterations {
+            Some(LoopExit::MaxIterations)
+        } else if self
+            .config
+            .timeout
+            .is_some_and(|timeout| self.started.elapsed() >= timeout)
+        {
+            Some(LoopExit::TimedOut)
+        } else {
+            None
+        }
+    }
+}
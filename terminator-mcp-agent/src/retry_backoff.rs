@@ -0,0 +1,136 @@
+//! Delays between step retries.
+//!
+//! Without a `retry` block, a step is retried `retries` times with a fixed 500ms pause.
+//! A `retry` block switches to exponential backoff: the n-th retry waits
+//! `initial_delay * multiplier^(n-1)`, spread by up to `jitter` (a fraction of the delay)
+//! in either direction so that several workflows retrying together don't stay in step.
+//! Waits end early when the request is cancelled.
+
+use crate::utils::SequenceStep;
+use rmcp::{schemars, schemars::JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Pause between attempts for steps without a `retry` block
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Multiplier used when a `retry` block does not set one
+pub const DEFAULT_MULTIPLIER: f64 = 2.0;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RetryConfig {
+    #[schemars(
+        description = "Total number of attempts, including the first. Defaults to 'retries' + 1, or 3 without 'retries'."
+    )]
+    pub max_attempts: Option<u32>,
+    #[schemars(
+        description = "Wait before the first retry, e.g. '500ms' or '2s'. Defaults to 500ms."
+    )]
+    pub initial_delay: Option<String>,
+    #[schemars(
+        description = "Factor the wait grows by after each retry (at least 1). Defaults to 2."
+    )]
+    pub multiplier: Option<f64>,
+    #[schemars(
+        description = "Random spread of each wait, as a fraction of it between 0 and 1: 0.2 waits between 80% and 120% of the computed delay. Defaults to 0."
+    )]
+    pub jitter: Option<f64>,
+}
+
+/// The retry settings of a step, resolved from `retries` and `retry`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryBackoff {
+    pub max_attempts: u32,
+    pub initial_delay: Duration,
+    pub multiplier: f64,
+    pub jitter: f64,
+}
+
+impl RetryBackoff {
+    /// Reads the retry settings from a step
+    pub fn from_step(step: &SequenceStep) -> Result<Self, String> {
+        let Some(config) = &step.retry else {
+            return Ok(Self {
+                max_attempts: step.retries.unwrap_or(0).saturating_add(1),
+                initial_delay: DEFAULT_RETRY_DELAY,
+                multiplier: 1.0,
+                jitter: 0.0,
+            });
+        };
+
+        let max_attempts = config
+            .max_attempts
+            .or(step.retries.map(|r| r.saturating_add(1)))
+            .unwrap_or(3);
+        if max_attempts == 0 {
+            return Err("retry.max_attempts must be at least 1".to_string());
+        }
+        let initial_delay = config
+            .initial_delay
+            .as_deref()
+            .map(|d| {
+                crate::duration_parser::parse_std_duration(d)
+                    .map_err(|e| format!("Invalid retry.initial_delay '{d}': {e}"))
+            })
+            .transpose()?
+            .unwrap_or(DEFAULT_RETRY_DELAY);
+        let multiplier = config.multiplier.unwrap_or(DEFAULT_MULTIPLIER);
+        if !multiplier.is_finite() || multiplier < 1.0 {
+            return Err(format!(
+                "retry.multiplier must be at least 1, got {multiplier}"
+            ));
+        }
+        let jitter = config.jitter.unwrap_or(0.0);
+        if !(0.0..=1.0).contains(&jitter) {
+            return Err(format!(
+                "retry.jitter must be between 0 and 1, got {jitter}"
+            ));
+        }
+
+        Ok(Self {
+            max_attempts,
+            initial_delay,
+            multiplier,
+            jitter,
+        })
+    }
+
+    /// Number of retries after the first attempt
+    pub fn retries(&self) -> u32 {
+        self.max_attempts - 1
+    }
+
+    /// The wait before the given retry (1 for the first), without jitter
+    pub fn base_delay(&self, retry: u32) -> Duration {
+        let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let secs = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        capped(secs)
+    }
+
+    /// The wait before the given retry, with jitter applied using `random` in `[0, 1]`:
+    /// 0 gives the shortest wait, 1 the longest
+    pub fn delay(&self, retry: u32, random: f64) -> Duration {
+        let factor = 1.0 - self.jitter + 2.0 * self.jitter * random.clamp(0.0, 1.0);
+        capped(self.base_delay(retry).as_secs_f64() * factor)
+    }
+
+    /// The wait before the given retry, with random jitter
+    pub fn next_delay(&self, retry: u32) -> Duration {
+        self.delay(retry, rand::random())
+    }
+}
+
+/// Keeps runaway backoff within what a timer can wait for
+fn capped(secs: f64) -> Duration {
+    let max = Duration::from_millis(crate::duration_parser::MAX_DURATION_MS);
+    Duration::try_from_secs_f64(secs).map_or(max, |delay| delay.min(max))
+}
+
+/// Waits for `delay` unless the token is cancelled first. Returns `false` when cancelled.
+pub async fn wait_for_retry(delay: Duration, cancellation: &CancellationToken) -> bool {
+    tokio::select! {
+        _ = tokio::time::sleep(delay) => true,
+        _ = cancellation.cancelled() => false,
+    }
+}
@@ -0,0 +1,349 @@
+//! Reproducible run bundles.
+//!
+//! A bundle captures everything needed to reproduce a workflow run without the original
+//! desktop: the workflow definition (with its inputs), the variables the run started
+//! from, a trace of every tool call made with the response it returned, and the run's
+//! outcome. Replaying a bundle runs the workflow through the sequence runner again from
+//! the same variables, with the tools answered from the trace instead of the live
+//! desktop, so a bug report can ship as a single self-contained file.
+
+use crate::utils::{DesktopWrapper, ExecuteSequenceArgs};
+use rmcp::model::CallToolResult;
+use rmcp::ErrorData as McpError;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::future::Future;
+use std::path::Path;
+use std::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// Current bundle format version. Bump when the layout changes incompatibly.
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// One recorded tool call
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TraceEntry {
+    pub tool_name: String,
+    /// Arguments the tool was called with
+    pub arguments: Value,
+    /// What the tool returned; missing when the call was cancelled before it returned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response: Option<ToolResponse>,
+}
+
+/// A tool's response as the sequence runner received it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolResponse {
+    Result(CallToolResult),
+    Error(McpError),
+}
+
+impl ToolResponse {
+    pub fn from_result(result: &Result<CallToolResult, McpError>) -> Self {
+        match result {
+            Ok(result) => Self::Result(result.clone()),
+            Err(error) => Self::Error(error.clone()),
+        }
+    }
+
+    pub fn into_result(self) -> Result<CallToolResult, McpError> {
+        match self {
+            Self::Result(result) => Ok(result),
+            Self::Error(error) => Err(error),
+        }
+    }
+}
+
+/// The observable result of a run: the final status and the status of every executed step
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RunOutcome {
+    pub status: String,
+    pub step_statuses: Vec<String>,
+}
+
+impl RunOutcome {
+    /// Extracts the outcome from an `execute_sequence` summary
+    pub fn from_summary(summary: &Value) -> Self {
+        Self {
+            status: summary["status"].as_str().unwrap_or_default().to_string(),
+            step_statuses: summary["results"]
+                .as_array()
+                .map(|results| {
+                    results
+                        .iter()
+                        .map(|r| r["status"].as_str().unwrap_or_default().to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunBundle {
+    pub format_version: u32,
+    pub agent_version: String,
+    pub created_at: String,
+    /// The workflow as executed, with remote definitions already inlined
+    pub workflow: ExecuteSequenceArgs,
+    /// The variables the run started from: inputs over defaults, persisted values and
+    /// the saved state of a resumed run, none of which a replay can load again. Missing
+    /// from bundles written before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_context: Option<Map<String, Value>>,
+    pub trace: Vec<TraceEntry>,
+    pub outcome: RunOutcome,
+}
+
+impl RunBundle {
+    pub fn new(
+        mut workflow: ExecuteSequenceArgs,
+        initial_context: Map<String, Value>,
+        trace: Vec<TraceEntry>,
+        outcome: RunOutcome,
+    ) -> Self {
+        // The steps are inlined, so the bundle must not depend on the original location,
+        // and replaying it must not export over the bundle
+        workflow.url = None;
+        workflow.export_bundle = None;
+        Self {
+            format_version: BUNDLE_FORMAT_VERSION,
+            agent_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+            workflow,
+            initial_context: Some(initial_context),
+            trace,
+            outcome,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize bundle: {e}"))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let bundle: RunBundle =
+            serde_json::from_str(json).map_err(|e| format!("Invalid run bundle: {e}"))?;
+        if bundle.format_version > BUNDLE_FORMAT_VERSION {
+            return Err(format!(
+                "Run bundle format version {} is newer than the supported version {}",
+                bundle.format_version, BUNDLE_FORMAT_VERSION
+            ));
+        }
+        Ok(bundle)
+    }
+
+    pub async fn write_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            if !parent.as_os_str().is_empty() {
+                tokio::fs::create_dir_all(parent)
+                    .await
+                    .map_err(|e| format!("Failed to create bundle directory: {e}"))?;
+            }
+        }
+        tokio::fs::write(path, self.to_json()?)
+            .await
+            .map_err(|e| format!("Failed to write bundle to {}: {e}", path.display()))
+    }
+
+    pub async fn read_from(path: &Path) -> Result<Self, String> {
+        let json = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| format!("Failed to read bundle from {}: {e}", path.display()))?;
+        Self::from_json(&json)
+    }
+}
+
+/// Runs the tools of a workflow for the sequence runner
+pub trait ToolBackend: Sync {
+    /// Runs one tool. `variables` are the workflow variables when the step started, for
+    /// tools that evaluate expressions over them. `cancellation` is cancelled when the
+    /// call is no longer wanted, such as when its step times out.
+    fn call_tool(
+        &self,
+        tool_name: &str,
+        arguments: &Value,
+        variables: &Value,
+        cancellation: CancellationToken,
+    ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send;
+}
+
+/// Passes calls on to another backend and, when recording, traces every call it serves
+pub struct RecordingBackend<'a, B> {
+    inner: &'a B,
+    trace: Option<Mutex<Vec<TraceEntry>>>,
+}
+
+impl<'a, B: ToolBackend> RecordingBackend<'a, B> {
+    pub fn new(inner: &'a B, record: bool) -> Self {
+        Self {
+            inner,
+            trace: record.then(|| Mutex::new(Vec::new())),
+        }
+    }
+
+    /// The recorded calls in the order they were made, if recording
+    pub fn into_trace(self) -> Option<Vec<TraceEntry>> {
+        self.trace
+            .map(|trace| trace.into_inner().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+impl<B: ToolBackend> ToolBackend for RecordingBackend<'_, B> {
+    async fn call_tool(
+        &self,
+        tool_name: &str,
+        arguments: &Value,
+        variables: &Value,
+        cancellation: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        let Some(trace) = &self.trace else {
+            return self
+                .inner
+                .call_tool(tool_name, arguments, variables, cancellation)
+                .await;
+        };
+        // Entered before the call, so a call dropped on cancellation stays without a
+        // response
+        let slot = {
+            let mut trace = trace.lock().unwrap_or_else(|e| e.into_inner());
+            trace.push(TraceEntry {
+                tool_name: tool_name.to_string(),
+                arguments: arguments.clone(),
+                response: None,
+            });
+            trace.len() - 1
+        };
+        let result = self
+            .inner
+            .call_tool(tool_name, arguments, variables, cancellation)
+            .await;
+        trace.lock().unwrap_or_else(|e| e.into_inner())[slot].response =
+            Some(ToolResponse::from_result(&result));
+        result
+    }
+}
+
+/// Answers tool calls from a recorded trace.
+///
+/// A call gets the response of the first unused entry with the same tool and arguments,
+/// so the steps of a parallel group may finish in any order. A call the trace has no
+/// entry for is answered with an error and noted as a divergence.
+pub struct ReplayBackend {
+    entries: Mutex<Vec<Option<TraceEntry>>>,
+    divergence: Mutex<Option<String>>,
+}
+
+impl ReplayBackend {
+    pub fn new(trace: &[TraceEntry]) -> Self {
+        Self {
+            entries: Mutex::new(trace.iter().cloned().map(Some).collect()),
+            divergence: Mutex::new(None),
+        }
+    }
+
+    /// Number of recorded calls not consumed by the replay
+    pub fn remaining(&self) -> usize {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.iter().filter(|entry| entry.is_some()).count()
+    }
+
+    /// The first call the trace had no entry for
+    pub fn divergence(&self) -> Option<String> {
+        self.divergence
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn take(&self, tool_name: &str, arguments: &Value) -> Option<TraceEntry> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .iter_mut()
+            .find(|entry| {
+                entry
+                    .as_ref()
+                    .is_some_and(|e| e.tool_name == tool_name && &e.arguments == arguments)
+            })
+            .and_then(Option::take)
+    }
+}
+
+impl ToolBackend for ReplayBackend {
+    async fn call_tool(
+        &self,
+        tool_name: &str,
+        arguments: &Value,
+        _variables: &Value,
+        cancellation: CancellationToken,
+    ) -> Result<CallToolResult, McpError> {
+        match self.take(tool_name, arguments) {
+            Some(TraceEntry {
+                response: Some(response),
+                ..
+            }) => response.into_result(),
+            // The recorded call was cancelled before it returned; it is again once
+            // the run no longer wants it
+            Some(TraceEntry { response: None, .. }) => {
+                cancellation.cancelled().await;
+                Err(McpError::internal_error("Tool call cancelled", None))
+            }
+            None => {
+                let message = format!(
+                    "Replay diverged: '{tool_name}' was called with {arguments}, which the trace does not contain"
+                );
+                self.divergence
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .get_or_insert_with(|| message.clone());
+                Err(McpError::internal_error(message, None))
+            }
+        }
+    }
+}
+
+/// Replays a bundle through `server`'s sequence runner against its recorded trace.
+///
+/// Fails if the workflow makes a call the trace does not contain, leaves recorded calls
+/// unused, or ends with a different outcome than the recorded one.
+pub async fn replay(server: &DesktopWrapper, bundle: &RunBundle) -> Result<RunOutcome, String> {
+    let backend = ReplayBackend::new(&bundle.trace);
+    let mut workflow = bundle.workflow.clone();
+    // Dialogs the watcher dismissed are not tool calls and cannot be replayed, and a
+    // replay must not overwrite the persistent variables of real runs
+    workflow.modal_watcher = None;
+    workflow.persistence_key = None;
+    let cancellation = CancellationToken::new();
+    let summary = match &bundle.initial_context {
+        Some(context) => {
+            server
+                .execute_sequence_from(&backend, &cancellation, workflow, context.clone())
+                .await
+        }
+        None => {
+            server
+                .execute_sequence_with(&backend, &cancellation, workflow)
+                .await
+        }
+    }
+    .map_err(|e| format!("Replay failed: {e}"))?;
+
+    if let Some(divergence) = backend.divergence() {
+        return Err(divergence);
+    }
+    if backend.remaining() > 0 {
+        return Err(format!(
+            "Replay finished with {} recorded call(s) unused",
+            backend.remaining()
+        ));
+    }
+    let outcome = RunOutcome::from_summary(&summary);
+    if outcome != bundle.outcome {
+        return Err(format!(
+            "Replay outcome {outcome:?} differs from recorded outcome {:?}",
+            bundle.outcome
+        ));
+    }
+    Ok(outcome)
+}
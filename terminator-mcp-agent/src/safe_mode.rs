@@ -0,0 +1,238 @@
+//! Safe mode: destructive actions need an explicit confirmation.
+//!
+//! When `TERMINATOR_SAFE_MODE` is set, tool calls classified as destructive (closing
+//! windows, clicking delete/submit buttons, running commands, ...) are rejected with a
+//! `ConfirmationRequired` error unless their arguments include `"confirmed": true`.
+//! This keeps a client from doing irreversible things unprompted.
+//!
+//! A call is destructive when its tool is in the destructive tool list, when it is an
+//! element action whose selectors mention one of the destructive keywords, or when it
+//! sends one of the destructive keys to the keyboard. The lists can be replaced with
+//! comma-separated values in `TERMINATOR_SAFE_MODE_TOOLS`,
+//! `TERMINATOR_SAFE_MODE_KEYWORDS` and `TERMINATOR_SAFE_MODE_KEYS`.
+
+use serde_json::{json, Value};
+use std::sync::OnceLock;
+
+pub const SAFE_MODE_ENV: &str = "TERMINATOR_SAFE_MODE";
+pub const DESTRUCTIVE_TOOLS_ENV: &str = "TERMINATOR_SAFE_MODE_TOOLS";
+pub const DESTRUCTIVE_KEYWORDS_ENV: &str = "TERMINATOR_SAFE_MODE_KEYWORDS";
+pub const DESTRUCTIVE_KEYS_ENV: &str = "TERMINATOR_SAFE_MODE_KEYS";
+
+/// Tools that are destructive regardless of their arguments. A command can do anything.
+pub const DEFAULT_DESTRUCTIVE_TOOLS: &[&str] = &["close_element", "run_command"];
+
+/// Words that make an element action destructive when they appear in its selectors
+pub const DEFAULT_DESTRUCTIVE_KEYWORDS: &[&str] = &[
+    "delete",
+    "remove",
+    "submit",
+    "close",
+    "discard",
+    "uninstall",
+];
+
+/// Keys that delete or close whatever has focus, in the `{Key}` syntax of the key tools
+pub const DEFAULT_DESTRUCTIVE_KEYS: &[&str] =
+    &["{delete}", "{del}", "{alt}{f4}", "{ctrl}w", "{ctrl}{w}"];
+
+/// Tools whose target element is checked against the destructive keywords
+const ELEMENT_ACTION_TOOLS: &[&str] = &[
+    "click_element",
+    "invoke_element",
+    "press_key",
+    "mouse_drag",
+    "drag_and_drop",
+    "set_selected",
+    "set_toggled",
+];
+
+/// Tools that send keys, with the arguments holding them, checked against the
+/// destructive keys. Those acting on the focused element have no selector to check.
+const KEY_ARGUMENTS: &[(&str, &str)] = &[
+    ("press_key", "key"),
+    ("press_key_global", "key"),
+    ("press_keys", "keys"),
+    ("type_into_focused", "text"),
+];
+
+const SELECTOR_ARGUMENTS: &[&str] = &[
+    "selector",
+    "alternative_selectors",
+    "fallback_selectors",
+    "source_selector",
+    "target_selector",
+];
+
+/// A destructive call that was made without `confirmed: true`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfirmationRequired {
+    pub tool_name: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for ConfirmationRequired {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "'{}' requires confirmation in safe mode: {}",
+            self.tool_name, self.reason
+        )
+    }
+}
+
+impl std::error::Error for ConfirmationRequired {}
+
+impl From<ConfirmationRequired> for rmcp::ErrorData {
+    fn from(err: ConfirmationRequired) -> Self {
+        rmcp::ErrorData::invalid_request(
+            "ConfirmationRequired",
+            Some(json!({
+                "error_type": "ConfirmationRequired",
+                "tool_name": err.tool_name,
+                "reason": err.reason,
+                "suggestion": "Ask the user to approve this action, then repeat the call with \"confirmed\": true.",
+            })),
+        )
+    }
+}
+
+/// Safe mode settings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SafeMode {
+    pub enabled: bool,
+    pub destructive_tools: Vec<String>,
+    pub destructive_keywords: Vec<String>,
+    pub destructive_keys: Vec<String>,
+}
+
+impl Default for SafeMode {
+    /// Safe mode turned off, with the default classification lists
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            destructive_tools: to_strings(DEFAULT_DESTRUCTIVE_TOOLS),
+            destructive_keywords: to_strings(DEFAULT_DESTRUCTIVE_KEYWORDS),
+            destructive_keys: to_strings(DEFAULT_DESTRUCTIVE_KEYS),
+        }
+    }
+}
+
+fn to_strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+}
+
+fn parse_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().to_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+impl SafeMode {
+    /// Safe mode turned on, with the default classification lists
+    pub fn enabled() -> Self {
+        Self {
+            enabled: true,
+            ..Self::default()
+        }
+    }
+
+    /// Reads the settings from `TERMINATOR_SAFE_MODE` and the list overrides.
+    pub fn from_env() -> Self {
+        let enabled = std::env::var(SAFE_MODE_ENV)
+            .map(|v| {
+                matches!(
+                    v.trim().to_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+            .unwrap_or(false);
+        let mut mode = Self {
+            enabled,
+            ..Self::default()
+        };
+        if let Ok(tools) = std::env::var(DESTRUCTIVE_TOOLS_ENV) {
+            mode.destructive_tools = parse_list(&tools);
+        }
+        if let Ok(keywords) = std::env::var(DESTRUCTIVE_KEYWORDS_ENV) {
+            mode.destructive_keywords = parse_list(&keywords);
+        }
+        if let Ok(keys) = std::env::var(DESTRUCTIVE_KEYS_ENV) {
+            mode.destructive_keys = parse_list(&keys);
+        }
+        mode
+    }
+
+    /// Explains why a call is destructive, or returns `None` when it is not.
+    pub fn classify(&self, tool_name: &str, arguments: &Value) -> Option<String> {
+        if self
+            .destructive_tools
+            .iter()
+            .any(|t| t.eq_ignore_ascii_case(tool_name))
+        {
+            return Some(format!("'{tool_name}' is classified as destructive"));
+        }
+        if let Some(reason) = self.destructive_key(tool_name, arguments) {
+            return Some(reason);
+        }
+        if !ELEMENT_ACTION_TOOLS.contains(&tool_name) {
+            return None;
+        }
+        SELECTOR_ARGUMENTS
+            .iter()
+            .filter_map(|key| arguments.get(*key).and_then(|v| v.as_str()))
+            .find_map(|selector| {
+                let lowered = selector.to_lowercase();
+                self.destructive_keywords
+                    .iter()
+                    .find(|keyword| lowered.contains(keyword.as_str()))
+                    .map(|keyword| {
+                        format!("target '{selector}' matches destructive keyword '{keyword}'")
+                    })
+            })
+    }
+
+    /// The destructive key a key-sending call would press, described
+    fn destructive_key(&self, tool_name: &str, arguments: &Value) -> Option<String> {
+        let (_, key_argument) = KEY_ARGUMENTS.iter().find(|(tool, _)| *tool == tool_name)?;
+        let sent: Vec<&str> = match arguments.get(*key_argument)? {
+            Value::String(keys) => vec![keys.as_str()],
+            Value::Array(keys) => keys.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        sent.into_iter().find_map(|keys| {
+            // `{Ctrl} W` and `{ctrl}w` are the same combination
+            let normalized: String = keys
+                .to_lowercase()
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect();
+            self.destructive_keys
+                .iter()
+                .find(|key| normalized.contains(key.as_str()))
+                .map(|key| format!("keys '{keys}' include destructive key '{key}'"))
+        })
+    }
+
+    /// Rejects destructive calls that do not carry `"confirmed": true`.
+    pub fn check(&self, tool_name: &str, arguments: &Value) -> Result<(), ConfirmationRequired> {
+        if !self.enabled || arguments.get("confirmed").and_then(|v| v.as_bool()) == Some(true) {
+            return Ok(());
+        }
+        match self.classify(tool_name, arguments) {
+            Some(reason) => Err(ConfirmationRequired {
+                tool_name: tool_name.to_string(),
+                reason,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The process-wide settings, read from the environment on first use
+pub fn global() -> &'static SafeMode {
+    static SAFE_MODE: OnceLock<SafeMode> = OnceLock::new();
+    SAFE_MODE.get_or_init(SafeMode::from_env)
+}
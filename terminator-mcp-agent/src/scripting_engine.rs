@@ -1,6 +1,9 @@
+use crate::duration_parser::parse_std_duration;
 use rmcp::ErrorData as McpError;
+use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::path::PathBuf;
+use std::time::Duration;
 use tracing::{debug, error, info, warn};
 
 /// Find executable with cross-platform path resolution
@@ -869,10 +872,254 @@ async fn ensure_terminator_js_installed(runtime: &str) -> Result<std::path::Path
     }
 }
 
+/// A language `run_command` can run engine scripts in.
+///
+/// Every language follows the same contract: the workflow's `env` and `variables` are
+/// defined before the script runs, and the value it `return`s comes back as JSON.
+///
+/// A Python script is the body of an `async def`: use `return` for the result and
+/// `await` for async calls, such as the `sleep(ms)` helper; `log(..., level='log')` adds
+/// a line to the step's logs. Scripts that get `desktop` (see [`desktop_allowed`]) run on the system
+/// `python` (or `python3`) with terminator.py installed, and `desktop` is a ready
+/// `terminator.Desktop()`. When the crate is built with the `embedded-python` feature,
+/// scripts without `desktop` or an explicit `timeout` run in an embedded interpreter
+/// instead, as described in the `embedded_python` module; without it they run on the
+/// system `python` too.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptLanguage {
+    JavaScript,
+    TypeScript,
+    Python,
+}
+
+impl ScriptLanguage {
+    /// The language of a `run_command` engine name, case-insensitively
+    pub fn from_engine(engine: &str) -> Option<Self> {
+        match engine.to_ascii_lowercase().as_str() {
+            "node" | "bun" | "javascript" | "js" => Some(Self::JavaScript),
+            "typescript" | "ts" => Some(Self::TypeScript),
+            "python" | "py" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::JavaScript => "JavaScript",
+            Self::TypeScript => "TypeScript",
+            Self::Python => "Python",
+        }
+    }
+}
+
+/// Severity of a [`ScriptLogLine`], named after the `console` method that wrote it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScriptLogLevel {
+    Debug,
+    Info,
+    Log,
+    Warn,
+    Error,
+}
+
+/// One line of script output, returned in the order the script wrote it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptLogLine {
+    pub level: ScriptLogLevel,
+    pub message: String,
+}
+
+/// Prefix of the stdout lines that carry a `console` call
+const LOG_MARKER: &str = "__LOG__";
+
+/// Routes every `console` method through stdout as one `__LOG__` line per call, so
+/// `console.error` keeps its place among the other lines instead of going to stderr.
+/// The wrapper's own messages use `__console`, the untouched originals.
+const CONSOLE_CAPTURE_JS: &str = r#"
+const __console = { log: console.log.bind(console), error: console.error.bind(console) };
+const __format = require('util').format;
+for (const level of ['debug', 'info', 'log', 'warn', 'error']) {
+    console[level] = (...args) => {
+        process.stdout.write('__LOG__' + JSON.stringify({ level, message: __format(...args) }) + '\n');
+    };
+}
+"#;
+
+/// Python's counterpart of [`CONSOLE_CAPTURE_JS`]: `log(*args, level='log')` prints one
+/// `__LOG__` line, where `level` is one of the [`ScriptLogLevel`]s. It is defined inside
+/// the function the script runs in, so it prints with that scope's `print`.
+pub(crate) const LOG_HELPER_PY: &str = r#"
+    def log(*args, level='log', sep=' '):
+        if level not in ('debug', 'info', 'log', 'warn', 'error'):
+            level = 'log'
+        print('__LOG__' + json.dumps({'level': level, 'message': sep.join(str(a) for a in args)}))
+"#;
+
+impl ScriptLogLine {
+    /// Reads a line of script stdout: a captured `console` call, or output the script
+    /// wrote some other way, such as `process.stdout.write`, as a `log` line.
+    pub fn from_stdout(line: &str) -> Self {
+        line.strip_prefix(LOG_MARKER)
+            .and_then(|captured| serde_json::from_str(captured).ok())
+            .unwrap_or_else(|| Self {
+                level: ScriptLogLevel::Log,
+                message: line.to_string(),
+            })
+    }
+}
+
+/// Adds the lines logged before a script failed to its error data
+pub(crate) fn with_logs(
+    error_data: serde_json::Value,
+    logs: &[ScriptLogLine],
+) -> serde_json::Value {
+    match error_data {
+        serde_json::Value::Object(mut obj) => {
+            obj.insert("logs".to_string(), json!(logs));
+            serde_json::Value::Object(obj)
+        }
+        other => json!({"error": other, "logs": logs}),
+    }
+}
+
+/// Time limit for engine scripts whose call sets no `timeout`
+pub const DEFAULT_SCRIPT_TIMEOUT: &str = "10m";
+
+/// Environment variable overriding [`DEFAULT_SCRIPT_TIMEOUT`]
+pub const SCRIPT_TIMEOUT_ENV: &str = "TERMINATOR_SCRIPT_TIMEOUT";
+
+/// The time limit of a script run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptTimeout {
+    pub limit: Duration,
+    /// Whether the call or [`SCRIPT_TIMEOUT_ENV`] set the limit, rather than the default
+    pub explicit: bool,
+}
+
+impl From<Duration> for ScriptTimeout {
+    fn from(limit: Duration) -> Self {
+        Self {
+            limit,
+            explicit: true,
+        }
+    }
+}
+
+/// The time limit for a script: `requested`, else [`SCRIPT_TIMEOUT_ENV`], else
+/// [`DEFAULT_SCRIPT_TIMEOUT`], each a duration such as "30s" or "1h30m".
+pub fn script_timeout(requested: Option<&str>) -> Result<ScriptTimeout, McpError> {
+    let timeout = match requested {
+        Some(requested) => parse_std_duration(requested)
+            .map(ScriptTimeout::from)
+            .map_err(|e| {
+                McpError::invalid_params(
+                    format!("Invalid timeout '{requested}': {e}"),
+                    Some(json!({"timeout": requested})),
+                )
+            })?,
+        None => match std::env::var(SCRIPT_TIMEOUT_ENV) {
+            Ok(configured) => parse_std_duration(&configured)
+                .map(ScriptTimeout::from)
+                .unwrap_or_else(|e| {
+                    warn!(
+                        "Ignoring invalid {}='{}': {}",
+                        SCRIPT_TIMEOUT_ENV, configured, e
+                    );
+                    default_script_timeout()
+                }),
+            Err(_) => default_script_timeout(),
+        },
+    };
+    if timeout.limit.is_zero() {
+        return Err(McpError::invalid_params(
+            "Script timeout must be greater than zero",
+            requested.map(|t| json!({"timeout": t})),
+        ));
+    }
+    Ok(timeout)
+}
+
+fn default_script_timeout() -> ScriptTimeout {
+    ScriptTimeout {
+        limit: parse_std_duration(DEFAULT_SCRIPT_TIMEOUT)
+            .expect("DEFAULT_SCRIPT_TIMEOUT is a valid duration"),
+        explicit: false,
+    }
+}
+
+/// Whether an engine script gets the `desktop` object: only when its step sets
+/// `allow_desktop: true`, since `desktop` can act on the UI.
+pub fn desktop_allowed(allow_desktop: Option<bool>) -> bool {
+    allow_desktop == Some(true)
+}
+
+/// Runs `script` with the runtime of `language`.
+///
+/// Returns `{result, logs}`, where `logs` are the [`ScriptLogLine`]s the script wrote
+/// with `console` in JavaScript and TypeScript, or with `log` and `print` in Python. A
+/// script that fails has the lines it logged first in its error data. The script gets a
+/// `desktop` object only when `desktop` is set (see [`desktop_allowed`]). The
+/// cancellation token stops JavaScript and TypeScript runs. A script still running
+/// after `timeout` has its process killed, so even a busy loop is stopped, and the call
+/// fails with a `ScriptTimeout` error. The embedded interpreter cannot stop a script
+/// part-way, so only Python scripts without an explicit timeout run there, to completion.
+pub async fn run_script(
+    language: ScriptLanguage,
+    script: String,
+    desktop: bool,
+    cancellation_token: Option<tokio_util::sync::CancellationToken>,
+    timeout: impl Into<ScriptTimeout>,
+) -> Result<serde_json::Value, McpError> {
+    let timeout = timeout.into();
+    #[cfg(feature = "embedded-python")]
+    if language == ScriptLanguage::Python && !desktop && !timeout.explicit {
+        return crate::embedded_python::run_python_embedded(script).await;
+    }
+    let run = async {
+        match language {
+            ScriptLanguage::JavaScript => run_javascript(script, cancellation_token, desktop).await,
+            ScriptLanguage::TypeScript => run_typescript(script, cancellation_token, desktop).await,
+            ScriptLanguage::Python => run_python(script, desktop).await,
+        }
+    };
+    // Dropping the run on timeout drops its child process, which is spawned with kill_on_drop
+    match tokio::time::timeout(timeout.limit, run).await {
+        Ok(result) => result,
+        Err(_) => {
+            warn!(
+                "[{}] Script did not finish within {:?}, killed it",
+                language.name(),
+                timeout.limit
+            );
+            Err(McpError::internal_error(
+                format!(
+                    "ScriptTimeout: {} script did not finish within {}ms",
+                    language.name(),
+                    timeout.limit.as_millis()
+                ),
+                Some(json!({
+                    "reason": "script_timeout",
+                    "timeout_ms": timeout.limit.as_millis() as u64,
+                })),
+            ))
+        }
+    }
+}
+
 /// Execute JavaScript using Node.js/Bun runtime with terminator.js bindings available
 pub async fn execute_javascript_with_nodejs(
     script: String,
     cancellation_token: Option<tokio_util::sync::CancellationToken>,
+) -> Result<serde_json::Value, McpError> {
+    run_javascript(script, cancellation_token, true).await
+}
+
+/// Runs JavaScript with Node.js/Bun, defining the global `desktop` only when `desktop` is set
+async fn run_javascript(
+    script: String,
+    cancellation_token: Option<tokio_util::sync::CancellationToken>,
+    desktop: bool,
 ) -> Result<serde_json::Value, McpError> {
     // Dev override: allow forcing local bindings via env var
     if std::env::var("TERMINATOR_JS_USE_LOCAL")
@@ -885,13 +1132,13 @@ pub async fn execute_javascript_with_nodejs(
         .unwrap_or(false)
     {
         info!("[Node.js] Using local bindings due to TERMINATOR_JS_USE_LOCAL env var");
-        return execute_javascript_with_local_bindings(script).await;
+        return run_javascript_with_local_bindings(script, desktop).await;
     }
 
     // In tests with TERMINATOR_SKIP_NPM_INSTALL, use local bindings instead
     if std::env::var("TERMINATOR_SKIP_NPM_INSTALL").is_ok() {
         info!("[Node.js] Using local bindings due to TERMINATOR_SKIP_NPM_INSTALL");
-        return execute_javascript_with_local_bindings(script).await;
+        return run_javascript_with_local_bindings(script, desktop).await;
     }
 
     use std::process::Stdio;
@@ -952,36 +1199,40 @@ pub async fn execute_javascript_with_nodejs(
     // 2. Executes user script
     // 3. Returns result
 
+    let desktop_js = if desktop {
+        "global.desktop = new Desktop();"
+    } else {
+        ""
+    };
     let wrapper_script = format!(
         r#"
 const {{ Desktop }} = require('terminator.js');
-
+{CONSOLE_CAPTURE_JS}
 // Create global objects
-global.desktop = new Desktop();
+{desktop_js}
 global.log = console.log;
 global.sleep = (ms) => new Promise(resolve => setTimeout(resolve, ms));
 
-console.log('[Node.js Wrapper] Starting user script execution...');
+__console.log('[Node.js Wrapper] Starting user script execution...');
 
 // Execute user script
 (async () => {{
     try {{
-        console.log('[Node.js Wrapper] Executing user script...');
+        __console.log('[Node.js Wrapper] Executing user script...');
         const result = await (async () => {{
             {script}
         }})();
         
-        console.log('[Node.js Wrapper] User script completed, result:', typeof result);
+        __console.log('[Node.js Wrapper] User script completed, result:', typeof result);
         
         // Send result back, handling undefined properly
         const resultToSend = result === undefined ? null : result;
         process.stdout.write('__RESULT__' + JSON.stringify(resultToSend) + '__END__\n');
-        console.log('[Node.js Wrapper] Result sent back to parent process');
+        __console.log('[Node.js Wrapper] Result sent back to parent process');
     }} catch (error) {{
-        console.error('[Node.js Wrapper] User script error:', error && error.message);
-        console.error('[Node.js Wrapper] Stack trace:', error && error.stack);
-        // Emit machine-readable marker on stdout as well for parent capture
-        console.log('__ERROR__' + JSON.stringify({{ message: String((error && error.message) || error), stack: String((error && error.stack) || '') }}) + '__END__');
+        __console.error('[Node.js Wrapper] User script error:', error && error.message);
+        __console.error('[Node.js Wrapper] Stack trace:', error && error.stack);
+        // Emit machine-readable marker on stdout for parent capture
         process.stdout.write('__ERROR__' + JSON.stringify({{
             message: String((error && error.message) || error),
             stack: String((error && error.stack) || '')
@@ -1043,6 +1294,7 @@ console.log('[Node.js Wrapper] Starting user script execution...');
             .arg(&unique_filename)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
     } else if cfg!(windows) && is_batch_file {
         info!("[Node.js] Using cmd.exe for batch file execution on Windows");
@@ -1052,6 +1304,7 @@ console.log('[Node.js Wrapper] Starting user script execution...');
             .args(["/c", &runtime_exe, &unique_filename])
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
     } else if cfg!(windows) && runtime_exe.ends_with(".exe") {
         info!("[Node.js] Using direct .exe execution on Windows");
@@ -1061,6 +1314,7 @@ console.log('[Node.js Wrapper] Starting user script execution...');
             .arg(&unique_filename)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
     } else {
         info!("[Node.js] Using direct execution");
@@ -1069,6 +1323,7 @@ console.log('[Node.js Wrapper] Starting user script execution...');
             .arg(&unique_filename)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
     }
     .map_err(|e| {
@@ -1087,7 +1342,7 @@ console.log('[Node.js Wrapper] Starting user script execution...');
     // Accumulate env updates from GitHub Actions-style log commands, e.g. ::set-env name=FOO::bar
     let mut env_updates: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
     let mut stderr_output = Vec::new();
-    let mut captured_logs = Vec::new(); // Capture all console output
+    let mut captured_logs: Vec<ScriptLogLine> = Vec::new(); // Capture all console output
 
     // Handle communication with Node.js process
     loop {
@@ -1130,15 +1385,17 @@ console.log('[Node.js Wrapper] Starting user script execution...');
                 match stdout_line {
                     Ok(Some(line)) => {
                         info!("[Node.js stdout] {}", line);
+                        // console.log output arrives as a __LOG__ line; this is what it printed
+                        let output = ScriptLogLine::from_stdout(&line);
                         // Capture non-marker lines as logs (excluding wrapper debug output)
                         if !line.starts_with("__RESULT__")
                             && !line.starts_with("__ERROR__")
-                            && !line.starts_with("::set-env ")
+                            && !output.message.starts_with("::set-env ")
                             && !line.starts_with("[Node.js Wrapper]") {
-                            captured_logs.push(line.clone());
+                            captured_logs.push(output.clone());
                         }
                         // Parse GitHub Actions style env updates: ::set-env name=KEY::VALUE
-                        if let Some(stripped) = line.strip_prefix("::set-env ") {
+                        if let Some(stripped) = output.message.strip_prefix("::set-env ") {
                             // Expect pattern: name=KEY::VALUE
                             if let Some(name_pos) = stripped.find("name=") {
                                 let after_name = &stripped[name_pos + 5..];
@@ -1177,7 +1434,7 @@ console.log('[Node.js Wrapper] Starting user script execution...');
                             if let Ok(error_data) = serde_json::from_str::<serde_json::Value>(&error_json) {
                                 return Err(McpError::internal_error(
                                     "JavaScript execution error",
-                                    Some(error_data),
+                                    Some(with_logs(error_data, &captured_logs)),
                                 ));
                             }
                             break;
@@ -1318,6 +1575,15 @@ console.log('[Node.js Wrapper] Starting user script execution...');
 pub async fn execute_typescript_with_nodejs(
     script: String,
     cancellation_token: Option<tokio_util::sync::CancellationToken>,
+) -> Result<serde_json::Value, McpError> {
+    run_typescript(script, cancellation_token, true).await
+}
+
+/// Runs TypeScript, defining `desktop` only when `desktop` is set
+async fn run_typescript(
+    script: String,
+    cancellation_token: Option<tokio_util::sync::CancellationToken>,
+    desktop: bool,
 ) -> Result<serde_json::Value, McpError> {
     use std::process::Stdio;
     use tokio::io::{AsyncBufReadExt, BufReader};
@@ -1361,32 +1627,39 @@ pub async fn execute_typescript_with_nodejs(
     let script_path = script_dir.join(&script_filename);
 
     // Wrap the script with terminator.js imports and helpers (TypeScript version)
+    let desktop_ts = if desktop {
+        "const desktop = new Desktop();"
+    } else {
+        ""
+    };
     let wrapped_script = format!(
         r#"
 import {{ Desktop }} from 'terminator.js';
-
-const desktop = new Desktop();
+{CONSOLE_CAPTURE_JS}
+{desktop_ts}
 const log = console.log;
 const sleep = (ms: number): Promise<void> => new Promise(resolve => setTimeout(resolve, ms));
 
 // Helper to set environment variables
 const setEnv = (updates: Record<string, any>) => {{
     for (const [key, value] of Object.entries(updates)) {{
-        console.log(`::set-env name=${{key}}::${{value}}`);
+        process.stdout.write(`::set-env name=${{key}}::${{value}}\n`);
     }}
 }};
 
 (async () => {{
     try {{
-        // User script starts here
-        {script}
-        // User script ends here
+        const result = await (async () => {{
+            // User script starts here
+            {script}
+            // User script ends here
+        }})();
+        process.stdout.write('__RESULT__' + JSON.stringify(result === undefined ? null : result) + '__END__\n');
     }} catch (error: any) {{
-        console.error('__ERROR__' + JSON.stringify({{
-            message: error?.message || String(error),
-            stack: error?.stack
-        }}) + '__END__');
-        process.exit(1);
+        process.stdout.write('__ERROR__' + JSON.stringify({{
+            message: String(error?.message || error),
+            stack: String(error?.stack || '')
+        }}) + '__END__\n');
     }}
 }})();
 "#
@@ -1428,6 +1701,7 @@ const setEnv = (updates: Record<string, any>) => {{
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
     cmd.current_dir(&script_dir);
+    cmd.kill_on_drop(true);
 
     info!("[TypeScript] Executing command: {:?}", cmd);
 
@@ -1445,7 +1719,7 @@ const setEnv = (updates: Record<string, any>) => {{
     let mut stderr_reader = BufReader::new(stderr).lines();
 
     let mut result: Option<serde_json::Value> = None;
-    let mut captured_logs: Vec<String> = Vec::new();
+    let mut captured_logs: Vec<ScriptLogLine> = Vec::new();
     let mut stderr_output: Vec<String> = Vec::new();
     let mut env_updates = serde_json::Map::new();
 
@@ -1472,22 +1746,23 @@ const setEnv = (updates: Record<string, any>) => {{
                                 }
                             } else if let Some(error_json) = line.strip_prefix("__ERROR__").and_then(|s| s.strip_suffix("__END__")) {
                                 error!("[TypeScript] Script error: {}", error_json);
+                                let error_data = serde_json::from_str(error_json)
+                                    .unwrap_or_else(|_| json!(error_json));
                                 return Err(McpError::internal_error(
                                     "TypeScript execution error",
-                                    serde_json::from_str(error_json).ok(),
+                                    Some(with_logs(error_data, &captured_logs)),
                                 ));
-                            } else if line.starts_with("::set-env name=") && line.contains("::") {
+                            } else {
+                                // console.log output arrives as a __LOG__ line; this is what it printed
+                                let output = ScriptLogLine::from_stdout(&line);
                                 // Parse GitHub Actions-style env var setting
-                                if let Some(rest) = line.strip_prefix("::set-env name=") {
-                                    if let Some(colon_pos) = rest.find("::") {
-                                        let key = &rest[..colon_pos];
-                                        let value = &rest[colon_pos + 2..];
+                                match output.message.strip_prefix("::set-env name=").and_then(|rest| rest.split_once("::")) {
+                                    Some((key, value)) => {
                                         info!("[TypeScript] Setting env var: {} = {}", key, value);
                                         env_updates.insert(key.to_string(), serde_json::Value::String(value.to_string()));
                                     }
+                                    None => captured_logs.push(output),
                                 }
-                            } else {
-                                captured_logs.push(line);
                             }
                         }
                         Ok(None) => break,
@@ -1586,6 +1861,11 @@ const setEnv = (updates: Record<string, any>) => {{
 
 /// Execute Python using system interpreter with terminator.py bindings available
 pub async fn execute_python_with_bindings(script: String) -> Result<serde_json::Value, McpError> {
+    run_python(script, true).await
+}
+
+/// Runs Python, defining `desktop` only when `desktop` is set
+async fn run_python(script: String, desktop: bool) -> Result<serde_json::Value, McpError> {
     use std::process::Stdio;
     use tokio::io::{AsyncBufReadExt, BufReader};
     use tokio::process::Command;
@@ -1609,6 +1889,11 @@ pub async fn execute_python_with_bindings(script: String) -> Result<serde_json::
     log_terminator_py_version(&python_exe, &site_packages_dir).await;
 
     // Prepare wrapper script that imports terminator and runs the user code
+    let desktop_py = if desktop {
+        "desktop = _terminator.Desktop()"
+    } else {
+        ""
+    };
     let wrapper_script = {
         let mut indented = String::new();
         for line in script.lines() {
@@ -1626,14 +1911,13 @@ except Exception as e:
     sys.stdout.write('__ERROR__' + json.dumps({{ 'message': 'Failed to import terminator (terminator.py)', 'error': str(e) }}) + '__END__\n')
     sys.exit(0)
 
-desktop = _terminator.Desktop()
+{desktop_py}
 
 async def __user_main__():
     # Helpers
     async def sleep(ms):
         await asyncio.sleep(ms / 1000.0)
-    def log(*args, **kwargs):
-        print(*args, **kwargs)
+{LOG_HELPER_PY}
     # User code (must use 'return' for final value)
 {indented}
 
@@ -1698,6 +1982,7 @@ asyncio.run(__runner__())
         .env("PYTHONPATH", pythonpath_value)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
+        .kill_on_drop(true)
         .spawn()
         .map_err(|e| {
             McpError::internal_error(
@@ -1711,6 +1996,7 @@ asyncio.run(__runner__())
     let mut result: Option<serde_json::Value> = None;
     let mut env_updates: serde_json::Map<String, serde_json::Value> = serde_json::Map::new();
     let mut stderr_output: Vec<String> = Vec::new();
+    let mut captured_logs: Vec<ScriptLogLine> = Vec::new();
 
     loop {
         tokio::select! {
@@ -1739,7 +2025,10 @@ asyncio.run(__runner__())
                             }
                         } else if text.starts_with("__ERROR__") && text.ends_with("__END__") {
                             let error_json = text.replace("__ERROR__", "").replace("__END__", "");
-                            return Err(McpError::internal_error("Python execution error", serde_json::from_str::<serde_json::Value>(&error_json).ok()));
+                            let error_data = serde_json::from_str(&error_json).unwrap_or_else(|_| json!(error_json));
+                            return Err(McpError::internal_error("Python execution error", Some(with_logs(error_data, &captured_logs))));
+                        } else if !text.starts_with("::set-env ") {
+                            captured_logs.push(ScriptLogLine::from_stdout(&text));
                         }
                     },
                     Ok(None) => { break; },
@@ -1804,7 +2093,10 @@ asyncio.run(__runner__())
                     r = serde_json::json!({ "output": r, "set_env": env_updates });
                 }
             }
-            Ok(r)
+            Ok(json!({
+                "result": r,
+                "logs": captured_logs
+            }))
         }
         None => Err(McpError::internal_error(
             "No result received from Python process",
@@ -1992,6 +2284,14 @@ print(v)
 /// Execute JavaScript using Node.js runtime with LOCAL terminator.js bindings (for development/testing)
 pub async fn execute_javascript_with_local_bindings(
     script: String,
+) -> Result<serde_json::Value, McpError> {
+    run_javascript_with_local_bindings(script, true).await
+}
+
+/// Runs JavaScript with the local bindings, defining `desktop` only when `desktop` is set
+async fn run_javascript_with_local_bindings(
+    script: String,
+    desktop: bool,
 ) -> Result<serde_json::Value, McpError> {
     use std::process::Stdio;
     use tokio::io::{AsyncBufReadExt, BufReader};
@@ -2126,12 +2426,17 @@ pub async fn execute_javascript_with_local_bindings(
     // Require the bindings index.js explicitly for maximum compatibility (Node/Bun)
     let bindings_entry_path = local_bindings_path.join("index.js");
     let bindings_abs_path = bindings_entry_path.to_string_lossy().replace('\\', "\\\\");
+    let desktop_js = if desktop {
+        "global.desktop = new Desktop();"
+    } else {
+        ""
+    };
     let wrapper_script = format!(
         r#"
  const {{ Desktop }} = require("{bindings_abs_path}");
-
+{CONSOLE_CAPTURE_JS}
  // Create global objects
- global.desktop = new Desktop();
+ {desktop_js}
  global.log = console.log;
  global.sleep = (ms) => new Promise(resolve => setTimeout(resolve, ms));
 
@@ -2198,6 +2503,7 @@ pub async fn execute_javascript_with_local_bindings(
             .arg(&unique_filename)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
     } else if cfg!(windows) && is_batch_file {
         // Use cmd.exe for batch files on Windows
@@ -2206,6 +2512,7 @@ pub async fn execute_javascript_with_local_bindings(
             .args(["/c", &runtime_exe, &unique_filename])
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
     } else if cfg!(windows) && runtime_exe.ends_with(".exe") {
         // Direct execution should work for .exe files
@@ -2214,6 +2521,7 @@ pub async fn execute_javascript_with_local_bindings(
             .arg(&unique_filename)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
     } else {
         Command::new(&runtime_exe)
@@ -2221,6 +2529,7 @@ pub async fn execute_javascript_with_local_bindings(
             .arg(&unique_filename)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
+            .kill_on_drop(true)
             .spawn()
     }
     .map_err(|e| {
@@ -2232,6 +2541,7 @@ pub async fn execute_javascript_with_local_bindings(
 
     let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
     let mut result: Option<serde_json::Value> = None;
+    let mut captured_logs: Vec<ScriptLogLine> = Vec::new();
 
     // Handle communication with Node.js process
     while let Ok(Some(line)) = stdout.next_line().await {
@@ -2246,13 +2556,14 @@ pub async fn execute_javascript_with_local_bindings(
             if let Ok(error_data) = serde_json::from_str::<serde_json::Value>(&error_json) {
                 return Err(McpError::internal_error(
                     "JavaScript execution error with local bindings",
-                    Some(error_data),
+                    Some(with_logs(error_data, &captured_logs)),
                 ));
             }
             break;
         } else {
             // Regular console output
             info!("[Node.js Local] {}", line);
+            captured_logs.push(ScriptLogLine::from_stdout(&line));
         }
     }
 
@@ -2289,10 +2600,9 @@ pub async fn execute_javascript_with_local_bindings(
     match result {
         Some(r) => {
             info!("[Node.js Local] Execution completed successfully");
-            // Return result with empty logs array to match main function format
             Ok(json!({
                 "result": r,
-                "logs": []
+                "logs": captured_logs
             }))
         }
         None => Err(McpError::internal_error(
@@ -0,0 +1,226 @@
+//! Scrolling an element into its viewport, behind the `scroll_into_view` tool.
+//!
+//! The viewport is the bounding box of the nearest ancestor that does not fully contain
+//! the element, which is the scroll container clipping it; when every ancestor contains
+//! the element it is already in view. The viewport is fixed before the first scroll, so
+//! the container does not change as the element moves. Each attempt scrolls one step
+//! toward the element, vertically before horizontally, and re-reads its bounds. Element
+//! access goes through [`ScrollTarget`] so the loop can be tested without a desktop.
+
+use crate::highlight_screenshot::Rect;
+use serde::Serialize;
+use std::time::Duration;
+use terminator::{AutomationError, UIElement};
+
+/// Default number of scrolls before giving up
+pub const DEFAULT_MAX_SCROLLS: u32 = 20;
+
+/// Default scroll step, in the units `UIElement::scroll` takes
+pub const DEFAULT_SCROLL_AMOUNT: f64 = 0.5;
+
+/// Pause after each scroll for the UI to settle
+pub const SETTLE_DELAY: Duration = Duration::from_millis(60);
+
+/// How far up the tree ancestors are checked for the viewport
+const MAX_ANCESTORS: usize = 25;
+
+/// Bounds in view may be off by this much, to allow for rounding
+const TOLERANCE: f64 = 0.5;
+
+/// The element operations scrolling needs
+pub trait ScrollTarget {
+    fn bounds(&self) -> Result<Rect, AutomationError>;
+    /// Bounding boxes of the element's ancestors, nearest first
+    fn ancestor_bounds(&self) -> Vec<Rect>;
+    /// Scrolls the nearest scrollable ancestor; `direction` is up, down, left or right
+    fn scroll(&self, direction: &str, amount: f64) -> Result<(), AutomationError>;
+}
+
+fn rect((x, y, width, height): (f64, f64, f64, f64)) -> Rect {
+    Rect {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+impl ScrollTarget for UIElement {
+    fn bounds(&self) -> Result<Rect, AutomationError> {
+        UIElement::bounds(self).map(rect)
+    }
+
+    fn ancestor_bounds(&self) -> Vec<Rect> {
+        let mut ancestors = Vec::new();
+        let mut current = self.parent().ok().flatten();
+        while let Some(element) = current {
+            if ancestors.len() >= MAX_ANCESTORS {
+                break;
+            }
+            if let Ok(bounds) = UIElement::bounds(&element) {
+                ancestors.push(rect(bounds));
+            }
+            current = element.parent().ok().flatten();
+        }
+        ancestors
+    }
+
+    fn scroll(&self, direction: &str, amount: f64) -> Result<(), AutomationError> {
+        UIElement::scroll(self, direction, amount)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollOptions {
+    pub max_scrolls: u32,
+    pub amount: f64,
+    pub settle_delay: Duration,
+}
+
+impl Default for ScrollOptions {
+    fn default() -> Self {
+        Self {
+            max_scrolls: DEFAULT_MAX_SCROLLS,
+            amount: DEFAULT_SCROLL_AMOUNT,
+            settle_delay: SETTLE_DELAY,
+        }
+    }
+}
+
+/// An element that is in view
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct InView {
+    pub bounds: Rect,
+    /// The scroll container's bounds, or `None` when nothing clipped the element
+    pub viewport: Option<Rect>,
+    pub scrolls: u32,
+}
+
+/// Why an element could not be scrolled into view
+#[derive(Debug)]
+pub enum ScrollError {
+    /// The element's bounds could not be read
+    Bounds(AutomationError),
+    /// The element was still out of view after `scrolls` scrolls
+    ScrollFailed {
+        scrolls: u32,
+        bounds: Rect,
+        viewport: Rect,
+        /// The last error from a scroll call, e.g. when no ancestor can scroll
+        last_error: Option<String>,
+    },
+}
+
+impl ScrollError {
+    /// A stable name for the error, for clients to branch on
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ScrollError::Bounds(_) => "element_bounds_unavailable",
+            ScrollError::ScrollFailed { .. } => "scroll_failed",
+        }
+    }
+}
+
+impl std::fmt::Display for ScrollError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScrollError::Bounds(e) => write!(f, "Failed to get the element's bounds: {e}"),
+            ScrollError::ScrollFailed {
+                scrolls,
+                last_error,
+                ..
+            } => {
+                write!(f, "Element is still out of view after {scrolls} scrolls")?;
+                if let Some(e) = last_error {
+                    write!(f, " (last scroll error: {e})")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ScrollError {}
+
+fn contains(outer: &Rect, inner: &Rect) -> bool {
+    inner.x >= outer.x - TOLERANCE
+        && inner.y >= outer.y - TOLERANCE
+        && inner.x + inner.width <= outer.x + outer.width + TOLERANCE
+        && inner.y + inner.height <= outer.y + outer.height + TOLERANCE
+}
+
+/// The nearest ancestor that does not fully contain `element`, or `None` if all do
+pub fn clipping_viewport(element: &Rect, ancestors: &[Rect]) -> Option<Rect> {
+    ancestors
+        .iter()
+        .find(|ancestor| !contains(ancestor, element))
+        .copied()
+}
+
+/// Whether `element` shows as much of itself as `viewport` has room for, on both axes
+pub fn in_view(element: &Rect, viewport: &Rect) -> bool {
+    axis_in_view(element.x, element.width, viewport.x, viewport.width)
+        && axis_in_view(element.y, element.height, viewport.y, viewport.height)
+}
+
+fn axis_in_view(start: f64, len: f64, view_start: f64, view_len: f64) -> bool {
+    let shown = (start + len).min(view_start + view_len) - start.max(view_start);
+    shown >= len.min(view_len) - TOLERANCE
+}
+
+/// The direction to scroll to bring `element` into `viewport`, or `None` when it is in view
+pub fn scroll_direction(element: &Rect, viewport: &Rect) -> Option<&'static str> {
+    if !axis_in_view(element.y, element.height, viewport.y, viewport.height) {
+        return Some(if element.y < viewport.y { "up" } else { "down" });
+    }
+    if !axis_in_view(element.x, element.width, viewport.x, viewport.width) {
+        return Some(if element.x < viewport.x {
+            "left"
+        } else {
+            "right"
+        });
+    }
+    None
+}
+
+/// Scrolls until `target` is inside its viewport, giving up after `options.max_scrolls`
+pub async fn scroll_into_view<T: ScrollTarget>(
+    target: &T,
+    options: &ScrollOptions,
+) -> Result<InView, ScrollError> {
+    let mut bounds = target.bounds().map_err(ScrollError::Bounds)?;
+    let Some(viewport) = clipping_viewport(&bounds, &target.ancestor_bounds()) else {
+        return Ok(InView {
+            bounds,
+            viewport: None,
+            scrolls: 0,
+        });
+    };
+
+    let mut scrolls = 0;
+    let mut last_error = None;
+    loop {
+        let Some(direction) = scroll_direction(&bounds, &viewport) else {
+            return Ok(InView {
+                bounds,
+                viewport: Some(viewport),
+                scrolls,
+            });
+        };
+        if scrolls >= options.max_scrolls {
+            return Err(ScrollError::ScrollFailed {
+                scrolls,
+                bounds,
+                viewport,
+                last_error,
+            });
+        }
+
+        if let Err(e) = target.scroll(direction, options.amount) {
+            last_error = Some(e.to_string());
+        }
+        scrolls += 1;
+        tokio::time::sleep(options.settle_delay).await;
+        bounds = target.bounds().map_err(ScrollError::Bounds)?;
+    }
+}
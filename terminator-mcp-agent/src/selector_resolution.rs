@@ -0,0 +1,46 @@
+//! Batch selector checks behind the `resolve_selectors` tool.
+//!
+//! Each selector is searched once, and the report says whether it matched, how many
+//! elements it matched and what the first match looks like. A selector that finds
+//! nothing (including a search that timed out) is simply unmatched; other failures,
+//! such as an invalid selector, are reported as the selector's error so one bad
+//! candidate does not fail the whole batch.
+
+use serde::{Deserialize, Serialize};
+use terminator::{AutomationError, ElementInfo};
+
+/// Default time to wait for each selector, in milliseconds
+pub const DEFAULT_TIMEOUT_MS: u64 = 1000;
+
+/// What one selector resolved to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SelectorResolution {
+    pub selector: String,
+    pub matched: bool,
+    pub match_count: usize,
+    /// The first match, in reading order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub element: Option<ElementInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SelectorResolution {
+    /// Builds the report for `selector` from the elements its search returned.
+    pub fn new(selector: &str, found: Result<Vec<ElementInfo>, AutomationError>) -> Self {
+        let (elements, error) = match found {
+            Ok(elements) => (elements, None),
+            Err(AutomationError::ElementNotFound(_) | AutomationError::Timeout(_)) => {
+                (Vec::new(), None)
+            }
+            Err(e) => (Vec::new(), Some(e.to_string())),
+        };
+        Self {
+            selector: selector.to_string(),
+            matched: !elements.is_empty(),
+            match_count: elements.len(),
+            element: elements.into_iter().next(),
+            error,
+        }
+    }
+}
@@ -1,26 +1,38 @@
+use crate::action_journal::{ActionJournal, Inverse, JournalEntry};
+use crate::drag_drop::{self, DragEndpoint, DragOptions};
 use crate::helpers::*;
-use crate::scripting_engine;
+use crate::monitors;
+use crate::scripting_engine::{self, ScriptLanguage};
+use crate::scroll_view::{self, ScrollOptions};
+use crate::tree_formatter::{format_tree, TreeFormatOptions};
 use crate::utils::find_and_execute_with_retry_with_fallback;
 pub use crate::utils::DesktopWrapper;
 use crate::utils::{
-    get_timeout, ActionHighlightConfig, ActivateElementArgs, ClickElementArgs, CloseElementArgs,
-    DelayArgs, ExecuteBrowserScriptArgs, ExecuteSequenceArgs, ExportWorkflowSequenceArgs,
-    GetApplicationsArgs, GetFocusedWindowTreeArgs, GetWindowTreeArgs, GlobalKeyArgs,
-    HighlightElementArgs, ImportWorkflowSequenceArgs, LocatorArgs, MaximizeWindowArgs,
-    MinimizeWindowArgs, MouseDragArgs, NavigateBrowserArgs, OpenApplicationArgs, PressKeyArgs,
-    RecordWorkflowArgs, RunCommandArgs, ScrollElementArgs, SelectOptionArgs, SetRangeValueArgs,
-    SetSelectedArgs, SetToggledArgs, SetValueArgs, SetZoomArgs, StopHighlightingArgs,
-    TypeIntoElementArgs, ValidateElementArgs, WaitForElementArgs, ZoomArgs,
+    get_timeout, ActionHighlightConfig, ActivateElementArgs, AssertVisualArgs, CheckFieldsArgs,
+    ClickElementArgs, ClipboardSetArgs, CloseElementArgs, DelayArgs, DragAndDropArgs, EmptyArgs,
+    ExecuteBrowserScriptArgs, ExecuteSequenceArgs, ExportWorkflowSequenceArgs, FindByIdentityArgs,
+    FindElementsArgs, GetApplicationsArgs, GetCellArgs, GetEventsArgs, GetFocusedWindowTreeArgs,
+    GetWindowTreeArgs, GlobalKeyArgs, HighlightElementArgs, HighlightedScreenshotArgs,
+    ImportWorkflowSequenceArgs, LocatorArgs, MaximizeWindowArgs, MinimizeWindowArgs, MouseDragArgs,
+    NavigateBrowserArgs, OcrRegionArgs, OpenApplicationArgs, PressKeyArgs, PressKeysArgs,
+    ReadMenuArgs, RecordWorkflowArgs, ResolveSelectorsArgs, RunCommandArgs, ScrollElementArgs,
+    ScrollIntoViewArgs, SelectOptionArgs, SetRangeValueArgs, SetSelectedArgs, SetToggledArgs,
+    SetValueArgs, SetZoomArgs, StopHighlightingArgs, TypeIntoElementArgs, TypeIntoFocusedArgs,
+    UndoArgs, ValidateElementArgs, WaitForElementArgs, ZoomArgs,
+};
+use crate::wait_predicate::{
+    parse_wait_timing, wait_until, ElementCondition, WaitFailure, WorkflowVariables,
 };
 use futures::StreamExt;
 use image::{ExtendedColorType, ImageEncoder};
 use regex::Regex;
+use rmcp::handler::server::tool::ToolCallContext;
 use rmcp::handler::server::wrapper::Parameters;
 use rmcp::model::{
-    CallToolResult, Content, Implementation, ProtocolVersion, ServerCapabilities, ServerInfo,
+    CallToolRequestParam, CallToolResult, Content, Implementation, ListToolsResult,
+    PaginatedRequestParam, ProtocolVersion, ServerCapabilities, ServerInfo,
 };
-use rmcp::{tool, ErrorData as McpError, ServerHandler};
-use rmcp::{tool_handler, tool_router};
+use rmcp::{tool, tool_router, ErrorData as McpError, ServerHandler};
 use serde_json::json;
 use std::io::Cursor;
 use std::sync::Arc;
@@ -312,9 +324,25 @@ impl DesktopWrapper {
             log_capture,
             current_workflow_dir: Arc::new(Mutex::new(None)),
             current_scripts_base_path: Arc::new(Mutex::new(None)),
+            action_journal: Arc::new(Mutex::new(ActionJournal::default())),
+            middleware: crate::middleware::MiddlewareChain::standard(),
         })
     }
 
+    /// Appends a middleware to the chain run around every tool call
+    pub fn with_middleware(
+        mut self,
+        middleware: Arc<dyn crate::middleware::ActionMiddleware>,
+    ) -> Self {
+        self.middleware = self.middleware.with(middleware);
+        self
+    }
+
+    /// Adds an action to the journal used by the `undo` tool
+    async fn record_action(&self, entry: JournalEntry) {
+        self.action_journal.lock().await.record(entry);
+    }
+
     /// Create TreeBuildConfig based on include_detailed_attributes parameter
     /// Defaults to comprehensive attributes for LLM usage if include_detailed_attributes is not specified
     fn create_tree_config(
@@ -334,6 +362,30 @@ impl DesktopWrapper {
         }
     }
 
+    /// Inserts `tree` into `result_json` as JSON under `ui_tree`, or as text under
+    /// `ui_tree_text` when `as_text` is set
+    fn insert_tree(
+        result_json: &mut serde_json::Value,
+        tree: &terminator::UINode,
+        as_text: Option<bool>,
+        format: &TreeFormatOptions,
+    ) {
+        let (key, tree_val) = if as_text.unwrap_or(false) {
+            (
+                "ui_tree_text",
+                serde_json::Value::String(format_tree(tree, format)),
+            )
+        } else {
+            match serde_json::to_value(tree) {
+                Ok(tree_val) => ("ui_tree", tree_val),
+                Err(_) => return,
+            }
+        };
+        if let Some(obj) = result_json.as_object_mut() {
+            obj.insert(key.to_string(), tree_val);
+        }
+    }
+
     #[tool(
         description = "Get the complete UI tree for an application by PID and optional window title. This is your primary tool for understanding the application's current state. This is a read-only operation."
     )]
@@ -364,11 +416,38 @@ impl DesktopWrapper {
         });
 
         // Always include the tree unless explicitly disabled
-        if let Ok(tree_val) = serde_json::to_value(tree) {
-            if let Some(obj) = result_json.as_object_mut() {
-                obj.insert("ui_tree".to_string(), tree_val);
-            }
-        }
+        let format = TreeFormatOptions {
+            max_depth: args.max_depth,
+            roles: args.roles.unwrap_or_default(),
+            name_contains: args.name_contains,
+            show_paths: args.show_paths.unwrap_or(false),
+            show_bounds: args.show_bounds.unwrap_or(false),
+        };
+        Self::insert_tree(&mut result_json, &tree, args.as_text, &format);
+
+        Ok(CallToolResult::success(vec![Content::json(result_json)?]))
+    }
+
+    #[tool(
+        description = "Get metadata for the application in the foreground: process name, PID, window title, executable path and version. Use it to assert a workflow is acting on the right application before proceeding. Fields that cannot be read (e.g. for protected processes) are null. This is a read-only operation."
+    )]
+    pub async fn foreground_app(
+        &self,
+        Parameters(_args): Parameters<EmptyArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let info = self.desktop.foreground_app().await.map_err(|e| {
+            McpError::internal_error(
+                "Failed to get foreground application",
+                Some(json!({"reason": e.to_string()})),
+            )
+        })?;
+
+        let result_json = json!({
+            "action": "foreground_app",
+            "status": "success",
+            "app": info,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        });
 
         Ok(CallToolResult::success(vec![Content::json(result_json)?]))
     }
@@ -419,7 +498,7 @@ impl DesktopWrapper {
                 )
             })?;
 
-        let result_json = json!({
+        let mut result_json = json!({
             "action": "get_focused_window_tree",
             "status": "success",
             "focused_window": {
@@ -427,11 +506,19 @@ impl DesktopWrapper {
                 "window_title": window_title,
                 "application_name": app_name,
             },
+            "focused_element": build_element_info(&focused_element),
             "detailed_attributes": args.include_detailed_attributes.unwrap_or(true),
-            "ui_tree": tree,
             "timestamp": chrono::Utc::now().to_rfc3339(),
             "recommendation": "Prefer role|name selectors (e.g., 'button|Submit'). Use the element ID (e.g., '#12345') as a fallback if the name is missing or generic."
         });
+        let format = TreeFormatOptions {
+            max_depth: args.max_depth,
+            roles: args.roles.unwrap_or_default(),
+            name_contains: args.name_contains,
+            show_paths: args.show_paths.unwrap_or(false),
+            show_bounds: args.show_bounds.unwrap_or(false),
+        };
+        Self::insert_tree(&mut result_json, &tree, args.as_text, &format);
 
         Ok(CallToolResult::success(vec![Content::json(result_json)?]))
     }
@@ -891,6 +978,15 @@ impl DesktopWrapper {
                 )),
             }?;
 
+        self.record_action(JournalEntry::reversible(
+            "type_into_element",
+            Some(&successful_selector),
+            Inverse::AppUndo {
+                selector: Some(successful_selector.clone()),
+            },
+        ))
+        .await;
+
         let mut result_json = json!({
             "action": "type_into_element",
             "status": "success",
@@ -989,9 +1085,11 @@ impl DesktopWrapper {
         let action = {
             let highlight_config = args.highlight_before_action.clone();
             let click_position = args.click_position.clone();
+            let desktop = self.desktop.clone();
             move |element: UIElement| {
                 let highlight_config = highlight_config.clone();
                 let click_position = click_position.clone();
+                let desktop = desktop.clone();
                 async move {
                     // Ensure element is visible and apply highlighting if configured
                     Self::ensure_visible_and_apply_highlight(
@@ -1014,6 +1112,9 @@ impl DesktopWrapper {
                                     x, y, bounds.0, bounds.1, bounds.2, bounds.3
                                 );
 
+                                // Another window on top would receive the click instead
+                                desktop.ensure_not_obscured(&element, x, y)?;
+
                                 // Perform click at specific position
                                 element.mouse_click_and_hold(x, y)?;
                                 element.mouse_release()?;
@@ -1063,6 +1164,12 @@ impl DesktopWrapper {
                 )),
             }?;
 
+        self.record_action(JournalEntry::irreversible(
+            "click_element",
+            Some(&successful_selector),
+        ))
+        .await;
+
         // Optionally include troubleshooting recommendations when evidence suggests the click may have missed the intended target
         let details_str = &click_result.details;
         // Consider the outcome uncertain whenever immediate post-change signals are absent,
@@ -1244,6 +1351,352 @@ impl DesktopWrapper {
         Ok(CallToolResult::success(vec![Content::json(result_json)?]))
     }
 
+    #[tool(
+        description = "Types text into the element that currently has keyboard focus (no selector required). Use it when focus is already in place, e.g. after tabbing. Fails if nothing has focus. This action may change the UI."
+    )]
+    async fn type_into_focused(
+        &self,
+        Parameters(args): Parameters<TypeIntoFocusedArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let element = crate::focused_input::type_into_focused(
+            self.desktop.as_ref(),
+            &args.text,
+            args.use_clipboard.unwrap_or(true),
+        )
+        .map_err(|e| focused_input_error(e, json!({ "text_length": args.text.len() })))?;
+
+        self.record_action(JournalEntry::reversible(
+            "type_into_focused",
+            None,
+            Inverse::AppUndo { selector: None },
+        ))
+        .await;
+
+        let mut result_json = json!({
+            "action": "type_into_focused",
+            "status": "success",
+            "text_typed": args.text,
+            "element": build_element_info(&element),
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        });
+        maybe_attach_tree(
+            &self.desktop,
+            Self::get_include_tree_default(args.include_tree),
+            args.include_detailed_attributes,
+            element.process_id().ok(),
+            &mut result_json,
+        );
+
+        Ok(CallToolResult::success(vec![Content::json(result_json)?]))
+    }
+
+    #[tool(
+        description = "Reads the text on the clipboard, e.g. what an application just copied. Fails with error_type 'clipboard_empty' when the clipboard is empty and 'clipboard_not_text' when it holds something else, such as an image. This is a read-only operation."
+    )]
+    async fn clipboard_get(
+        &self,
+        Parameters(_args): Parameters<EmptyArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut clipboard = crate::clipboard::system_clipboard().map_err(clipboard_error)?;
+        let text = crate::clipboard::read_text(&mut clipboard).map_err(clipboard_error)?;
+
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "action": "clipboard_get",
+            "status": "success",
+            "text": text,
+            "length": text.chars().count(),
+        }))?]))
+    }
+
+    #[tool(
+        description = "Puts text on the clipboard, replacing its content, e.g. to prepare a paste with '{Ctrl}v'. This changes the clipboard but not the UI."
+    )]
+    async fn clipboard_set(
+        &self,
+        Parameters(args): Parameters<ClipboardSetArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut clipboard = crate::clipboard::system_clipboard().map_err(clipboard_error)?;
+        crate::clipboard::write_text(&mut clipboard, &args.text).map_err(clipboard_error)?;
+
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "action": "clipboard_set",
+            "status": "success",
+            "length": args.text.chars().count(),
+        }))?]))
+    }
+
+    #[tool(
+        description = "Presses a sequence of keys on the element that currently has keyboard focus (no selector required). Use curly brace format: '{Tab}', '{Ctrl}a', '{Enter}'. Fails if nothing has focus. This action may change the UI."
+    )]
+    async fn press_keys(
+        &self,
+        Parameters(args): Parameters<PressKeysArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let element = crate::focused_input::press_keys(self.desktop.as_ref(), &args.keys)
+            .map_err(|e| focused_input_error(e, json!({ "keys": args.keys })))?;
+
+        let mut result_json = json!({
+            "action": "press_keys",
+            "status": "success",
+            "keys_pressed": args.keys,
+            "element": build_element_info(&element),
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        });
+        maybe_attach_tree(
+            &self.desktop,
+            Self::get_include_tree_default(args.include_tree),
+            args.include_detailed_attributes,
+            element.process_id().ok(),
+            &mut result_json,
+        );
+
+        Ok(CallToolResult::success(vec![Content::json(result_json)?]))
+    }
+
+    #[tool(
+        description = "Capability handshake: returns the agent version, compile-time features, OCR availability, supported transports, selector types and the list of available tools. Call it once after connecting to adapt to this build. This is a read-only operation."
+    )]
+    async fn capabilities(
+        &self,
+        Parameters(_args): Parameters<EmptyArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let tools = self
+            .tool_router
+            .list_all()
+            .into_iter()
+            .map(|tool| tool.name.to_string())
+            .collect();
+        let capabilities = crate::capabilities::Capabilities::current(tools);
+
+        let mut result_json = serde_json::to_value(&capabilities).map_err(|e| {
+            McpError::internal_error(
+                "Failed to serialize capabilities",
+                Some(json!({"reason": e.to_string()})),
+            )
+        })?;
+        result_json["action"] = json!("capabilities");
+        result_json["status"] = json!("success");
+
+        Ok(CallToolResult::success(vec![Content::json(result_json)?]))
+    }
+
+    #[tool(
+        description = "Reports latency percentiles (p50/p90/p95/p99, in milliseconds) per action type (click, type, find, read, other) and per tool over the most recent tool calls, plus per-step retry counts (attempts, successes after retry, exhaustions) from execute_sequence. This is a read-only operation."
+    )]
+    async fn latency_report(
+        &self,
+        Parameters(_args): Parameters<EmptyArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let report = crate::telemetry::latency_report();
+        let retries = crate::telemetry::retry_tracker()
+            .lock()
+            .map(|tracker| tracker.report())
+            .unwrap_or_default();
+
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "action": "latency_report",
+            "status": "success",
+            "sample_limit": report.sample_limit,
+            "actions": report.actions,
+            "tools": report.tools,
+            "retries": retries,
+        }))?]))
+    }
+
+    #[tool(
+        description = "Checks that form fields are filled in before submitting. Reads the current value of each field and reports which are empty or could not be found/read. Fields marked IsRequiredForForm by the platform are required; when the platform does not say, every listed field is treated as required. 'complete' is true when no required field is empty and every field was read. This is a read-only operation."
+    )]
+    async fn check_fields(
+        &self,
+        Parameters(args): Parameters<CheckFieldsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        use crate::form_check::FieldReading;
+
+        let timeout_ms = args.timeout_ms.unwrap_or(3000);
+        let mut readings = Vec::with_capacity(args.selectors.len());
+        for selector in &args.selectors {
+            let reading = match find_and_execute_with_retry_with_fallback(
+                &self.desktop,
+                selector,
+                None,
+                None,
+                Some(timeout_ms),
+                None,
+                |element: UIElement| async move { Ok(FieldReading::read(&element)) },
+            )
+            .await
+            {
+                Ok(((reading, _), _)) => reading,
+                Err(e) => FieldReading::NotFound(e.to_string()),
+            };
+            readings.push((selector.clone(), reading));
+        }
+        let report = crate::form_check::check_fields(readings);
+
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "action": "check_fields",
+            "status": "success",
+            "complete": report.complete,
+            "empty_required": report.empty_required,
+            "invalid": report.invalid,
+            "fields": report.fields,
+        }))?]))
+    }
+
+    #[tool(
+        description = "Looks up several candidate selectors in one call, for planning which one to use. For each selector, reports whether it matched, how many elements it matched and a summary of the first match. Selectors that match nothing are reported as unmatched rather than failing the call; invalid selectors carry an 'error'. This is a read-only operation."
+    )]
+    async fn resolve_selectors(
+        &self,
+        Parameters(args): Parameters<ResolveSelectorsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        use crate::selector_resolution::{SelectorResolution, DEFAULT_TIMEOUT_MS};
+
+        let timeout = Duration::from_millis(args.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+        let mut results = Vec::with_capacity(args.selectors.len());
+        for selector in &args.selectors {
+            let found = self
+                .desktop
+                .locator(Selector::from(selector.as_str()))
+                .all(Some(timeout), None)
+                .await
+                .map(|elements| elements.iter().map(UIElement::info).collect());
+            results.push(SelectorResolution::new(selector, found));
+        }
+        let matched = results.iter().filter(|r| r.matched).count();
+
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "action": "resolve_selectors",
+            "status": "success",
+            "matched": matched,
+            "results": results,
+        }))?]))
+    }
+
+    #[tool(
+        description = "Finds every element matching a selector, e.g. to scrape the rows of a list, and returns their role, name, text and bounds in reading order. Use 'limit' to cap the number returned; 'total' always counts every match and 'truncated' tells whether some were left out. A selector that matches nothing returns an empty list rather than an error. This is a read-only operation."
+    )]
+    async fn find_elements(
+        &self,
+        Parameters(args): Parameters<FindElementsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        use crate::find_elements::{collect_matches, DEFAULT_TIMEOUT_MS};
+
+        let timeout = Duration::from_millis(args.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+        let found = self
+            .desktop
+            .locator(Selector::from(args.selector.as_str()))
+            .all(Some(timeout), None)
+            .await;
+        let matches = collect_matches(found, args.limit)
+            .map_err(|e| build_element_not_found_error(&args.selector, None, None, e.into()))?;
+
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "action": "find_elements",
+            "status": "success",
+            "selector": args.selector,
+            "total": matches.total,
+            "truncated": matches.truncated,
+            "elements": matches.elements,
+        }))?]))
+    }
+
+    #[tool(
+        description = "Reads the item hierarchy of a menu, including submenus, as a tree without invoking any item. Collapsed submenus are expanded through their expand/collapse pattern to read their items and collapsed again afterwards, so the menu is left as it was. Point the selector at a menu bar, an open menu or a menu item such as 'role:MenuItem|name:File'. Each item reports its name, role, whether it is enabled and whether it opens a submenu."
+    )]
+    async fn read_menu(
+        &self,
+        Parameters(args): Parameters<ReadMenuArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let max_depth = args
+            .max_depth
+            .unwrap_or(crate::menu_reader::DEFAULT_MAX_DEPTH);
+        let ((read, element), successful_selector) =
+            find_and_execute_with_retry_with_fallback(
+                &self.desktop,
+                &args.selector,
+                args.alternative_selectors.as_deref(),
+                args.fallback_selectors.as_deref(),
+                args.timeout_ms,
+                args.retries,
+                |element: UIElement| async move {
+                    Ok(crate::menu_reader::read_menu(&element, max_depth))
+                },
+            )
+            .await
+            .map_err(|e| {
+                build_element_not_found_error(
+                    &args.selector,
+                    args.alternative_selectors.as_deref(),
+                    args.fallback_selectors.as_deref(),
+                    e,
+                )
+            })?;
+
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "action": "read_menu",
+            "status": "success",
+            "element": build_element_info(&element),
+            "selector_used": successful_selector,
+            "menu": read.menu,
+            "expanded_submenus": read.expanded,
+            "errors": read.errors,
+        }))?]))
+    }
+
+    #[tool(
+        description = "Returns the recent execution events (e.g. dismissed modals, a step_started/step_finished pair per step with its duration and outcome, variable_changed for each env variable the runner sets, and a final run_finished with the run's status) of a workflow run, so a client that connected mid-run can catch up. Events carry increasing sequence numbers; pass the last seen 'seq' as 'since_seq' to fetch only newer ones. 'truncated' is true when older events were dropped from the bounded buffer. This is a read-only operation."
+    )]
+    async fn get_events(
+        &self,
+        Parameters(args): Parameters<GetEventsArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let Some((run_id, emitter)) = crate::workflow_events::runs().get(args.run_id.as_deref())
+        else {
+            return Err(McpError::invalid_params(
+                "No workflow run found",
+                Some(json!({"run_id": args.run_id})),
+            ));
+        };
+        let since = emitter.since(args.since_seq.unwrap_or(0));
+
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "action": "get_events",
+            "status": "success",
+            "run_id": run_id,
+            "events": since.events,
+            "latest_seq": since.latest_seq,
+            "truncated": since.truncated,
+        }))?]))
+    }
+
+    #[tool(
+        description = "Undoes the last N automation actions, newest first. Toggles, selections, range values and set values are restored to their previous state; typed text is undone with Ctrl+Z in the target application. Actions that cannot be reversed (clicks, closing windows, ...) are reported as skipped. This action may change the UI."
+    )]
+    async fn undo(
+        &self,
+        Parameters(args): Parameters<UndoArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let count = args.count.unwrap_or(1);
+        let entries = self.action_journal.lock().await.take_last(count);
+        let requested = entries.len();
+        let backend = crate::action_journal::DesktopUndo {
+            desktop: &self.desktop,
+            timeout: Duration::from_millis(args.timeout_ms.unwrap_or(3000)),
+        };
+        let report = crate::action_journal::undo_entries(entries, &backend).await;
+
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "action": "undo",
+            "status": if report.failed.is_empty() { "success" } else { "partial" },
+            "requested": count,
+            "available": requested,
+            "undone": report.undone,
+            "skipped": report.skipped,
+            "failed": report.failed,
+        }))?]))
+    }
+
     #[tool(
         description = "Executes a shell command (GitHub Actions-style) OR runs inline code via an engine. Use 'run' for shell commands. Or set 'engine' to 'node'/'bun'/'javascript'/'typescript'/'ts' for JS/TS with terminator.js, or 'python' for Python with terminator.py and provide the code in 'run' or 'script_file'. TypeScript is supported with automatic transpilation. When using engine mode, you can pass data to subsequent workflow steps by returning { set_env: { key: value } } or using console.log('::set-env name=key::value'). Access variables in later steps with {{env.key}} substitution. NEW: Use 'script_file' to load scripts from files, 'env' to inject environment variables as 'var env = {...}' (JS/TS) or 'env = {...}' (Python)."
     )]
@@ -1262,6 +1715,12 @@ impl DesktopWrapper {
         // Engine-based execution path (provides SDK bindings)
         if let Some(engine_value) = args.engine.as_ref() {
             let engine = engine_value.to_ascii_lowercase();
+            let Some(language) = ScriptLanguage::from_engine(&engine) else {
+                return Err(McpError::invalid_params(
+                    "Unsupported engine. Use 'node'/'bun'/'javascript'/'typescript'/'ts' or 'python'",
+                    Some(json!({"engine": engine_value})),
+                ));
+            };
 
             // Resolve script content from file or inline
             let script_content = if let Some(script_file) = &args.script_file {
@@ -1416,10 +1875,7 @@ impl DesktopWrapper {
             };
 
             // Inject based on engine type
-            if matches!(
-                engine.as_str(),
-                "node" | "bun" | "javascript" | "js" | "typescript" | "ts"
-            ) {
+            if language != ScriptLanguage::Python {
                 // First inject accumulated env
                 final_script.push_str(&format!("var env = {accumulated_env_json};\n"));
 
@@ -1456,7 +1912,7 @@ impl DesktopWrapper {
                 // Inject variables
                 final_script.push_str(&format!("var variables = {variables_json};\n"));
                 tracing::debug!("[run_command] Injected accumulated env, explicit env, individual vars, and workflow variables for JavaScript");
-            } else if matches!(engine.as_str(), "python" | "py") {
+            } else {
                 // For Python, inject as dictionaries
                 final_script.push_str(&format!("env = {accumulated_env_json}\n"));
 
@@ -1504,173 +1960,74 @@ impl DesktopWrapper {
             // Append the actual script
             final_script.push_str(&script_content);
 
-            // Map engine to executor
-            let is_js = matches!(engine.as_str(), "node" | "bun" | "javascript" | "js");
-            let is_ts = matches!(engine.as_str(), "typescript" | "ts");
-            let is_py = matches!(engine.as_str(), "python" | "py");
+            let timeout = scripting_engine::script_timeout(args.timeout.as_deref())?;
+            let desktop = scripting_engine::desktop_allowed(args.allow_desktop);
+            let execution_result = scripting_engine::run_script(
+                language,
+                final_script,
+                desktop,
+                cancellation_token,
+                timeout,
+            )
+            .await?;
+            let name = language.name();
+
+            // Every engine wraps the script's value with the lines it logged
+            let logs = execution_result.get("logs").cloned();
+            let actual_result = execution_result
+                .get("result")
+                .cloned()
+                .unwrap_or(execution_result.clone());
+
+            // Debug log extraction
+            if let Some(arr) = logs.as_ref().and_then(|l| l.as_array()) {
+                info!(
+                    "[run_command] Extracted {} log lines from {} execution",
+                    arr.len(),
+                    name
+                );
+            }
+
+            // Check if the script result indicates a failure
+            // This makes run_command consistent with execute_browser_script behavior
+            if let Some(obj) = actual_result.as_object() {
+                if let Some(status_str) = obj.get("status").and_then(|s| s.as_str()) {
+                    if status_str == "failed" || status_str == "error" {
+                        // Extract error message if provided
+                        let message = obj
+                            .get("message")
+                            .and_then(|m| m.as_str())
+                            .unwrap_or("Script returned failure status");
 
-            if is_js {
-                let execution_result = scripting_engine::execute_javascript_with_nodejs(
-                    final_script,
-                    cancellation_token,
-                )
-                .await?;
-
-                // Extract logs and actual result
-                let logs = execution_result.get("logs").cloned();
-                let actual_result = execution_result
-                    .get("result")
-                    .cloned()
-                    .unwrap_or(execution_result.clone());
-
-                // Debug log extraction
-                if let Some(ref log_array) = logs {
-                    if let Some(arr) = log_array.as_array() {
                         info!(
-                            "[run_command] Extracted {} log lines from JavaScript execution",
-                            arr.len()
+                            "[run_command] {} script returned status: '{}', treating as error",
+                            name, status_str
                         );
-                    }
-                }
-
-                // Check if the JavaScript result indicates a failure
-                // This makes run_command consistent with execute_browser_script behavior
-                if let Some(obj) = actual_result.as_object() {
-                    if let Some(status) = obj.get("status") {
-                        if let Some(status_str) = status.as_str() {
-                            if status_str == "failed" || status_str == "error" {
-                                // Extract error message if provided
-                                let message = obj
-                                    .get("message")
-                                    .and_then(|m| m.as_str())
-                                    .unwrap_or("Script returned failure status");
-
-                                info!(
-                                    "[run_command] Script returned status: '{}', treating as error",
-                                    status_str
-                                );
 
-                                // Return an error to trigger fallback_id in workflows
-                                return Err(McpError::internal_error(
-                                    format!("JavaScript execution failed: {message}"),
-                                    Some(actual_result),
-                                ));
-                            }
-                        }
+                        // Return an error to trigger fallback_id in workflows
+                        return Err(McpError::internal_error(
+                            format!("{name} execution failed: {message}"),
+                            Some(actual_result),
+                        ));
                     }
                 }
+            }
 
-                // Build response with logs
-                let mut response = json!({
-                    "action": "run_command",
-                    "mode": "engine",
-                    "engine": engine,
-                    "status": "success",
-                    "result": actual_result
-                });
+            // Build response with logs
+            let mut response = json!({
+                "action": "run_command",
+                "mode": "engine",
+                "engine": engine,
+                "status": "success",
+                "result": actual_result
+            });
 
-                if let Some(logs) = logs {
-                    response["logs"] = logs;
-                }
+            if let Some(logs) = logs {
+                response["logs"] = logs;
+            }
 
-                return Ok(CallToolResult::success(vec![Content::json(response)?]));
-            } else if is_ts {
-                let execution_result = scripting_engine::execute_typescript_with_nodejs(
-                    final_script,
-                    cancellation_token,
-                )
-                .await?;
-
-                // Extract logs and actual result (same as JS)
-                let logs = execution_result.get("logs").cloned();
-                let actual_result = execution_result
-                    .get("result")
-                    .cloned()
-                    .unwrap_or(execution_result.clone());
-
-                // Check if the TypeScript result indicates a failure
-                if let Some(obj) = actual_result.as_object() {
-                    if let Some(status) = obj.get("status") {
-                        if let Some(status_str) = status.as_str() {
-                            if status_str == "failed" || status_str == "error" {
-                                // Extract error message if provided
-                                let message = obj
-                                    .get("message")
-                                    .and_then(|m| m.as_str())
-                                    .unwrap_or("Script returned failure status");
-
-                                info!(
-                                    "[run_command] TypeScript script returned status: '{}', treating as error",
-                                    status_str
-                                );
-
-                                // Return an error to trigger fallback_id in workflows
-                                return Err(McpError::internal_error(
-                                    format!("TypeScript execution failed: {message}"),
-                                    Some(actual_result),
-                                ));
-                            }
-                        }
-                    }
-                }
-
-                // Build response with logs
-                let mut response = json!({
-                    "action": "run_command",
-                    "mode": "engine",
-                    "engine": engine,
-                    "status": "success",
-                    "result": actual_result
-                });
-
-                if let Some(logs) = logs {
-                    response["logs"] = logs;
-                }
-
-                return Ok(CallToolResult::success(vec![Content::json(response)?]));
-            } else if is_py {
-                let execution_result =
-                    scripting_engine::execute_python_with_bindings(final_script).await?;
-
-                // Check if the Python result indicates a failure (same as JavaScript)
-                if let Some(obj) = execution_result.as_object() {
-                    if let Some(status) = obj.get("status") {
-                        if let Some(status_str) = status.as_str() {
-                            if status_str == "failed" || status_str == "error" {
-                                // Extract error message if provided
-                                let message = obj
-                                    .get("message")
-                                    .and_then(|m| m.as_str())
-                                    .unwrap_or("Script returned failure status");
-
-                                info!("[run_command] Python script returned status: '{}', treating as error", status_str);
-
-                                // Return an error to trigger fallback_id in workflows
-                                return Err(McpError::internal_error(
-                                    format!("Python execution failed: {message}"),
-                                    Some(execution_result),
-                                ));
-                            }
-                        }
-                    }
-                }
-
-                // For now, Python doesn't capture logs yet, but we can add it later
-                // Just pass through the result as before
-                return Ok(CallToolResult::success(vec![Content::json(json!({
-                    "action": "run_command",
-                    "mode": "engine",
-                    "engine": engine,
-                    "status": "success",
-                    "result": execution_result
-                }))?]));
-            } else {
-                return Err(McpError::invalid_params(
-                    "Unsupported engine. Use 'node'/'bun'/'javascript'/'typescript'/'ts' or 'python'",
-                    Some(json!({"engine": engine_value})),
-                ));
-            }
-        }
+            return Ok(CallToolResult::success(vec![Content::json(response)?]));
+        }
 
         // Shell-based execution path
         // For shell mode, we also support script_file but env is ignored
@@ -2001,9 +2358,22 @@ impl DesktopWrapper {
         &self,
         Parameters(args): Parameters<MouseDragArgs>,
     ) -> Result<CallToolResult, McpError> {
-        let action = |element: UIElement| async move {
-            element.mouse_drag(args.start_x, args.start_y, args.end_x, args.end_y)
+        let (start, end) = match args.monitor {
+            Some(index) => {
+                let monitor = monitors::monitor_at(&*self.desktop, index)
+                    .await
+                    .map_err(monitor_error)?;
+                (
+                    monitors::to_desktop(&monitor, index, args.start_x, args.start_y)
+                        .map_err(monitor_error)?,
+                    monitors::to_desktop(&monitor, index, args.end_x, args.end_y)
+                        .map_err(monitor_error)?,
+                )
+            }
+            None => ((args.start_x, args.start_y), (args.end_x, args.end_y)),
         };
+        let action =
+            |element: UIElement| async move { element.mouse_drag(start.0, start.1, end.0, end.1) };
 
         let ((_result, element), successful_selector) =
             match find_and_execute_with_retry_with_fallback(
@@ -2034,8 +2404,175 @@ impl DesktopWrapper {
             "element": element_info,
             "selector_used": successful_selector,
             "selectors_tried": get_selectors_tried_all(&args.selector, args.alternative_selectors.as_deref(), args.fallback_selectors.as_deref()),
-            "start": (args.start_x, args.start_y),
-            "end": (args.end_x, args.end_y),
+            "start": start,
+            "end": end,
+            "monitor": args.monitor,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        });
+        maybe_attach_tree(
+            &self.desktop,
+            Self::get_include_tree_default(args.include_tree),
+            args.include_detailed_attributes,
+            element.process_id().ok(),
+            &mut result_json,
+        );
+
+        Ok(CallToolResult::success(vec![Content::json(result_json)?]))
+    }
+
+    #[tool(
+        description = "Drags from a source to a target with a press-move-release gesture, e.g. to move an item between lists. Each end is given as a selector (the element's center is used) or as screen coordinates. The pointer moves in 'steps' increments so apps that need movement events see them. This action may change the UI."
+    )]
+    async fn drag_and_drop(
+        &self,
+        Parameters(args): Parameters<DragAndDropArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let endpoints = DragEndpoint::from_args(
+            "source",
+            args.source_selector.as_deref(),
+            args.source_x,
+            args.source_y,
+        )
+        .and_then(|source| {
+            DragEndpoint::from_args(
+                "target",
+                args.target_selector.as_deref(),
+                args.target_x,
+                args.target_y,
+            )
+            .map(|target| (source, target))
+        });
+        let (source, target) = endpoints.map_err(|reason| {
+            McpError::invalid_params(
+                "Invalid drag_and_drop arguments",
+                Some(json!({ "reason": reason })),
+            )
+        })?;
+        let (source, target) = match args.monitor {
+            Some(index) => {
+                let monitor = monitors::monitor_at(&*self.desktop, index)
+                    .await
+                    .map_err(monitor_error)?;
+                (
+                    source.on_monitor(&monitor, index).map_err(monitor_error)?,
+                    target.on_monitor(&monitor, index).map_err(monitor_error)?,
+                )
+            }
+            None => (source, target),
+        };
+
+        let (from, source_element) = self.drag_endpoint(source, args.timeout_ms).await?;
+        let (to, target_element) = self.drag_endpoint(target, args.timeout_ms).await?;
+        let options = DragOptions {
+            steps: args.steps.unwrap_or(drag_drop::DEFAULT_STEPS),
+            step_delay: args
+                .step_delay_ms
+                .map(std::time::Duration::from_millis)
+                .unwrap_or(drag_drop::DEFAULT_STEP_DELAY),
+        };
+
+        // The mouse input is not tied to an element, so any element can send it
+        let sink = source_element
+            .clone()
+            .or_else(|| target_element.clone())
+            .unwrap_or_else(|| self.desktop.root());
+        drag_drop::drag(&sink, from, to, &options)
+            .await
+            .map_err(drag_error)?;
+
+        let mut result_json = json!({
+            "action": "drag_and_drop",
+            "status": "success",
+            "from": from,
+            "to": to,
+            "steps": options.steps.max(1),
+            "source_element": source_element.as_ref().map(build_element_info),
+            "target_element": target_element.as_ref().map(build_element_info),
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        });
+        maybe_attach_tree(
+            &self.desktop,
+            Self::get_include_tree_default(args.include_tree),
+            args.include_detailed_attributes,
+            sink.process_id().ok(),
+            &mut result_json,
+        );
+
+        Ok(CallToolResult::success(vec![Content::json(result_json)?]))
+    }
+
+    /// The screen point of one end of a drag, and the element it was found on
+    async fn drag_endpoint(
+        &self,
+        endpoint: DragEndpoint<'_>,
+        timeout_ms: Option<u64>,
+    ) -> Result<(drag_drop::Point, Option<UIElement>), McpError> {
+        let selector = match endpoint {
+            DragEndpoint::Point(point) => return Ok((point, None)),
+            DragEndpoint::Selector(selector) => selector,
+        };
+        let element = self
+            .desktop
+            .locator(Selector::from(selector))
+            .wait(get_timeout(timeout_ms))
+            .await
+            .map_err(|e| build_element_not_found_error(selector, None, None, e.into()))?;
+        let bounds = element.bounds().map_err(|e| {
+            McpError::internal_error(
+                "Failed to get element bounds for drag_and_drop",
+                Some(json!({ "selector": selector, "reason": e.to_string() })),
+            )
+        })?;
+        Ok((drag_drop::Point::center_of(bounds), Some(element)))
+    }
+
+    #[tool(
+        description = "Scrolls the element's scroll container until the element is in view, e.g. before clicking an item far down a list, and returns its final bounds. Fails with error_type 'scroll_failed' if it is still out of view after max_scrolls scrolls. This action may change the UI."
+    )]
+    async fn scroll_into_view(
+        &self,
+        Parameters(args): Parameters<ScrollIntoViewArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let action = |element: UIElement| async move { Ok(element) };
+        let ((element, _), successful_selector) = find_and_execute_with_retry_with_fallback(
+            &self.desktop,
+            &args.selector,
+            args.alternative_selectors.as_deref(),
+            args.fallback_selectors.as_deref(),
+            args.timeout_ms,
+            args.retries,
+            action,
+        )
+        .await
+        .map_err(|e| {
+            build_element_not_found_error(
+                &args.selector,
+                args.alternative_selectors.as_deref(),
+                args.fallback_selectors.as_deref(),
+                e,
+            )
+        })?;
+
+        let options = ScrollOptions {
+            max_scrolls: args.max_scrolls.unwrap_or(scroll_view::DEFAULT_MAX_SCROLLS),
+            amount: args
+                .scroll_amount
+                .unwrap_or(scroll_view::DEFAULT_SCROLL_AMOUNT),
+            ..Default::default()
+        };
+        let in_view = scroll_view::scroll_into_view(&element, &options)
+            .await
+            .map_err(scroll_error)?;
+
+        let mut result_json = json!({
+            "action": "scroll_into_view",
+            "status": "success",
+            "element": build_element_info(&element),
+            "selector_used": successful_selector,
+            "selectors_tried": get_selectors_tried_all(&args.selector, args.alternative_selectors.as_deref(), args.fallback_selectors.as_deref()),
+            "bounds": in_view.bounds,
+            "viewport": in_view.viewport,
+            "scrolls": in_view.scrolls,
             "timestamp": chrono::Utc::now().to_rfc3339()
         });
         maybe_attach_tree(
@@ -2227,198 +2764,157 @@ impl DesktopWrapper {
     }
 
     #[tool(
-        description = "Waits for an element to meet a specific condition (visible, enabled, focused, exists)."
+        description = "Waits for an element to meet a specific condition (visible, enabled, focused, exists), returning as soon as it does. Use this instead of a fixed delay. The timeout and the interval between checks take duration strings such as '10s' or '250ms'. Optionally also waits for an expression over the workflow variables to hold."
     )]
     async fn wait_for_element(
         &self,
+        request_context: RequestContext<RoleServer>,
         Parameters(args): Parameters<WaitForElementArgs>,
     ) -> Result<CallToolResult, McpError> {
         info!(
-            "[wait_for_element] Called with selector: '{}', condition: '{}', timeout_ms: {:?}, include_tree: {:?}",
-            args.selector, args.condition, args.timeout_ms, args.include_tree
+            "[wait_for_element] Called with selector: '{}', condition: '{}', timeout: {:?}, timeout_ms: {:?}, poll_interval: {:?}, include_tree: {:?}",
+            args.selector, args.condition, args.timeout, args.timeout_ms, args.poll_interval, args.include_tree
         );
 
-        let locator = self.desktop.locator(Selector::from(args.selector.as_str()));
-        let timeout = get_timeout(args.timeout_ms);
-        let condition_lower = args.condition.to_lowercase();
-
-        // For the "exists" condition, we can use the standard wait
-        if condition_lower == "exists" {
-            info!(
-                "[wait_for_element] Waiting for element to exist: selector='{}', timeout={:?}",
-                args.selector, timeout
-            );
-            match locator.wait(timeout).await {
-                Ok(element) => {
-                    info!(
-                        "[wait_for_element] Element found for selector='{}' within timeout.",
-                        args.selector
-                    );
-                    let mut result_json = json!({
-                        "action": "wait_for_element",
-                        "status": "success",
-                        "condition": args.condition,
-                        "condition_met": true,
-                        "selector": args.selector,
-                        "timeout_ms": args.timeout_ms.unwrap_or(5000),
-                        "timestamp": chrono::Utc::now().to_rfc3339()
-                    });
-
-                    maybe_attach_tree(
-                        &self.desktop,
-                        Self::get_include_tree_default(args.include_tree),
-                        args.include_detailed_attributes,
-                        element.process_id().ok(),
-                        &mut result_json,
-                    );
+        let condition = ElementCondition::parse(&args.condition).ok_or_else(|| {
+            McpError::invalid_params(
+                "Invalid condition. Valid: exists, visible, enabled, focused",
+                Some(json!({"provided_condition": args.condition})),
+            )
+        })?;
+        let (timeout, poll_interval) = parse_wait_timing(
+            args.timeout.as_deref(),
+            args.timeout_ms,
+            args.poll_interval.as_deref(),
+            get_timeout(None).unwrap_or_default(),
+        )
+        .map_err(|reason| {
+            McpError::invalid_params(
+                "Invalid wait timing",
+                Some(json!({
+                    "reason": reason,
+                    "timeout": args.timeout,
+                    "poll_interval": args.poll_interval
+                })),
+            )
+        })?;
 
-                    return Ok(CallToolResult::success(vec![Content::json(result_json)?]));
-                }
-                Err(e) => {
-                    let error_msg = format!("Element not found within timeout: {e}");
-                    info!(
-                        "[wait_for_element] Element NOT found for selector='{}' within timeout. Error: {}",
-                        args.selector, e
-                    );
-                    return Err(McpError::internal_error(
-                        error_msg,
-                        Some(json!({
-                            "selector": args.selector,
-                            "condition": args.condition,
-                            "timeout_ms": args.timeout_ms.unwrap_or(5000),
-                            "error": e.to_string()
-                        })),
-                    ));
+        let locator = self.desktop.locator(Selector::from(args.selector.as_str()));
+        let expression = args.expression.as_deref();
+        let variables = request_context
+            .extensions
+            .get::<WorkflowVariables>()
+            .map_or_else(|| json!({}), |variables| variables.0.clone());
+        let found_pid = std::sync::Mutex::new(None::<u32>);
+
+        let locator_ref = &locator;
+        let found_pid_ref = &found_pid;
+        let outcome = wait_until(
+            move || async move {
+                match locator_ref
+                    .wait(Some(std::time::Duration::from_millis(100)))
+                    .await
+                {
+                    Ok(element) => {
+                        *found_pid_ref.lock().unwrap() = element.process_id().ok();
+                        match condition {
+                            ElementCondition::Exists => true,
+                            ElementCondition::Visible => element.is_visible().unwrap_or(false),
+                            ElementCondition::Enabled => element.is_enabled().unwrap_or(false),
+                            ElementCondition::Focused => element.is_focused().unwrap_or(false),
+                        }
+                    }
+                    Err(_) => false,
                 }
-            }
-        }
-
-        // For other conditions (visible, enabled, focused), we need to poll
-        let start_time = std::time::Instant::now();
-        let timeout_duration = timeout.unwrap_or(std::time::Duration::from_millis(5000));
-        info!(
-            "[wait_for_element] Polling for condition '{}' on selector='{}' with timeout {:?}",
-            args.condition, args.selector, timeout_duration
-        );
+            },
+            expression,
+            || variables.clone(),
+            timeout,
+            poll_interval,
+            &request_context.ct,
+        )
+        .await;
 
-        loop {
-            // Check if we've exceeded the timeout
-            if start_time.elapsed() > timeout_duration {
-                let timeout_msg = format!(
-                    "Timeout waiting for element to be {} within {}ms",
-                    args.condition,
-                    timeout_duration.as_millis()
-                );
+        match outcome {
+            Ok(status) => {
                 info!(
-                    "[wait_for_element] Timeout exceeded for selector='{}', condition='{}', waited {}ms",
-                    args.selector, args.condition, start_time.elapsed().as_millis()
+                    "[wait_for_element] Condition '{}' met for selector='{}' after {}ms ({} polls)",
+                    args.condition, args.selector, status.elapsed_ms, status.polls
                 );
-                return Err(McpError::internal_error(
-                    timeout_msg,
+                let mut result_json = json!({
+                    "action": "wait_for_element",
+                    "status": "success",
+                    "condition": args.condition,
+                    "condition_met": true,
+                    "selector": args.selector,
+                    "timeout_ms": timeout.as_millis(),
+                    "poll_interval_ms": poll_interval.as_millis(),
+                    "polls": status.polls,
+                    "elapsed_ms": status.elapsed_ms,
+                    "timestamp": chrono::Utc::now().to_rfc3339()
+                });
+                if let Some(expression) = expression {
+                    result_json["expression"] = json!(expression);
+                }
+                let pid = *found_pid.lock().unwrap();
+                maybe_attach_tree(
+                    &self.desktop,
+                    Self::get_include_tree_default(args.include_tree),
+                    args.include_detailed_attributes,
+                    pid,
+                    &mut result_json,
+                );
+                Ok(CallToolResult::success(vec![Content::json(result_json)?]))
+            }
+            Err(WaitFailure::Cancelled(status)) => {
+                warn!(
+                    "[wait_for_element] Request cancelled while waiting for selector='{}' after {}ms",
+                    args.selector, status.elapsed_ms
+                );
+                Err(McpError::internal_error(
+                    "Request cancelled by user",
                     Some(json!({
+                        "code": -32001,
+                        "reason": "user_cancelled",
                         "selector": args.selector,
                         "condition": args.condition,
-                        "timeout_ms": args.timeout_ms.unwrap_or(5000),
-                        "elapsed_ms": start_time.elapsed().as_millis()
+                        "elapsed_ms": status.elapsed_ms
                     })),
-                ));
+                ))
             }
-
-            // Try to find the element with a short timeout
-            match locator
-                .wait(Some(std::time::Duration::from_millis(100)))
-                .await
-            {
-                Ok(element) => {
-                    info!(
-                        "[wait_for_element] Element found for selector='{}', checking condition '{}'",
-                        args.selector, args.condition
-                    );
-                    // Element exists, now check the specific condition
-                    let condition_met = match condition_lower.as_str() {
-                        "visible" => {
-                            let v = element.is_visible().unwrap_or(false);
-                            info!(
-                                "[wait_for_element] is_visible() for selector='{}': {}",
-                                args.selector, v
-                            );
-                            v
-                        }
-                        "enabled" => {
-                            let v = element.is_enabled().unwrap_or(false);
-                            info!(
-                                "[wait_for_element] is_enabled() for selector='{}': {}",
-                                args.selector, v
-                            );
-                            v
-                        }
-                        "focused" => {
-                            let v = element.is_focused().unwrap_or(false);
-                            info!(
-                                "[wait_for_element] is_focused() for selector='{}': {}",
-                                args.selector, v
-                            );
-                            v
-                        }
-                        _ => {
-                            info!(
-                                "[wait_for_element] Invalid condition provided: '{}'",
-                                args.condition
-                            );
-                            return Err(McpError::invalid_params(
-                                "Invalid condition. Valid: exists, visible, enabled, focused",
-                                Some(json!({"provided_condition": args.condition})),
-                            ));
-                        }
-                    };
-
-                    if condition_met {
-                        info!(
-                            "[wait_for_element] Condition '{}' met for selector='{}' after {}ms",
-                            args.condition,
-                            args.selector,
-                            start_time.elapsed().as_millis()
-                        );
-                        // Condition is met, return success
-                        let mut result_json = json!({
-                            "action": "wait_for_element",
-                            "status": "success",
-                            "condition": args.condition,
-                            "condition_met": true,
-                            "selector": args.selector,
-                            "timeout_ms": args.timeout_ms.unwrap_or(5000),
-                            "elapsed_ms": start_time.elapsed().as_millis(),
-                            "timestamp": chrono::Utc::now().to_rfc3339()
-                        });
-
-                        maybe_attach_tree(
-                            &self.desktop,
-                            Self::get_include_tree_default(args.include_tree),
-                            args.include_detailed_attributes,
-                            element.process_id().ok(),
-                            &mut result_json,
-                        );
-
-                        return Ok(CallToolResult::success(vec![Content::json(result_json)?]));
-                    } else {
-                        info!(
-                            "[wait_for_element] Condition '{}' NOT met for selector='{}', continuing to poll...",
-                            args.condition, args.selector
-                        );
-                    }
-                    // Condition not met yet, continue polling
-                }
-                Err(_) => {
-                    info!(
-                        "[wait_for_element] Element not found for selector='{}', will retry...",
-                        args.selector
-                    );
-                    // Element doesn't exist yet, continue polling
+            Err(WaitFailure::TimedOut(status)) => {
+                info!(
+                    "[wait_for_element] Timeout exceeded for selector='{}', condition='{}', waited {}ms",
+                    args.selector, args.condition, status.elapsed_ms
+                );
+                let message = match expression {
+                    Some(expression) => format!(
+                        "Timeout waiting for element to be {} and '{}' to hold within {}ms",
+                        args.condition,
+                        expression,
+                        timeout.as_millis()
+                    ),
+                    None => format!(
+                        "Timeout waiting for element to be {} within {}ms",
+                        args.condition,
+                        timeout.as_millis()
+                    ),
+                };
+                let mut details = json!({
+                    "selector": args.selector,
+                    "condition": args.condition,
+                    "element_condition_met": status.element_met,
+                    "timeout_ms": timeout.as_millis(),
+                    "poll_interval_ms": poll_interval.as_millis(),
+                    "polls": status.polls,
+                    "elapsed_ms": status.elapsed_ms
+                });
+                if let Some(expression) = expression {
+                    details["expression"] = json!(expression);
+                    details["expression_met"] = json!(status.expression_met);
                 }
+                Err(McpError::internal_error(message, Some(details)))
             }
-
-            // Wait a bit before the next poll
-            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
         }
     }
 
@@ -2530,6 +3026,12 @@ impl DesktopWrapper {
                 )),
             }?;
 
+        self.record_action(JournalEntry::irreversible(
+            "close_element",
+            Some(&successful_selector),
+        ))
+        .await;
+
         let element_info = build_element_info(&element);
 
         Ok(CallToolResult::success(vec![Content::json(json!({
@@ -2750,10 +3252,13 @@ impl DesktopWrapper {
             if let Err(e) = Self::ensure_element_in_view(&element) {
                 tracing::warn!("Failed to ensure element is in view for set_toggled: {e}");
             }
-            element.set_toggled_with_state(state)
+            let previous = element.is_toggled().ok();
+            element
+                .set_toggled_with_state(state)
+                .map(|result| (result, previous))
         };
 
-        let ((result, element), successful_selector) =
+        let (((result, previous), element), successful_selector) =
             match find_and_execute_with_retry_with_fallback(
                 &self.desktop,
                 &args.selector,
@@ -2776,6 +3281,19 @@ impl DesktopWrapper {
 
         let element_info = build_element_info(&element);
 
+        self.record_action(match previous {
+            Some(previous) => JournalEntry::reversible(
+                "set_toggled",
+                Some(&successful_selector),
+                Inverse::SetToggled {
+                    selector: successful_selector.clone(),
+                    state: previous,
+                },
+            ),
+            None => JournalEntry::irreversible("set_toggled", Some(&successful_selector)),
+        })
+        .await;
+
         let mut result_json = json!({
             "action": "set_toggled",
             "status": "success",
@@ -2812,10 +3330,11 @@ impl DesktopWrapper {
             if let Err(e) = Self::ensure_element_in_view(&element) {
                 tracing::warn!("Failed to ensure element is in view for set_range_value: {e}");
             }
-            element.set_range_value(value)
+            let previous = element.get_range_value().ok();
+            element.set_range_value(value).map(|()| previous)
         };
 
-        let ((_result, element), successful_selector) =
+        let ((previous, element), successful_selector) =
             match find_and_execute_with_retry_with_fallback(
                 &self.desktop,
                 &args.selector,
@@ -2836,6 +3355,19 @@ impl DesktopWrapper {
                 )),
             }?;
 
+        self.record_action(match previous {
+            Some(previous) => JournalEntry::reversible(
+                "set_range_value",
+                Some(&successful_selector),
+                Inverse::SetRangeValue {
+                    selector: successful_selector.clone(),
+                    value: previous,
+                },
+            ),
+            None => JournalEntry::irreversible("set_range_value", Some(&successful_selector)),
+        })
+        .await;
+
         let element_info = build_element_info(&element);
 
         let mut result_json = json!({
@@ -2864,10 +3396,14 @@ impl DesktopWrapper {
         Parameters(args): Parameters<SetSelectedArgs>,
     ) -> Result<CallToolResult, McpError> {
         let state = args.state;
-        let action =
-            move |element: UIElement| async move { element.set_selected_with_state(state) };
+        let action = move |element: UIElement| async move {
+            let previous = element.is_selected().ok();
+            element
+                .set_selected_with_state(state)
+                .map(|result| (result, previous))
+        };
 
-        let ((result, element), successful_selector) =
+        let (((result, previous), element), successful_selector) =
             match find_and_execute_with_retry_with_fallback(
                 &self.desktop,
                 &args.selector,
@@ -2888,6 +3424,19 @@ impl DesktopWrapper {
                 )),
             }?;
 
+        self.record_action(match previous {
+            Some(previous) => JournalEntry::reversible(
+                "set_selected",
+                Some(&successful_selector),
+                Inverse::SetSelected {
+                    selector: successful_selector.clone(),
+                    state: previous,
+                },
+            ),
+            None => JournalEntry::irreversible("set_selected", Some(&successful_selector)),
+        })
+        .await;
+
         let element_info = build_element_info(&element);
 
         let mut result_json = json!({
@@ -3010,60 +3559,498 @@ impl DesktopWrapper {
     }
 
     #[tool(
-        description = "Checks if a selectable item (e.g., in a calendar, list, or tab) is currently selected. This is a read-only operation."
+        description = "Reads a cell of a table or data grid by data row index and column header label, so workflows survive column reordering. This is a read-only operation."
+    )]
+    async fn get_cell(
+        &self,
+        Parameters(args): Parameters<GetCellArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let row = args.row;
+        let column_header = args.column_header.clone();
+        let ((cell, table), successful_selector) = match find_and_execute_with_retry_with_fallback(
+            &self.desktop,
+            &args.selector,
+            args.alternative_selectors.as_deref(),
+            args.fallback_selectors.as_deref(),
+            args.timeout_ms,
+            args.retries,
+            |element| {
+                let column_header = column_header.clone();
+                async move { element.get_cell(row, &column_header) }
+            },
+        )
+        .await
+        {
+            Ok(((result, element), selector)) => Ok(((result, element), selector)),
+            Err(e) => match e.downcast_ref::<terminator::AutomationError>() {
+                Some(terminator::AutomationError::InvalidArgument(msg)) => {
+                    Err(McpError::invalid_params(
+                        "Unknown column header",
+                        Some(json!({
+                            "error": msg,
+                            "column_header": args.column_header,
+                            "selector": args.selector,
+                        })),
+                    ))
+                }
+                _ => Err(build_element_not_found_error(
+                    &args.selector,
+                    args.alternative_selectors.as_deref(),
+                    args.fallback_selectors.as_deref(),
+                    e,
+                )),
+            },
+        }?;
+
+        let value = cell
+            .name()
+            .filter(|name| !name.is_empty())
+            .or_else(|| cell.text(2).ok())
+            .unwrap_or_default();
+
+        let mut result_json = json!({
+            "action": "get_cell",
+            "status": "success",
+            "element": build_element_info(&table),
+            "cell": build_element_info(&cell),
+            "row": args.row,
+            "column_header": args.column_header,
+            "value": value,
+            "selector_used": successful_selector,
+            "selectors_tried": get_selectors_tried_all(&args.selector, args.alternative_selectors.as_deref(), args.fallback_selectors.as_deref()),
+        });
+        maybe_attach_tree(
+            &self.desktop,
+            Self::get_include_tree_default(args.include_tree),
+            args.include_detailed_attributes,
+            table.process_id().ok(),
+            &mut result_json,
+        );
+        Ok(CallToolResult::success(vec![Content::json(result_json)?]))
+    }
+
+    #[tool(
+        description = "Re-finds an element by its stable identity key after the UI re-rendered (e.g. a list refresh). Element info returned by other tools includes an 'identity_key' derived from the automation id or the element's content; pass it here with a selector for the scope to search. This is a read-only operation."
+    )]
+    async fn find_by_identity(
+        &self,
+        Parameters(args): Parameters<FindByIdentityArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let identity_key = args.identity_key.clone();
+        let max_depth = args.max_depth;
+        let ((element, scope), successful_selector) =
+            match find_and_execute_with_retry_with_fallback(
+                &self.desktop,
+                &args.selector,
+                args.alternative_selectors.as_deref(),
+                args.fallback_selectors.as_deref(),
+                args.timeout_ms,
+                args.retries,
+                |scope| {
+                    let identity_key = identity_key.clone();
+                    async move { scope.find_by_identity(&identity_key, max_depth) }
+                },
+            )
+            .await
+            {
+                Ok(((result, scope), selector)) => Ok(((result, scope), selector)),
+                Err(e) => match e.downcast_ref::<terminator::AutomationError>() {
+                    Some(terminator::AutomationError::InvalidArgument(msg)) => {
+                        Err(McpError::invalid_params(
+                            "Invalid identity key",
+                            Some(json!({
+                                "error": msg,
+                                "identity_key": args.identity_key,
+                            })),
+                        ))
+                    }
+                    _ => Err(build_element_not_found_error(
+                        &args.selector,
+                        args.alternative_selectors.as_deref(),
+                        args.fallback_selectors.as_deref(),
+                        e,
+                    )),
+                },
+            }?;
+
+        let mut result_json = json!({
+            "action": "find_by_identity",
+            "status": "success",
+            "identity_key": args.identity_key,
+            "element": build_element_info(&element),
+            "scope": build_element_info(&scope),
+            "selector_used": successful_selector,
+            "selectors_tried": get_selectors_tried_all(&args.selector, args.alternative_selectors.as_deref(), args.fallback_selectors.as_deref()),
+        });
+        maybe_attach_tree(
+            &self.desktop,
+            Self::get_include_tree_default(args.include_tree),
+            args.include_detailed_attributes,
+            element.process_id().ok(),
+            &mut result_json,
+        );
+        Ok(CallToolResult::success(vec![Content::json(result_json)?]))
+    }
+
+    #[tool(
+        description = "Checks if a selectable item (e.g., in a calendar, list, or tab) is currently selected. This is a read-only operation."
+    )]
+    async fn is_selected(
+        &self,
+        Parameters(args): Parameters<LocatorArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let ((is_selected, element), successful_selector) =
+            match find_and_execute_with_retry_with_fallback(
+                &self.desktop,
+                &args.selector,
+                args.alternative_selectors.as_deref(),
+                args.fallback_selectors.as_deref(),
+                args.timeout_ms,
+                args.retries,
+                |element| async move { element.is_selected() },
+            )
+            .await
+            {
+                Ok(((result, element), selector)) => Ok(((result, element), selector)),
+                Err(e) => Err(build_element_not_found_error(
+                    &args.selector,
+                    args.alternative_selectors.as_deref(),
+                    args.fallback_selectors.as_deref(),
+                    e,
+                )),
+            }?;
+
+        let element_info = build_element_info(&element);
+
+        let mut result_json = json!({
+            "action": "is_selected",
+            "status": "success",
+            "element": element_info,
+            "selector_used": successful_selector,
+            "selectors_tried": get_selectors_tried_all(&args.selector, args.alternative_selectors.as_deref(), args.fallback_selectors.as_deref()),
+            "is_selected": is_selected,
+        });
+        maybe_attach_tree(
+            &self.desktop,
+            Self::get_include_tree_default(args.include_tree),
+            args.include_detailed_attributes,
+            element.process_id().ok(),
+            &mut result_json,
+        );
+        Ok(CallToolResult::success(vec![Content::json(result_json)?]))
+    }
+
+    #[tool(description = "Captures a screenshot of a specific UI element.")]
+    async fn capture_element_screenshot(
+        &self,
+        Parameters(args): Parameters<ValidateElementArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let ((screenshot_result, element), successful_selector) =
+            match find_and_execute_with_retry_with_fallback(
+                &self.desktop,
+                &args.selector,
+                args.alternative_selectors.as_deref(),
+                args.fallback_selectors.as_deref(),
+                args.timeout_ms,
+                args.retries,
+                |element| async move { element.capture() },
+            )
+            .await
+            {
+                Ok(((result, element), selector)) => Ok(((result, element), selector)),
+                Err(e) => Err(build_element_not_found_error(
+                    &args.selector,
+                    args.alternative_selectors.as_deref(),
+                    args.fallback_selectors.as_deref(),
+                    e,
+                )),
+            }?;
+
+        let mut png_data = Vec::new();
+        let encoder = PngEncoder::new(Cursor::new(&mut png_data));
+        encoder
+            .write_image(
+                &screenshot_result.image_data,
+                screenshot_result.width,
+                screenshot_result.height,
+                ExtendedColorType::Rgba8,
+            )
+            .map_err(|e| {
+                McpError::internal_error(
+                    "Failed to encode screenshot to PNG",
+                    Some(json!({ "reason": e.to_string() })),
+                )
+            })?;
+
+        let base64_image = general_purpose::STANDARD.encode(&png_data);
+
+        let element_info = build_element_info(&element);
+
+        Ok(CallToolResult::success(vec![
+            Content::json(json!({
+                "action": "capture_element_screenshot",
+                "status": "success",
+                "element": element_info,
+                "selector_used": successful_selector,
+                "selectors_tried": get_selectors_tried_all(&args.selector, args.alternative_selectors.as_deref(), args.fallback_selectors.as_deref()),
+                "image_format": "png",
+            }))?,
+            Content::image(base64_image, "image/png".to_string()),
+        ]))
+    }
+
+    #[tool(
+        description = "Lists the connected monitors with their index, bounds in desktop coordinates, scale factor (DPI scaling), logical size and whether each is primary. Pass an index as 'monitor' to coordinate and screenshot tools to target that display. This is a read-only operation."
+    )]
+    async fn list_monitors(
+        &self,
+        Parameters(_args): Parameters<EmptyArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        let monitors = monitors::list_monitors(&*self.desktop)
+            .await
+            .map_err(monitor_error)?;
+
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "action": "list_monitors",
+            "status": "success",
+            "count": monitors.len(),
+            "monitors": monitors,
+        }))?]))
+    }
+
+    #[tool(
+        description = "Captures a screenshot of the monitor showing a UI element with a rectangle drawn over the element's bounding box, for debugging selectors or giving a vision model context. Returns the PNG plus the element's bounds in desktop coordinates, the monitor captured, the highlighted area in image pixels and 'clipped' when part of the element is off that monitor. Fails when the element is entirely off-screen. This is a read-only operation."
+    )]
+    async fn capture_highlighted_screenshot(
+        &self,
+        Parameters(args): Parameters<HighlightedScreenshotArgs>,
+    ) -> Result<CallToolResult, McpError> {
+        use crate::highlight_screenshot::{
+            capture_highlighted, HighlightStyle, DEFAULT_BORDER_WIDTH, DEFAULT_COLOR_BGR,
+        };
+
+        let (element, successful_selector) = find_and_execute_with_retry_with_fallback(
+            &self.desktop,
+            &args.selector,
+            args.alternative_selectors.as_deref(),
+            args.fallback_selectors.as_deref(),
+            args.timeout_ms,
+            args.retries,
+            |_| async { Ok(()) },
+        )
+        .await
+        .map(|(((), element), selector)| (element, selector))
+        .map_err(|e| {
+            build_element_not_found_error(
+                &args.selector,
+                args.alternative_selectors.as_deref(),
+                args.fallback_selectors.as_deref(),
+                e,
+            )
+        })?;
+
+        let all_monitors = monitors::ordered_monitors(&*self.desktop)
+            .await
+            .map_err(monitor_error)?;
+        let candidates = match args.monitor {
+            Some(index) => vec![monitors::nth_monitor(&all_monitors, index)
+                .map_err(monitor_error)?
+                .clone()],
+            None => all_monitors.clone(),
+        };
+        let style = HighlightStyle {
+            color_bgr: args.color.unwrap_or(DEFAULT_COLOR_BGR),
+            border_width: args.border_width.unwrap_or(DEFAULT_BORDER_WIDTH),
+        };
+        let desktop = &self.desktop;
+        let screenshot = capture_highlighted(
+            &element,
+            &candidates,
+            |monitor| async move {
+                let capture = desktop
+                    .capture_monitor(&monitor)
+                    .await
+                    .map_err(|e| format!("Failed to capture monitor '{}': {e}", monitor.name))?;
+                image::RgbaImage::from_raw(capture.width, capture.height, capture.image_data)
+                    .ok_or_else(|| "Screenshot data does not match its size".to_string())
+            },
+            &style,
+        )
+        .await
+        .map_err(|e| {
+            McpError::internal_error(
+                "Failed to capture highlighted screenshot",
+                Some(json!({ "reason": e, "selector_used": successful_selector })),
+            )
+        })?;
+
+        let mut png_data = Vec::new();
+        PngEncoder::new(Cursor::new(&mut png_data))
+            .write_image(
+                screenshot.image.as_raw(),
+                screenshot.image.width(),
+                screenshot.image.height(),
+                ExtendedColorType::Rgba8,
+            )
+            .map_err(|e| {
+                McpError::internal_error(
+                    "Failed to encode screenshot to PNG",
+                    Some(json!({ "reason": e.to_string() })),
+                )
+            })?;
+
+        Ok(CallToolResult::success(vec![
+            Content::json(json!({
+                "action": "capture_highlighted_screenshot",
+                "status": "success",
+                "element": build_element_info(&element),
+                "selector_used": successful_selector,
+                "bounds": screenshot.bounds,
+                "monitor": screenshot.monitor,
+                "monitor_index": all_monitors.iter().position(|m| m.id == screenshot.monitor.id),
+                "highlight": screenshot.highlight,
+                "clipped": screenshot.clipped,
+                "image_format": "png",
+            }))?,
+            Content::image(
+                general_purpose::STANDARD.encode(&png_data),
+                "image/png".to_string(),
+            ),
+        ]))
+    }
+
+    #[tool(
+        description = "Reads text from part of the screen with OCR, for apps whose text is not in the accessibility tree. Give either a selector, whose bounding box is read, or a region as x, y, width and height. Returns the text and every word with its bounding box in desktop coordinates, so a word can be clicked. Fails with error_type 'ocr_not_enabled' when this build has no OCR engine (see ocr_available in capabilities); on Linux and macOS the tesseract binary must be installed. This is a read-only operation."
     )]
-    async fn is_selected(
+    async fn ocr_region(
         &self,
-        Parameters(args): Parameters<LocatorArgs>,
+        Parameters(args): Parameters<OcrRegionArgs>,
     ) -> Result<CallToolResult, McpError> {
-        let ((is_selected, element), successful_selector) =
-            match find_and_execute_with_retry_with_fallback(
-                &self.desktop,
-                &args.selector,
-                args.alternative_selectors.as_deref(),
-                args.fallback_selectors.as_deref(),
-                args.timeout_ms,
-                args.retries,
-                |element| async move { element.is_selected() },
-            )
+        use crate::highlight_screenshot::Rect;
+        use crate::ocr_region::{ocr_region, RegionSource};
+
+        let source = RegionSource::from_args(
+            args.selector.as_deref(),
+            args.x,
+            args.y,
+            args.width,
+            args.height,
+        )
+        .map_err(|e| McpError::invalid_params(e, None))?;
+
+        let all_monitors = monitors::ordered_monitors(&*self.desktop)
             .await
-            {
-                Ok(((result, element), selector)) => Ok(((result, element), selector)),
-                Err(e) => Err(build_element_not_found_error(
-                    &args.selector,
+            .map_err(monitor_error)?;
+        let target = match args.monitor {
+            Some(index) => Some((
+                index,
+                monitors::nth_monitor(&all_monitors, index)
+                    .map_err(monitor_error)?
+                    .clone(),
+            )),
+            None => None,
+        };
+
+        let (region, element, successful_selector) = match source {
+            RegionSource::Selector(selector) => {
+                let (element, successful_selector) = find_and_execute_with_retry_with_fallback(
+                    &self.desktop,
+                    selector,
                     args.alternative_selectors.as_deref(),
                     args.fallback_selectors.as_deref(),
-                    e,
-                )),
-            }?;
+                    args.timeout_ms,
+                    args.retries,
+                    |_| async { Ok(()) },
+                )
+                .await
+                .map(|(((), element), selector)| (element, selector))
+                .map_err(|e| {
+                    build_element_not_found_error(
+                        selector,
+                        args.alternative_selectors.as_deref(),
+                        args.fallback_selectors.as_deref(),
+                        e,
+                    )
+                })?;
+                let (x, y, width, height) = element.bounds().map_err(|e| {
+                    McpError::internal_error(
+                        "Failed to get element bounds for ocr_region",
+                        Some(json!({ "selector": selector, "reason": e.to_string() })),
+                    )
+                })?;
+                let region = Rect {
+                    x,
+                    y,
+                    width,
+                    height,
+                };
+                (region, Some(element), Some(successful_selector))
+            }
+            RegionSource::Rect(region) => match &target {
+                Some((index, monitor)) => {
+                    let (x, y) = monitors::to_desktop(monitor, *index, region.x, region.y)
+                        .map_err(monitor_error)?;
+                    (Rect { x, y, ..region }, None, None)
+                }
+                None => (region, None, None),
+            },
+        };
 
-        let element_info = build_element_info(&element);
+        let candidates = match target {
+            Some((_, monitor)) => vec![monitor],
+            None => all_monitors.clone(),
+        };
+        let desktop = &self.desktop;
+        let text = ocr_region(
+            &terminator::ocr::PlatformOcr,
+            region,
+            &candidates,
+            |monitor| async move {
+                let capture = desktop
+                    .capture_monitor(&monitor)
+                    .await
+                    .map_err(|e| format!("Failed to capture monitor '{}': {e}", monitor.name))?;
+                image::RgbaImage::from_raw(capture.width, capture.height, capture.image_data)
+                    .ok_or_else(|| "Screenshot data does not match its size".to_string())
+            },
+            args.locale.as_deref(),
+        )
+        .await
+        .map_err(ocr_region_error)?;
 
-        let mut result_json = json!({
-            "action": "is_selected",
+        Ok(CallToolResult::success(vec![Content::json(json!({
+            "action": "ocr_region",
             "status": "success",
-            "element": element_info,
+            "element": element.as_ref().map(build_element_info),
             "selector_used": successful_selector,
-            "selectors_tried": get_selectors_tried_all(&args.selector, args.alternative_selectors.as_deref(), args.fallback_selectors.as_deref()),
-            "is_selected": is_selected,
-        });
-        maybe_attach_tree(
-            &self.desktop,
-            Self::get_include_tree_default(args.include_tree),
-            args.include_detailed_attributes,
-            element.process_id().ok(),
-            &mut result_json,
-        );
-        Ok(CallToolResult::success(vec![Content::json(result_json)?]))
+            "region": text.region,
+            "monitor": text.monitor,
+            "monitor_index": all_monitors.iter().position(|m| m.id == text.monitor.id),
+            "clipped": text.clipped,
+            "text": text.text,
+            "words": text.words,
+        }))?]))
     }
 
-    #[tool(description = "Captures a screenshot of a specific UI element.")]
-    async fn capture_element_screenshot(
+    #[tool(
+        description = "Asserts that a UI element looks like a stored baseline image. Captures the element (optionally a region of it) and compares it pixel by pixel with the baseline PNG; fails when more than 'tolerance' of the pixels differ, saving '<baseline>.diff.png' (differences in red) and '<baseline>.actual.png' next to the baseline. A missing baseline is created from the capture; set 'update_baseline' to replace it."
+    )]
+    async fn assert_visual(
         &self,
-        Parameters(args): Parameters<ValidateElementArgs>,
+        Parameters(args): Parameters<AssertVisualArgs>,
     ) -> Result<CallToolResult, McpError> {
+        use crate::visual_assert::{VisualOptions, DEFAULT_PIXEL_THRESHOLD, DEFAULT_TOLERANCE};
+
+        let tolerance = args.tolerance.unwrap_or(DEFAULT_TOLERANCE);
+        if !(0.0..=1.0).contains(&tolerance) {
+            return Err(McpError::invalid_params(
+                "tolerance must be between 0.0 and 1.0",
+                Some(json!({"tolerance": tolerance})),
+            ));
+        }
+
         let ((screenshot_result, element), successful_selector) =
-            match find_and_execute_with_retry_with_fallback(
+            find_and_execute_with_retry_with_fallback(
                 &self.desktop,
                 &args.selector,
                 args.alternative_selectors.as_deref(),
@@ -3073,47 +4060,74 @@ impl DesktopWrapper {
                 |element| async move { element.capture() },
             )
             .await
-            {
-                Ok(((result, element), selector)) => Ok(((result, element), selector)),
-                Err(e) => Err(build_element_not_found_error(
+            .map_err(|e| {
+                build_element_not_found_error(
                     &args.selector,
                     args.alternative_selectors.as_deref(),
                     args.fallback_selectors.as_deref(),
                     e,
-                )),
-            }?;
+                )
+            })?;
 
-        let mut png_data = Vec::new();
-        let encoder = PngEncoder::new(Cursor::new(&mut png_data));
-        encoder
-            .write_image(
-                &screenshot_result.image_data,
-                screenshot_result.width,
-                screenshot_result.height,
-                ExtendedColorType::Rgba8,
+        let mut capture = image::RgbaImage::from_raw(
+            screenshot_result.width,
+            screenshot_result.height,
+            screenshot_result.image_data,
+        )
+        .ok_or_else(|| McpError::internal_error("Screenshot data does not match its size", None))?;
+        if let Some(region) = &args.region {
+            capture = crate::visual_assert::crop(
+                &capture,
+                region.x,
+                region.y,
+                region.width,
+                region.height,
             )
-            .map_err(|e| {
+            .map_err(|e| McpError::invalid_params(e, Some(json!({"region": region}))))?;
+        }
+
+        // Relative baselines live with the workflow that uses them
+        let mut baseline = std::path::PathBuf::from(&args.baseline);
+        if baseline.is_relative() {
+            if let Some(workflow_dir) = self.current_workflow_dir.lock().await.as_ref() {
+                baseline = workflow_dir.join(baseline);
+            }
+        }
+
+        let options = VisualOptions {
+            tolerance,
+            pixel_threshold: args.pixel_threshold.unwrap_or(DEFAULT_PIXEL_THRESHOLD),
+            update_baseline: args.update_baseline.unwrap_or(false),
+        };
+        let outcome =
+            crate::visual_assert::assert_visual(&capture, &baseline, &options).map_err(|e| {
                 McpError::internal_error(
-                    "Failed to encode screenshot to PNG",
-                    Some(json!({ "reason": e.to_string() })),
+                    "Visual assertion could not run",
+                    Some(json!({"reason": e, "baseline": baseline})),
                 )
             })?;
 
-        let base64_image = general_purpose::STANDARD.encode(&png_data);
-
-        let element_info = build_element_info(&element);
+        let mut result_json = json!({
+            "action": "assert_visual",
+            "status": "success",
+            "element": build_element_info(&element),
+            "selector_used": successful_selector,
+            "tolerance": tolerance,
+        });
+        if let (Some(result), Ok(serde_json::Value::Object(fields))) =
+            (result_json.as_object_mut(), serde_json::to_value(&outcome))
+        {
+            result.extend(fields);
+        }
 
-        Ok(CallToolResult::success(vec![
-            Content::json(json!({
-                "action": "capture_element_screenshot",
-                "status": "success",
-                "element": element_info,
-                "selector_used": successful_selector,
-                "selectors_tried": get_selectors_tried_all(&args.selector, args.alternative_selectors.as_deref(), args.fallback_selectors.as_deref()),
-                "image_format": "png",
-            }))?,
-            Content::image(base64_image, "image/png".to_string()),
-        ]))
+        if !outcome.passed() {
+            result_json["status"] = json!("failed");
+            return Err(McpError::internal_error(
+                "Element does not match its visual baseline",
+                Some(result_json),
+            ));
+        }
+        Ok(CallToolResult::success(vec![Content::json(result_json)?]))
     }
 
     #[tool(
@@ -3150,6 +4164,12 @@ impl DesktopWrapper {
                 )),
             }?;
 
+        self.record_action(JournalEntry::irreversible(
+            "invoke_element",
+            Some(&successful_selector),
+        ))
+        .await;
+
         let element_info = build_element_info(&element);
 
         let mut result_json = json!({
@@ -3669,10 +4689,13 @@ impl DesktopWrapper {
         let value_to_set = args.value.clone();
         let action = move |element: UIElement| {
             let value_to_set = value_to_set.clone();
-            async move { element.set_value(&value_to_set) }
+            async move {
+                let previous = element.attributes().value;
+                element.set_value(&value_to_set).map(|()| previous)
+            }
         };
 
-        let ((_result, element), successful_selector) =
+        let ((previous, element), successful_selector) =
             match find_and_execute_with_retry_with_fallback(
                 &self.desktop,
                 &args.selector,
@@ -3693,6 +4716,19 @@ impl DesktopWrapper {
                 )),
             }?;
 
+        self.record_action(match previous {
+            Some(previous) => JournalEntry::reversible(
+                "set_value",
+                Some(&successful_selector),
+                Inverse::SetValue {
+                    selector: successful_selector.clone(),
+                    value: previous,
+                },
+            ),
+            None => JournalEntry::irreversible("set_value", Some(&successful_selector)),
+        })
+        .await;
+
         let element_info = build_element_info(&element);
 
         let mut result_json = json!({
@@ -4108,6 +5144,21 @@ Requires Chrome extension to be installed. See browser_dom_extraction.yml and de
         request_context: RequestContext<RoleServer>,
         tool_name: &str,
         arguments: &serde_json::Value,
+    ) -> Result<CallToolResult, McpError> {
+        let action = crate::middleware::Action::new(tool_name, arguments).nested();
+        self.middleware
+            .run(action, || {
+                self.dispatch_tool_unwrapped(_peer, request_context, tool_name, arguments)
+            })
+            .await
+    }
+
+    async fn dispatch_tool_unwrapped(
+        &self,
+        _peer: Peer<RoleServer>,
+        request_context: RequestContext<RoleServer>,
+        tool_name: &str,
+        arguments: &serde_json::Value,
     ) -> Result<CallToolResult, McpError> {
         use rmcp::handler::server::wrapper::Parameters;
         match tool_name {
@@ -4120,6 +5171,13 @@ Requires Chrome extension to be installed. See browser_dom_extraction.yml and de
                     )),
                 }
             }
+            "foreground_app" => match serde_json::from_value::<EmptyArgs>(arguments.clone()) {
+                Ok(args) => self.foreground_app(Parameters(args)).await,
+                Err(e) => Err(McpError::invalid_params(
+                    "Invalid arguments for foreground_app",
+                    Some(json!({"error": e.to_string()})),
+                )),
+            },
             "get_focused_window_tree" => {
                 match serde_json::from_value::<GetFocusedWindowTreeArgs>(arguments.clone()) {
                     Ok(args) => self.get_focused_window_tree(Parameters(args)).await,
@@ -4183,7 +5241,10 @@ Requires Chrome extension to be installed. See browser_dom_extraction.yml and de
             }
             "wait_for_element" => {
                 match serde_json::from_value::<WaitForElementArgs>(arguments.clone()) {
-                    Ok(args) => self.wait_for_element(Parameters(args)).await,
+                    Ok(args) => {
+                        self.wait_for_element(request_context, Parameters(args))
+                            .await
+                    }
                     Err(e) => Err(McpError::invalid_params(
                         "Invalid arguments for wait_for_element",
                         Some(json!({"error": e.to_string()})),
@@ -4236,6 +5297,15 @@ Requires Chrome extension to be installed. See browser_dom_extraction.yml and de
                     )),
                 }
             }
+            "scroll_into_view" => {
+                match serde_json::from_value::<ScrollIntoViewArgs>(arguments.clone()) {
+                    Ok(args) => self.scroll_into_view(Parameters(args)).await,
+                    Err(e) => Err(McpError::invalid_params(
+                        "Invalid arguments for scroll_into_view",
+                        Some(json!({"error": e.to_string()})),
+                    )),
+                }
+            }
             "delay" => match serde_json::from_value::<DelayArgs>(arguments.clone()) {
                 Ok(args) => self.delay(Parameters(args)).await,
                 Err(e) => Err(McpError::invalid_params(
@@ -4263,6 +5333,13 @@ Requires Chrome extension to be installed. See browser_dom_extraction.yml and de
                     Some(json!({"error": e.to_string()})),
                 )),
             },
+            "drag_and_drop" => match serde_json::from_value::<DragAndDropArgs>(arguments.clone()) {
+                Ok(args) => self.drag_and_drop(Parameters(args)).await,
+                Err(e) => Err(McpError::invalid_params(
+                    "Invalid arguments for drag_and_drop",
+                    Some(json!({"error": e.to_string()})),
+                )),
+            },
             "mouse_drag" => match serde_json::from_value::<MouseDragArgs>(arguments.clone()) {
                 Ok(args) => self.mouse_drag(Parameters(args)).await,
                 Err(e) => Err(McpError::invalid_params(
@@ -4341,6 +5418,146 @@ Requires Chrome extension to be installed. See browser_dom_extraction.yml and de
                     Some(json!({"error": e.to_string()})),
                 )),
             },
+            "find_by_identity" => {
+                match serde_json::from_value::<FindByIdentityArgs>(arguments.clone()) {
+                    Ok(args) => self.find_by_identity(Parameters(args)).await,
+                    Err(e) => Err(McpError::invalid_params(
+                        "Invalid arguments for find_by_identity",
+                        Some(json!({"error": e.to_string()})),
+                    )),
+                }
+            }
+            "get_cell" => match serde_json::from_value::<GetCellArgs>(arguments.clone()) {
+                Ok(args) => self.get_cell(Parameters(args)).await,
+                Err(e) => Err(McpError::invalid_params(
+                    "Invalid arguments for get_cell",
+                    Some(json!({"error": e.to_string()})),
+                )),
+            },
+            "type_into_focused" => {
+                match serde_json::from_value::<TypeIntoFocusedArgs>(arguments.clone()) {
+                    Ok(args) => self.type_into_focused(Parameters(args)).await,
+                    Err(e) => Err(McpError::invalid_params(
+                        "Invalid arguments for type_into_focused",
+                        Some(json!({"error": e.to_string()})),
+                    )),
+                }
+            }
+            "clipboard_get" => match serde_json::from_value::<EmptyArgs>(arguments.clone()) {
+                Ok(args) => self.clipboard_get(Parameters(args)).await,
+                Err(e) => Err(McpError::invalid_params(
+                    "Invalid arguments for clipboard_get",
+                    Some(json!({"error": e.to_string()})),
+                )),
+            },
+            "clipboard_set" => {
+                match serde_json::from_value::<ClipboardSetArgs>(arguments.clone()) {
+                    Ok(args) => self.clipboard_set(Parameters(args)).await,
+                    Err(e) => Err(McpError::invalid_params(
+                        "Invalid arguments for clipboard_set",
+                        Some(json!({"error": e.to_string()})),
+                    )),
+                }
+            }
+            "press_keys" => match serde_json::from_value::<PressKeysArgs>(arguments.clone()) {
+                Ok(args) => self.press_keys(Parameters(args)).await,
+                Err(e) => Err(McpError::invalid_params(
+                    "Invalid arguments for press_keys",
+                    Some(json!({"error": e.to_string()})),
+                )),
+            },
+            "capabilities" => match serde_json::from_value::<EmptyArgs>(arguments.clone()) {
+                Ok(args) => self.capabilities(Parameters(args)).await,
+                Err(e) => Err(McpError::invalid_params(
+                    "Invalid arguments for capabilities",
+                    Some(json!({"error": e.to_string()})),
+                )),
+            },
+            "list_monitors" => match serde_json::from_value::<EmptyArgs>(arguments.clone()) {
+                Ok(args) => self.list_monitors(Parameters(args)).await,
+                Err(e) => Err(McpError::invalid_params(
+                    "Invalid arguments for list_monitors",
+                    Some(json!({"error": e.to_string()})),
+                )),
+            },
+            "latency_report" => match serde_json::from_value::<EmptyArgs>(arguments.clone()) {
+                Ok(args) => self.latency_report(Parameters(args)).await,
+                Err(e) => Err(McpError::invalid_params(
+                    "Invalid arguments for latency_report",
+                    Some(json!({"error": e.to_string()})),
+                )),
+            },
+            "capture_highlighted_screenshot" => {
+                match serde_json::from_value::<HighlightedScreenshotArgs>(arguments.clone()) {
+                    Ok(args) => self.capture_highlighted_screenshot(Parameters(args)).await,
+                    Err(e) => Err(McpError::invalid_params(
+                        "Invalid arguments for capture_highlighted_screenshot",
+                        Some(json!({"error": e.to_string()})),
+                    )),
+                }
+            }
+            "ocr_region" => match serde_json::from_value::<OcrRegionArgs>(arguments.clone()) {
+                Ok(args) => self.ocr_region(Parameters(args)).await,
+                Err(e) => Err(McpError::invalid_params(
+                    "Invalid arguments for ocr_region",
+                    Some(json!({"error": e.to_string()})),
+                )),
+            },
+            "assert_visual" => {
+                match serde_json::from_value::<AssertVisualArgs>(arguments.clone()) {
+                    Ok(args) => self.assert_visual(Parameters(args)).await,
+                    Err(e) => Err(McpError::invalid_params(
+                        "Invalid arguments for assert_visual",
+                        Some(json!({"error": e.to_string()})),
+                    )),
+                }
+            }
+            "check_fields" => match serde_json::from_value::<CheckFieldsArgs>(arguments.clone()) {
+                Ok(args) => self.check_fields(Parameters(args)).await,
+                Err(e) => Err(McpError::invalid_params(
+                    "Invalid arguments for check_fields",
+                    Some(json!({"error": e.to_string()})),
+                )),
+            },
+            "resolve_selectors" => {
+                match serde_json::from_value::<ResolveSelectorsArgs>(arguments.clone()) {
+                    Ok(args) => self.resolve_selectors(Parameters(args)).await,
+                    Err(e) => Err(McpError::invalid_params(
+                        "Invalid arguments for resolve_selectors",
+                        Some(json!({"error": e.to_string()})),
+                    )),
+                }
+            }
+            "find_elements" => {
+                match serde_json::from_value::<FindElementsArgs>(arguments.clone()) {
+                    Ok(args) => self.find_elements(Parameters(args)).await,
+                    Err(e) => Err(McpError::invalid_params(
+                        "Invalid arguments for find_elements",
+                        Some(json!({"error": e.to_string()})),
+                    )),
+                }
+            }
+            "read_menu" => match serde_json::from_value::<ReadMenuArgs>(arguments.clone()) {
+                Ok(args) => self.read_menu(Parameters(args)).await,
+                Err(e) => Err(McpError::invalid_params(
+                    "Invalid arguments for read_menu",
+                    Some(json!({"error": e.to_string()})),
+                )),
+            },
+            "get_events" => match serde_json::from_value::<GetEventsArgs>(arguments.clone()) {
+                Ok(args) => self.get_events(Parameters(args)).await,
+                Err(e) => Err(McpError::invalid_params(
+                    "Invalid arguments for get_events",
+                    Some(json!({"error": e.to_string()})),
+                )),
+            },
+            "undo" => match serde_json::from_value::<UndoArgs>(arguments.clone()) {
+                Ok(args) => self.undo(Parameters(args)).await,
+                Err(e) => Err(McpError::invalid_params(
+                    "Invalid arguments for undo",
+                    Some(json!({"error": e.to_string()})),
+                )),
+            },
             "is_selected" => match serde_json::from_value::<LocatorArgs>(arguments.clone()) {
                 Ok(args) => self.is_selected(Parameters(args)).await,
                 Err(e) => Err(McpError::invalid_params(
@@ -4463,12 +5680,53 @@ Requires Chrome extension to be installed. See browser_dom_extraction.yml and de
     }
 }
 
-#[tool_handler]
 impl ServerHandler for DesktopWrapper {
+    async fn call_tool(
+        &self,
+        request: CallToolRequestParam,
+        context: RequestContext<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        // Middleware (safe mode, latency, ...) runs here rather than per tool so every
+        // entry point is covered
+        let arguments = request
+            .arguments
+            .clone()
+            .map(serde_json::Value::Object)
+            .unwrap_or_default();
+
+        // Large responses are gzipped for clients that opted in during the handshake
+        let compression = context.peer.peer_info().and_then(|client| {
+            crate::compression::negotiate(client.capabilities.experimental.as_ref())
+        });
+
+        let tool_name = request.name.clone();
+        let action = crate::middleware::Action::new(tool_name.as_ref(), &arguments);
+        let tcc = ToolCallContext::new(self, request, context);
+        let mut result = self
+            .middleware
+            .run(action, || self.tool_router.call(tcc))
+            .await;
+        if let (Ok(result), Some(settings)) = (result.as_mut(), compression) {
+            crate::compression::compress_result(result, &settings);
+        }
+        result
+    }
+
+    async fn list_tools(
+        &self,
+        _request: Option<PaginatedRequestParam>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListToolsResult, McpError> {
+        Ok(ListToolsResult::with_all_items(self.tool_router.list_all()))
+    }
+
     fn get_info(&self) -> ServerInfo {
         ServerInfo {
             protocol_version: ProtocolVersion::LATEST,
             capabilities: ServerCapabilities::builder()
+                .enable_experimental_with(std::collections::BTreeMap::from([
+                    crate::compression::server_capability(),
+                ]))
                 .enable_tools()
                 .enable_logging()
                 .build(),
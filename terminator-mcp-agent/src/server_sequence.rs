@@ -1,14 +1,34 @@
-use crate::helpers::substitute_variables;
+use crate::element_ref::{self, ElementRefError};
+use crate::expression_eval::{evaluate_checked, IndexedContext};
+use crate::helpers::{
+    env_updates_from_result, evaluate_step_gate, merge_step_outputs, step_succeeded,
+    substitute_variables, verify_step, StepGate,
+};
+use crate::modal_watcher::{spawn_modal_watcher, DesktopDialogSource};
+use crate::on_error::{self, ErrorAction, StepError};
 use crate::output_parser;
+use crate::parallel_group::{self, ChildOutcome, ParallelGroup};
+use crate::repeat_loop::{LoopGuard, RepeatUntil};
+use crate::retry_backoff::RetryBackoff;
+use crate::run_bundle::{RecordingBackend, RunBundle, RunOutcome, ToolBackend};
 use crate::server::extract_content_json;
+use crate::step_timeout;
 use crate::telemetry::{StepSpan, WorkflowSpan};
 use crate::utils::{DesktopWrapper, ExecuteSequenceArgs, SequenceItem, ToolCall, ToolGroup};
+use crate::variable_scope::{self, ScopeStack};
+use crate::variable_store::{collect_persistent_values, persistent_names, VariableStore};
+use crate::wait_predicate::WorkflowVariables;
+use crate::workflow_events::{ExecutionEvent, ExecutionEventEmitter, StepOutcome};
 use rmcp::model::{CallToolResult, Content};
 use rmcp::service::{Peer, RequestContext, RoleServer};
 use rmcp::ErrorData as McpError;
 use serde_json::{json, Value};
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
+use terminator::ErrorCategory;
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, info, warn};
 
 impl DesktopWrapper {
@@ -101,8 +121,54 @@ impl DesktopWrapper {
         &self,
         peer: Peer<RoleServer>,
         request_context: RequestContext<RoleServer>,
-        mut args: ExecuteSequenceArgs,
+        args: ExecuteSequenceArgs,
     ) -> Result<CallToolResult, McpError> {
+        let cancellation = request_context.ct.clone();
+        let dispatcher = Dispatcher {
+            server: self,
+            peer,
+            request_context,
+        };
+        let summary = self
+            .execute_sequence_with(&dispatcher, &cancellation, args)
+            .await?;
+        Ok(CallToolResult::success(vec![Content::json(summary)?]))
+    }
+
+    /// Runs a sequence with its tools executed by `backend` and returns the run summary.
+    ///
+    /// The server runs sequences with the tools dispatched to the desktop; replaying a
+    /// run bundle answers them from the recorded trace instead.
+    pub async fn execute_sequence_with<B: ToolBackend>(
+        &self,
+        backend: &B,
+        cancellation: &CancellationToken,
+        args: ExecuteSequenceArgs,
+    ) -> Result<Value, McpError> {
+        self.run_sequence(backend, cancellation, args, None).await
+    }
+
+    /// Runs a sequence like `execute_sequence_with`, starting from `initial_context`
+    /// instead of the variables resolved from the inputs, persisted values and saved
+    /// resume state. Replaying a run bundle starts from the context it recorded.
+    pub async fn execute_sequence_from<B: ToolBackend>(
+        &self,
+        backend: &B,
+        cancellation: &CancellationToken,
+        args: ExecuteSequenceArgs,
+        initial_context: serde_json::Map<String, Value>,
+    ) -> Result<Value, McpError> {
+        self.run_sequence(backend, cancellation, args, Some(initial_context))
+            .await
+    }
+
+    async fn run_sequence<B: ToolBackend>(
+        &self,
+        backend: &B,
+        cancellation: &CancellationToken,
+        mut args: ExecuteSequenceArgs,
+        initial_context: Option<serde_json::Map<String, Value>>,
+    ) -> Result<Value, McpError> {
         // Validate that either URL or steps are provided
         if args.url.is_none() && args.steps.as_ref().map(|s| s.is_empty()).unwrap_or(true) {
             return Err(McpError::invalid_params(
@@ -238,6 +304,9 @@ impl DesktopWrapper {
             if args.scripts_base_path.is_none() {
                 args.scripts_base_path = remote_workflow.scripts_base_path;
             }
+            if args.modal_watcher.is_none() {
+                args.modal_watcher = remote_workflow.modal_watcher;
+            }
         }
 
         // Set the scripts_base_path for file resolution in run_command and execute_browser_script
@@ -367,6 +436,32 @@ impl DesktopWrapper {
             }
         }
 
+        // Persistent variables from the previous run override their defaults
+        let persistent_variables = args
+            .variables
+            .as_ref()
+            .map(persistent_names)
+            .unwrap_or_default();
+        let persistence_key = args.persistence_key.clone().or_else(|| args.url.clone());
+        let variable_store = VariableStore::default_location();
+        if !persistent_variables.is_empty() && initial_context.is_none() {
+            match &persistence_key {
+                Some(key) => match variable_store.load(key).await {
+                    Ok(stored) => {
+                        for name in &persistent_variables {
+                            if let Some(value) = stored.get(name) {
+                                execution_context_map.insert(name.clone(), value.clone());
+                            }
+                        }
+                    }
+                    Err(e) => warn!("Failed to load persistent variables: {}", e),
+                },
+                None => warn!(
+                    "Workflow declares persistent variables but has no url or persistence_key; they will not be saved"
+                ),
+            }
+        }
+
         // Then override with user-provided inputs (inputs take precedence over defaults)
         if let Some(inputs) = &args.inputs {
             // Validate inputs is an object
@@ -484,7 +579,7 @@ impl DesktopWrapper {
         };
 
         // NEW: Load saved state if starting from a specific step
-        if start_from_index > 0 {
+        if start_from_index > 0 && initial_context.is_none() {
             if let Some(url) = &args.url {
                 if let Some(saved_env) = Self::load_workflow_state(url).await? {
                     execution_context_map.insert("env".to_string(), saved_env);
@@ -496,6 +591,15 @@ impl DesktopWrapper {
             }
         }
 
+        if let Some(initial_context) = initial_context {
+            execution_context_map = initial_context;
+        }
+        // What the run starts from, for the run bundle to replay it from the same point
+        let recorded_context = args
+            .export_bundle
+            .as_ref()
+            .map(|_| execution_context_map.clone());
+
         let execution_context = serde_json::Value::Object(execution_context_map.clone());
         debug!(
             "Executing sequence with context: {}",
@@ -526,6 +630,12 @@ impl DesktopWrapper {
         let empty_steps = Vec::new();
         let steps = args.steps.as_ref().unwrap_or(&empty_steps);
         for step in steps {
+            RepeatUntil::from_step(step)
+                .and(RetryBackoff::from_step(step))
+                .and(ParallelGroup::from_step(step))
+                .and(step_timeout::step_timeout(step))
+                .and(on_error::validate(step))
+                .map_err(|e| McpError::invalid_params(e, Some(json!({"invalid_step": step}))))?;
             let item = if let Some(tool_name) = &step.tool_name {
                 // Parse delay from either delay_ms or human-readable delay field
                 let delay_ms = if let Some(delay_str) = &step.delay {
@@ -583,6 +693,14 @@ impl DesktopWrapper {
                 troubleshooting.len()
             );
             for step in troubleshooting {
+                RepeatUntil::from_step(step)
+                    .and(RetryBackoff::from_step(step))
+                    .and(ParallelGroup::from_step(step))
+                    .and(step_timeout::step_timeout(step))
+                    .and(on_error::validate(step))
+                    .map_err(|e| {
+                        McpError::invalid_params(e, Some(json!({"invalid_step": step})))
+                    })?;
                 let item = if let Some(tool_name) = &step.tool_name {
                     // Parse delay from either delay_ms or human-readable delay field
                     let delay_ms = if let Some(delay_str) = &step.delay {
@@ -634,6 +752,32 @@ impl DesktopWrapper {
             }
         }
 
+        // Events emitted during this run (e.g. by the modal watcher), queryable by
+        // late clients through `get_events`
+        let run_id = uuid::Uuid::new_v4().to_string();
+        let execution_events = ExecutionEventEmitter::new();
+        crate::workflow_events::runs().register(&run_id, execution_events.clone());
+        info!("Started workflow run {}", run_id);
+        // Ends the run's live event streams, however the run ends
+        let run_guard = execution_events.run_started();
+
+        // Tool calls are recorded for the run bundle, if one was requested
+        let backend = RecordingBackend::new(backend, args.export_bundle.is_some());
+
+        // Start the modal watcher, scoped to this request, if the workflow configures one
+        let modal_watcher = match &args.modal_watcher {
+            Some(config) => Some(
+                spawn_modal_watcher(
+                    Arc::new(DesktopDialogSource::new(self.desktop.clone())),
+                    config,
+                    execution_events.clone(),
+                    cancellation,
+                )
+                .map_err(|e| McpError::invalid_params(e, None))?,
+            ),
+            None => None,
+        };
+
         // ---------------------------
         // Fallback-enabled execution loop (while-based)
         // ---------------------------
@@ -641,6 +785,7 @@ impl DesktopWrapper {
         let mut results = Vec::new();
         let mut sequence_had_errors = false;
         let mut critical_error_occurred = false;
+        let mut scopes = ScopeStack::new();
         let start_time = chrono::Utc::now();
 
         let mut current_index: usize = start_from_index;
@@ -699,7 +844,7 @@ impl DesktopWrapper {
             iterations += 1;
 
             // Check if the request has been cancelled
-            if request_context.ct.is_cancelled() {
+            if cancellation.is_cancelled() {
                 warn!("Request cancelled by user, stopping sequence execution");
                 return Err(McpError::internal_error(
                     "Request cancelled by user",
@@ -736,17 +881,25 @@ impl DesktopWrapper {
                     );
                 }
             }
+            let step_timer = execution_events.step_started(
+                current_index,
+                original_step.and_then(|s| s.id.as_deref()),
+                original_step.and_then(|s| s.tool_name.as_deref()),
+                original_step.and_then(|s| s.group_name.as_deref()),
+            );
 
-            // Extract values from the step if it exists
-            let (if_expr, retries, fallback_id_opt) = if let Some(step) = original_step {
+            // Extract values from the step if it exists (retry settings were validated upfront)
+            let (if_expr, backoff, fallback_id_opt) = if let Some(step) = original_step {
                 (
                     step.r#if.clone(),
-                    step.retries.unwrap_or(0),
+                    RetryBackoff::from_step(step).ok(),
                     step.fallback_id.clone(),
                 )
             } else {
-                (None, 0, None)
+                (None, None, None)
             };
+            let retries = backoff.as_ref().map_or(0, RetryBackoff::retries);
+            let timeout = original_step.and_then(|s| step_timeout::step_timeout(s).ok().flatten());
 
             let is_always_step = if_expr.as_deref().is_some_and(|s| s.trim() == "always()");
 
@@ -757,36 +910,87 @@ impl DesktopWrapper {
                     "status": "skipped",
                     "reason": "Skipped due to a previous unrecoverable error in the sequence."
                 }));
+                step_timer.finish(StepOutcome::Skipped);
                 current_index += 1;
                 continue;
             }
 
-            // 1. Evaluate condition, unless it's an 'always' step.
-            if let Some(cond_str) = &if_expr {
-                let execution_context = serde_json::Value::Object(execution_context_map.clone());
-                if !is_always_step
-                    && !crate::expression_eval::evaluate(cond_str, &execution_context)
-                {
+            // 1. Evaluate condition (unless it's an 'always' step) and precondition.
+            let precondition = original_step.and_then(|s| s.precondition.as_deref());
+            let execution_context = serde_json::Value::Object(execution_context_map.clone());
+            let gate = evaluate_step_gate(
+                if_expr.as_deref(),
+                precondition,
+                &IndexedContext::new(&execution_context),
+            );
+            match gate {
+                StepGate::Run => {}
+                StepGate::ConditionNotMet => {
+                    let cond_str = if_expr.as_deref().unwrap_or_default();
                     info!(
                         "Skipping step {} due to if expression not met: `{}`",
                         current_index, cond_str
                     );
-                    results.push(json!({
+                    let mut skipped = json!({
                         "index": current_index,
                         "status": "skipped",
                         "reason": format!("if_expr not met: {}", cond_str)
+                    });
+                    // A condition that could not be evaluated is reported, not just skipped
+                    if let Err(e) = evaluate_checked(cond_str, &execution_context) {
+                        warn!("Step {} has an invalid if expression: {}", current_index, e);
+                        skipped["error"] = json!(format!("Invalid if expression: {e}"));
+                    }
+                    results.push(skipped);
+                    step_timer.finish(StepOutcome::Skipped);
+                    current_index += 1;
+                    continue;
+                }
+                StepGate::AlreadySatisfied => {
+                    let pre_str = precondition.unwrap_or_default();
+                    info!(
+                        "Skipping step {} because its precondition is already satisfied: `{}`",
+                        current_index, pre_str
+                    );
+                    if let Some(step_id) = original_step.and_then(|s| s.id.as_deref()) {
+                        if let Some(env_map) = execution_context_map
+                            .get_mut("env")
+                            .and_then(|v| v.as_object_mut())
+                        {
+                            execution_events.set_variable(
+                                env_map,
+                                format!("{step_id}_status"),
+                                json!("satisfied"),
+                                Some(step_id),
+                            );
+                        }
+                    }
+                    results.push(json!({
+                        "index": current_index,
+                        "status": "satisfied",
+                        "reason": format!("precondition already met: {}", pre_str)
                     }));
+                    step_timer.finish(StepOutcome::Skipped);
                     current_index += 1;
                     continue;
                 }
             }
 
             // 2. Execute with retries
+            let errors_before = (sequence_had_errors, critical_error_occurred);
             let mut final_result = json!(null);
             let mut step_error_occurred = false;
+            let mut succeeded_on_attempt = None;
             let total_steps = sequence_items.len();
+            // Retry metrics are labeled by step id, falling back to the step's position
+            let retry_label = original_step
+                .and_then(|s| s.id.clone())
+                .unwrap_or_else(|| format!("step_{}", current_index + 1));
 
             for attempt in 0..=retries {
+                if attempt > 0 {
+                    crate::telemetry::record_retry_attempt(&retry_label);
+                }
                 let item = &mut sequence_items[current_index];
                 match item {
                     SequenceItem::Tool { tool_call } => {
@@ -803,6 +1007,20 @@ impl DesktopWrapper {
                         let mut substituted_args = tool_call.arguments.clone();
                         substitute_variables(&mut substituted_args, &execution_context);
 
+                        // Target the element an earlier step captured instead of the selector
+                        let element_ref_error = original_step
+                            .and_then(|s| s.element_ref.as_deref())
+                            .and_then(|reference| {
+                                self.resolve_element_ref(&execution_context, reference)
+                                    .map(|selector| {
+                                        element_ref::apply_selector(
+                                            &mut substituted_args,
+                                            &selector,
+                                        )
+                                    })
+                                    .err()
+                            });
+
                         // Inject workflow variables and accumulated env for run_command and execute_browser_script
                         if matches!(
                             tool_call.tool_name.as_str(),
@@ -850,8 +1068,12 @@ impl DesktopWrapper {
 
                         // Start step telemetry span
                         let step_id = original_step.and_then(|s| s.id.as_deref());
-                        let mut step_span = StepSpan::new(&tool_call.tool_name, step_id);
+                        let mut step_span =
+                            StepSpan::child_of(&workflow_span, &tool_call.tool_name, step_id);
                         step_span.set_attribute("step.number", (current_index + 1).to_string());
+                        if let Some(selector) = substituted_args["selector"].as_str() {
+                            step_span.set_attribute("step.selector", selector.to_string());
+                        }
                         step_span.set_attribute("step.total", total_steps.to_string());
                         if attempt > 0 {
                             step_span.set_attribute("step.retry_attempt", attempt.to_string());
@@ -866,18 +1088,67 @@ impl DesktopWrapper {
                             ],
                         );
 
-                        let (result, error_occurred) = self
-                            .execute_single_tool(
-                                peer.clone(),
-                                request_context.clone(),
-                                &tool_call.tool_name,
-                                &substituted_args,
-                                tool_call.continue_on_error.unwrap_or(false),
-                                current_index,
-                                include_detailed,
-                                original_step.and_then(|s| s.id.as_deref()),
-                            )
-                            .await;
+                        let (result, error_occurred) = match element_ref_error {
+                            Some(e) => {
+                                let skippable = tool_call.continue_on_error.unwrap_or(false);
+                                warn!("Step {} not run: {}", current_index + 1, e);
+                                let mut error_result = json!({
+                                    "tool_name": tool_call.tool_name,
+                                    "index": current_index,
+                                    "status": if skippable { "skipped" } else { "error" },
+                                    "duration_ms": 0,
+                                    "error": e.to_string(),
+                                });
+                                if let Some(id) = original_step.and_then(|s| s.id.as_deref()) {
+                                    error_result["step_id"] = json!(id);
+                                }
+                                (error_result, !skippable)
+                            }
+                            None => {
+                                // The attempt gets its own token so a timeout cancels only it
+                                let step_token = cancellation.child_token();
+                                let attempt_run = self.execute_single_tool(
+                                    &backend,
+                                    step_token.clone(),
+                                    &tool_call.tool_name,
+                                    &substituted_args,
+                                    &execution_context,
+                                    tool_call.continue_on_error.unwrap_or(false),
+                                    current_index,
+                                    include_detailed,
+                                    original_step.and_then(|s| s.id.as_deref()),
+                                );
+                                match step_timeout::run_with_timeout(
+                                    attempt_run,
+                                    timeout,
+                                    &step_token,
+                                )
+                                .await
+                                {
+                                    Ok(outcome) => outcome,
+                                    Err(timed_out) => {
+                                        let skippable =
+                                            tool_call.continue_on_error.unwrap_or(false);
+                                        warn!("Step {} {}", current_index + 1, timed_out);
+                                        let mut error_result = json!({
+                                            "tool_name": tool_call.tool_name,
+                                            "index": current_index,
+                                            "status": if skippable { "skipped" } else { "error" },
+                                            "duration_ms": timed_out.limit.as_millis() as u64,
+                                            "error": timed_out.to_string(),
+                                            "reason": "step_timeout",
+                                            "timeout_ms": timed_out.limit.as_millis() as u64,
+                                        });
+                                        if let Some(id) =
+                                            original_step.and_then(|s| s.id.as_deref())
+                                        {
+                                            error_result["step_id"] = json!(id);
+                                        }
+                                        (error_result, !skippable)
+                                    }
+                                }
+                            }
+                        };
 
                         final_result = result.clone();
 
@@ -920,9 +1191,18 @@ impl DesktopWrapper {
                                     }
 
                                     // Store both result and status
-                                    env_map.insert(result_key.clone(), result_content);
-                                    env_map
-                                        .insert(status_key.clone(), final_result["status"].clone());
+                                    execution_events.set_variable(
+                                        env_map,
+                                        result_key.clone(),
+                                        result_content,
+                                        Some(step_id),
+                                    );
+                                    execution_events.set_variable(
+                                        env_map,
+                                        status_key,
+                                        final_result["status"].clone(),
+                                        Some(step_id),
+                                    );
 
                                     info!(
                                         "Stored tool result for step '{}' in env as '{}'",
@@ -944,11 +1224,66 @@ impl DesktopWrapper {
                             }
                         }
 
+                        // An action that succeeded must also meet the step's post-condition
+                        let skippable = tool_call.continue_on_error.unwrap_or(false);
+                        let verification_failure = original_step
+                            .and_then(|s| s.verify.as_deref())
+                            .filter(|_| step_succeeded(&result))
+                            .and_then(|verify| {
+                                verify_step(
+                                    verify,
+                                    &result,
+                                    &Value::Object(execution_context_map.clone()),
+                                    skippable,
+                                )
+                            });
+                        let (result, error_occurred) = match verification_failure {
+                            Some(failed) => {
+                                warn!(
+                                    "Step {} failed verification on attempt {}: {}",
+                                    current_index + 1,
+                                    attempt + 1,
+                                    failed["error"].as_str().unwrap_or_default()
+                                );
+                                if let (Some(id), Some(env_map)) = (
+                                    step_id,
+                                    execution_context_map
+                                        .get_mut("env")
+                                        .and_then(|v| v.as_object_mut()),
+                                ) {
+                                    execution_events.set_variable(
+                                        env_map,
+                                        format!("{id}_status"),
+                                        failed["status"].clone(),
+                                        Some(id),
+                                    );
+                                }
+                                final_result = failed.clone();
+                                (failed, !skippable)
+                            }
+                            None => (result, error_occurred),
+                        };
+
                         // Update step span status and end it
-                        // Support both 'status' field and 'success' field
-                        let success = result["status"] == "success"
-                            || result["success"] == true
-                            || (result["status"].is_null() && result["success"] != false);
+                        let success = step_succeeded(&result);
+                        if let Some(error) = result["error"].as_str().filter(|_| !success) {
+                            let category: Option<ErrorCategory> =
+                                serde_json::from_value(result["error_category"].clone()).ok();
+                            if let Some(category) = category {
+                                step_span.set_attribute("error.category", category.to_string());
+                            }
+                            execution_events.emit(ExecutionEvent::StepFailed {
+                                step_id: step_id.map(str::to_string),
+                                tool_name: tool_call.tool_name.clone(),
+                                error: error.to_string(),
+                                category,
+                                timestamp: chrono::Utc::now().to_rfc3339(),
+                            });
+                        }
+                        step_span.set_attribute(
+                            "step.outcome",
+                            if success { "success" } else { "failure" }.to_string(),
+                        );
                         step_span.set_status(
                             success,
                             if !success {
@@ -972,128 +1307,28 @@ impl DesktopWrapper {
                             ],
                         );
 
-                        // Define reserved keys that shouldn't auto-merge
-                        const RESERVED_KEYS: &[&str] =
-                            &["status", "error", "logs", "duration_ms", "set_env"];
-
                         // Merge env updates from engine/script-based steps into the internal context
                         if (tool_name_normalized == "execute_browser_script"
                             || tool_name_normalized == "run_command")
-                            && final_result["status"] == "success"
+                            && step_succeeded(&final_result)
                         {
-                            // Helper to merge updates into the env context map
-                            let mut merge_env_obj = |update_val: &serde_json::Value| {
-                                if let Some(update_map) = update_val.as_object() {
-                                    if let Some(env_value) = execution_context_map.get_mut("env") {
-                                        if let Some(env_map) = env_value.as_object_mut() {
-                                            for (k, v) in update_map.iter() {
-                                                env_map.insert(k.clone(), v.clone());
-                                            }
-                                        }
-                                    }
-                                }
-                            };
-
-                            // Special handling for execute_browser_script
-                            if tool_name_normalized == "execute_browser_script" {
-                                // Browser scripts return their result as a plain string in final_result["result"]["content"][0]["result"]
-                                if let Some(result_str) = final_result
-                                    .get("result")
-                                    .and_then(|r| r.get("content"))
-                                    .and_then(|c| c.as_array())
-                                    .and_then(|arr| arr.first())
-                                    .and_then(|item| item.get("result"))
-                                    .and_then(|r| r.as_str())
-                                {
-                                    info!(
-                                        "[execute_browser_script] Browser script returned: {}",
-                                        result_str
-                                    );
-                                    // Try to parse the browser script result as JSON
-                                    match serde_json::from_str::<serde_json::Value>(result_str) {
-                                        Ok(parsed_json) => {
-                                            info!("[execute_browser_script] Successfully parsed browser result as JSON");
-
-                                            // First handle explicit set_env for backward compatibility
-                                            if let Some(set_env) = parsed_json.get("set_env") {
-                                                info!("[execute_browser_script] Found set_env in browser script result, merging into context");
-                                                merge_env_obj(set_env);
-                                            }
-
-                                            // Then auto-merge non-reserved fields
-                                            if let Some(obj) = parsed_json.as_object() {
-                                                if let Some(env_value) =
-                                                    execution_context_map.get_mut("env")
-                                                {
-                                                    if let Some(env_map) = env_value.as_object_mut()
-                                                    {
-                                                        for (k, v) in obj {
-                                                            if !RESERVED_KEYS.contains(&k.as_str())
-                                                            {
-                                                                env_map
-                                                                    .insert(k.clone(), v.clone());
-                                                                info!("[execute_browser_script] Auto-merged field '{}' to env", k);
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                        Err(e) => {
-                                            info!("[execute_browser_script] Browser result is not JSON: {}", e);
-                                        }
-                                    }
-                                } else {
-                                    info!("[execute_browser_script] Could not extract browser script result string from response structure");
-                                }
-                            } else if tool_name_normalized == "run_command" {
-                                // Original logic for run_command
-                                if let Some(content_arr) = final_result
-                                    .get("result")
-                                    .and_then(|r| r.get("content"))
-                                    .and_then(|c| c.as_array())
-                                {
-                                    for item in content_arr {
-                                        // Typical engine payload is under item.result
-                                        if let Some(res) = item.get("result") {
-                                            // First handle explicit set_env/env for backward compatibility
-                                            if let Some(v) =
-                                                res.get("set_env").or_else(|| res.get("env"))
-                                            {
-                                                merge_env_obj(v);
-                                            }
-                                        }
-                                        // Also support top-level set_env/env directly on the item
-                                        if let Some(v) =
-                                            item.get("set_env").or_else(|| item.get("env"))
-                                        {
-                                            merge_env_obj(v);
-                                        }
-                                    }
-
-                                    // Auto-merge non-reserved fields from run_command results
-                                    for item in content_arr {
-                                        if let Some(res) = item.get("result") {
-                                            if let Some(obj) = res.as_object() {
-                                                if let Some(env_value) =
-                                                    execution_context_map.get_mut("env")
-                                                {
-                                                    if let Some(env_map) = env_value.as_object_mut()
-                                                    {
-                                                        for (k, v) in obj {
-                                                            if !RESERVED_KEYS.contains(&k.as_str())
-                                                            {
-                                                                env_map
-                                                                    .insert(k.clone(), v.clone());
-                                                                info!("[run_command] Auto-merged field '{}' to env", k);
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
+                            let outputs =
+                                env_updates_from_result(&tool_call.tool_name, &final_result);
+                            let namespace = original_step
+                                .filter(|s| s.namespace_outputs == Some(true))
+                                .and_then(|s| s.id.as_deref());
+                            if let Some(env_map) = execution_context_map
+                                .get_mut("env")
+                                .and_then(|v| v.as_object_mut())
+                            {
+                                info!(
+                                    "[{}] Merging {} output(s) into env{}",
+                                    tool_name_normalized,
+                                    outputs.len(),
+                                    namespace.map(|n| format!(".{n}")).unwrap_or_default()
+                                );
+                                let changes = merge_step_outputs(env_map, outputs, namespace);
+                                execution_events.variables_changed(changes, step_id);
                             }
 
                             // NEW: Save state after env update
@@ -1110,21 +1345,21 @@ impl DesktopWrapper {
                                 }
                             }
                         }
-                        // Check for success using both 'status' and 'success' fields
-                        if result["status"] == "success"
-                            || result["success"] == true
-                            || (result["status"].is_null() && result["success"] != false)
-                        {
+                        if step_succeeded(&result) {
                             // Apply delay after successful execution
                             if let Some(delay_ms) = tool_call.delay_ms {
                                 if delay_ms > 0 {
                                     tokio::time::sleep(Duration::from_millis(delay_ms)).await;
                                 }
                             }
+                            succeeded_on_attempt = Some(attempt);
+                            // A retry recovered from the earlier failed attempts
+                            step_error_occurred = false;
                             break;
                         }
 
-                        if error_occurred {
+                        // Failed attempts that will be retried are not unrecoverable yet
+                        if error_occurred && attempt == retries {
                             // Only mark as critical if there's no fallback to handle it
                             if fallback_id_opt.is_none() {
                                 critical_error_occurred = true;
@@ -1177,63 +1412,259 @@ impl DesktopWrapper {
                         let mut group_had_errors = false;
                         let mut group_results = Vec::new();
                         let is_skippable = tool_group.skippable.unwrap_or(false);
-
-                        for (step_index, step_tool_call) in tool_group.steps.iter_mut().enumerate()
+                        let repeat = original_step
+                            .and_then(|step| RepeatUntil::from_step(step).ok().flatten());
+                        let mut loop_guard = repeat.as_ref().map(LoopGuard::new);
+                        let mut loop_exit = None;
+                        let parallel = original_step
+                            .and_then(|step| ParallelGroup::from_step(step).ok().flatten());
+                        // Outputs of parallel steps, merged into env once they have all finished
+                        let mut parallel_outputs = Vec::new();
+
+                        // Variables the group introduces stay inside it unless promoted
+                        scopes.enter(variable_scope::env_mut(&mut execution_context_map));
+                        for name in original_step
+                            .and_then(|step| step.promote.as_deref())
+                            .unwrap_or_default()
                         {
-                            // Substitute variables in arguments before execution
-                            let execution_context =
-                                serde_json::Value::Object(execution_context_map.clone());
-                            let mut substituted_args = step_tool_call.arguments.clone();
-                            substitute_variables(&mut substituted_args, &execution_context);
-
-                            let (result, error_occurred) = self
-                                .execute_single_tool(
-                                    peer.clone(),
-                                    request_context.clone(),
-                                    &step_tool_call.tool_name,
-                                    &substituted_args,
-                                    step_tool_call.continue_on_error.unwrap_or(false),
-                                    step_index,
-                                    include_detailed,
-                                    step_tool_call.id.as_deref(), // Use step ID if available
+                            scopes.promote(name);
+                        }
+
+                        loop {
+                            if let Some(guard) = loop_guard.as_mut() {
+                                if cancellation.is_cancelled() {
+                                    warn!("Request cancelled by user, stopping repeat_until loop");
+                                    return Err(McpError::internal_error(
+                                        "Request cancelled by user",
+                                        Some(json!({"code": -32001, "reason": "user_cancelled"})),
+                                    ));
+                                }
+                                let iteration = guard.start_iteration(&mut execution_context_map);
+                                debug!(
+                                    "Group '{}' starting repeat_until iteration {}",
+                                    tool_group.group_name, iteration
+                                );
+                            }
+
+                            if let Some(parallel) = parallel {
+                                let group_token = cancellation.child_token();
+                                let execution_context =
+                                    serde_json::Value::Object(execution_context_map.clone());
+                                let prepared: Vec<Value> = tool_group
+                                    .steps
+                                    .iter()
+                                    .map(|call| {
+                                        let mut arguments = call.arguments.clone();
+                                        substitute_variables(&mut arguments, &execution_context);
+                                        arguments
+                                    })
+                                    .collect();
+                                let children = tool_group
+                                    .steps
+                                    .iter()
+                                    .zip(&prepared)
+                                    .enumerate()
+                                    .map(|(step_index, (call, arguments))| {
+                                        self.execute_single_tool(
+                                            &backend,
+                                            group_token.clone(),
+                                            &call.tool_name,
+                                            arguments,
+                                            &execution_context,
+                                            call.continue_on_error.unwrap_or(false),
+                                            step_index,
+                                            include_detailed,
+                                            call.id.as_deref(),
+                                        )
+                                    })
+                                    .collect();
+                                // A step fails the group when its error is not continued past
+                                let outcomes = parallel_group::run_children(
+                                    children,
+                                    &group_token,
+                                    parallel.fail_fast,
+                                    |(_, error_occurred): &(Value, bool)| *error_occurred,
                                 )
                                 .await;
 
-                            group_results.push(result.clone());
+                                if cancellation.is_cancelled() {
+                                    warn!("Request cancelled by user, stopping parallel group");
+                                    return Err(McpError::internal_error(
+                                        "Request cancelled by user",
+                                        Some(json!({"code": -32001, "reason": "user_cancelled"})),
+                                    ));
+                                }
 
-                            if let Some(delay_ms) = step_tool_call.delay_ms {
-                                if delay_ms > 0 {
-                                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                                for (call, outcome) in tool_group.steps.iter().zip(outcomes) {
+                                    let (result, error_occurred) = match outcome {
+                                        ChildOutcome::Finished(finished) => finished,
+                                        ChildOutcome::Cancelled => (
+                                            json!({
+                                                "tool_name": call.tool_name,
+                                                "status": "cancelled",
+                                                "reason": "Cancelled after another step of the parallel group failed"
+                                            }),
+                                            false,
+                                        ),
+                                    };
+                                    if error_occurred {
+                                        group_had_errors = true;
+                                        tracing::warn!(
+                                            group = %tool_group.group_name,
+                                            tool = %call.tool_name,
+                                            step_id = %call.id.clone().unwrap_or_default(),
+                                            "Parallel group step failed"
+                                        );
+                                    }
+                                    parallel_outputs.push((
+                                        call.id.clone(),
+                                        env_updates_from_result(&call.tool_name, &result),
+                                        result["status"].clone(),
+                                    ));
+                                    group_results.push(result);
                                 }
+                                break;
                             }
 
-                            // Check for failure using both 'status' and 'success' fields
-                            let tool_failed = !(result["status"] == "success"
-                                || result["success"] == true
-                                || (result["status"].is_null() && result["success"] != false));
-                            if tool_failed {
-                                group_had_errors = true;
-                                if error_occurred || is_skippable {
-                                    if error_occurred && !is_skippable {
-                                        // Only mark as critical if there's no fallback to handle it
-                                        if fallback_id_opt.is_none() {
-                                            critical_error_occurred = true;
+                            for (step_index, step_tool_call) in
+                                tool_group.steps.iter_mut().enumerate()
+                            {
+                                // Substitute variables in arguments before execution
+                                let execution_context =
+                                    serde_json::Value::Object(execution_context_map.clone());
+                                let mut substituted_args = step_tool_call.arguments.clone();
+                                substitute_variables(&mut substituted_args, &execution_context);
+
+                                let (result, error_occurred) = self
+                                    .execute_single_tool(
+                                        &backend,
+                                        cancellation.clone(),
+                                        &step_tool_call.tool_name,
+                                        &substituted_args,
+                                        &execution_context,
+                                        step_tool_call.continue_on_error.unwrap_or(false),
+                                        step_index,
+                                        include_detailed,
+                                        step_tool_call.id.as_deref(), // Use step ID if available
+                                    )
+                                    .await;
+
+                                group_results.push(result.clone());
+
+                                // Inside a loop, body steps update env so the condition can
+                                // observe their progress
+                                if loop_guard.is_some() {
+                                    let updates =
+                                        env_updates_from_result(&step_tool_call.tool_name, &result);
+                                    let namespace = original_step
+                                        .filter(|s| s.namespace_outputs == Some(true))
+                                        .and(step_tool_call.id.as_deref());
+                                    if let Some(env_map) = execution_context_map
+                                        .get_mut("env")
+                                        .and_then(|v| v.as_object_mut())
+                                    {
+                                        let call_id = step_tool_call.id.as_deref();
+                                        let changes =
+                                            merge_step_outputs(env_map, updates, namespace);
+                                        execution_events.variables_changed(changes, call_id);
+                                        if let Some(call_id) = call_id {
+                                            execution_events.set_variable(
+                                                env_map,
+                                                format!("{call_id}_status"),
+                                                result["status"].clone(),
+                                                Some(call_id),
+                                            );
                                         }
                                     }
-                                    tracing::warn!(
-                                        group = %tool_group.group_name,
-                                        tool = %step_tool_call.tool_name,
-                                        step_index = step_index,
-                                        step_id = %step_tool_call.id.clone().unwrap_or_default(),
-                                        skippable = %is_skippable,
-                                        has_fallback = fallback_id_opt.is_some(),
-                                        "Group step failed; breaking out of group"
+                                }
+
+                                if let Some(delay_ms) = step_tool_call.delay_ms {
+                                    if delay_ms > 0 {
+                                        tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                                    }
+                                }
+
+                                if !step_succeeded(&result) {
+                                    group_had_errors = true;
+                                    if error_occurred || is_skippable {
+                                        if error_occurred && !is_skippable {
+                                            // Only mark as critical if there's no fallback to handle it
+                                            if fallback_id_opt.is_none() {
+                                                critical_error_occurred = true;
+                                            }
+                                        }
+                                        tracing::warn!(
+                                            group = %tool_group.group_name,
+                                            tool = %step_tool_call.tool_name,
+                                            step_index = step_index,
+                                            step_id = %step_tool_call.id.clone().unwrap_or_default(),
+                                            skippable = %is_skippable,
+                                            has_fallback = fallback_id_opt.is_some(),
+                                            "Group step failed; breaking out of group"
+                                        );
+                                        break;
+                                    }
+                                }
+                            }
+
+                            let Some(guard) = loop_guard.as_ref() else {
+                                break;
+                            };
+                            if group_had_errors {
+                                break;
+                            }
+                            let execution_context =
+                                serde_json::Value::Object(execution_context_map.clone());
+                            if let Some(exit) = guard.after_iteration(&execution_context) {
+                                if exit.is_guard() {
+                                    warn!(
+                                        "Group '{}' stopped after {} iteration(s): {} reached before `{}` held",
+                                        tool_group.group_name,
+                                        guard.iteration(),
+                                        exit.as_str(),
+                                        repeat.as_ref().map(|r| r.condition.as_str()).unwrap_or_default()
                                     );
-                                    break;
+                                    group_had_errors = true;
+                                }
+                                loop_exit = Some((exit, guard.iteration()));
+                                break;
+                            }
+                        }
+
+                        // Join the parallel steps' outputs, in step order, before the group's
+                        // scope closes, so only the variables it promotes outlive it
+                        if !parallel_outputs.is_empty() {
+                            let namespaced =
+                                original_step.is_some_and(|s| s.namespace_outputs == Some(true));
+                            if let Some(env_map) = execution_context_map
+                                .get_mut("env")
+                                .and_then(|v| v.as_object_mut())
+                            {
+                                for (call_id, updates, status) in parallel_outputs {
+                                    let namespace = call_id.as_deref().filter(|_| namespaced);
+                                    let changes = merge_step_outputs(env_map, updates, namespace);
+                                    execution_events.variables_changed(changes, call_id.as_deref());
+                                    if let Some(call_id) = call_id.as_deref() {
+                                        execution_events.set_variable(
+                                            env_map,
+                                            format!("{call_id}_status"),
+                                            status,
+                                            Some(call_id),
+                                        );
+                                    }
                                 }
                             }
                         }
 
+                        let removed =
+                            scopes.exit(variable_scope::env_mut(&mut execution_context_map));
+                        if !removed.is_empty() {
+                            debug!(
+                                "Group '{}' exited; removed its variables {:?}",
+                                tool_group.group_name, removed
+                            );
+                        }
+
                         let group_status = if group_had_errors {
                             "partial_success"
                         } else {
@@ -1257,25 +1688,172 @@ impl DesktopWrapper {
                             "status": group_status,
                             "results": group_results
                         });
+                        if let Some((exit, iterations)) = loop_exit {
+                            final_result["loop_exit"] = json!(exit.as_str());
+                            final_result["iterations"] = json!(iterations);
+                        }
 
                         if !group_had_errors {
+                            succeeded_on_attempt = Some(attempt);
                             break; // Group succeeded, break retry loop.
                         }
                     }
                 }
                 if attempt < retries {
+                    let delay = backoff
+                        .as_ref()
+                        .map_or(crate::retry_backoff::DEFAULT_RETRY_DELAY, |b| {
+                            b.next_delay(attempt + 1)
+                        });
                     warn!(
-                        "Step {} failed on attempt {}/{}. Retrying...",
+                        "Step {} failed on attempt {}/{}. Retrying in {:?}...",
                         current_index,
                         attempt + 1,
-                        retries
+                        retries + 1,
+                        delay
                     );
-                    tokio::time::sleep(Duration::from_millis(500)).await; // Wait before retry
+                    if !crate::retry_backoff::wait_for_retry(delay, cancellation).await {
+                        warn!("Request cancelled by user while waiting to retry a step");
+                        return Err(McpError::internal_error(
+                            "Request cancelled by user",
+                            Some(json!({"code": -32001, "reason": "user_cancelled"})),
+                        ));
+                    }
+                }
+            }
+
+            match succeeded_on_attempt {
+                Some(attempt) if attempt > 0 => {
+                    crate::telemetry::record_retry_success(&retry_label)
                 }
+                None if retries > 0 => crate::telemetry::record_retry_exhausted(&retry_label),
+                _ => {}
             }
 
             results.push(final_result);
 
+            // A step that still fails after its retries goes to its on_error handlers
+            let mut aborted = false;
+            if let Some((step, handlers)) = original_step
+                .and_then(|s| s.on_error.as_deref().map(|handlers| (s, handlers)))
+                .filter(|_| step_error_occurred)
+            {
+                let error = StepError::from_result(results.last().unwrap_or(&Value::Null));
+                info!(
+                    "Step {} failed ({}): {}. Looking for an on_error handler",
+                    current_index, error.kind, error.message
+                );
+                let previous = on_error::expose_error(
+                    variable_scope::env_mut(&mut execution_context_map),
+                    error.variable(step),
+                );
+                let handler = on_error::select_handler(
+                    handlers,
+                    &Value::Object(execution_context_map.clone()),
+                );
+                if let Some((handler_index, handler)) = handler {
+                    let mut action = handler.then.unwrap_or_default();
+                    for (name, value) in handler.set_env.iter().flatten() {
+                        let mut value = value.clone();
+                        substitute_variables(
+                            &mut value,
+                            &Value::Object(execution_context_map.clone()),
+                        );
+                        execution_events.set_variable(
+                            variable_scope::env_mut(&mut execution_context_map),
+                            name.clone(),
+                            value,
+                            step.id.as_deref(),
+                        );
+                    }
+
+                    let mut handler_results = Vec::new();
+                    for (call_index, call) in handler
+                        .steps
+                        .as_deref()
+                        .unwrap_or_default()
+                        .iter()
+                        .enumerate()
+                    {
+                        let execution_context =
+                            serde_json::Value::Object(execution_context_map.clone());
+                        let mut arguments = call.arguments.clone();
+                        substitute_variables(&mut arguments, &execution_context);
+
+                        let (result, error_occurred) = self
+                            .execute_single_tool(
+                                &backend,
+                                cancellation.clone(),
+                                &call.tool_name,
+                                &arguments,
+                                &execution_context,
+                                call.continue_on_error.unwrap_or(false),
+                                call_index,
+                                include_detailed,
+                                call.id.as_deref(),
+                            )
+                            .await;
+
+                        let env_map = variable_scope::env_mut(&mut execution_context_map);
+                        let changes = merge_step_outputs(
+                            env_map,
+                            env_updates_from_result(&call.tool_name, &result),
+                            None,
+                        );
+                        execution_events.variables_changed(changes, call.id.as_deref());
+                        if let Some(call_id) = call.id.as_deref() {
+                            execution_events.set_variable(
+                                env_map,
+                                format!("{call_id}_status"),
+                                result["status"].clone(),
+                                Some(call_id),
+                            );
+                        }
+                        handler_results.push(result);
+                        if error_occurred {
+                            warn!(
+                                "on_error step '{}' of step {} failed; the step stays failed",
+                                call.tool_name, current_index
+                            );
+                            action = ErrorAction::Fail;
+                            break;
+                        }
+                    }
+
+                    match action {
+                        ErrorAction::Continue => {
+                            step_error_occurred = false;
+                            (sequence_had_errors, critical_error_occurred) = errors_before;
+                        }
+                        ErrorAction::Abort => {
+                            critical_error_occurred = true;
+                            aborted = true;
+                        }
+                        ErrorAction::Fail => {}
+                    }
+                    info!(
+                        "Step {} on_error handler {} resolved the failure with '{}'",
+                        current_index,
+                        handler_index,
+                        action.as_str()
+                    );
+                    if let Some(last) = results.last_mut() {
+                        if action == ErrorAction::Continue {
+                            last["status"] = json!("recovered");
+                        }
+                        last["on_error"] = json!({
+                            "handler": handler_index,
+                            "then": action.as_str(),
+                            "results": handler_results,
+                        });
+                    }
+                }
+                on_error::restore_error(
+                    variable_scope::env_mut(&mut execution_context_map),
+                    previous,
+                );
+            }
+
             // Decide next index based on success or fallback
             let step_succeeded = !step_error_occurred;
             let step_status_str = if step_succeeded { "success" } else { "failed" };
@@ -1296,6 +1874,11 @@ impl DesktopWrapper {
                     step_status_str
                 );
             }
+            step_timer.finish(if step_succeeded {
+                StepOutcome::Success
+            } else {
+                StepOutcome::Failure
+            });
 
             if step_succeeded {
                 // For successful steps, check if we're about to enter troubleshooting section
@@ -1306,7 +1889,7 @@ impl DesktopWrapper {
                     break;
                 }
                 current_index += 1;
-            } else if let Some(fb_id) = fallback_id_opt {
+            } else if let Some(fb_id) = fallback_id_opt.filter(|_| !aborted) {
                 if let Some(&fb_idx) = id_to_index.get(&fb_id) {
                     // Check if we should follow this fallback based on end_at_step and follow_fallback setting
                     let should_follow_fallback = if args.end_at_step.is_some()
@@ -1369,6 +1952,25 @@ impl DesktopWrapper {
             warn!("Maximum iteration count reached. Possible infinite fallback loop detected.");
         }
 
+        if let Some(watcher) = modal_watcher {
+            let dismissed = watcher.stop().await;
+            if dismissed > 0 {
+                info!(
+                    "Modal watcher dismissed {} dialog(s) during the run",
+                    dismissed
+                );
+            }
+        }
+
+        let mut persisted_variables = None;
+        if let (false, Some(key)) = (persistent_variables.is_empty(), &persistence_key) {
+            let values = collect_persistent_values(&persistent_variables, &execution_context_map);
+            match variable_store.save(key, &values).await {
+                Ok(()) => persisted_variables = Some(values),
+                Err(e) => warn!("Failed to save persistent variables: {}", e),
+            }
+        }
+
         let total_duration = (chrono::Utc::now() - start_time).num_milliseconds();
 
         let final_status = if !sequence_had_errors {
@@ -1388,6 +1990,7 @@ impl DesktopWrapper {
         let mut summary = json!({
             "action": "execute_sequence",
             "status": final_status,
+            "run_id": run_id,
             "total_tools": sequence_items.len(),
             "executed_tools": results.len(),
             "total_duration_ms": total_duration,
@@ -1395,6 +1998,43 @@ impl DesktopWrapper {
             "results": results,
         });
 
+        if let (Some(path), Some(trace), Some(initial_context)) =
+            (&args.export_bundle, backend.into_trace(), recorded_context)
+        {
+            let bundle = RunBundle::new(
+                args.clone(),
+                initial_context,
+                trace,
+                RunOutcome::from_summary(&summary),
+            );
+            let key_and_value = match bundle.write_to(Path::new(path)).await {
+                Ok(()) => {
+                    info!("Exported run bundle to {}", path);
+                    ("bundle_path", json!(path))
+                }
+                Err(e) => {
+                    warn!("Failed to export run bundle: {}", e);
+                    ("bundle_error", json!(e))
+                }
+            };
+            if let Some(obj) = summary.as_object_mut() {
+                obj.insert(key_and_value.0.to_string(), key_and_value.1);
+            }
+        }
+
+        if let Some(values) = persisted_variables {
+            if let Some(obj) = summary.as_object_mut() {
+                obj.insert("persisted_variables".to_string(), Value::Object(values));
+            }
+        }
+
+        let emitted_events = execution_events.events();
+        if !emitted_events.is_empty() {
+            if let Some(obj) = summary.as_object_mut() {
+                obj.insert("events".to_string(), json!(emitted_events));
+            }
+        }
+
         // Support both 'output_parser' (legacy) and 'output' (simplified)
         let parser_def = args.output_parser.as_ref().or(args.output.as_ref());
 
@@ -1418,6 +2058,14 @@ impl DesktopWrapper {
                 Err(e) => {
                     if let Some(obj) = summary.as_object_mut() {
                         obj.insert("parser_error".to_string(), json!(e.to_string()));
+                        if let Some(validation) =
+                            e.downcast_ref::<output_parser::SchemaValidationError>()
+                        {
+                            obj.insert(
+                                "validation_errors".to_string(),
+                                json!(validation.violations),
+                            );
+                        }
                     }
                 }
             }
@@ -1436,8 +2084,6 @@ impl DesktopWrapper {
             }
         }
 
-        let contents = vec![Content::json(summary.clone())?];
-
         // End workflow span with success status
         let had_errors = summary
             .get("had_errors")
@@ -1459,17 +2105,41 @@ impl DesktopWrapper {
             ],
         );
         workflow_span.end();
+        run_guard.finish(final_status);
 
-        Ok(CallToolResult::success(contents))
+        Ok(summary)
+    }
+
+    /// Finds the element captured by step `reference` again and returns a selector for it.
+    fn resolve_element_ref(
+        &self,
+        context: &Value,
+        reference: &str,
+    ) -> Result<String, ElementRefError> {
+        element_ref::resolve(context, reference, |captured| {
+            let scope = if captured.process_id == 0 {
+                self.desktop.root()
+            } else {
+                self.desktop
+                    .application_by_pid(captured.process_id, Some(Duration::from_secs(2)))?
+            };
+            let element = scope.find_by_identity(&captured.identity_key, None)?;
+            element.id().ok_or_else(|| {
+                terminator::AutomationError::ElementNotFound(
+                    "the element has no id to target".to_string(),
+                )
+            })
+        })
     }
 
     #[allow(clippy::too_many_arguments)]
-    pub async fn execute_single_tool(
+    pub async fn execute_single_tool<B: ToolBackend>(
         &self,
-        peer: Peer<RoleServer>,
-        request_context: RequestContext<RoleServer>,
+        backend: &B,
+        cancellation: CancellationToken,
         tool_name: &str,
         arguments: &Value,
+        variables: &Value,
         is_skippable: bool,
         index: usize,
         include_detailed: bool,
@@ -1487,9 +2157,9 @@ impl DesktopWrapper {
             }
         }
 
-        // The substitution is handled in `execute_sequence_impl`.
-        let tool_result = self
-            .dispatch_tool(peer, request_context, tool_name_short, arguments)
+        // The substitution is handled in `execute_sequence_with`.
+        let tool_result = backend
+            .call_tool(tool_name_short, arguments, variables, cancellation)
             .await;
 
         let (processed_result, error_occurred) = match tool_result {
@@ -1598,6 +2268,10 @@ impl DesktopWrapper {
                     "duration_ms": duration_ms,
                     "error": format!("{}", e),
                 });
+                // Carry the category over from the tool's structured error
+                if let Some(category) = e.data.as_ref().and_then(|d| d.get("error_category")) {
+                    error_result["error_category"] = category.clone();
+                }
 
                 // Include server logs in error result if captured
                 if let Some(logs) = captured_logs {
@@ -1631,3 +2305,28 @@ impl DesktopWrapper {
         (processed_result, error_occurred)
     }
 }
+
+/// Runs a sequence's tools on the desktop, through the server's own tool dispatch
+struct Dispatcher<'a> {
+    server: &'a DesktopWrapper,
+    peer: Peer<RoleServer>,
+    request_context: RequestContext<RoleServer>,
+}
+
+impl ToolBackend for Dispatcher<'_> {
+    fn call_tool(
+        &self,
+        tool_name: &str,
+        arguments: &Value,
+        variables: &Value,
+        cancellation: CancellationToken,
+    ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send {
+        let mut request_context = self.request_context.clone();
+        request_context.ct = cancellation;
+        request_context
+            .extensions
+            .insert(WorkflowVariables(variables.clone()));
+        self.server
+            .dispatch_tool(self.peer.clone(), request_context, tool_name, arguments)
+    }
+}
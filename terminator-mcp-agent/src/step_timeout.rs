@@ -0,0 +1,68 @@
+//! Per-step timeouts.
+//!
+//! A tool step with a `timeout` (a duration such as `30s`) is abandoned once an attempt
+//! runs longer, failing that attempt with a `StepTimeout` error. The failure is then
+//! handled like any other: `retries`, `continue_on_error` and `fallback_id` apply. Each
+//! attempt runs with its own cancellation token, a child of the request's, so cancelling
+//! the request still stops the step, and a timeout cancels only that step.
+
+use crate::utils::SequenceStep;
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Reads the timeout of a step. Returns `Ok(None)` for steps without one.
+pub fn step_timeout(step: &SequenceStep) -> Result<Option<Duration>, String> {
+    let Some(timeout) = &step.timeout else {
+        return Ok(None);
+    };
+    if step.tool_name.is_none() {
+        return Err("timeout is only supported on tool steps".to_string());
+    }
+    let limit = crate::duration_parser::parse_std_duration(timeout)
+        .map_err(|e| format!("Invalid timeout '{timeout}': {e}"))?;
+    if limit.is_zero() {
+        return Err(format!(
+            "timeout must be greater than zero, got '{timeout}'"
+        ));
+    }
+    Ok(Some(limit))
+}
+
+/// An attempt that did not finish within its step's timeout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepTimedOut {
+    pub limit: Duration,
+}
+
+impl fmt::Display for StepTimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "StepTimeout: step did not finish within {}ms",
+            self.limit.as_millis()
+        )
+    }
+}
+
+impl std::error::Error for StepTimedOut {}
+
+/// Runs a step attempt, giving up after `limit`. On timeout the step's `cancellation`
+/// token is cancelled, so work that watches it stops, and the attempt is dropped.
+pub async fn run_with_timeout<F: Future>(
+    step: F,
+    limit: Option<Duration>,
+    cancellation: &CancellationToken,
+) -> Result<F::Output, StepTimedOut> {
+    let Some(limit) = limit else {
+        return Ok(step.await);
+    };
+    match tokio::time::timeout(limit, step).await {
+        Ok(output) => Ok(output),
+        Err(_) => {
+            cancellation.cancel();
+            Err(StepTimedOut { limit })
+        }
+    }
+}
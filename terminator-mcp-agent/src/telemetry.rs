@@ -1,5 +1,23 @@
-// OpenTelemetry support for MCP workflow tracing
-// This module is only compiled when the 'telemetry' feature is enabled
+//! OpenTelemetry support for MCP workflow tracing.
+//!
+//! Spans and metrics are only exported when the crate is built with the `telemetry`
+//! feature, which is off by default; without it the span types are no-ops that still
+//! feed the local latency and retry reports. Each `execute_sequence` run is traced as a
+//! workflow span with one child span per tool step, carrying `step.id`, `step.selector`
+//! and `step.outcome` attributes.
+//!
+//! Environment variables:
+//! - `OTEL_EXPORTER_OTLP_ENDPOINT`: OTLP/HTTP collector endpoint, default
+//!   `http://localhost:4318`; traces go to `/v1/traces`, metrics to `/v1/metrics`.
+//! - `OTEL_SDK_DISABLED=true`: disables export entirely.
+//! - `OTEL_SKIP_COLLECTOR_CHECK=true`: initializes the exporter even when the collector
+//!   is not reachable at startup.
+//! - `TERMINATOR_TRACES_SAMPLE_RATE`: fraction of workflow traces exported, from `0.0`
+//!   to `1.0` (default). A workflow's step spans follow its sampling decision.
+//! - `TERMINATOR_TELEMETRY_PRIVACY=true`: keeps screen text and coordinates out of
+//!   exported attributes. Text is replaced by a hash keyed with a random per-install key.
+//! - `TERMINATOR_TELEMETRY_KEY_FILE`: where that key is kept, default
+//!   `~/.terminator/telemetry.key`.
 
 #[cfg(feature = "telemetry")]
 pub use with_telemetry::*;
@@ -7,26 +25,533 @@ pub use with_telemetry::*;
 #[cfg(not(feature = "telemetry"))]
 pub use without_telemetry::*;
 
+pub use privacy::{
+    load_or_create_hash_key, privacy_mode_enabled, scrub_attribute, scrub_message,
+    set_privacy_mode, HASH_KEY_FILE_ENV, PRIVACY_MODE_ENV,
+};
+
+pub use latency::{
+    action_type, latency_report, latency_tracker, record_tool_latency, ActionLatency,
+    LatencyReport, LatencyTracker, DEFAULT_LATENCY_SAMPLES,
+};
+
+pub use sampling::{parse_sample_rate, traces_sample_rate, TRACES_SAMPLE_RATE_ENV};
+
+pub use retries::{
+    record_retry_attempt, record_retry_exhausted, record_retry_success, retry_tracker, RetryCounts,
+    RetryTracker,
+};
+
+// Privacy scrubbing applied to span attributes, events and status messages
+mod privacy {
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicU8, Ordering};
+    use std::sync::OnceLock;
+
+    /// Set to `true` to keep screen text and coordinates out of exported telemetry
+    pub const PRIVACY_MODE_ENV: &str = "TERMINATOR_TELEMETRY_PRIVACY";
+
+    /// Overrides the file holding the key scrubbed text is hashed with
+    pub const HASH_KEY_FILE_ENV: &str = "TERMINATOR_TELEMETRY_KEY_FILE";
+
+    // 0 = follow the environment, 1 = forced off, 2 = forced on
+    static PRIVACY_OVERRIDE: AtomicU8 = AtomicU8::new(0);
+
+    /// Attributes that only describe the workflow structure and are always kept
+    const STRUCTURAL_KEYS: &[&str] = &[
+        "workflow.name",
+        "workflow.total_steps",
+        "workflow.stop_on_error",
+        "workflow.had_errors",
+        "tool.name",
+        "step.id",
+        "step.number",
+        "step.total",
+        "step.retry_attempt",
+        "step.tool",
+        "step.index",
+        "step.status",
+        "step.outcome",
+    ];
+
+    const COORDINATE_NAMES: &[&str] = &[
+        "x",
+        "y",
+        "left",
+        "top",
+        "right",
+        "bottom",
+        "width",
+        "height",
+        "bounds",
+        "rect",
+        "point",
+        "position",
+        "location",
+        "coordinates",
+    ];
+
+    /// Overrides the privacy mode for this process, regardless of the environment.
+    pub fn set_privacy_mode(enabled: bool) {
+        PRIVACY_OVERRIDE.store(if enabled { 2 } else { 1 }, Ordering::Relaxed);
+    }
+
+    pub fn privacy_mode_enabled() -> bool {
+        static FROM_ENV: OnceLock<bool> = OnceLock::new();
+        match PRIVACY_OVERRIDE.load(Ordering::Relaxed) {
+            1 => false,
+            2 => true,
+            _ => *FROM_ENV.get_or_init(|| {
+                std::env::var(PRIVACY_MODE_ENV)
+                    .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+                    .unwrap_or(false)
+            }),
+        }
+    }
+
+    fn is_coordinate_key(key: &str) -> bool {
+        let name = key.rsplit('.').next().unwrap_or(key).to_ascii_lowercase();
+        COORDINATE_NAMES.iter().any(|c| {
+            name == *c || name.ends_with(&format!("_{c}")) || name.starts_with(&format!("{c}_"))
+        })
+    }
+
+    // A key stored as 64 hex digits
+    fn parse_hash_key(hex: &str) -> Option<[u8; 32]> {
+        if hex.len() != 64 || !hex.is_ascii() {
+            return None;
+        }
+        let mut key = [0u8; 32];
+        for (i, byte) in key.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+        }
+        Some(key)
+    }
+
+    // Creates the key file readable by its owner alone
+    fn write_hash_key(path: &Path, hex: &str) -> std::io::Result<()> {
+        use std::io::Write;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(path)?.write_all(hex.as_bytes())
+    }
+
+    /// Reads the hashing key stored at `path`, creating it with a random key when it is
+    /// missing or malformed. Falls back to a key for this process alone when the file
+    /// cannot be written, so hashes then only correlate within the process.
+    pub fn load_or_create_hash_key(path: &Path) -> [u8; 32] {
+        let stored = std::fs::read_to_string(path)
+            .ok()
+            .and_then(|hex| parse_hash_key(hex.trim()));
+        if let Some(key) = stored {
+            return key;
+        }
+        let key: [u8; 32] = rand::random();
+        let hex: String = key.iter().map(|b| format!("{b:02x}")).collect();
+        if let Err(e) = write_hash_key(path, &hex) {
+            tracing::warn!(
+                "Could not store the telemetry hashing key at {}: {}",
+                path.display(),
+                e
+            );
+        }
+        key
+    }
+
+    fn hash_key() -> &'static [u8; 32] {
+        static KEY: OnceLock<[u8; 32]> = OnceLock::new();
+        KEY.get_or_init(|| {
+            let path = std::env::var_os(HASH_KEY_FILE_ENV)
+                .map(PathBuf::from)
+                .unwrap_or_else(|| {
+                    std::env::var_os("HOME")
+                        .or_else(|| std::env::var_os("USERPROFILE"))
+                        .map(PathBuf::from)
+                        .unwrap_or_else(std::env::temp_dir)
+                        .join(".terminator")
+                        .join("telemetry.key")
+                });
+            load_or_create_hash_key(&path)
+        })
+    }
+
+    /// Keyed BLAKE3, so equal values can still be correlated across spans without
+    /// exposing them. Without the key, short values cannot be recovered by hashing
+    /// guesses.
+    fn hash_text(value: &str) -> String {
+        let hash = blake3::keyed_hash(hash_key(), value.as_bytes());
+        format!("hash:{}", &hash.to_hex()[..16])
+    }
+
+    /// Scrubs one attribute for privacy mode.
+    ///
+    /// Structural attributes are kept, coordinate attributes are dropped (`None`) and
+    /// everything else, which may carry screen text, is replaced by a hash.
+    pub fn scrub_attribute(key: &str, value: &str) -> Option<String> {
+        if STRUCTURAL_KEYS.contains(&key) {
+            Some(value.to_string())
+        } else if is_coordinate_key(key) {
+            None
+        } else {
+            Some(hash_text(value))
+        }
+    }
+
+    /// Scrubs a free-form message such as a span status description.
+    pub fn scrub_message(message: &str) -> String {
+        hash_text(message)
+    }
+}
+
+// Trace sampling rate, read once from the environment
+mod sampling {
+    use std::sync::OnceLock;
+
+    /// Fraction of workflow traces to export, from 0.0 to 1.0
+    pub const TRACES_SAMPLE_RATE_ENV: &str = "TERMINATOR_TRACES_SAMPLE_RATE";
+
+    /// Parses a sample rate, clamping it to 0.0..=1.0. Returns `None` for anything that
+    /// is not a number.
+    pub fn parse_sample_rate(value: &str) -> Option<f64> {
+        let rate: f64 = value.trim().parse().ok()?;
+        (!rate.is_nan()).then(|| rate.clamp(0.0, 1.0))
+    }
+
+    /// The configured sample rate; every trace is exported when it is unset or invalid.
+    pub fn traces_sample_rate() -> f64 {
+        static RATE: OnceLock<f64> = OnceLock::new();
+        *RATE.get_or_init(|| match std::env::var(TRACES_SAMPLE_RATE_ENV) {
+            Ok(value) => parse_sample_rate(&value).unwrap_or_else(|| {
+                tracing::warn!(
+                    "Ignoring invalid {}='{}', exporting every trace",
+                    TRACES_SAMPLE_RATE_ENV,
+                    value
+                );
+                1.0
+            }),
+            Err(_) => 1.0,
+        })
+    }
+}
+
+// Per-action latency samples behind the `latency_report` tool. Kept regardless of the
+// telemetry feature, since it is reported locally rather than exported.
+mod latency {
+    use serde::Serialize;
+    use std::collections::{BTreeMap, VecDeque};
+    use std::sync::{Mutex, OnceLock};
+    use std::time::Duration;
+
+    /// Number of recent samples kept per action type
+    pub const DEFAULT_LATENCY_SAMPLES: usize = 500;
+
+    /// Groups a tool into the action type its latency is reported under.
+    pub fn action_type(tool_name: &str) -> &'static str {
+        match tool_name {
+            "click_element" | "invoke_element" | "mouse_drag" | "drag_and_drop"
+            | "activate_element" => "click",
+            "type_into_element" | "type_into_focused" | "press_key" | "press_key_global"
+            | "press_keys" | "set_value" => "type",
+            "validate_element" | "wait_for_element" | "highlight_element" | "resolve_selectors"
+            | "find_elements" => "find",
+            "get_window_tree"
+            | "get_focused_window_tree"
+            | "get_applications"
+            | "foreground_app"
+            | "get_range_value"
+            | "is_toggled"
+            | "is_selected"
+            | "list_options"
+            | "read_menu"
+            | "get_cell"
+            | "capture_element_screenshot"
+            | "capture_highlighted_screenshot"
+            | "clipboard_get"
+            | "list_monitors"
+            | "ocr_region" => "read",
+            _ => "other",
+        }
+    }
+
+    /// Latency percentiles of one action type or tool, in milliseconds
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct ActionLatency {
+        pub count: usize,
+        pub p50_ms: f64,
+        pub p90_ms: f64,
+        pub p95_ms: f64,
+        pub p99_ms: f64,
+        pub max_ms: f64,
+    }
+
+    /// Percentiles for every action type and every tool that has samples
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct LatencyReport {
+        pub sample_limit: usize,
+        pub actions: BTreeMap<String, ActionLatency>,
+        /// Keyed by tool name, e.g. `click_element`
+        pub tools: BTreeMap<String, ActionLatency>,
+    }
+
+    /// Bounded ring buffers of recent latencies, one per action type and one per tool
+    #[derive(Debug)]
+    pub struct LatencyTracker {
+        capacity: usize,
+        samples: BTreeMap<String, VecDeque<Duration>>,
+        tools: BTreeMap<String, VecDeque<Duration>>,
+    }
+
+    impl Default for LatencyTracker {
+        fn default() -> Self {
+            Self::new(DEFAULT_LATENCY_SAMPLES)
+        }
+    }
+
+    // Nearest-rank percentile over sorted samples
+    fn percentile(sorted: &[Duration], p: f64) -> f64 {
+        let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+        let index = rank.clamp(1, sorted.len()) - 1;
+        sorted[index].as_secs_f64() * 1000.0
+    }
+
+    fn push_bounded(buffer: &mut VecDeque<Duration>, capacity: usize, latency: Duration) {
+        if buffer.len() == capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(latency);
+    }
+
+    fn summarize(
+        samples: &BTreeMap<String, VecDeque<Duration>>,
+    ) -> BTreeMap<String, ActionLatency> {
+        samples
+            .iter()
+            .filter(|(_, buffer)| !buffer.is_empty())
+            .map(|(name, buffer)| {
+                let mut sorted: Vec<Duration> = buffer.iter().copied().collect();
+                sorted.sort();
+                let latency = ActionLatency {
+                    count: sorted.len(),
+                    p50_ms: percentile(&sorted, 50.0),
+                    p90_ms: percentile(&sorted, 90.0),
+                    p95_ms: percentile(&sorted, 95.0),
+                    p99_ms: percentile(&sorted, 99.0),
+                    max_ms: percentile(&sorted, 100.0),
+                };
+                (name.clone(), latency)
+            })
+            .collect()
+    }
+
+    impl LatencyTracker {
+        pub fn new(capacity: usize) -> Self {
+            Self {
+                capacity: capacity.max(1),
+                samples: BTreeMap::new(),
+                tools: BTreeMap::new(),
+            }
+        }
+
+        /// Records one sample, dropping the oldest once the action's buffer is full.
+        pub fn record(&mut self, action: &str, latency: Duration) {
+            let buffer = self.samples.entry(action.to_string()).or_default();
+            push_bounded(buffer, self.capacity, latency);
+        }
+
+        /// Records a tool call under both its tool name and its action type.
+        pub fn record_tool(&mut self, tool_name: &str, latency: Duration) {
+            self.record(action_type(tool_name), latency);
+            let buffer = self.tools.entry(tool_name.to_string()).or_default();
+            push_bounded(buffer, self.capacity, latency);
+        }
+
+        pub fn report(&self) -> LatencyReport {
+            LatencyReport {
+                sample_limit: self.capacity,
+                actions: summarize(&self.samples),
+                tools: summarize(&self.tools),
+            }
+        }
+
+        pub fn clear(&mut self) {
+            self.samples.clear();
+            self.tools.clear();
+        }
+    }
+
+    /// The process-wide tracker fed by step spans and tool calls
+    pub fn latency_tracker() -> &'static Mutex<LatencyTracker> {
+        static TRACKER: OnceLock<Mutex<LatencyTracker>> = OnceLock::new();
+        TRACKER.get_or_init(|| Mutex::new(LatencyTracker::default()))
+    }
+
+    /// Records a tool call's latency under its tool name and action type.
+    pub fn record_tool_latency(tool_name: &str, latency: Duration) {
+        if let Ok(mut tracker) = latency_tracker().lock() {
+            tracker.record_tool(tool_name, latency);
+        }
+    }
+
+    /// Percentiles of the tool calls recorded so far in this process
+    pub fn latency_report() -> LatencyReport {
+        latency_tracker()
+            .lock()
+            .map(|tracker| tracker.report())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().report())
+    }
+}
+
+// Retry counters for `execute_sequence` steps, so flaky steps show up in reports and
+// metrics. Counted locally regardless of the telemetry feature; with the feature enabled
+// each increment is also exported as an OpenTelemetry counter labeled by `step.id`.
+mod retries {
+    use serde::Serialize;
+    use std::collections::BTreeMap;
+    use std::sync::{Mutex, OnceLock};
+
+    /// Retry counts of one step
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+    pub struct RetryCounts {
+        /// Attempts made after the first one failed
+        pub attempts: u64,
+        /// Times the step failed at first but then succeeded on a retry
+        pub successes: u64,
+        /// Times the step still failed after its last retry
+        pub exhaustions: u64,
+    }
+
+    /// Retry counts keyed by step id
+    #[derive(Debug, Default)]
+    pub struct RetryTracker {
+        steps: BTreeMap<String, RetryCounts>,
+    }
+
+    impl RetryTracker {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn record_attempt(&mut self, step_id: &str) {
+            self.steps.entry(step_id.to_string()).or_default().attempts += 1;
+        }
+
+        pub fn record_success(&mut self, step_id: &str) {
+            self.steps.entry(step_id.to_string()).or_default().successes += 1;
+        }
+
+        pub fn record_exhausted(&mut self, step_id: &str) {
+            self.steps
+                .entry(step_id.to_string())
+                .or_default()
+                .exhaustions += 1;
+        }
+
+        /// Counts for one step, zero if it never retried
+        pub fn counts(&self, step_id: &str) -> RetryCounts {
+            self.steps.get(step_id).copied().unwrap_or_default()
+        }
+
+        pub fn report(&self) -> BTreeMap<String, RetryCounts> {
+            self.steps.clone()
+        }
+
+        pub fn clear(&mut self) {
+            self.steps.clear();
+        }
+    }
+
+    /// The process-wide tracker fed by the `execute_sequence` retry loop
+    pub fn retry_tracker() -> &'static Mutex<RetryTracker> {
+        static TRACKER: OnceLock<Mutex<RetryTracker>> = OnceLock::new();
+        TRACKER.get_or_init(|| Mutex::new(RetryTracker::new()))
+    }
+
+    #[cfg(feature = "telemetry")]
+    fn export(counter: &'static str, step_id: &str) {
+        use opentelemetry::{global, KeyValue};
+
+        // Instruments are looked up per call so they bind to the provider set by
+        // `init_telemetry`; retries are rare enough that this costs nothing noticeable.
+        global::meter("terminator-mcp")
+            .u64_counter(counter)
+            .build()
+            .add(1, &[KeyValue::new("step.id", step_id.to_string())]);
+    }
+
+    #[cfg(not(feature = "telemetry"))]
+    fn export(_counter: &'static str, _step_id: &str) {}
+
+    /// Records that a step is being retried.
+    pub fn record_retry_attempt(step_id: &str) {
+        if let Ok(mut tracker) = retry_tracker().lock() {
+            tracker.record_attempt(step_id);
+        }
+        export("terminator.step.retry.attempts", step_id);
+    }
+
+    /// Records that a step succeeded after at least one retry.
+    pub fn record_retry_success(step_id: &str) {
+        if let Ok(mut tracker) = retry_tracker().lock() {
+            tracker.record_success(step_id);
+        }
+        export("terminator.step.retry.successes", step_id);
+    }
+
+    /// Records that a step used up its retries without succeeding.
+    pub fn record_retry_exhausted(step_id: &str) {
+        if let Ok(mut tracker) = retry_tracker().lock() {
+            tracker.record_exhausted(step_id);
+        }
+        export("terminator.step.retry.exhaustions", step_id);
+    }
+}
+
 // Implementation with telemetry enabled
 #[cfg(feature = "telemetry")]
 mod with_telemetry {
     use opentelemetry::global::BoxedSpan;
     use opentelemetry::{
         global,
-        trace::{Span, SpanKind, Status, Tracer},
-        KeyValue,
+        trace::{Span, SpanKind, Status, TraceContextExt, Tracer},
+        Context, KeyValue,
     };
     use opentelemetry_otlp::WithExportConfig;
     use opentelemetry_sdk::{
-        propagation::TraceContextPropagator, runtime, trace::TracerProvider as SdkTracerProvider,
+        metrics::{PeriodicReader, SdkMeterProvider},
+        propagation::TraceContextPropagator,
+        runtime,
+        trace::{Sampler, TracerProvider as SdkTracerProvider},
         Resource,
     };
     use opentelemetry_semantic_conventions::{
         attribute::{SERVICE_NAME, SERVICE_VERSION},
         SCHEMA_URL,
     };
+    use std::sync::OnceLock;
     use std::time::Duration;
-    use tracing::{debug, info};
+    use tracing::{debug, info, warn};
+
+    use super::privacy::{privacy_mode_enabled, scrub_attribute, scrub_message};
+
+    fn key_value(key: &str, value: String) -> Option<KeyValue> {
+        if !privacy_mode_enabled() {
+            return Some(KeyValue::new(key.to_string(), value));
+        }
+        scrub_attribute(key, &value).map(|value| KeyValue::new(key.to_string(), value))
+    }
+
+    fn error_status(message: &str) -> Status {
+        if privacy_mode_enabled() {
+            Status::error(scrub_message(message))
+        } else {
+            Status::error(message.to_string())
+        }
+    }
 
     pub struct WorkflowSpan {
         span: BoxedSpan,
@@ -46,21 +571,22 @@ mod with_telemetry {
         pub fn add_event(&mut self, name: &str, attributes: Vec<(&str, String)>) {
             let kvs: Vec<KeyValue> = attributes
                 .into_iter()
-                .map(|(k, v)| KeyValue::new(k.to_string(), v))
+                .filter_map(|(k, v)| key_value(k, v))
                 .collect();
             self.span.add_event(name.to_string(), kvs);
         }
 
         pub fn set_attribute(&mut self, key: &str, value: String) {
-            self.span
-                .set_attribute(KeyValue::new(key.to_string(), value));
+            if let Some(kv) = key_value(key, value) {
+                self.span.set_attribute(kv);
+            }
         }
 
         pub fn set_status(&mut self, success: bool, message: &str) {
             let status = if success {
                 Status::Ok
             } else {
-                Status::error(message.to_string())
+                error_status(message)
             };
             self.span.set_status(status);
         }
@@ -72,27 +598,45 @@ mod with_telemetry {
 
     pub struct StepSpan {
         span: BoxedSpan,
+        tool_name: String,
+        started: std::time::Instant,
     }
 
     impl StepSpan {
         pub fn new(tool_name: &str, step_id: Option<&str>) -> Self {
+            Self::start(tool_name, step_id, &Context::current())
+        }
+
+        /// A step span in the workflow's trace, so it shares the workflow's sampling decision
+        pub fn child_of(workflow: &WorkflowSpan, tool_name: &str, step_id: Option<&str>) -> Self {
+            let parent =
+                Context::new().with_remote_span_context(workflow.span.span_context().clone());
+            Self::start(tool_name, step_id, &parent)
+        }
+
+        fn start(tool_name: &str, step_id: Option<&str>, parent: &Context) -> Self {
             let tracer = global::tracer("terminator-mcp");
             let mut span = tracer
                 .span_builder(format!("step.{tool_name}"))
                 .with_kind(SpanKind::Internal)
-                .start(&tracer);
+                .start_with_context(&tracer, parent);
 
             span.set_attribute(KeyValue::new("tool.name", tool_name.to_string()));
             if let Some(id) = step_id {
                 span.set_attribute(KeyValue::new("step.id", id.to_string()));
             }
 
-            StepSpan { span }
+            StepSpan {
+                span,
+                tool_name: tool_name.to_string(),
+                started: std::time::Instant::now(),
+            }
         }
 
         pub fn set_attribute(&mut self, key: &str, value: String) {
-            self.span
-                .set_attribute(KeyValue::new(key.to_string(), value));
+            if let Some(kv) = key_value(key, value) {
+                self.span.set_attribute(kv);
+            }
         }
 
         pub fn set_status(&mut self, success: bool, error: Option<&str>) {
@@ -100,16 +644,22 @@ mod with_telemetry {
                 Status::Ok
             } else {
                 let message = error.unwrap_or("Failed");
-                Status::error(message.to_string())
+                error_status(message)
             };
             self.span.set_status(status);
         }
 
         pub fn end(mut self) {
+            super::latency::record_tool_latency(&self.tool_name, self.started.elapsed());
             self.span.end();
         }
     }
 
+    /// Samples whole workflow traces at `rate`; step spans follow their workflow span
+    pub fn trace_sampler(rate: f64) -> Sampler {
+        Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(rate)))
+    }
+
     /// Check if the OpenTelemetry collector is available
     fn check_collector_availability(endpoint: &str) -> bool {
         use std::net::{SocketAddr, TcpStream};
@@ -139,6 +689,9 @@ mod with_telemetry {
         false
     }
 
+    // Kept so pending metrics are flushed on shutdown
+    static METER_PROVIDER: OnceLock<SdkMeterProvider> = OnceLock::new();
+
     pub fn init_telemetry() -> anyhow::Result<()> {
         // Check if telemetry is enabled via environment variable
         if std::env::var("OTEL_SDK_DISABLED").unwrap_or_default() == "true" {
@@ -169,9 +722,10 @@ mod with_telemetry {
             }
         }
 
+        let sample_rate = super::traces_sample_rate();
         info!(
-            "Initializing OpenTelemetry with endpoint: {}",
-            otlp_endpoint
+            "Initializing OpenTelemetry with endpoint: {} (sampling {} of traces)",
+            otlp_endpoint, sample_rate
         );
 
         let exporter = opentelemetry_otlp::SpanExporter::builder()
@@ -180,20 +734,36 @@ mod with_telemetry {
             .with_timeout(Duration::from_secs(3))
             .build()?;
 
+        let resource = Resource::from_schema_url(
+            [
+                KeyValue::new(SERVICE_NAME, "terminator-mcp-agent"),
+                KeyValue::new(SERVICE_VERSION, env!("CARGO_PKG_VERSION")),
+            ],
+            SCHEMA_URL,
+        );
+
         // Create tracer provider with OTLP exporter
         let provider = SdkTracerProvider::builder()
             .with_batch_exporter(exporter, runtime::Tokio)
-            .with_resource(Resource::from_schema_url(
-                [
-                    KeyValue::new(SERVICE_NAME, "terminator-mcp-agent"),
-                    KeyValue::new(SERVICE_VERSION, env!("CARGO_PKG_VERSION")),
-                ],
-                SCHEMA_URL,
-            ))
+            .with_sampler(trace_sampler(sample_rate))
+            .with_resource(resource.clone())
             .build();
 
         global::set_tracer_provider(provider);
 
+        // Metrics (retry counters) go to the same collector
+        let metric_exporter = opentelemetry_otlp::MetricExporter::builder()
+            .with_http()
+            .with_endpoint(format!("{}/v1/metrics", &otlp_endpoint))
+            .with_timeout(Duration::from_secs(3))
+            .build()?;
+        let meter_provider = SdkMeterProvider::builder()
+            .with_reader(PeriodicReader::builder(metric_exporter, runtime::Tokio).build())
+            .with_resource(resource)
+            .build();
+        global::set_meter_provider(meter_provider.clone());
+        let _ = METER_PROVIDER.set(meter_provider);
+
         info!("OpenTelemetry telemetry initialized successfully");
         Ok(())
     }
@@ -201,6 +771,11 @@ mod with_telemetry {
     pub fn shutdown_telemetry() {
         // Shutdown with a short timeout to avoid hanging
         global::shutdown_tracer_provider();
+        if let Some(meter_provider) = METER_PROVIDER.get() {
+            if let Err(e) = meter_provider.shutdown() {
+                warn!("Failed to flush OpenTelemetry metrics: {e}");
+            }
+        }
     }
 }
 
@@ -223,17 +798,30 @@ mod without_telemetry {
         pub fn end(self) {}
     }
 
-    pub struct StepSpan;
+    /// Only measures latency for `latency_report` when telemetry is disabled
+    pub struct StepSpan {
+        tool_name: String,
+        started: std::time::Instant,
+    }
 
     impl StepSpan {
-        pub fn new(_tool_name: &str, _step_id: Option<&str>) -> Self {
+        pub fn new(tool_name: &str, _step_id: Option<&str>) -> Self {
             debug!("Telemetry disabled: StepSpan created (no-op)");
-            StepSpan
+            StepSpan {
+                tool_name: tool_name.to_string(),
+                started: std::time::Instant::now(),
+            }
+        }
+
+        pub fn child_of(_workflow: &WorkflowSpan, tool_name: &str, step_id: Option<&str>) -> Self {
+            Self::new(tool_name, step_id)
         }
 
         pub fn set_attribute(&mut self, _key: &str, _value: String) {}
         pub fn set_status(&mut self, _success: bool, _error: Option<&str>) {}
-        pub fn end(self) {}
+        pub fn end(self) {
+            super::latency::record_tool_latency(&self.tool_name, self.started.elapsed());
+        }
     }
 
     pub fn init_telemetry() -> anyhow::Result<()> {
@@ -0,0 +1,175 @@
+//! Rendering a UI tree as indented text, one element per line.
+//!
+//! The JSON tree returned by the tree tools repeats every attribute name on every
+//! element. The text form keeps the role, name and value of each element, indented two
+//! spaces per level, at a fraction of the size. With a filter, only matching elements
+//! and the ancestors leading to them are rendered, so the hierarchy around each match
+//! stays readable; branches without a match are dropped. Elements deeper than
+//! `max_depth` are not rendered; their parent gets a single `…` line counting the
+//! children left out.
+//!
+//! With `show_paths`, each line ends with the element's path from the root in the
+//! syntax of path selectors: one `Role[n]` step per level, `n` being the element's
+//! 1-based position among its siblings with the same role. Paths are computed on the
+//! whole tree before filtering, so the same snapshot always gives each element the same
+//! path, whatever is filtered out or cut by `max_depth`.
+//!
+//! With `show_bounds`, each element with known bounds also gets `bounds=x,y,width,height`
+//! in screen coordinates. An element without area is marked `(zero-size)`, and one
+//! lying entirely outside the root's bounds, such as a row scrolled out of view, is
+//! marked `(off-screen)`; their coordinates are still shown.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use terminator::UINode;
+
+/// How [`format_tree`] renders a tree
+#[derive(Debug, Clone, Default)]
+pub struct TreeFormatOptions {
+    /// Deepest level rendered, the root being level 0; `None` renders every level
+    pub max_depth: Option<usize>,
+    /// Only elements with one of these roles match, compared case-insensitively;
+    /// empty matches every role
+    pub roles: Vec<String>,
+    /// Only elements whose name contains this match, compared case-insensitively
+    pub name_contains: Option<String>,
+    /// End each line with the element's path selector, relative to the root
+    pub show_paths: bool,
+    /// End each line with the element's bounding box
+    pub show_bounds: bool,
+}
+
+impl TreeFormatOptions {
+    fn filters(&self) -> bool {
+        !self.roles.is_empty() || self.name_contains.is_some()
+    }
+
+    fn matches(&self, node: &UINode) -> bool {
+        let attributes = &node.attributes;
+        let role_matches = self.roles.is_empty()
+            || self
+                .roles
+                .iter()
+                .any(|role| role.eq_ignore_ascii_case(&attributes.role));
+        let name_matches = self.name_contains.as_deref().is_none_or(|needle| {
+            attributes
+                .name
+                .as_deref()
+                .is_some_and(|name| name.to_lowercase().contains(&needle.to_lowercase()))
+        });
+        role_matches && name_matches
+    }
+}
+
+/// An element kept by the filter, with its kept children
+struct Kept<'a> {
+    node: &'a UINode,
+    path: String,
+    children: Vec<Kept<'a>>,
+}
+
+/// Renders `root` and its descendants, one line per element. The root is always
+/// rendered, even when nothing below it matches the filter.
+pub fn format_tree(root: &UINode, options: &TreeFormatOptions) -> String {
+    let root = keep(root, "/".to_string(), options).unwrap_or(Kept {
+        node: root,
+        path: "/".to_string(),
+        children: Vec::new(),
+    });
+    let mut out = String::new();
+    let viewport = root.node.attributes.bounds;
+    write_node(&mut out, &root, 0, viewport, options);
+    out
+}
+
+/// `node` and the parts of its subtree to render, or `None` when neither it nor any
+/// descendant matches the filter
+fn keep<'a>(node: &'a UINode, path: String, options: &TreeFormatOptions) -> Option<Kept<'a>> {
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    let children: Vec<Kept<'a>> = node
+        .children
+        .iter()
+        .filter_map(|child| {
+            let role = child.attributes.role.as_str();
+            let ordinal = seen.entry(role).or_default();
+            *ordinal += 1;
+            let step = format!("{role}[{ordinal}]");
+            let child_path = if path == "/" {
+                format!("/{step}")
+            } else {
+                format!("{path}/{step}")
+            };
+            keep(child, child_path, options)
+        })
+        .collect();
+    let kept = !options.filters() || !children.is_empty() || options.matches(node);
+    kept.then_some(Kept {
+        node,
+        path,
+        children,
+    })
+}
+
+type Bounds = (f64, f64, f64, f64);
+
+fn write_node(
+    out: &mut String,
+    kept: &Kept,
+    depth: usize,
+    viewport: Option<Bounds>,
+    options: &TreeFormatOptions,
+) {
+    let indent = "  ".repeat(depth);
+    let mut line = describe(kept.node);
+    if options.show_paths {
+        let _ = write!(line, " path={}", kept.path);
+    }
+    if let Some(bounds) = kept.node.attributes.bounds.filter(|_| options.show_bounds) {
+        line.push_str(&describe_bounds(bounds, viewport));
+    }
+    let _ = writeln!(out, "{indent}{line}");
+
+    if kept.children.is_empty() {
+        return;
+    }
+    if options.max_depth.is_some_and(|max| depth >= max) {
+        let omitted = kept.children.len();
+        let noun = if omitted == 1 { "child" } else { "children" };
+        let _ = writeln!(out, "{indent}  … {omitted} {noun} omitted");
+        return;
+    }
+    for child in &kept.children {
+        write_node(out, child, depth + 1, viewport, options);
+    }
+}
+
+/// The element's role, followed by its name and value when it has them
+fn describe(node: &UINode) -> String {
+    let attributes = &node.attributes;
+    let mut line = attributes.role.clone();
+    if let Some(name) = attributes.name.as_deref().filter(|name| !name.is_empty()) {
+        let _ = write!(line, " {name:?}");
+    }
+    if let Some(value) = attributes
+        .value
+        .as_deref()
+        .filter(|value| !value.is_empty())
+    {
+        let _ = write!(line, " value={value:?}");
+    }
+    line
+}
+
+/// ` bounds=x,y,width,height`, marked when the box is empty or outside `viewport`
+fn describe_bounds((x, y, width, height): Bounds, viewport: Option<Bounds>) -> String {
+    let mut text = format!(" bounds={x},{y},{width},{height}");
+    if width <= 0.0 || height <= 0.0 {
+        text.push_str(" (zero-size)");
+    } else if let Some((vx, vy, vwidth, vheight)) = viewport {
+        let outside = x + width <= vx || y + height <= vy || x >= vx + vwidth || y >= vy + vheight;
+        if outside {
+            text.push_str(" (off-screen)");
+        }
+    }
+    text
+}
@@ -61,6 +61,10 @@ pub struct DesktopWrapper {
     pub current_workflow_dir: Arc<Mutex<Option<std::path::PathBuf>>>,
     #[serde(skip)]
     pub current_scripts_base_path: Arc<Mutex<Option<String>>>,
+    #[serde(skip)]
+    pub action_journal: Arc<Mutex<crate::action_journal::ActionJournal>>,
+    #[serde(skip)]
+    pub middleware: crate::middleware::MiddlewareChain,
 }
 
 impl Default for DesktopWrapper {
@@ -102,6 +106,30 @@ pub struct GetWindowTreeArgs {
         description = "Whether to include detailed element attributes (enabled, focused, selected, etc.). Defaults to true for comprehensive LLM context."
     )]
     pub include_detailed_attributes: Option<bool>,
+    #[schemars(
+        description = "Return the tree as indented text ('ui_tree_text'), one element per line, instead of JSON. Much smaller for large trees. Defaults to false."
+    )]
+    pub as_text: Option<bool>,
+    #[schemars(
+        description = "With as_text, the deepest level rendered (the window is level 0). Deeper elements are replaced by a line counting them. Defaults to unlimited."
+    )]
+    pub max_depth: Option<usize>,
+    #[schemars(
+        description = "With as_text, only render elements with one of these roles (e.g. ['Button', 'Edit']), case-insensitive. Their ancestors are kept so the hierarchy stays readable."
+    )]
+    pub roles: Option<Vec<String>>,
+    #[schemars(
+        description = "With as_text, only render elements whose name contains this text, case-insensitive. Combined with roles, an element must match both."
+    )]
+    pub name_contains: Option<String>,
+    #[schemars(
+        description = "With as_text, end each line with the element's path from the window, e.g. 'path=/Group[2]/Button[1]' (role and 1-based position among siblings with that role). The same tree always gives the same paths. On Windows a path can be chained after a selector for the window to target the element."
+    )]
+    pub show_paths: Option<bool>,
+    #[schemars(
+        description = "With as_text, end each line with the element's screen bounds as 'bounds=x,y,width,height', where known. Elements with no area are marked '(zero-size)' and elements entirely outside the window '(off-screen)'."
+    )]
+    pub show_bounds: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -110,6 +138,30 @@ pub struct GetFocusedWindowTreeArgs {
         description = "Whether to include detailed element attributes (enabled, focused, selected, etc.). Defaults to true for comprehensive LLM context."
     )]
     pub include_detailed_attributes: Option<bool>,
+    #[schemars(
+        description = "Return the tree as indented text ('ui_tree_text'), one element per line, instead of JSON. Much smaller for large trees. Defaults to false."
+    )]
+    pub as_text: Option<bool>,
+    #[schemars(
+        description = "With as_text, the deepest level rendered (the window is level 0). Deeper elements are replaced by a line counting them. Defaults to unlimited."
+    )]
+    pub max_depth: Option<usize>,
+    #[schemars(
+        description = "With as_text, only render elements with one of these roles (e.g. ['Button', 'Edit']), case-insensitive. Their ancestors are kept so the hierarchy stays readable."
+    )]
+    pub roles: Option<Vec<String>>,
+    #[schemars(
+        description = "With as_text, only render elements whose name contains this text, case-insensitive. Combined with roles, an element must match both."
+    )]
+    pub name_contains: Option<String>,
+    #[schemars(
+        description = "With as_text, end each line with the element's path from the window, e.g. 'path=/Group[2]/Button[1]' (role and 1-based position among siblings with that role). The same tree always gives the same paths. On Windows a path can be chained after a selector for the window to target the element."
+    )]
+    pub show_paths: Option<bool>,
+    #[schemars(
+        description = "With as_text, end each line with the element's screen bounds as 'bounds=x,y,width,height', where known. Elements with no area are marked '(zero-size)' and elements entirely outside the window '(off-screen)'."
+    )]
+    pub show_bounds: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -147,6 +199,10 @@ pub struct LocatorArgs {
     )]
     pub include_detailed_attributes: Option<bool>,
     pub retries: Option<u32>,
+    #[schemars(
+        description = "Set to true once the user has approved this action. Required for destructive actions when the server runs in safe mode (TERMINATOR_SAFE_MODE)."
+    )]
+    pub confirmed: Option<bool>,
 }
 
 #[derive(Debug, Serialize, JsonSchema, Clone)]
@@ -248,6 +304,10 @@ pub struct ClickElementArgs {
         description = "Optional click position as percentage (0-100) within the element. If not provided, clicks the center."
     )]
     pub click_position: Option<ClickPosition>,
+    #[schemars(
+        description = "Set to true once the user has approved this action. Required for destructive actions when the server runs in safe mode (TERMINATOR_SAFE_MODE)."
+    )]
+    pub confirmed: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -314,6 +374,10 @@ pub struct PressKeyArgs {
         description = "Optional highlighting configuration to visually indicate the target element before pressing keys"
     )]
     pub highlight_before_action: Option<ActionHighlightConfig>,
+    #[schemars(
+        description = "Set to true once the user has approved this action. Required for destructive actions when the server runs in safe mode (TERMINATOR_SAFE_MODE)."
+    )]
+    pub confirmed: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -328,6 +392,230 @@ pub struct GlobalKeyArgs {
         description = "Whether to include detailed element attributes (enabled, focused, selected, etc.) when include_tree is true. Defaults to true for comprehensive LLM context."
     )]
     pub include_detailed_attributes: Option<bool>,
+    #[schemars(
+        description = "Set to true once the user has approved this action. Required for destructive actions when the server runs in safe mode (TERMINATOR_SAFE_MODE)."
+    )]
+    pub confirmed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct TypeIntoFocusedArgs {
+    #[schemars(
+        description = "The text to type into the element that currently has keyboard focus"
+    )]
+    pub text: String,
+    #[schemars(description = "Whether to paste the text via the clipboard (default: true)")]
+    pub use_clipboard: Option<bool>,
+    #[schemars(description = "Whether to include full UI tree in the response. Defaults to true.")]
+    pub include_tree: Option<bool>,
+    #[schemars(
+        description = "Whether to include detailed element attributes (enabled, focused, selected, etc.) when include_tree is true. Defaults to true for comprehensive LLM context."
+    )]
+    pub include_detailed_attributes: Option<bool>,
+    #[schemars(
+        description = "Set to true once the user has approved this action. Required for destructive actions when the server runs in safe mode (TERMINATOR_SAFE_MODE)."
+    )]
+    pub confirmed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ClipboardSetArgs {
+    #[schemars(description = "The text to put on the clipboard, replacing its content")]
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct PressKeysArgs {
+    #[schemars(
+        description = "Keys or key combinations to press in order on the focused element (e.g., ['{Tab}', '{Ctrl}a', '{Enter}'])"
+    )]
+    pub keys: Vec<String>,
+    #[schemars(description = "Whether to include full UI tree in the response. Defaults to true.")]
+    pub include_tree: Option<bool>,
+    #[schemars(
+        description = "Whether to include detailed element attributes (enabled, focused, selected, etc.) when include_tree is true. Defaults to true for comprehensive LLM context."
+    )]
+    pub include_detailed_attributes: Option<bool>,
+    #[schemars(
+        description = "Set to true once the user has approved this action. Required for destructive actions when the server runs in safe mode (TERMINATOR_SAFE_MODE)."
+    )]
+    pub confirmed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct CheckFieldsArgs {
+    #[schemars(description = "Selectors of the form fields to check, in display order")]
+    pub selectors: Vec<String>,
+    #[schemars(description = "Timeout in milliseconds for finding each field (default: 3000)")]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ResolveSelectorsArgs {
+    #[schemars(description = "Candidate selectors to look up, each resolved independently")]
+    pub selectors: Vec<String>,
+    #[schemars(description = "Timeout in milliseconds for each selector (default: 1000)")]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindElementsArgs {
+    #[schemars(description = "Selector to match. Can be chained with ` >> `.")]
+    pub selector: String,
+    #[schemars(description = "Maximum number of matches to return (default: all of them)")]
+    pub limit: Option<usize>,
+    #[schemars(
+        description = "Timeout in milliseconds to wait for the first match (default: 1000)"
+    )]
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ReadMenuArgs {
+    #[schemars(
+        description = "Selector of the menu bar, menu or menu item to read. Can be chained with ` >> `."
+    )]
+    pub selector: String,
+    #[schemars(
+        description = "Optional alternative selectors to try in parallel. The first selector that finds an element will be used."
+    )]
+    pub alternative_selectors: Option<String>,
+    #[schemars(
+        description = "Optional fallback selectors to try sequentially if the primary selector fails."
+    )]
+    pub fallback_selectors: Option<String>,
+    #[schemars(description = "Optional timeout in milliseconds")]
+    pub timeout_ms: Option<u64>,
+    pub retries: Option<u32>,
+    #[schemars(description = "Number of submenu levels to read (default: 5)")]
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct VisualRegion {
+    #[schemars(description = "Left edge in pixels, relative to the element's top-left corner")]
+    pub x: u32,
+    #[schemars(description = "Top edge in pixels, relative to the element's top-left corner")]
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct AssertVisualArgs {
+    #[schemars(
+        description = "A string selector to locate the element. Can be chained with ` >> `."
+    )]
+    pub selector: String,
+    #[schemars(
+        description = "Optional alternative selectors to try in parallel. The first selector that finds an element will be used."
+    )]
+    pub alternative_selectors: Option<String>,
+    #[schemars(
+        description = "Optional fallback selectors to try sequentially if the primary selector fails."
+    )]
+    pub fallback_selectors: Option<String>,
+    #[schemars(description = "Optional timeout in milliseconds")]
+    pub timeout_ms: Option<u64>,
+    pub retries: Option<u32>,
+    #[schemars(
+        description = "Path of the baseline PNG. Relative paths resolve against the running workflow's directory. Created from the capture when missing."
+    )]
+    pub baseline: String,
+    #[schemars(description = "Only compare this part of the element's capture")]
+    pub region: Option<VisualRegion>,
+    #[schemars(
+        description = "Share of pixels (0.0-1.0) allowed to differ before the assertion fails (default: 0.01)"
+    )]
+    pub tolerance: Option<f64>,
+    #[schemars(
+        description = "Per-channel difference (0-255) below which two pixels count as equal (default: 16)"
+    )]
+    pub pixel_threshold: Option<u8>,
+    #[schemars(description = "Replace the baseline with the current capture (default: false)")]
+    pub update_baseline: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct HighlightedScreenshotArgs {
+    #[schemars(
+        description = "A string selector to locate the element. Can be chained with ` >> `."
+    )]
+    pub selector: String,
+    #[schemars(
+        description = "Optional alternative selectors to try in parallel. The first selector that finds an element will be used."
+    )]
+    pub alternative_selectors: Option<String>,
+    #[schemars(
+        description = "Optional fallback selectors to try sequentially if the primary selector fails."
+    )]
+    pub fallback_selectors: Option<String>,
+    #[schemars(description = "Optional timeout in milliseconds")]
+    pub timeout_ms: Option<u64>,
+    pub retries: Option<u32>,
+    #[schemars(description = "BGR color code of the rectangle (optional, default red)")]
+    pub color: Option<u32>,
+    #[schemars(description = "Rectangle thickness in pixels (optional, default 3)")]
+    pub border_width: Option<u32>,
+    #[schemars(
+        description = "Index of the monitor to capture, as returned by list_monitors (default: the monitor showing most of the element)"
+    )]
+    pub monitor: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct OcrRegionArgs {
+    #[schemars(
+        description = "Selector of an element whose bounding box is read. Use either this or x, y, width and height."
+    )]
+    pub selector: Option<String>,
+    #[schemars(
+        description = "Optional alternative selectors to try in parallel. The first selector that finds an element will be used."
+    )]
+    pub alternative_selectors: Option<String>,
+    #[schemars(
+        description = "Optional fallback selectors to try sequentially if the primary selector fails."
+    )]
+    pub fallback_selectors: Option<String>,
+    #[schemars(description = "Left edge of the region to read")]
+    pub x: Option<f64>,
+    #[schemars(description = "Top edge of the region to read")]
+    pub y: Option<f64>,
+    #[schemars(description = "Width of the region to read")]
+    pub width: Option<f64>,
+    #[schemars(description = "Height of the region to read")]
+    pub height: Option<f64>,
+    #[schemars(
+        description = "Index of the monitor to read from, as returned by list_monitors. x and y are then relative to it. Without it, x and y are desktop coordinates and the monitor showing most of the region is read."
+    )]
+    pub monitor: Option<usize>,
+    #[schemars(
+        description = "BCP 47 locale of the text, such as 'en-US' or 'de-DE' (default: the OCR engine's default language)"
+    )]
+    pub locale: Option<String>,
+    #[schemars(description = "Optional timeout in milliseconds for finding the element")]
+    pub timeout_ms: Option<u64>,
+    pub retries: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetEventsArgs {
+    #[schemars(description = "Run id returned by execute_sequence (default: the most recent run)")]
+    pub run_id: Option<String>,
+    #[schemars(
+        description = "Only return events with a sequence number greater than this (default: 0, i.e. everything buffered)"
+    )]
+    pub since_seq: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct UndoArgs {
+    #[schemars(description = "Number of recent actions to undo (default: 1)")]
+    pub count: Option<usize>,
+    #[schemars(
+        description = "Timeout in milliseconds for finding each element to restore (default: 3000)"
+    )]
+    pub timeout_ms: Option<u64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -348,6 +636,14 @@ pub struct RunCommandArgs {
         description = "Optional high-level engine to execute inline code with SDK bindings. One of: 'node', 'bun', 'javascript', 'js', 'typescript', 'ts', 'python'. When set, 'run' or 'script_file' must contain the code to execute."
     )]
     pub engine: Option<String>,
+    #[schemars(
+        description = "Maximum time an 'engine' script may run, such as '30s' or '5m'. The script is killed and the call fails when it is exceeded. Defaults to TERMINATOR_SCRIPT_TIMEOUT, or 10 minutes. Python scripts the agent runs in its embedded interpreter cannot be killed; setting a timeout runs them in a python process."
+    )]
+    pub timeout: Option<String>,
+    #[schemars(
+        description = "Set true to give an 'engine' script the 'desktop' object for driving the UI. Without it 'desktop' is not defined, so the script can only work on data."
+    )]
+    pub allow_desktop: Option<bool>,
     #[schemars(
         description = "The shell to use for 'run' (ignored when 'engine' is used). If not specified, defaults to PowerShell on Windows, bash on Unix. Common values: 'bash', 'sh', 'cmd', 'powershell', 'pwsh'"
     )]
@@ -356,6 +652,10 @@ pub struct RunCommandArgs {
         description = "Working directory where the command should be executed. Defaults to current directory."
     )]
     pub working_directory: Option<String>,
+    #[schemars(
+        description = "Set to true once the user has approved this action. Required for destructive actions when the server runs in safe mode (TERMINATOR_SAFE_MODE)."
+    )]
+    pub confirmed: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -372,6 +672,10 @@ pub struct MouseDragArgs {
     pub end_x: f64,
     #[schemars(description = "End Y coordinate")]
     pub end_y: f64,
+    #[schemars(
+        description = "Index of the monitor the coordinates are relative to, as returned by list_monitors. Without it, coordinates are desktop coordinates."
+    )]
+    pub monitor: Option<usize>,
     #[schemars(
         description = "Optional alternative selectors to try in parallel. The first selector that finds an element will be used."
     )]
@@ -389,6 +693,54 @@ pub struct MouseDragArgs {
     )]
     pub include_detailed_attributes: Option<bool>,
     pub retries: Option<u32>,
+    #[schemars(
+        description = "Set to true once the user has approved this action. Required for destructive actions when the server runs in safe mode (TERMINATOR_SAFE_MODE)."
+    )]
+    pub confirmed: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct DragAndDropArgs {
+    #[schemars(
+        description = "Selector of the element to drag; the drag starts at its center. Use either this or source_x and source_y."
+    )]
+    pub source_selector: Option<String>,
+    #[schemars(description = "Screen X coordinate to start the drag at")]
+    pub source_x: Option<f64>,
+    #[schemars(description = "Screen Y coordinate to start the drag at")]
+    pub source_y: Option<f64>,
+    #[schemars(
+        description = "Selector of the element to drop onto; the drag ends at its center. Use either this or target_x and target_y."
+    )]
+    pub target_selector: Option<String>,
+    #[schemars(description = "Screen X coordinate to drop at")]
+    pub target_x: Option<f64>,
+    #[schemars(description = "Screen Y coordinate to drop at")]
+    pub target_y: Option<f64>,
+    #[schemars(
+        description = "Index of the monitor the coordinates are relative to, as returned by list_monitors. Without it, coordinates are desktop coordinates."
+    )]
+    pub monitor: Option<usize>,
+    #[schemars(
+        description = "Number of mouse moves between press and release (default 10). Raise it for apps that ignore fast drags."
+    )]
+    pub steps: Option<u32>,
+    #[schemars(
+        description = "Pause in milliseconds after the press and after each move (default 10)"
+    )]
+    pub step_delay_ms: Option<u64>,
+    #[schemars(description = "Optional timeout in milliseconds for finding each element")]
+    pub timeout_ms: Option<u64>,
+    #[schemars(description = "Whether to include full UI tree in the response (verbose mode)")]
+    pub include_tree: Option<bool>,
+    #[schemars(
+        description = "Whether to include detailed element attributes (enabled, focused, selected, etc.) when include_tree is true. Defaults to true for comprehensive LLM context."
+    )]
+    pub include_detailed_attributes: Option<bool>,
+    #[schemars(
+        description = "Set to true once the user has approved this action. Required for destructive actions when the server runs in safe mode (TERMINATOR_SAFE_MODE)."
+    )]
+    pub confirmed: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -416,6 +768,39 @@ pub struct ValidateElementArgs {
     pub retries: Option<u32>,
 }
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ScrollIntoViewArgs {
+    #[schemars(
+        description = "A string selector to locate the element. Can be chained with ` >> `."
+    )]
+    pub selector: String,
+    #[schemars(
+        description = "Optional alternative selectors to try in parallel. The first selector that finds an element will be used."
+    )]
+    pub alternative_selectors: Option<String>,
+    #[schemars(
+        description = "Optional fallback selectors to try sequentially if the primary selector fails.  These selectors are **only** attempted after the primary selector (and any parallel alternatives) time-out.  List can be comma-separated."
+    )]
+    pub fallback_selectors: Option<String>,
+    #[schemars(
+        description = "Scrolls to try before failing with error_type 'scroll_failed' (default 20)"
+    )]
+    pub max_scrolls: Option<u32>,
+    #[schemars(
+        description = "Size of each scroll, as for scroll_element (default 0.5). Larger values reach distant elements in fewer scrolls but may overshoot."
+    )]
+    pub scroll_amount: Option<f64>,
+    #[schemars(description = "Optional timeout in milliseconds")]
+    pub timeout_ms: Option<u64>,
+    #[schemars(description = "Whether to include full UI tree in the response (verbose mode)")]
+    pub include_tree: Option<bool>,
+    #[schemars(
+        description = "Whether to include detailed element attributes (enabled, focused, selected, etc.) when include_tree is true. Defaults to true for comprehensive LLM context."
+    )]
+    pub include_detailed_attributes: Option<bool>,
+    pub retries: Option<u32>,
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct HighlightElementArgs {
     #[schemars(
@@ -475,6 +860,14 @@ pub struct WaitForElementArgs {
     pub condition: String,
     #[schemars(description = "Optional timeout in milliseconds")]
     pub timeout_ms: Option<u64>,
+    #[schemars(
+        description = "How long to wait, e.g. '10s', '500ms' or '1m30s'. Takes precedence over timeout_ms. Defaults to 3s."
+    )]
+    pub timeout: Option<String>,
+    #[schemars(
+        description = "How long to sleep between checks, e.g. '250ms' or '1s'. Defaults to 100ms."
+    )]
+    pub poll_interval: Option<String>,
     #[schemars(description = "Whether to include full UI tree in the response (verbose mode)")]
     pub include_tree: Option<bool>,
     #[schemars(
@@ -482,6 +875,10 @@ pub struct WaitForElementArgs {
     )]
     pub include_detailed_attributes: Option<bool>,
     pub retries: Option<u32>,
+    #[schemars(
+        description = "Optional expression over the workflow variables that must also hold, e.g. \"env.status == 'ready'\". The wait succeeds only when the element condition and the expression are both true. Inside execute_sequence the expression sees the variables as they were when the step started; outside it there are none."
+    )]
+    pub expression: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
@@ -586,6 +983,10 @@ pub struct SetToggledArgs {
     )]
     pub include_detailed_attributes: Option<bool>,
     pub retries: Option<u32>,
+    #[schemars(
+        description = "Set to true once the user has approved this action. Required for destructive actions when the server runs in safe mode (TERMINATOR_SAFE_MODE)."
+    )]
+    pub confirmed: Option<bool>,
 }
 
 #[derive(Debug, serde::Deserialize, JsonSchema)]
@@ -645,6 +1046,64 @@ pub struct SetRangeValueArgs {
     pub retries: Option<u32>,
 }
 
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GetCellArgs {
+    #[schemars(description = "A string selector to locate the table or data grid.")]
+    pub selector: String,
+    #[schemars(
+        description = "0-based index of the data row to read. The header row is not counted."
+    )]
+    pub row: usize,
+    #[schemars(
+        description = "Label of the column header to read from, e.g. 'Amount'. Matched case-insensitively."
+    )]
+    pub column_header: String,
+    #[schemars(description = "Optional alternative selectors.")]
+    pub alternative_selectors: Option<String>,
+    #[schemars(
+        description = "Optional fallback selectors to try sequentially if the primary selector fails.  These selectors are **only** attempted after the primary selector (and any parallel alternatives) time-out.  List can be comma-separated."
+    )]
+    pub fallback_selectors: Option<String>,
+    #[schemars(description = "Optional timeout in milliseconds.")]
+    pub timeout_ms: Option<u64>,
+    #[schemars(description = "Whether to include full UI tree in the response.")]
+    pub include_tree: Option<bool>,
+    #[schemars(
+        description = "Whether to include detailed element attributes (enabled, focused, selected, etc.) when include_tree is true. Defaults to true for comprehensive LLM context."
+    )]
+    pub include_detailed_attributes: Option<bool>,
+    pub retries: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindByIdentityArgs {
+    #[schemars(
+        description = "A string selector for the scope to search in, e.g. the window or list that re-renders."
+    )]
+    pub selector: String,
+    #[schemars(
+        description = "Identity key of the element, as returned in the 'identity_key' field of element info."
+    )]
+    pub identity_key: String,
+    #[schemars(description = "How many levels below the scope element to search. Defaults to 50.")]
+    pub max_depth: Option<usize>,
+    #[schemars(description = "Optional alternative selectors.")]
+    pub alternative_selectors: Option<String>,
+    #[schemars(
+        description = "Optional fallback selectors to try sequentially if the primary selector fails.  These selectors are **only** attempted after the primary selector (and any parallel alternatives) time-out.  List can be comma-separated."
+    )]
+    pub fallback_selectors: Option<String>,
+    #[schemars(description = "Optional timeout in milliseconds.")]
+    pub timeout_ms: Option<u64>,
+    #[schemars(description = "Whether to include full UI tree in the response.")]
+    pub include_tree: Option<bool>,
+    #[schemars(
+        description = "Whether to include detailed element attributes (enabled, focused, selected, etc.) when include_tree is true. Defaults to true for comprehensive LLM context."
+    )]
+    pub include_detailed_attributes: Option<bool>,
+    pub retries: Option<u32>,
+}
+
 #[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct SetValueArgs {
     #[schemars(description = "A string selector to locate the element whose value will be set.")]
@@ -689,6 +1148,10 @@ pub struct SetSelectedArgs {
     )]
     pub include_detailed_attributes: Option<bool>,
     pub retries: Option<u32>,
+    #[schemars(
+        description = "Set to true once the user has approved this action. Required for destructive actions when the server runs in safe mode (TERMINATOR_SAFE_MODE)."
+    )]
+    pub confirmed: Option<bool>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
@@ -786,8 +1249,44 @@ pub struct SequenceStep {
         description = "An optional expression to determine if this step should run. e.g., \"policy.use_max_budget == true\" or \"contains(policy.product_types, 'FEX')\""
     )]
     pub r#if: Option<String>,
+    #[schemars(
+        description = "Optional expression describing the step's post-state. If it is already true when the step is reached, the step is skipped and reported as 'satisfied', making re-runs idempotent. e.g., \"env.login_status == 'logged_in'\""
+    )]
+    pub precondition: Option<String>,
+    #[schemars(
+        description = "For group steps: an expression that ends a loop over the group's steps. The steps re-run until it holds (checked after each iteration), e.g. \"env.queue_empty == true\". The current iteration (from 1) is available as env.<loop_variable>."
+    )]
+    pub repeat_until: Option<String>,
+    #[schemars(
+        description = "Safeguard for repeat_until: the most iterations to run before giving up (default: 100). Hitting it fails the step."
+    )]
+    pub max_iterations: Option<u32>,
+    #[schemars(
+        description = "Safeguard for repeat_until: stop looping once this much time has passed, e.g. '30s' or '2m'. Hitting it fails the step."
+    )]
+    pub loop_timeout: Option<String>,
+    #[schemars(
+        description = "Name of the env variable holding the current repeat_until iteration (default: 'iteration')."
+    )]
+    pub loop_variable: Option<String>,
+    #[schemars(
+        description = "For group steps: env variables set inside the group to keep after it ends. Other variables the group introduces (including its loop variable) are removed when it exits; variables that existed before the group keep their updated values."
+    )]
+    pub promote: Option<Vec<String>>,
+    #[schemars(
+        description = "For group steps: run the group's steps concurrently instead of in order. Their outputs are merged into env, in step order, once all have ended. Cannot be combined with repeat_until."
+    )]
+    pub parallel: Option<bool>,
+    #[schemars(
+        description = "For parallel groups: when a step fails (and does not set continue_on_error), cancel the steps still running. Defaults to true; when false, the other steps run to completion and the group still fails."
+    )]
+    pub fail_fast: Option<bool>,
     #[schemars(description = "Number of times to retry this step or group on failure.")]
     pub retries: Option<u32>,
+    #[schemars(
+        description = "Optional exponential backoff between retries, replacing the fixed 500ms pause. e.g. {\"max_attempts\": 5, \"initial_delay\": \"1s\", \"multiplier\": 2, \"jitter\": 0.2}"
+    )]
+    pub retry: Option<crate::retry_backoff::RetryConfig>,
     #[schemars(
         description = "Optional unique identifier for this step (string). If provided, it can be a target for other steps' fallback_id. Additionally, the tool's result will be stored as {step_id}_result and its status as {step_id}_status in the environment, making it accessible to subsequent steps."
     )]
@@ -796,6 +1295,26 @@ pub struct SequenceStep {
         description = "Optional id of the step to jump to if this step ultimately fails after all retries. This enables robust fallback flows without relying on numeric indices."
     )]
     pub fallback_id: Option<String>,
+    #[schemars(
+        description = "For single tool steps: the id of an earlier step whose result contains an element (e.g. find_by_identity or validate_element). The step acts on that exact element, found again by its identity key, instead of resolving its own selector. Fails if the element is no longer in the UI."
+    )]
+    pub element_ref: Option<String>,
+    #[schemars(
+        description = "For single tool steps: an expression that must hold after the tool succeeds, checked with the step's result already stored as env.<id>_result, e.g. \"env.dialog_open == true\". If it does not hold, the action counts as failed and is retried like any other failure (see 'retries')."
+    )]
+    pub verify: Option<String>,
+    #[schemars(
+        description = "For single tool steps: the longest an attempt may run, e.g. '30s' or '2m'. An attempt that runs longer is cancelled and fails with a StepTimeout error, which 'retries', 'continue_on_error' and 'fallback_id' handle like any other failure."
+    )]
+    pub timeout: Option<String>,
+    #[schemars(
+        description = "Handlers for when this step still fails after its retries. The first handler whose 'when' holds sets its variables, runs its fallback steps and resolves the failure with 'then'. The failure is available to them as env.error (message, kind, category, step_id, tool_name)."
+    )]
+    pub on_error: Option<Vec<crate::on_error::ErrorHandler>>,
+    #[schemars(
+        description = "For run_command and execute_browser_script steps with an 'id' (and the calls of a repeat_until group): store the fields the script returns under env.<id> instead of as top-level env variables, e.g. env.parse_invoice.total. Defaults to false."
+    )]
+    pub namespace_outputs: Option<bool>,
 
     // Simplified aliases (keeping originals for backward compatibility)
     #[schemars(
@@ -870,6 +1389,18 @@ pub struct ExecuteSequenceArgs {
         description = "Optional base path for resolving script files. When script_file is used in run_command or execute_browser_script, relative paths will first be searched in this directory, then fallback to workflow directory or current directory. Useful for mounting external file sources like S3 via rclone."
     )]
     pub scripts_base_path: Option<String>,
+    #[schemars(
+        description = "Optional watcher that automatically dismisses modal dialogs (update prompts, error popups) matching the given title patterns while the workflow runs."
+    )]
+    pub modal_watcher: Option<crate::modal_watcher::ModalWatcherConfig>,
+    #[schemars(
+        description = "Optional file path. When set, the run is exported there as a self-contained bundle (workflow, inputs, and a trace of every tool call with its result) that can be replayed to reproduce the outcome."
+    )]
+    pub export_bundle: Option<String>,
+    #[schemars(
+        description = "Key under which persistent variables are stored between runs. Defaults to the workflow URL."
+    )]
+    pub persistence_key: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
@@ -899,6 +1430,10 @@ pub struct VariableDefinition {
     pub options: Option<Vec<String>>,
     #[schemars(description = "Whether this variable is required. Defaults to true.")]
     pub required: Option<bool>,
+    #[schemars(
+        description = "Whether the variable's final value is saved when the run completes and loaded at the start of the next run (e.g. a running counter). Defaults to false."
+    )]
+    pub persistent: Option<bool>,
 }
 
 // Keep the old structures for internal use
@@ -931,6 +1466,10 @@ pub struct CloseElementArgs {
     )]
     pub include_detailed_attributes: Option<bool>,
     pub retries: Option<u32>,
+    #[schemars(
+        description = "Set to true once the user has approved this action. Required for destructive actions when the server runs in safe mode (TERMINATOR_SAFE_MODE)."
+    )]
+    pub confirmed: Option<bool>,
 }
 
 #[derive(Deserialize, JsonSchema, Debug, Clone)]
@@ -1341,6 +1880,13 @@ pub struct ImportWorkflowSequenceArgs {
     pub return_raw: Option<bool>,
 }
 
+/// Whether an error is a UI Automation failure that retrying will not fix.
+///
+/// Transient codes (e.g. element not available) still go through the normal retries.
+pub fn is_fatal_automation_error(error: &AutomationError) -> bool {
+    matches!(error, AutomationError::UIAutomationAPIError { .. }) && !error.is_transient()
+}
+
 /// A robust helper that finds a UI element and executes a provided action on it,
 /// with built-in retry logic for both finding the element and performing the action.
 ///
@@ -1379,7 +1925,11 @@ where
             Ok((element, successful_selector)) => match action(element.clone()).await {
                 Ok(result) => return Ok(((result, element), successful_selector)),
                 Err(e) => {
+                    let fatal = is_fatal_automation_error(&e);
                     last_error = Some(e.into());
+                    if fatal {
+                        break;
+                    }
                     if attempt < retry_count {
                         warn!(
                             "Action failed on attempt {}/{}. Retrying... Error: {}",
@@ -1392,7 +1942,11 @@ where
                 }
             },
             Err(e) => {
+                let fatal = is_fatal_automation_error(&e);
                 last_error = Some(e.into());
+                if fatal {
+                    break;
+                }
                 if attempt < retry_count {
                     warn!(
                         "Find element failed on attempt {}/{}. Retrying... Error: {}",
@@ -1445,7 +1999,11 @@ where
             Ok((element, successful_selector)) => match action(element.clone()).await {
                 Ok(result) => return Ok(((result, element), successful_selector)),
                 Err(e) => {
+                    let fatal = is_fatal_automation_error(&e);
                     last_error = Some(e.into());
+                    if fatal {
+                        break;
+                    }
                     if attempt < retry_count {
                         warn!(
                             "Action failed on attempt {}/{}. Retrying... Error: {}",
@@ -1458,7 +2016,11 @@ where
                 }
             },
             Err(e) => {
+                let fatal = is_fatal_automation_error(&e);
                 last_error = Some(e.into());
+                if fatal {
+                    break;
+                }
                 if attempt < retry_count {
                     warn!(
                         "Find element failed on attempt {}/{}. Retrying... Error: {}",
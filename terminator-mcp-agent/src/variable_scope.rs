@@ -0,0 +1,82 @@
+//! Block scoping of workflow variables.
+//!
+//! Group steps open a block. Variables a block introduces into `env` (the loop variable,
+//! values set by its steps, `<id>_status` of its calls) are visible to the block's later
+//! steps and removed when it exits. Variables that already existed when the block was
+//! entered belong to an enclosing scope, so updating them inside the block keeps the new
+//! value. A block can keep some of its own variables by promoting them, which moves
+//! them to the enclosing scope.
+//!
+//! All scopes share the single `env` object that expressions and substitutions read, so
+//! expressions always see the innermost value of every visible variable.
+
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+
+#[derive(Debug, Default)]
+struct Scope {
+    /// Names visible when the block was entered
+    enclosing: HashSet<String>,
+    promoted: HashSet<String>,
+}
+
+/// The open blocks of a workflow run, innermost last
+#[derive(Debug, Default)]
+pub struct ScopeStack {
+    scopes: Vec<Scope>,
+}
+
+impl ScopeStack {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of open blocks
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Opens a block. Every name currently in `env` belongs to an enclosing scope.
+    pub fn enter(&mut self, env: &Map<String, Value>) {
+        self.scopes.push(Scope {
+            enclosing: env.keys().cloned().collect(),
+            promoted: HashSet::new(),
+        });
+    }
+
+    /// Keeps `name` after the innermost block exits, as a variable of the enclosing scope.
+    pub fn promote(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.promoted.insert(name.to_string());
+        }
+    }
+
+    /// Closes the innermost block and removes the variables it introduced, except
+    /// promoted ones. Returns the removed names in sorted order.
+    pub fn exit(&mut self, env: &mut Map<String, Value>) -> Vec<String> {
+        let Some(scope) = self.scopes.pop() else {
+            return Vec::new();
+        };
+        let mut removed: Vec<String> = env
+            .keys()
+            .filter(|name| !scope.enclosing.contains(*name) && !scope.promoted.contains(*name))
+            .cloned()
+            .collect();
+        removed.sort();
+        for name in &removed {
+            env.remove(name);
+        }
+        removed
+    }
+}
+
+/// The `env` object of an execution context, created when missing.
+pub fn env_mut(context: &mut Map<String, Value>) -> &mut Map<String, Value> {
+    if !context.get("env").is_some_and(Value::is_object) {
+        context.insert("env".to_string(), Value::Object(Map::new()));
+    }
+    context
+        .get_mut("env")
+        .and_then(Value::as_object_mut)
+        .expect("env was just inserted as an object")
+}
@@ -0,0 +1,127 @@
+//! Persistent workflow variables.
+//!
+//! Variables declared with `persistent: true` survive between runs: their final values
+//! are saved under a workflow key when a run completes and loaded again when the next
+//! run starts. Each key is stored in its own JSON file, named after a hash of the key so
+//! that any two keys get different files; the key itself is kept inside the file. Every
+//! save writes a temporary file and renames it into place so concurrent runs never leave
+//! a partially written file.
+
+use crate::utils::VariableDefinition;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Overrides the directory persistent variables are stored in
+pub const STORE_DIR_ENV: &str = "TERMINATOR_VARIABLE_STORE_DIR";
+
+#[derive(Debug, Clone)]
+pub struct VariableStore {
+    dir: PathBuf,
+}
+
+impl VariableStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The store at `$TERMINATOR_VARIABLE_STORE_DIR`, falling back to
+    /// `~/.terminator/variables`.
+    pub fn default_location() -> Self {
+        if let Ok(dir) = std::env::var(STORE_DIR_ENV) {
+            return Self::new(dir);
+        }
+        let home = std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir);
+        Self::new(home.join(".terminator").join("variables"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn file_for(&self, key: &str) -> PathBuf {
+        let hash = blake3::hash(key.as_bytes());
+        self.dir.join(format!("{}.json", hash.to_hex()))
+    }
+
+    /// Loads the values stored under `key`. A missing store yields an empty map.
+    pub async fn load(&self, key: &str) -> Result<Map<String, Value>, String> {
+        let path = self.file_for(key);
+        let content = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Map::new()),
+            Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+        };
+        let stored: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse {}: {e}", path.display()))?;
+        Ok(stored
+            .get("variables")
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// Atomically replaces the values stored under `key`.
+    pub async fn save(&self, key: &str, values: &Map<String, Value>) -> Result<(), String> {
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| format!("Failed to create {}: {e}", self.dir.display()))?;
+
+        let path = self.file_for(key);
+        let content = serde_json::to_string_pretty(&serde_json::json!({
+            "key": key,
+            "last_updated": chrono::Utc::now().to_rfc3339(),
+            "variables": values,
+        }))
+        .map_err(|e| format!("Failed to serialize persistent variables: {e}"))?;
+
+        // Write to a unique temp file in the same directory, then rename over the target
+        let tmp = path.with_extension(format!("{}.tmp", uuid::Uuid::new_v4().simple()));
+        tokio::fs::write(&tmp, content)
+            .await
+            .map_err(|e| format!("Failed to write {}: {e}", tmp.display()))?;
+        if let Err(e) = tokio::fs::rename(&tmp, &path).await {
+            let _ = tokio::fs::remove_file(&tmp).await;
+            return Err(format!("Failed to replace {}: {e}", path.display()));
+        }
+        debug!(
+            "Saved {} persistent variable(s) to {:?}",
+            values.len(),
+            path
+        );
+        Ok(())
+    }
+}
+
+/// Names of the variables declared `persistent: true`
+pub fn persistent_names(definitions: &HashMap<String, VariableDefinition>) -> Vec<String> {
+    let mut names: Vec<String> = definitions
+        .iter()
+        .filter(|(_, def)| def.persistent.unwrap_or(false))
+        .map(|(name, _)| name.clone())
+        .collect();
+    names.sort();
+    names
+}
+
+/// Picks the final values of the persistent variables from a run's context.
+///
+/// Values set at runtime (in `env`) win over the variable's value at the start of the run.
+pub fn collect_persistent_values(
+    names: &[String],
+    context: &Map<String, Value>,
+) -> Map<String, Value> {
+    let env = context.get("env").and_then(|e| e.as_object());
+    names
+        .iter()
+        .filter_map(|name| {
+            env.and_then(|env| env.get(name))
+                .or_else(|| context.get(name))
+                .map(|value| (name.clone(), value.clone()))
+        })
+        .collect()
+}
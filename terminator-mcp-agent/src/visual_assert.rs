@@ -0,0 +1,218 @@
+//! Visual assertions behind the `assert_visual` tool.
+//!
+//! A capture is compared pixel by pixel against a baseline PNG. A pixel differs when any
+//! channel is off by more than `pixel_threshold`, which absorbs anti-aliasing and
+//! rendering noise; the assertion passes while the share of differing pixels stays
+//! within `tolerance`. When it fails, a diff image (differing pixels in red over a faded
+//! copy of the capture) and the capture itself are saved next to the baseline as
+//! `<name>.diff.png` and `<name>.actual.png`.
+//!
+//! A missing baseline is created from the capture, and `update_baseline` overwrites it.
+
+use image::{ImageFormat, Rgba, RgbaImage};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Default share of pixels (0.0-1.0) allowed to differ
+pub const DEFAULT_TOLERANCE: f64 = 0.01;
+
+/// Default per-channel difference below which two pixels count as equal
+pub const DEFAULT_PIXEL_THRESHOLD: u8 = 16;
+
+const DIFF_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VisualOptions {
+    pub tolerance: f64,
+    pub pixel_threshold: u8,
+    /// Replace the baseline with the capture instead of comparing
+    pub update_baseline: bool,
+}
+
+impl Default for VisualOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: DEFAULT_TOLERANCE,
+            pixel_threshold: DEFAULT_PIXEL_THRESHOLD,
+            update_baseline: false,
+        }
+    }
+}
+
+/// How far a capture is from its baseline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImageDiff {
+    pub differing_pixels: u64,
+    pub total_pixels: u64,
+    /// `differing_pixels / total_pixels`
+    pub ratio: f64,
+    /// The images have different dimensions, which never passes
+    pub size_mismatch: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum VisualOutcome {
+    BaselineCreated {
+        baseline: PathBuf,
+    },
+    BaselineUpdated {
+        baseline: PathBuf,
+    },
+    Matched {
+        baseline: PathBuf,
+        diff: ImageDiff,
+    },
+    Deviated {
+        baseline: PathBuf,
+        diff: ImageDiff,
+        diff_path: PathBuf,
+        actual_path: PathBuf,
+    },
+}
+
+impl VisualOutcome {
+    pub fn passed(&self) -> bool {
+        !matches!(self, VisualOutcome::Deviated { .. })
+    }
+}
+
+/// Compares two images and renders the diff image.
+///
+/// Images of different sizes are compared over the larger area, where pixels present in
+/// only one of them count as differing.
+pub fn diff_images(
+    baseline: &RgbaImage,
+    actual: &RgbaImage,
+    pixel_threshold: u8,
+) -> (ImageDiff, RgbaImage) {
+    let width = baseline.width().max(actual.width());
+    let height = baseline.height().max(actual.height());
+    let mut diff_image = RgbaImage::new(width, height);
+    let mut differing_pixels = 0u64;
+
+    for y in 0..height {
+        for x in 0..width {
+            let expected = baseline.get_pixel_checked(x, y);
+            let found = actual.get_pixel_checked(x, y);
+            let differs = match (expected, found) {
+                (Some(a), Some(b)) => {
+                    a.0.iter()
+                        .zip(b.0.iter())
+                        .any(|(a, b)| a.abs_diff(*b) > pixel_threshold)
+                }
+                _ => true,
+            };
+            let pixel = if differs {
+                differing_pixels += 1;
+                DIFF_COLOR
+            } else {
+                faded(found.copied().unwrap_or(Rgba([0, 0, 0, 0])))
+            };
+            diff_image.put_pixel(x, y, pixel);
+        }
+    }
+
+    let total_pixels = u64::from(width) * u64::from(height);
+    let diff = ImageDiff {
+        differing_pixels,
+        total_pixels,
+        ratio: if total_pixels == 0 {
+            0.0
+        } else {
+            differing_pixels as f64 / total_pixels as f64
+        },
+        size_mismatch: baseline.dimensions() != actual.dimensions(),
+    };
+    (diff, diff_image)
+}
+
+// A light grayscale version of a pixel, so the red differences stand out
+fn faded(pixel: Rgba<u8>) -> Rgba<u8> {
+    let [r, g, b, _] = pixel.0;
+    let luma = (u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114) / 1000;
+    let light = (192 + luma / 4) as u8;
+    Rgba([light, light, light, 255])
+}
+
+/// Crops a capture to a region given relative to its top-left corner.
+pub fn crop(
+    image: &RgbaImage,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+) -> Result<RgbaImage, String> {
+    let fits = width > 0
+        && height > 0
+        && x.saturating_add(width) <= image.width()
+        && y.saturating_add(height) <= image.height();
+    if !fits {
+        return Err(format!(
+            "Region {width}x{height} at ({x}, {y}) is outside the {}x{} capture",
+            image.width(),
+            image.height()
+        ));
+    }
+    Ok(image::imageops::crop_imm(image, x, y, width, height).to_image())
+}
+
+/// Where an artifact of a failed assertion is written, e.g. `login.diff.png`
+pub fn artifact_path(baseline: &Path, kind: &str) -> PathBuf {
+    let stem = baseline
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "baseline".to_string());
+    baseline.with_file_name(format!("{stem}.{kind}.png"))
+}
+
+fn save_png(image: &RgbaImage, path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory {}: {e}", parent.display()))?;
+    }
+    image
+        .save_with_format(path, ImageFormat::Png)
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// Checks a capture against the baseline at `baseline_path`.
+pub fn assert_visual(
+    actual: &RgbaImage,
+    baseline_path: &Path,
+    options: &VisualOptions,
+) -> Result<VisualOutcome, String> {
+    let baseline = baseline_path.to_path_buf();
+    if options.update_baseline || !baseline_path.exists() {
+        let existed = baseline_path.exists();
+        save_png(actual, baseline_path)?;
+        return Ok(if existed {
+            VisualOutcome::BaselineUpdated { baseline }
+        } else {
+            VisualOutcome::BaselineCreated { baseline }
+        });
+    }
+
+    let expected = image::open(baseline_path)
+        .map_err(|e| format!("Failed to read baseline {}: {e}", baseline_path.display()))?
+        .to_rgba8();
+    let (diff, diff_image) = diff_images(&expected, actual, options.pixel_threshold);
+    let diff_path = artifact_path(baseline_path, "diff");
+    let actual_path = artifact_path(baseline_path, "actual");
+
+    if !diff.size_mismatch && diff.ratio <= options.tolerance {
+        // Artifacts of an earlier failure no longer describe the current state
+        let _ = std::fs::remove_file(&diff_path);
+        let _ = std::fs::remove_file(&actual_path);
+        return Ok(VisualOutcome::Matched { baseline, diff });
+    }
+
+    save_png(&diff_image, &diff_path)?;
+    save_png(actual, &actual_path)?;
+    Ok(VisualOutcome::Deviated {
+        baseline,
+        diff,
+        diff_path,
+        actual_path,
+    })
+}
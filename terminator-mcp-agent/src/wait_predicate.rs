@@ -0,0 +1,191 @@
+//! Composite wait predicates.
+//!
+//! A composite predicate combines an element condition (exists/visible/enabled/focused)
+//! with an optional `expression_eval` condition over the workflow variables. The wait
+//! succeeds only when every part holds on the same poll.
+//!
+//! Waits return as soon as the predicate holds, and stop early when their cancellation
+//! token is cancelled, even in the middle of a poll.
+
+use serde::Serialize;
+use serde_json::Value;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// How long a wait sleeps between polls unless told otherwise
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The workflow variables a tool call made by `execute_sequence` can evaluate expressions
+/// against, passed in the request extensions. They are the variables as they were when
+/// the step started; nothing the workflow does while the tool runs changes them.
+#[derive(Debug, Clone)]
+pub struct WorkflowVariables(pub Value);
+
+/// The element state a wait checks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElementCondition {
+    Exists,
+    Visible,
+    Enabled,
+    Focused,
+}
+
+impl ElementCondition {
+    pub fn parse(condition: &str) -> Option<Self> {
+        match condition.trim().to_lowercase().as_str() {
+            "exists" => Some(Self::Exists),
+            "visible" => Some(Self::Visible),
+            "enabled" => Some(Self::Enabled),
+            "focused" => Some(Self::Focused),
+            _ => None,
+        }
+    }
+}
+
+/// Which parts of a composite predicate held on the last poll
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PredicateStatus {
+    pub element_met: bool,
+    pub expression_met: bool,
+    pub polls: u32,
+    pub elapsed_ms: u128,
+}
+
+impl PredicateStatus {
+    pub fn all_met(&self) -> bool {
+        self.element_met && self.expression_met
+    }
+}
+
+/// Why a wait ended without the predicate holding, with the status of the last poll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitFailure {
+    TimedOut(PredicateStatus),
+    Cancelled(PredicateStatus),
+}
+
+impl WaitFailure {
+    pub fn status(&self) -> &PredicateStatus {
+        match self {
+            WaitFailure::TimedOut(status) | WaitFailure::Cancelled(status) => status,
+        }
+    }
+}
+
+/// The timeout and poll interval of a wait. `timeout` and `poll_interval` are duration
+/// strings such as "10s" or "250ms"; `timeout` takes precedence over `timeout_ms`, and
+/// without either the timeout is `default_timeout`.
+pub fn parse_wait_timing(
+    timeout: Option<&str>,
+    timeout_ms: Option<u64>,
+    poll_interval: Option<&str>,
+    default_timeout: Duration,
+) -> Result<(Duration, Duration), String> {
+    let timeout = match (timeout, timeout_ms) {
+        (Some(timeout), _) => crate::duration_parser::parse_std_duration(timeout)
+            .map_err(|e| format!("Invalid timeout '{timeout}': {e}"))?,
+        (None, Some(ms)) => Duration::from_millis(ms),
+        (None, None) => default_timeout,
+    };
+    let poll_interval = match poll_interval {
+        Some(interval) => crate::duration_parser::parse_std_duration(interval)
+            .map_err(|e| format!("Invalid poll_interval '{interval}': {e}"))?,
+        None => DEFAULT_POLL_INTERVAL,
+    };
+    if poll_interval.is_zero() {
+        return Err("poll_interval must be greater than zero".to_string());
+    }
+    Ok((timeout, poll_interval))
+}
+
+/// Polls until both the element check and the expression hold, or the timeout elapses.
+///
+/// `element_holds` is awaited on every poll, and so is `variables`, which gives the values
+/// the expression is evaluated against on that poll. Without an expression only the
+/// element part is considered. Returns the final status as `Err` on
+/// timeout, which tells the caller which part never held.
+pub async fn wait_for_composite<E, EF, V>(
+    element_holds: E,
+    expression: Option<&str>,
+    variables: V,
+    timeout: Duration,
+    poll_interval: Duration,
+) -> Result<PredicateStatus, PredicateStatus>
+where
+    E: FnMut() -> EF,
+    EF: Future<Output = bool>,
+    V: Fn() -> Value,
+{
+    wait_until(
+        element_holds,
+        expression,
+        variables,
+        timeout,
+        poll_interval,
+        &CancellationToken::new(),
+    )
+    .await
+    .map_err(|failure| *failure.status())
+}
+
+/// Like [`wait_for_composite`], but gives up with [`WaitFailure::Cancelled`] as soon as
+/// `cancellation` is cancelled, whether the wait is polling or sleeping.
+pub async fn wait_until<E, EF, V>(
+    mut element_holds: E,
+    expression: Option<&str>,
+    variables: V,
+    timeout: Duration,
+    poll_interval: Duration,
+    cancellation: &CancellationToken,
+) -> Result<PredicateStatus, WaitFailure>
+where
+    E: FnMut() -> EF,
+    EF: Future<Output = bool>,
+    V: Fn() -> Value,
+{
+    let start = Instant::now();
+    let mut status = PredicateStatus {
+        element_met: false,
+        expression_met: false,
+        polls: 0,
+        elapsed_ms: 0,
+    };
+    loop {
+        if cancellation.is_cancelled() {
+            status.elapsed_ms = start.elapsed().as_millis();
+            return Err(WaitFailure::Cancelled(status));
+        }
+        let element_met = tokio::select! {
+            met = element_holds() => met,
+            _ = cancellation.cancelled() => {
+                status.elapsed_ms = start.elapsed().as_millis();
+                return Err(WaitFailure::Cancelled(status));
+            }
+        };
+        let expression_met = match expression {
+            Some(expr) => crate::expression_eval::evaluate(expr, &variables()),
+            None => true,
+        };
+        status = PredicateStatus {
+            element_met,
+            expression_met,
+            polls: status.polls + 1,
+            elapsed_ms: start.elapsed().as_millis(),
+        };
+
+        if status.all_met() {
+            return Ok(status);
+        }
+        if start.elapsed() >= timeout {
+            return Err(WaitFailure::TimedOut(status));
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(poll_interval.min(timeout.saturating_sub(start.elapsed()))) => {}
+            _ = cancellation.cancelled() => {
+                status.elapsed_ms = start.elapsed().as_millis();
+                return Err(WaitFailure::Cancelled(status));
+            }
+        }
+    }
+}
@@ -1,7 +1,14 @@
 // This module uses terminator-workflow-recorder types directly where possible
 // and only creates wrapper types where needed for MCP conversion
 
+use futures::Stream;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+use terminator::ErrorCategory;
+use tokio::sync::broadcast;
 
 // Re-export types from recorder that we use directly
 pub use terminator_workflow_recorder::{
@@ -115,3 +122,475 @@ pub struct McpToolStep {
 
 // No conversion needed - using recorder types directly
 // All From implementations have been removed since we're using recorder types directly
+
+/// Events emitted while a workflow is executing.
+///
+/// These are distinct from the recorder's `WorkflowEvent`, which describes captured
+/// user input; an `ExecutionEvent` describes something the sequence runner did.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExecutionEvent {
+    /// A modal dialog matching a configured pattern was dismissed automatically
+    ModalDismissed {
+        title: String,
+        button: String,
+        timestamp: String,
+    },
+    /// A step's tool call failed; emitted for every failed attempt, including retries
+    StepFailed {
+        step_id: Option<String>,
+        tool_name: String,
+        error: String,
+        /// Who is at fault, when the tool reported it
+        category: Option<ErrorCategory>,
+        timestamp: String,
+    },
+    /// A step began; emitted for skipped steps too, so every step has a pair
+    StepStarted {
+        step_index: usize,
+        step_id: Option<String>,
+        tool_name: Option<String>,
+        group_name: Option<String>,
+        /// Milliseconds since the run started, from a monotonic clock
+        monotonic_ms: f64,
+        timestamp: String,
+    },
+    /// A step ended; always follows the step's `StepStarted`
+    StepFinished {
+        step_index: usize,
+        step_id: Option<String>,
+        tool_name: Option<String>,
+        group_name: Option<String>,
+        outcome: StepOutcome,
+        /// Time since the step started, from a monotonic clock
+        duration_ms: f64,
+        monotonic_ms: f64,
+        timestamp: String,
+    },
+    /// The runner set or merged an env variable. Values longer than
+    /// [`MAX_VARIABLE_PREVIEW_CHARS`] are truncated, see [`truncate_value`].
+    VariableChanged {
+        /// Variable name; `namespace.name` for namespaced step outputs
+        key: String,
+        /// `None` when the variable did not exist before
+        old_value: Option<Value>,
+        new_value: Value,
+        /// Id of the step that changed it
+        step_id: Option<String>,
+        timestamp: String,
+    },
+    /// The run is over; always the last event of a run, and live streams end after it
+    RunFinished {
+        /// The run's final status, e.g. `success`, `completed_with_errors` or `error`
+        status: String,
+        /// Milliseconds since the run started, from a monotonic clock
+        duration_ms: f64,
+        timestamp: String,
+    },
+}
+
+/// A change to an env variable, before truncation
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableChange {
+    pub key: String,
+    pub old_value: Option<Value>,
+    pub new_value: Value,
+}
+
+/// Longest serialized value a `VariableChanged` event carries in full
+pub const MAX_VARIABLE_PREVIEW_CHARS: usize = 256;
+
+/// Returns small values unchanged. A value whose JSON is longer than
+/// [`MAX_VARIABLE_PREVIEW_CHARS`] becomes `{"truncated": true, "length": <chars>,
+/// "preview": <first chars of the JSON>}`.
+pub fn truncate_value(value: &Value) -> Value {
+    let text = value.to_string();
+    let length = text.chars().count();
+    if length <= MAX_VARIABLE_PREVIEW_CHARS {
+        return value.clone();
+    }
+    json!({
+        "truncated": true,
+        "length": length,
+        "preview": text.chars().take(MAX_VARIABLE_PREVIEW_CHARS).collect::<String>(),
+    })
+}
+
+/// How a step ended
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepOutcome {
+    Success,
+    Failure,
+    Skipped,
+}
+
+/// Number of events kept per run for late clients
+pub const DEFAULT_EVENT_BUFFER_CAPACITY: usize = 1000;
+
+/// Number of recent runs whose event buffers stay queryable
+pub const MAX_BUFFERED_RUNS: usize = 16;
+
+/// An execution event tagged with its position in the run's stream.
+///
+/// Sequence numbers start at 1 and increase by one per event, so `seq` doubles as the
+/// cursor for `get_events`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SequencedEvent {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub event: ExecutionEvent,
+}
+
+/// The buffered events after a cursor
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EventsSince {
+    pub events: Vec<SequencedEvent>,
+    /// Sequence number of the newest event emitted so far (0 when there are none)
+    pub latest_seq: u64,
+    /// `true` when events after the cursor were evicted from the buffer
+    pub truncated: bool,
+}
+
+#[derive(Debug)]
+struct EventLog {
+    capacity: usize,
+    latest_seq: u64,
+    buffer: VecDeque<SequencedEvent>,
+    /// Set once `RunFinished` was emitted
+    finished: bool,
+}
+
+impl EventLog {
+    fn since(&self, since_seq: u64) -> EventsSince {
+        let first_buffered = self.buffer.front().map(|e| e.seq);
+        EventsSince {
+            events: self
+                .buffer
+                .iter()
+                .filter(|e| e.seq > since_seq)
+                .cloned()
+                .collect(),
+            latest_seq: self.latest_seq,
+            truncated: first_buffered.is_some_and(|first| first > since_seq + 1),
+        }
+    }
+}
+
+/// Collects execution events for a single workflow run.
+///
+/// Cloning the emitter shares the underlying buffer, so background tasks scoped to the
+/// run (e.g. the modal watcher) can emit into the same stream as the runner. The most
+/// recent events are kept in a bounded ring buffer for clients that connect mid-run,
+/// and every event is also broadcast to live subscribers.
+#[derive(Debug, Clone)]
+pub struct ExecutionEventEmitter {
+    log: Arc<Mutex<EventLog>>,
+    live: broadcast::Sender<SequencedEvent>,
+    /// Start of the run, the origin of `monotonic_ms`
+    started: Instant,
+}
+
+impl Default for ExecutionEventEmitter {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_EVENT_BUFFER_CAPACITY)
+    }
+}
+
+impl ExecutionEventEmitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An emitter that keeps at most `capacity` events
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (live, _) = broadcast::channel(capacity);
+        Self {
+            log: Arc::new(Mutex::new(EventLog {
+                capacity,
+                latest_seq: 0,
+                buffer: VecDeque::with_capacity(capacity),
+                finished: false,
+            })),
+            live,
+            started: Instant::now(),
+        }
+    }
+
+    /// Milliseconds since the emitter was created, from a monotonic clock
+    pub fn monotonic_ms(&self) -> f64 {
+        self.started.elapsed().as_secs_f64() * 1000.0
+    }
+
+    /// Emits `StepStarted` and returns the timer that emits the matching `StepFinished`
+    pub fn step_started(
+        &self,
+        step_index: usize,
+        step_id: Option<&str>,
+        tool_name: Option<&str>,
+        group_name: Option<&str>,
+    ) -> StepTimer {
+        let timer = StepTimer {
+            emitter: self.clone(),
+            step_index,
+            step_id: step_id.map(str::to_string),
+            tool_name: tool_name.map(str::to_string),
+            group_name: group_name.map(str::to_string),
+            started: Instant::now(),
+        };
+        self.emit(ExecutionEvent::StepStarted {
+            step_index,
+            step_id: timer.step_id.clone(),
+            tool_name: timer.tool_name.clone(),
+            group_name: timer.group_name.clone(),
+            monotonic_ms: self.monotonic_ms(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+        timer
+    }
+
+    /// Returns the guard that emits `RunFinished` when the run is over
+    pub fn run_started(&self) -> RunGuard {
+        RunGuard {
+            emitter: self.clone(),
+            status: None,
+        }
+    }
+
+    /// Records an event, mirrors it to the tracing log and broadcasts it to subscribers
+    pub fn emit(&self, event: ExecutionEvent) {
+        tracing::info!(target: "workflow_events", "{:?}", event);
+        if let Ok(mut log) = self.log.lock() {
+            if matches!(event, ExecutionEvent::RunFinished { .. }) {
+                log.finished = true;
+            }
+            log.latest_seq += 1;
+            let sequenced = SequencedEvent {
+                seq: log.latest_seq,
+                event,
+            };
+            if log.buffer.len() == log.capacity {
+                log.buffer.pop_front();
+            }
+            log.buffer.push_back(sequenced.clone());
+            // Sent under the lock so a subscriber never sees an event twice or misses one
+            let _ = self.live.send(sequenced);
+        }
+    }
+
+    /// Emits `VariableChanged` for each change that altered a value
+    pub fn variables_changed(&self, changes: Vec<VariableChange>, step_id: Option<&str>) {
+        for change in changes {
+            if change.old_value.as_ref() == Some(&change.new_value) {
+                continue;
+            }
+            self.emit(ExecutionEvent::VariableChanged {
+                key: change.key,
+                old_value: change.old_value.as_ref().map(truncate_value),
+                new_value: truncate_value(&change.new_value),
+                step_id: step_id.map(str::to_string),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            });
+        }
+    }
+
+    /// Sets an env variable, emitting `VariableChanged` when its value changes
+    pub fn set_variable(
+        &self,
+        env: &mut Map<String, Value>,
+        key: String,
+        value: Value,
+        step_id: Option<&str>,
+    ) {
+        let old_value = env.insert(key.clone(), value.clone());
+        self.variables_changed(
+            vec![VariableChange {
+                key,
+                old_value,
+                new_value: value,
+            }],
+            step_id,
+        );
+    }
+
+    /// Returns a snapshot of the buffered events
+    pub fn events(&self) -> Vec<ExecutionEvent> {
+        self.log
+            .lock()
+            .map(|log| log.buffer.iter().map(|e| e.event.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the buffered events with a sequence number greater than `since_seq`
+    pub fn since(&self, since_seq: u64) -> EventsSince {
+        self.log
+            .lock()
+            .map(|log| log.since(since_seq))
+            .unwrap_or_else(|_| EventsSince {
+                events: Vec::new(),
+                latest_seq: 0,
+                truncated: false,
+            })
+    }
+
+    /// A live stream of the events emitted from now on.
+    ///
+    /// Emitting never waits for subscribers. One that falls more than the buffer capacity
+    /// behind skips the events it missed, which shows as a gap in `seq`; they can still be
+    /// fetched with [`since`](Self::since) while buffered. The stream ends after the
+    /// run's `RunFinished` event, right away when the run has already finished, or once
+    /// every clone of the emitter has been dropped.
+    pub fn subscribe(&self) -> impl Stream<Item = SequencedEvent> + Send + 'static {
+        // Subscribed under the lock, so `RunFinished` is either seen as finished here or
+        // delivered to the receiver
+        let receiver = match self.log.lock() {
+            Ok(log) if log.finished => None,
+            _ => Some(self.live.subscribe()),
+        };
+        futures::stream::unfold(receiver, |receiver| async move {
+            let mut receiver = receiver?;
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        let last = matches!(event.event, ExecutionEvent::RunFinished { .. });
+                        return Some((event, (!last).then_some(receiver)));
+                    }
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        tracing::warn!(
+                            target: "workflow_events",
+                            "Event subscriber fell behind and skipped {} events",
+                            missed
+                        );
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+
+    /// Catches up from `since_seq` and subscribes to later events in one step.
+    ///
+    /// The receiver yields exactly the events emitted after the returned batch.
+    pub fn subscribe_since(
+        &self,
+        since_seq: u64,
+    ) -> (EventsSince, broadcast::Receiver<SequencedEvent>) {
+        match self.log.lock() {
+            Ok(log) => (log.since(since_seq), self.live.subscribe()),
+            Err(_) => (
+                EventsSince {
+                    events: Vec::new(),
+                    latest_seq: 0,
+                    truncated: false,
+                },
+                self.live.subscribe(),
+            ),
+        }
+    }
+}
+
+/// Times a running step; see [`ExecutionEventEmitter::step_started`]
+#[derive(Debug)]
+pub struct StepTimer {
+    emitter: ExecutionEventEmitter,
+    step_index: usize,
+    step_id: Option<String>,
+    tool_name: Option<String>,
+    group_name: Option<String>,
+    started: Instant,
+}
+
+impl StepTimer {
+    /// Emits `StepFinished` with the time elapsed since the step started
+    pub fn finish(self, outcome: StepOutcome) {
+        let duration_ms = self.started.elapsed().as_secs_f64() * 1000.0;
+        self.emitter.emit(ExecutionEvent::StepFinished {
+            step_index: self.step_index,
+            step_id: self.step_id,
+            tool_name: self.tool_name,
+            group_name: self.group_name,
+            outcome,
+            duration_ms,
+            monotonic_ms: self.emitter.monotonic_ms(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+}
+
+/// Ends a run's event stream; see [`ExecutionEventEmitter::run_started`].
+///
+/// Emits `RunFinished` with the status passed to [`finish`](Self::finish), or with
+/// `error` when dropped without it, e.g. because the run was cancelled or failed early.
+/// The registry keeps emitters of finished runs alive, so without this event their
+/// live subscribers would never see the stream end.
+#[derive(Debug)]
+pub struct RunGuard {
+    emitter: ExecutionEventEmitter,
+    status: Option<String>,
+}
+
+impl RunGuard {
+    /// Ends the run with the given status
+    pub fn finish(mut self, status: &str) {
+        self.status = Some(status.to_string());
+    }
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        self.emitter.emit(ExecutionEvent::RunFinished {
+            status: self.status.take().unwrap_or_else(|| "error".to_string()),
+            duration_ms: self.emitter.monotonic_ms(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        });
+    }
+}
+
+/// Event buffers of the most recent runs, looked up by run id
+#[derive(Debug)]
+pub struct EventRuns {
+    capacity: usize,
+    runs: Mutex<VecDeque<(String, ExecutionEventEmitter)>>,
+}
+
+impl Default for EventRuns {
+    fn default() -> Self {
+        Self::with_capacity(MAX_BUFFERED_RUNS)
+    }
+}
+
+impl EventRuns {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            runs: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Makes a run's events queryable, evicting the oldest run when full
+    pub fn register(&self, run_id: &str, emitter: ExecutionEventEmitter) {
+        if let Ok(mut runs) = self.runs.lock() {
+            runs.retain(|(id, _)| id != run_id);
+            if runs.len() == self.capacity {
+                runs.pop_front();
+            }
+            runs.push_back((run_id.to_string(), emitter));
+        }
+    }
+
+    /// Finds a run by id, or the most recent run when `run_id` is `None`
+    pub fn get(&self, run_id: Option<&str>) -> Option<(String, ExecutionEventEmitter)> {
+        let runs = self.runs.lock().ok()?;
+        match run_id {
+            Some(run_id) => runs.iter().find(|(id, _)| id == run_id).cloned(),
+            None => runs.back().cloned(),
+        }
+    }
+}
+
+/// The process-wide run registry behind the `get_events` tool
+pub fn runs() -> &'static EventRuns {
+    static RUNS: OnceLock<EventRuns> = OnceLock::new();
+    RUNS.get_or_init(EventRuns::default)
+}
@@ -0,0 +1,248 @@
+//! Renders a workflow definition as Markdown for readers who do not read YAML.
+//!
+//! [`to_markdown`] is a pure transformation of [`ExecuteSequenceArgs`]: the inputs
+//! become a table, the steps (and troubleshooting steps) a numbered list with their
+//! arguments, and the output parser a short description. Conditions are shown exactly
+//! as written in the workflow so they can be matched against the source.
+//!
+//! Inputs are listed by name and argument keys alphabetically, so the same workflow
+//! always renders to the same document.
+
+use crate::utils::{ExecuteSequenceArgs, SequenceStep, ToolCall, VariableDefinition};
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Renders `workflow` as a Markdown document.
+pub fn to_markdown(workflow: &ExecuteSequenceArgs) -> String {
+    let mut out = String::from("# Workflow\n");
+    if let Some(url) = &workflow.url {
+        let _ = write!(out, "\nSource: {}\n", code(url));
+    }
+
+    out.push_str("\n## Inputs\n\n");
+    render_inputs(&mut out, workflow);
+
+    out.push_str("\n## Steps\n\n");
+    match workflow.steps.as_deref() {
+        Some(steps) if !steps.is_empty() => render_steps(&mut out, steps),
+        _ => out.push_str("_No steps._\n"),
+    }
+
+    if let Some(steps) = workflow
+        .troubleshooting
+        .as_deref()
+        .filter(|s| !s.is_empty())
+    {
+        out.push_str("\n## Troubleshooting\n\nOnly reached through a step's `fallback_id`.\n\n");
+        render_steps(&mut out, steps);
+    }
+
+    out.push_str("\n## Outputs\n\n");
+    render_outputs(&mut out, workflow);
+    out
+}
+
+fn render_inputs(out: &mut String, workflow: &ExecuteSequenceArgs) {
+    let mut variables: Vec<(&String, &VariableDefinition)> = workflow
+        .variables
+        .iter()
+        .flat_map(|variables| variables.iter())
+        .collect();
+    if variables.is_empty() {
+        out.push_str("_No inputs._\n");
+        return;
+    }
+    variables.sort_by(|a, b| a.0.cmp(b.0));
+
+    out.push_str("| Name | Type | Required | Default | Description |\n");
+    out.push_str("| --- | --- | --- | --- | --- |\n");
+    for (name, definition) in variables {
+        let kind = serde_json::to_value(&definition.r#type)
+            .ok()
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let required = if definition.required.unwrap_or(true) {
+            "yes"
+        } else {
+            "no"
+        };
+        let default = definition
+            .default
+            .as_ref()
+            .map(|v| code(&v.to_string()))
+            .unwrap_or_default();
+
+        let mut description = definition.label.clone();
+        if let Some(text) = definition.description.as_deref().filter(|d| !d.is_empty()) {
+            let _ = write!(description, " — {text}");
+        }
+        if let Some(options) = definition.options.as_deref().filter(|o| !o.is_empty()) {
+            let options: Vec<String> = options.iter().map(|o| code(o)).collect();
+            let _ = write!(description, " (one of {})", options.join(", "));
+        }
+
+        let _ = writeln!(
+            out,
+            "| {} | {kind} | {required} | {} | {} |",
+            cell(&code(name)),
+            cell(&default),
+            cell(&description)
+        );
+    }
+}
+
+fn render_steps(out: &mut String, steps: &[SequenceStep]) {
+    for (index, step) in steps.iter().enumerate() {
+        let number = index + 1;
+        match (&step.tool_name, &step.group_name) {
+            (Some(tool_name), _) => {
+                let _ = writeln!(out, "{number}. {}", title(step.id.as_deref(), tool_name));
+                render_step_details(out, step);
+                render_arguments(out, step.arguments.as_ref(), "   ");
+            }
+            (None, Some(group_name)) => {
+                let label = match &step.id {
+                    Some(id) => format!("**{group_name}** (group {})", code(id)),
+                    None => format!("**{group_name}** (group)"),
+                };
+                let _ = writeln!(out, "{number}. {label}");
+                render_step_details(out, step);
+            }
+            (None, None) => {
+                let _ = writeln!(out, "{number}. _Empty step_");
+            }
+        }
+
+        for (inner, call) in step.steps.iter().flatten().enumerate() {
+            render_call(out, inner + 1, call);
+        }
+    }
+}
+
+fn render_call(out: &mut String, number: usize, call: &ToolCall) {
+    let _ = writeln!(
+        out,
+        "   {number}. {}",
+        title(call.id.as_deref(), &call.tool_name)
+    );
+    render_arguments(out, Some(&call.arguments), "      ");
+    if call.continue_on_error == Some(true) {
+        out.push_str("      - Continues on error\n");
+    }
+}
+
+fn title(id: Option<&str>, tool_name: &str) -> String {
+    match id {
+        Some(id) => format!("**{id}**: {}", code(tool_name)),
+        None => code(tool_name),
+    }
+}
+
+fn render_arguments(out: &mut String, arguments: Option<&Value>, indent: &str) {
+    let Some(Value::Object(arguments)) = arguments else {
+        return;
+    };
+    let mut keys: Vec<&String> = arguments.keys().collect();
+    keys.sort();
+    for key in keys {
+        let value = match &arguments[key] {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let _ = writeln!(out, "{indent}- {}: {}", code(key), code(&value));
+    }
+}
+
+fn render_step_details(out: &mut String, step: &SequenceStep) {
+    let indent = "   ";
+    if let Some(condition) = &step.r#if {
+        let _ = writeln!(out, "{indent}- Runs only if {}", code(condition));
+    }
+    if let Some(precondition) = &step.precondition {
+        let _ = writeln!(
+            out,
+            "{indent}- Skipped when {} already holds",
+            code(precondition)
+        );
+    }
+    if let Some(until) = &step.repeat_until {
+        let limit = step
+            .max_iterations
+            .map(|n| format!(" (at most {n} times)"))
+            .unwrap_or_default();
+        let _ = writeln!(out, "{indent}- Repeats until {}{limit}", code(until));
+    }
+    if let Some(names) = step.promote.as_deref().filter(|n| !n.is_empty()) {
+        let names: Vec<String> = names.iter().map(|n| code(n)).collect();
+        let _ = writeln!(out, "{indent}- Keeps {} after the group", names.join(", "));
+    }
+    if let Some(reference) = &step.element_ref {
+        let _ = writeln!(
+            out,
+            "{indent}- Acts on the element captured by step {}",
+            code(reference)
+        );
+    }
+    if let Some(verify) = &step.verify {
+        let _ = writeln!(
+            out,
+            "{indent}- Fails unless {} holds afterwards",
+            code(verify)
+        );
+    }
+    if let Some(retries) = step.retries.filter(|r| *r > 0) {
+        let _ = writeln!(out, "{indent}- Retried up to {retries} times");
+    }
+    if let Some(fallback) = &step.fallback_id {
+        let _ = writeln!(out, "{indent}- On failure, continues at {}", code(fallback));
+    } else if step.continue_on_error == Some(true) {
+        let _ = writeln!(out, "{indent}- Continues on error");
+    }
+}
+
+fn render_outputs(out: &mut String, workflow: &ExecuteSequenceArgs) {
+    let parser = workflow.output_parser.as_ref().or(workflow.output.as_ref());
+    let description = match parser {
+        None => "The workflow returns the result of each step.".to_string(),
+        Some(Value::String(_)) => "Parsed by inline JavaScript.".to_string(),
+        Some(Value::Object(definition)) => {
+            let mut text = match definition
+                .get("javascript_file_path")
+                .and_then(Value::as_str)
+            {
+                Some(path) => format!("Parsed by {}", code(path)),
+                None => "Parsed by inline JavaScript".to_string(),
+            };
+            if let Some(step_id) = definition
+                .get("ui_tree_source_step_id")
+                .and_then(Value::as_str)
+            {
+                let _ = write!(text, " from the UI tree of step {}", code(step_id));
+            }
+            text.push('.');
+            text
+        }
+        Some(_) => "Parsed by a custom output parser.".to_string(),
+    };
+    out.push_str(&description);
+    out.push('\n');
+}
+
+/// Wraps text in a code span, widening the fence when the text contains backticks
+fn code(text: &str) -> String {
+    let fence = "`".repeat(longest_backtick_run(text) + 1);
+    if text.starts_with('`') || text.ends_with('`') {
+        format!("{fence} {text} {fence}")
+    } else {
+        format!("{fence}{text}{fence}")
+    }
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+/// Escapes a value for use inside a table cell
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
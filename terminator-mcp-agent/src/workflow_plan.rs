@@ -0,0 +1,206 @@
+//! Static preview of what `execute_sequence` would do.
+//!
+//! [`plan`] resolves a workflow against its inputs without executing anything: every
+//! step's `if` condition is evaluated, its arguments and selectors are interpolated, and
+//! it is marked as one that would run, be skipped, or already be satisfied. Unlike a dry
+//! run, nothing is read from the desktop, so a reviewer can check a workflow before it
+//! touches the UI.
+//!
+//! The preview uses the variables as they stand before the first step: variable
+//! defaults overridden by inputs. Persistent variables from earlier runs are not
+//! loaded, and values produced while the workflow runs (`env.*`, `{step_id}_result`,
+//! `{step_id}_status`) do not exist yet. Steps whose condition reads such values are
+//! flagged with `runtime_dependent`, since their real outcome may differ.
+
+use crate::expression_eval::{self, IndexedContext};
+use crate::helpers::{evaluate_step_gate, substitute_variables, StepGate};
+use crate::utils::{ExecuteSequenceArgs, SequenceStep, ValidationError};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// Argument keys whose values are selectors
+const SELECTOR_KEYS: &[&str] = &["selector", "alternative_selectors", "fallback_selectors"];
+
+/// What would happen to a step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannedStatus {
+    Run,
+    /// The step's `if` condition is false
+    Skipped,
+    /// The step's `precondition` already holds
+    Satisfied,
+}
+
+/// One tool call of a step, with its arguments interpolated
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannedCall {
+    pub tool_name: String,
+    pub arguments: Value,
+    pub selectors: Vec<String>,
+}
+
+/// A step of the workflow as it would be executed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannedStep {
+    pub index: usize,
+    pub id: Option<String>,
+    pub group_name: Option<String>,
+    pub condition: Option<String>,
+    /// Value of `condition`, or `None` when the step has none
+    pub condition_result: Option<bool>,
+    pub status: PlannedStatus,
+    /// Whether the condition or precondition reads values only known at runtime
+    pub runtime_dependent: bool,
+    /// The tool call of a single step, or the calls of a group in order
+    pub calls: Vec<PlannedCall>,
+}
+
+/// The resolved preview of a workflow run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WorkflowPlan {
+    /// The variables the steps were resolved against
+    pub variables: Value,
+    pub steps: Vec<PlannedStep>,
+}
+
+impl WorkflowPlan {
+    /// Steps that would not run because their condition is false.
+    pub fn skipped(&self) -> impl Iterator<Item = &PlannedStep> {
+        self.steps
+            .iter()
+            .filter(|step| step.status == PlannedStatus::Skipped)
+    }
+}
+
+/// Resolves `workflow` against `inputs` (or the workflow's own `inputs` when `None`)
+/// without executing any step.
+pub fn plan(
+    workflow: &ExecuteSequenceArgs,
+    inputs: Option<&Value>,
+) -> Result<WorkflowPlan, ValidationError> {
+    let variables = initial_variables(workflow, inputs.or(workflow.inputs.as_ref()))?;
+    let step_ids: Vec<&str> = workflow
+        .steps
+        .iter()
+        .flatten()
+        .filter_map(|step| step.id.as_deref())
+        .collect();
+    let indexed = IndexedContext::new(&variables);
+
+    let steps = workflow
+        .steps
+        .iter()
+        .flatten()
+        .enumerate()
+        .map(|(index, step)| plan_step(index, step, &variables, &indexed, &step_ids))
+        .collect();
+    Ok(WorkflowPlan { variables, steps })
+}
+
+/// Builds the variables `execute_sequence` starts with: defaults, then inputs, then
+/// the workflow's selectors and an empty `env`.
+fn initial_variables(
+    workflow: &ExecuteSequenceArgs,
+    inputs: Option<&Value>,
+) -> Result<Value, ValidationError> {
+    let mut variables = Map::new();
+    for (name, definition) in workflow.variables.iter().flatten() {
+        if let Some(default) = &definition.default {
+            variables.insert(name.clone(), default.clone());
+        }
+    }
+
+    if let Some(inputs) = inputs {
+        crate::utils::validate_inputs(inputs)?;
+        if let Some(inputs) = inputs.as_object() {
+            variables.extend(inputs.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+    }
+
+    for (name, definition) in workflow.variables.iter().flatten() {
+        if definition.required.unwrap_or(true) && !variables.contains_key(name) {
+            return Err(ValidationError::new(name, "a value", "missing"));
+        }
+    }
+
+    if let Some(selectors) = &workflow.selectors {
+        crate::utils::validate_selectors(selectors)?;
+        let selectors = match selectors {
+            Value::String(s) => serde_json::from_str(s).unwrap_or_else(|_| selectors.clone()),
+            _ => selectors.clone(),
+        };
+        variables.insert("selectors".to_string(), selectors);
+    }
+    variables.insert("env".to_string(), json!({}));
+    Ok(Value::Object(variables))
+}
+
+fn plan_step(
+    index: usize,
+    step: &SequenceStep,
+    variables: &Value,
+    indexed: &IndexedContext,
+    step_ids: &[&str],
+) -> PlannedStep {
+    let condition = step.r#if.clone();
+    let condition_result = condition
+        .as_deref()
+        .map(|cond| cond.trim() == "always()" || expression_eval::evaluate_indexed(cond, indexed));
+    let status =
+        match evaluate_step_gate(condition.as_deref(), step.precondition.as_deref(), indexed) {
+            StepGate::Run => PlannedStatus::Run,
+            StepGate::ConditionNotMet => PlannedStatus::Skipped,
+            StepGate::AlreadySatisfied => PlannedStatus::Satisfied,
+        };
+    let runtime_dependent = [condition.as_deref(), step.precondition.as_deref()]
+        .into_iter()
+        .flatten()
+        .any(|expr| reads_runtime_values(expr, step_ids));
+
+    let calls = match (&step.tool_name, &step.steps) {
+        (Some(tool_name), _) => vec![plan_call(
+            tool_name,
+            step.arguments.clone().unwrap_or_else(|| json!({})),
+            variables,
+        )],
+        (None, Some(group)) => group
+            .iter()
+            .map(|call| plan_call(&call.tool_name, call.arguments.clone(), variables))
+            .collect(),
+        (None, None) => Vec::new(),
+    };
+
+    PlannedStep {
+        index,
+        id: step.id.clone(),
+        group_name: step.group_name.clone(),
+        condition,
+        condition_result,
+        status,
+        runtime_dependent,
+        calls,
+    }
+}
+
+fn plan_call(tool_name: &str, mut arguments: Value, variables: &Value) -> PlannedCall {
+    substitute_variables(&mut arguments, variables);
+    let selectors = SELECTOR_KEYS
+        .iter()
+        .filter_map(|key| arguments.get(key).and_then(Value::as_str))
+        .map(str::to_string)
+        .collect();
+    PlannedCall {
+        tool_name: tool_name.to_string(),
+        arguments,
+        selectors,
+    }
+}
+
+/// Whether `expr` reads `env.*` or a step's `_result`/`_status` variable.
+fn reads_runtime_values(expr: &str, step_ids: &[&str]) -> bool {
+    expr.contains("env.")
+        || step_ids.iter().any(|id| {
+            expr.contains(&format!("{id}_result")) || expr.contains(&format!("{id}_status"))
+        })
+}
@@ -0,0 +1,121 @@
+use std::sync::Mutex;
+use terminator_mcp_agent::action_journal::{
+    undo_entries, ActionJournal, Inverse, JournalEntry, UndoBackend,
+};
+
+/// A fake desktop with one checkbox, recording the inverses applied to it
+#[derive(Default)]
+struct MockDesktop {
+    checkbox: Mutex<bool>,
+    applied: Mutex<Vec<Inverse>>,
+}
+
+impl MockDesktop {
+    /// Toggles the checkbox the way the set_toggled tool does, journaling the old state
+    fn set_toggled(&self, journal: &mut ActionJournal, state: bool) {
+        let previous = std::mem::replace(&mut *self.checkbox.lock().unwrap(), state);
+        journal.record(JournalEntry::reversible(
+            "set_toggled",
+            Some("role:CheckBox|name:Bold"),
+            Inverse::SetToggled {
+                selector: "role:CheckBox|name:Bold".to_string(),
+                state: previous,
+            },
+        ));
+    }
+}
+
+impl UndoBackend for MockDesktop {
+    async fn apply(&self, inverse: &Inverse) -> Result<(), String> {
+        self.applied.lock().unwrap().push(inverse.clone());
+        match inverse {
+            Inverse::SetToggled { state, .. } => {
+                *self.checkbox.lock().unwrap() = *state;
+                Ok(())
+            }
+            Inverse::AppUndo { .. } => Err("application does not support undo".to_string()),
+            other => Err(format!("unexpected inverse {other:?}")),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_undo_restores_toggle() {
+    let desktop = MockDesktop::default();
+    let mut journal = ActionJournal::default();
+
+    desktop.set_toggled(&mut journal, true);
+    assert!(*desktop.checkbox.lock().unwrap());
+
+    let report = undo_entries(journal.take_last(1), &desktop).await;
+
+    assert!(!*desktop.checkbox.lock().unwrap());
+    assert_eq!(report.undone.len(), 1);
+    assert_eq!(report.undone[0].tool_name, "set_toggled");
+    assert!(report.skipped.is_empty() && report.failed.is_empty());
+    assert!(journal.is_empty());
+}
+
+#[tokio::test]
+async fn test_undo_replays_in_reverse_and_skips_irreversible() {
+    let desktop = MockDesktop::default();
+    let mut journal = ActionJournal::default();
+
+    desktop.set_toggled(&mut journal, true);
+    journal.record(JournalEntry::irreversible(
+        "click_element",
+        Some("name:Apply"),
+    ));
+    desktop.set_toggled(&mut journal, false);
+
+    let report = undo_entries(journal.take_last(5), &desktop).await;
+
+    // Newest first: false -> true, then the click is skipped, then true -> false
+    assert_eq!(
+        *desktop.applied.lock().unwrap(),
+        vec![
+            Inverse::SetToggled {
+                selector: "role:CheckBox|name:Bold".to_string(),
+                state: true
+            },
+            Inverse::SetToggled {
+                selector: "role:CheckBox|name:Bold".to_string(),
+                state: false
+            },
+        ]
+    );
+    assert!(!*desktop.checkbox.lock().unwrap());
+    assert_eq!(report.undone.len(), 2);
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].tool_name, "click_element");
+}
+
+#[tokio::test]
+async fn test_failed_inverse_is_reported() {
+    let desktop = MockDesktop::default();
+    let mut journal = ActionJournal::default();
+    journal.record(JournalEntry::reversible(
+        "type_into_focused",
+        None,
+        Inverse::AppUndo { selector: None },
+    ));
+
+    let report = undo_entries(journal.take_last(1), &desktop).await;
+    assert_eq!(report.failed.len(), 1);
+    assert!(report.failed[0].error.contains("does not support undo"));
+}
+
+#[test]
+fn test_journal_is_bounded() {
+    let mut journal = ActionJournal::new(2);
+    for name in ["a", "b", "c"] {
+        journal.record(JournalEntry::irreversible(name, None));
+    }
+    assert_eq!(journal.len(), 2);
+    let names: Vec<String> = journal
+        .take_last(10)
+        .into_iter()
+        .map(|e| e.tool_name)
+        .collect();
+    assert_eq!(names, vec!["c", "b"]);
+}
@@ -0,0 +1,80 @@
+use terminator_mcp_agent::capabilities::{
+    enabled_features, Capabilities, FEATURE_SELECTOR_TYPES, OCR_AVAILABLE, SELECTOR_TYPES,
+    TRANSPORTS,
+};
+
+#[test]
+fn test_capabilities_reflect_build_features() {
+    let capabilities = Capabilities::current(vec![]);
+
+    assert_eq!(capabilities.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(
+        capabilities.features.contains(&"telemetry"),
+        cfg!(feature = "telemetry")
+    );
+    assert_eq!(capabilities.features, enabled_features());
+    assert_eq!(
+        capabilities.ocr_available,
+        terminator::ocr::OCR_ENABLED
+            && cfg!(any(
+                target_os = "windows",
+                target_os = "macos",
+                target_os = "linux"
+            ))
+    );
+    assert_eq!(capabilities.ocr_available, OCR_AVAILABLE);
+}
+
+#[test]
+fn test_capabilities_list_transports_selectors_and_tools() {
+    let capabilities = Capabilities::current(vec![
+        "type_into_element".to_string(),
+        "click_element".to_string(),
+        "capabilities".to_string(),
+    ]);
+
+    assert_eq!(capabilities.transports, TRANSPORTS);
+    assert!(capabilities.transports.contains(&"stdio"));
+    assert_eq!(capabilities.compression, vec!["gzip"]);
+    assert_eq!(capabilities.selector_types, SELECTOR_TYPES);
+    assert!(capabilities.selector_types.contains(&"role"));
+    assert_eq!(
+        capabilities.tools,
+        vec!["capabilities", "click_element", "type_into_element"]
+    );
+
+    let json = serde_json::to_value(&capabilities).unwrap();
+    for key in [
+        "version",
+        "features",
+        "ocr_available",
+        "transports",
+        "compression",
+        "selector_types",
+        "unavailable_selector_types",
+        "tools",
+    ] {
+        assert!(json.get(key).is_some(), "missing '{key}'");
+    }
+}
+
+#[test]
+fn test_capabilities_report_feature_selectors() {
+    let capabilities = Capabilities::current(vec![]);
+
+    // No image matching backend is compiled in, and ocr: selectors are not evaluated yet
+    assert_eq!(
+        capabilities.unavailable_selector_types.get("image"),
+        Some(&terminator::selector::IMAGE_NOT_ENABLED.to_string())
+    );
+    let ocr_reason = capabilities.unavailable_selector_types.get("ocr").unwrap();
+    if terminator::ocr::OCR_ENABLED {
+        assert_ne!(ocr_reason, terminator::ocr::OCR_NOT_ENABLED);
+    } else {
+        assert_eq!(ocr_reason, terminator::ocr::OCR_NOT_ENABLED);
+    }
+    for kind in capabilities.unavailable_selector_types.keys() {
+        assert!(FEATURE_SELECTOR_TYPES.contains(kind));
+        assert!(!capabilities.selector_types.contains(kind));
+    }
+}
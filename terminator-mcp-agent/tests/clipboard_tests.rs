@@ -0,0 +1,117 @@
+use arboard::Error as ArboardError;
+use terminator_mcp_agent::clipboard::{read_text, write_text, ClipboardAccess, ClipboardError};
+
+/// A clipboard that holds `text`, or fails reads with `error`
+#[derive(Default)]
+struct MockClipboard {
+    text: String,
+    error: Option<fn() -> ArboardError>,
+    image: bool,
+    occupied: bool,
+}
+
+impl ClipboardAccess for MockClipboard {
+    fn get_text(&mut self) -> Result<String, ArboardError> {
+        match self.error {
+            Some(error) => Err(error()),
+            None => Ok(self.text.clone()),
+        }
+    }
+
+    fn has_image(&mut self) -> bool {
+        self.image
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), ArboardError> {
+        if self.occupied {
+            return Err(ArboardError::ClipboardOccupied);
+        }
+        self.text = text.to_string();
+        self.error = None;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_text_round_trips_through_the_clipboard() {
+    let mut clipboard = MockClipboard::default();
+    write_text(&mut clipboard, "Grüße, 世界 👋").unwrap();
+    assert_eq!(read_text(&mut clipboard).unwrap(), "Grüße, 世界 👋");
+}
+
+#[test]
+fn test_empty_clipboard_is_a_typed_error() {
+    let mut clipboard = MockClipboard::default();
+    assert_eq!(read_text(&mut clipboard), Err(ClipboardError::Empty));
+
+    // arboard reports a clipboard with nothing readable as ContentNotAvailable
+    let mut clipboard = MockClipboard {
+        error: Some(|| ArboardError::ContentNotAvailable),
+        ..Default::default()
+    };
+    let error = read_text(&mut clipboard).unwrap_err();
+    assert_eq!(error, ClipboardError::Empty);
+    assert_eq!(error.kind(), "clipboard_empty");
+}
+
+#[test]
+fn test_non_text_content_is_a_typed_error() {
+    let mut clipboard = MockClipboard {
+        error: Some(|| ArboardError::ContentNotAvailable),
+        image: true,
+        ..Default::default()
+    };
+    let error = read_text(&mut clipboard).unwrap_err();
+    assert_eq!(error.kind(), "clipboard_not_text");
+    assert_eq!(error.to_string(), "The clipboard holds an image, not text");
+
+    let mut clipboard = MockClipboard {
+        error: Some(|| ArboardError::ConversionFailure),
+        ..Default::default()
+    };
+    let error = read_text(&mut clipboard).unwrap_err();
+    assert_eq!(error.kind(), "clipboard_not_text");
+    assert!(error.to_string().contains("not valid UTF-8"));
+}
+
+#[test]
+fn test_clipboard_access_failures_are_unavailable() {
+    let mut clipboard = MockClipboard {
+        error: Some(|| ArboardError::ClipboardNotSupported),
+        ..Default::default()
+    };
+    let error = read_text(&mut clipboard).unwrap_err();
+    assert!(matches!(error, ClipboardError::Unavailable(_)));
+    assert_eq!(error.kind(), "clipboard_unavailable");
+
+    let mut clipboard = MockClipboard {
+        occupied: true,
+        ..Default::default()
+    };
+    let error = write_text(&mut clipboard, "hello").unwrap_err();
+    assert_eq!(error.kind(), "clipboard_unavailable");
+}
+
+/// Round trip through the real clipboard, which always exists on Windows and macOS
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+#[test]
+fn test_system_clipboard_round_trip() {
+    let mut clipboard = terminator_mcp_agent::clipboard::system_clipboard().unwrap();
+    write_text(&mut clipboard, "terminator clipboard test ✓").unwrap();
+    assert_eq!(
+        read_text(&mut clipboard).unwrap(),
+        "terminator clipboard test ✓"
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+#[ignore = "Requires an X11 or Wayland session"]
+fn test_system_clipboard_round_trip_linux() {
+    let mut clipboard = terminator_mcp_agent::clipboard::system_clipboard().unwrap();
+    write_text(&mut clipboard, "terminator clipboard test ✓").unwrap();
+    assert_eq!(
+        read_text(&mut clipboard).unwrap(),
+        "terminator clipboard test ✓"
+    );
+}
@@ -0,0 +1,106 @@
+use base64::{engine::general_purpose, Engine as _};
+use rmcp::model::{CallToolResult, Content, JsonObject, RawContent};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use terminator_mcp_agent::compression::{
+    compress_result, compress_text, decompress_text, negotiate, server_capability,
+    CompressionSettings, DEFAULT_COMPRESSION_THRESHOLD,
+};
+
+fn experimental(capability: Value) -> BTreeMap<String, JsonObject> {
+    let Value::Object(capability) = capability else {
+        panic!("capability must be an object");
+    };
+    BTreeMap::from([("compression".to_string(), capability)])
+}
+
+/// A UI tree large enough to cross the default threshold
+fn large_tree() -> Value {
+    let children: Vec<Value> = (0..5000)
+        .map(|i| {
+            json!({
+                "role": "ListItem",
+                "name": format!("Row {i}"),
+                "automation_id": format!("row-{i}"),
+                "states": {"enabled": true, "focused": false},
+            })
+        })
+        .collect();
+    json!({"action": "get_window_tree", "status": "success", "ui_tree": {"role": "List", "children": children}})
+}
+
+fn text_of(content: &Content) -> &str {
+    match &content.raw {
+        RawContent::Text(text) => &text.text,
+        _ => panic!("expected text content"),
+    }
+}
+
+#[test]
+fn test_negotiation_requires_gzip_opt_in() {
+    assert_eq!(negotiate(None), None);
+    assert_eq!(negotiate(Some(&BTreeMap::new())), None);
+    assert_eq!(
+        negotiate(Some(&experimental(json!({"encodings": ["br"]})))),
+        None
+    );
+    assert_eq!(
+        negotiate(Some(&experimental(json!({"encodings": ["gzip"]})))),
+        Some(CompressionSettings {
+            threshold: DEFAULT_COMPRESSION_THRESHOLD
+        })
+    );
+    assert_eq!(
+        negotiate(Some(&experimental(
+            json!({"encodings": ["br", "gzip"], "threshold": 1024})
+        ))),
+        Some(CompressionSettings { threshold: 1024 })
+    );
+
+    let (name, advertised) = server_capability();
+    assert_eq!(name, "compression");
+    assert_eq!(advertised["encodings"], json!(["gzip"]));
+}
+
+#[test]
+fn test_large_response_round_trips_when_compression_is_enabled() {
+    let tree = large_tree();
+    let original = tree.to_string();
+    assert!(original.len() > DEFAULT_COMPRESSION_THRESHOLD);
+
+    let settings = negotiate(Some(&experimental(json!({"encodings": ["gzip"]})))).unwrap();
+    let mut result = CallToolResult::success(vec![Content::json(tree.clone()).unwrap()]);
+    compress_result(&mut result, &settings);
+
+    let sent = text_of(&result.content[0]);
+    assert!(sent.len() < original.len() / 4, "sent {} bytes", sent.len());
+    let envelope: Value = serde_json::from_str(sent).unwrap();
+    assert_eq!(envelope["content_encoding"], "gzip");
+
+    let restored = decompress_text(sent).unwrap().unwrap();
+    assert_eq!(serde_json::from_str::<Value>(&restored).unwrap(), tree);
+}
+
+#[test]
+fn test_small_and_incompressible_text_is_left_alone() {
+    let settings = CompressionSettings { threshold: 1024 };
+    let mut result =
+        CallToolResult::success(vec![Content::json(json!({"status": "success"})).unwrap()]);
+    let before = text_of(&result.content[0]).to_string();
+    compress_result(&mut result, &settings);
+    assert_eq!(text_of(&result.content[0]), before);
+    assert!(decompress_text(&before).is_none());
+
+    // Base64 of random bytes does not shrink once re-encoded, so it is sent as is
+    let mut state = 0x9E37_79B9_7F4A_7C15u64;
+    let bytes: Vec<u8> = (0..3072)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect();
+    let noise = general_purpose::STANDARD.encode(bytes);
+    assert!(compress_text(&noise, &CompressionSettings { threshold: 0 }).is_none());
+}
@@ -0,0 +1,189 @@
+use std::cell::RefCell;
+use std::time::Duration;
+use terminator::{AutomationError, Monitor};
+use terminator_mcp_agent::drag_drop::{
+    drag, drag_path, DragEndpoint, DragError, DragOptions, InputSink, Point,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Event {
+    Press(f64, f64),
+    Move(f64, f64),
+    Release,
+}
+
+/// An input sink that records the gesture, optionally failing one move
+#[derive(Default)]
+struct RecordingSink {
+    events: RefCell<Vec<Event>>,
+    fail_move: Option<usize>,
+}
+
+impl InputSink for RecordingSink {
+    fn press(&self, x: f64, y: f64) -> Result<(), AutomationError> {
+        self.events.borrow_mut().push(Event::Press(x, y));
+        Ok(())
+    }
+
+    fn move_to(&self, x: f64, y: f64) -> Result<(), AutomationError> {
+        let moves = self
+            .events
+            .borrow()
+            .iter()
+            .filter(|e| matches!(e, Event::Move(..)))
+            .count();
+        if self.fail_move == Some(moves + 1) {
+            return Err(AutomationError::PlatformError(
+                "SendInput failed".to_string(),
+            ));
+        }
+        self.events.borrow_mut().push(Event::Move(x, y));
+        Ok(())
+    }
+
+    fn release(&self) -> Result<(), AutomationError> {
+        self.events.borrow_mut().push(Event::Release);
+        Ok(())
+    }
+}
+
+fn options(steps: u32) -> DragOptions {
+    DragOptions {
+        steps,
+        step_delay: Duration::ZERO,
+    }
+}
+
+#[tokio::test]
+async fn test_drag_presses_moves_in_steps_and_releases() {
+    let sink = RecordingSink::default();
+    let from = Point { x: 100.0, y: 200.0 };
+    let to = Point { x: 500.0, y: 600.0 };
+
+    drag(&sink, from, to, &options(4)).await.unwrap();
+
+    assert_eq!(
+        sink.events.into_inner(),
+        vec![
+            Event::Press(100.0, 200.0),
+            Event::Move(200.0, 300.0),
+            Event::Move(300.0, 400.0),
+            Event::Move(400.0, 500.0),
+            Event::Move(500.0, 600.0),
+            Event::Release,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_zero_steps_still_moves_onto_the_target() {
+    let sink = RecordingSink::default();
+    let to = Point { x: 30.0, y: 40.0 };
+
+    drag(&sink, Point { x: 0.0, y: 0.0 }, to, &options(0))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        sink.events.into_inner(),
+        vec![
+            Event::Press(0.0, 0.0),
+            Event::Move(30.0, 40.0),
+            Event::Release
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_failed_move_releases_the_button() {
+    let sink = RecordingSink {
+        fail_move: Some(3),
+        ..Default::default()
+    };
+
+    let error = drag(
+        &sink,
+        Point { x: 0.0, y: 0.0 },
+        Point { x: 100.0, y: 0.0 },
+        &options(5),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(
+        error,
+        DragError::MoveFailed {
+            step: 3,
+            steps: 5,
+            ..
+        }
+    ));
+    assert_eq!(error.kind(), "drag_move_failed");
+    assert!(error.to_string().contains("step 3 of 5"), "{error}");
+    // The button is not left held down
+    let events = sink.events.into_inner();
+    assert_eq!(events.last(), Some(&Event::Release));
+    assert_eq!(events.len(), 4);
+}
+
+#[test]
+fn test_drag_path_ends_on_the_target() {
+    let path = drag_path(Point { x: 0.0, y: 0.0 }, Point { x: 10.0, y: -7.0 }, 3);
+    assert_eq!(path.len(), 3);
+    assert_eq!(path.last(), Some(&Point { x: 10.0, y: -7.0 }));
+}
+
+#[test]
+fn test_endpoints_take_a_selector_or_coordinates() {
+    assert_eq!(
+        DragEndpoint::from_args("source", Some("name:Item 1"), None, None),
+        Ok(DragEndpoint::Selector("name:Item 1"))
+    );
+    assert_eq!(
+        DragEndpoint::from_args("target", None, Some(5.0), Some(6.0)),
+        Ok(DragEndpoint::Point(Point { x: 5.0, y: 6.0 }))
+    );
+
+    let error = DragEndpoint::from_args("source", None, None, None).unwrap_err();
+    assert!(error.contains("source_selector"), "{error}");
+    assert!(DragEndpoint::from_args("target", Some("name:List"), Some(1.0), Some(2.0)).is_err());
+    assert!(DragEndpoint::from_args("target", None, Some(1.0), None).is_err());
+}
+
+#[test]
+fn test_selector_endpoints_use_the_element_center() {
+    assert_eq!(
+        Point::center_of((100.0, 50.0, 40.0, 20.0)),
+        Point { x: 120.0, y: 60.0 }
+    );
+}
+
+#[test]
+fn test_monitor_relative_endpoints_are_moved_onto_the_monitor() {
+    let monitor = Monitor {
+        id: "2".to_string(),
+        name: "Virtual display 2".to_string(),
+        is_primary: false,
+        width: 1280,
+        height: 1024,
+        x: 1920,
+        y: 0,
+        scale_factor: 1.0,
+        work_area: None,
+    };
+
+    let point = DragEndpoint::Point(Point { x: 100.0, y: 200.0 });
+    assert_eq!(
+        point.on_monitor(&monitor, 1).unwrap(),
+        DragEndpoint::Point(Point {
+            x: 2020.0,
+            y: 200.0
+        })
+    );
+    // Selectors are not coordinates and are left alone
+    let selector = DragEndpoint::Selector("name:Inbox");
+    assert_eq!(selector.on_monitor(&monitor, 1).unwrap(), selector);
+
+    let outside = DragEndpoint::Point(Point { x: 1500.0, y: 0.0 });
+    assert!(outside.on_monitor(&monitor, 1).is_err());
+}
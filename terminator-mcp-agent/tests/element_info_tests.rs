@@ -0,0 +1,67 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use terminator::{ElementInfo, UIElementAttributes, UINode};
+use terminator_mcp_agent::helpers::element_info_with_extras;
+
+fn checkbox_node() -> UINode {
+    let mut properties = HashMap::new();
+    properties.insert("AutomationId".to_string(), Some(json!("rememberMe")));
+    UINode {
+        id: Some("1001".to_string()),
+        attributes: UIElementAttributes {
+            role: "CheckBox".to_string(),
+            name: Some("Remember me".to_string()),
+            properties,
+            bounds: Some((5.0, 6.0, 120.0, 18.0)),
+            enabled: Some(true),
+            is_toggled: Some(true),
+            ..Default::default()
+        },
+        children: vec![],
+    }
+}
+
+#[test]
+fn test_tool_result_embeds_element_info_unchanged() {
+    let info = checkbox_node().info();
+    let result = element_info_with_extras(
+        &info,
+        json!({
+            "id": "1001",
+            "suggested_selector": "CheckBox|Remember me",
+            "process_id": 4242,
+        }),
+    );
+
+    // Every ElementInfo field appears in the tool result with the same value...
+    let shared = serde_json::to_value(&info).unwrap();
+    for (key, value) in shared.as_object().unwrap() {
+        assert_eq!(&result[key], value, "field '{key}' differs");
+    }
+    // ...so the tool result parses back into the same ElementInfo as the tree node
+    let parsed: ElementInfo = serde_json::from_value(result.clone()).unwrap();
+    assert_eq!(parsed, info);
+
+    assert_eq!(result["suggested_selector"], "CheckBox|Remember me");
+    assert_eq!(result["states"]["toggled"], true);
+    assert_eq!(result["automation_id"], "rememberMe");
+}
+
+#[test]
+fn test_extras_cannot_change_the_shared_shape() {
+    let info = checkbox_node().info();
+    let result = element_info_with_extras(
+        &info,
+        json!({"role": "Overridden", "bounds": "n/a", "text": ""}),
+    );
+    assert_eq!(result["role"], "CheckBox");
+    assert_eq!(result["bounds"]["width"], 120.0);
+    assert_eq!(result["text"], "");
+}
+
+#[test]
+fn test_non_object_extras_are_ignored() {
+    let info = checkbox_node().info();
+    let result = element_info_with_extras(&info, Value::Null);
+    assert_eq!(result, serde_json::to_value(&info).unwrap());
+}
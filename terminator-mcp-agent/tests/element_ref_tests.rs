@@ -0,0 +1,93 @@
+use serde_json::{json, Value};
+use terminator::AutomationError;
+use terminator_mcp_agent::element_ref::{
+    apply_selector, captured_element, resolve, CapturedElement, ElementRefError,
+};
+
+/// The execution context after a `find_by_identity` step with id `row`
+fn context() -> Value {
+    json!({
+        "env": {
+            "row_result": [{
+                "action": "find_by_identity",
+                "status": "success",
+                "element": {
+                    "role": "ListItem",
+                    "name": "Order 42",
+                    "id": "1111",
+                    "identity_key": "aid:ListItem:o-42",
+                    "process_id": 4242
+                },
+                "scope": {"role": "List", "identity_key": "aid:List:orders", "process_id": 4242}
+            }],
+            "log_result": [{"type": "text", "text": "done"}]
+        }
+    })
+}
+
+#[test]
+fn test_captured_element_is_read_from_step_result() {
+    let captured = captured_element(&context(), "row").unwrap();
+    assert_eq!(
+        captured,
+        CapturedElement {
+            identity_key: "aid:ListItem:o-42".to_string(),
+            process_id: 4242,
+        }
+    );
+}
+
+#[test]
+fn test_step_acts_on_captured_element() {
+    // The row re-rendered and now has a new handle id
+    let selector = resolve(&context(), "row", |captured| {
+        assert_eq!(captured.identity_key, "aid:ListItem:o-42");
+        Ok("2222".to_string())
+    })
+    .unwrap();
+    assert_eq!(selector, "#2222");
+
+    let mut arguments = json!({
+        "selector": "role:ListItem|name:Order",
+        "alternative_selectors": "role:ListItem",
+        "fallback_selectors": "role:DataItem",
+        "click_position": {"x_percentage": 10, "y_percentage": 50}
+    });
+    apply_selector(&mut arguments, &selector);
+    assert_eq!(
+        arguments,
+        json!({
+            "selector": "#2222",
+            "click_position": {"x_percentage": 10, "y_percentage": 50}
+        })
+    );
+}
+
+#[test]
+fn test_stale_ref_errors_clearly() {
+    let err = resolve(&context(), "row", |_| {
+        Err(AutomationError::ElementNotFound(
+            "No element with identity 'aid:ListItem:o-42' within 50 levels".to_string(),
+        ))
+    })
+    .unwrap_err();
+    assert!(matches!(err, ElementRefError::Stale { .. }));
+
+    let message = err.to_string();
+    assert!(message.contains("element_ref 'row' is stale"), "{message}");
+    assert!(message.contains("aid:ListItem:o-42"), "{message}");
+}
+
+#[test]
+fn test_unknown_step_and_missing_element() {
+    let never_called =
+        |_: &CapturedElement| -> Result<String, AutomationError> { panic!("nothing to look up") };
+    assert_eq!(
+        resolve(&context(), "missing", never_called),
+        Err(ElementRefError::UnknownStep("missing".to_string()))
+    );
+    assert_eq!(
+        resolve(&context(), "log", never_called),
+        Err(ElementRefError::NoElement("log".to_string()))
+    );
+}
@@ -0,0 +1,446 @@
+use futures::StreamExt;
+use serde_json::json;
+use std::time::Duration;
+use terminator::ErrorCategory;
+use terminator_mcp_agent::helpers::{env_updates_from_result, merge_step_outputs};
+use terminator_mcp_agent::workflow_events::{
+    truncate_value, EventRuns, ExecutionEvent, ExecutionEventEmitter, SequencedEvent, StepOutcome,
+    MAX_VARIABLE_PREVIEW_CHARS,
+};
+
+fn modal(n: u64) -> ExecutionEvent {
+    ExecutionEvent::ModalDismissed {
+        title: format!("Dialog {n}"),
+        button: "OK".to_string(),
+        timestamp: "2024-01-01T00:00:00Z".to_string(),
+    }
+}
+
+fn seqs(events: &[SequencedEvent]) -> Vec<u64> {
+    events.iter().map(|e| e.seq).collect()
+}
+
+#[test]
+fn test_events_are_numbered_from_one() {
+    let emitter = ExecutionEventEmitter::new();
+    for n in 1..=3 {
+        emitter.emit(modal(n));
+    }
+
+    let all = emitter.since(0);
+    assert_eq!(seqs(&all.events), vec![1, 2, 3]);
+    assert_eq!(all.latest_seq, 3);
+    assert!(!all.truncated);
+    assert_eq!(all.events[1].event, modal(2));
+
+    assert_eq!(seqs(&emitter.since(2).events), vec![3]);
+    assert!(emitter.since(3).events.is_empty());
+}
+
+#[test]
+fn test_buffer_is_bounded() {
+    let emitter = ExecutionEventEmitter::with_capacity(3);
+    for n in 1..=5 {
+        emitter.emit(modal(n));
+    }
+
+    let all = emitter.since(0);
+    assert_eq!(seqs(&all.events), vec![3, 4, 5]);
+    assert_eq!(all.latest_seq, 5);
+    assert!(all.truncated, "events 1 and 2 were evicted");
+
+    // A cursor inside the buffer has lost nothing
+    assert!(!emitter.since(2).truncated);
+    assert_eq!(emitter.events().len(), 3);
+}
+
+#[test]
+fn test_sequenced_event_serialization() {
+    let emitter = ExecutionEventEmitter::new();
+    emitter.emit(modal(1));
+
+    let json = serde_json::to_value(&emitter.since(0).events[0]).unwrap();
+    assert_eq!(json["seq"], 1);
+    assert_eq!(json["type"], "modal_dismissed");
+    assert_eq!(json["title"], "Dialog 1");
+}
+
+#[test]
+fn test_step_failed_event_serialization() {
+    let emitter = ExecutionEventEmitter::new();
+    emitter.emit(ExecutionEvent::StepFailed {
+        step_id: Some("submit".to_string()),
+        tool_name: "click_element".to_string(),
+        error: "Element not found".to_string(),
+        category: Some(ErrorCategory::User),
+        timestamp: "2024-01-01T00:00:00Z".to_string(),
+    });
+
+    let json = serde_json::to_value(&emitter.since(0).events[0]).unwrap();
+    assert_eq!(json["type"], "step_failed");
+    assert_eq!(json["step_id"], "submit");
+    assert_eq!(json["category"], "user");
+}
+
+#[test]
+fn test_step_started_and_finished_fire_in_order() {
+    let emitter = ExecutionEventEmitter::new();
+
+    let timer = emitter.step_started(2, Some("submit"), Some("click_element"), None);
+    std::thread::sleep(Duration::from_millis(20));
+    timer.finish(StepOutcome::Success);
+    emitter
+        .step_started(3, None, None, Some("login"))
+        .finish(StepOutcome::Skipped);
+
+    let events = emitter.events();
+    assert_eq!(events.len(), 4);
+    let ExecutionEvent::StepStarted {
+        step_index,
+        ref step_id,
+        monotonic_ms: started_at,
+        ..
+    } = events[0]
+    else {
+        panic!("expected StepStarted first, got {:?}", events[0]);
+    };
+    assert_eq!(step_index, 2);
+    assert_eq!(step_id.as_deref(), Some("submit"));
+    let ExecutionEvent::StepFinished {
+        step_index,
+        outcome,
+        duration_ms,
+        monotonic_ms: finished_at,
+        ..
+    } = events[1]
+    else {
+        panic!("expected StepFinished second, got {:?}", events[1]);
+    };
+    assert_eq!(step_index, 2);
+    assert_eq!(outcome, StepOutcome::Success);
+    assert!(duration_ms >= 20.0, "measured {duration_ms}ms");
+    assert!(finished_at - started_at >= duration_ms - 1.0);
+
+    // A skipped step still gets its pair, with a non-negative duration
+    assert!(matches!(
+        events[2],
+        ExecutionEvent::StepStarted { step_index: 3, .. }
+    ));
+    let ExecutionEvent::StepFinished {
+        outcome,
+        duration_ms,
+        ref group_name,
+        ..
+    } = events[3]
+    else {
+        panic!("expected StepFinished last, got {:?}", events[3]);
+    };
+    assert_eq!(outcome, StepOutcome::Skipped);
+    assert!(duration_ms >= 0.0);
+    assert_eq!(group_name.as_deref(), Some("login"));
+}
+
+#[test]
+fn test_step_finished_event_serialization() {
+    let emitter = ExecutionEventEmitter::new();
+    emitter
+        .step_started(0, Some("open"), Some("open_application"), None)
+        .finish(StepOutcome::Failure);
+
+    let events = emitter.since(0).events;
+    let started = serde_json::to_value(&events[0]).unwrap();
+    assert_eq!(started["type"], "step_started");
+    assert_eq!(started["tool_name"], "open_application");
+    assert!(started["monotonic_ms"].is_f64());
+
+    let finished = serde_json::to_value(&events[1]).unwrap();
+    assert_eq!(finished["seq"], 2);
+    assert_eq!(finished["type"], "step_finished");
+    assert_eq!(finished["step_id"], "open");
+    assert_eq!(finished["outcome"], "failure");
+    assert!(finished["duration_ms"].as_f64().unwrap() >= 0.0);
+}
+
+#[test]
+fn test_script_step_outputs_emit_variable_changed() {
+    let emitter = ExecutionEventEmitter::new();
+    let result = json!({
+        "status": "success",
+        "result": {"content": [{"result": {
+            "total": 120,
+            "currency": "EUR",
+            "user": "ada",
+            "status": "success"
+        }}]}
+    });
+    let mut env = json!({"total": 99, "user": "ada"})
+        .as_object()
+        .cloned()
+        .unwrap();
+
+    // As the runner does after a successful script step
+    let changes = merge_step_outputs(
+        &mut env,
+        env_updates_from_result("run_command", &result),
+        None,
+    );
+    emitter.variables_changed(changes, Some("parse_invoice"));
+
+    let events = emitter.events();
+    // `user` was written with the value it already had, so it is not reported
+    assert_eq!(events.len(), 2);
+    let json: Vec<_> = events
+        .iter()
+        .map(|e| serde_json::to_value(e).unwrap())
+        .collect();
+    let total = json.iter().find(|e| e["key"] == "total").unwrap();
+    assert_eq!(total["type"], "variable_changed");
+    assert_eq!(total["old_value"], 99);
+    assert_eq!(total["new_value"], 120);
+    assert_eq!(total["step_id"], "parse_invoice");
+    let currency = json.iter().find(|e| e["key"] == "currency").unwrap();
+    assert!(currency["old_value"].is_null());
+    assert_eq!(currency["new_value"], "EUR");
+}
+
+#[test]
+fn test_namespaced_and_direct_variable_changes() {
+    let emitter = ExecutionEventEmitter::new();
+    let mut env = serde_json::Map::new();
+
+    let outputs = json!({"total": 120}).as_object().cloned().unwrap();
+    let changes = merge_step_outputs(&mut env, outputs, Some("parse_invoice"));
+    emitter.variables_changed(changes, Some("parse_invoice"));
+    emitter.set_variable(
+        &mut env,
+        "submit_status".to_string(),
+        json!("success"),
+        Some("submit"),
+    );
+    emitter.set_variable(
+        &mut env,
+        "submit_status".to_string(),
+        json!("success"),
+        Some("submit"),
+    );
+
+    let keys: Vec<String> = emitter
+        .events()
+        .into_iter()
+        .map(|event| match event {
+            ExecutionEvent::VariableChanged { key, .. } => key,
+            other => panic!("unexpected event {other:?}"),
+        })
+        .collect();
+    assert_eq!(keys, vec!["parse_invoice.total", "submit_status"]);
+    assert_eq!(env["submit_status"], "success");
+}
+
+#[test]
+fn test_large_variable_values_are_truncated() {
+    let small = json!({"rows": [1, 2, 3]});
+    assert_eq!(truncate_value(&small), small);
+
+    let large = json!("x".repeat(10_000));
+    let truncated = truncate_value(&large);
+    assert_eq!(truncated["truncated"], true);
+    assert_eq!(truncated["length"], 10_002, "the JSON includes the quotes");
+    assert_eq!(
+        truncated["preview"].as_str().unwrap().chars().count(),
+        MAX_VARIABLE_PREVIEW_CHARS
+    );
+
+    let emitter = ExecutionEventEmitter::new();
+    let mut env = serde_json::Map::new();
+    emitter.set_variable(&mut env, "page".to_string(), large.clone(), None);
+    let ExecutionEvent::VariableChanged { new_value, .. } = &emitter.events()[0] else {
+        panic!("expected VariableChanged");
+    };
+    assert_eq!(new_value["length"], 10_002);
+    // The env itself keeps the full value
+    assert_eq!(env["page"], large);
+}
+
+#[tokio::test]
+async fn test_subscriber_streams_a_workflow_run() {
+    let emitter = ExecutionEventEmitter::new();
+    let stream = emitter.subscribe();
+
+    // A tiny two-step workflow, as the runner reports it
+    let runner = emitter.clone();
+    tokio::spawn(async move {
+        let mut env = serde_json::Map::new();
+        let step = runner.step_started(0, Some("read"), Some("run_command"), None);
+        runner.set_variable(&mut env, "total".to_string(), json!(120), Some("read"));
+        step.finish(StepOutcome::Success);
+        runner
+            .step_started(1, Some("notify"), Some("click_element"), None)
+            .finish(StepOutcome::Skipped);
+    });
+    drop(emitter);
+
+    let events: Vec<SequencedEvent> =
+        tokio::time::timeout(Duration::from_secs(5), stream.collect())
+            .await
+            .expect("the stream ends once the run's emitters are dropped");
+
+    assert_eq!(seqs(&events), vec![1, 2, 3, 4, 5]);
+    let types: Vec<String> = events
+        .iter()
+        .map(|e| {
+            serde_json::to_value(e).unwrap()["type"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            "step_started",
+            "variable_changed",
+            "step_finished",
+            "step_started",
+            "step_finished"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_stream_ends_after_run_finished_while_the_run_stays_registered() {
+    let runs = EventRuns::with_capacity(2);
+    let emitter = ExecutionEventEmitter::new();
+    // The registry keeps a clone alive, so only RunFinished can end the stream
+    runs.register("run-1", emitter.clone());
+    let stream = emitter.subscribe();
+
+    let run = emitter.run_started();
+    emitter
+        .step_started(0, Some("read"), Some("run_command"), None)
+        .finish(StepOutcome::Success);
+    run.finish("success");
+
+    let events: Vec<SequencedEvent> =
+        tokio::time::timeout(Duration::from_secs(5), stream.collect())
+            .await
+            .expect("the stream ends with the run");
+    assert_eq!(seqs(&events), vec![1, 2, 3]);
+    let ExecutionEvent::RunFinished { status, .. } = &events[2].event else {
+        panic!("expected RunFinished, got {:?}", events[2].event);
+    };
+    assert_eq!(status, "success");
+
+    // Subscribing to a finished run ends right away; its events stay in the buffer
+    let (_, registered) = runs.get(Some("run-1")).unwrap();
+    let late: Vec<SequencedEvent> =
+        tokio::time::timeout(Duration::from_secs(5), registered.subscribe().collect())
+            .await
+            .expect("a finished run's stream ends immediately");
+    assert!(late.is_empty());
+    assert_eq!(registered.since(0).latest_seq, 3);
+}
+
+#[tokio::test]
+async fn test_run_dropped_without_finishing_ends_as_error() {
+    let emitter = ExecutionEventEmitter::new();
+    let stream = emitter.subscribe();
+    {
+        // An early return, e.g. on cancellation
+        let _run = emitter.run_started();
+    }
+
+    let events: Vec<SequencedEvent> =
+        tokio::time::timeout(Duration::from_secs(5), stream.collect())
+            .await
+            .expect("the stream ends with the run");
+    assert_eq!(events.len(), 1);
+    let ExecutionEvent::RunFinished { status, .. } = &events[0].event else {
+        panic!("expected RunFinished");
+    };
+    assert_eq!(status, "error");
+}
+
+#[tokio::test]
+async fn test_lagging_subscriber_skips_ahead_without_stalling_the_run() {
+    let emitter = ExecutionEventEmitter::with_capacity(2);
+    let mut stream = Box::pin(emitter.subscribe());
+
+    // Nobody reads the stream while these are emitted
+    for n in 1..=5 {
+        emitter.emit(modal(n));
+    }
+
+    let first = stream.next().await.unwrap();
+    assert_eq!(first.seq, 4, "events 1 to 3 were skipped");
+    assert_eq!(stream.next().await.unwrap().seq, 5);
+
+    emitter.emit(modal(6));
+    assert_eq!(stream.next().await.unwrap().event, modal(6));
+}
+
+#[test]
+fn test_run_registry_lookup_and_eviction() {
+    let runs = EventRuns::with_capacity(2);
+    assert!(runs.get(None).is_none());
+
+    let first = ExecutionEventEmitter::new();
+    first.emit(modal(1));
+    runs.register("run-1", first);
+    runs.register("run-2", ExecutionEventEmitter::new());
+
+    let (id, emitter) = runs.get(Some("run-1")).unwrap();
+    assert_eq!(id, "run-1");
+    assert_eq!(emitter.since(0).latest_seq, 1);
+    assert_eq!(runs.get(None).unwrap().0, "run-2");
+
+    runs.register("run-3", ExecutionEventEmitter::new());
+    assert!(runs.get(Some("run-1")).is_none());
+    assert_eq!(runs.get(None).unwrap().0, "run-3");
+}
+
+#[tokio::test]
+async fn test_catch_up_then_subscribe_has_no_gaps_or_duplicates() {
+    const TOTAL: u64 = 200;
+    let emitter = ExecutionEventEmitter::new();
+    for n in 1..=20 {
+        emitter.emit(modal(n));
+    }
+
+    // Keep emitting while the late client catches up
+    let producer = {
+        let emitter = emitter.clone();
+        tokio::spawn(async move {
+            for n in 21..=TOTAL {
+                emitter.emit(modal(n));
+                if n % 10 == 0 {
+                    tokio::task::yield_now().await;
+                }
+            }
+        })
+    };
+
+    // First page through the buffer with the cursor, as a client polling get_events would
+    let mut received = Vec::new();
+    let mut cursor = 0;
+    for _ in 0..3 {
+        let batch = emitter.since(cursor);
+        assert!(!batch.truncated);
+        received.extend(seqs(&batch.events));
+        cursor = received.last().copied().unwrap_or(cursor);
+        tokio::task::yield_now().await;
+    }
+
+    // Then switch to the live stream
+    let (batch, mut live) = emitter.subscribe_since(cursor);
+    received.extend(seqs(&batch.events));
+    while received.last().copied() != Some(TOTAL) {
+        let event = tokio::time::timeout(Duration::from_secs(5), live.recv())
+            .await
+            .expect("live stream stalled")
+            .expect("subscriber lagged");
+        received.push(event.seq);
+    }
+    producer.await.unwrap();
+
+    assert_eq!(received, (1..=TOTAL).collect::<Vec<_>>());
+}
@@ -1,5 +1,9 @@
 use serde_json::json;
-use terminator_mcp_agent::expression_eval::evaluate;
+use terminator_mcp_agent::expression_eval::{
+    evaluate, evaluate_checked, evaluate_indexed, evaluate_value, evaluate_value_indexed,
+    evaluate_with_options, get_value, try_evaluate_with_options, EvalError, EvalOptions,
+    Expression, IndexedContext, DEFAULT_MAX_DEPTH, DEFAULT_UNIT_SUFFIXES,
+};
 
 #[test]
 fn test_evaluate_binary_expressions() {
@@ -26,6 +30,30 @@ fn test_evaluate_contains() {
     assert!(evaluate("contains(description, 'Expense')", &vars));
 }
 
+#[test]
+fn test_commas_inside_quoted_arguments() {
+    let vars = json!({
+        "tags": ["a,b", "c"],
+        "title": "Smith, John (Jr.)",
+        "greeting": null
+    });
+
+    assert!(evaluate("contains(tags, 'a,b')", &vars));
+    assert!(!evaluate("contains(tags, 'a')", &vars));
+    assert!(evaluate("contains(title, \"Smith, John\")", &vars));
+    assert!(evaluate("startsWith(title, 'Smith, ')", &vars));
+    assert!(evaluate("endsWith(title, '(Jr.)')", &vars));
+
+    assert_eq!(
+        evaluate_value("coalesce(greeting, 'hello, world')", &vars).unwrap(),
+        json!("hello, world")
+    );
+    assert_eq!(
+        evaluate_value("coalesce(greeting, \"a, (b\", 'c')", &vars).unwrap(),
+        json!("a, (b")
+    );
+}
+
 #[test]
 fn test_evaluate_starts_with() {
     let vars = json!({ "name": "John Doe" });
@@ -40,6 +68,246 @@ fn test_evaluate_ends_with() {
     assert!(!evaluate("endsWith(name, 'John')", &vars));
 }
 
+#[test]
+fn test_evaluate_matches() {
+    let vars = json!({ "order_id": "ORD-1042", "code": 307, "tags": ["a"] });
+    assert!(evaluate("matches(order_id, '^ORD-[0-9]+$')", &vars));
+    assert!(!evaluate("matches(order_id, '^INV-[0-9]+$')", &vars));
+    // Unanchored patterns match anywhere; numbers are matched as text
+    assert!(evaluate("matches(order_id, '10')", &vars));
+    assert!(evaluate("matches(code, '^3[0-9]{2}$')", &vars));
+    assert!(evaluate("matches(order_id, '^(ORD|INV)-')", &vars));
+    assert!(!evaluate("matches(tags, 'a')", &vars));
+
+    assert!(evaluate("!matches(order_id, '^INV-')", &vars));
+    assert!(evaluate(
+        "matches(order_id, '^ORD-') && !matches(order_id, 'x|y')",
+        &vars
+    ));
+}
+
+#[test]
+fn test_matches_invalid_pattern_is_false() {
+    let vars = json!({ "order_id": "ORD-1042" });
+    assert!(!evaluate("matches(order_id, '^ORD-[0-9+$')", &vars));
+    let err = evaluate_checked("matches(order_id, '^ORD-[0-9+$')", &vars).unwrap_err();
+    assert!(matches!(err, EvalError::Malformed(_)), "{err:?}");
+    assert!(err.to_string().contains("invalid pattern"), "{err}");
+
+    // Repeated evaluation, as in a loop, gives the same answers
+    for _ in 0..3 {
+        assert!(evaluate("matches(order_id, '^ORD-')", &vars));
+        assert!(!evaluate("matches(order_id, '(')", &vars));
+    }
+}
+
+#[test]
+fn test_in_operator() {
+    let vars = json!({ "status": "pending", "code": 404, "retry": "3", "done": false });
+
+    assert!(evaluate("status in ('active', 'pending', 'review')", &vars));
+    assert!(!evaluate("status in ('active', 'review')", &vars));
+    assert!(evaluate("status not in ('active', 'review')", &vars));
+    assert!(!evaluate("status not in ('pending')", &vars));
+    assert!(evaluate("status in (\"pending\", 'a, b')", &vars));
+
+    // Same coercions as `==`
+    assert!(evaluate("code in (404, 500)", &vars));
+    assert!(evaluate("code in ('404')", &vars));
+    assert!(evaluate("retry in (1, 2, 3)", &vars));
+    assert!(evaluate("done in (false)", &vars));
+    assert!(!evaluate("code in ()", &vars));
+
+    // Combines with other conditions
+    assert!(evaluate(
+        "status in ('pending') && code not in (200)",
+        &vars
+    ));
+    assert!(evaluate("(status in ('x') || code in (404))", &vars));
+    // `in` inside a quoted string is plain text
+    assert!(!evaluate("status == 'log in (now)'", &vars));
+}
+
+#[test]
+fn test_in_operator_rejects_invalid_lists() {
+    let vars = json!({ "status": "pending", "allowed": ["pending"] });
+
+    assert!(!evaluate("status in allowed", &vars));
+    assert!(!evaluate("status not in (allowed)", &vars));
+    assert!(!evaluate("missing in ('pending')", &vars));
+    assert!(matches!(
+        evaluate_checked("status in (allowed)", &vars),
+        Err(EvalError::Malformed(_))
+    ));
+    assert!(matches!(
+        evaluate_checked("missing not in ('pending')", &vars),
+        Err(EvalError::VariableNotFound(_))
+    ));
+}
+
+#[test]
+fn test_ignore_case_functions() {
+    let vars = json!({
+        "status": "Success",
+        "city": "MÜNCHEN",
+        "tags": ["Urgent", "Billing"],
+        "code": 200,
+    });
+
+    assert!(evaluate("equalsIgnoreCase(status, 'success')", &vars));
+    assert!(evaluate("equalsIgnoreCase(status, 'SUCCESS')", &vars));
+    assert!(!evaluate("equalsIgnoreCase(status, 'succes')", &vars));
+    assert!(evaluate("equalsIgnoreCase(city, 'München')", &vars));
+    assert!(evaluate("equalsIgnoreCase(code, '200')", &vars));
+
+    assert!(evaluate("containsIgnoreCase(status, 'CESS')", &vars));
+    assert!(evaluate("containsIgnoreCase(city, 'ünch')", &vars));
+    assert!(evaluate("containsIgnoreCase(tags, 'urgent')", &vars));
+    assert!(!evaluate("containsIgnoreCase(tags, 'urg')", &vars));
+
+    assert!(evaluate("startsWithIgnoreCase(status, 'succ')", &vars));
+    assert!(evaluate("endsWithIgnoreCase(city, 'chen')", &vars));
+    assert!(!evaluate("endsWithIgnoreCase(city, 'münch')", &vars));
+    assert!(!evaluate("startsWithIgnoreCase(code, '2')", &vars));
+
+    assert!(evaluate("!equalsIgnoreCase(status, 'failed')", &vars));
+}
+
+#[test]
+fn test_case_sensitive_functions_are_unchanged() {
+    let vars = json!({ "status": "Success", "tags": ["Urgent"] });
+
+    assert!(!evaluate("contains(status, 'success')", &vars));
+    assert!(!evaluate("contains(tags, 'urgent')", &vars));
+    assert!(!evaluate("startsWith(status, 'success')", &vars));
+    assert!(!evaluate("endsWith(status, 'SS')", &vars));
+    assert!(!evaluate("status == 'success'", &vars));
+}
+
+#[test]
+fn test_is_empty_and_is_null() {
+    let vars = json!({
+        "empty_string": "",
+        "text": "hello",
+        "empty_array": [],
+        "array": [0],
+        "empty_object": {},
+        "object": {"a": null},
+        "nothing": null,
+        "zero": 0,
+        "no": false,
+    });
+    let cases = [
+        ("missing", true, true),
+        ("nothing", true, true),
+        ("null", true, true),
+        ("empty_string", true, false),
+        ("''", true, false),
+        ("empty_array", true, false),
+        ("empty_object", true, false),
+        ("text", false, false),
+        ("array", false, false),
+        ("object", false, false),
+        ("object.a", true, true),
+        ("zero", false, false),
+        ("no", false, false),
+        ("map(empty_array, 'x')", true, false),
+    ];
+    for (arg, empty, null) in cases {
+        assert_eq!(
+            evaluate(&format!("isEmpty({arg})"), &vars),
+            empty,
+            "isEmpty({arg})"
+        );
+        assert_eq!(
+            evaluate(&format!("!isEmpty({arg})"), &vars),
+            !empty,
+            "!isEmpty({arg})"
+        );
+        assert_eq!(
+            evaluate(&format!("isNull({arg})"), &vars),
+            null,
+            "isNull({arg})"
+        );
+        assert_eq!(
+            evaluate(&format!("!isNull({arg})"), &vars),
+            !null,
+            "!isNull({arg})"
+        );
+    }
+
+    assert!(evaluate("isEmpty(missing) && !isEmpty(text)", &vars));
+    assert_eq!(
+        evaluate_value("isEmpty(text)", &vars).unwrap(),
+        json!(false)
+    );
+    assert!(evaluate_value("isEmpty()", &vars).is_err());
+    assert!(evaluate_value("isNull(a, b)", &vars).is_err());
+}
+
+#[test]
+fn test_unquoted_numeric_equality() {
+    let vars = json!({ "count": 5, "zero": 0.0, "price": 5.0, "text": "5", "word": "five" });
+
+    assert!(evaluate("count == 5", &vars));
+    assert!(evaluate("count == 5.0", &vars));
+    assert!(evaluate("price == 5", &vars));
+    assert!(evaluate("zero == 0", &vars));
+    assert!(evaluate("count != 6", &vars));
+    assert!(!evaluate("count != 5.0", &vars));
+    assert!(evaluate("0.0 == 0", &vars));
+    assert!(evaluate("5 == 5.0", &vars));
+    assert!(evaluate("5 == count", &vars));
+    assert!(!evaluate("5 == 6", &vars));
+
+    // Non-numbers still compare as strings
+    assert!(evaluate("text == 5", &vars));
+    assert!(!evaluate("word == 5", &vars));
+
+    // The quoted form keeps working
+    assert!(evaluate("count == '5'", &vars));
+    assert!(evaluate("text == '5'", &vars));
+    assert!(!evaluate("count == '6'", &vars));
+}
+
+#[test]
+fn test_between() {
+    let vars = json!({
+        "total": 100,
+        "expected": 98.5,
+        "tolerance": {"low": 95, "high": 105},
+        "ratio": 0.25,
+        "amount": "42.50",
+        "width": "120px",
+        "label": "n/a",
+    });
+
+    // Bounds are inclusive
+    assert!(evaluate("between(total, 90, 110)", &vars));
+    assert!(evaluate("between(total, 100, 100)", &vars));
+    assert!(!evaluate("between(total, 101, 200)", &vars));
+    assert!(!evaluate("between(total, 110, 90)", &vars));
+
+    // Float bounds, variables and numeric strings
+    assert!(evaluate("between(ratio, 0.2, 0.3)", &vars));
+    assert!(!evaluate("between(ratio, 0.26, 0.3)", &vars));
+    assert!(evaluate(
+        "between(expected, tolerance.low, tolerance.high)",
+        &vars
+    ));
+    assert!(evaluate("between(amount, '42.5', 50)", &vars));
+    assert!(evaluate("between(width, '100px', '150px')", &vars));
+    assert!(evaluate("between(len(label), 1, 3)", &vars));
+
+    // Undefined or non-numeric values are not in range
+    assert!(!evaluate("between(missing, 0, 10)", &vars));
+    assert!(!evaluate("between(total, missing, 200)", &vars));
+    assert!(!evaluate("between(label, 0, 10)", &vars));
+    assert!(evaluate("!between(missing, 0, 10)", &vars));
+
+    assert!(evaluate_value("between(total, 1)", &vars).is_err());
+}
+
 #[test]
 fn test_string_with_spaces() {
     let vars = json!({
@@ -271,6 +539,97 @@ fn test_simple_boolean_variables() {
     ));
 }
 
+#[test]
+fn test_and_binds_tighter_than_or() {
+    let vars = json!({ "a": "1", "b": "0", "c": "0", "d": "1" });
+
+    // a || (b && c): true, although (a || b) && c would be false
+    assert!(evaluate("a == '1' || b == '2' && c == '3'", &vars));
+    // (b && c) || a
+    assert!(evaluate("b == '2' && c == '3' || a == '1'", &vars));
+    // (a && b) || (c && d)
+    assert!(!evaluate(
+        "a == '1' && b == '1' || c == '1' && d == '1'",
+        &vars
+    ));
+    assert!(evaluate(
+        "a == '1' && b == '0' || c == '1' && d == '1'",
+        &vars
+    ));
+    // b || (c && d) || (a && d)
+    assert!(evaluate(
+        "b == '1' || c == '1' && d == '1' || a == '1' && d == '1'",
+        &vars
+    ));
+    assert!(!evaluate(
+        "b == '1' || c == '1' && d == '1' || a == '0'",
+        &vars
+    ));
+}
+
+#[test]
+fn test_negation_and_parentheses_in_logical_chains() {
+    let vars = json!({ "done": false, "ready": true, "retry": false });
+
+    // ! applies to its own operand only
+    assert!(evaluate("!done && ready", &vars));
+    assert!(!evaluate("!ready && done || retry", &vars));
+    assert!(evaluate("!(done || retry) && ready", &vars));
+    // Parentheses override precedence
+    assert!(!evaluate("(ready || done) && retry", &vars));
+    assert!(evaluate("ready || (done && retry)", &vars));
+}
+
+#[test]
+fn test_parentheses_group_conditions() {
+    let vars = json!({ "a": "1", "b": "0", "c": "0", "d": "1" });
+
+    // Without parentheses && binds first and this is true
+    assert!(evaluate("a == '1' || b == '2' && c == '3'", &vars));
+    assert!(!evaluate("(a == '1' || b == '2') && c == '3'", &vars));
+    assert!(evaluate("(a == '1' || b == '2') && d == '1'", &vars));
+    assert!(evaluate(
+        "c == '3' || (a == '1' && (b == '0' || c == '1'))",
+        &vars
+    ));
+    assert!(!evaluate(
+        "((a == '0' || b == '1')) || (c == '1' && d == '1')",
+        &vars
+    ));
+    assert!(evaluate("!((a == '0') || (b == '1'))", &vars));
+}
+
+#[test]
+fn test_parentheses_inside_quotes_are_literal() {
+    let vars = json!({ "face": ":)", "label": "(draft", "ok": true });
+
+    assert!(evaluate("face == ':)'", &vars));
+    assert!(evaluate("(label == '(draft') && ok", &vars));
+    assert!(evaluate("(face == ')(' || ok)", &vars));
+}
+
+#[test]
+fn test_unbalanced_parentheses_are_false() {
+    let vars = json!({ "a": "1", "ok": true });
+
+    assert!(!evaluate("(a == '1'", &vars));
+    assert!(!evaluate("a == '1')", &vars));
+    assert!(!evaluate("(ok || ok", &vars));
+    assert!(!evaluate("ok && (ok))", &vars));
+    assert!(!evaluate(")ok(", &vars));
+}
+
+#[test]
+fn test_logical_operators_inside_quotes_are_literal() {
+    let vars = json!({ "name": "a && b", "sep": "||", "flag": true });
+
+    assert!(evaluate("name == 'a && b'", &vars));
+    assert!(evaluate("sep == '||' && flag", &vars));
+    assert!(evaluate("name == \"x || y\" || sep == '||'", &vars));
+    assert!(!evaluate("name == 'a || b' && flag", &vars));
+    assert!(evaluate("contains(name, '&&') && flag", &vars));
+}
+
 #[test]
 fn test_truthiness_of_different_types() {
     let vars = json!({
@@ -387,3 +746,1018 @@ fn test_unicode_spaces_normalized() {
     // Test with thin space
     assert!(evaluate("env.status\u{2009}==\u{2009}'active'", &vars));
 }
+
+#[test]
+fn test_number_format_rounds_to_decimals() {
+    let vars = json!({ "total": 1234.5678, "price": "19.999", "count": 7 });
+
+    assert_eq!(
+        evaluate_value("numberFormat(total, 2)", &vars).unwrap(),
+        json!("1,234.57")
+    );
+    assert_eq!(
+        evaluate_value("numberFormat(price, 2)", &vars).unwrap(),
+        json!("20.00")
+    );
+    assert_eq!(
+        evaluate_value("numberFormat(count, 0)", &vars).unwrap(),
+        json!("7")
+    );
+    assert_eq!(
+        evaluate_value("numberFormat(-0.001, 2)", &vars).unwrap(),
+        json!("0.00")
+    );
+}
+
+#[test]
+fn test_number_format_thousands_grouping() {
+    let vars = json!({ "big": 1234567.891, "negative": -9876543 });
+
+    assert_eq!(
+        evaluate_value("numberFormat(big, 2)", &vars).unwrap(),
+        json!("1,234,567.89")
+    );
+    assert_eq!(
+        evaluate_value("numberFormat(negative, 0)", &vars).unwrap(),
+        json!("-9,876,543")
+    );
+    assert_eq!(
+        evaluate_value("numberFormat(999, 1)", &vars).unwrap(),
+        json!("999.0")
+    );
+}
+
+#[test]
+fn test_number_format_follows_locale() {
+    let vars = json!({ "amount": 1234567.891, "env": { "locale": "de-DE" } });
+    assert_eq!(
+        evaluate_value("numberFormat(amount, 2)", &vars).unwrap(),
+        json!("1.234.567,89")
+    );
+    assert_eq!(
+        evaluate_value("numberFormat(amount, 2, 'en-US')", &vars).unwrap(),
+        json!("1,234,567.89")
+    );
+    assert_eq!(
+        evaluate_value("numberFormat(amount, 1, 'fr-FR')", &vars).unwrap(),
+        json!("1\u{00A0}234\u{00A0}567,9")
+    );
+}
+
+#[test]
+fn test_number_format_rejects_non_numeric_input() {
+    let vars = json!({ "name": "John", "flags": [1, 2] });
+
+    assert!(evaluate_value("numberFormat(name, 2)", &vars).is_err());
+    assert!(evaluate_value("numberFormat(flags, 2)", &vars).is_err());
+    assert!(evaluate_value("numberFormat(missing, 2)", &vars).is_err());
+    assert!(evaluate_value("numberFormat(1.5, -1)", &vars).is_err());
+    assert!(evaluate_value("numberFormat(1.5)", &vars).is_err());
+}
+
+#[test]
+fn test_evaluate_value_falls_back_to_condition() {
+    let vars = json!({ "status": "done" });
+    assert_eq!(
+        evaluate_value("status == 'done'", &vars).unwrap(),
+        json!(true)
+    );
+}
+
+#[test]
+fn test_variable_to_variable_equality() {
+    let vars = json!({
+        "current_status": "approved",
+        "expected_status": "approved",
+        "other_status": "pending",
+        "env": { "confirmed": true, "needs_confirmation": "true" },
+        "is_done": false
+    });
+
+    assert!(evaluate("current_status == expected_status", &vars));
+    assert!(!evaluate("current_status == other_status", &vars));
+    assert!(evaluate("env.confirmed == env.needs_confirmation", &vars));
+    assert!(!evaluate("env.confirmed == is_done", &vars));
+}
+
+#[test]
+fn test_variable_to_variable_inequality() {
+    let vars = json!({
+        "current_status": "approved",
+        "other_status": "pending",
+        "same_status": "approved"
+    });
+
+    assert!(evaluate("current_status != other_status", &vars));
+    assert!(!evaluate("current_status != same_status", &vars));
+}
+
+#[test]
+fn test_variable_to_variable_numeric_comparison() {
+    let vars = json!({
+        "current_total": 42,
+        "expected_total": 42.0,
+        "total_text": "42",
+        "other_total": 41
+    });
+
+    assert!(evaluate("current_total == expected_total", &vars));
+    assert!(evaluate("current_total == total_text", &vars));
+    assert!(evaluate("total_text == expected_total", &vars));
+    assert!(evaluate("current_total != other_total", &vars));
+    assert!(!evaluate("current_total == other_total", &vars));
+}
+
+#[test]
+fn test_undefined_rhs_variable_matches_undefined_lhs() {
+    let vars = json!({ "status": "done" });
+
+    // An undefined variable on either side makes the comparison false
+    assert!(!evaluate("status == missing", &vars));
+    assert!(!evaluate("status != missing", &vars));
+    assert!(!evaluate("missing == status", &vars));
+    assert!(!evaluate("missing != status", &vars));
+}
+
+#[test]
+fn test_normalization_can_be_disabled() {
+    let vars = json!({ "name": "Bob", "note": "it\u{2019}s" });
+    let strict = EvalOptions {
+        normalize: false,
+        ..EvalOptions::default()
+    };
+
+    // By default smart quotes are rewritten, so they match straight-quoted data
+    assert!(evaluate("name == \u{2018}Bob\u{2019}", &vars));
+    assert!(evaluate_with_options(
+        "name == \u{2018}Bob\u{2019}",
+        &vars,
+        &EvalOptions::default()
+    ));
+    // Without normalization the smart-quoted literal is compared as written
+    assert!(!evaluate_with_options(
+        "name == \u{2018}Bob\u{2019}",
+        &vars,
+        &strict
+    ));
+    assert!(evaluate_with_options("name == 'Bob'", &vars, &strict));
+
+    // Data containing smart quotes only matches when it is left untouched
+    assert!(!evaluate("note == 'it\u{2019}s'", &vars));
+    assert!(evaluate_with_options(
+        "note == 'it\u{2019}s'",
+        &vars,
+        &strict
+    ));
+}
+
+#[test]
+fn test_map_projects_field() {
+    let vars = json!({
+        "rows": [
+            { "name": "Acme", "amount": 10, "address": { "city": "Berlin" } },
+            { "name": "Globex", "amount": 25 },
+            { "amount": 5 }
+        ],
+        "not_a_list": "Acme"
+    });
+
+    assert_eq!(
+        evaluate_value("map(rows, 'name')", &vars).unwrap(),
+        json!(["Acme", "Globex", null])
+    );
+    assert_eq!(
+        evaluate_value("map(rows, 'amount')", &vars).unwrap(),
+        json!([10, 25, 5])
+    );
+    // Nested fields use dot paths; missing ones become null entries
+    assert_eq!(
+        evaluate_value("map(rows, 'address.city')", &vars).unwrap(),
+        json!(["Berlin", null, null])
+    );
+    // Non-array input projects to an empty array
+    assert_eq!(
+        evaluate_value("map(not_a_list, 'name')", &vars).unwrap(),
+        json!([])
+    );
+    assert_eq!(
+        evaluate_value("map(missing, 'name')", &vars).unwrap(),
+        json!([])
+    );
+    assert!(evaluate_value("map(rows)", &vars).is_err());
+}
+
+#[test]
+fn test_map_composes_with_contains_and_len() {
+    let vars = json!({
+        "rows": [{ "name": "Acme" }, { "name": "Globex" }, { "id": 3 }]
+    });
+
+    assert!(evaluate("contains(map(rows, 'name'), 'Globex')", &vars));
+    assert!(!evaluate("contains(map(rows, 'name'), 'Initech')", &vars));
+    assert_eq!(
+        evaluate_value("len(map(rows, 'name'))", &vars).unwrap(),
+        json!(3)
+    );
+    assert_eq!(evaluate_value("len(rows)", &vars).unwrap(), json!(3));
+}
+
+#[test]
+fn test_bool_matches_truthiness_table() {
+    let vars = json!({
+        "t": true, "f": false,
+        "empty": "", "zero_str": "0", "false_str": "false", "text": "yes",
+        "zero": 0, "one": 1, "negative": -2,
+        "float_zero": 0.0, "half": 0.5, "fraction": 2.5, "negative_fraction": -1.5,
+        "nothing": null,
+        "empty_list": [], "list": [1],
+        "empty_obj": {}, "obj": { "a": 1 }
+    });
+
+    let table = [
+        ("t", true),
+        ("f", false),
+        ("empty", false),
+        ("zero_str", false),
+        ("false_str", false),
+        ("text", true),
+        ("zero", false),
+        ("one", true),
+        ("negative", true),
+        ("float_zero", false),
+        ("half", true),
+        ("fraction", true),
+        ("negative_fraction", true),
+        ("nothing", false),
+        ("empty_list", false),
+        ("list", true),
+        ("empty_obj", false),
+        ("obj", true),
+        ("missing", false),
+    ];
+    for (name, expected) in table {
+        // bool() agrees with the implicit truthiness of a bare variable
+        assert_eq!(evaluate(name, &vars), expected, "{name}");
+        assert_eq!(
+            evaluate_value(&format!("bool({name})"), &vars).unwrap(),
+            json!(expected),
+            "bool({name})"
+        );
+        assert_eq!(evaluate(&format!("bool({name})"), &vars), expected);
+        assert_eq!(evaluate(&format!("not({name})"), &vars), !expected);
+    }
+}
+
+#[test]
+fn test_bool_and_not_in_comparisons() {
+    let vars = json!({ "flag": "1", "status": "done", "count": 0 });
+
+    assert!(evaluate("bool(flag) == true", &vars));
+    assert!(evaluate("bool(count) == false", &vars));
+    assert!(evaluate("bool(count) != true", &vars));
+    assert!(evaluate("not(count) == true", &vars));
+    // Literals and nested conditions
+    assert!(!evaluate("bool('0')", &vars));
+    assert!(evaluate("bool('no')", &vars));
+    assert!(evaluate("not(status == 'pending')", &vars));
+    assert!(!evaluate("not(status == 'done')", &vars));
+    assert!(evaluate_value("bool()", &vars).is_err());
+}
+
+#[test]
+fn test_array_index_paths() {
+    let vars = json!({
+        "rows": [
+            {"name": "Alice", "cells": [{"value": 10}, {"value": 20}]},
+            {"name": "Bob", "cells": []},
+        ],
+        "matrix": [[1, 2], [3, 4]],
+        "env": {"tables": [{"rows": [{"id": "A-1"}]}]},
+    });
+
+    assert_eq!(get_value("rows.0.name", &vars), Some(&json!("Alice")));
+    assert_eq!(get_value("rows[1].name", &vars), Some(&json!("Bob")));
+    assert_eq!(get_value("rows[0].cells[1].value", &vars), Some(&json!(20)));
+    assert_eq!(get_value("rows.0.cells.1.value", &vars), Some(&json!(20)));
+    assert_eq!(get_value("matrix[1][0]", &vars), Some(&json!(3)));
+    assert_eq!(
+        get_value("env.tables[0].rows[0].id", &vars),
+        Some(&json!("A-1"))
+    );
+
+    // Out of bounds or malformed indices are undefined
+    assert_eq!(get_value("rows.2.name", &vars), None);
+    assert_eq!(get_value("rows[1].cells[0]", &vars), None);
+    assert_eq!(get_value("rows[-1]", &vars), None);
+    assert_eq!(get_value("rows[0", &vars), None);
+
+    assert!(evaluate("rows[0].name == 'Alice'", &vars));
+    assert!(evaluate("rows.1.name == 'Bob'", &vars));
+    assert!(evaluate("rows[0].cells[1].value > 15", &vars));
+    assert!(evaluate("rows[1].name == rows.1.name", &vars));
+    assert!(evaluate(
+        "startsWith(env.tables[0].rows[0].id, 'A-')",
+        &vars
+    ));
+    assert!(!evaluate("rows[5].name == 'Alice'", &vars));
+    assert!(matches!(
+        evaluate_checked("rows[5].name == 'Alice'", &vars),
+        Err(EvalError::VariableNotFound(_))
+    ));
+    // A number is still a number
+    assert!(evaluate("matrix.0.1 > 1.5", &vars));
+}
+
+#[test]
+fn test_filter_rows_can_index_their_own_arrays() {
+    let vars = json!({ "rows": [[1, "open"], [2, "closed"], [3, "open"]] });
+    assert_eq!(
+        evaluate_value("map(filter(rows, \"item[1] == 'open'\"), '0')", &vars).unwrap(),
+        json!([1, 3])
+    );
+}
+
+fn indexed_fixture() -> serde_json::Value {
+    json!({
+        "env": {
+            "troubleshooting": true,
+            "locale": "de-DE",
+            "totals": {"expected": 1234.5, "actual": "1234.5"},
+            "empty": {},
+        },
+        "policy": {"product_types": ["FEX", "HIP"], "name": "Gold Plan"},
+        "rows": [{"name": "Alice", "age": 30}, {"name": "Bob"}],
+        "status": "done",
+        "count": 0,
+        "dotted.key": "unreachable",
+        "": {"x": "empty key"},
+    })
+}
+
+#[test]
+fn test_indexed_context_matches_tree_walk() {
+    let vars = indexed_fixture();
+    let context = IndexedContext::new(&vars);
+
+    for path in [
+        "env",
+        "env.totals.expected",
+        "env.totals.missing",
+        "policy.product_types",
+        "rows",
+        "rows.0",
+        "rows.1.name",
+        "rows[0].age",
+        "rows[1].age",
+        "rows[2]",
+        "rows[x]",
+        "rows.01",
+        "dotted.key",
+        "dotted",
+        "",
+        ".x",
+        "missing",
+        "status.length",
+    ] {
+        assert_eq!(context.get(path), get_value(path, &vars), "path '{path}'");
+    }
+
+    for expr in [
+        "env.troubleshooting",
+        "!env.troubleshooting",
+        "status == 'done' && count == '0'",
+        "env.totals.expected == env.totals.actual",
+        "contains(policy.product_types, 'FEX')",
+        "startsWith(policy.name, 'Gold')",
+        "contains(map(rows, 'name'), 'Bob')",
+        "len(rows) == '2'",
+        "not(env.missing)",
+        "bool(env.empty)",
+        "undefined_var == 'x'",
+        "always()",
+    ] {
+        assert_eq!(
+            evaluate_indexed(expr, &context),
+            evaluate(expr, &vars),
+            "expression '{expr}'"
+        );
+    }
+
+    for expr in [
+        "numberFormat(env.totals.expected, 2)",
+        "map(rows, 'age')",
+        "len(policy.name)",
+        "len(missing)",
+    ] {
+        assert_eq!(
+            evaluate_value_indexed(expr, &context),
+            evaluate_value(expr, &vars),
+            "expression '{expr}'"
+        );
+    }
+}
+
+#[test]
+fn test_indexed_context_resolves_each_path_once() {
+    // Thousands of variables, each ten levels deep; timing lives in benches/expression_eval.rs
+    let depth = 10;
+    let mut vars = serde_json::Map::new();
+    for i in 0..2000 {
+        let mut leaf = json!(i);
+        for level in (1..depth).rev() {
+            leaf = json!({ format!("l{level}"): leaf });
+        }
+        vars.insert(format!("v{i}"), leaf);
+    }
+    let vars = serde_json::Value::Object(vars);
+    let path: String = std::iter::once("v1999".to_string())
+        .chain((1..depth).map(|level| format!("l{level}")))
+        .collect::<Vec<_>>()
+        .join(".");
+    let exprs = [
+        format!("{path} == '1999'"),
+        format!("{path} == '1998'"),
+        format!("{path}.missing == '1999'"),
+    ];
+
+    // Nothing is walked up front
+    let context = IndexedContext::new(&vars);
+    assert!(context.is_empty());
+
+    for expr in &exprs {
+        assert_eq!(
+            evaluate_indexed(expr, &context),
+            evaluate(expr, &vars),
+            "expression '{expr}'"
+        );
+    }
+    let resolved = context.len();
+    assert!(resolved > 0);
+
+    // Evaluating again reuses the resolved paths
+    for _ in 0..100 {
+        for expr in &exprs {
+            assert_eq!(evaluate_indexed(expr, &context), evaluate(expr, &vars));
+        }
+    }
+    assert_eq!(context.len(), resolved);
+}
+
+#[test]
+fn test_get_with_literal_path() {
+    let vars = json!({
+        "config": {
+            "features": { "export": { "enabled": true, "format": "csv" } },
+            "retries": 3
+        }
+    });
+
+    assert_eq!(
+        evaluate_value("get(config, 'features.export.format')", &vars).unwrap(),
+        json!("csv")
+    );
+    assert_eq!(
+        evaluate_value("get(config, 'retries')", &vars).unwrap(),
+        json!(3)
+    );
+    assert!(evaluate("get(config, 'features.export.enabled')", &vars));
+    assert!(evaluate(
+        "get(config, 'features.export.format') == 'csv'",
+        &vars
+    ));
+
+    // Missing paths and missing objects are null
+    assert_eq!(
+        evaluate_value("get(config, 'features.import.enabled')", &vars).unwrap(),
+        json!(null)
+    );
+    assert_eq!(
+        evaluate_value("get(missing, 'a')", &vars).unwrap(),
+        json!(null)
+    );
+    assert!(!evaluate("get(config, 'features.import.enabled')", &vars));
+}
+
+#[test]
+fn test_get_with_path_from_variable() {
+    let vars = json!({
+        "config": {
+            "limits": { "invoice": 500, "refund": 50 }
+        },
+        "limit_path": "limits.refund",
+        "kind": "invoice"
+    });
+
+    assert_eq!(
+        evaluate_value("get(config, limit_path)", &vars).unwrap(),
+        json!(50)
+    );
+    assert!(evaluate("get(config, limit_path) == '50'", &vars));
+    // The path is taken as data, so it can name any key at runtime
+    assert_eq!(
+        evaluate_value("get(get(config, 'limits'), kind)", &vars).unwrap(),
+        json!(500)
+    );
+    assert!(evaluate_value("get(config, retries)", &vars).is_err());
+}
+
+#[test]
+fn test_null_coalescing_chain() {
+    let vars = json!({
+        "nickname": null,
+        "profile": { "display_name": "Ada" },
+        "fallback": "Guest"
+    });
+
+    // Right-associative: stops at the first defined, non-null operand
+    assert_eq!(
+        evaluate_value(
+            "nickname ?? missing ?? profile.display_name ?? fallback",
+            &vars
+        )
+        .unwrap(),
+        json!("Ada")
+    );
+    assert_eq!(
+        evaluate_value("nickname ?? missing ?? 'anonymous'", &vars).unwrap(),
+        json!("anonymous")
+    );
+    assert_eq!(
+        evaluate_value("missing ?? null", &vars).unwrap(),
+        json!(null)
+    );
+    // Operators inside quotes and calls are not split on
+    assert_eq!(
+        evaluate_value("missing ?? 'a ?? b'", &vars).unwrap(),
+        json!("a ?? b")
+    );
+    assert_eq!(
+        evaluate_value("get(profile, 'nickname') ?? len(fallback)", &vars).unwrap(),
+        json!(5)
+    );
+    assert!(evaluate_value("nickname ?? ", &vars).is_err());
+}
+
+#[test]
+fn test_null_coalescing_keeps_falsy_values_unlike_coalesce() {
+    let vars = json!({
+        "count": 0,
+        "label": "",
+        "enabled": false,
+        "missing_value": null,
+        "default_count": 10,
+        "default_label": "n/a"
+    });
+
+    // `??` only skips null and undefined operands
+    assert_eq!(
+        evaluate_value("count ?? default_count", &vars).unwrap(),
+        json!(0)
+    );
+    assert_eq!(
+        evaluate_value("label ?? default_label", &vars).unwrap(),
+        json!("")
+    );
+    assert_eq!(
+        evaluate_value("enabled ?? true", &vars).unwrap(),
+        json!(false)
+    );
+
+    // coalesce() skips every falsy value
+    assert_eq!(
+        evaluate_value("coalesce(count, default_count)", &vars).unwrap(),
+        json!(10)
+    );
+    assert_eq!(
+        evaluate_value("coalesce(label, default_label)", &vars).unwrap(),
+        json!("n/a")
+    );
+    assert_eq!(
+        evaluate_value("coalesce(enabled, true)", &vars).unwrap(),
+        json!(true)
+    );
+
+    // Both agree when the only gap is null or undefined
+    assert_eq!(
+        evaluate_value("missing_value ?? undefined_value ?? default_count", &vars).unwrap(),
+        evaluate_value(
+            "coalesce(missing_value, undefined_value, default_count)",
+            &vars
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        evaluate_value("coalesce(count, label)", &vars).unwrap(),
+        json!(null)
+    );
+}
+
+#[test]
+fn test_ordering_comparisons() {
+    let vars = json!({ "amount": 120, "limit": "100", "label": "n/a" });
+
+    assert!(evaluate("amount > 100", &vars));
+    assert!(evaluate("amount >= 120", &vars));
+    assert!(!evaluate("amount < 100", &vars));
+    assert!(evaluate("amount <= 120.5", &vars));
+    // Numeric strings and variables on the right-hand side are coerced
+    assert!(evaluate("amount > limit", &vars));
+    assert!(evaluate("limit < '150'", &vars));
+    // A side that is not a number never compares
+    assert!(!evaluate("label > 1", &vars));
+    assert!(!evaluate("label <= 1", &vars));
+    assert!(!evaluate("missing > 1", &vars));
+}
+
+#[test]
+fn test_filter_table_rows() {
+    let vars = json!({
+        "rows": [
+            { "id": 1, "name": "Acme", "amount": 250, "paid": true },
+            { "id": 2, "name": "Globex", "amount": 40, "paid": false },
+            { "id": 3, "name": "Initech", "amount": 101, "paid": false },
+            { "id": 4, "name": "Umbrella", "amount": 100 }
+        ],
+        "threshold": 100,
+        "not_a_list": { "amount": 500 }
+    });
+
+    // Matching rows are returned whole and in order
+    assert_eq!(
+        evaluate_value("filter(rows, 'amount > 100')", &vars).unwrap(),
+        json!([
+            { "id": 1, "name": "Acme", "amount": 250, "paid": true },
+            { "id": 3, "name": "Initech", "amount": 101, "paid": false }
+        ])
+    );
+    // Row fields shadow outer variables; the rest of the context stays visible
+    assert_eq!(
+        evaluate_value("map(filter(rows, 'amount <= threshold'), 'id')", &vars).unwrap(),
+        json!([2, 4])
+    );
+    assert_eq!(
+        evaluate_value("map(filter(rows, \"name == 'Globex'\"), 'id')", &vars).unwrap(),
+        json!([2])
+    );
+    assert_eq!(
+        evaluate_value("map(filter(rows, 'item.paid'), 'name')", &vars).unwrap(),
+        json!(["Acme"])
+    );
+    assert_eq!(
+        evaluate_value("len(filter(rows, \"startsWith(name, 'U')\"))", &vars).unwrap(),
+        json!(1)
+    );
+
+    // Non-array input filters to an empty array
+    assert_eq!(
+        evaluate_value("filter(not_a_list, 'amount > 100')", &vars).unwrap(),
+        json!([])
+    );
+    assert_eq!(
+        evaluate_value("filter(missing, 'amount > 100')", &vars).unwrap(),
+        json!([])
+    );
+    assert!(evaluate_value("filter(rows)", &vars).is_err());
+}
+
+#[test]
+fn test_filter_scalar_rows_and_indexed_context() {
+    let vars = json!({ "amounts": [5, 150, 99, 300], "names": ["Ann", "Bob", "Alice"] });
+
+    assert_eq!(
+        evaluate_value("filter(amounts, 'item > 100')", &vars).unwrap(),
+        json!([150, 300])
+    );
+    assert_eq!(
+        evaluate_value("filter(names, \"startsWith(item, 'A')\")", &vars).unwrap(),
+        json!(["Ann", "Alice"])
+    );
+
+    let context = IndexedContext::new(&vars);
+    assert_eq!(
+        evaluate_value_indexed("filter(amounts, 'item > 100')", &context).unwrap(),
+        evaluate_value("filter(amounts, 'item > 100')", &vars).unwrap()
+    );
+}
+
+#[test]
+fn test_depth_limit_boundary() {
+    let vars = json!({ "flag": true });
+    let options = EvalOptions {
+        max_depth: 10,
+        ..EvalOptions::default()
+    };
+
+    // Nine negations around a variable are ten levels: just under the limit
+    let just_under = format!("{}flag", "!".repeat(9));
+    assert_eq!(
+        try_evaluate_with_options(&just_under, &vars, &options),
+        Ok(false)
+    );
+    let even = format!("{}flag", "!".repeat(8));
+    assert!(evaluate_with_options(&even, &vars, &options));
+
+    // One more level is over it: an error when strict, false when lenient
+    let over = format!("{}flag", "!".repeat(10));
+    let err = try_evaluate_with_options(&over, &vars, &options).unwrap_err();
+    assert!(err.contains("limit of 10"), "{err}");
+    // Even negation counts can't turn the failure into `true`
+    let over_even = format!("{}flag", "!".repeat(12));
+    assert!(!evaluate_with_options(&over_even, &vars, &options));
+}
+
+#[test]
+fn test_long_flat_chains_do_not_count_as_nesting() {
+    let vars = json!({ "flag": true });
+    let terms = |last: &str, operator: &str, other: &str| {
+        let mut terms = vec![other; 119];
+        terms.push(last);
+        terms.join(operator)
+    };
+
+    // 120 alternatives are one level, not 120
+    let any = terms("flag", " || ", "!flag");
+    assert!(evaluate(&any, &vars));
+    assert_eq!(evaluate_checked(&any, &vars), Ok(true));
+    assert_eq!(
+        evaluate_checked(&terms("!flag", " || ", "!flag"), &vars),
+        Ok(false)
+    );
+
+    let all = terms("flag", " && ", "flag");
+    assert_eq!(evaluate_checked(&all, &vars), Ok(true));
+    assert_eq!(
+        evaluate_checked(&terms("!flag", " && ", "flag"), &vars),
+        Ok(false)
+    );
+
+    // Parentheses still nest, chain or not: eight groups around `!flag` are ten levels
+    let options = EvalOptions {
+        max_depth: 10,
+        ..EvalOptions::default()
+    };
+    let grouped = format!("{}{any}{}", "(".repeat(8), ")".repeat(8));
+    assert_eq!(
+        try_evaluate_with_options(&grouped, &vars, &options),
+        Ok(true)
+    );
+    let too_deep = format!("{}{any}{}", "(".repeat(9), ")".repeat(9));
+    assert!(try_evaluate_with_options(&too_deep, &vars, &options).is_err());
+}
+
+#[test]
+fn test_depth_limit_covers_function_nesting() {
+    let vars = json!({ "flag": true });
+    let nested = |n: usize| format!("{}flag{}", "not(".repeat(n), ")".repeat(n));
+    let options = EvalOptions {
+        max_depth: 20,
+        ..EvalOptions::default()
+    };
+
+    assert_eq!(
+        try_evaluate_with_options(&nested(4), &vars, &options),
+        Ok(true)
+    );
+    assert!(try_evaluate_with_options(&nested(30), &vars, &options).is_err());
+    assert!(!evaluate_with_options(&nested(30), &vars, &options));
+
+    // The value layer uses the default limit and reports it as an error
+    assert_eq!(
+        evaluate_value(&nested(DEFAULT_MAX_DEPTH), &vars).unwrap(),
+        json!(true)
+    );
+    let err = evaluate_value(&nested(DEFAULT_MAX_DEPTH + 1), &vars).unwrap_err();
+    assert!(err.contains("nested deeper"), "{err}");
+}
+
+#[test]
+fn test_huge_expressions_do_not_overflow_the_stack() {
+    let vars = json!({ "flag": true, "a": null });
+
+    // Far beyond any sane depth; must fail cleanly rather than crash
+    assert!(!evaluate(&format!("{}flag", "!".repeat(100_000)), &vars));
+    // A flat chain is not nesting, so a huge one is just slow, never deep
+    assert!(evaluate(&vec!["flag"; 50_000].join(" && "), &vars));
+    assert!(evaluate_value(&format!("{}flag", "a ?? ".repeat(50_000)), &vars).is_err());
+
+    // Realistic expressions are nowhere near the default
+    assert!(evaluate("!!flag && flag || !flag", &vars));
+}
+
+#[test]
+fn test_strict_equality_option() {
+    let vars = json!({ "text": "42", "number": 42, "other_number": 42.0 });
+    let lenient = EvalOptions {
+        strict_equality: false,
+        ..EvalOptions::default()
+    };
+    let strict = EvalOptions {
+        strict_equality: true,
+        ..EvalOptions::default()
+    };
+
+    // "42" == 42 only holds when numeric strings are coerced
+    assert!(evaluate_with_options("text == number", &vars, &lenient));
+    assert!(!evaluate_with_options("text == number", &vars, &strict));
+    assert!(evaluate_with_options("number != text", &vars, &strict));
+    assert!(evaluate_with_options("number == '42'", &vars, &lenient));
+    assert!(!evaluate_with_options("number == '42'", &vars, &strict));
+    assert!(evaluate_with_options("text == 42", &vars, &lenient));
+    assert!(!evaluate_with_options("text == 42", &vars, &strict));
+
+    // Values of the same type still compare as before
+    assert!(evaluate_with_options(
+        "number == other_number",
+        &vars,
+        &strict
+    ));
+    assert!(evaluate_with_options("number == 42", &vars, &strict));
+    assert!(evaluate_with_options("text == '42'", &vars, &strict));
+}
+
+#[test]
+fn test_pixel_suffixes_compare_numerically() {
+    let vars = json!({ "width": "150px", "height": "80 px", "plain": 120 });
+    assert!(evaluate("width > '100px'", &vars));
+    assert!(!evaluate("width < '100px'", &vars));
+    assert!(evaluate("width >= 150", &vars));
+    assert!(evaluate("height <= '80px'", &vars));
+    assert!(evaluate("plain < width", &vars));
+    // Different units are not comparable
+    assert!(!evaluate("width > '99.5%'", &vars));
+}
+
+#[test]
+fn test_time_suffixes_compare_numerically() {
+    let vars = json!({ "duration": "1.5s", "latency": "250ms", "limit": "300ms" });
+    assert!(evaluate("duration < '2s'", &vars));
+    assert!(evaluate("latency < limit", &vars));
+    assert!(evaluate("latency >= '250ms'", &vars));
+    // Units are not converted
+    assert!(!evaluate("duration < '2000ms'", &vars));
+    assert!(!evaluate("duration > '100ms'", &vars));
+}
+
+#[test]
+fn test_unknown_suffix_fails_the_comparison() {
+    let vars = json!({ "width": "150qx", "size": "12kb", "count": "7 items" });
+    assert!(!evaluate("width > '100px'", &vars));
+    assert!(!evaluate("width < '100px'", &vars));
+    assert!(!evaluate("size > 1", &vars));
+    assert!(!evaluate("size < 100", &vars));
+    assert!(!evaluate("count >= 1", &vars));
+}
+
+#[test]
+fn test_unit_suffixes_are_configurable() {
+    let vars = json!({ "size": "12kb", "width": "150px" });
+    let options = EvalOptions {
+        unit_suffixes: &["kb", "mb"],
+        ..EvalOptions::default()
+    };
+    assert!(evaluate_with_options("size > '10kb'", &vars, &options));
+    assert!(!evaluate_with_options("width > 100", &vars, &options));
+
+    let no_units = EvalOptions {
+        unit_suffixes: &[],
+        ..EvalOptions::default()
+    };
+    assert!(!evaluate_with_options("width > 100", &vars, &no_units));
+    assert_eq!(EvalOptions::default().unit_suffixes, DEFAULT_UNIT_SUFFIXES);
+}
+
+#[test]
+fn test_evaluate_checked_returns_the_result() {
+    let vars = json!({ "env": { "status": "done", "items": ["a", "b"] } });
+
+    assert_eq!(evaluate_checked("env.status == 'done'", &vars), Ok(true));
+    assert_eq!(evaluate_checked("len(env.items) > 2", &vars), Ok(false));
+    assert_eq!(
+        evaluate_checked("contains(env.items, 'b') && !env.missing_flag", &vars),
+        Err(EvalError::VariableNotFound(
+            "'env.missing_flag' is not defined".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_evaluate_checked_classifies_failures() {
+    let vars = json!({ "env": { "count": 3, "name": "Ada" } });
+    let kind = |expr: &str| match evaluate_checked(expr, &vars) {
+        Err(EvalError::UnknownFunction(_)) => "unknown function",
+        Err(EvalError::VariableNotFound(_)) => "variable not found",
+        Err(EvalError::Malformed(_)) => "malformed",
+        Err(EvalError::TypeMismatch(_)) => "type mismatch",
+        Ok(_) => "ok",
+    };
+
+    assert_eq!(kind("lenght(env.name) > 1"), "unknown function");
+    assert_eq!(kind("isBlank(env.name)"), "unknown function");
+    assert_eq!(kind("env.nmae == 'Ada'"), "variable not found");
+    assert_eq!(kind("contains(env.tags, 'x')"), "variable not found");
+    assert_eq!(kind("len(env.missing) > 0"), "variable not found");
+    assert_eq!(kind("(env.count > 1"), "malformed");
+    assert_eq!(kind("contains(env.name)"), "malformed");
+    assert_eq!(kind("startsWith(env.count, '3')"), "type mismatch");
+    assert_eq!(kind("len(env.count) > 0"), "type mismatch");
+    assert_eq!(kind("numberFormat(env.name, 2)"), "type mismatch");
+}
+
+#[test]
+fn test_evaluate_checked_messages_name_the_problem() {
+    let vars = json!({ "env": {} });
+
+    let err = evaluate_checked("lenght(env.rows) > 1", &vars).unwrap_err();
+    assert_eq!(err.to_string(), "unknown function 'lenght'");
+
+    let nested = format!("{}true{}", "not(".repeat(150), ")".repeat(150));
+    let err = evaluate_checked(&nested, &vars).unwrap_err();
+    assert!(matches!(err, EvalError::Malformed(_)), "{err:?}");
+    assert!(err.to_string().contains("nested deeper"), "{err}");
+}
+
+#[test]
+fn test_evaluate_keeps_falling_back_to_false() {
+    let vars = json!({ "env": { "ready": true } });
+
+    // The lenient API is unchanged: failures are false, and `||` still recovers
+    assert!(!evaluate("lenght(env.rows) > 1", &vars));
+    assert!(evaluate("env.missing || env.ready", &vars));
+    assert!(evaluate_checked("env.missing || env.ready", &vars).is_err());
+}
+
+#[test]
+fn test_compiled_expression_matches_evaluate() {
+    let rows = json!([
+        {"env": {"status": "done", "count": 3, "tags": ["a"], "rows": [{"total": 10}]}},
+        {"env": {"status": "pending", "count": 0, "tags": [], "rows": []}},
+        {"env": {}},
+    ]);
+    let expressions = [
+        "env.status == 'done'",
+        "!env.count || env.status == 'pending'",
+        "(env.count > 1 || contains(env.tags, 'a')) && !isEmpty(env.rows)",
+        "env.status in ('done', 'pending') && len(env.tags) == 0",
+        "between(env.rows[0].total, 5, 15)",
+        "env.missing || env.status == 'done'",
+        "len(filter(env.rows, 'total > 5')) == 1",
+        "always()",
+    ];
+
+    for expression in expressions {
+        let compiled = Expression::compile(expression).unwrap();
+        for vars in rows.as_array().unwrap() {
+            assert_eq!(
+                compiled.evaluate(vars),
+                evaluate(expression, vars),
+                "{expression} with {vars}"
+            );
+            assert_eq!(
+                compiled.evaluate_checked(vars),
+                evaluate_checked(expression, vars),
+                "{expression} with {vars}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_compile_rejects_invalid_expressions() {
+    let unknown = Expression::compile("lenght(env.rows) > 1 || env.done").unwrap_err();
+    assert_eq!(
+        unknown,
+        EvalError::UnknownFunction("unknown function 'lenght'".to_string())
+    );
+    assert!(matches!(
+        Expression::compile("(env.a || env.b"),
+        Err(EvalError::Malformed(_))
+    ));
+
+    let options = EvalOptions {
+        max_depth: 10,
+        ..EvalOptions::default()
+    };
+    assert!(Expression::compile_with_options(&format!("{}flag", "!".repeat(9)), &options).is_ok());
+    let too_deep =
+        Expression::compile_with_options(&format!("{}flag", "!".repeat(10)), &options).unwrap_err();
+    assert!(too_deep.to_string().contains("limit of 10"), "{too_deep}");
+
+    // Undefined variables depend on the variables, so they are only found when evaluating
+    let compiled = Expression::compile("env.missing == 'x'").unwrap();
+    assert!(!compiled.evaluate(&json!({ "env": {} })));
+    assert!(matches!(
+        compiled.evaluate_checked(&json!({ "env": {} })),
+        Err(EvalError::VariableNotFound(_))
+    ));
+}
+
+#[test]
+fn test_compiled_expression_is_reusable() {
+    let compiled = Expression::compile("env.page >= 3 && env.status == ‘done’").unwrap();
+    assert_eq!(compiled.source(), "env.page >= 3 && env.status == 'done'");
+
+    let results: Vec<bool> = (1..=4)
+        .map(|page| compiled.evaluate(&json!({ "env": { "page": page, "status": "done" } })))
+        .collect();
+    assert_eq!(results, vec![false, false, true, true]);
+}
@@ -0,0 +1,180 @@
+use terminator::{AutomationError, ElementBounds};
+use terminator_mcp_agent::find_elements::{collect_matches, Describe, ElementMatch};
+
+/// A node of a mock accessibility tree
+struct Node {
+    role: &'static str,
+    name: &'static str,
+    text: &'static str,
+    y: f64,
+    children: Vec<Node>,
+}
+
+fn node(role: &'static str, name: &'static str, text: &'static str, y: f64) -> Node {
+    Node {
+        role,
+        name,
+        text,
+        y,
+        children: Vec::new(),
+    }
+}
+
+impl Describe for &Node {
+    fn describe(&self) -> ElementMatch {
+        ElementMatch {
+            role: self.role.to_string(),
+            name: Some(self.name.to_string()).filter(|s| !s.is_empty()),
+            text: Some(self.text.to_string()).filter(|s| !s.is_empty()),
+            bounds: Some(ElementBounds {
+                x: 0.0,
+                y: self.y,
+                width: 200.0,
+                height: 20.0,
+            }),
+        }
+    }
+}
+
+/// An inbox window: a toolbar with one Refresh button and a list of three messages
+fn inbox() -> Node {
+    let mut list = node("List", "Messages", "", 40.0);
+    list.children = vec![
+        node("ListItem", "Invoice 42", "Due Friday", 40.0),
+        node("ListItem", "Lunch", "", 60.0),
+        node("ListItem", "Invoice 43", "Paid", 80.0),
+    ];
+    let mut toolbar = node("ToolBar", "", "", 0.0);
+    toolbar.children = vec![node("Button", "Refresh", "", 0.0)];
+    let mut window = node("Window", "Inbox", "", 0.0);
+    window.children = vec![toolbar, list];
+    window
+}
+
+/// Searches the tree the way a locator does: `role:<role>` and `name:<name>` parts
+/// joined with `|` must all match, and nothing matching is `ElementNotFound`
+fn find_all<'a>(root: &'a Node, selector: &str) -> Result<Vec<&'a Node>, AutomationError> {
+    let mut predicates = Vec::new();
+    for part in selector.split('|') {
+        match part.split_once(':') {
+            Some(("role", role)) => predicates.push((true, role)),
+            Some(("name", name)) => predicates.push((false, name)),
+            _ => {
+                return Err(AutomationError::InvalidSelector(format!(
+                    "Unknown selector format: {selector}"
+                )))
+            }
+        }
+    }
+
+    fn walk<'a>(node: &'a Node, predicates: &[(bool, &str)], found: &mut Vec<&'a Node>) {
+        let matches = predicates.iter().all(|&(is_role, value)| {
+            if is_role {
+                node.role.eq_ignore_ascii_case(value)
+            } else {
+                node.name == value
+            }
+        });
+        if matches {
+            found.push(node);
+        }
+        for child in &node.children {
+            walk(child, predicates, found);
+        }
+    }
+    let mut found = Vec::new();
+    walk(root, &predicates, &mut found);
+    if found.is_empty() {
+        return Err(AutomationError::ElementNotFound(format!(
+            "No element matched {selector}"
+        )));
+    }
+    Ok(found)
+}
+
+#[test]
+fn test_no_match_is_an_empty_list() {
+    let tree = inbox();
+
+    let matches = collect_matches(find_all(&tree, "role:CheckBox"), None).unwrap();
+    assert_eq!(matches.total, 0);
+    assert!(!matches.truncated);
+    assert!(matches.elements.is_empty());
+    assert_eq!(
+        serde_json::to_value(&matches).unwrap()["elements"],
+        serde_json::json!([])
+    );
+
+    // A search that timed out before anything appeared is not an error either
+    let timed_out: Result<Vec<&Node>, _> = Err(AutomationError::Timeout(
+        "Timed out after 1000ms".to_string(),
+    ));
+    assert_eq!(collect_matches(timed_out, Some(5)).unwrap().total, 0);
+}
+
+#[test]
+fn test_one_match() {
+    let tree = inbox();
+
+    let matches = collect_matches(find_all(&tree, "role:Button"), None).unwrap();
+    assert_eq!(matches.total, 1);
+    assert_eq!(
+        matches.elements,
+        [ElementMatch {
+            role: "Button".to_string(),
+            name: Some("Refresh".to_string()),
+            text: None,
+            bounds: Some(ElementBounds {
+                x: 0.0,
+                y: 0.0,
+                width: 200.0,
+                height: 20.0
+            }),
+        }]
+    );
+}
+
+#[test]
+fn test_many_matches_keep_their_order() {
+    let tree = inbox();
+
+    let matches = collect_matches(find_all(&tree, "role:ListItem"), None).unwrap();
+    let rows: Vec<_> = matches
+        .elements
+        .iter()
+        .map(|e| (e.name.as_deref(), e.text.as_deref()))
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            (Some("Invoice 42"), Some("Due Friday")),
+            (Some("Lunch"), None),
+            (Some("Invoice 43"), Some("Paid")),
+        ]
+    );
+    assert_eq!(matches.total, 3);
+    assert!(!matches.truncated);
+}
+
+#[test]
+fn test_limit_caps_the_matches_but_not_the_total() {
+    let tree = inbox();
+
+    let matches = collect_matches(find_all(&tree, "role:ListItem"), Some(2)).unwrap();
+    assert_eq!(matches.total, 3);
+    assert!(matches.truncated);
+    assert_eq!(matches.elements.len(), 2);
+    assert_eq!(matches.elements[1].name.as_deref(), Some("Lunch"));
+
+    let matches = collect_matches(find_all(&tree, "role:ListItem"), Some(10)).unwrap();
+    assert_eq!(matches.elements.len(), 3);
+    assert!(!matches.truncated);
+}
+
+#[test]
+fn test_invalid_selector_is_still_an_error() {
+    let tree = inbox();
+
+    let error = collect_matches(find_all(&tree, "bogus::selector"), None).unwrap_err();
+    assert!(matches!(error, AutomationError::InvalidSelector(_)));
+}
@@ -0,0 +1,107 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use terminator::AutomationError;
+use terminator_mcp_agent::focused_input::{
+    press_keys, type_into_focused, FocusProvider, FocusedElement, FocusedInputError,
+};
+
+/// A control that records the input it receives
+#[derive(Clone, Default)]
+struct MockControl {
+    typed: Rc<RefCell<String>>,
+    keys: Rc<RefCell<Vec<String>>>,
+    reject_input: bool,
+}
+
+impl FocusedElement for MockControl {
+    fn type_text(&self, text: &str, _use_clipboard: bool) -> Result<(), AutomationError> {
+        if self.reject_input {
+            return Err(AutomationError::PlatformError("read-only".to_string()));
+        }
+        self.typed.borrow_mut().push_str(text);
+        Ok(())
+    }
+
+    fn press_key(&self, key: &str) -> Result<(), AutomationError> {
+        if self.reject_input {
+            return Err(AutomationError::PlatformError("read-only".to_string()));
+        }
+        self.keys.borrow_mut().push(key.to_string());
+        Ok(())
+    }
+}
+
+/// A desktop whose focus is either on `focused` or nowhere
+struct MockDesktop {
+    focused: Option<MockControl>,
+}
+
+impl FocusProvider for MockDesktop {
+    type Element = MockControl;
+
+    fn focused(&self) -> Result<MockControl, AutomationError> {
+        self.focused
+            .clone()
+            .ok_or_else(|| AutomationError::ElementNotFound("no focused element".to_string()))
+    }
+}
+
+#[test]
+fn test_type_into_focused_types_into_focused_control() {
+    let control = MockControl::default();
+    let desktop = MockDesktop {
+        focused: Some(control.clone()),
+    };
+
+    type_into_focused(&desktop, "hello", true).unwrap();
+    type_into_focused(&desktop, " world", false).unwrap();
+
+    assert_eq!(control.typed.borrow().as_str(), "hello world");
+}
+
+#[test]
+fn test_press_keys_presses_in_order() {
+    let control = MockControl::default();
+    let desktop = MockDesktop {
+        focused: Some(control.clone()),
+    };
+    let keys = vec![
+        "{Ctrl}a".to_string(),
+        "{Delete}".to_string(),
+        "{Tab}".to_string(),
+    ];
+
+    press_keys(&desktop, &keys).unwrap();
+
+    assert_eq!(*control.keys.borrow(), keys);
+}
+
+#[test]
+fn test_no_focus_is_a_clear_error() {
+    let desktop = MockDesktop { focused: None };
+
+    let err = type_into_focused(&desktop, "hello", true).err().unwrap();
+    assert!(matches!(err, FocusedInputError::NoFocus(_)));
+    assert!(err.to_string().starts_with("No element has keyboard focus"));
+
+    let err = press_keys(&desktop, &["{Enter}".to_string()])
+        .err()
+        .unwrap();
+    assert!(matches!(err, FocusedInputError::NoFocus(_)));
+}
+
+#[test]
+fn test_rejected_input_is_reported_separately() {
+    let desktop = MockDesktop {
+        focused: Some(MockControl {
+            reject_input: true,
+            ..Default::default()
+        }),
+    };
+
+    let err = type_into_focused(&desktop, "hello", true).err().unwrap();
+    assert!(matches!(
+        err,
+        FocusedInputError::Input(AutomationError::PlatformError(_))
+    ));
+}
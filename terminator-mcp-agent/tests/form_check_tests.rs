@@ -0,0 +1,89 @@
+use terminator_mcp_agent::form_check::{check_fields, FieldReading, FieldStatus};
+
+fn found(value: &str, required: Option<bool>) -> FieldReading {
+    FieldReading::Found {
+        value: Some(value.to_string()),
+        required,
+    }
+}
+
+#[test]
+fn test_reports_empty_required_fields() {
+    let report = check_fields(vec![
+        ("name:First name".to_string(), found("Ada", Some(true))),
+        ("name:Last name".to_string(), found("", Some(true))),
+        ("name:Email".to_string(), found("   ", None)),
+        (
+            "name:Phone".to_string(),
+            FieldReading::Found {
+                value: None,
+                required: Some(true),
+            },
+        ),
+    ]);
+
+    assert!(!report.complete);
+    assert_eq!(
+        report.empty_required,
+        vec!["name:Last name", "name:Email", "name:Phone"]
+    );
+    assert!(report.invalid.is_empty());
+    assert_eq!(report.fields[0].status, FieldStatus::Filled);
+    assert_eq!(report.fields[2].status, FieldStatus::Empty);
+}
+
+#[test]
+fn test_optional_fields_may_stay_empty() {
+    let report = check_fields(vec![
+        ("name:First name".to_string(), found("Ada", Some(true))),
+        ("name:Middle name".to_string(), found("", Some(false))),
+    ]);
+
+    assert!(report.complete);
+    assert!(report.empty_required.is_empty());
+    assert_eq!(report.fields[1].status, FieldStatus::Empty);
+    assert!(!report.fields[1].required);
+}
+
+#[test]
+fn test_completed_form() {
+    let report = check_fields(vec![
+        ("name:First name".to_string(), found("Ada", Some(true))),
+        ("name:Last name".to_string(), found("Lovelace", None)),
+        (
+            "name:Email".to_string(),
+            found("ada@example.com", Some(true)),
+        ),
+    ]);
+
+    assert!(report.complete);
+    assert!(report.empty_required.is_empty());
+    assert!(report.invalid.is_empty());
+    assert!(report
+        .fields
+        .iter()
+        .all(|f| f.status == FieldStatus::Filled));
+}
+
+#[test]
+fn test_missing_and_unreadable_fields_are_invalid() {
+    let report = check_fields(vec![
+        ("name:First name".to_string(), found("Ada", Some(true))),
+        (
+            "name:Nickname".to_string(),
+            FieldReading::NotFound("timed out".to_string()),
+        ),
+        (
+            "name:Avatar".to_string(),
+            FieldReading::Unreadable("no value pattern".to_string()),
+        ),
+    ]);
+
+    assert!(!report.complete);
+    assert_eq!(report.invalid, vec!["name:Nickname", "name:Avatar"]);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["fields"][1]["status"], "not_found");
+    assert_eq!(json["fields"][1]["error"], "timed out");
+    assert_eq!(json["fields"][2]["status"], "unreadable");
+}
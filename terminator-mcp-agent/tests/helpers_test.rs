@@ -1,6 +1,14 @@
 // Import the functions to be tested
-use serde_json::json;
-use terminator_mcp_agent::helpers::substitute_variables;
+use rmcp::model::{CallToolResult, Content};
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::Mutex;
+use terminator::AutomationError;
+use terminator_mcp_agent::helpers::{build_element_not_found_error, substitute_variables};
+use terminator_mcp_agent::run_bundle::{RunOutcome, ToolBackend};
+use terminator_mcp_agent::utils::{DesktopWrapper, ExecuteSequenceArgs};
+use tokio_util::sync::CancellationToken;
 
 #[test]
 fn test_substitute_no_variables() {
@@ -106,3 +114,215 @@ fn test_substitute_multiple_in_one_string() {
     substitute_variables(&mut args, &variables);
     assert_eq!(args, json!({"key": "Hello Alex, welcome to the machine!"}));
 }
+
+/// Logs in by script and records which tools were invoked
+struct LoginDesktop {
+    login_status: &'static str,
+    invoked: Mutex<Vec<String>>,
+}
+
+impl ToolBackend for LoginDesktop {
+    fn call_tool(
+        &self,
+        tool_name: &str,
+        _arguments: &Value,
+        _variables: &Value,
+        _cancellation: CancellationToken,
+    ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send {
+        self.invoked.lock().unwrap().push(tool_name.to_string());
+        let content = if tool_name == "run_command" {
+            json!({"result": {"set_env": {"login_status": self.login_status}}})
+        } else {
+            json!({"action": tool_name, "status": "success"})
+        };
+        std::future::ready(Ok(CallToolResult::success(vec![
+            Content::json(content).unwrap()
+        ])))
+    }
+}
+
+/// Checks the login status, then clicks "Sign in" unless already logged in
+async fn sign_in(login_status: &'static str) -> (Vec<String>, RunOutcome) {
+    let workflow: ExecuteSequenceArgs = serde_json::from_value(json!({
+        "steps": [
+            {"tool_name": "run_command", "arguments": {"engine": "javascript", "run": "..."}},
+            {
+                "tool_name": "click_element",
+                "arguments": {"selector": "role:Button|name:Sign in"},
+                "precondition": "env.login_status == 'logged_in'"
+            }
+        ]
+    }))
+    .unwrap();
+    let desktop = LoginDesktop {
+        login_status,
+        invoked: Mutex::new(Vec::new()),
+    };
+    let summary = DesktopWrapper::new()
+        .unwrap()
+        .execute_sequence_with(&desktop, &CancellationToken::new(), workflow)
+        .await
+        .unwrap();
+    let invoked = desktop.invoked.into_inner().unwrap();
+    (invoked, RunOutcome::from_summary(&summary))
+}
+
+#[tokio::test]
+async fn test_step_gate_satisfied_precondition_skips_action() {
+    let (invoked, outcome) = sign_in("logged_in").await;
+
+    assert_eq!(invoked, vec!["run_command"]);
+    assert_eq!(outcome.step_statuses, vec!["success", "satisfied"]);
+}
+
+#[tokio::test]
+async fn test_step_gate_unsatisfied_precondition_runs_action() {
+    let (invoked, outcome) = sign_in("logged_out").await;
+
+    assert_eq!(invoked, vec!["run_command", "click_element"]);
+    assert_eq!(outcome.step_statuses, vec!["success", "success"]);
+}
+
+#[test]
+fn test_step_gate_if_condition_checked_before_precondition() {
+    use terminator_mcp_agent::expression_eval::IndexedContext;
+    use terminator_mcp_agent::helpers::{evaluate_step_gate, StepGate};
+
+    let variables = json!({"enabled": false, "done": true});
+    let variables = IndexedContext::new(&variables);
+    assert_eq!(
+        evaluate_step_gate(Some("enabled == true"), Some("done"), &variables),
+        StepGate::ConditionNotMet
+    );
+    assert_eq!(
+        evaluate_step_gate(Some("always()"), Some("done"), &variables),
+        StepGate::AlreadySatisfied
+    );
+    assert_eq!(
+        evaluate_step_gate(Some("always()"), None, &variables),
+        StepGate::Run
+    );
+}
+
+#[test]
+fn test_env_updates_from_run_command_result() {
+    use terminator_mcp_agent::helpers::env_updates_from_result;
+
+    let result = json!({
+        "status": "success",
+        "result": {"content": [{"result": {"set_env": {"queue_empty": true}, "count": 2, "logs": []}}]}
+    });
+    let updates = env_updates_from_result("run_command", &result);
+    assert_eq!(updates["queue_empty"], json!(true));
+    assert_eq!(updates["count"], json!(2));
+    assert!(!updates.contains_key("logs"));
+
+    let failed = json!({"status": "error", "result": result["result"].clone()});
+    assert!(env_updates_from_result("run_command", &failed).is_empty());
+}
+
+#[test]
+fn test_script_object_outputs_merge_into_env() {
+    use terminator_mcp_agent::helpers::{env_updates_from_result, merge_step_outputs};
+
+    let result = json!({
+        "status": "success",
+        "result": {"content": [{"result": {
+            "total": 120,
+            "currency": "EUR",
+            "set_env": {"total": 0, "vendor": "ACME"},
+            "status": "success"
+        }}]}
+    });
+    let outputs = env_updates_from_result("run_command", &result);
+
+    let mut env = json!({"total": 99, "user": "ada"})
+        .as_object()
+        .cloned()
+        .unwrap();
+    merge_step_outputs(&mut env, outputs, None);
+
+    // Returned fields replace existing variables and win over set_env
+    assert_eq!(
+        serde_json::Value::Object(env),
+        json!({"total": 120, "currency": "EUR", "vendor": "ACME", "user": "ada"})
+    );
+}
+
+#[test]
+fn test_namespaced_outputs_leave_top_level_variables_alone() {
+    use terminator_mcp_agent::helpers::merge_step_outputs;
+
+    let outputs = json!({"total": 120, "currency": "EUR"})
+        .as_object()
+        .cloned()
+        .unwrap();
+    let mut env = json!({"total": 99, "parse_invoice": {"pages": 2}, "extract": "stale"})
+        .as_object()
+        .cloned()
+        .unwrap();
+
+    merge_step_outputs(&mut env, outputs.clone(), Some("parse_invoice"));
+    merge_step_outputs(&mut env, outputs, Some("extract"));
+
+    assert_eq!(
+        serde_json::Value::Object(env),
+        json!({
+            "total": 99,
+            "parse_invoice": {"pages": 2, "total": 120, "currency": "EUR"},
+            "extract": {"total": 120, "currency": "EUR"}
+        })
+    );
+}
+
+#[test]
+fn test_substitute_null_coalescing() {
+    let vars = json!({ "inputs": { "region": null }, "default_region": "eu-west" });
+    let mut args = json!({
+        "region": "{{inputs.region ?? default_region}}",
+        "label": "Region: {{inputs.region ?? 'unknown'}}"
+    });
+    substitute_variables(&mut args, &vars);
+    assert_eq!(args["region"], json!("eu-west"));
+    assert_eq!(args["label"], json!("Region: unknown"));
+}
+
+#[test]
+fn test_element_error_carries_category() {
+    let category = |error: anyhow::Error| {
+        build_element_not_found_error("role:Button|name:Save", None, None, error)
+            .data
+            .unwrap()["error_category"]
+            .clone()
+    };
+
+    assert_eq!(
+        category(AutomationError::InvalidSelector("role:".to_string()).into()),
+        json!("user")
+    );
+    assert_eq!(
+        category(AutomationError::Timeout("no match in 5s".to_string()).into()),
+        json!("transient")
+    );
+    assert_eq!(
+        category(AutomationError::PlatformError("COM not initialized".to_string()).into()),
+        json!("system")
+    );
+    // Plain errors from the search mean no selector matched
+    assert_eq!(category(anyhow::anyhow!("not found")), json!("user"));
+}
+
+#[test]
+fn test_step_succeeded_reads_status_and_success() {
+    use terminator_mcp_agent::helpers::step_succeeded;
+
+    assert!(step_succeeded(&json!({"status": "success"})));
+    assert!(step_succeeded(&json!({"status": "done", "success": true})));
+    // Without a status, only an explicit `success: false` is a failure
+    assert!(step_succeeded(&json!({"result": 1})));
+    assert!(!step_succeeded(&json!({"success": false})));
+    assert!(!step_succeeded(&json!({"status": "error"})));
+    assert!(!step_succeeded(
+        &json!({"status": "skipped", "error": "Element not found"})
+    ));
+}
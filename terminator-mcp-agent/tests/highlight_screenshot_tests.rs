@@ -0,0 +1,216 @@
+use image::{Rgba, RgbaImage};
+use std::cell::RefCell;
+use terminator::Monitor;
+use terminator_mcp_agent::highlight_screenshot::{
+    capture_highlighted, BoundsSource, HighlightStyle, PixelRect, Rect,
+};
+
+const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+const BACKGROUND: Rgba<u8> = Rgba([20, 20, 20, 255]);
+
+/// An element that only knows where it is
+struct FakeElement((f64, f64, f64, f64));
+
+impl BoundsSource for FakeElement {
+    fn bounds(&self) -> Result<(f64, f64, f64, f64), String> {
+        Ok(self.0)
+    }
+}
+
+fn monitor(id: &str, x: i32, y: i32, width: u32, height: u32) -> Monitor {
+    Monitor {
+        id: id.to_string(),
+        name: format!("Display {id}"),
+        is_primary: x == 0 && y == 0,
+        width,
+        height,
+        x,
+        y,
+        scale_factor: 1.0,
+        work_area: None,
+    }
+}
+
+/// A primary 200x100 monitor with a second one to its right
+fn two_monitors() -> Vec<Monitor> {
+    vec![monitor("1", 0, 0, 200, 100), monitor("2", 200, 0, 200, 100)]
+}
+
+/// Captures `monitor` as a blank image `scale` times its size, recording which monitor
+async fn capture(
+    monitor: Monitor,
+    scale: u32,
+    captured: &RefCell<Vec<String>>,
+) -> Result<RgbaImage, String> {
+    captured.borrow_mut().push(monitor.id.clone());
+    Ok(RgbaImage::from_pixel(
+        monitor.width * scale,
+        monitor.height * scale,
+        BACKGROUND,
+    ))
+}
+
+#[tokio::test]
+async fn test_element_on_a_secondary_monitor_is_outlined_in_its_capture() {
+    let captured = RefCell::new(Vec::new());
+    let element = FakeElement((250.0, 20.0, 40.0, 30.0));
+
+    let shot = capture_highlighted(
+        &element,
+        &two_monitors(),
+        |m| capture(m, 1, &captured),
+        &HighlightStyle {
+            border_width: 2,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(*captured.borrow(), vec!["2"]);
+    assert_eq!(
+        shot.bounds,
+        Rect {
+            x: 250.0,
+            y: 20.0,
+            width: 40.0,
+            height: 30.0
+        }
+    );
+    assert_eq!(
+        shot.highlight,
+        PixelRect {
+            x: 50,
+            y: 20,
+            width: 40,
+            height: 30
+        }
+    );
+    assert!(!shot.clipped);
+
+    // A two-pixel red outline, shifted by the monitor's position
+    for (x, y) in [(50, 20), (51, 21), (89, 49), (88, 35), (70, 20), (70, 49)] {
+        assert_eq!(*shot.image.get_pixel(x, y), RED, "({x}, {y})");
+    }
+    for (x, y) in [(52, 22), (70, 35), (49, 20), (90, 49), (50, 50)] {
+        assert_eq!(*shot.image.get_pixel(x, y), BACKGROUND, "({x}, {y})");
+    }
+}
+
+#[tokio::test]
+async fn test_element_spanning_monitors_uses_the_one_showing_most_of_it() {
+    let captured = RefCell::new(Vec::new());
+    // 10px on the primary monitor, 30px on the second
+    let element = FakeElement((190.0, 10.0, 40.0, 20.0));
+
+    let shot = capture_highlighted(
+        &element,
+        &two_monitors(),
+        |m| capture(m, 1, &captured),
+        &HighlightStyle::default(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(shot.monitor.id, "2");
+    assert!(shot.clipped);
+    assert_eq!(
+        shot.highlight,
+        PixelRect {
+            x: 0,
+            y: 10,
+            width: 30,
+            height: 20
+        }
+    );
+    // The left edge lies on the other monitor, so the rectangle is open on that side
+    assert_eq!(*shot.image.get_pixel(0, 20), BACKGROUND);
+    assert_eq!(*shot.image.get_pixel(0, 10), RED);
+    assert_eq!(*shot.image.get_pixel(29, 20), RED);
+}
+
+#[tokio::test]
+async fn test_bounds_are_scaled_to_a_high_dpi_capture() {
+    let captured = RefCell::new(Vec::new());
+    let element = FakeElement((10.0, 10.0, 20.0, 10.0));
+
+    let shot = capture_highlighted(
+        &element,
+        &two_monitors(),
+        |m| capture(m, 2, &captured),
+        &HighlightStyle {
+            color_bgr: 0xFF0000,
+            border_width: 1,
+        },
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(shot.image.dimensions(), (400, 200));
+    assert_eq!(
+        shot.highlight,
+        PixelRect {
+            x: 20,
+            y: 20,
+            width: 40,
+            height: 20
+        }
+    );
+    // BGR 0xFF0000 is blue
+    assert_eq!(*shot.image.get_pixel(20, 20), Rgba([0, 0, 255, 255]));
+    assert_eq!(*shot.image.get_pixel(59, 39), Rgba([0, 0, 255, 255]));
+}
+
+#[tokio::test]
+async fn test_partly_and_entirely_off_screen_elements() {
+    let captured = RefCell::new(Vec::new());
+
+    // Hanging off the top-left corner of the desktop
+    let shot = capture_highlighted(
+        &FakeElement((-20.0, -10.0, 60.0, 40.0)),
+        &two_monitors(),
+        |m| capture(m, 1, &captured),
+        &HighlightStyle::default(),
+    )
+    .await
+    .unwrap();
+    assert!(shot.clipped);
+    assert_eq!(
+        shot.highlight,
+        PixelRect {
+            x: 0,
+            y: 0,
+            width: 40,
+            height: 30
+        }
+    );
+    assert_eq!(*shot.image.get_pixel(0, 0), BACKGROUND);
+    assert_eq!(*shot.image.get_pixel(39, 0), RED);
+    assert_eq!(*shot.image.get_pixel(0, 29), RED);
+
+    // Below every monitor: nothing is captured
+    captured.borrow_mut().clear();
+    let err = capture_highlighted(
+        &FakeElement((50.0, 500.0, 40.0, 30.0)),
+        &two_monitors(),
+        |m| capture(m, 1, &captured),
+        &HighlightStyle::default(),
+    )
+    .await
+    .unwrap_err();
+    assert!(err.starts_with("Element is entirely off-screen"), "{err}");
+    assert!(captured.borrow().is_empty());
+
+    let err = capture_highlighted(
+        &FakeElement((50.0, 50.0, 0.0, 30.0)),
+        &two_monitors(),
+        |m| capture(m, 1, &captured),
+        &HighlightStyle::default(),
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        err.starts_with("Element has an empty bounding box"),
+        "{err}"
+    );
+}
@@ -0,0 +1,111 @@
+use std::time::Duration;
+use terminator_mcp_agent::telemetry::{
+    action_type, latency_report, record_tool_latency, LatencyTracker, StepSpan,
+};
+
+#[test]
+fn test_report_percentiles_after_timed_actions() {
+    let mut tracker = LatencyTracker::new(100);
+    for ms in 1..=100 {
+        tracker.record("click", Duration::from_millis(ms));
+    }
+    tracker.record("type", Duration::from_millis(40));
+
+    let report = tracker.report();
+    let click = &report.actions["click"];
+    assert_eq!(click.count, 100);
+    assert_eq!(click.p50_ms, 50.0);
+    assert_eq!(click.p90_ms, 90.0);
+    assert_eq!(click.p99_ms, 99.0);
+    assert_eq!(click.max_ms, 100.0);
+    assert!(click.p50_ms <= click.p90_ms && click.p90_ms <= click.p99_ms);
+
+    // A single sample is every percentile
+    let typing = &report.actions["type"];
+    assert_eq!(typing.count, 1);
+    assert_eq!(typing.p50_ms, 40.0);
+    assert_eq!(typing.p99_ms, 40.0);
+    assert!(!report.actions.contains_key("read"));
+}
+
+#[test]
+fn test_tool_percentiles_from_synthetic_durations() {
+    let mut tracker = LatencyTracker::new(100);
+    for ms in 1..=20 {
+        tracker.record_tool("click_element", Duration::from_millis(ms * 10));
+    }
+    tracker.record_tool("invoke_element", Duration::from_millis(5));
+
+    let report = tracker.report();
+    let click = &report.tools["click_element"];
+    assert_eq!(click.count, 20);
+    assert_eq!(click.p50_ms, 100.0);
+    assert_eq!(click.p95_ms, 190.0);
+    assert_eq!(click.max_ms, 200.0);
+    assert_eq!(report.tools["invoke_element"].p95_ms, 5.0);
+
+    // Both tools also count towards their shared action type
+    assert_eq!(report.actions["click"].count, 21);
+    assert!(!report.tools.contains_key("click"));
+
+    tracker.clear();
+    assert!(tracker.report().tools.is_empty());
+}
+
+#[test]
+fn test_latency_report_covers_recorded_tool_calls() {
+    record_tool_latency("latency_probe_tool", Duration::from_millis(30));
+    record_tool_latency("latency_probe_tool", Duration::from_millis(10));
+
+    let report = latency_report();
+    let probe = &report.tools["latency_probe_tool"];
+    assert_eq!(probe.count, 2);
+    assert_eq!(probe.p50_ms, 10.0);
+    assert_eq!(probe.p95_ms, 30.0);
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["tools"]["latency_probe_tool"]["count"], 2);
+    assert_eq!(json["tools"]["latency_probe_tool"]["p95_ms"], 30.0);
+}
+
+#[test]
+fn test_samples_are_bounded() {
+    let mut tracker = LatencyTracker::new(10);
+    // 100 slow samples followed by 10 fast ones; only the fast ones are kept
+    for _ in 0..100 {
+        tracker.record("find", Duration::from_millis(500));
+    }
+    for _ in 0..10 {
+        tracker.record("find", Duration::from_millis(5));
+    }
+
+    let report = tracker.report();
+    assert_eq!(report.sample_limit, 10);
+    assert_eq!(report.actions["find"].count, 10);
+    assert_eq!(report.actions["find"].max_ms, 5.0);
+}
+
+#[test]
+fn test_action_types() {
+    assert_eq!(action_type("click_element"), "click");
+    assert_eq!(action_type("type_into_element"), "type");
+    assert_eq!(action_type("press_key"), "type");
+    assert_eq!(action_type("wait_for_element"), "find");
+    assert_eq!(action_type("get_window_tree"), "read");
+    assert_eq!(action_type("run_command"), "other");
+}
+
+#[test]
+fn test_step_spans_feed_the_global_tracker() {
+    let span = StepSpan::new("validate_element", Some("latency_probe"));
+    std::thread::sleep(Duration::from_millis(2));
+    span.end();
+
+    let report = terminator_mcp_agent::telemetry::latency_tracker()
+        .lock()
+        .unwrap()
+        .report();
+    let find = &report.actions["find"];
+    assert!(find.count >= 1);
+    assert!(find.max_ms >= 2.0);
+}
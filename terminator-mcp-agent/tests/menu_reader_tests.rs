@@ -0,0 +1,209 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use terminator_mcp_agent::menu_reader::{read_menu, MenuItem, MenuNode};
+
+/// A menu element whose submenu items are only exposed while it is expanded, behind a
+/// `Menu` container as on Windows
+#[derive(Clone)]
+struct FakeNode {
+    name: &'static str,
+    role: &'static str,
+    enabled: bool,
+    expanded: Option<Rc<Cell<bool>>>,
+    children: Vec<FakeNode>,
+    log: Rc<RefCell<Vec<String>>>,
+}
+
+impl FakeNode {
+    fn item(log: &Rc<RefCell<Vec<String>>>, name: &'static str) -> Self {
+        FakeNode {
+            name,
+            role: "MenuItem",
+            enabled: true,
+            expanded: None,
+            children: Vec::new(),
+            log: log.clone(),
+        }
+    }
+
+    fn submenu(
+        log: &Rc<RefCell<Vec<String>>>,
+        name: &'static str,
+        children: Vec<FakeNode>,
+    ) -> Self {
+        FakeNode {
+            expanded: Some(Rc::new(Cell::new(false))),
+            children,
+            ..FakeNode::item(log, name)
+        }
+    }
+
+    fn disabled(self) -> Self {
+        FakeNode {
+            enabled: false,
+            ..self
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.expanded.as_ref().is_some_and(|e| e.get())
+    }
+}
+
+impl MenuNode for FakeNode {
+    fn name(&self) -> String {
+        self.name.to_string()
+    }
+
+    fn role(&self) -> String {
+        self.role.to_string()
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    fn children(&self) -> Vec<Self> {
+        match &self.expanded {
+            Some(expanded) if !expanded.get() => Vec::new(),
+            Some(_) => vec![FakeNode {
+                name: self.name,
+                role: "Menu",
+                enabled: true,
+                expanded: None,
+                children: self.children.clone(),
+                log: self.log.clone(),
+            }],
+            None => self.children.clone(),
+        }
+    }
+
+    fn is_expanded(&self) -> Option<bool> {
+        self.expanded.as_ref().map(|e| e.get())
+    }
+
+    fn set_expanded(&self, expanded: bool) -> Result<(), String> {
+        let state = self.expanded.as_ref().ok_or("not expandable")?;
+        state.set(expanded);
+        let verb = if expanded { "expand" } else { "collapse" };
+        self.log.borrow_mut().push(format!("{verb} {}", self.name));
+        Ok(())
+    }
+}
+
+fn leaf(name: &str) -> MenuItem {
+    MenuItem {
+        name: name.to_string(),
+        role: "MenuItem".to_string(),
+        enabled: true,
+        has_submenu: false,
+        children: Vec::new(),
+    }
+}
+
+fn submenu(name: &str, children: Vec<MenuItem>) -> MenuItem {
+    MenuItem {
+        name: name.to_string(),
+        role: "MenuItem".to_string(),
+        enabled: true,
+        has_submenu: true,
+        children,
+    }
+}
+
+#[test]
+fn test_reads_two_level_menu_and_restores_it() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let file = FakeNode::submenu(
+        &log,
+        "File",
+        vec![
+            FakeNode::item(&log, "New"),
+            FakeNode::submenu(
+                &log,
+                "Recent",
+                vec![
+                    FakeNode::item(&log, "report.txt"),
+                    FakeNode::item(&log, "notes.txt"),
+                ],
+            ),
+        ],
+    );
+    let edit = FakeNode::submenu(&log, "Edit", vec![FakeNode::item(&log, "Undo").disabled()]);
+    let bar = FakeNode {
+        name: "Application",
+        role: "MenuBar",
+        children: vec![file.clone(), edit.clone()],
+        ..FakeNode::item(&log, "Application")
+    };
+
+    let read = read_menu(&bar, 5);
+
+    let mut undo = leaf("Undo");
+    undo.enabled = false;
+    assert_eq!(
+        read.menu.children,
+        vec![
+            submenu(
+                "File",
+                vec![
+                    leaf("New"),
+                    submenu("Recent", vec![leaf("report.txt"), leaf("notes.txt")]),
+                ]
+            ),
+            submenu("Edit", vec![undo]),
+        ]
+    );
+    assert_eq!(read.menu.role, "MenuBar");
+    assert_eq!(read.expanded, 3);
+    assert!(read.errors.is_empty());
+
+    // Each submenu is closed again, innermost first
+    assert_eq!(
+        *log.borrow(),
+        vec![
+            "expand File",
+            "expand Recent",
+            "collapse Recent",
+            "collapse File",
+            "expand Edit",
+            "collapse Edit",
+        ]
+    );
+    assert!(!file.is_open());
+    assert!(!edit.is_open());
+}
+
+#[test]
+fn test_open_submenu_is_read_statically_and_left_open() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let file = FakeNode::submenu(&log, "File", vec![FakeNode::item(&log, "Save")]);
+    file.set_expanded(true).unwrap();
+    log.borrow_mut().clear();
+
+    let read = read_menu(&file, 5);
+
+    assert_eq!(read.menu, submenu("File", vec![leaf("Save")]));
+    assert_eq!(read.expanded, 0);
+    assert!(log.borrow().is_empty());
+    assert!(file.is_open());
+}
+
+#[test]
+fn test_depth_limit_reports_unread_submenus() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let file = FakeNode::submenu(
+        &log,
+        "File",
+        vec![FakeNode::submenu(
+            &log,
+            "Recent",
+            vec![FakeNode::item(&log, "report.txt")],
+        )],
+    );
+
+    let read = read_menu(&file, 1);
+
+    assert_eq!(read.menu, submenu("File", vec![submenu("Recent", vec![])]));
+    assert_eq!(*log.borrow(), vec!["expand File", "collapse File"]);
+}
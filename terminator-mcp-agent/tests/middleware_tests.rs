@@ -0,0 +1,230 @@
+use rmcp::model::{CallToolResult, Content};
+use rmcp::ErrorData as McpError;
+use serde_json::json;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use terminator_mcp_agent::middleware::{
+    Action, ActionMiddleware, JsonlSink, LoggingMiddleware, MiddlewareChain, ToolLogRecord,
+};
+use terminator_mcp_agent::redaction::{Redactor, REDACTED};
+
+/// Records every action it sees and whether it succeeded
+#[derive(Default)]
+struct Observer {
+    seen: Mutex<Vec<(String, bool)>>,
+}
+
+impl ActionMiddleware for Observer {
+    fn name(&self) -> &str {
+        "observer"
+    }
+
+    fn after(&self, action: &Action<'_>, result: &mut Result<CallToolResult, McpError>) {
+        self.seen
+            .lock()
+            .unwrap()
+            .push((action.tool_name.to_string(), result.is_ok()));
+    }
+}
+
+/// Blocks one tool by name
+struct Blocker {
+    tool_name: &'static str,
+}
+
+impl ActionMiddleware for Blocker {
+    fn name(&self) -> &str {
+        "blocker"
+    }
+
+    fn before(&self, action: &Action<'_>) -> Result<(), McpError> {
+        if action.tool_name == self.tool_name {
+            return Err(McpError::invalid_request("blocked by middleware", None));
+        }
+        Ok(())
+    }
+}
+
+fn ok_result() -> Result<CallToolResult, McpError> {
+    Ok(CallToolResult::success(vec![Content::text("done")]))
+}
+
+#[tokio::test]
+async fn test_observer_sees_every_action() {
+    let observer = Arc::new(Observer::default());
+    let chain = MiddlewareChain::default().with(observer.clone());
+    let arguments = json!({});
+
+    for tool_name in ["click_element", "type_into_element", "get_window_tree"] {
+        let result = chain
+            .run(Action::new(tool_name, &arguments), || async { ok_result() })
+            .await;
+        assert!(result.is_ok());
+    }
+
+    let seen = observer.seen.lock().unwrap().clone();
+    assert_eq!(
+        seen,
+        vec![
+            ("click_element".to_string(), true),
+            ("type_into_element".to_string(), true),
+            ("get_window_tree".to_string(), true),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_blocking_middleware_short_circuits() {
+    let observer = Arc::new(Observer::default());
+    let chain = MiddlewareChain::default()
+        .with(observer.clone())
+        .with(Arc::new(Blocker {
+            tool_name: "close_element",
+        }));
+    let calls = AtomicUsize::new(0);
+    let arguments = json!({ "selector": "window:Notepad" });
+
+    let result = chain
+        .run(Action::new("close_element", &arguments), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            ok_result()
+        })
+        .await;
+    let err = result.unwrap_err();
+    assert_eq!(err.message, "blocked by middleware");
+    assert_eq!(calls.load(Ordering::SeqCst), 0, "blocked tool must not run");
+
+    let result = chain
+        .run(Action::new("click_element", &arguments), || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            ok_result()
+        })
+        .await;
+    assert!(result.is_ok());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Middleware ahead of the blocker still sees the blocked call
+    let seen = observer.seen.lock().unwrap().clone();
+    assert_eq!(
+        seen,
+        vec![
+            ("close_element".to_string(), false),
+            ("click_element".to_string(), true),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_middleware_behind_blocker_is_skipped() {
+    let observer = Arc::new(Observer::default());
+    let chain = MiddlewareChain::default()
+        .with(Arc::new(Blocker {
+            tool_name: "close_element",
+        }))
+        .with(observer.clone());
+    let arguments = json!({});
+
+    let result = chain
+        .run(Action::new("close_element", &arguments), || async {
+            ok_result()
+        })
+        .await;
+    assert!(result.is_err());
+    assert!(observer.seen.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_standard_chain_order() {
+    assert_eq!(
+        MiddlewareChain::standard().names(),
+        vec!["logging", "safe_mode", "latency"]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_jsonl_tool_log_has_one_record_per_call() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tools.jsonl");
+    let logging =
+        LoggingMiddleware::new(Redactor::default()).with_jsonl(JsonlSink::open(&path).unwrap());
+    let chain = Arc::new(MiddlewareChain::default().with(Arc::new(logging)));
+
+    let calls: Vec<_> = (0..50)
+        .map(|n| {
+            let chain = chain.clone();
+            tokio::spawn(async move {
+                let arguments = json!({
+                    "selector": format!("name:Field {n}"),
+                    "text_to_type": "x".repeat(2000),
+                    "password": "hunter2"
+                });
+                chain
+                    .run(
+                        Action::new("type_into_element", &arguments),
+                        || async move {
+                            if n % 10 == 0 {
+                                Err(McpError::internal_error("Element not found", None))
+                            } else {
+                                ok_result()
+                            }
+                        },
+                    )
+                    .await
+            })
+        })
+        .collect();
+    for call in calls {
+        let _ = call.await.unwrap();
+    }
+
+    let log = std::fs::read_to_string(&path).unwrap();
+    let records: Vec<ToolLogRecord> = log
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("every line is one whole record"))
+        .collect();
+    assert_eq!(records.len(), 50);
+    assert!(records
+        .iter()
+        .all(|r| r.tool == "type_into_element" && !r.nested));
+    assert!(records.iter().all(|r| r.args["password"] == REDACTED));
+    assert_eq!(records.iter().filter(|r| r.status == "error").count(), 5);
+    assert!(records
+        .iter()
+        .all(|r| chrono::DateTime::parse_from_rfc3339(&r.timestamp).is_ok()));
+    let mut selectors: Vec<&str> = records
+        .iter()
+        .map(|r| r.args["selector"].as_str().unwrap())
+        .collect();
+    selectors.sort();
+    selectors.dedup();
+    assert_eq!(selectors.len(), 50);
+}
+
+#[tokio::test]
+async fn test_jsonl_sink_appends_to_an_existing_log() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tools.jsonl");
+    std::fs::write(&path, "{\"earlier\":true}\n").unwrap();
+
+    let chain = MiddlewareChain::default().with(Arc::new(
+        LoggingMiddleware::new(Redactor::default()).with_jsonl(JsonlSink::open(&path).unwrap()),
+    ));
+    let arguments = json!({"selector": "name:OK"});
+    chain
+        .run(
+            Action::new("click_element", &arguments).nested(),
+            || async { Ok(CallToolResult::error(vec![Content::text("not clickable")])) },
+        )
+        .await
+        .unwrap();
+
+    let log = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = log.lines().collect();
+    assert_eq!(lines.len(), 2);
+    let record: ToolLogRecord = serde_json::from_str(lines[1]).unwrap();
+    assert_eq!(record.tool, "click_element");
+    assert_eq!(record.args, arguments);
+    // A tool result flagged as an error counts as a failed call
+    assert_eq!(record.status, "error");
+    assert!(record.nested);
+}
@@ -0,0 +1,161 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use terminator::AutomationError;
+use terminator_mcp_agent::modal_watcher::{
+    spawn_modal_watcher, DialogSource, ModalPattern, ModalWatcherConfig,
+};
+use terminator_mcp_agent::workflow_events::{ExecutionEvent, ExecutionEventEmitter};
+use tokio_util::sync::CancellationToken;
+
+/// Simulates a desktop where a dialog pops up after a few polls and disappears once
+/// one of its buttons is clicked.
+struct SimulatedDesktop {
+    polls: AtomicUsize,
+    appear_after: usize,
+    title: String,
+    buttons: Vec<String>,
+    open: Mutex<bool>,
+    clicked: Mutex<Vec<String>>,
+}
+
+impl SimulatedDesktop {
+    fn new(title: &str, buttons: &[&str], appear_after: usize) -> Self {
+        Self {
+            polls: AtomicUsize::new(0),
+            appear_after,
+            title: title.to_string(),
+            buttons: buttons.iter().map(|b| b.to_string()).collect(),
+            open: Mutex::new(false),
+            clicked: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl DialogSource for SimulatedDesktop {
+    type Dialog = String;
+
+    fn open_dialogs(&self) -> Result<Vec<String>, AutomationError> {
+        let poll = self.polls.fetch_add(1, Ordering::SeqCst);
+        let mut open = self.open.lock().unwrap();
+        if poll == self.appear_after && self.clicked.lock().unwrap().is_empty() {
+            *open = true;
+        }
+        Ok(if *open {
+            vec![self.title.clone()]
+        } else {
+            vec![]
+        })
+    }
+
+    fn dialog_title(&self, dialog: &String) -> String {
+        dialog.clone()
+    }
+
+    fn click_button(&self, _dialog: &String, button: &str) -> Result<bool, AutomationError> {
+        if !self.buttons.iter().any(|b| b == button) {
+            return Ok(false);
+        }
+        *self.open.lock().unwrap() = false;
+        self.clicked.lock().unwrap().push(button.to_string());
+        Ok(true)
+    }
+}
+
+fn config(title: &str, buttons: &[&str]) -> ModalWatcherConfig {
+    ModalWatcherConfig {
+        patterns: vec![ModalPattern {
+            title: title.to_string(),
+            buttons: buttons.iter().map(|b| b.to_string()).collect(),
+        }],
+        poll_interval: Some("10ms".to_string()),
+    }
+}
+
+#[tokio::test]
+async fn test_modal_appearing_mid_run_is_dismissed() {
+    let desktop = Arc::new(SimulatedDesktop::new(
+        "Update Available",
+        &["Remind me later", "Install"],
+        3,
+    ));
+    let emitter = ExecutionEventEmitter::new();
+    let run_token = CancellationToken::new();
+
+    let watcher = spawn_modal_watcher(
+        desktop.clone(),
+        &config("update available", &["Later", "Remind me later"]),
+        emitter.clone(),
+        &run_token,
+    )
+    .unwrap();
+
+    // The "workflow" keeps running while the dialog shows up partway through
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    let dismissed = watcher.stop().await;
+
+    assert_eq!(dismissed, 1);
+    assert_eq!(*desktop.clicked.lock().unwrap(), vec!["Remind me later"]);
+
+    let events = emitter.events();
+    assert_eq!(events.len(), 1);
+    match &events[0] {
+        ExecutionEvent::ModalDismissed { title, button, .. } => {
+            assert_eq!(title, "Update Available");
+            assert_eq!(button, "Remind me later");
+        }
+        other => panic!("unexpected event: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_non_matching_modal_is_left_alone() {
+    let desktop = Arc::new(SimulatedDesktop::new("Save changes?", &["Don't Save"], 0));
+    let emitter = ExecutionEventEmitter::new();
+    let run_token = CancellationToken::new();
+
+    let watcher = spawn_modal_watcher(
+        desktop.clone(),
+        &config("^error", &["OK"]),
+        emitter.clone(),
+        &run_token,
+    )
+    .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(watcher.stop().await, 0);
+    assert!(desktop.clicked.lock().unwrap().is_empty());
+    assert!(emitter.events().is_empty());
+}
+
+#[tokio::test]
+async fn test_watcher_stops_with_the_run() {
+    let desktop = Arc::new(SimulatedDesktop::new("Error", &["OK"], 1000));
+    let run_token = CancellationToken::new();
+
+    let watcher = spawn_modal_watcher(
+        desktop.clone(),
+        &config("error", &["OK"]),
+        ExecutionEventEmitter::new(),
+        &run_token,
+    )
+    .unwrap();
+
+    run_token.cancel();
+    tokio::time::timeout(Duration::from_secs(1), watcher.stop())
+        .await
+        .expect("watcher should stop once the run is cancelled");
+}
+
+#[test]
+fn test_invalid_title_pattern_is_rejected() {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let _guard = rt.enter();
+    let result = spawn_modal_watcher(
+        Arc::new(SimulatedDesktop::new("x", &[], 0)),
+        &config("(unclosed", &["OK"]),
+        ExecutionEventEmitter::new(),
+        &CancellationToken::new(),
+    );
+    assert!(result.is_err());
+}
@@ -0,0 +1,121 @@
+use terminator::{AutomationError, Monitor};
+use terminator_mcp_agent::monitors::{
+    list_monitors, monitor_at, to_desktop, MonitorError, MonitorProvider,
+};
+
+/// A provider that returns a fixed set of monitors, or fails
+struct MockMonitors(Result<Vec<Monitor>, String>);
+
+impl MonitorProvider for MockMonitors {
+    async fn monitors(&self) -> Result<Vec<Monitor>, AutomationError> {
+        self.0.clone().map_err(AutomationError::PlatformError)
+    }
+}
+
+fn monitor(name: &str, x: i32, y: i32, width: u32, height: u32, scale_factor: f64) -> Monitor {
+    Monitor {
+        id: format!("id-{name}"),
+        name: name.to_string(),
+        is_primary: x == 0 && y == 0,
+        width,
+        height,
+        x,
+        y,
+        scale_factor,
+        work_area: None,
+    }
+}
+
+/// A VM with three virtual displays, reported out of order: one left of the primary at
+/// 150% scaling and one to its right
+fn three_displays() -> MockMonitors {
+    MockMonitors(Ok(vec![
+        monitor("right", 1920, 0, 1280, 1024, 1.0),
+        monitor("primary", 0, 0, 1920, 1080, 1.0),
+        monitor("left", -2880, 0, 2880, 1620, 1.5),
+    ]))
+}
+
+#[tokio::test]
+async fn test_monitors_are_indexed_left_to_right() {
+    let monitors = list_monitors(&three_displays()).await.unwrap();
+
+    let names: Vec<_> = monitors.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["left", "primary", "right"]);
+    assert_eq!(
+        monitors.iter().map(|m| m.index).collect::<Vec<_>>(),
+        [0, 1, 2]
+    );
+    assert!(monitors[1].is_primary);
+    assert!(!monitors[0].is_primary);
+}
+
+#[tokio::test]
+async fn test_scale_factor_gives_the_logical_size() {
+    let monitors = list_monitors(&three_displays()).await.unwrap();
+
+    let left = &monitors[0];
+    assert_eq!(
+        (left.x, left.y, left.width, left.height),
+        (-2880, 0, 2880, 1620)
+    );
+    assert_eq!(left.scale_factor, 1.5);
+    assert_eq!((left.logical_width, left.logical_height), (1920.0, 1080.0));
+
+    // A missing scale factor counts as 100%
+    let monitors = list_monitors(&MockMonitors(Ok(vec![monitor(
+        "primary", 0, 0, 1024, 768, 0.0,
+    )])))
+    .await
+    .unwrap();
+    assert_eq!(monitors[0].scale_factor, 1.0);
+    assert_eq!(monitors[0].logical_width, 1024.0);
+}
+
+#[tokio::test]
+async fn test_monitor_relative_coordinates_become_desktop_coordinates() {
+    let provider = three_displays();
+
+    let left = monitor_at(&provider, 0).await.unwrap();
+    assert_eq!(to_desktop(&left, 0, 100.0, 50.0).unwrap(), (-2780.0, 50.0));
+
+    let right = monitor_at(&provider, 2).await.unwrap();
+    assert_eq!(to_desktop(&right, 2, 10.0, 20.0).unwrap(), (1930.0, 20.0));
+
+    let error = to_desktop(&right, 2, 1280.0, 20.0).unwrap_err();
+    assert_eq!(error.kind(), "coordinates_outside_monitor");
+    assert!(to_desktop(&right, 2, -1.0, 20.0).is_err());
+}
+
+#[tokio::test]
+async fn test_single_monitor() {
+    let provider = MockMonitors(Ok(vec![monitor("primary", 0, 0, 1920, 1080, 1.25)]));
+
+    let only = monitor_at(&provider, 0).await.unwrap();
+    assert_eq!(to_desktop(&only, 0, 5.0, 6.0).unwrap(), (5.0, 6.0));
+
+    let error = monitor_at(&provider, 1).await.unwrap_err();
+    assert!(matches!(
+        error,
+        MonitorError::NotFound { index: 1, count: 1 }
+    ));
+    assert_eq!(error.kind(), "monitor_not_found");
+    assert_eq!(
+        error.to_string(),
+        "There is no monitor 1; the only monitor is 0"
+    );
+}
+
+#[tokio::test]
+async fn test_no_monitors_and_provider_failures_are_typed() {
+    let error = list_monitors(&MockMonitors(Ok(Vec::new())))
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), "no_monitors");
+
+    let error = monitor_at(&MockMonitors(Err("xcap failed".to_string())), 0)
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), "monitor_list_failed");
+    assert!(error.to_string().contains("xcap failed"), "{error}");
+}
@@ -0,0 +1,295 @@
+use image::{DynamicImage, RgbaImage};
+use std::sync::Mutex;
+use terminator::ocr::{OcrWord, PlatformOcr, WordRecognizer};
+use terminator::{AutomationError, Monitor};
+use terminator_mcp_agent::highlight_screenshot::Rect;
+use terminator_mcp_agent::ocr_region::{line_text, ocr_region, RegionSource};
+
+/// A 320x120 white image reading "INVOICE TOTAL" over "AMOUNT 42" in black
+fn fixture() -> RgbaImage {
+    image::load_from_memory(include_bytes!("fixtures/ocr_invoice.png"))
+        .unwrap()
+        .to_rgba8()
+}
+
+/// Where each word of the fixture is, in fixture pixels: text, line, x, y, width, height
+const FIXTURE_WORDS: &[(&str, usize, f64, f64, f64, f64)] = &[
+    ("INVOICE", 0, 16.0, 16.0, 164.0, 28.0),
+    ("TOTAL", 0, 196.0, 16.0, 116.0, 28.0),
+    ("AMOUNT", 1, 16.0, 64.0, 140.0, 28.0),
+    ("42", 1, 172.0, 64.0, 44.0, 28.0),
+];
+
+/// "Reads" a part of the fixture by reporting the fixture words inside it. `origin` is
+/// where the part it is given should start, in fixture pixels.
+struct FixtureReader {
+    origin: (u32, u32),
+    seen: Mutex<Vec<RgbaImage>>,
+}
+
+impl FixtureReader {
+    fn at(x: u32, y: u32) -> Self {
+        Self {
+            origin: (x, y),
+            seen: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl WordRecognizer for FixtureReader {
+    async fn recognize_words(
+        &self,
+        image: DynamicImage,
+        _locale: Option<&str>,
+    ) -> Result<Vec<OcrWord>, AutomationError> {
+        let image = image.to_rgba8();
+        let (x, y) = (self.origin.0 as f64, self.origin.1 as f64);
+        let (width, height) = (image.width() as f64, image.height() as f64);
+        self.seen.lock().unwrap().push(image);
+
+        Ok(FIXTURE_WORDS
+            .iter()
+            .filter(|(_, _, wx, wy, ww, wh)| {
+                *wx >= x && *wy >= y && wx + ww <= x + width && wy + wh <= y + height
+            })
+            .map(|&(text, line, wx, wy, ww, wh)| OcrWord {
+                text: text.to_string(),
+                line,
+                x: wx - x,
+                y: wy - y,
+                width: ww,
+                height: wh,
+                confidence: Some(0.9),
+            })
+            .collect())
+    }
+}
+
+/// An OCR engine that is not compiled in
+struct NoEngine;
+
+impl WordRecognizer for NoEngine {
+    async fn recognize_words(
+        &self,
+        _image: DynamicImage,
+        _locale: Option<&str>,
+    ) -> Result<Vec<OcrWord>, AutomationError> {
+        Err(terminator::ocr::ocr_not_enabled())
+    }
+}
+
+fn monitor(x: i32, y: i32, width: u32, height: u32) -> Monitor {
+    Monitor {
+        id: format!("{x},{y}"),
+        name: "Virtual display".to_string(),
+        is_primary: x == 0 && y == 0,
+        width,
+        height,
+        x,
+        y,
+        scale_factor: 1.0,
+        work_area: None,
+    }
+}
+
+fn rect(x: f64, y: f64, width: f64, height: f64) -> Rect {
+    Rect {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+fn boxes(words: &[OcrWord]) -> Vec<(&str, f64, f64, f64, f64)> {
+    words
+        .iter()
+        .map(|w| (w.text.as_str(), w.x, w.y, w.width, w.height))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_words_are_returned_in_desktop_coordinates() {
+    // The fixture fills a second monitor to the right of the primary one
+    let monitors = [monitor(0, 0, 1920, 1080), monitor(1920, 0, 320, 120)];
+    let reader = FixtureReader::at(0, 0);
+
+    let text = ocr_region(
+        &reader,
+        rect(1920.0, 0.0, 320.0, 120.0),
+        &monitors,
+        |_| async { Ok(fixture()) },
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(text.monitor, monitors[1]);
+    assert!(!text.clipped);
+    assert_eq!(text.text, "INVOICE TOTAL\nAMOUNT 42");
+    assert_eq!(
+        boxes(&text.words),
+        [
+            ("INVOICE", 1936.0, 16.0, 164.0, 28.0),
+            ("TOTAL", 2116.0, 16.0, 116.0, 28.0),
+            ("AMOUNT", 1936.0, 64.0, 140.0, 28.0),
+            ("42", 2092.0, 64.0, 44.0, 28.0),
+        ]
+    );
+    assert_eq!(text.words[0].confidence, Some(0.9));
+    assert_eq!(reader.seen.lock().unwrap()[0], fixture());
+}
+
+#[tokio::test]
+async fn test_region_is_cropped_and_scaled_with_display_scaling() {
+    // At 200% the 320x120 capture covers a 160x60 monitor
+    let monitors = [monitor(0, 0, 160, 60)];
+    let reader = FixtureReader::at(0, 30);
+
+    let text = ocr_region(
+        &reader,
+        rect(0.0, 15.0, 160.0, 35.0),
+        &monitors,
+        |_| async { Ok(fixture()) },
+        Some("en-US"),
+    )
+    .await
+    .unwrap();
+
+    let expected = image::imageops::crop_imm(&fixture(), 0, 30, 320, 70).to_image();
+    assert_eq!(reader.seen.lock().unwrap()[0], expected);
+    assert_eq!(text.text, "AMOUNT 42");
+    assert_eq!(
+        boxes(&text.words),
+        [
+            ("AMOUNT", 8.0, 32.0, 70.0, 14.0),
+            ("42", 86.0, 32.0, 22.0, 14.0)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_region_partly_off_the_monitor_is_clipped() {
+    let monitors = [monitor(0, 0, 320, 120)];
+    let reader = FixtureReader::at(160, 0);
+
+    let text = ocr_region(
+        &reader,
+        rect(160.0, 0.0, 400.0, 120.0),
+        &monitors,
+        |_| async { Ok(fixture()) },
+        None,
+    )
+    .await
+    .unwrap();
+
+    assert!(text.clipped);
+    assert_eq!(reader.seen.lock().unwrap()[0].dimensions(), (160, 120));
+    assert_eq!(text.text, "TOTAL\n42");
+    assert_eq!(text.region, rect(160.0, 0.0, 400.0, 120.0));
+}
+
+#[tokio::test]
+async fn test_unreadable_regions_are_typed_errors() {
+    let monitors = [monitor(0, 0, 320, 120)];
+    let reader = FixtureReader::at(0, 0);
+    let read = |region| {
+        ocr_region(
+            &reader,
+            region,
+            &monitors,
+            |_| async { Ok(fixture()) },
+            None,
+        )
+    };
+
+    let error = read(rect(10.0, 10.0, 0.0, 20.0)).await.unwrap_err();
+    assert_eq!(error.kind(), "empty_region");
+    let error = read(rect(1000.0, 10.0, 20.0, 20.0)).await.unwrap_err();
+    assert_eq!(error.kind(), "region_off_screen");
+    assert!(reader.seen.lock().unwrap().is_empty());
+
+    let error = ocr_region(
+        &reader,
+        rect(0.0, 0.0, 20.0, 20.0),
+        &monitors,
+        |_| async { Err("capture refused".to_string()) },
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.kind(), "screen_capture_failed");
+
+    let error = ocr_region(
+        &NoEngine,
+        rect(0.0, 0.0, 20.0, 20.0),
+        &monitors,
+        |_| async { Ok(fixture()) },
+        None,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.kind(), "ocr_not_enabled");
+}
+
+#[test]
+fn test_region_takes_a_selector_or_a_rectangle() {
+    assert_eq!(
+        RegionSource::from_args(Some("name:Total"), None, None, None, None),
+        Ok(RegionSource::Selector("name:Total"))
+    );
+    assert_eq!(
+        RegionSource::from_args(None, Some(1.0), Some(2.0), Some(3.0), Some(4.0)),
+        Ok(RegionSource::Rect(rect(1.0, 2.0, 3.0, 4.0)))
+    );
+    assert!(RegionSource::from_args(None, None, None, None, None).is_err());
+    assert!(RegionSource::from_args(Some("name:Total"), Some(1.0), None, None, None).is_err());
+    assert!(RegionSource::from_args(None, Some(1.0), Some(2.0), None, Some(4.0)).is_err());
+}
+
+#[test]
+fn test_line_text_groups_words_by_line() {
+    let word = |text: &str, line| OcrWord {
+        text: text.to_string(),
+        line,
+        x: 0.0,
+        y: 0.0,
+        width: 1.0,
+        height: 1.0,
+        confidence: None,
+    };
+    let words = [
+        word("Due", 1),
+        word("Total", 0),
+        word("today", 1),
+        word("42", 0),
+    ];
+    assert_eq!(line_text(&words), "Total 42\nDue today");
+    assert_eq!(line_text(&[]), "");
+}
+
+#[tokio::test]
+#[ignore = "Requires the platform OCR engine; on Linux and macOS the tesseract binary"]
+async fn test_platform_ocr_reads_the_fixture() {
+    let words = PlatformOcr
+        .recognize_words(DynamicImage::ImageRgba8(fixture()), Some("en-US"))
+        .await
+        .unwrap();
+
+    for &(expected, line, x, y, width, height) in FIXTURE_WORDS {
+        let word = words
+            .iter()
+            .find(|w| w.text == expected)
+            .unwrap_or_else(|| panic!("{expected} not recognized in {words:?}"));
+        assert_eq!(word.line, line, "{word:?}");
+        // Engines pad or trim glyph boxes a little
+        for (got, want) in [
+            (word.x, x),
+            (word.y, y),
+            (word.width, width),
+            (word.height, height),
+        ] {
+            assert!((got - want).abs() <= 8.0, "{word:?}");
+        }
+    }
+}
@@ -0,0 +1,293 @@
+use rmcp::model::{CallToolResult, Content};
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Map, Value};
+use std::future::Future;
+use std::sync::Mutex;
+use terminator_mcp_agent::on_error::{self, ErrorAction, ErrorHandler, StepError};
+use terminator_mcp_agent::run_bundle::{RunOutcome, ToolBackend};
+use terminator_mcp_agent::utils::{DesktopWrapper, ExecuteSequenceArgs, SequenceStep, ToolCall};
+use tokio_util::sync::CancellationToken;
+
+/// Records every call; clicks on "name:Missing" fail as an element that was not found
+#[derive(Default)]
+struct Desktop {
+    calls: Mutex<Vec<(String, Value)>>,
+}
+
+fn element_not_found() -> McpError {
+    McpError::internal_error(
+        "Element not found: name:Missing",
+        Some(json!({"error_category": "user"})),
+    )
+}
+
+impl ToolBackend for Desktop {
+    fn call_tool(
+        &self,
+        tool_name: &str,
+        arguments: &Value,
+        _variables: &Value,
+        _cancellation: CancellationToken,
+    ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send {
+        self.calls
+            .lock()
+            .unwrap()
+            .push((tool_name.to_string(), arguments.clone()));
+        std::future::ready(if arguments["selector"] == "name:Missing" {
+            Err(element_not_found())
+        } else {
+            Ok(CallToolResult::success(vec![Content::json(
+                json!({"action": tool_name}),
+            )
+            .unwrap()]))
+        })
+    }
+}
+
+fn step(tool_name: &str, arguments: Value) -> SequenceStep {
+    SequenceStep {
+        tool_name: Some(tool_name.to_string()),
+        arguments: Some(arguments),
+        ..Default::default()
+    }
+}
+
+fn call(tool_name: &str, arguments: Value) -> ToolCall {
+    ToolCall {
+        tool_name: tool_name.to_string(),
+        arguments,
+        continue_on_error: None,
+        delay_ms: None,
+        id: None,
+    }
+}
+
+fn failing_step(on_error: Vec<ErrorHandler>) -> SequenceStep {
+    SequenceStep {
+        id: Some("submit".to_string()),
+        on_error: Some(on_error),
+        ..step("click_element", json!({"selector": "name:Missing"}))
+    }
+}
+
+async fn run(steps: Vec<SequenceStep>) -> (Vec<String>, String, Vec<(String, Value)>) {
+    run_with_troubleshooting(steps, None).await
+}
+
+async fn run_with_troubleshooting(
+    steps: Vec<SequenceStep>,
+    troubleshooting: Option<Vec<SequenceStep>>,
+) -> (Vec<String>, String, Vec<(String, Value)>) {
+    let workflow = ExecuteSequenceArgs {
+        steps: Some(steps),
+        troubleshooting,
+        // Troubleshooting steps only run when a failed step jumps to them
+        follow_fallback: Some(true),
+        ..Default::default()
+    };
+    let desktop = Desktop::default();
+    let summary = DesktopWrapper::new()
+        .unwrap()
+        .execute_sequence_with(&desktop, &CancellationToken::new(), workflow)
+        .await
+        .unwrap();
+    let outcome = RunOutcome::from_summary(&summary);
+    (
+        outcome.step_statuses,
+        outcome.status,
+        desktop.calls.into_inner().unwrap(),
+    )
+}
+
+#[tokio::test]
+async fn test_fallback_steps_run_and_the_workflow_continues() {
+    let handler = ErrorHandler {
+        steps: Some(vec![
+            call("press_key", json!({"key": "{Escape}"})),
+            call("click_element", json!({"selector": "name:Submit"})),
+        ]),
+        then: Some(ErrorAction::Continue),
+        ..Default::default()
+    };
+
+    let (statuses, status, calls) = run(vec![
+        failing_step(vec![handler]),
+        step("click_element", json!({"selector": "name:Next"})),
+    ])
+    .await;
+
+    assert_eq!(statuses, vec!["recovered", "success"]);
+    assert_eq!(status, "success");
+    let selectors: Vec<String> = calls
+        .iter()
+        .map(|(tool, args)| format!("{tool} {}", args["selector"].as_str().unwrap_or("")))
+        .collect();
+    assert_eq!(
+        selectors,
+        vec![
+            "click_element name:Missing",
+            "press_key ",
+            "click_element name:Submit",
+            "click_element name:Next",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_handlers_receive_the_error_as_variables() {
+    let timeout_handler = ErrorHandler {
+        when: Some("env.error.kind == 'step_timeout'".to_string()),
+        steps: Some(vec![call("press_key", json!({"key": "{F5}"}))]),
+        then: Some(ErrorAction::Continue),
+        ..Default::default()
+    };
+    let mut set_env = Map::new();
+    set_env.insert("failure".to_string(), json!("{{env.error.message}}"));
+    let user_error_handler = ErrorHandler {
+        when: Some(
+            "env.error.kind == 'tool_error' && env.error.category == 'user' && env.error.step_id == 'submit'"
+                .to_string(),
+        ),
+        set_env: Some(set_env),
+        steps: Some(vec![call(
+            "type_into_element",
+            json!({"selector": "name:Log", "text_to_type": "{{env.error.kind}}: {{env.error.message}}"}),
+        )]),
+        then: Some(ErrorAction::Continue),
+    };
+
+    let (statuses, _, calls) = run(vec![
+        failing_step(vec![timeout_handler, user_error_handler]),
+        step(
+            "type_into_element",
+            json!({"selector": "name:Notes", "text_to_type": "{{env.failure}}"}),
+        ),
+    ])
+    .await;
+
+    assert_eq!(statuses, vec!["recovered", "success"]);
+    // The timeout handler did not match, so F5 was never pressed
+    assert!(calls.iter().all(|(tool, _)| tool != "press_key"));
+    let message = element_not_found().to_string();
+    assert_eq!(calls[1].1["text_to_type"], format!("tool_error: {message}"));
+    // Variables set by the handler outlive it
+    assert_eq!(calls[2].1["text_to_type"], message);
+}
+
+#[tokio::test]
+async fn test_abort_stops_the_workflow_without_following_fallback_id() {
+    let handler = ErrorHandler {
+        then: Some(ErrorAction::Abort),
+        ..Default::default()
+    };
+    let failing = SequenceStep {
+        fallback_id: Some("recover".to_string()),
+        ..failing_step(vec![handler])
+    };
+    let recover = SequenceStep {
+        id: Some("recover".to_string()),
+        ..step("press_key", json!({"key": "{Escape}"}))
+    };
+
+    let (statuses, status, calls) = run_with_troubleshooting(
+        vec![
+            failing,
+            step("click_element", json!({"selector": "name:Next"})),
+        ],
+        Some(vec![recover]),
+    )
+    .await;
+
+    assert_eq!(statuses, vec!["error", "skipped"]);
+    assert_eq!(status, "partial_success");
+    assert_eq!(calls.len(), 1);
+}
+
+#[tokio::test]
+async fn test_failing_fallback_step_leaves_the_step_failed() {
+    let handler = ErrorHandler {
+        steps: Some(vec![
+            call("click_element", json!({"selector": "name:Missing"})),
+            call("click_element", json!({"selector": "name:Never"})),
+        ]),
+        then: Some(ErrorAction::Continue),
+        ..Default::default()
+    };
+
+    let (statuses, status, calls) = run(vec![failing_step(vec![handler])]).await;
+
+    assert_eq!(statuses, vec!["error"]);
+    assert_eq!(status, "partial_success");
+    assert_eq!(calls.len(), 2);
+}
+
+#[tokio::test]
+async fn test_default_then_keeps_the_step_failed_for_fallback_id() {
+    let mut set_env = Map::new();
+    set_env.insert("needs_login".to_string(), json!(true));
+    let handler = ErrorHandler {
+        set_env: Some(set_env),
+        ..Default::default()
+    };
+    let failing = SequenceStep {
+        fallback_id: Some("recover".to_string()),
+        ..failing_step(vec![handler])
+    };
+    let recover = SequenceStep {
+        id: Some("recover".to_string()),
+        r#if: Some("env.needs_login == true".to_string()),
+        ..step("press_key", json!({"key": "{Escape}"}))
+    };
+
+    let (statuses, _, calls) = run_with_troubleshooting(vec![failing], Some(vec![recover])).await;
+
+    assert_eq!(statuses, vec!["error", "success"]);
+    assert_eq!(calls[1].0, "press_key");
+}
+
+#[test]
+fn test_error_kinds() {
+    let timeout = json!({"status": "error", "error": "StepTimeout: step did not finish within 100ms", "reason": "step_timeout"});
+    assert_eq!(StepError::from_result(&timeout).kind, "step_timeout");
+
+    let verification = json!({"status": "error", "error": "Post-condition not met: env.saved", "verification_failed": true});
+    assert_eq!(
+        StepError::from_result(&verification).kind,
+        "verification_failed"
+    );
+
+    // A group reports its first failed call
+    let group = json!({
+        "group_name": "login",
+        "status": "partial_success",
+        "results": [
+            {"status": "success"},
+            {"status": "error", "error": "Element not found", "error_category": "transient"}
+        ]
+    });
+    assert_eq!(
+        StepError::from_result(&group),
+        StepError {
+            message: "Element not found".to_string(),
+            kind: "tool_error".to_string(),
+            category: Some("transient".to_string()),
+        }
+    );
+    assert_eq!(
+        StepError::from_result(&json!({"group_name": "login", "status": "partial_success"}))
+            .message,
+        "Group 'login' failed"
+    );
+}
+
+#[test]
+fn test_invalid_when_is_rejected() {
+    let step = failing_step(vec![ErrorHandler {
+        when: Some("(env.error.kind == 'tool_error'".to_string()),
+        ..Default::default()
+    }]);
+    assert!(on_error::validate(&step)
+        .unwrap_err()
+        .starts_with("Invalid on_error when"));
+    assert!(on_error::validate(&failing_step(vec![ErrorHandler::default()])).is_ok());
+}
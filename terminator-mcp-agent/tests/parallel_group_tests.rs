@@ -0,0 +1,284 @@
+use rmcp::model::{CallToolResult, Content};
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use terminator_mcp_agent::parallel_group::{run_children, ChildOutcome, ParallelGroup};
+use terminator_mcp_agent::run_bundle::{RunOutcome, ToolBackend};
+use terminator_mcp_agent::utils::{DesktopWrapper, ExecuteSequenceArgs, SequenceStep, ToolCall};
+use tokio_util::sync::CancellationToken;
+
+/// A child that ends after `delay_ms` with the given success
+async fn child(delay_ms: u64, succeeds: bool) -> Result<u64, u64> {
+    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+    if succeeds {
+        Ok(delay_ms)
+    } else {
+        Err(delay_ms)
+    }
+}
+
+#[tokio::test]
+async fn test_fail_fast_cancels_the_children_still_running() {
+    let token = CancellationToken::new();
+    let started = Instant::now();
+
+    let outcomes = run_children(
+        vec![child(10, false), child(30_000, true), child(1, true)],
+        &token,
+        true,
+        Result::is_err,
+    )
+    .await;
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(
+        outcomes,
+        vec![
+            ChildOutcome::Finished(Err(10)),
+            ChildOutcome::Cancelled,
+            ChildOutcome::Finished(Ok(1)),
+        ]
+    );
+    assert!(token.is_cancelled());
+}
+
+#[tokio::test]
+async fn test_without_fail_fast_every_child_finishes() {
+    let token = CancellationToken::new();
+
+    let outcomes = run_children(
+        vec![child(10, false), child(50, true)],
+        &token,
+        false,
+        Result::is_err,
+    )
+    .await;
+
+    assert_eq!(
+        outcomes,
+        vec![
+            ChildOutcome::Finished(Err(10)),
+            ChildOutcome::Finished(Ok(50)),
+        ]
+    );
+    assert!(!token.is_cancelled());
+}
+
+#[tokio::test]
+async fn test_children_run_concurrently_and_keep_their_order() {
+    let token = CancellationToken::new();
+    let started = Instant::now();
+
+    let outcomes = run_children(
+        vec![child(300, true), child(200, true), child(100, true)],
+        &token,
+        true,
+        Result::is_err,
+    )
+    .await;
+
+    // Run one after another these would take 600ms
+    assert!(started.elapsed() < Duration::from_millis(550));
+    assert_eq!(
+        outcomes,
+        vec![
+            ChildOutcome::Finished(Ok(300)),
+            ChildOutcome::Finished(Ok(200)),
+            ChildOutcome::Finished(Ok(100)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_request_cancellation_reaches_every_child() {
+    let request = CancellationToken::new();
+    let group = request.child_token();
+    let canceller = request.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        canceller.cancel();
+    });
+
+    let outcomes = run_children(
+        vec![child(30_000, true), child(30_000, true)],
+        &group,
+        true,
+        Result::is_err,
+    )
+    .await;
+
+    assert_eq!(
+        outcomes,
+        vec![ChildOutcome::Cancelled, ChildOutcome::Cancelled]
+    );
+}
+
+fn parallel_group(calls: Vec<ToolCall>, fail_fast: Option<bool>) -> SequenceStep {
+    SequenceStep {
+        group_name: Some("read panels".to_string()),
+        steps: Some(calls),
+        parallel: Some(true),
+        fail_fast,
+        ..Default::default()
+    }
+}
+
+fn call(selector: &str, continue_on_error: Option<bool>) -> ToolCall {
+    ToolCall {
+        tool_name: "get_element_text".to_string(),
+        arguments: json!({"selector": selector}),
+        continue_on_error,
+        delay_ms: None,
+        id: None,
+    }
+}
+
+#[test]
+fn test_parallel_settings() {
+    let group = parallel_group(vec![], None);
+    assert_eq!(
+        ParallelGroup::from_step(&group).unwrap(),
+        Some(ParallelGroup { fail_fast: true })
+    );
+    let group = parallel_group(vec![], Some(false));
+    assert_eq!(
+        ParallelGroup::from_step(&group).unwrap(),
+        Some(ParallelGroup { fail_fast: false })
+    );
+    assert_eq!(
+        ParallelGroup::from_step(&SequenceStep::default()).unwrap(),
+        None
+    );
+
+    let looping = SequenceStep {
+        repeat_until: Some("env.done == true".to_string()),
+        ..parallel_group(vec![], None)
+    };
+    assert!(ParallelGroup::from_step(&looping).is_err());
+    let tool = SequenceStep {
+        tool_name: Some("click_element".to_string()),
+        parallel: Some(true),
+        ..Default::default()
+    };
+    assert!(ParallelGroup::from_step(&tool).is_err());
+}
+
+/// Reads panels; the one named "Broken" fails
+#[derive(Default)]
+struct Panels {
+    calls: Mutex<Vec<String>>,
+}
+
+impl ToolBackend for Panels {
+    fn call_tool(
+        &self,
+        _tool_name: &str,
+        arguments: &Value,
+        _variables: &Value,
+        _cancellation: CancellationToken,
+    ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send {
+        let selector = arguments["selector"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        self.calls.lock().unwrap().push(selector.clone());
+        std::future::ready(if selector == "name:Broken" {
+            Err(McpError::internal_error("Panel could not be read", None))
+        } else {
+            Ok(CallToolResult::success(vec![Content::text("42")]))
+        })
+    }
+}
+
+async fn run(workflow: ExecuteSequenceArgs, panels: &Panels) -> RunOutcome {
+    let summary = DesktopWrapper::new()
+        .unwrap()
+        .execute_sequence_with(panels, &CancellationToken::new(), workflow)
+        .await
+        .unwrap();
+    RunOutcome::from_summary(&summary)
+}
+
+#[tokio::test]
+async fn test_parallel_group_fails_when_a_step_fails() {
+    let workflow = ExecuteSequenceArgs {
+        steps: Some(vec![parallel_group(
+            vec![call("name:Broken", None), call("name:Totals", None)],
+            Some(false),
+        )]),
+        ..Default::default()
+    };
+    let panels = Panels::default();
+
+    let outcome = run(workflow, &panels).await;
+
+    assert_eq!(outcome.status, "partial_success");
+    assert_eq!(outcome.step_statuses, vec!["partial_success"]);
+    // Unlike a sequential group, the failure does not stop the remaining steps
+    let mut calls = panels.calls.lock().unwrap().clone();
+    calls.sort();
+    assert_eq!(calls, vec!["name:Broken", "name:Totals"]);
+}
+
+#[tokio::test]
+async fn test_continued_errors_do_not_fail_a_parallel_group() {
+    let workflow = ExecuteSequenceArgs {
+        steps: Some(vec![parallel_group(
+            vec![call("name:Broken", Some(true)), call("name:Totals", None)],
+            None,
+        )]),
+        ..Default::default()
+    };
+    let panels = Panels::default();
+
+    let outcome = run(workflow, &panels).await;
+
+    assert_eq!(outcome.step_statuses, vec!["success"]);
+    assert_eq!(panels.calls.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_parallel_group_variables_stay_inside_the_group() {
+    let open_report = SequenceStep {
+        tool_name: Some("click_element".to_string()),
+        arguments: Some(json!({"selector": "name:Open report"})),
+        r#if: Some("env.totals_status == 'success'".to_string()),
+        ..Default::default()
+    };
+    let group = |promote: Option<Vec<String>>| SequenceStep {
+        promote,
+        ..parallel_group(
+            vec![ToolCall {
+                id: Some("totals".to_string()),
+                ..call("name:Totals", None)
+            }],
+            None,
+        )
+    };
+
+    let workflow = ExecuteSequenceArgs {
+        steps: Some(vec![group(None), open_report.clone()]),
+        ..Default::default()
+    };
+    let panels = Panels::default();
+    let outcome = run(workflow, &panels).await;
+    assert_eq!(outcome.step_statuses, vec!["success", "skipped"]);
+    assert_eq!(*panels.calls.lock().unwrap(), vec!["name:Totals"]);
+
+    let workflow = ExecuteSequenceArgs {
+        steps: Some(vec![
+            group(Some(vec!["totals_status".to_string()])),
+            open_report,
+        ]),
+        ..Default::default()
+    };
+    let panels = Panels::default();
+    let outcome = run(workflow, &panels).await;
+    assert_eq!(outcome.step_statuses, vec!["success", "success"]);
+    assert_eq!(
+        *panels.calls.lock().unwrap(),
+        vec!["name:Totals", "name:Open report"]
+    );
+}
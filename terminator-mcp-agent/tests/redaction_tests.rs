@@ -0,0 +1,92 @@
+use serde_json::json;
+use terminator_mcp_agent::middleware::{Action, LoggingMiddleware};
+use terminator_mcp_agent::redaction::{Redactor, REDACTED};
+
+#[test]
+fn test_sensitive_arguments_are_masked() {
+    let arguments = json!({
+        "selector": "role:Edit|name:Password",
+        "password": "hunter2",
+        "api_token": "abc123",
+        "clientSecret": "s3cr3t",
+        "timeout_ms": 500
+    });
+
+    let redacted = Redactor::default().redact(&arguments);
+
+    assert_eq!(
+        redacted,
+        json!({
+            "selector": "role:Edit|name:Password",
+            "password": REDACTED,
+            "api_token": REDACTED,
+            "clientSecret": REDACTED,
+            "timeout_ms": 500
+        })
+    );
+}
+
+#[test]
+fn test_nested_arguments_are_masked() {
+    let arguments = json!({
+        "steps": [
+            {"tool_name": "type_into_element", "arguments": {"text_to_type": "hi", "ssn": "123-45-6789"}},
+            {"tool_name": "run_command", "arguments": {"env": {"DB_PASSWORD": "pw", "region": "eu"}}}
+        ],
+        "credentials": {"Token": {"value": "abc"}, "user": "ada"}
+    });
+
+    let redacted = Redactor::default().redact(&arguments);
+
+    assert_eq!(redacted["steps"][0]["arguments"]["ssn"], REDACTED);
+    assert_eq!(redacted["steps"][0]["arguments"]["text_to_type"], "hi");
+    assert_eq!(
+        redacted["steps"][1]["arguments"]["env"]["DB_PASSWORD"],
+        REDACTED
+    );
+    assert_eq!(redacted["steps"][1]["arguments"]["env"]["region"], "eu");
+    // A sensitive key masks its whole value, objects included
+    assert_eq!(redacted["credentials"]["Token"], REDACTED);
+    assert_eq!(redacted["credentials"]["user"], "ada");
+}
+
+#[test]
+fn test_keys_match_whole_words() {
+    let redactor = Redactor::default();
+    assert!(redactor.is_sensitive("newPassword"));
+    assert!(redactor.is_sensitive("access-token"));
+    assert!(redactor.is_sensitive("SSN"));
+    assert!(!redactor.is_sensitive("classname"));
+    assert!(!redactor.is_sensitive("tokenizer"));
+    assert!(!redactor.is_sensitive("selector"));
+}
+
+#[test]
+fn test_custom_denylist() {
+    let redactor = Redactor::new(["text_to_type", " PIN ", ""]);
+    assert_eq!(redactor.sensitive_keys, vec!["text_to_type", "pin"]);
+
+    let redacted = redactor.redact(&json!({
+        "text_to_type": "hunter2",
+        "card_pin": "1234",
+        "password": "still visible"
+    }));
+    assert_eq!(
+        redacted,
+        json!({"text_to_type": REDACTED, "card_pin": REDACTED, "password": "still visible"})
+    );
+}
+
+#[test]
+fn test_logging_middleware_logs_redacted_arguments() {
+    let arguments = json!({"selector": "name:Login", "password": "hunter2"});
+    let action = Action::new("type_into_element", &arguments);
+
+    let logged = LoggingMiddleware::new(Redactor::default()).loggable_arguments(&action);
+
+    assert_eq!(
+        logged,
+        json!({"selector": "name:Login", "password": REDACTED})
+    );
+    assert!(!logged.to_string().contains("hunter2"));
+}
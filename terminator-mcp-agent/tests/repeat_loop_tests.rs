@@ -0,0 +1,191 @@
+use rmcp::model::{CallToolResult, Content};
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Map, Value};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+use terminator_mcp_agent::repeat_loop::{LoopExit, LoopGuard, RepeatUntil};
+use terminator_mcp_agent::run_bundle::{RunOutcome, ToolBackend};
+use terminator_mcp_agent::utils::{DesktopWrapper, ExecuteSequenceArgs, SequenceStep, ToolCall};
+use tokio_util::sync::CancellationToken;
+
+/// Drains a queue one item per `run_command` call and reports when it is empty
+struct QueueDesktop {
+    remaining: Mutex<u32>,
+    calls: Mutex<u32>,
+}
+
+impl QueueDesktop {
+    fn new(remaining: u32) -> Self {
+        Self {
+            remaining: Mutex::new(remaining),
+            calls: Mutex::new(0),
+        }
+    }
+}
+
+impl ToolBackend for QueueDesktop {
+    fn call_tool(
+        &self,
+        tool_name: &str,
+        _arguments: &Value,
+        _variables: &Value,
+        _cancellation: CancellationToken,
+    ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send {
+        *self.calls.lock().unwrap() += 1;
+        let content = if tool_name == "run_command" {
+            let mut remaining = self.remaining.lock().unwrap();
+            *remaining = remaining.saturating_sub(1);
+            json!({"result": {"set_env": {"queue_empty": *remaining == 0}}})
+        } else {
+            json!({"action": tool_name})
+        };
+        std::future::ready(Ok(CallToolResult::success(vec![
+            Content::json(content).unwrap()
+        ])))
+    }
+}
+
+async fn run(workflow: ExecuteSequenceArgs, desktop: &QueueDesktop) -> RunOutcome {
+    let summary = DesktopWrapper::new()
+        .unwrap()
+        .execute_sequence_with(desktop, &CancellationToken::new(), workflow)
+        .await
+        .unwrap();
+    RunOutcome::from_summary(&summary)
+}
+
+fn drain_loop(repeat_until: &str, max_iterations: Option<u32>) -> SequenceStep {
+    SequenceStep {
+        group_name: Some("drain queue".to_string()),
+        steps: Some(vec![ToolCall {
+            tool_name: "run_command".to_string(),
+            arguments: json!({"engine": "javascript", "run": "..."}),
+            continue_on_error: None,
+            delay_ms: None,
+            id: None,
+        }]),
+        repeat_until: Some(repeat_until.to_string()),
+        max_iterations,
+        ..Default::default()
+    }
+}
+
+fn workflow(steps: Vec<SequenceStep>) -> ExecuteSequenceArgs {
+    ExecuteSequenceArgs {
+        steps: Some(steps),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_loop_exits_when_condition_holds() {
+    let desktop = QueueDesktop::new(3);
+    let outcome = run(
+        workflow(vec![drain_loop("env.queue_empty == true", Some(10))]),
+        &desktop,
+    )
+    .await;
+
+    assert_eq!(*desktop.calls.lock().unwrap(), 3);
+    assert_eq!(outcome.status, "success");
+    assert_eq!(outcome.step_statuses, vec!["success"]);
+}
+
+#[tokio::test]
+async fn test_loop_stops_at_max_iterations() {
+    let desktop = QueueDesktop::new(100);
+    let outcome = run(
+        workflow(vec![
+            drain_loop("env.queue_empty == true", Some(5)),
+            SequenceStep {
+                tool_name: Some("click_element".to_string()),
+                arguments: Some(json!({"selector": "role:Button|name:Done"})),
+                ..Default::default()
+            },
+        ]),
+        &desktop,
+    )
+    .await;
+
+    // The guard stopped the loop, failed the step and skipped the rest of the workflow
+    assert_eq!(*desktop.calls.lock().unwrap(), 5);
+    assert_eq!(outcome.status, "partial_success");
+    assert_eq!(outcome.step_statuses, vec!["partial_success", "skipped"]);
+}
+
+#[test]
+fn test_loop_guard_exposes_iteration_variable() {
+    let step = SequenceStep {
+        loop_variable: Some("attempt".to_string()),
+        ..drain_loop("env.attempt == target", None)
+    };
+    let config = RepeatUntil::from_step(&step).unwrap().unwrap();
+    let mut guard = LoopGuard::new(&config);
+    let mut context = Map::new();
+    context.insert("target".to_string(), json!(3));
+
+    let mut exit = None;
+    while exit.is_none() {
+        guard.start_iteration(&mut context);
+        exit = guard.after_iteration(&Value::Object(context.clone()));
+    }
+
+    assert_eq!(exit, Some(LoopExit::ConditionMet));
+    assert_eq!(guard.iteration(), 3);
+    assert_eq!(context["env"]["attempt"], json!(3));
+}
+
+#[test]
+fn test_loop_guard_times_out() {
+    let config = RepeatUntil {
+        condition: "env.done == true".to_string(),
+        max_iterations: 1_000,
+        timeout: Some(Duration::from_millis(20)),
+        variable: "iteration".to_string(),
+    };
+    let mut guard = LoopGuard::new(&config);
+    let mut context = Map::new();
+    guard.start_iteration(&mut context);
+    assert_eq!(guard.after_iteration(&Value::Object(context.clone())), None);
+
+    std::thread::sleep(Duration::from_millis(30));
+    let exit = guard.after_iteration(&Value::Object(context)).unwrap();
+    assert_eq!(exit, LoopExit::TimedOut);
+    assert!(exit.is_guard());
+}
+
+#[test]
+fn test_repeat_until_config_validation() {
+    let tool_step = SequenceStep {
+        tool_name: Some("click_element".to_string()),
+        repeat_until: Some("env.done == true".to_string()),
+        ..Default::default()
+    };
+    assert!(RepeatUntil::from_step(&tool_step).is_err());
+    assert!(RepeatUntil::from_step(&drain_loop("env.done == true", Some(0))).is_err());
+
+    let with_timeout = SequenceStep {
+        loop_timeout: Some("2m".to_string()),
+        ..drain_loop("env.done == true", None)
+    };
+    let config = RepeatUntil::from_step(&with_timeout).unwrap().unwrap();
+    assert_eq!(config.timeout, Some(Duration::from_secs(120)));
+    assert_eq!(config.variable, "iteration");
+    assert!(RepeatUntil::from_step(&SequenceStep::default())
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_invalid_repeat_until_condition_is_rejected() {
+    let err = RepeatUntil::from_step(&drain_loop("lenght(env.rows) == 0", None)).unwrap_err();
+    assert_eq!(
+        err,
+        "Invalid repeat_until 'lenght(env.rows) == 0': unknown function 'lenght'"
+    );
+    assert!(RepeatUntil::from_step(&drain_loop("(env.done == true", None)).is_err());
+
+    // A condition over variables that do not exist yet is fine; they appear as the loop runs
+    assert!(RepeatUntil::from_step(&drain_loop("env.queue_empty == true", None)).is_ok());
+}
@@ -0,0 +1,151 @@
+use std::time::{Duration, Instant};
+use terminator_mcp_agent::retry_backoff::{wait_for_retry, RetryBackoff, RetryConfig};
+use terminator_mcp_agent::utils::SequenceStep;
+use tokio_util::sync::CancellationToken;
+
+fn step_with_retry(retries: Option<u32>, retry: Option<RetryConfig>) -> SequenceStep {
+    SequenceStep {
+        tool_name: Some("click_element".to_string()),
+        retries,
+        retry,
+        ..Default::default()
+    }
+}
+
+fn backoff(initial_delay: &str, multiplier: f64, jitter: f64) -> RetryBackoff {
+    RetryBackoff::from_step(&step_with_retry(
+        None,
+        Some(RetryConfig {
+            max_attempts: Some(5),
+            initial_delay: Some(initial_delay.to_string()),
+            multiplier: Some(multiplier),
+            jitter: Some(jitter),
+        }),
+    ))
+    .unwrap()
+}
+
+#[test]
+fn test_delays_grow_exponentially() {
+    let backoff = backoff("200ms", 2.0, 0.0);
+    let delays: Vec<Duration> = (1..=4).map(|retry| backoff.base_delay(retry)).collect();
+    assert_eq!(
+        delays,
+        vec![
+            Duration::from_millis(200),
+            Duration::from_millis(400),
+            Duration::from_millis(800),
+            Duration::from_millis(1600),
+        ]
+    );
+    // Without jitter the random draw makes no difference
+    assert_eq!(backoff.delay(3, 0.0), Duration::from_millis(800));
+    assert_eq!(backoff.delay(3, 1.0), Duration::from_millis(800));
+    assert_eq!(backoff.retries(), 4);
+}
+
+#[test]
+fn test_fractional_multiplier() {
+    let backoff = backoff("1s", 1.5, 0.0);
+    let delays: Vec<Duration> = (1..=3).map(|retry| backoff.base_delay(retry)).collect();
+    assert_eq!(
+        delays,
+        vec![
+            Duration::from_millis(1000),
+            Duration::from_millis(1500),
+            Duration::from_millis(2250),
+        ]
+    );
+}
+
+#[test]
+fn test_jitter_spreads_the_delay_both_ways() {
+    let backoff = backoff("1s", 2.0, 0.25);
+    assert_eq!(backoff.delay(2, 0.0), Duration::from_millis(1500));
+    assert_eq!(backoff.delay(2, 0.5), Duration::from_millis(2000));
+    assert_eq!(backoff.delay(2, 1.0), Duration::from_millis(2500));
+
+    for _ in 0..100 {
+        let delay = backoff.next_delay(2);
+        assert!(
+            (Duration::from_millis(1500)..=Duration::from_millis(2500)).contains(&delay),
+            "{delay:?}"
+        );
+    }
+}
+
+#[test]
+fn test_steps_without_retry_keep_the_fixed_pause() {
+    let backoff = RetryBackoff::from_step(&step_with_retry(Some(2), None)).unwrap();
+    assert_eq!(backoff.retries(), 2);
+    assert_eq!(backoff.base_delay(1), Duration::from_millis(500));
+    assert_eq!(backoff.base_delay(2), Duration::from_millis(500));
+
+    let backoff = RetryBackoff::from_step(&step_with_retry(None, None)).unwrap();
+    assert_eq!(backoff.retries(), 0);
+}
+
+#[test]
+fn test_retry_defaults() {
+    let backoff =
+        RetryBackoff::from_step(&step_with_retry(None, Some(RetryConfig::default()))).unwrap();
+    assert_eq!(backoff.max_attempts, 3);
+    assert_eq!(backoff.base_delay(1), Duration::from_millis(500));
+    assert_eq!(backoff.base_delay(2), Duration::from_millis(1000));
+
+    // max_attempts falls back to the step's retries
+    let backoff =
+        RetryBackoff::from_step(&step_with_retry(Some(4), Some(RetryConfig::default()))).unwrap();
+    assert_eq!(backoff.max_attempts, 5);
+}
+
+#[test]
+fn test_invalid_retry_settings() {
+    let invalid = |config: RetryConfig| {
+        RetryBackoff::from_step(&step_with_retry(None, Some(config))).unwrap_err()
+    };
+    assert_eq!(
+        invalid(RetryConfig {
+            max_attempts: Some(0),
+            ..Default::default()
+        }),
+        "retry.max_attempts must be at least 1"
+    );
+    assert!(invalid(RetryConfig {
+        initial_delay: Some("soon".to_string()),
+        ..Default::default()
+    })
+    .starts_with("Invalid retry.initial_delay 'soon'"));
+    assert!(invalid(RetryConfig {
+        multiplier: Some(0.5),
+        ..Default::default()
+    })
+    .starts_with("retry.multiplier must be at least 1"));
+    assert!(invalid(RetryConfig {
+        jitter: Some(1.5),
+        ..Default::default()
+    })
+    .starts_with("retry.jitter must be between 0 and 1"));
+}
+
+#[test]
+fn test_huge_backoff_is_capped() {
+    let backoff = backoff("1h", 1000.0, 0.0);
+    assert!(backoff.base_delay(u32::MAX) <= Duration::from_secs(100 * 365 * 86_400));
+}
+
+#[tokio::test]
+async fn test_cancellation_ends_the_wait() {
+    let token = CancellationToken::new();
+    let canceller = token.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        canceller.cancel();
+    });
+
+    let started = Instant::now();
+    assert!(!wait_for_retry(Duration::from_secs(30), &token).await);
+    assert!(started.elapsed() < Duration::from_secs(5));
+
+    assert!(wait_for_retry(Duration::from_millis(1), &CancellationToken::new()).await);
+}
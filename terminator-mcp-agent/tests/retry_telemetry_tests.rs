@@ -0,0 +1,55 @@
+use terminator_mcp_agent::telemetry::{
+    record_retry_attempt, record_retry_exhausted, record_retry_success, retry_tracker, RetryCounts,
+    RetryTracker,
+};
+
+#[test]
+fn test_step_retrying_once_counts_attempt_and_success() {
+    // First attempt failed, the single retry succeeded
+    record_retry_attempt("retry_once_probe");
+    record_retry_success("retry_once_probe");
+
+    let counts = retry_tracker().lock().unwrap().counts("retry_once_probe");
+    assert_eq!(
+        counts,
+        RetryCounts {
+            attempts: 1,
+            successes: 1,
+            exhaustions: 0,
+        }
+    );
+}
+
+#[test]
+fn test_exhausted_retries_are_counted_per_step() {
+    let mut tracker = RetryTracker::new();
+    for _ in 0..3 {
+        tracker.record_attempt("flaky_click");
+    }
+    tracker.record_exhausted("flaky_click");
+    tracker.record_attempt("other_step");
+
+    assert_eq!(tracker.counts("flaky_click").attempts, 3);
+    assert_eq!(tracker.counts("flaky_click").exhaustions, 1);
+    assert_eq!(tracker.counts("flaky_click").successes, 0);
+    assert_eq!(tracker.counts("other_step").attempts, 1);
+    assert_eq!(tracker.counts("never_retried"), RetryCounts::default());
+
+    let report = serde_json::to_value(tracker.report()).unwrap();
+    assert_eq!(report["flaky_click"]["attempts"], 3);
+    assert_eq!(report["flaky_click"]["exhaustions"], 1);
+
+    tracker.clear();
+    assert!(tracker.report().is_empty());
+}
+
+#[test]
+fn test_global_exhaustion_counter() {
+    record_retry_attempt("exhausted_probe");
+    record_retry_exhausted("exhausted_probe");
+
+    let counts = retry_tracker().lock().unwrap().counts("exhausted_probe");
+    assert_eq!(counts.attempts, 1);
+    assert_eq!(counts.successes, 0);
+    assert_eq!(counts.exhaustions, 1);
+}
@@ -0,0 +1,289 @@
+use rmcp::model::{CallToolResult, Content};
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Map, Value};
+use std::future::Future;
+use std::path::PathBuf;
+use terminator_mcp_agent::run_bundle::{
+    replay, RunBundle, RunOutcome, ToolBackend, ToolResponse, BUNDLE_FORMAT_VERSION,
+};
+use terminator_mcp_agent::utils::{DesktopWrapper, ExecuteSequenceArgs, SequenceStep};
+use tokio_util::sync::CancellationToken;
+
+/// Stand-in for the live desktop: answers tool calls deterministically
+struct SimulatedDesktop;
+
+impl ToolBackend for SimulatedDesktop {
+    fn call_tool(
+        &self,
+        tool_name: &str,
+        arguments: &Value,
+        _variables: &Value,
+        cancellation: CancellationToken,
+    ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send {
+        let tool_name = tool_name.to_string();
+        let arguments = arguments.clone();
+        async move {
+            let reply = |content: Value| Ok(CallToolResult::success(vec![Content::json(content)?]));
+            match tool_name.as_str() {
+                "run_command" => reply(json!({"result": {"set_env": {"customer": "ACME Corp"}}})),
+                "click_element" if arguments["selector"] == "role:Button|name:Missing" => {
+                    Err(McpError::internal_error(
+                        "Element not found",
+                        Some(json!({"selector": arguments["selector"]})),
+                    ))
+                }
+                // Never appears, so the wait lasts until the step gives up on it
+                "wait_for_element" => {
+                    cancellation.cancelled().await;
+                    Err(McpError::internal_error("Wait cancelled", None))
+                }
+                _ => reply(json!({"action": tool_name, "echo": arguments})),
+            }
+        }
+    }
+}
+
+fn tool_step(tool_name: &str, arguments: Value) -> SequenceStep {
+    SequenceStep {
+        tool_name: Some(tool_name.to_string()),
+        arguments: Some(arguments),
+        ..Default::default()
+    }
+}
+
+fn sample_workflow() -> ExecuteSequenceArgs {
+    ExecuteSequenceArgs {
+        inputs: Some(json!({"open_settings": false})),
+        selectors: Some(json!({"submit": "role:Button|name:Submit"})),
+        steps: Some(vec![
+            tool_step("run_command", json!({"engine": "javascript", "run": "..."})),
+            tool_step(
+                "type_into_element",
+                json!({"selector": "role:Edit", "text_to_type": "{{env.customer}}"}),
+            ),
+            SequenceStep {
+                r#if: Some("open_settings == true".to_string()),
+                ..tool_step(
+                    "click_element",
+                    json!({"selector": "role:Button|name:Settings"}),
+                )
+            },
+            SequenceStep {
+                timeout: Some("50ms".to_string()),
+                continue_on_error: Some(true),
+                ..tool_step("wait_for_element", json!({"selector": "role:Dialog"}))
+            },
+            SequenceStep {
+                id: Some("confirm".to_string()),
+                continue_on_error: Some(true),
+                ..tool_step(
+                    "click_element",
+                    json!({"selector": "role:Button|name:Missing"}),
+                )
+            },
+            tool_step("click_element", json!({"selector": "{{selectors.submit}}"})),
+        ]),
+        ..Default::default()
+    }
+}
+
+fn bundle_path() -> PathBuf {
+    std::env::temp_dir()
+        .join(format!("terminator_bundle_{}", uuid::Uuid::new_v4()))
+        .join("run.json")
+}
+
+/// Runs `workflow` on the simulated desktop through the sequence runner and returns the
+/// bundle it exported
+async fn record(server: &DesktopWrapper, workflow: ExecuteSequenceArgs) -> RunBundle {
+    let path = bundle_path();
+    let summary = server
+        .execute_sequence_with(
+            &SimulatedDesktop,
+            &CancellationToken::new(),
+            ExecuteSequenceArgs {
+                export_bundle: Some(path.to_string_lossy().to_string()),
+                ..workflow
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(summary["bundle_path"], json!(path.to_string_lossy()));
+    let bundle = RunBundle::read_from(&path).await.unwrap();
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    bundle
+}
+
+#[tokio::test]
+async fn test_exported_bundle_replays_to_same_outcome() {
+    let server = DesktopWrapper::new().unwrap();
+    let bundle = record(&server, sample_workflow()).await;
+
+    assert_eq!(bundle.format_version, BUNDLE_FORMAT_VERSION);
+    assert_eq!(bundle.outcome.status, "completed_with_errors");
+    assert_eq!(
+        bundle.outcome.step_statuses,
+        vec!["success", "success", "skipped", "skipped", "skipped", "success"]
+    );
+    let tools: Vec<&str> = bundle.trace.iter().map(|e| e.tool_name.as_str()).collect();
+    assert_eq!(
+        tools,
+        [
+            "run_command",
+            "type_into_element",
+            "wait_for_element",
+            "click_element",
+            "click_element"
+        ]
+    );
+    // The env set by the script flowed into the next step's arguments
+    assert_eq!(bundle.trace[1].arguments["text_to_type"], "ACME Corp");
+    // The wait was cancelled by its step timeout before it returned
+    assert_eq!(bundle.trace[2].response, None);
+    assert!(matches!(
+        bundle.trace[3].response,
+        Some(ToolResponse::Error(_))
+    ));
+    assert!(bundle.workflow.export_bundle.is_none());
+
+    assert_eq!(replay(&server, &bundle).await.unwrap(), bundle.outcome);
+}
+
+#[tokio::test]
+async fn test_replay_answers_parallel_steps_in_any_order() {
+    let server = DesktopWrapper::new().unwrap();
+    let workflow: ExecuteSequenceArgs = serde_json::from_value(json!({
+        "steps": [{
+            "group_name": "fill form",
+            "parallel": true,
+            "steps": [
+                {"tool_name": "type_into_element", "arguments": {"selector": "role:Edit|name:First", "text_to_type": "Ada"}},
+                {"tool_name": "type_into_element", "arguments": {"selector": "role:Edit|name:Last", "text_to_type": "Lovelace"}},
+                {"tool_name": "click_element", "arguments": {"selector": "role:CheckBox|name:Subscribe"}}
+            ]
+        }]
+    }))
+    .unwrap();
+    let mut bundle = record(&server, workflow).await;
+    assert_eq!(bundle.trace.len(), 3);
+
+    bundle.trace.reverse();
+    assert_eq!(replay(&server, &bundle).await.unwrap(), bundle.outcome);
+}
+
+#[tokio::test]
+async fn test_replay_detects_divergence_from_trace() {
+    let server = DesktopWrapper::new().unwrap();
+    let recorded = record(&server, sample_workflow()).await;
+
+    // The script reported a different customer, so the typed text no longer matches
+    let mut bundle = recorded.clone();
+    bundle.trace[0].response = Some(ToolResponse::Result(CallToolResult::success(vec![
+        Content::json(json!({"result": {"set_env": {"customer": "Someone else"}}})).unwrap(),
+    ])));
+    let err = replay(&server, &bundle).await.unwrap_err();
+    assert!(err.contains("diverged"), "unexpected error: {err}");
+    assert!(err.contains("Someone else"), "unexpected error: {err}");
+
+    let mut bundle = recorded.clone();
+    bundle.trace.push(bundle.trace[1].clone());
+    let err = replay(&server, &bundle).await.unwrap_err();
+    assert!(err.contains("unused"), "unexpected error: {err}");
+
+    let mut bundle = recorded;
+    bundle.outcome.status = "success".to_string();
+    let err = replay(&server, &bundle).await.unwrap_err();
+    assert!(err.contains("differs"), "unexpected error: {err}");
+}
+
+#[tokio::test]
+async fn test_replay_starts_from_the_recorded_context() {
+    let server = DesktopWrapper::new().unwrap();
+    let dir = std::env::temp_dir().join(format!("terminator_resume_{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(dir.join(".workflow_state")).unwrap();
+    let workflow_file = dir.join("invoice.yml");
+    std::fs::write(
+        &workflow_file,
+        r#"
+steps:
+  - id: open
+    tool_name: click_element
+    arguments: { selector: "role:Button|name:Open" }
+  - id: fill
+    tool_name: type_into_element
+    arguments: { selector: "role:Edit", text_to_type: "{{env.customer}} {{invoice}}" }
+"#,
+    )
+    .unwrap();
+    // A previous run stopped after `open` with the customer in its env
+    std::fs::write(
+        dir.join(".workflow_state").join("invoice.json"),
+        json!({"last_step_index": 0, "env": {"customer": "Resumed Co"}}).to_string(),
+    )
+    .unwrap();
+
+    let bundle = record(
+        &server,
+        ExecuteSequenceArgs {
+            url: Some(format!("file://{}", workflow_file.display())),
+            inputs: Some(json!({"invoice": "INV-7"})),
+            start_from_step: Some("fill".to_string()),
+            ..Default::default()
+        },
+    )
+    .await;
+    assert_eq!(
+        bundle.trace[0].arguments["text_to_type"],
+        "Resumed Co INV-7"
+    );
+    let context = bundle.initial_context.as_ref().unwrap();
+    assert_eq!(context["invoice"], "INV-7");
+    assert_eq!(context["env"], json!({"customer": "Resumed Co"}));
+
+    // The saved state is gone, as it would be on another machine; the bundle still
+    // replays because it carries the context the run started from
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(replay(&server, &bundle).await.unwrap(), bundle.outcome);
+
+    let mut stale = bundle.clone();
+    stale.initial_context = None;
+    let err = replay(&server, &stale).await.unwrap_err();
+    assert!(err.contains("diverged"), "unexpected error: {err}");
+}
+
+#[test]
+fn test_bundle_rejects_newer_format_version() {
+    let mut bundle = RunBundle::new(
+        sample_workflow(),
+        Map::new(),
+        Vec::new(),
+        RunOutcome {
+            status: "success".to_string(),
+            step_statuses: Vec::new(),
+        },
+    );
+    bundle.format_version = BUNDLE_FORMAT_VERSION + 1;
+    let json = bundle.to_json().unwrap();
+    assert!(RunBundle::from_json(&json).is_err());
+}
+
+#[test]
+fn test_bundle_is_self_contained() {
+    let workflow = ExecuteSequenceArgs {
+        url: Some("https://example.com/workflow.yml".to_string()),
+        export_bundle: Some("run.json".to_string()),
+        ..sample_workflow()
+    };
+    let bundle = RunBundle::new(
+        workflow,
+        Map::new(),
+        Vec::new(),
+        RunOutcome {
+            status: "success".to_string(),
+            step_statuses: Vec::new(),
+        },
+    );
+    assert!(bundle.workflow.url.is_none());
+    assert!(bundle.workflow.export_bundle.is_none());
+    assert!(bundle.workflow.steps.is_some());
+}
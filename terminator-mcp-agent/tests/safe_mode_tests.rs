@@ -0,0 +1,159 @@
+use serde_json::json;
+use terminator_mcp_agent::safe_mode::SafeMode;
+use terminator_mcp_agent::utils::{CloseElementArgs, PressKeysArgs, RunCommandArgs};
+
+#[test]
+fn test_destructive_action_requires_confirmation() {
+    let safe_mode = SafeMode::enabled();
+
+    let err = safe_mode
+        .check("close_element", &json!({ "selector": "window:Notepad" }))
+        .unwrap_err();
+    assert_eq!(err.tool_name, "close_element");
+
+    let err = safe_mode
+        .check(
+            "click_element",
+            &json!({ "selector": "role:Button|name:Delete" }),
+        )
+        .unwrap_err();
+    assert!(err.reason.contains("delete"), "{}", err.reason);
+
+    // Keywords in fallback selectors count too
+    assert!(safe_mode
+        .check(
+            "invoke_element",
+            &json!({ "selector": "#btn1", "fallback_selectors": "name:Submit" })
+        )
+        .is_err());
+
+    // Dropping onto a destructive target, e.g. a trash can
+    assert!(safe_mode
+        .check(
+            "drag_and_drop",
+            &json!({ "source_selector": "name:report.txt", "target_selector": "name:Remove" })
+        )
+        .is_err());
+}
+
+#[test]
+fn test_confirmed_destructive_action_is_allowed() {
+    let safe_mode = SafeMode::enabled();
+
+    assert!(safe_mode
+        .check(
+            "close_element",
+            &json!({ "selector": "window:Notepad", "confirmed": true })
+        )
+        .is_ok());
+    assert!(safe_mode
+        .check(
+            "click_element",
+            &json!({ "selector": "name:Delete", "confirmed": true })
+        )
+        .is_ok());
+    // Only a literal `true` confirms
+    assert!(safe_mode
+        .check(
+            "click_element",
+            &json!({ "selector": "name:Delete", "confirmed": "yes" })
+        )
+        .is_err());
+}
+
+#[test]
+fn test_non_destructive_actions_pass() {
+    let safe_mode = SafeMode::enabled();
+
+    assert!(safe_mode
+        .check("click_element", &json!({ "selector": "name:Save" }))
+        .is_ok());
+    // Read-only tools are never classified by keyword
+    assert!(safe_mode
+        .check("validate_element", &json!({ "selector": "name:Delete" }))
+        .is_ok());
+    assert!(safe_mode.check("get_window_tree", &json!({})).is_ok());
+}
+
+#[test]
+fn test_disabled_safe_mode_allows_everything() {
+    let safe_mode = SafeMode::default();
+    assert!(safe_mode
+        .check("close_element", &json!({ "selector": "window:Notepad" }))
+        .is_ok());
+}
+
+#[test]
+fn test_classification_lists_are_configurable() {
+    let safe_mode = SafeMode {
+        enabled: true,
+        destructive_tools: vec!["run_command".to_string()],
+        destructive_keywords: vec!["archive".to_string()],
+        destructive_keys: vec!["{f5}".to_string()],
+    };
+
+    assert!(safe_mode
+        .check("run_command", &json!({ "run": "echo hi" }))
+        .is_err());
+    assert!(safe_mode
+        .check("close_element", &json!({ "selector": "window:Notepad" }))
+        .is_ok());
+    assert!(safe_mode
+        .check("click_element", &json!({ "selector": "name:Archive" }))
+        .is_err());
+    assert!(safe_mode
+        .check("click_element", &json!({ "selector": "name:Delete" }))
+        .is_ok());
+    assert!(safe_mode
+        .check("press_keys", &json!({ "keys": ["{F5}"] }))
+        .is_err());
+    assert!(safe_mode
+        .check("press_keys", &json!({ "keys": ["{Delete}"] }))
+        .is_ok());
+}
+
+#[test]
+fn test_commands_and_focused_keys_are_classified() {
+    let safe_mode = SafeMode::enabled();
+
+    assert!(safe_mode
+        .check("run_command", &json!({ "run": "echo hi" }))
+        .is_err());
+
+    // Keys go to whatever has focus, so the keys themselves are checked
+    let err = safe_mode
+        .check("press_keys", &json!({ "keys": ["{Tab}", "{Ctrl} W"] }))
+        .unwrap_err();
+    assert!(err.reason.contains("{ctrl}w"), "{}", err.reason);
+    assert!(safe_mode
+        .check("press_key_global", &json!({ "key": "{Alt}{F4}" }))
+        .is_err());
+    assert!(safe_mode
+        .check("type_into_focused", &json!({ "text": "{Delete}" }))
+        .is_err());
+    assert!(safe_mode
+        .check("press_keys", &json!({ "keys": ["{Ctrl}a", "{Tab}"] }))
+        .is_ok());
+    assert!(safe_mode
+        .check("type_into_focused", &json!({ "text": "hello" }))
+        .is_ok());
+    assert!(safe_mode
+        .check(
+            "press_keys",
+            &json!({ "keys": ["{Delete}"], "confirmed": true })
+        )
+        .is_ok());
+}
+
+#[test]
+fn test_gated_tools_accept_confirmed() {
+    let args: PressKeysArgs =
+        serde_json::from_value(json!({ "keys": ["{Delete}"], "confirmed": true })).unwrap();
+    assert_eq!(args.confirmed, Some(true));
+    let args: RunCommandArgs = serde_json::from_value(json!({ "run": "echo hi" })).unwrap();
+    assert_eq!(args.confirmed, None);
+
+    // Advertised to clients in the tool schemas
+    let schema = serde_json::to_value(rmcp::schemars::schema_for!(CloseElementArgs)).unwrap();
+    assert!(schema["properties"]["confirmed"].is_object(), "{schema}");
+}
@@ -0,0 +1,167 @@
+use std::cell::{Cell, RefCell};
+use std::time::Duration;
+use terminator::AutomationError;
+use terminator_mcp_agent::highlight_screenshot::Rect;
+use terminator_mcp_agent::scroll_view::{
+    clipping_viewport, in_view, scroll_into_view, ScrollError, ScrollOptions, ScrollTarget,
+};
+
+fn rect(x: f64, y: f64, width: f64, height: f64) -> Rect {
+    Rect {
+        x,
+        y,
+        width,
+        height,
+    }
+}
+
+/// A list item inside a 300px high list that scrolls by `step` pixels per scroll
+struct ListItem {
+    top: Cell<f64>,
+    step: f64,
+    scrolls: RefCell<Vec<String>>,
+    /// Whether scroll calls fail, as when no ancestor can scroll
+    stuck: bool,
+}
+
+impl ListItem {
+    fn at(top: f64, step: f64) -> Self {
+        Self {
+            top: Cell::new(top),
+            step,
+            scrolls: RefCell::new(Vec::new()),
+            stuck: false,
+        }
+    }
+}
+
+impl ScrollTarget for ListItem {
+    fn bounds(&self) -> Result<Rect, AutomationError> {
+        Ok(rect(20.0, self.top.get(), 200.0, 30.0))
+    }
+
+    fn ancestor_bounds(&self) -> Vec<Rect> {
+        // The list, then the window
+        vec![
+            rect(10.0, 100.0, 300.0, 300.0),
+            rect(0.0, 0.0, 1000.0, 800.0),
+        ]
+    }
+
+    fn scroll(&self, direction: &str, _amount: f64) -> Result<(), AutomationError> {
+        self.scrolls.borrow_mut().push(direction.to_string());
+        if self.stuck {
+            return Err(AutomationError::UnsupportedOperation(
+                "No scrollable ancestor".to_string(),
+            ));
+        }
+        match direction {
+            "down" => self.top.set(self.top.get() - self.step),
+            "up" => self.top.set(self.top.get() + self.step),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+fn options(max_scrolls: u32) -> ScrollOptions {
+    ScrollOptions {
+        max_scrolls,
+        settle_delay: Duration::ZERO,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_element_below_the_list_is_scrolled_into_view() {
+    // The list shows y 100..400; the item starts at 700 and moves up 100px per scroll
+    let item = ListItem::at(700.0, 100.0);
+
+    let in_view = scroll_into_view(&item, &options(20)).await.unwrap();
+
+    assert_eq!(in_view.scrolls, 4);
+    assert_eq!(in_view.bounds, rect(20.0, 300.0, 200.0, 30.0));
+    assert_eq!(in_view.viewport, Some(rect(10.0, 100.0, 300.0, 300.0)));
+    assert_eq!(*item.scrolls.borrow(), vec!["down"; 4]);
+}
+
+#[tokio::test]
+async fn test_element_above_the_list_scrolls_up() {
+    let item = ListItem::at(-150.0, 100.0);
+
+    let in_view = scroll_into_view(&item, &options(20)).await.unwrap();
+
+    assert_eq!(in_view.scrolls, 3);
+    assert_eq!(*item.scrolls.borrow(), vec!["up"; 3]);
+}
+
+#[tokio::test]
+async fn test_element_already_in_view_is_not_scrolled() {
+    let item = ListItem::at(200.0, 100.0);
+
+    let in_view = scroll_into_view(&item, &options(20)).await.unwrap();
+
+    assert_eq!(in_view.scrolls, 0);
+    assert_eq!(in_view.viewport, None);
+    assert!(item.scrolls.borrow().is_empty());
+}
+
+#[tokio::test]
+async fn test_gives_up_with_scroll_failed_after_max_scrolls() {
+    let item = ListItem::at(5000.0, 100.0);
+
+    let error = scroll_into_view(&item, &options(5)).await.unwrap_err();
+
+    let ScrollError::ScrollFailed {
+        scrolls,
+        bounds,
+        viewport,
+        last_error,
+    } = &error
+    else {
+        panic!("expected ScrollFailed, got {error:?}");
+    };
+    assert_eq!(*scrolls, 5);
+    assert_eq!(bounds.y, 4500.0);
+    assert_eq!(*viewport, rect(10.0, 100.0, 300.0, 300.0));
+    assert_eq!(*last_error, None);
+    assert_eq!(error.kind(), "scroll_failed");
+    assert_eq!(item.scrolls.borrow().len(), 5);
+}
+
+#[tokio::test]
+async fn test_scroll_errors_are_reported_when_giving_up() {
+    let item = ListItem {
+        stuck: true,
+        ..ListItem::at(700.0, 100.0)
+    };
+
+    let error = scroll_into_view(&item, &options(3)).await.unwrap_err();
+
+    assert_eq!(error.kind(), "scroll_failed");
+    assert!(
+        error.to_string().contains("No scrollable ancestor"),
+        "{error}"
+    );
+}
+
+#[test]
+fn test_viewport_is_the_nearest_ancestor_clipping_the_element() {
+    let item = rect(20.0, 700.0, 200.0, 30.0);
+    let ancestors = [
+        rect(10.0, 650.0, 250.0, 100.0), // A row group that contains the item
+        rect(10.0, 100.0, 300.0, 300.0), // The list, which clips it
+        rect(0.0, 0.0, 1000.0, 800.0),
+    ];
+    assert_eq!(clipping_viewport(&item, &ancestors), Some(ancestors[1]));
+    assert_eq!(clipping_viewport(&item, &ancestors[..1]), None);
+}
+
+#[test]
+fn test_elements_larger_than_the_viewport_count_as_in_view_when_they_fill_it() {
+    let viewport = rect(0.0, 100.0, 300.0, 300.0);
+    assert!(in_view(&rect(0.0, 50.0, 300.0, 500.0), &viewport));
+    assert!(!in_view(&rect(0.0, 350.0, 300.0, 500.0), &viewport));
+    // Partly visible is not in view
+    assert!(!in_view(&rect(0.0, 390.0, 100.0, 30.0), &viewport));
+}
@@ -0,0 +1,92 @@
+use terminator::{AutomationError, ElementInfo};
+use terminator_mcp_agent::selector_resolution::SelectorResolution;
+
+fn element(role: &str, name: &str) -> ElementInfo {
+    ElementInfo {
+        role: role.to_string(),
+        name: Some(name.to_string()),
+        ..Default::default()
+    }
+}
+
+/// Resolves selectors against a fixed window holding a Save button and two text fields
+fn resolve(selectors: &[&str]) -> Vec<SelectorResolution> {
+    selectors
+        .iter()
+        .map(|selector| {
+            let found = match *selector {
+                "role:Button|name:Save" => Ok(vec![element("Button", "Save")]),
+                "role:Edit" => Ok(vec![
+                    element("Edit", "First name"),
+                    element("Edit", "Last name"),
+                ]),
+                "role:Button|name:Delete" => Err(AutomationError::ElementNotFound(
+                    "No element matched role:Button|name:Delete".to_string(),
+                )),
+                "role:Dialog" => Err(AutomationError::Timeout(
+                    "Timed out after 1000ms".to_string(),
+                )),
+                other => Err(AutomationError::InvalidSelector(format!(
+                    "Unknown selector format: {other}"
+                ))),
+            };
+            SelectorResolution::new(selector, found)
+        })
+        .collect()
+}
+
+#[test]
+fn test_resolves_existing_and_missing_selectors() {
+    let results = resolve(&[
+        "role:Button|name:Save",
+        "role:Edit",
+        "role:Button|name:Delete",
+        "role:Dialog",
+    ]);
+
+    let summary: Vec<(&str, bool, usize)> = results
+        .iter()
+        .map(|r| (r.selector.as_str(), r.matched, r.match_count))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("role:Button|name:Save", true, 1),
+            ("role:Edit", true, 2),
+            ("role:Button|name:Delete", false, 0),
+            ("role:Dialog", false, 0),
+        ]
+    );
+
+    // The summary is the first match; lookups that found nothing are not errors
+    assert_eq!(results[0].element, Some(element("Button", "Save")));
+    assert_eq!(results[1].element, Some(element("Edit", "First name")));
+    assert!(results[2].element.is_none());
+    assert!(results.iter().all(|r| r.error.is_none()));
+}
+
+#[test]
+fn test_invalid_selector_reports_its_error() {
+    let results = resolve(&["bogus::selector", "role:Button|name:Save"]);
+
+    assert!(!results[0].matched);
+    assert!(results[0]
+        .error
+        .as_deref()
+        .is_some_and(|e| e.contains("bogus::selector")));
+    // The rest of the batch is still resolved
+    assert!(results[1].matched);
+}
+
+#[test]
+fn test_unmatched_result_serializes_without_element() {
+    let result = SelectorResolution::new("role:Dialog", Ok(vec![]));
+    assert_eq!(
+        serde_json::to_value(&result).unwrap(),
+        serde_json::json!({
+            "selector": "role:Dialog",
+            "matched": false,
+            "match_count": 0,
+        })
+    );
+}
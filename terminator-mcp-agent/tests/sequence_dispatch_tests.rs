@@ -0,0 +1,209 @@
+use rmcp::model::CallToolRequestParam;
+use rmcp::service::RunningService;
+use rmcp::{RoleClient, ServiceExt};
+use serde_json::{json, Value};
+use terminator_mcp_agent::utils::DesktopWrapper;
+
+/// Serves the agent over an in-memory transport and connects a client to it
+async fn connect() -> RunningService<RoleClient, ()> {
+    let (server_io, client_io) = tokio::io::duplex(1 << 20);
+    let server = DesktopWrapper::new().unwrap();
+    tokio::spawn(async move {
+        let running = server.serve(server_io).await.unwrap();
+        let _ = running.waiting().await;
+    });
+    ().serve(client_io).await.unwrap()
+}
+
+/// Runs `steps` through execute_sequence and returns the summary
+async fn run_sequence(steps: Value) -> Value {
+    let client = connect().await;
+    let result = client
+        .call_tool(CallToolRequestParam {
+            name: "execute_sequence".into(),
+            arguments: json!({"steps": steps}).as_object().cloned(),
+        })
+        .await
+        .unwrap();
+    let summary = serde_json::to_value(&result.content[0]).unwrap();
+    serde_json::from_str(summary["text"].as_str().unwrap()).unwrap()
+}
+
+/// The error a step reported, or "" if it succeeded
+fn step_error(summary: &Value, index: usize) -> &str {
+    summary["results"][index]["error"]
+        .as_str()
+        .unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_scroll_into_view_runs_as_a_step() {
+    let summary = run_sequence(json!([{
+        "tool_name": "scroll_into_view",
+        "arguments": {"selector": "role:Button|name:Nowhere", "timeout_ms": 200, "retries": 0},
+        "continue_on_error": true
+    }]))
+    .await;
+
+    // The step reached the tool, which looked for the element
+    let error = step_error(&summary, 0);
+    assert!(!error.contains("Unknown tool"), "{error}");
+    assert!(error.contains("Element not found"), "{error}");
+}
+
+#[tokio::test]
+async fn test_list_monitors_runs_as_a_step() {
+    let summary = run_sequence(json!([{
+        "tool_name": "list_monitors",
+        "arguments": {},
+        "continue_on_error": true
+    }]))
+    .await;
+
+    let error = step_error(&summary, 0);
+    assert!(!error.contains("Unknown tool"), "{error}");
+    // Without a display the tool itself fails; with one it lists the monitors
+    if error.is_empty() {
+        let listed = &summary["results"][0]["result"]["content"][0];
+        assert_eq!(listed["action"], "list_monitors");
+    }
+}
+
+#[tokio::test]
+async fn test_repeat_until_loops_through_the_server() {
+    let summary = run_sequence(json!([{
+        "group_name": "poll",
+        "steps": [{"tool_name": "delay", "arguments": {"delay_ms": 1}, "id": "tick"}],
+        "repeat_until": "env.iteration == 3",
+        "max_iterations": 10
+    }]))
+    .await;
+
+    let group = &summary["results"][0];
+    assert_eq!(group["status"], "success");
+    assert_eq!(group["loop_exit"], "condition_met");
+    assert_eq!(group["iterations"], 3);
+    assert_eq!(group["results"].as_array().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_verify_checks_the_real_tool_result() {
+    let summary = run_sequence(json!([
+        {
+            "id": "settle",
+            "tool_name": "delay",
+            "arguments": {"delay_ms": 10},
+            "verify": "env.settle_result[0].actual_delay_ms >= 10"
+        },
+        {
+            "id": "wait",
+            "tool_name": "delay",
+            "arguments": {"delay_ms": 10},
+            "verify": "env.wait_result[0].status == 'failed'",
+            "retries": 1,
+            "continue_on_error": true
+        }
+    ]))
+    .await;
+
+    assert_eq!(summary["results"][0]["status"], "success");
+    let unmet = &summary["results"][1];
+    assert_eq!(unmet["status"], "skipped");
+    assert_eq!(unmet["verification_failed"], true);
+    assert!(step_error(&summary, 1).contains("Post-condition not met"));
+}
+
+#[tokio::test]
+async fn test_group_variables_are_scoped_through_the_server() {
+    let group = |promote: Value| {
+        json!({
+            "group_name": "wait",
+            "steps": [{"tool_name": "delay", "arguments": {"delay_ms": 1}, "id": "pause"}],
+            "repeat_until": "env.pause_status == 'success'",
+            "promote": promote
+        })
+    };
+    let after = json!({
+        "tool_name": "delay",
+        "arguments": {"delay_ms": 1},
+        "if": "env.pause_status == 'success'"
+    });
+
+    let summary = run_sequence(json!([group(Value::Null), after.clone()])).await;
+    assert_eq!(summary["results"][1]["status"], "skipped");
+
+    let summary = run_sequence(json!([group(json!(["pause_status"])), after])).await;
+    assert_eq!(summary["results"][1]["status"], "success");
+}
+
+#[tokio::test]
+async fn test_parallel_group_runs_its_steps_concurrently_through_the_server() {
+    let pause = json!({"tool_name": "delay", "arguments": {"delay_ms": 300}});
+    let started = std::time::Instant::now();
+    let summary = run_sequence(json!([{
+        "group_name": "settle panels",
+        "parallel": true,
+        "steps": [pause, pause, pause]
+    }]))
+    .await;
+
+    let group = &summary["results"][0];
+    assert_eq!(group["status"], "success");
+    assert_eq!(group["results"].as_array().unwrap().len(), 3);
+    // Three 300ms pauses one after another would take 900ms
+    assert!(
+        started.elapsed() < std::time::Duration::from_millis(800),
+        "{:?}",
+        started.elapsed()
+    );
+}
+
+#[tokio::test]
+async fn test_on_error_recovers_a_failed_tool_through_the_server() {
+    let summary = run_sequence(json!([
+        {
+            "tool_name": "click_element",
+            "arguments": {"selector": "role:Button|name:Nowhere", "timeout_ms": 100, "retries": 0},
+            "on_error": [{
+                "when": "env.error.kind == 'tool_error' && env.error.category == 'user'",
+                "set_env": {"recovered": true},
+                "steps": [{"tool_name": "delay", "arguments": {"delay_ms": 1}}],
+                "then": "continue"
+            }]
+        },
+        {"tool_name": "delay", "arguments": {"delay_ms": 1}, "if": "env.recovered == true"}
+    ]))
+    .await;
+
+    let failed = &summary["results"][0];
+    assert_eq!(failed["status"], "recovered");
+    assert_eq!(failed["on_error"]["results"][0]["status"], "success");
+    assert_eq!(summary["results"][1]["status"], "success");
+}
+
+#[tokio::test]
+async fn test_finished_run_ends_its_event_stream() {
+    use futures::StreamExt;
+    use terminator_mcp_agent::workflow_events::{runs, ExecutionEvent};
+
+    let summary = run_sequence(json!([
+        {"tool_name": "delay", "arguments": {"delay_ms": 1}, "id": "wait"}
+    ]))
+    .await;
+
+    // The run stays registered for get_events, yet its stream has ended
+    let run_id = summary["run_id"].as_str().unwrap();
+    let (_, emitter) = runs().get(Some(run_id)).unwrap();
+    let events = emitter.since(0).events;
+    let Some(ExecutionEvent::RunFinished { status, .. }) = events.last().map(|e| &e.event) else {
+        panic!("expected RunFinished last, got {events:?}");
+    };
+    assert_eq!(status, "success");
+    let late: Vec<_> = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        emitter.subscribe().collect::<Vec<_>>(),
+    )
+    .await
+    .expect("the finished run's stream ends");
+    assert!(late.is_empty());
+}
@@ -0,0 +1,106 @@
+use std::time::{Duration, Instant};
+use terminator_mcp_agent::step_timeout::{run_with_timeout, step_timeout, StepTimedOut};
+use terminator_mcp_agent::utils::SequenceStep;
+use tokio_util::sync::CancellationToken;
+
+fn tool_step(timeout: &str) -> SequenceStep {
+    SequenceStep {
+        tool_name: Some("wait_for_element".to_string()),
+        timeout: Some(timeout.to_string()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_step_sleeping_past_its_timeout_is_abandoned() {
+    let token = CancellationToken::new();
+    let limit = step_timeout(&tool_step("100ms")).unwrap();
+    let started = Instant::now();
+
+    let outcome =
+        run_with_timeout(tokio::time::sleep(Duration::from_secs(30)), limit, &token).await;
+
+    assert_eq!(
+        outcome,
+        Err(StepTimedOut {
+            limit: Duration::from_millis(100)
+        })
+    );
+    assert!(started.elapsed() < Duration::from_secs(5));
+    // Work watching the step's token is told to stop
+    assert!(token.is_cancelled());
+    assert_eq!(
+        outcome.unwrap_err().to_string(),
+        "StepTimeout: step did not finish within 100ms"
+    );
+}
+
+#[tokio::test]
+async fn test_step_finishing_in_time_keeps_its_result() {
+    let token = CancellationToken::new();
+    let step = async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        "clicked"
+    };
+
+    let outcome = run_with_timeout(step, Some(Duration::from_secs(5)), &token).await;
+
+    assert_eq!(outcome, Ok("clicked"));
+    assert!(!token.is_cancelled());
+}
+
+#[tokio::test]
+async fn test_step_without_timeout_runs_to_completion() {
+    let outcome = run_with_timeout(async { 42 }, None, &CancellationToken::new()).await;
+    assert_eq!(outcome, Ok(42));
+}
+
+#[tokio::test]
+async fn test_request_cancellation_still_reaches_the_step() {
+    let request = CancellationToken::new();
+    let step_token = request.child_token();
+    let canceller = request.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        canceller.cancel();
+    });
+
+    // A step that stops once its token is cancelled, like a cancellable tool
+    let watched = step_token.clone();
+    let step = async move {
+        tokio::select! {
+            _ = tokio::time::sleep(Duration::from_secs(30)) => "finished",
+            _ = watched.cancelled() => "cancelled",
+        }
+    };
+    let started = Instant::now();
+
+    let outcome = run_with_timeout(step, Some(Duration::from_secs(30)), &step_token).await;
+
+    assert_eq!(outcome, Ok("cancelled"));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_step_timeout_settings() {
+    assert_eq!(
+        step_timeout(&tool_step("1m30s")).unwrap(),
+        Some(Duration::from_secs(90))
+    );
+    assert_eq!(step_timeout(&SequenceStep::default()).unwrap(), None);
+
+    assert!(step_timeout(&tool_step("soon"))
+        .unwrap_err()
+        .starts_with("Invalid timeout 'soon'"));
+    assert!(step_timeout(&tool_step("0s")).is_err());
+
+    let group = SequenceStep {
+        group_name: Some("login".to_string()),
+        timeout: Some("30s".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        step_timeout(&group).unwrap_err(),
+        "timeout is only supported on tool steps"
+    );
+}
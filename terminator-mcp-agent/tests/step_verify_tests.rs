@@ -0,0 +1,146 @@
+use rmcp::model::{CallToolResult, Content};
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::Mutex;
+use terminator_mcp_agent::helpers::verify_step;
+use terminator_mcp_agent::run_bundle::{RunOutcome, ToolBackend};
+use terminator_mcp_agent::utils::{DesktopWrapper, ExecuteSequenceArgs, SequenceStep};
+use tokio_util::sync::CancellationToken;
+
+/// A dialog that only opens on the n-th click of its button; each click reports
+/// whether the dialog is open
+struct FlakyDialog {
+    opens_on_click: u32,
+    clicks: Mutex<u32>,
+    calls: Mutex<Vec<String>>,
+}
+
+impl FlakyDialog {
+    fn new(opens_on_click: u32) -> Self {
+        Self {
+            opens_on_click,
+            clicks: Mutex::new(0),
+            calls: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl ToolBackend for FlakyDialog {
+    fn call_tool(
+        &self,
+        tool_name: &str,
+        _arguments: &Value,
+        _variables: &Value,
+        _cancellation: CancellationToken,
+    ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send {
+        self.calls.lock().unwrap().push(tool_name.to_string());
+        let mut clicks = self.clicks.lock().unwrap();
+        if tool_name == "click_element" {
+            *clicks += 1;
+        }
+        let content = json!({"dialog_open": *clicks >= self.opens_on_click});
+        std::future::ready(Ok(CallToolResult::success(vec![
+            Content::json(content).unwrap()
+        ])))
+    }
+}
+
+fn workflow(retries: u32) -> ExecuteSequenceArgs {
+    ExecuteSequenceArgs {
+        steps: Some(vec![
+            SequenceStep {
+                id: Some("open_dialog".to_string()),
+                tool_name: Some("click_element".to_string()),
+                arguments: Some(json!({"selector": "role:Button|name:Open"})),
+                verify: Some("env.open_dialog_result[0].dialog_open == true".to_string()),
+                retries: Some(retries),
+                ..Default::default()
+            },
+            SequenceStep {
+                tool_name: Some("type_into_element".to_string()),
+                arguments: Some(json!({"selector": "role:Edit", "text_to_type": "hello"})),
+                ..Default::default()
+            },
+        ]),
+        ..Default::default()
+    }
+}
+
+async fn run(workflow: ExecuteSequenceArgs, desktop: &FlakyDialog) -> RunOutcome {
+    let summary = DesktopWrapper::new()
+        .unwrap()
+        .execute_sequence_with(desktop, &CancellationToken::new(), workflow)
+        .await
+        .unwrap();
+    RunOutcome::from_summary(&summary)
+}
+
+#[tokio::test]
+async fn test_passing_verification_continues() {
+    let desktop = FlakyDialog::new(1);
+    let outcome = run(workflow(2), &desktop).await;
+
+    assert_eq!(
+        *desktop.calls.lock().unwrap(),
+        vec!["click_element", "type_into_element"]
+    );
+    assert_eq!(outcome.status, "success");
+    assert_eq!(outcome.step_statuses, vec!["success", "success"]);
+}
+
+#[tokio::test]
+async fn test_failed_verification_retries_the_click() {
+    let desktop = FlakyDialog::new(2);
+    let outcome = run(workflow(2), &desktop).await;
+
+    // The first click did not open the dialog, so it was repeated before moving on
+    assert_eq!(
+        *desktop.calls.lock().unwrap(),
+        vec!["click_element", "click_element", "type_into_element"]
+    );
+    assert_eq!(outcome.step_statuses, vec!["success", "success"]);
+}
+
+#[tokio::test]
+async fn test_verification_failing_on_every_attempt_fails_the_step() {
+    let desktop = FlakyDialog::new(10);
+    let outcome = run(workflow(1), &desktop).await;
+
+    assert_eq!(*desktop.clicks.lock().unwrap(), 2);
+    assert_eq!(outcome.status, "partial_success");
+    assert_eq!(outcome.step_statuses, vec!["error", "skipped"]);
+}
+
+#[test]
+fn test_verify_step_reports_the_failed_condition() {
+    let variables = json!({"env": {"saved": false}});
+    let result = json!({"status": "success", "action": "click"});
+
+    let failed = verify_step("env.saved == true", &result, &variables, false).unwrap();
+    assert_eq!(failed["status"], "error");
+    assert_eq!(failed["error"], "Post-condition not met: env.saved == true");
+    assert_eq!(failed["action"], "click");
+    assert_eq!(failed["verification_failed"], true);
+
+    let skipped = verify_step("env.saved == true", &result, &variables, true).unwrap();
+    assert_eq!(skipped["status"], "skipped");
+
+    let variables = json!({"env": {"saved": true}});
+    assert_eq!(
+        verify_step("env.saved == true", &result, &variables, false),
+        None
+    );
+}
+
+#[test]
+fn test_verify_step_explains_a_condition_it_cannot_evaluate() {
+    let variables = json!({"env": {}});
+    let result = json!({"status": "success"});
+
+    let failed = verify_step("env.dialog_opne == true", &result, &variables, false).unwrap();
+    assert_eq!(
+        failed["error"],
+        "Post-condition not met: env.dialog_opne == true ('env.dialog_opne' is not defined)"
+    );
+}
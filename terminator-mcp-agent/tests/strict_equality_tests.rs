@@ -0,0 +1,32 @@
+//! The process-wide equality mode. Kept in its own test binary because it changes
+//! global state that the other expression tests rely on.
+use serde_json::json;
+use terminator_mcp_agent::expression_eval::{
+    evaluate, evaluate_value, set_strict_equality, strict_equality_enabled, EvalOptions,
+};
+
+#[test]
+fn test_global_strict_equality_toggle() {
+    let vars = json!({ "text": "42", "number": 42 });
+
+    set_strict_equality(false);
+    assert!(!strict_equality_enabled());
+    assert!(evaluate("text == number", &vars));
+    assert_eq!(
+        evaluate_value("text == number", &vars).unwrap(),
+        json!(true)
+    );
+
+    set_strict_equality(true);
+    assert!(strict_equality_enabled());
+    assert!(EvalOptions::default().strict_equality);
+    assert!(!evaluate("text == number", &vars));
+    assert_eq!(
+        evaluate_value("text == number", &vars).unwrap(),
+        json!(false)
+    );
+    assert!(evaluate("number == 42", &vars));
+
+    set_strict_equality(false);
+    assert!(evaluate("text == number", &vars));
+}
@@ -0,0 +1,171 @@
+use terminator_mcp_agent::telemetry::{
+    load_or_create_hash_key, scrub_attribute, scrub_message, HASH_KEY_FILE_ENV,
+};
+
+/// Keeps the hashing key these tests create out of the home directory
+fn use_test_hash_key() {
+    static ONCE: std::sync::Once = std::sync::Once::new();
+    ONCE.call_once(|| {
+        let path =
+            std::env::temp_dir().join(format!("terminator-telemetry-{}.key", std::process::id()));
+        std::env::set_var(HASH_KEY_FILE_ENV, path);
+    });
+}
+
+#[test]
+fn test_scrub_keeps_structural_attributes() {
+    assert_eq!(
+        scrub_attribute("tool.name", "click_element").as_deref(),
+        Some("click_element")
+    );
+    assert_eq!(scrub_attribute("step.number", "3").as_deref(), Some("3"));
+    assert_eq!(
+        scrub_attribute("step.status", "success").as_deref(),
+        Some("success")
+    );
+}
+
+#[test]
+fn test_scrub_drops_coordinates() {
+    for key in [
+        "x",
+        "click.y",
+        "start_x",
+        "bounds",
+        "element.position",
+        "coordinates",
+    ] {
+        assert_eq!(scrub_attribute(key, "120"), None, "{key} should be dropped");
+    }
+}
+
+#[test]
+fn test_scrub_hashes_text() {
+    use_test_hash_key();
+    let scrubbed = scrub_attribute("text", "Account number 12345").unwrap();
+    assert!(scrubbed.starts_with("hash:"), "{scrubbed}");
+    assert!(!scrubbed.contains("12345"));
+    // Equal values hash equally so failures can still be correlated
+    assert_eq!(
+        scrub_attribute("selector", "name:Submit"),
+        scrub_attribute("selector", "name:Submit")
+    );
+    assert_ne!(
+        scrub_message("Element 'Submit' not found"),
+        scrub_message("Element 'Cancel' not found")
+    );
+}
+
+#[test]
+fn test_scrub_hash_is_keyed() {
+    use_test_hash_key();
+    // An unkeyed hash of a short value could be reversed by hashing guesses
+    let unkeyed = format!("hash:{}", &blake3::hash(b"12345").to_hex()[..16]);
+    assert_ne!(scrub_attribute("text", "12345").unwrap(), unkeyed);
+}
+
+#[test]
+fn test_hash_key_is_created_once_and_reused() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("nested").join("telemetry.key");
+
+    let key = load_or_create_hash_key(&path);
+    assert!(path.exists());
+    assert_eq!(load_or_create_hash_key(&path), key);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // Another install gets another key
+    let other = dir.path().join("other.key");
+    assert_ne!(load_or_create_hash_key(&other), key);
+
+    // A malformed key file is replaced
+    std::fs::write(&path, "not a key").unwrap();
+    let replaced = load_or_create_hash_key(&path);
+    assert_ne!(replaced, key);
+    assert_eq!(load_or_create_hash_key(&path), replaced);
+}
+
+#[cfg(feature = "telemetry")]
+#[test]
+fn test_privacy_mode_keeps_text_and_coordinates_out_of_spans() {
+    use_test_hash_key();
+    use opentelemetry::trace::Status;
+    use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use terminator_mcp_agent::telemetry::{set_privacy_mode, StepSpan, WorkflowSpan};
+
+    let exporter = InMemorySpanExporter::default();
+    let provider = TracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    opentelemetry::global::set_tracer_provider(provider.clone());
+    set_privacy_mode(true);
+
+    let secret = "Jane Doe";
+    let mut workflow = WorkflowSpan::new("privacy_workflow");
+    workflow.add_event(
+        "step.started",
+        vec![
+            ("step.tool", "type_into_element".to_string()),
+            ("text", secret.to_string()),
+            ("click.x", "640".to_string()),
+        ],
+    );
+    let mut step = StepSpan::new("type_into_element", Some("fill_name"));
+    step.set_attribute("text_to_type", secret.to_string());
+    step.set_attribute("selector", format!("role:Edit|name:{secret}"));
+    step.set_attribute("x", "640".to_string());
+    step.set_attribute("bounds", "(640, 480, 100, 20)".to_string());
+    step.set_status(false, Some(&format!("Could not type '{secret}'")));
+    step.end();
+    workflow.set_status(true, "done");
+    workflow.end();
+    let _ = provider.force_flush();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let step_span = spans
+        .iter()
+        .find(|s| s.name == "step.type_into_element")
+        .expect("step span exported");
+    let workflow_span = spans
+        .iter()
+        .find(|s| s.name == "privacy_workflow")
+        .expect("workflow span exported");
+
+    let mut attributes: Vec<_> = step_span
+        .attributes
+        .iter()
+        .chain(workflow_span.attributes.iter())
+        .collect();
+    for event in workflow_span.events.iter() {
+        attributes.extend(event.attributes.iter());
+    }
+
+    for kv in &attributes {
+        let key = kv.key.as_str();
+        let value = kv.value.as_str();
+        assert!(!value.contains(secret), "raw text leaked in {key}={value}");
+        assert!(
+            !["x", "click.x", "bounds"].contains(&key),
+            "coordinate attribute {key} was exported"
+        );
+    }
+    // Structural attributes survive
+    assert!(attributes
+        .iter()
+        .any(|kv| kv.key.as_str() == "tool.name" && kv.value.as_str() == "type_into_element"));
+
+    match &step_span.status {
+        Status::Error { description } => {
+            assert!(!description.contains(secret), "{description}")
+        }
+        other => panic!("expected an error status, got {other:?}"),
+    }
+
+    set_privacy_mode(false);
+}
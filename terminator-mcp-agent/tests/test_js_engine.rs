@@ -1,4 +1,5 @@
-use terminator_mcp_agent::scripting_engine;
+use std::time::{Duration, Instant};
+use terminator_mcp_agent::scripting_engine::{self, ScriptLanguage, ScriptLogLevel, ScriptLogLine};
 
 #[tokio::test]
 #[ignore = "Requires local bindings which aren't available in CI"]
@@ -60,3 +61,194 @@ async fn test_javascript_engine_with_desktop_api() {
     assert_eq!(result["result"]["hasDesktop"], true);
     assert_eq!(result["result"]["hasLocator"], true);
 }
+
+#[tokio::test]
+#[ignore = "Requires local bindings which aren't available in CI"]
+async fn test_javascript_engine_captures_console_output_in_order() {
+    // Skip npm install in tests to avoid timeout
+    std::env::set_var("TERMINATOR_SKIP_NPM_INSTALL", "1");
+
+    let script = r#"
+        console.log('loading', 3, 'rows');
+        console.error('row 2 is empty');
+        console.warn({ skipped: 1 });
+        return { loaded: 2 };
+    "#
+    .to_string();
+
+    let result = scripting_engine::execute_javascript_with_nodejs(script, None)
+        .await
+        .expect("JavaScript execution should succeed");
+
+    assert_eq!(result["result"]["loaded"], 2);
+    let logs: Vec<ScriptLogLine> = serde_json::from_value(result["logs"].clone()).unwrap();
+    let logs: Vec<(ScriptLogLevel, &str)> =
+        logs.iter().map(|l| (l.level, l.message.as_str())).collect();
+    assert_eq!(
+        logs,
+        vec![
+            (ScriptLogLevel::Log, "loading 3 rows"),
+            (ScriptLogLevel::Error, "row 2 is empty"),
+            (ScriptLogLevel::Warn, "{ skipped: 1 }"),
+        ]
+    );
+}
+
+#[tokio::test]
+#[ignore = "Requires local bindings which aren't available in CI"]
+async fn test_javascript_engine_keeps_logs_when_the_script_throws() {
+    // Skip npm install in tests to avoid timeout
+    std::env::set_var("TERMINATOR_SKIP_NPM_INSTALL", "1");
+
+    let script = "console.log('before'); throw new Error('bad row');".to_string();
+    let error = scripting_engine::execute_javascript_with_nodejs(script, None)
+        .await
+        .expect_err("The script throws");
+
+    let data = error.data.unwrap();
+    assert_eq!(data["message"], "bad row");
+    assert_eq!(data["logs"][0]["message"], "before");
+}
+
+#[tokio::test]
+#[ignore = "Requires local bindings which aren't available in CI"]
+async fn test_typescript_engine_captures_console_output_in_order() {
+    // Skip npm install in tests to avoid timeout
+    std::env::set_var("TERMINATOR_SKIP_NPM_INSTALL", "1");
+
+    let script = r#"
+        const rows: number[] = [1, 2, 3];
+        console.log('loading', rows.length, 'rows');
+        console.error('row 2 is empty');
+        setEnv({ loaded: 2 });
+        return { loaded: 2 };
+    "#
+    .to_string();
+
+    let result = scripting_engine::execute_typescript_with_nodejs(script, None)
+        .await
+        .expect("TypeScript execution should succeed");
+
+    assert_eq!(result["result"]["loaded"], 2);
+    assert_eq!(result["result"]["set_env"]["loaded"], "2");
+    let logs: Vec<ScriptLogLine> = serde_json::from_value(result["logs"].clone()).unwrap();
+    let logs: Vec<(ScriptLogLevel, &str)> =
+        logs.iter().map(|l| (l.level, l.message.as_str())).collect();
+    assert_eq!(
+        logs,
+        vec![
+            (ScriptLogLevel::Log, "loading 3 rows"),
+            (ScriptLogLevel::Error, "row 2 is empty"),
+        ]
+    );
+}
+
+#[tokio::test]
+#[ignore = "Requires local bindings which aren't available in CI"]
+async fn test_typescript_engine_keeps_logs_when_the_script_throws() {
+    // Skip npm install in tests to avoid timeout
+    std::env::set_var("TERMINATOR_SKIP_NPM_INSTALL", "1");
+
+    let script = "console.log('before'); throw new Error('bad row');".to_string();
+    let error = scripting_engine::execute_typescript_with_nodejs(script, None)
+        .await
+        .expect_err("The script throws");
+
+    let data = error.data.unwrap();
+    assert_eq!(data["message"], "bad row");
+    assert_eq!(data["logs"][0]["message"], "before");
+}
+
+#[test]
+fn test_script_log_lines_are_read_from_stdout() {
+    assert_eq!(
+        ScriptLogLine::from_stdout(r#"__LOG__{"level":"error","message":"row 2\nis empty"}"#),
+        ScriptLogLine {
+            level: ScriptLogLevel::Error,
+            message: "row 2\nis empty".to_string(),
+        }
+    );
+    // Output written without console is kept as a plain log line
+    assert_eq!(
+        ScriptLogLine::from_stdout("progress: 50%"),
+        ScriptLogLine {
+            level: ScriptLogLevel::Log,
+            message: "progress: 50%".to_string(),
+        }
+    );
+    assert_eq!(
+        serde_json::to_value(ScriptLogLine::from_stdout("done")).unwrap(),
+        serde_json::json!({"level": "log", "message": "done"})
+    );
+}
+
+#[tokio::test]
+#[ignore = "Requires local bindings which aren't available in CI"]
+async fn test_javascript_busy_loop_is_killed_at_the_timeout() {
+    // Skip npm install in tests to avoid timeout
+    std::env::set_var("TERMINATOR_SKIP_NPM_INSTALL", "1");
+
+    // Never yields, so only killing the process can stop it
+    let script = "while (true) {}".to_string();
+    let started = Instant::now();
+    let error = scripting_engine::run_script(
+        ScriptLanguage::JavaScript,
+        script,
+        true,
+        None,
+        Duration::from_secs(2),
+    )
+    .await
+    .expect_err("A busy loop should time out");
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert!(
+        error.message.starts_with("ScriptTimeout"),
+        "{}",
+        error.message
+    );
+    assert_eq!(error.data.unwrap()["reason"], "script_timeout");
+}
+
+#[test]
+fn test_script_timeout_parses_durations() {
+    assert_eq!(
+        scripting_engine::script_timeout(Some("30s")).unwrap().limit,
+        Duration::from_secs(30)
+    );
+    let requested = scripting_engine::script_timeout(Some("1m30s")).unwrap();
+    assert_eq!(requested.limit, Duration::from_secs(90));
+    assert!(requested.explicit);
+    assert!(scripting_engine::script_timeout(Some("0s")).is_err());
+    assert!(scripting_engine::script_timeout(Some("soon")).is_err());
+}
+
+#[test]
+fn test_desktop_is_only_given_to_steps_that_ask_for_it() {
+    use scripting_engine::desktop_allowed;
+
+    assert!(desktop_allowed(Some(true)));
+    assert!(!desktop_allowed(Some(false)));
+    assert!(!desktop_allowed(None));
+}
+
+#[tokio::test]
+#[ignore = "Requires local bindings which aren't available in CI"]
+async fn test_withheld_desktop_is_not_defined() {
+    // Skip npm install in tests to avoid timeout
+    std::env::set_var("TERMINATOR_SKIP_NPM_INSTALL", "1");
+
+    let script = "return { desktop: typeof desktop };".to_string();
+    for (allowed, expected) in [(true, "object"), (false, "undefined")] {
+        let result = scripting_engine::run_script(
+            ScriptLanguage::JavaScript,
+            script.clone(),
+            allowed,
+            None,
+            Duration::from_secs(60),
+        )
+        .await
+        .expect("JavaScript execution should succeed");
+        assert_eq!(result["result"]["desktop"], expected);
+    }
+}
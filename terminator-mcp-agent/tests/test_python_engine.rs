@@ -0,0 +1,181 @@
+use std::time::Duration;
+use terminator_mcp_agent::scripting_engine::{self, ScriptLanguage};
+
+#[test]
+fn test_engine_names_map_to_languages() {
+    assert_eq!(
+        ScriptLanguage::from_engine("python"),
+        Some(ScriptLanguage::Python)
+    );
+    assert_eq!(
+        ScriptLanguage::from_engine("PY"),
+        Some(ScriptLanguage::Python)
+    );
+    assert_eq!(
+        ScriptLanguage::from_engine("bun"),
+        Some(ScriptLanguage::JavaScript)
+    );
+    assert_eq!(
+        ScriptLanguage::from_engine("ts"),
+        Some(ScriptLanguage::TypeScript)
+    );
+    assert_eq!(ScriptLanguage::from_engine("ruby"), None);
+}
+
+#[tokio::test]
+#[ignore = "Requires Python with terminator.py, which isn't available in CI"]
+async fn test_python_engine_computes_from_variables() {
+    // Variables are injected ahead of the script, the way run_command does it
+    let script = r#"variables = {"price": 20, "quantity": 3}
+env = {"currency": "EUR"}
+total = variables["price"] * variables["quantity"]
+return {"total": total, "label": f"{total} {env['currency']}"}"#
+        .to_string();
+
+    // With `desktop` the script always runs on the system Python
+    let result = scripting_engine::run_script(
+        ScriptLanguage::Python,
+        script,
+        true,
+        None,
+        Duration::from_secs(60),
+    )
+    .await
+    .expect("Python execution should succeed");
+
+    assert_eq!(result["result"]["total"], 60);
+    assert_eq!(result["result"]["label"], "60 EUR");
+}
+
+#[tokio::test]
+#[ignore = "Requires Python with terminator.py, which isn't available in CI"]
+async fn test_python_engine_captures_logs_in_order() {
+    let script = r#"log("loading", 3, "rows")
+print("plain output")
+log("row 2 is empty", level="error")
+return {"loaded": 2}"#
+        .to_string();
+
+    let result = scripting_engine::run_script(
+        ScriptLanguage::Python,
+        script,
+        true,
+        None,
+        Duration::from_secs(60),
+    )
+    .await
+    .expect("Python execution should succeed");
+
+    assert_eq!(result["result"]["loaded"], 2);
+    assert_eq!(
+        result["logs"],
+        serde_json::json!([
+            {"level": "log", "message": "loading 3 rows"},
+            {"level": "log", "message": "plain output"},
+            {"level": "error", "message": "row 2 is empty"},
+        ])
+    );
+}
+
+/// Runs `script` the way run_command runs a script without `desktop` or a `timeout`
+#[cfg(feature = "embedded-python")]
+async fn run_embedded(script: &str) -> Result<serde_json::Value, String> {
+    scripting_engine::run_script(
+        ScriptLanguage::Python,
+        script.to_string(),
+        false,
+        None,
+        scripting_engine::script_timeout(None).unwrap(),
+    )
+    .await
+    .map_err(|e| format!("{}: {}", e.message, e.data.unwrap_or_default()))
+}
+
+#[cfg(feature = "embedded-python")]
+#[tokio::test]
+async fn test_embedded_python_computes_from_variables() {
+    // The prelude run_command writes: JSON values, including true and null
+    let script = r#"variables = {"price": 20, "quantity": 3, "discounted": true, "note": null}
+env = {"currency": "EUR"}
+total = variables["price"] * variables["quantity"]
+if variables["discounted"]:
+    total = round(total * 0.9, 2)
+log("computed", total)
+await sleep(10)
+return {"total": total, "label": f"{total} {env['currency']}", "note": variables["note"]}"#;
+
+    let result = run_embedded(script).await.unwrap();
+    assert_eq!(
+        result["result"],
+        serde_json::json!({"total": 54.0, "label": "54.0 EUR", "note": null})
+    );
+    assert_eq!(
+        result["logs"],
+        serde_json::json!([{"level": "log", "message": "computed 54.0"}])
+    );
+}
+
+#[cfg(feature = "embedded-python")]
+#[tokio::test]
+async fn test_embedded_python_logs_carry_their_level() {
+    let script = r#"log("starting")
+log("row", 2, "is empty", level="warn")
+print("plain", "output", sep="-")
+log("unknown levels are plain logs", level="fatal")
+return None"#;
+
+    let result = run_embedded(script).await.unwrap();
+    assert_eq!(result["result"], serde_json::Value::Null);
+    assert_eq!(
+        result["logs"],
+        serde_json::json!([
+            {"level": "log", "message": "starting"},
+            {"level": "warn", "message": "row 2 is empty"},
+            {"level": "log", "message": "plain-output"},
+            {"level": "log", "message": "unknown levels are plain logs"},
+        ])
+    );
+}
+
+#[cfg(feature = "embedded-python")]
+#[tokio::test]
+async fn test_embedded_python_errors_carry_the_exception() {
+    let error = run_embedded("return {}['missing']").await.unwrap_err();
+    assert!(error.starts_with("Python execution error"), "{error}");
+    assert!(error.contains("KeyError"), "{error}");
+
+    // Lines logged before the failure are kept
+    let error = run_embedded("log('before', level='info')\nraise ValueError('bad row')")
+        .await
+        .unwrap_err();
+    assert!(
+        error.contains(r#""logs":[{"level":"info","message":"before"}]"#),
+        "{error}"
+    );
+
+    // A value that isn't JSON is an error too
+    let error = run_embedded("return {1, 2}").await.unwrap_err();
+    assert!(error.contains("not JSON serializable"), "{error}");
+}
+
+#[tokio::test]
+#[ignore = "Requires Python with terminator.py, which isn't available in CI"]
+async fn test_python_busy_loop_is_killed_at_an_explicit_timeout() {
+    // An explicit timeout keeps the script in a python process, even without `desktop`
+    let started = std::time::Instant::now();
+    let error = scripting_engine::run_script(
+        ScriptLanguage::Python,
+        "while True:\n    pass".to_string(),
+        false,
+        None,
+        scripting_engine::script_timeout(Some("1s")).unwrap(),
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        error.message.starts_with("ScriptTimeout"),
+        "{}",
+        error.message
+    );
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
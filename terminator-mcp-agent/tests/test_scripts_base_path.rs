@@ -105,6 +105,9 @@ steps:
             end_at_step: Some("step5".to_string()),
             follow_fallback: Some(false),
             scripts_base_path: Some("/custom/path".to_string()),
+            modal_watcher: None,
+            export_bundle: None,
+            persistence_key: None,
         };
 
         let serialized = serde_json::to_string(&args).unwrap();
@@ -0,0 +1,83 @@
+use terminator_mcp_agent::telemetry::parse_sample_rate;
+
+#[test]
+fn test_sample_rate_parsing() {
+    assert_eq!(parse_sample_rate("0.25"), Some(0.25));
+    assert_eq!(parse_sample_rate(" 1 "), Some(1.0));
+    assert_eq!(parse_sample_rate("0"), Some(0.0));
+    // Out-of-range rates are clamped rather than rejected
+    assert_eq!(parse_sample_rate("2.5"), Some(1.0));
+    assert_eq!(parse_sample_rate("-1"), Some(0.0));
+
+    assert_eq!(parse_sample_rate("half"), None);
+    assert_eq!(parse_sample_rate(""), None);
+    assert_eq!(parse_sample_rate("NaN"), None);
+}
+
+#[cfg(feature = "telemetry")]
+#[test]
+fn test_step_spans_are_sampled_with_their_workflow() {
+    use opentelemetry::{trace::Status, KeyValue};
+    use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use terminator_mcp_agent::telemetry::{trace_sampler, StepSpan, WorkflowSpan};
+
+    fn run_workflow(name: &str) {
+        let workflow = WorkflowSpan::new(name);
+        let mut step = StepSpan::child_of(&workflow, "click_element", Some("submit"));
+        step.set_attribute("step.selector", "role:Button|name:Submit".to_string());
+        step.set_attribute("step.outcome", "failure".to_string());
+        step.set_status(false, Some("Element not found"));
+        step.end();
+        workflow.end();
+    }
+
+    // Everything sampled: the step span is exported inside the workflow's trace
+    let exporter = InMemorySpanExporter::default();
+    let provider = TracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .with_sampler(trace_sampler(1.0))
+        .build();
+    opentelemetry::global::set_tracer_provider(provider.clone());
+    run_workflow("sampled_workflow");
+    let _ = provider.force_flush();
+
+    let spans = exporter.get_finished_spans().unwrap();
+    let workflow = spans
+        .iter()
+        .find(|s| s.name == "sampled_workflow")
+        .expect("workflow span exported");
+    let step = spans
+        .iter()
+        .find(|s| s.name == "step.click_element")
+        .expect("step span exported");
+    assert_eq!(
+        step.span_context.trace_id(),
+        workflow.span_context.trace_id()
+    );
+    assert_eq!(step.parent_span_id, workflow.span_context.span_id());
+    for expected in [
+        KeyValue::new("step.id", "submit"),
+        KeyValue::new("step.selector", "role:Button|name:Submit"),
+        KeyValue::new("step.outcome", "failure"),
+    ] {
+        assert!(
+            step.attributes.contains(&expected),
+            "missing {expected:?} in {:?}",
+            step.attributes
+        );
+    }
+    assert!(matches!(step.status, Status::Error { .. }));
+
+    // Nothing sampled: neither the workflow nor its steps are exported
+    let exporter = InMemorySpanExporter::default();
+    let provider = TracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .with_sampler(trace_sampler(0.0))
+        .build();
+    opentelemetry::global::set_tracer_provider(provider.clone());
+    run_workflow("dropped_workflow");
+    let _ = provider.force_flush();
+
+    assert!(exporter.get_finished_spans().unwrap().is_empty());
+}
@@ -0,0 +1,356 @@
+use terminator::{UIElementAttributes, UINode};
+use terminator_mcp_agent::tree_formatter::{format_tree, TreeFormatOptions};
+
+fn node(role: &str, name: &str, children: Vec<UINode>) -> UINode {
+    UINode {
+        id: None,
+        attributes: UIElementAttributes {
+            role: role.to_string(),
+            name: (!name.is_empty()).then(|| name.to_string()),
+            ..Default::default()
+        },
+        children,
+    }
+}
+
+/// A window holding a toolbar of three buttons and a form with an edit and a nested group
+fn sample_tree() -> UINode {
+    node(
+        "Window",
+        "Login",
+        vec![
+            node(
+                "ToolBar",
+                "",
+                vec![
+                    node("Button", "Back", vec![]),
+                    node("Button", "Forward", vec![]),
+                    node("Button", "Reload", vec![]),
+                ],
+            ),
+            node(
+                "Group",
+                "Form",
+                vec![
+                    node("Edit", "User name", vec![]),
+                    node("Group", "", vec![node("Button", "Sign in", vec![])]),
+                ],
+            ),
+        ],
+    )
+}
+
+#[test]
+fn renders_every_level_by_default() {
+    let text = format_tree(&sample_tree(), &TreeFormatOptions::default());
+    assert_eq!(
+        text,
+        "Window \"Login\"\n\
+         \x20 ToolBar\n\
+         \x20   Button \"Back\"\n\
+         \x20   Button \"Forward\"\n\
+         \x20   Button \"Reload\"\n\
+         \x20 Group \"Form\"\n\
+         \x20   Edit \"User name\"\n\
+         \x20   Group\n\
+         \x20     Button \"Sign in\"\n"
+    );
+}
+
+#[test]
+fn value_is_shown_after_the_name() {
+    let mut edit = node("Edit", "User name", vec![]);
+    edit.attributes.value = Some("alice".to_string());
+    let text = format_tree(&edit, &TreeFormatOptions::default());
+    assert_eq!(text, "Edit \"User name\" value=\"alice\"\n");
+}
+
+#[test]
+fn max_depth_replaces_deeper_levels_with_an_omitted_count() {
+    let options = TreeFormatOptions {
+        max_depth: Some(1),
+        ..Default::default()
+    };
+    let text = format_tree(&sample_tree(), &options);
+    assert_eq!(
+        text,
+        "Window \"Login\"\n\
+         \x20 ToolBar\n\
+         \x20   … 3 children omitted\n\
+         \x20 Group \"Form\"\n\
+         \x20   … 2 children omitted\n"
+    );
+}
+
+#[test]
+fn omitted_count_uses_the_singular_for_one_child() {
+    let options = TreeFormatOptions {
+        max_depth: Some(2),
+        ..Default::default()
+    };
+    let text = format_tree(&sample_tree(), &options);
+    assert!(
+        text.ends_with("    Group\n      … 1 child omitted\n"),
+        "{text}"
+    );
+    assert!(!text.contains("Sign in"));
+}
+
+#[test]
+fn max_depth_zero_renders_only_the_root() {
+    let options = TreeFormatOptions {
+        max_depth: Some(0),
+        ..Default::default()
+    };
+    let text = format_tree(&sample_tree(), &options);
+    assert_eq!(text, "Window \"Login\"\n  … 2 children omitted\n");
+}
+
+#[test]
+fn leaves_at_max_depth_get_no_marker() {
+    let options = TreeFormatOptions {
+        max_depth: Some(3),
+        ..Default::default()
+    };
+    let limited = format_tree(&sample_tree(), &options);
+    let full = format_tree(&sample_tree(), &TreeFormatOptions::default());
+    assert_eq!(limited, full);
+    assert!(!limited.contains('…'));
+}
+
+fn interactive_controls() -> TreeFormatOptions {
+    TreeFormatOptions {
+        roles: vec!["button".to_string(), "Edit".to_string()],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn role_filter_keeps_matches_and_their_ancestors() {
+    let mut tree = sample_tree();
+    tree.children.push(node(
+        "Group",
+        "Footer",
+        vec![node("Text", "Copyright", vec![])],
+    ));
+    let text = format_tree(&tree, &interactive_controls());
+    assert_eq!(
+        text,
+        "Window \"Login\"\n\
+         \x20 ToolBar\n\
+         \x20   Button \"Back\"\n\
+         \x20   Button \"Forward\"\n\
+         \x20   Button \"Reload\"\n\
+         \x20 Group \"Form\"\n\
+         \x20   Edit \"User name\"\n\
+         \x20   Group\n\
+         \x20     Button \"Sign in\"\n"
+    );
+}
+
+#[test]
+fn name_filter_is_a_case_insensitive_substring() {
+    let options = TreeFormatOptions {
+        name_contains: Some("SIGN".to_string()),
+        ..Default::default()
+    };
+    let text = format_tree(&sample_tree(), &options);
+    assert_eq!(
+        text,
+        "Window \"Login\"\n\
+         \x20 Group \"Form\"\n\
+         \x20   Group\n\
+         \x20     Button \"Sign in\"\n"
+    );
+}
+
+#[test]
+fn role_and_name_filters_must_both_match() {
+    let options = TreeFormatOptions {
+        name_contains: Some("r".to_string()),
+        ..interactive_controls()
+    };
+    let text = format_tree(&sample_tree(), &options);
+    assert_eq!(
+        text,
+        "Window \"Login\"\n\
+         \x20 ToolBar\n\
+         \x20   Button \"Forward\"\n\
+         \x20   Button \"Reload\"\n\
+         \x20 Group \"Form\"\n\
+         \x20   Edit \"User name\"\n"
+    );
+}
+
+#[test]
+fn filter_without_matches_renders_only_the_root() {
+    let options = TreeFormatOptions {
+        roles: vec!["Slider".to_string()],
+        ..Default::default()
+    };
+    let text = format_tree(&sample_tree(), &options);
+    assert_eq!(text, "Window \"Login\"\n");
+}
+
+#[test]
+fn omitted_count_only_counts_children_kept_by_the_filter() {
+    let options = TreeFormatOptions {
+        max_depth: Some(1),
+        name_contains: Some("back".to_string()),
+        ..Default::default()
+    };
+    let text = format_tree(&sample_tree(), &options);
+    assert_eq!(
+        text,
+        "Window \"Login\"\n\
+         \x20 ToolBar\n\
+         \x20   … 1 child omitted\n"
+    );
+}
+
+fn with_paths(options: TreeFormatOptions) -> TreeFormatOptions {
+    TreeFormatOptions {
+        show_paths: true,
+        ..options
+    }
+}
+
+#[test]
+fn paths_number_siblings_by_role() {
+    let text = format_tree(&sample_tree(), &with_paths(TreeFormatOptions::default()));
+    assert_eq!(
+        text,
+        "Window \"Login\" path=/\n\
+         \x20 ToolBar path=/ToolBar[1]\n\
+         \x20   Button \"Back\" path=/ToolBar[1]/Button[1]\n\
+         \x20   Button \"Forward\" path=/ToolBar[1]/Button[2]\n\
+         \x20   Button \"Reload\" path=/ToolBar[1]/Button[3]\n\
+         \x20 Group \"Form\" path=/Group[1]\n\
+         \x20   Edit \"User name\" path=/Group[1]/Edit[1]\n\
+         \x20   Group path=/Group[1]/Group[1]\n\
+         \x20     Button \"Sign in\" path=/Group[1]/Group[1]/Button[1]\n"
+    );
+}
+
+#[test]
+fn paths_are_the_same_across_two_formats_of_a_snapshot() {
+    let tree = sample_tree();
+    let options = with_paths(TreeFormatOptions::default());
+    assert_eq!(format_tree(&tree, &options), format_tree(&tree, &options));
+}
+
+#[test]
+fn filtering_does_not_renumber_paths() {
+    let full = format_tree(&sample_tree(), &with_paths(TreeFormatOptions::default()));
+    let options = with_paths(TreeFormatOptions {
+        name_contains: Some("reload".to_string()),
+        ..Default::default()
+    });
+    let filtered = format_tree(&sample_tree(), &options);
+    assert!(filtered.contains("Button \"Reload\" path=/ToolBar[1]/Button[3]\n"));
+    for line in filtered.lines() {
+        assert!(full.contains(line.trim_start()), "{line}");
+    }
+}
+
+#[test]
+fn paths_follow_the_element_not_its_line() {
+    let mut tree = sample_tree();
+    tree.children[0]
+        .children
+        .insert(0, node("Separator", "", vec![]));
+    let text = format_tree(&tree, &with_paths(TreeFormatOptions::default()));
+    assert!(text.contains("Separator path=/ToolBar[1]/Separator[1]\n"));
+    assert!(text.contains("Button \"Back\" path=/ToolBar[1]/Button[1]\n"));
+}
+
+fn with_bounds(mut node: UINode, bounds: (f64, f64, f64, f64)) -> UINode {
+    node.attributes.bounds = Some(bounds);
+    node
+}
+
+/// A 800x600 window at (100, 50) with a list holding a visible row, a row scrolled
+/// above the window, a collapsed row and a row without bounds
+fn list_tree() -> UINode {
+    with_bounds(
+        node(
+            "Window",
+            "Inbox",
+            vec![with_bounds(
+                node(
+                    "List",
+                    "",
+                    vec![
+                        with_bounds(
+                            node("ListItem", "Visible", vec![]),
+                            (110.0, 80.0, 300.5, 20.0),
+                        ),
+                        with_bounds(
+                            node("ListItem", "Scrolled", vec![]),
+                            (110.0, -40.0, 300.0, 20.0),
+                        ),
+                        with_bounds(
+                            node("ListItem", "Collapsed", vec![]),
+                            (110.0, 100.0, 300.0, 0.0),
+                        ),
+                        node("ListItem", "Unknown", vec![]),
+                    ],
+                ),
+                (100.0, 50.0, 800.0, 600.0),
+            )],
+        ),
+        (100.0, 50.0, 800.0, 600.0),
+    )
+}
+
+#[test]
+fn bounds_are_appended_to_each_line() {
+    let options = TreeFormatOptions {
+        show_bounds: true,
+        ..Default::default()
+    };
+    let text = format_tree(&list_tree(), &options);
+    assert_eq!(
+        text,
+        "Window \"Inbox\" bounds=100,50,800,600\n\
+         \x20 List bounds=100,50,800,600\n\
+         \x20   ListItem \"Visible\" bounds=110,80,300.5,20\n\
+         \x20   ListItem \"Scrolled\" bounds=110,-40,300,20 (off-screen)\n\
+         \x20   ListItem \"Collapsed\" bounds=110,100,300,0 (zero-size)\n\
+         \x20   ListItem \"Unknown\"\n"
+    );
+}
+
+#[test]
+fn bounds_touching_the_window_edge_are_off_screen() {
+    let tree = with_bounds(
+        node(
+            "Window",
+            "",
+            vec![
+                with_bounds(node("Button", "Left", vec![]), (80.0, 60.0, 20.0, 20.0)),
+                with_bounds(node("Button", "Right", vec![]), (899.0, 60.0, 20.0, 20.0)),
+            ],
+        ),
+        (100.0, 50.0, 800.0, 600.0),
+    );
+    let options = TreeFormatOptions {
+        show_bounds: true,
+        ..Default::default()
+    };
+    let text = format_tree(&tree, &options);
+    assert!(
+        text.contains("Button \"Left\" bounds=80,60,20,20 (off-screen)\n"),
+        "{text}"
+    );
+    assert!(
+        text.contains("Button \"Right\" bounds=899,60,20,20\n"),
+        "{text}"
+    );
+}
+
+#[test]
+fn bounds_are_hidden_unless_requested() {
+    let text = format_tree(&list_tree(), &TreeFormatOptions::default());
+    assert!(!text.contains("bounds="));
+}
@@ -35,6 +35,9 @@ fn test_execute_sequence_args_serialization() {
         follow_fallback: None,
         end_at_step: None,
         troubleshooting: None,
+        modal_watcher: None,
+        export_bundle: None,
+        persistence_key: None,
     };
 
     let json = serde_json::to_string(&args).unwrap();
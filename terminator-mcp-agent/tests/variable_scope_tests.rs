@@ -0,0 +1,168 @@
+use rmcp::model::{CallToolResult, Content};
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Map, Value};
+use std::future::Future;
+use std::sync::Mutex;
+use terminator_mcp_agent::run_bundle::{RunOutcome, ToolBackend};
+use terminator_mcp_agent::utils::{DesktopWrapper, ExecuteSequenceArgs, SequenceStep, ToolCall};
+use terminator_mcp_agent::variable_scope::ScopeStack;
+use tokio_util::sync::CancellationToken;
+
+fn env(value: Value) -> Map<String, Value> {
+    value.as_object().cloned().unwrap()
+}
+
+#[test]
+fn test_block_variables_are_removed_on_exit() {
+    let mut env = env(json!({"user": "ada"}));
+    let mut scopes = ScopeStack::new();
+
+    scopes.enter(&env);
+    env.insert("iteration".to_string(), json!(3));
+    env.insert("row".to_string(), json!({"id": 7}));
+    assert_eq!(scopes.depth(), 1);
+
+    let removed = scopes.exit(&mut env);
+
+    assert_eq!(removed, vec!["iteration", "row"]);
+    assert_eq!(Value::Object(env), json!({"user": "ada"}));
+    assert_eq!(scopes.depth(), 0);
+}
+
+#[test]
+fn test_updates_to_outer_variables_survive_the_block() {
+    let mut env = env(json!({"total": 0}));
+    let mut scopes = ScopeStack::new();
+
+    scopes.enter(&env);
+    env.insert("total".to_string(), json!(42));
+    scopes.exit(&mut env);
+
+    assert_eq!(env["total"], json!(42));
+}
+
+#[test]
+fn test_promoted_variables_move_to_the_enclosing_scope() {
+    let mut env = Map::new();
+    let mut scopes = ScopeStack::new();
+
+    scopes.enter(&env);
+    scopes.enter(&env);
+    scopes.promote("invoice_id");
+    env.insert("invoice_id".to_string(), json!("INV-1"));
+    env.insert("cell".to_string(), json!("B2"));
+    assert_eq!(scopes.exit(&mut env), vec!["cell"]);
+
+    // Visible to the rest of the outer block, then cleaned up with it
+    assert_eq!(env["invoice_id"], json!("INV-1"));
+    assert_eq!(scopes.exit(&mut env), vec!["invoice_id"]);
+    assert!(env.is_empty());
+}
+
+#[test]
+fn test_exit_without_open_block_changes_nothing() {
+    let mut env = env(json!({"user": "ada"}));
+    assert!(ScopeStack::new().exit(&mut env).is_empty());
+    assert_eq!(env.len(), 1);
+}
+
+/// Searches one page per `run_command`, reporting the page and the invoice it found
+#[derive(Default)]
+struct InvoiceDesktop {
+    pages: Mutex<u32>,
+    calls: Mutex<Vec<String>>,
+}
+
+impl ToolBackend for InvoiceDesktop {
+    fn call_tool(
+        &self,
+        tool_name: &str,
+        _arguments: &Value,
+        _variables: &Value,
+        _cancellation: CancellationToken,
+    ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send {
+        self.calls.lock().unwrap().push(tool_name.to_string());
+        let content = if tool_name == "run_command" {
+            let mut pages = self.pages.lock().unwrap();
+            *pages += 1;
+            json!({"result": {"set_env": {"page": *pages, "found_invoice": "INV-1"}}})
+        } else {
+            json!({"action": tool_name})
+        };
+        std::future::ready(Ok(CallToolResult::success(vec![
+            Content::json(content).unwrap()
+        ])))
+    }
+}
+
+async fn run(workflow: ExecuteSequenceArgs, desktop: &InvoiceDesktop) -> RunOutcome {
+    let summary = DesktopWrapper::new()
+        .unwrap()
+        .execute_sequence_with(desktop, &CancellationToken::new(), workflow)
+        .await
+        .unwrap();
+    RunOutcome::from_summary(&summary)
+}
+
+fn search_loop(promote: Option<Vec<String>>) -> ExecuteSequenceArgs {
+    ExecuteSequenceArgs {
+        steps: Some(vec![
+            SequenceStep {
+                group_name: Some("search pages".to_string()),
+                steps: Some(vec![ToolCall {
+                    tool_name: "run_command".to_string(),
+                    arguments: json!({"engine": "javascript", "run": "..."}),
+                    continue_on_error: None,
+                    delay_ms: None,
+                    id: None,
+                }]),
+                repeat_until: Some("env.page == 2".to_string()),
+                promote,
+                ..Default::default()
+            },
+            SequenceStep {
+                tool_name: Some("click_element".to_string()),
+                arguments: Some(json!({"selector": "role:Button|name:Open invoice"})),
+                r#if: Some("env.found_invoice == 'INV-1'".to_string()),
+                ..Default::default()
+            },
+            SequenceStep {
+                tool_name: Some("press_key".to_string()),
+                arguments: Some(json!({"key": "{Escape}"})),
+                r#if: Some("env.iteration == 2".to_string()),
+                ..Default::default()
+            },
+        ]),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_loop_variables_do_not_leak_out_of_the_group() {
+    let desktop = InvoiceDesktop::default();
+    let outcome = run(search_loop(None), &desktop).await;
+
+    assert_eq!(
+        *desktop.calls.lock().unwrap(),
+        vec!["run_command", "run_command"]
+    );
+    assert_eq!(outcome.status, "success");
+    assert_eq!(outcome.step_statuses, vec!["success", "skipped", "skipped"]);
+}
+
+#[tokio::test]
+async fn test_promoted_variable_is_visible_after_the_group() {
+    let desktop = InvoiceDesktop::default();
+    let outcome = run(
+        search_loop(Some(vec!["found_invoice".to_string()])),
+        &desktop,
+    )
+    .await;
+
+    // found_invoice was promoted; the loop variable was still cleaned up
+    assert_eq!(
+        *desktop.calls.lock().unwrap(),
+        vec!["run_command", "run_command", "click_element"]
+    );
+    assert_eq!(outcome.step_statuses, vec!["success", "success", "skipped"]);
+}
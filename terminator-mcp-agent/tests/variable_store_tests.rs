@@ -0,0 +1,149 @@
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use terminator_mcp_agent::utils::{VariableDefinition, VariableType};
+use terminator_mcp_agent::variable_store::{
+    collect_persistent_values, persistent_names, VariableStore,
+};
+
+fn number_var(default: i64, persistent: bool) -> VariableDefinition {
+    VariableDefinition {
+        r#type: VariableType::Number,
+        label: "n".to_string(),
+        description: None,
+        default: Some(json!(default)),
+        regex: None,
+        options: None,
+        required: Some(false),
+        persistent: Some(persistent),
+    }
+}
+
+fn temp_store() -> VariableStore {
+    VariableStore::new(
+        std::env::temp_dir().join(format!("terminator_vars_{}", uuid::Uuid::new_v4())),
+    )
+}
+
+/// Mirrors what a run does: defaults, then stored values, then a script bumping both
+/// counters in env, then saving the persistent ones on completion.
+async fn simulate_run(
+    store: &VariableStore,
+    definitions: &HashMap<String, VariableDefinition>,
+) -> Map<String, Value> {
+    let mut context = Map::new();
+    for (name, def) in definitions {
+        context.insert(name.clone(), def.default.clone().unwrap());
+    }
+    let names = persistent_names(definitions);
+    let stored = store.load("counter-workflow").await.unwrap();
+    for name in &names {
+        if let Some(value) = stored.get(name) {
+            context.insert(name.clone(), value.clone());
+        }
+    }
+
+    let env = json!({
+        "run_count": context["run_count"].as_i64().unwrap() + 1,
+        "scratch": context["scratch"].as_i64().unwrap() + 1,
+    });
+    context.insert("env".to_string(), env);
+
+    let values = collect_persistent_values(&names, &context);
+    store.save("counter-workflow", &values).await.unwrap();
+    context
+}
+
+#[tokio::test]
+async fn test_persistent_counter_increments_across_runs() {
+    let store = temp_store();
+    let definitions = HashMap::from([
+        ("run_count".to_string(), number_var(0, true)),
+        ("scratch".to_string(), number_var(0, false)),
+    ]);
+
+    let first = simulate_run(&store, &definitions).await;
+    assert_eq!(first["env"]["run_count"], 1);
+
+    let second = simulate_run(&store, &definitions).await;
+    assert_eq!(second["env"]["run_count"], 2);
+    // The non-persistent variable starts from its default every run
+    assert_eq!(second["scratch"], 0);
+    assert_eq!(second["env"]["scratch"], 1);
+
+    let stored = store.load("counter-workflow").await.unwrap();
+    assert_eq!(stored.get("run_count"), Some(&json!(2)));
+    assert!(!stored.contains_key("scratch"));
+
+    let _ = std::fs::remove_dir_all(store.dir());
+}
+
+#[tokio::test]
+async fn test_missing_store_loads_empty() {
+    let store = temp_store();
+    assert!(store.load("never-saved").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_concurrent_saves_leave_a_valid_store() {
+    let store = temp_store();
+    let handles: Vec<_> = (0..20)
+        .map(|i| {
+            let store = store.clone();
+            tokio::spawn(async move {
+                let mut values = Map::new();
+                values.insert("last_id".to_string(), json!(i));
+                store.save("shared", &values).await
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.await.unwrap().unwrap();
+    }
+
+    let stored = store.load("shared").await.unwrap();
+    let last_id = stored["last_id"].as_i64().unwrap();
+    assert!((0..20).contains(&last_id));
+
+    // No temp files are left behind
+    let leftovers = std::fs::read_dir(store.dir())
+        .unwrap()
+        .filter(|e| {
+            e.as_ref()
+                .unwrap()
+                .path()
+                .extension()
+                .is_some_and(|ext| ext == "tmp")
+        })
+        .count();
+    assert_eq!(leftovers, 0);
+
+    let _ = std::fs::remove_dir_all(store.dir());
+}
+
+#[tokio::test]
+async fn test_keys_differing_only_in_punctuation_are_kept_apart() {
+    let store = temp_store();
+    for (key, id) in [("orders/2024", 1), ("orders:2024", 2), ("orders_2024", 3)] {
+        let mut values = Map::new();
+        values.insert("last_id".to_string(), json!(id));
+        store.save(key, &values).await.unwrap();
+    }
+
+    assert_eq!(store.load("orders/2024").await.unwrap()["last_id"], 1);
+    assert_eq!(store.load("orders:2024").await.unwrap()["last_id"], 2);
+    assert_eq!(store.load("orders_2024").await.unwrap()["last_id"], 3);
+
+    // Each file records the key it was saved under
+    let mut keys: Vec<String> = std::fs::read_dir(store.dir())
+        .unwrap()
+        .map(|entry| {
+            let content = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            let stored: Value = serde_json::from_str(&content).unwrap();
+            stored["key"].as_str().unwrap().to_string()
+        })
+        .collect();
+    keys.sort();
+    assert_eq!(keys, ["orders/2024", "orders:2024", "orders_2024"]);
+
+    let _ = std::fs::remove_dir_all(store.dir());
+}
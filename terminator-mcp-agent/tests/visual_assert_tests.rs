@@ -0,0 +1,136 @@
+use image::{Rgba, RgbaImage};
+use terminator_mcp_agent::visual_assert::{
+    artifact_path, assert_visual, crop, diff_images, VisualOptions, VisualOutcome,
+};
+
+/// A 20x10 white "button" with a blue 10x4 label area
+fn button(label: Rgba<u8>) -> RgbaImage {
+    RgbaImage::from_fn(20, 10, |x, y| {
+        if (5..15).contains(&x) && (3..7).contains(&y) {
+            label
+        } else {
+            Rgba([255, 255, 255, 255])
+        }
+    })
+}
+
+const BLUE: Rgba<u8> = Rgba([0, 0, 200, 255]);
+
+#[test]
+fn test_first_run_creates_the_baseline() {
+    let dir = tempfile::tempdir().unwrap();
+    let baseline = dir.path().join("baselines").join("button.png");
+
+    let outcome = assert_visual(&button(BLUE), &baseline, &VisualOptions::default()).unwrap();
+    assert_eq!(
+        outcome,
+        VisualOutcome::BaselineCreated {
+            baseline: baseline.clone()
+        }
+    );
+    assert!(outcome.passed());
+    assert_eq!(image::open(&baseline).unwrap().to_rgba8(), button(BLUE));
+}
+
+#[test]
+fn test_matching_capture_passes() {
+    let dir = tempfile::tempdir().unwrap();
+    let baseline = dir.path().join("button.png");
+    assert_visual(&button(BLUE), &baseline, &VisualOptions::default()).unwrap();
+
+    // Slight rendering noise stays under the per-pixel threshold
+    let noisy = button(Rgba([4, 2, 210, 255]));
+    let outcome = assert_visual(&noisy, &baseline, &VisualOptions::default()).unwrap();
+    match &outcome {
+        VisualOutcome::Matched { diff, .. } => {
+            assert_eq!(diff.differing_pixels, 0);
+            assert_eq!(diff.total_pixels, 200);
+        }
+        other => panic!("expected a match, got {other:?}"),
+    }
+    assert!(outcome.passed());
+    assert!(!artifact_path(&baseline, "diff").exists());
+}
+
+#[test]
+fn test_deviating_capture_fails_with_diff_artifact() {
+    let dir = tempfile::tempdir().unwrap();
+    let baseline = dir.path().join("button.png");
+    assert_visual(&button(BLUE), &baseline, &VisualOptions::default()).unwrap();
+
+    // The label turned red: 40 of 200 pixels (20%) differ
+    let changed = button(Rgba([200, 0, 0, 255]));
+    let outcome = assert_visual(&changed, &baseline, &VisualOptions::default()).unwrap();
+    assert!(!outcome.passed());
+    let VisualOutcome::Deviated {
+        diff,
+        diff_path,
+        actual_path,
+        ..
+    } = outcome
+    else {
+        panic!("expected a deviation");
+    };
+    assert_eq!(diff.differing_pixels, 40);
+    assert!((diff.ratio - 0.2).abs() < f64::EPSILON);
+    assert!(!diff.size_mismatch);
+
+    assert_eq!(diff_path, dir.path().join("button.diff.png"));
+    let diff_image = image::open(&diff_path).unwrap().to_rgba8();
+    assert_eq!(*diff_image.get_pixel(10, 5), Rgba([255, 0, 0, 255]));
+    assert_ne!(*diff_image.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+    assert_eq!(image::open(&actual_path).unwrap().to_rgba8(), changed);
+
+    // The baseline itself is left alone, and a looser tolerance accepts the change
+    assert_eq!(image::open(&baseline).unwrap().to_rgba8(), button(BLUE));
+    let loose = VisualOptions {
+        tolerance: 0.25,
+        ..VisualOptions::default()
+    };
+    assert!(assert_visual(&changed, &baseline, &loose).unwrap().passed());
+    assert!(
+        !diff_path.exists(),
+        "stale artifacts are removed on a match"
+    );
+}
+
+#[test]
+fn test_update_flag_replaces_the_baseline() {
+    let dir = tempfile::tempdir().unwrap();
+    let baseline = dir.path().join("button.png");
+    assert_visual(&button(BLUE), &baseline, &VisualOptions::default()).unwrap();
+
+    let changed = button(Rgba([200, 0, 0, 255]));
+    let update = VisualOptions {
+        update_baseline: true,
+        ..VisualOptions::default()
+    };
+    assert!(matches!(
+        assert_visual(&changed, &baseline, &update).unwrap(),
+        VisualOutcome::BaselineUpdated { .. }
+    ));
+    assert!(matches!(
+        assert_visual(&changed, &baseline, &VisualOptions::default()).unwrap(),
+        VisualOutcome::Matched { .. }
+    ));
+}
+
+#[test]
+fn test_size_mismatch_never_passes() {
+    let small = RgbaImage::from_pixel(10, 10, Rgba([255, 255, 255, 255]));
+    let wide = RgbaImage::from_pixel(20, 10, Rgba([255, 255, 255, 255]));
+
+    let (diff, diff_image) = diff_images(&small, &wide, 16);
+    assert!(diff.size_mismatch);
+    assert_eq!(diff.total_pixels, 200);
+    assert_eq!(diff.differing_pixels, 100);
+    assert_eq!(diff_image.dimensions(), (20, 10));
+}
+
+#[test]
+fn test_crop_region() {
+    let label = crop(&button(BLUE), 5, 3, 10, 4).unwrap();
+    assert_eq!(label, RgbaImage::from_pixel(10, 4, BLUE));
+    assert!(crop(&button(BLUE), 15, 0, 10, 4).is_err());
+    assert!(crop(&button(BLUE), 0, 0, 0, 4).is_err());
+}
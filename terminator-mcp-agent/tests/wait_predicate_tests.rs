@@ -0,0 +1,304 @@
+use rmcp::model::{CallToolResult, Content};
+use rmcp::ErrorData as McpError;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use terminator_mcp_agent::run_bundle::ToolBackend;
+use terminator_mcp_agent::utils::{DesktopWrapper, ExecuteSequenceArgs};
+use terminator_mcp_agent::wait_predicate::{
+    parse_wait_timing, wait_for_composite, wait_until, ElementCondition, WaitFailure,
+    DEFAULT_POLL_INTERVAL,
+};
+use tokio_util::sync::CancellationToken;
+
+#[tokio::test]
+async fn test_composite_predicate_becomes_true() {
+    // The button becomes enabled on the 3rd poll, the variable flips on the 5th
+    let polls = Arc::new(AtomicU32::new(0));
+    let variables = Arc::new(Mutex::new(json!({"env": {"status": "loading"}})));
+
+    let element_polls = polls.clone();
+    let element_vars = variables.clone();
+    let result = wait_for_composite(
+        move || {
+            let n = element_polls.fetch_add(1, Ordering::SeqCst) + 1;
+            if n >= 5 {
+                *element_vars.lock().unwrap() = json!({"env": {"status": "ready"}});
+            }
+            async move { n >= 3 }
+        },
+        Some("env.status == 'ready'"),
+        || variables.lock().unwrap().clone(),
+        Duration::from_secs(2),
+        Duration::from_millis(5),
+    )
+    .await;
+
+    let status = result.expect("composite predicate should hold");
+    assert!(status.element_met && status.expression_met);
+    assert_eq!(status.polls, 5);
+}
+
+#[tokio::test]
+async fn test_composite_predicate_times_out_when_only_element_holds() {
+    let result = wait_for_composite(
+        || async { true },
+        Some("env.status == 'ready'"),
+        || json!({"env": {"status": "loading"}}),
+        Duration::from_millis(60),
+        Duration::from_millis(10),
+    )
+    .await;
+
+    let status = result.expect_err("should time out");
+    assert!(status.element_met);
+    assert!(!status.expression_met);
+    assert!(status.elapsed_ms >= 60);
+}
+
+#[tokio::test]
+async fn test_composite_predicate_times_out_when_only_expression_holds() {
+    let result = wait_for_composite(
+        || async { false },
+        Some("env.status == 'ready'"),
+        || json!({"env": {"status": "ready"}}),
+        Duration::from_millis(60),
+        Duration::from_millis(10),
+    )
+    .await;
+
+    let status = result.expect_err("should time out");
+    assert!(!status.element_met);
+    assert!(status.expression_met);
+}
+
+#[tokio::test]
+async fn test_predicate_without_expression_only_checks_element() {
+    let result = wait_for_composite(
+        || async { true },
+        None,
+        || json!({}),
+        Duration::from_millis(50),
+        Duration::from_millis(10),
+    )
+    .await;
+    assert_eq!(result.unwrap().polls, 1);
+}
+
+#[test]
+fn test_element_condition_parsing() {
+    assert_eq!(
+        ElementCondition::parse("Enabled"),
+        Some(ElementCondition::Enabled)
+    );
+    assert_eq!(
+        ElementCondition::parse(" exists "),
+        Some(ElementCondition::Exists)
+    );
+    assert_eq!(ElementCondition::parse("clickable"), None);
+}
+
+/// An element that appears once `appears_after` has passed since the wait started
+fn element_appearing_after(appears_after: Duration) -> impl FnMut() -> std::future::Ready<bool> {
+    let start = Instant::now();
+    move || std::future::ready(start.elapsed() >= appears_after)
+}
+
+#[tokio::test]
+async fn test_wait_returns_as_soon_as_the_element_appears() {
+    let result = wait_until(
+        element_appearing_after(Duration::from_millis(80)),
+        None,
+        || json!({}),
+        Duration::from_secs(5),
+        Duration::from_millis(10),
+        &CancellationToken::new(),
+    )
+    .await;
+
+    let status = result.expect("the element appears before the timeout");
+    assert!(status.elapsed_ms >= 80);
+    // Returned within about one poll interval, nowhere near the 5s timeout
+    assert!(status.elapsed_ms < 1000, "{}", status.elapsed_ms);
+    assert!(status.polls > 1);
+}
+
+#[tokio::test]
+async fn test_poll_interval_sets_how_often_the_element_is_checked() {
+    let polls = Arc::new(AtomicU32::new(0));
+    let counter = polls.clone();
+    let result = wait_until(
+        move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { false }
+        },
+        None,
+        || json!({}),
+        Duration::from_millis(200),
+        Duration::from_millis(50),
+        &CancellationToken::new(),
+    )
+    .await;
+
+    let WaitFailure::TimedOut(status) = result.expect_err("never appears") else {
+        panic!("expected a timeout");
+    };
+    assert_eq!(status.polls, polls.load(Ordering::SeqCst));
+    // About one poll every 50ms over 200ms, not one every few milliseconds
+    assert!((3..=6).contains(&status.polls), "{}", status.polls);
+}
+
+#[tokio::test]
+async fn test_cancellation_ends_the_wait() {
+    let cancellation = CancellationToken::new();
+    let cancel = cancellation.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cancel.cancel();
+    });
+
+    let started = Instant::now();
+    let result = wait_until(
+        element_appearing_after(Duration::from_secs(60)),
+        None,
+        || json!({}),
+        Duration::from_secs(30),
+        Duration::from_secs(10),
+        &cancellation,
+    )
+    .await;
+
+    // Cancelled in the middle of the 10s sleep between polls
+    assert!(matches!(result, Err(WaitFailure::Cancelled(_))));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_cancellation_interrupts_a_slow_poll() {
+    let cancellation = CancellationToken::new();
+    cancellation.cancel();
+
+    let result = wait_until(
+        || async { true },
+        None,
+        || json!({}),
+        Duration::from_secs(5),
+        Duration::from_millis(10),
+        &cancellation,
+    )
+    .await;
+    assert_eq!(result.unwrap_err().status().polls, 0);
+
+    let cancellation = CancellationToken::new();
+    let cancel = cancellation.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        cancel.cancel();
+    });
+    let started = Instant::now();
+    let result = wait_until(
+        || async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            true
+        },
+        None,
+        || json!({}),
+        Duration::from_secs(60),
+        Duration::from_millis(10),
+        &cancellation,
+    )
+    .await;
+    assert!(matches!(result, Err(WaitFailure::Cancelled(_))));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn test_wait_timing_parses_durations() {
+    let default = Duration::from_secs(3);
+    assert_eq!(
+        parse_wait_timing(Some("10s"), None, Some("250ms"), default),
+        Ok((Duration::from_secs(10), Duration::from_millis(250)))
+    );
+    assert_eq!(
+        parse_wait_timing(Some("1m30s"), Some(500), None, default),
+        Ok((Duration::from_secs(90), DEFAULT_POLL_INTERVAL))
+    );
+    assert_eq!(
+        parse_wait_timing(None, Some(500), None, default),
+        Ok((Duration::from_millis(500), DEFAULT_POLL_INTERVAL))
+    );
+    assert_eq!(
+        parse_wait_timing(None, None, None, default),
+        Ok((default, DEFAULT_POLL_INTERVAL))
+    );
+
+    let error = parse_wait_timing(Some("soon"), None, None, default).unwrap_err();
+    assert!(error.starts_with("Invalid timeout 'soon'"), "{error}");
+    let error = parse_wait_timing(None, None, Some("10x"), default).unwrap_err();
+    assert!(error.starts_with("Invalid poll_interval '10x'"), "{error}");
+    assert!(parse_wait_timing(None, None, Some("0ms"), default).is_err());
+}
+
+/// Records the arguments and variables of every `wait_for_element` call; scripts report
+/// the status as ready
+#[derive(Default)]
+struct WaitRecorder {
+    waits: Mutex<Vec<(Value, Value)>>,
+}
+
+impl ToolBackend for WaitRecorder {
+    fn call_tool(
+        &self,
+        tool_name: &str,
+        arguments: &Value,
+        variables: &Value,
+        _cancellation: CancellationToken,
+    ) -> impl Future<Output = Result<CallToolResult, McpError>> + Send {
+        let content = if tool_name == "run_command" {
+            json!({"result": {"set_env": {"status": "ready"}}})
+        } else {
+            self.waits
+                .lock()
+                .unwrap()
+                .push((arguments.clone(), variables.clone()));
+            json!({"action": tool_name, "status": "success"})
+        };
+        std::future::ready(Ok(CallToolResult::success(vec![
+            Content::json(content).unwrap()
+        ])))
+    }
+}
+
+#[tokio::test]
+async fn test_sequence_passes_workflow_variables_to_the_wait() {
+    let workflow: ExecuteSequenceArgs = serde_json::from_value(json!({
+        "steps": [
+            {"tool_name": "run_command", "arguments": {"engine": "javascript", "run": "..."}},
+            {
+                "tool_name": "wait_for_element",
+                "arguments": {
+                    "selector": "role:Button|name:Save",
+                    "condition": "enabled",
+                    "expression": "env.status == 'ready'"
+                }
+            }
+        ]
+    }))
+    .unwrap();
+    let recorder = WaitRecorder::default();
+
+    DesktopWrapper::new()
+        .unwrap()
+        .execute_sequence_with(&recorder, &CancellationToken::new(), workflow)
+        .await
+        .unwrap();
+
+    let waits = recorder.waits.lock().unwrap();
+    assert_eq!(waits.len(), 1);
+    let (arguments, variables) = &waits[0];
+    // The variables travel beside the call, not in the tool's arguments
+    assert!(arguments.get("variables").is_none());
+    assert_eq!(variables["env"]["status"], "ready");
+}
@@ -0,0 +1,82 @@
+use serde_json::json;
+use terminator_mcp_agent::utils::ExecuteSequenceArgs;
+use terminator_mcp_agent::workflow_markdown::to_markdown;
+
+fn workflow() -> ExecuteSequenceArgs {
+    serde_json::from_value(json!({
+        "variables": {
+            "app": {"type": "string", "label": "Application", "default": "notepad"},
+            "mode": {
+                "type": "enum",
+                "label": "Save mode",
+                "description": "How the file is written",
+                "options": ["overwrite", "append"],
+                "required": false
+            }
+        },
+        "steps": [
+            {
+                "id": "open",
+                "tool_name": "open_application",
+                "arguments": {"app_name": "{{app}}"},
+                "retries": 2
+            },
+            {
+                "id": "fill",
+                "group_name": "Fill form",
+                "steps": [
+                    {"tool_name": "type_into_element", "arguments": {"selector": "role:Edit|name:Body", "text_to_type": "hello"}},
+                    {"tool_name": "click_element", "arguments": {"selector": "role:Button|name:Save"}}
+                ]
+            },
+            {
+                "tool_name": "click_element",
+                "arguments": {"selector": "role:Button|name:Append"},
+                "if": "mode == 'append' && !contains(app, 'word')",
+                "fallback_id": "recover"
+            }
+        ],
+        "troubleshooting": [
+            {"id": "recover", "tool_name": "press_key", "arguments": {"key": "{Escape}"}}
+        ],
+        "output_parser": {
+            "ui_tree_source_step_id": "fill",
+            "javascript_file_path": "parsers/summary.js"
+        }
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_workflow_matches_markdown_fixture() {
+    assert_eq!(
+        to_markdown(&workflow()),
+        include_str!("fixtures/workflow_markdown.md")
+    );
+}
+
+#[test]
+fn test_empty_workflow() {
+    let workflow: ExecuteSequenceArgs = serde_json::from_value(json!({})).unwrap();
+    let markdown = to_markdown(&workflow);
+    assert!(markdown.contains("## Inputs\n\n_No inputs._\n"));
+    assert!(markdown.contains("## Steps\n\n_No steps._\n"));
+    assert!(!markdown.contains("## Troubleshooting"));
+    assert!(markdown.ends_with("The workflow returns the result of each step.\n"));
+}
+
+#[test]
+fn test_backticks_and_pipes_are_escaped() {
+    let workflow: ExecuteSequenceArgs = serde_json::from_value(json!({
+        "variables": {
+            "cmd": {"type": "string", "label": "Shell | command", "default": "echo `date`"}
+        },
+        "steps": [
+            {"tool_name": "run_command", "arguments": {"run": "echo `date`"}}
+        ]
+    }))
+    .unwrap();
+    let markdown = to_markdown(&workflow);
+    assert!(markdown.contains("| `cmd` | string | yes | ``\"echo `date`\"`` | Shell \\| command |"));
+    assert!(markdown.contains("   - `run`: `` echo `date` ``\n"));
+}
@@ -0,0 +1,100 @@
+use serde_json::{json, Value};
+use terminator_mcp_agent::utils::ExecuteSequenceArgs;
+use terminator_mcp_agent::workflow_plan::{plan, PlannedStatus};
+
+fn workflow() -> ExecuteSequenceArgs {
+    serde_json::from_value(json!({
+        "variables": {
+            "app": {"type": "string", "label": "Application", "default": "notepad"},
+            "submit": {"type": "boolean", "label": "Submit the form", "default": false}
+        },
+        "selectors": {"save": "role:Button|name:Save"},
+        "steps": [
+            {
+                "id": "open",
+                "tool_name": "open_application",
+                "arguments": {"app_name": "{{app}}"}
+            },
+            {
+                "id": "fill",
+                "group_name": "Fill form",
+                "steps": [
+                    {"tool_name": "type_into_element", "arguments": {"selector": "role:Edit|name:{{app}}", "text_to_type": "hello"}},
+                    {"tool_name": "click_element", "arguments": {"selector": "{{selectors.save}}"}}
+                ]
+            },
+            {
+                "id": "submit",
+                "tool_name": "click_element",
+                "arguments": {"selector": "role:Button|name:Submit"},
+                "if": "submit == true"
+            },
+            {
+                "id": "confirm",
+                "tool_name": "wait_for_element",
+                "arguments": {"selector": "role:Window|name:Done"},
+                "if": "submit_status == 'success'"
+            }
+        ]
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_plan_marks_step_skipped_when_condition_is_false() {
+    let plan = plan(&workflow(), None).unwrap();
+
+    assert_eq!(plan.steps.len(), 4);
+    let submit = &plan.steps[2];
+    assert_eq!(submit.id.as_deref(), Some("submit"));
+    assert_eq!(submit.condition.as_deref(), Some("submit == true"));
+    assert_eq!(submit.condition_result, Some(false));
+    assert_eq!(submit.status, PlannedStatus::Skipped);
+    assert!(!submit.runtime_dependent);
+
+    // Steps without a condition run
+    assert_eq!(plan.steps[0].status, PlannedStatus::Run);
+    assert_eq!(plan.steps[0].condition_result, None);
+    assert_eq!(
+        plan.skipped()
+            .filter_map(|s| s.id.as_deref())
+            .collect::<Vec<_>>(),
+        ["submit", "confirm"]
+    );
+    // The last step depends on the outcome of "submit", which only exists at runtime
+    assert!(plan.steps[3].runtime_dependent);
+}
+
+#[test]
+fn test_plan_uses_inputs_and_interpolates_selectors() {
+    let inputs = json!({"app": "calc", "submit": true});
+    let plan = plan(&workflow(), Some(&inputs)).unwrap();
+
+    assert_eq!(plan.steps[2].status, PlannedStatus::Run);
+    assert_eq!(plan.steps[2].condition_result, Some(true));
+    assert_eq!(plan.steps[0].calls[0].arguments["app_name"], "calc");
+
+    let group = &plan.steps[1];
+    assert_eq!(group.group_name.as_deref(), Some("Fill form"));
+    let selectors: Vec<&str> = group
+        .calls
+        .iter()
+        .flat_map(|c| c.selectors.iter().map(String::as_str))
+        .collect();
+    assert_eq!(selectors, ["role:Edit|name:calc", "role:Button|name:Save"]);
+    assert_eq!(plan.variables["env"], json!({}));
+}
+
+#[test]
+fn test_plan_rejects_missing_required_variable() {
+    let workflow: ExecuteSequenceArgs = serde_json::from_value(json!({
+        "variables": {"customer": {"type": "string", "label": "Customer"}},
+        "steps": [{"tool_name": "delay", "arguments": {"delay_ms": 10}}]
+    }))
+    .unwrap();
+
+    let err = plan(&workflow, None).unwrap_err();
+    assert_eq!(err.field, "customer");
+    assert!(plan(&workflow, Some(&json!({"customer": "ACME"}))).is_ok());
+    assert!(plan(&workflow, Some(&Value::Null)).is_err());
+}
@@ -197,6 +197,78 @@ impl SerializableUIElement {
     }
 }
 
+/// Screen rectangle of an element, in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ElementBounds {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl From<(f64, f64, f64, f64)> for ElementBounds {
+    fn from((x, y, width, height): (f64, f64, f64, f64)) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// Interaction state of an element. A state that could not be read is `false`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ElementStates {
+    pub enabled: bool,
+    pub focused: bool,
+    pub keyboard_focusable: bool,
+    pub selected: bool,
+    pub toggled: bool,
+}
+
+/// The element summary shared by search results, tree nodes, the focused element
+/// and MCP tool results.
+///
+/// Every field is always serialized (missing values as `null`), so clients can rely
+/// on one shape whichever API produced it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ElementInfo {
+    pub role: String,
+    pub name: Option<String>,
+    pub automation_id: Option<String>,
+    pub bounds: Option<ElementBounds>,
+    pub value: Option<String>,
+    pub states: ElementStates,
+}
+
+impl ElementInfo {
+    /// Builds the info from an attribute snapshot, such as the one held by a tree node.
+    pub fn from_attributes(attributes: &UIElementAttributes) -> Self {
+        let non_empty = |s: &Option<String>| s.clone().filter(|s| !s.is_empty());
+        Self {
+            role: attributes.role.clone(),
+            name: non_empty(&attributes.name),
+            automation_id: attributes
+                .properties
+                .get("AutomationId")
+                .and_then(|value| value.as_ref())
+                .and_then(|value| value.as_str())
+                .filter(|id| !id.is_empty())
+                .map(str::to_string),
+            bounds: attributes.bounds.map(ElementBounds::from),
+            value: non_empty(&attributes.value),
+            states: ElementStates {
+                enabled: attributes.enabled.unwrap_or(false),
+                focused: attributes.is_focused.unwrap_or(false),
+                keyboard_focusable: attributes.is_keyboard_focusable.unwrap_or(false),
+                selected: attributes.is_selected.unwrap_or(false),
+                toggled: attributes.is_toggled.unwrap_or(false),
+            },
+        }
+    }
+}
+
 /// Helper functions for clean serialization
 fn is_empty_string(opt: &Option<String>) -> bool {
     match opt {
@@ -346,6 +418,11 @@ pub trait UIElementImpl: Send + Sync + Debug {
         self.attributes().name
     }
     fn children(&self) -> Result<Vec<UIElement>, AutomationError>;
+    /// Returns a copy of this element with the role, name and children of its whole
+    /// subtree fetched in one batch, or `None` when the platform reads them live.
+    fn cached_subtree(&self) -> Option<UIElement> {
+        None
+    }
     fn parent(&self) -> Result<Option<UIElement>, AutomationError>;
     fn bounds(&self) -> Result<(f64, f64, f64, f64), AutomationError>; // x, y, width, height
     fn click(&self) -> Result<ClickResult, AutomationError>;
@@ -518,6 +595,32 @@ pub trait UIElementImpl: Send + Sync + Debug {
         })
     }
 
+    /// Reads the control's current value (the value pattern on Windows), bypassing any
+    /// cached attributes. `None` when the control has no value.
+    fn get_value(&self) -> Result<Option<String>, AutomationError> {
+        Ok(self.attributes().value)
+    }
+
+    /// Whether the control must be filled in before its form is submitted
+    /// (`IsRequiredForForm` on Windows). `None` when the platform does not expose it.
+    fn is_required_for_form(&self) -> Result<Option<bool>, AutomationError> {
+        Ok(None)
+    }
+
+    /// Whether an expandable control (menu item, tree item, combo box) is expanded.
+    /// `None` when the control cannot be expanded.
+    fn is_expanded(&self) -> Result<Option<bool>, AutomationError> {
+        Ok(None)
+    }
+
+    /// Expands or collapses the control without clicking it.
+    fn set_expanded(&self, expanded: bool) -> Result<(), AutomationError> {
+        let _ = expanded;
+        Err(AutomationError::UnsupportedOperation(
+            "set_expanded is not supported on this platform".to_string(),
+        ))
+    }
+
     /// Returns the `Monitor` object that contains this element.
     ///
     /// By default this implementation uses the element's bounding box and
@@ -1007,17 +1110,7 @@ impl UIElement {
 
         let dynamic_image = image::DynamicImage::ImageRgba8(img_buffer);
 
-        // Perform OCR using uni_ocr directly
-        let engine = uni_ocr::OcrEngine::new(uni_ocr::OcrProvider::Auto).map_err(|e| {
-            AutomationError::PlatformError(format!("Failed to create OCR engine: {e}"))
-        })?;
-
-        let (text, _language, _confidence) = engine
-            .recognize_image(&dynamic_image)
-            .await
-            .map_err(|e| AutomationError::PlatformError(format!("OCR recognition failed: {e}")))?;
-
-        Ok(text)
+        crate::ocr::recognize_image(&dynamic_image).await
     }
 
     /// Close the element if it's closable (like windows, applications)
@@ -1070,6 +1163,40 @@ impl UIElement {
         self.inner.set_toggled_with_state(state)
     }
 
+    /// Gets a cell of this table or data grid by data row index and column header label.
+    ///
+    /// `row` is 0-based and does not count the header row. Fails with `InvalidArgument`
+    /// when no column has the given header.
+    pub fn get_cell(&self, row: usize, column_header: &str) -> Result<UIElement, AutomationError> {
+        crate::table::find_cell(self, row, column_header)
+    }
+
+    /// This element with its subtree's properties fetched in one batch, if the platform
+    /// supports it (see [`UIElementImpl::cached_subtree`]).
+    pub(crate) fn cached_subtree(&self) -> Option<UIElement> {
+        self.inner.cached_subtree()
+    }
+
+    /// Gets the stable identity key of this element, which stays the same when the UI
+    /// re-renders it. `None` when the element has neither an automation id nor content.
+    pub fn identity_key(&self) -> Option<String> {
+        crate::identity::identity_key(&ElementInfo::from_attributes(&self.attributes()))
+    }
+
+    /// Finds the element with identity `key` (see [`UIElement::identity_key`]) among this
+    /// element and its descendants, searching `max_depth` levels deep (default 50).
+    pub fn find_by_identity(
+        &self,
+        key: &str,
+        max_depth: Option<usize>,
+    ) -> Result<UIElement, AutomationError> {
+        crate::identity::find_by_identity(
+            self.clone(),
+            key,
+            max_depth.unwrap_or(crate::identity::DEFAULT_IDENTITY_SEARCH_DEPTH),
+        )
+    }
+
     /// Gets the current value from a range-based control like a slider or progress bar.
     pub fn get_range_value(&self) -> Result<f64, AutomationError> {
         self.inner.get_range_value()
@@ -1080,6 +1207,26 @@ impl UIElement {
         self.inner.set_range_value(value)
     }
 
+    /// Reads the control's current value live, e.g. the text of an edit field.
+    pub fn get_value(&self) -> Result<Option<String>, AutomationError> {
+        self.inner.get_value()
+    }
+
+    /// Whether the control is marked as required for its form, when the platform exposes it.
+    pub fn is_required_for_form(&self) -> Result<Option<bool>, AutomationError> {
+        self.inner.is_required_for_form()
+    }
+
+    /// Whether the control is expanded, or `None` when it cannot be expanded.
+    pub fn is_expanded(&self) -> Result<Option<bool>, AutomationError> {
+        self.inner.is_expanded()
+    }
+
+    /// Expands or collapses the control through its expand/collapse pattern.
+    pub fn set_expanded(&self, expanded: bool) -> Result<(), AutomationError> {
+        self.inner.set_expanded(expanded)
+    }
+
     /// Checks if a selectable item (e.g., in a calendar, list, or tab) is currently selected.
     pub fn is_selected(&self) -> Result<bool, AutomationError> {
         self.inner.is_selected()
@@ -1324,6 +1471,28 @@ impl UIElement {
         }
     }
 
+    /// Summarizes this element as an [`ElementInfo`].
+    ///
+    /// Starts from the element's attributes and refreshes bounds and states with live
+    /// reads, since attribute snapshots only carry some of them.
+    pub fn info(&self) -> ElementInfo {
+        let mut info = ElementInfo::from_attributes(&self.attributes());
+        if let Ok(bounds) = self.bounds() {
+            info.bounds = Some(bounds.into());
+        }
+        let states = info.states;
+        info.states = ElementStates {
+            enabled: self.is_enabled().unwrap_or(states.enabled),
+            focused: self.is_focused().unwrap_or(states.focused),
+            keyboard_focusable: self
+                .is_keyboard_focusable()
+                .unwrap_or(states.keyboard_focusable),
+            selected: self.is_selected().unwrap_or(states.selected),
+            toggled: self.is_toggled().unwrap_or(states.toggled),
+        };
+        info
+    }
+
     /// Convert this UIElement to a SerializableUIElement
     ///
     /// This creates a snapshot of the element's current state that can be
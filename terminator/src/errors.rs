@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -29,7 +30,15 @@ pub enum AutomationError {
     #[error("Invalid selector: {0}")]
     InvalidSelector(String),
 
-    #[error("UI Automation API error: {message}")]
+    #[error("Element obscured: {0}")]
+    ElementObscured(String),
+
+    /// The UI changed under an element that was already found, so it is no longer
+    /// part of the tree. Find the element again rather than retrying the action.
+    #[error("UI tree changed: {0}")]
+    TreeChanged(String),
+
+    #[error("UI Automation API error: {message}{}", format_com_error(.com_error))]
     UIAutomationAPIError {
         message: String,
         com_error: Option<i32>,
@@ -37,3 +46,136 @@ pub enum AutomationError {
         is_retryable: bool,
     },
 }
+
+/// Whether an error is worth retrying
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// The element or service was briefly unavailable; the same call may succeed later.
+    Transient,
+    /// Retrying the same call will not help.
+    Fatal,
+}
+
+/// Who is responsible for a failure, for grouping errors in reports and dashboards
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// The workflow or caller asked for something wrong: a bad selector or argument,
+    /// an element that does not exist, an operation the element does not support.
+    User,
+    /// The environment was briefly not ready; the same call may succeed later.
+    Transient,
+    /// The platform or terminator itself failed.
+    System,
+}
+
+impl ErrorCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCategory::User => "user",
+            ErrorCategory::Transient => "transient",
+            ErrorCategory::System => "system",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Well-known HRESULTs returned by UI Automation and COM calls
+pub mod hresult {
+    pub const E_FAIL: i32 = 0x80004005_u32 as i32;
+    pub const E_NOINTERFACE: i32 = 0x80004002_u32 as i32;
+    pub const E_NOTIMPL: i32 = 0x80004001_u32 as i32;
+    pub const E_ACCESSDENIED: i32 = 0x80070005_u32 as i32;
+    pub const E_INVALIDARG: i32 = 0x80070057_u32 as i32;
+    pub const RPC_E_CALL_REJECTED: i32 = 0x80010001_u32 as i32;
+    pub const RPC_E_SERVERCALL_RETRYLATER: i32 = 0x8001010A_u32 as i32;
+    pub const UIA_E_ELEMENTNOTENABLED: i32 = 0x80040200_u32 as i32;
+    pub const UIA_E_ELEMENTNOTAVAILABLE: i32 = 0x80040201_u32 as i32;
+    pub const UIA_E_NOCLICKABLEPOINT: i32 = 0x80040202_u32 as i32;
+    pub const UIA_E_PROXYASSEMBLYNOTLOADED: i32 = 0x80040203_u32 as i32;
+    pub const UIA_E_TIMEOUT: i32 = 0x80131505_u32 as i32;
+}
+
+/// Classifies a UI Automation/COM error code.
+///
+/// Codes that mean the target went away or the provider was busy are transient;
+/// everything else, including unknown codes, is treated as fatal.
+pub fn classify_com_error(code: i32) -> ErrorClass {
+    use hresult::*;
+    match code {
+        E_FAIL
+        | E_NOINTERFACE
+        | RPC_E_CALL_REJECTED
+        | RPC_E_SERVERCALL_RETRYLATER
+        | UIA_E_ELEMENTNOTENABLED
+        | UIA_E_ELEMENTNOTAVAILABLE
+        | UIA_E_NOCLICKABLEPOINT
+        | UIA_E_TIMEOUT => ErrorClass::Transient,
+        _ => ErrorClass::Fatal,
+    }
+}
+
+fn format_com_error(code: &Option<i32>) -> String {
+    code.map(|c| format!(" (HRESULT 0x{:08X})", c as u32))
+        .unwrap_or_default()
+}
+
+impl AutomationError {
+    /// The raw HRESULT carried by a `UIAutomationAPIError`, if any
+    pub fn com_error_code(&self) -> Option<i32> {
+        match self {
+            AutomationError::UIAutomationAPIError { com_error, .. } => *com_error,
+            _ => None,
+        }
+    }
+
+    /// Classifies the error as a user, transient or system fault.
+    ///
+    /// UI Automation API errors are transient when [`Self::is_transient`] says so and
+    /// system faults otherwise.
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            AutomationError::ElementNotFound(_)
+            | AutomationError::InvalidArgument(_)
+            | AutomationError::InvalidSelector(_)
+            | AutomationError::UnsupportedOperation(_) => ErrorCategory::User,
+            AutomationError::Timeout(_)
+            | AutomationError::ElementObscured(_)
+            | AutomationError::TreeChanged(_) => ErrorCategory::Transient,
+            AutomationError::PermissionDenied(_)
+            | AutomationError::PlatformError(_)
+            | AutomationError::UnsupportedPlatform(_)
+            | AutomationError::Internal(_) => ErrorCategory::System,
+            AutomationError::UIAutomationAPIError { .. } => {
+                if self.is_transient() {
+                    ErrorCategory::Transient
+                } else {
+                    ErrorCategory::System
+                }
+            }
+        }
+    }
+
+    /// Whether retrying the failed call may succeed.
+    ///
+    /// Only `UIAutomationAPIError`s are classified; they are transient when flagged
+    /// retryable or when their HRESULT is a known transient code.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            AutomationError::UIAutomationAPIError {
+                com_error,
+                is_retryable,
+                ..
+            } => {
+                *is_retryable
+                    || com_error.is_some_and(|c| classify_com_error(c) == ErrorClass::Transient)
+            }
+            _ => false,
+        }
+    }
+}
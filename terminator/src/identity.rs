@@ -0,0 +1,83 @@
+//! Stable identity keys that survive re-renders.
+//!
+//! When a list or grid re-renders, its rows come back as new element handles, so a
+//! workflow that held on to a row loses it. An identity key names the logical element
+//! instead of the handle: it is built from the automation id when the element has one,
+//! otherwise from its role and content (name, or value when there is no name). After a
+//! refresh, [`find_by_identity`] walks the tree again and returns the element whose key
+//! matches.
+//!
+//! Content keys are only as unique as the content: when two elements share role and
+//! name, the first one in tree order is returned.
+
+use crate::{AutomationError, ElementInfo, UIElement};
+use std::collections::VecDeque;
+
+/// How deep [`find_by_identity`] searches below the scope element by default
+pub const DEFAULT_IDENTITY_SEARCH_DEPTH: usize = 50;
+
+/// The parts of an element the identity search needs.
+pub(crate) trait IdentityNode: Sized {
+    fn info(&self) -> ElementInfo;
+    fn children(&self) -> Result<Vec<Self>, AutomationError>;
+}
+
+impl IdentityNode for UIElement {
+    fn info(&self) -> ElementInfo {
+        // Live bounds and states are not part of the key, so skip reading them
+        ElementInfo::from_attributes(&self.attributes())
+    }
+
+    fn children(&self) -> Result<Vec<Self>, AutomationError> {
+        UIElement::children(self)
+    }
+}
+
+/// Computes the identity key of an element, or `None` when it has neither an
+/// automation id nor any content to identify it by.
+pub fn identity_key(info: &ElementInfo) -> Option<String> {
+    if let Some(automation_id) = info.automation_id.as_deref().filter(|id| !id.is_empty()) {
+        return Some(format!("aid:{}:{automation_id}", info.role));
+    }
+    let content = info
+        .name
+        .as_deref()
+        .filter(|name| !name.trim().is_empty())
+        .or_else(|| {
+            info.value
+                .as_deref()
+                .filter(|value| !value.trim().is_empty())
+        })?;
+    Some(format!("content:{}:{}", info.role, content.trim()))
+}
+
+/// Finds the element with identity `key` among `scope` and its descendants, breadth
+/// first, at most `max_depth` levels below `scope`.
+pub(crate) fn find_by_identity<T: IdentityNode>(
+    scope: T,
+    key: &str,
+    max_depth: usize,
+) -> Result<T, AutomationError> {
+    if key.trim().is_empty() {
+        return Err(AutomationError::InvalidArgument(
+            "Identity key must not be empty".to_string(),
+        ));
+    }
+
+    let mut queue = VecDeque::from([(scope, 0usize)]);
+    while let Some((node, depth)) = queue.pop_front() {
+        if identity_key(&node.info()).as_deref() == Some(key) {
+            return Ok(node);
+        }
+        if depth < max_depth {
+            // A subtree that cannot be read is skipped rather than failing the search
+            if let Ok(children) = node.children() {
+                queue.extend(children.into_iter().map(|child| (child, depth + 1)));
+            }
+        }
+    }
+
+    Err(AutomationError::ElementNotFound(format!(
+        "No element with identity '{key}' within {max_depth} levels"
+    )))
+}
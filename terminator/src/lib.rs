@@ -12,19 +12,29 @@ pub mod browser_script;
 pub mod element;
 pub mod errors;
 pub mod extension_bridge;
+pub mod identity;
 pub mod locator;
+pub mod ocr;
+pub mod ordering;
 pub mod platforms;
 pub mod selector;
+pub mod table;
 #[cfg(test)]
 mod tests;
 pub mod types;
 pub mod utils;
+pub mod z_order;
 
-pub use element::{SerializableUIElement, UIElement, UIElementAttributes};
-pub use errors::AutomationError;
+pub use element::{
+    ElementBounds, ElementInfo, ElementStates, SerializableUIElement, UIElement,
+    UIElementAttributes,
+};
+pub use errors::{classify_com_error, AutomationError, ErrorCategory, ErrorClass};
 pub use locator::Locator;
-pub use selector::Selector;
-pub use types::{FontStyle, HighlightHandle, TextPosition};
+pub use ocr::{OcrBatchOptions, OcrBatchResult, OcrRegion, OcrWord};
+pub use selector::{Selector, StatePredicate};
+pub use types::{AppInfo, FontStyle, HighlightHandle, TextPosition};
+pub use z_order::WindowLayer;
 
 /// Recommend to use any of these: ["Default", "Chrome", "Firefox", "Edge", "Brave", "Opera", "Vivaldi"]
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -135,6 +145,11 @@ impl fmt::Debug for UINode {
 }
 
 impl UINode {
+    /// Summarizes this node as an [`ElementInfo`], the same shape as [`UIElement::info`].
+    pub fn info(&self) -> ElementInfo {
+        ElementInfo::from_attributes(&self.attributes)
+    }
+
     /// Helper method for debug formatting with depth control
     fn debug_with_depth(
         &self,
@@ -285,6 +300,15 @@ impl Desktop {
         self.engine.get_application_by_name(name)
     }
 
+    #[instrument(skip(self))]
+    pub fn application_by_pid(
+        &self,
+        pid: u32,
+        timeout: Option<std::time::Duration>,
+    ) -> Result<UIElement, AutomationError> {
+        self.engine.get_application_by_pid(pid as i32, timeout)
+    }
+
     #[instrument(skip(self, app_name))]
     pub fn open_application(&self, app_name: &str) -> Result<UIElement, AutomationError> {
         self.engine.open_application(app_name)
@@ -570,6 +594,19 @@ impl Desktop {
         self.engine.ocr_screenshot(screenshot).await
     }
 
+    /// Recognizes the text in several regions of one screenshot concurrently.
+    ///
+    /// Results are keyed by region label; see [`ocr::ocr_regions`].
+    #[instrument(skip(self, screenshot, regions))]
+    pub async fn ocr_regions(
+        &self,
+        screenshot: &ScreenshotResult,
+        regions: &[OcrRegion],
+        options: &OcrBatchOptions,
+    ) -> Result<OcrBatchResult, AutomationError> {
+        ocr::ocr_regions(&ocr::PlatformOcr, screenshot, regions, options).await
+    }
+
     #[instrument(skip(self, title))]
     pub fn activate_browser_window_by_title(&self, title: &str) -> Result<(), AutomationError> {
         self.engine.activate_browser_window_by_title(title)
@@ -590,6 +627,81 @@ impl Desktop {
         self.engine.get_current_application().await
     }
 
+    /// Gets process and window metadata for the application in the foreground.
+    ///
+    /// Only the pid is required: the process name, executable path and version
+    /// are `None` when they cannot be read, e.g. for protected processes.
+    #[instrument(skip(self))]
+    pub async fn foreground_app(&self) -> Result<AppInfo, AutomationError> {
+        let window = self.engine.get_current_window().await?;
+        let pid = window.process_id()?;
+        let window_title = window.name().filter(|title| !title.is_empty());
+
+        let mut system = sysinfo::System::new();
+        let sys_pid = sysinfo::Pid::from_u32(pid);
+        system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[sys_pid]), true);
+        let process = system.process(sys_pid);
+        let executable = process.and_then(|p| p.exe()).map(|p| p.to_path_buf());
+
+        #[cfg(target_os = "windows")]
+        let process_name = get_process_name_by_pid(pid as i32).ok();
+        #[cfg(not(target_os = "windows"))]
+        let process_name = process.map(|p| p.name().to_string_lossy().into_owned());
+
+        #[cfg(target_os = "windows")]
+        let version = executable
+            .as_deref()
+            .and_then(platforms::windows::get_file_version);
+        #[cfg(not(target_os = "windows"))]
+        let version = None;
+
+        if process_name.is_none() || executable.is_none() {
+            debug!("Limited process metadata available for foreground pid {pid}");
+        }
+
+        Ok(AppInfo {
+            process_name,
+            pid,
+            window_title,
+            executable_path: executable.map(|p| p.to_string_lossy().into_owned()),
+            version,
+        })
+    }
+
+    /// Lists the visible top-level windows, front-most first.
+    #[instrument(skip(self))]
+    pub fn window_z_order(&self) -> Result<Vec<WindowLayer>, AutomationError> {
+        self.engine.window_z_order()
+    }
+
+    /// Fails with `ElementObscured` when another window covers `element`'s window at the
+    /// screen point `(x, y)`, see [`z_order`].
+    ///
+    /// When the z-order or the element's window cannot be read, the element is not
+    /// reported as obscured.
+    pub fn ensure_not_obscured(
+        &self,
+        element: &UIElement,
+        x: f64,
+        y: f64,
+    ) -> Result<(), AutomationError> {
+        let layers = match self.engine.window_z_order() {
+            Ok(layers) => layers,
+            Err(AutomationError::UnsupportedOperation(_)) => return Ok(()),
+            Err(e) => {
+                debug!("Skipping obscured check, z-order unavailable: {e}");
+                return Ok(());
+            }
+        };
+        let Some(window) = element.window().ok().flatten() else {
+            return Ok(());
+        };
+        let (Ok(process_id), Ok(bounds)) = (window.process_id(), window.bounds()) else {
+            return Ok(());
+        };
+        z_order::ensure_not_obscured(&layers, process_id, bounds, x, y)
+    }
+
     #[instrument(skip(self, pid, title, config))]
     pub fn get_window_tree(
         &self,
@@ -598,7 +710,9 @@ impl Desktop {
         config: Option<crate::platforms::TreeBuildConfig>,
     ) -> Result<UINode, AutomationError> {
         let tree_config = config.unwrap_or_default();
-        self.engine.get_window_tree(pid, title, tree_config)
+        let mut tree = self.engine.get_window_tree(pid, title, tree_config)?;
+        ordering::sort_tree(&mut tree);
+        Ok(tree)
     }
 
     /// Get the UI tree for all open applications in parallel.
@@ -49,6 +49,9 @@ impl Locator {
 
     /// Get all elements matching this locator, waiting up to the specified timeout.
     /// If no timeout is provided, uses the locator's default timeout.
+    ///
+    /// Results are in reading order (see [`crate::ordering`]), so the same UI
+    /// yields the same order on every call.
     pub async fn all(
         &self,
         timeout: Option<Duration>,
@@ -56,12 +59,14 @@ impl Locator {
     ) -> Result<Vec<UIElement>, AutomationError> {
         let effective_timeout = timeout.unwrap_or(self.timeout);
         // find_elements itself handles the timeout now
-        self.engine.find_elements(
+        let mut elements = self.engine.find_elements(
             &self.selector,
             self.root.as_ref(),
             Some(effective_timeout),
             depth,
-        )
+        )?;
+        crate::ordering::sort_elements(&mut elements);
+        Ok(elements)
     }
 
     pub async fn first(&self, timeout: Option<Duration>) -> Result<UIElement, AutomationError> {
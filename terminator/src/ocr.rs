@@ -0,0 +1,442 @@
+//! OCR over several regions of one screenshot at a time.
+//!
+//! Recognizing a whole screen is slow. When the caller already knows where the text
+//! can be, [`ocr_regions`] crops each region out of a single screenshot and runs the
+//! recognizer on the crops concurrently, at most `max_concurrency` at a time. Every
+//! region is recognized with the same per-call locale, and results are keyed by the
+//! region's label. A region that fails does not fail the others.
+//!
+//! [`WordRecognizer`] reads words with their bounding boxes instead of plain text. The
+//! platform implementation uses Windows.Media.Ocr on Windows and the `tesseract` binary
+//! elsewhere, so on Linux and macOS tesseract must be installed.
+
+use crate::{AutomationError, ScreenshotResult};
+use futures::stream::{self, StreamExt};
+use image::{DynamicImage, ImageBuffer, Rgba};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+
+/// Number of regions recognized at the same time by default
+pub const DEFAULT_OCR_CONCURRENCY: usize = 4;
+
+/// A labelled rectangle in screenshot pixel coordinates
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OcrRegion {
+    pub label: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl OcrRegion {
+    pub fn new(label: impl Into<String>, x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            label: label.into(),
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+/// Settings shared by every region of one batch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OcrBatchOptions {
+    /// BCP 47 locale such as `en-US`; `None` lets the OCR engine pick
+    pub locale: Option<String>,
+    /// Upper bound on regions recognized at once (values below 1 are treated as 1)
+    pub max_concurrency: usize,
+}
+
+impl Default for OcrBatchOptions {
+    fn default() -> Self {
+        Self {
+            locale: None,
+            max_concurrency: DEFAULT_OCR_CONCURRENCY,
+        }
+    }
+}
+
+/// Text per region label, or the error for that region
+pub type OcrBatchResult = BTreeMap<String, Result<String, AutomationError>>;
+
+/// Turns one image into text.
+pub trait OcrRecognizer: Sync {
+    fn recognize(
+        &self,
+        image: DynamicImage,
+        locale: Option<&str>,
+    ) -> impl Future<Output = Result<String, AutomationError>> + Send;
+}
+
+/// A recognized word and its bounding box, in pixels of the recognized image
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OcrWord {
+    pub text: String,
+    /// Index of the line the word is on, counting from the top of the image
+    pub line: usize,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    /// Confidence from 0.0 to 1.0, when the engine reports one
+    pub confidence: Option<f64>,
+}
+
+/// Turns one image into words with their positions.
+pub trait WordRecognizer: Sync {
+    fn recognize_words(
+        &self,
+        image: DynamicImage,
+        locale: Option<&str>,
+    ) -> impl Future<Output = Result<Vec<OcrWord>, AutomationError>> + Send;
+}
+
+/// Whether the platform OCR engine is compiled in (the `ocr` feature)
+pub const OCR_ENABLED: bool = cfg!(feature = "ocr");
+
+/// Why OCR calls and `ocr:` selectors fail when the `ocr` feature is off
+pub const OCR_NOT_ENABLED: &str = "OCR feature not enabled in this build";
+
+/// The error every OCR entry point returns when the `ocr` feature is off
+pub fn ocr_not_enabled() -> AutomationError {
+    AutomationError::UnsupportedOperation(OCR_NOT_ENABLED.to_string())
+}
+
+/// Recognizes text with the platform OCR engine from `uni_ocr`
+pub struct PlatformOcr;
+
+impl OcrRecognizer for PlatformOcr {
+    async fn recognize(
+        &self,
+        image: DynamicImage,
+        locale: Option<&str>,
+    ) -> Result<String, AutomationError> {
+        #[cfg(feature = "ocr")]
+        {
+            let mut engine = platform_engine()?;
+            if let Some(locale) = locale {
+                engine = engine.with_options(
+                    uni_ocr::OcrOptions::default().languages(vec![ocr_language(locale)?]),
+                );
+            }
+
+            let (text, _language, _confidence) =
+                engine.recognize_image(&image).await.map_err(|e| {
+                    AutomationError::PlatformError(format!("OCR recognition failed: {e}"))
+                })?;
+
+            Ok(text)
+        }
+        #[cfg(not(feature = "ocr"))]
+        {
+            let _ = (image, locale);
+            Err(ocr_not_enabled())
+        }
+    }
+}
+
+impl WordRecognizer for PlatformOcr {
+    async fn recognize_words(
+        &self,
+        image: DynamicImage,
+        locale: Option<&str>,
+    ) -> Result<Vec<OcrWord>, AutomationError> {
+        #[cfg(feature = "ocr")]
+        {
+            // Both engines block, the Windows one on its async operations and tesseract
+            // on a child process
+            let locale = locale.map(str::to_string);
+            tokio::task::spawn_blocking(move || platform_words(&image, locale.as_deref()))
+                .await
+                .map_err(|e| AutomationError::PlatformError(format!("OCR task failed: {e}")))?
+        }
+        #[cfg(not(feature = "ocr"))]
+        {
+            let _ = (image, locale);
+            Err(ocr_not_enabled())
+        }
+    }
+}
+
+#[cfg(all(feature = "ocr", target_os = "windows"))]
+fn platform_words(
+    image: &DynamicImage,
+    locale: Option<&str>,
+) -> Result<Vec<OcrWord>, AutomationError> {
+    use std::io::Cursor;
+    use windows::core::HSTRING;
+    use windows::Globalization::Language;
+    use windows::Graphics::Imaging::BitmapDecoder;
+    use windows::Media::Ocr::OcrEngine;
+    use windows::Storage::Streams::{DataWriter, InMemoryRandomAccessStream};
+
+    let platform_error = |e: windows::core::Error| {
+        AutomationError::PlatformError(format!("OCR recognition failed: {e}"))
+    };
+
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| AutomationError::PlatformError(format!("Failed to encode image: {e}")))?;
+    let stream = InMemoryRandomAccessStream::new().map_err(platform_error)?;
+    let writer = DataWriter::CreateDataWriter(&stream).map_err(platform_error)?;
+    writer.WriteBytes(&png).map_err(platform_error)?;
+    writer
+        .StoreAsync()
+        .and_then(|op| op.get())
+        .map_err(platform_error)?;
+    stream.Seek(0).map_err(platform_error)?;
+    let bitmap = BitmapDecoder::CreateAsync(&stream)
+        .and_then(|op| op.get())
+        .and_then(|decoder| decoder.GetSoftwareBitmapAsync())
+        .and_then(|op| op.get())
+        .map_err(platform_error)?;
+
+    let engine = match locale {
+        Some(locale) => Language::CreateLanguage(&HSTRING::from(locale))
+            .and_then(|language| OcrEngine::TryCreateFromLanguage(&language))
+            .map_err(|_| {
+                AutomationError::InvalidArgument(format!("Unsupported OCR locale '{locale}'"))
+            })?,
+        None => OcrEngine::TryCreateFromUserProfileLanguages().map_err(platform_error)?,
+    };
+    let result = engine
+        .RecognizeAsync(&bitmap)
+        .and_then(|op| op.get())
+        .map_err(platform_error)?;
+
+    let mut words = Vec::new();
+    for (line_index, line) in result
+        .Lines()
+        .map_err(platform_error)?
+        .into_iter()
+        .enumerate()
+    {
+        for word in line.Words().map_err(platform_error)? {
+            let rect = word.BoundingRect().map_err(platform_error)?;
+            words.push(OcrWord {
+                text: word.Text().map_err(platform_error)?.to_string(),
+                line: line_index,
+                x: rect.X as f64,
+                y: rect.Y as f64,
+                width: rect.Width as f64,
+                height: rect.Height as f64,
+                // Windows OCR does not report confidence
+                confidence: None,
+            });
+        }
+    }
+    Ok(words)
+}
+
+#[cfg(all(feature = "ocr", not(target_os = "windows")))]
+fn platform_words(
+    image: &DynamicImage,
+    locale: Option<&str>,
+) -> Result<Vec<OcrWord>, AutomationError> {
+    const WORD_LEVEL: i32 = 5;
+
+    let lang = match locale {
+        Some(locale) => tesseract_language(locale)?,
+        None => "eng",
+    };
+    let args = rusty_tesseract::Args {
+        lang: lang.to_string(),
+        ..Default::default()
+    };
+    let tesseract_error = |e: rusty_tesseract::TessError| {
+        AutomationError::PlatformError(format!("Tesseract failed: {e}"))
+    };
+    let input = rusty_tesseract::Image::from_dynamic_image(image).map_err(tesseract_error)?;
+    let output = rusty_tesseract::image_to_data(&input, &args).map_err(tesseract_error)?;
+
+    // Tesseract numbers lines within a paragraph; renumber them from the top of the image
+    let mut words = Vec::new();
+    let mut line_keys = Vec::new();
+    for record in output.data {
+        let text = record.text.trim();
+        if record.level != WORD_LEVEL || text.is_empty() {
+            continue;
+        }
+        let key = (record.block_num, record.par_num, record.line_num);
+        let line = match line_keys.iter().position(|k| *k == key) {
+            Some(line) => line,
+            None => {
+                line_keys.push(key);
+                line_keys.len() - 1
+            }
+        };
+        words.push(OcrWord {
+            text: text.to_string(),
+            line,
+            x: record.left as f64,
+            y: record.top as f64,
+            width: record.width as f64,
+            height: record.height as f64,
+            // Tesseract reports -1 when it has no confidence for a word
+            confidence: (record.conf >= 0.0).then(|| record.conf as f64 / 100.0),
+        });
+    }
+    Ok(words)
+}
+
+/// Maps a locale to a tesseract language code.
+#[cfg(all(feature = "ocr", not(target_os = "windows")))]
+fn tesseract_language(locale: &str) -> Result<&'static str, AutomationError> {
+    let language = ocr_language(locale)?;
+    uni_ocr::TESSERACT_LANGUAGES
+        .iter()
+        .find(|(_, name)| language == *name)
+        .map(|(code, _)| *code)
+        .ok_or_else(|| {
+            AutomationError::InvalidArgument(format!("Unsupported OCR locale '{locale}'"))
+        })
+}
+
+#[cfg(feature = "ocr")]
+fn platform_engine() -> Result<uni_ocr::OcrEngine, AutomationError> {
+    uni_ocr::OcrEngine::new(uni_ocr::OcrProvider::Auto)
+        .map_err(|e| AutomationError::PlatformError(format!("Failed to create OCR engine: {e}")))
+}
+
+/// Recognizes the text of an image with the platform engine and default options.
+pub(crate) async fn recognize_image(image: &DynamicImage) -> Result<String, AutomationError> {
+    #[cfg(feature = "ocr")]
+    {
+        let (text, _language, _confidence) = platform_engine()?
+            .recognize_image(image)
+            .await
+            .map_err(|e| AutomationError::PlatformError(format!("OCR recognition failed: {e}")))?;
+        Ok(text)
+    }
+    #[cfg(not(feature = "ocr"))]
+    {
+        let _ = image;
+        Err(ocr_not_enabled())
+    }
+}
+
+/// Recognizes the text of an image file with the platform engine and default options.
+pub(crate) async fn recognize_file(path: &str) -> Result<String, AutomationError> {
+    #[cfg(feature = "ocr")]
+    {
+        let (text, _language, _confidence) = platform_engine()?
+            .recognize_file(path)
+            .await
+            .map_err(|e| AutomationError::PlatformError(format!("OCR recognition failed: {e}")))?;
+        Ok(text)
+    }
+    #[cfg(not(feature = "ocr"))]
+    {
+        let _ = path;
+        Err(ocr_not_enabled())
+    }
+}
+
+/// Maps a locale's primary language subtag to an OCR language.
+#[cfg(feature = "ocr")]
+fn ocr_language(locale: &str) -> Result<uni_ocr::Language, AutomationError> {
+    let primary = locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    let language = match primary.as_str() {
+        "en" => uni_ocr::Language::English,
+        "de" => uni_ocr::Language::German,
+        "fr" => uni_ocr::Language::French,
+        "es" => uni_ocr::Language::Spanish,
+        "it" => uni_ocr::Language::Italian,
+        "pt" => uni_ocr::Language::Portuguese,
+        "ru" => uni_ocr::Language::Russian,
+        "zh" => uni_ocr::Language::Chinese,
+        "ja" => uni_ocr::Language::Japanese,
+        "ko" => uni_ocr::Language::Korean,
+        _ => {
+            return Err(AutomationError::InvalidArgument(format!(
+                "Unsupported OCR locale '{locale}'"
+            )))
+        }
+    };
+    Ok(language)
+}
+
+/// Cuts `region` out of `image`, clipped to the image bounds.
+fn crop_region(
+    image: &ImageBuffer<Rgba<u8>, Vec<u8>>,
+    region: &OcrRegion,
+) -> Result<DynamicImage, AutomationError> {
+    let right = region.x.saturating_add(region.width).min(image.width());
+    let bottom = region.y.saturating_add(region.height).min(image.height());
+    if region.x >= right || region.y >= bottom {
+        return Err(AutomationError::InvalidArgument(format!(
+            "Region '{}' ({}x{} at {},{}) is empty or outside the {}x{} screenshot",
+            region.label,
+            region.width,
+            region.height,
+            region.x,
+            region.y,
+            image.width(),
+            image.height()
+        )));
+    }
+    let cropped = image::imageops::crop_imm(
+        image,
+        region.x,
+        region.y,
+        right - region.x,
+        bottom - region.y,
+    )
+    .to_image();
+    Ok(DynamicImage::ImageRgba8(cropped))
+}
+
+/// Recognizes the text in each region of `screenshot`.
+///
+/// Fails as a whole only when the screenshot cannot be decoded or two regions share a
+/// label; any other error is reported under the region it belongs to.
+pub async fn ocr_regions<R: OcrRecognizer>(
+    recognizer: &R,
+    screenshot: &ScreenshotResult,
+    regions: &[OcrRegion],
+    options: &OcrBatchOptions,
+) -> Result<OcrBatchResult, AutomationError> {
+    let mut labels = HashSet::new();
+    if let Some(duplicate) = regions.iter().find(|r| !labels.insert(r.label.as_str())) {
+        return Err(AutomationError::InvalidArgument(format!(
+            "Duplicate OCR region label '{}'",
+            duplicate.label
+        )));
+    }
+
+    let image: ImageBuffer<Rgba<u8>, Vec<u8>> = ImageBuffer::from_raw(
+        screenshot.width,
+        screenshot.height,
+        screenshot.image_data.clone(),
+    )
+    .ok_or_else(|| {
+        AutomationError::InvalidArgument("Invalid screenshot data for buffer creation".to_string())
+    })?;
+
+    let locale = options.locale.as_deref();
+    let results = stream::iter(regions)
+        .map(|region| {
+            let crop = crop_region(&image, region);
+            async move {
+                let text = match crop {
+                    Ok(crop) => recognizer.recognize(crop, locale).await,
+                    Err(e) => Err(e),
+                };
+                (region.label.clone(), text)
+            }
+        })
+        .buffer_unordered(options.max_concurrency.max(1))
+        .collect::<OcrBatchResult>()
+        .await;
+
+    Ok(results)
+}
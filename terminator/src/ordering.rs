@@ -0,0 +1,102 @@
+//! Deterministic ordering for element collections and UI trees.
+//!
+//! Platform accessibility APIs do not promise a stable order for search results,
+//! and the order can change between reads of the same UI. To keep `nth` selectors
+//! and tree snapshots reproducible, collections are put in reading order:
+//!
+//! 1. top-to-bottom (by the top edge, rounded to whole pixels),
+//! 2. then left-to-right (by the left edge, rounded to whole pixels),
+//! 3. then by automation id (falling back to the element id) as a tiebreaker.
+//!
+//! Items without geometry sort after items with geometry. The sort is stable, so
+//! items that compare equal keep the order the platform returned them in.
+
+use crate::{UIElement, UINode};
+use std::cmp::Ordering;
+
+/// The sort key of one element: its top-left corner and a tiebreaker id
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderKey {
+    origin: Option<(i64, i64)>,
+    tiebreaker: Option<String>,
+}
+
+impl OrderKey {
+    pub fn new(bounds: Option<(f64, f64, f64, f64)>, tiebreaker: Option<String>) -> Self {
+        let origin = bounds
+            .filter(|(x, y, _, _)| x.is_finite() && y.is_finite())
+            .map(|(x, y, _, _)| (y.round() as i64, x.round() as i64));
+        Self { origin, tiebreaker }
+    }
+}
+
+impl Ord for OrderKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let geometry = match (self.origin, other.origin) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        };
+        geometry.then_with(|| match (&self.tiebreaker, &other.tiebreaker) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        })
+    }
+}
+
+impl PartialOrd for OrderKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Sorts search results into reading order.
+///
+/// Bounds and ids are read once per element before sorting.
+pub fn sort_elements(elements: &mut Vec<UIElement>) {
+    if elements.len() < 2 {
+        return;
+    }
+    let mut keyed: Vec<(OrderKey, UIElement)> = elements
+        .drain(..)
+        .map(|element| (OrderKey::new(element.bounds().ok(), element.id()), element))
+        .collect();
+    keyed.sort_by(|a, b| a.0.cmp(&b.0));
+    elements.extend(keyed.into_iter().map(|(_, element)| element));
+}
+
+fn node_tiebreaker(node: &UINode) -> Option<String> {
+    node.attributes
+        .properties
+        .get("AutomationId")
+        .and_then(|value| value.as_ref())
+        .and_then(|value| value.as_str())
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .or_else(|| node.id.clone())
+}
+
+/// The top-left corner of a node, or of the first of its descendants that has bounds.
+///
+/// Tree nodes only carry bounds for some elements (e.g. keyboard-focusable ones), so
+/// containers are placed by the geometry of their content.
+fn node_bounds(node: &UINode) -> Option<(f64, f64, f64, f64)> {
+    node.attributes.bounds.or_else(|| {
+        node.children
+            .iter()
+            .filter_map(node_bounds)
+            .min_by(|a, b| OrderKey::new(Some(*a), None).cmp(&OrderKey::new(Some(*b), None)))
+    })
+}
+
+/// Recursively sorts the children of every node into reading order.
+pub fn sort_tree(node: &mut UINode) {
+    for child in &mut node.children {
+        sort_tree(child);
+    }
+    node.children
+        .sort_by_cached_key(|child| OrderKey::new(node_bounds(child), node_tiebreaker(child)));
+}
@@ -36,7 +36,6 @@ use atspi_proxies::{
 };
 use futures::future::join_all;
 use image::{DynamicImage, ImageBuffer, Rgba};
-use uni_ocr::{OcrEngine, OcrProvider};
 use zbus::fdo::DBusProxy;
 
 // Copied from atspi-common/src/role.rs (not public)
@@ -679,6 +678,11 @@ fn find_elements_inner<'a>(
                     "Selector::Attributes is not implemented for Linux".to_string(),
                 ));
             }
+            Selector::State { .. } => {
+                return Err(AutomationError::UnsupportedPlatform(
+                    "Selector::State is not implemented for Linux".to_string(),
+                ));
+            }
             Selector::Filter(_) => {
                 return Err(AutomationError::UnsupportedPlatform(
                     "Selector::Filter is not implemented for Linux".to_string(),
@@ -762,6 +766,9 @@ fn find_elements_inner<'a>(
             Selector::Role { .. } | Selector::Name(_) => {
                 // Supported - continue to processing below
             }
+            Selector::Unsupported(reason) => {
+                return Err(AutomationError::UnsupportedOperation(reason.clone()));
+            }
             Selector::Invalid(reason) => {
                 return Err(AutomationError::InvalidArgument(reason.clone()));
             }
@@ -1798,18 +1805,7 @@ impl AccessibilityEngine for LinuxEngine {
     }
 
     async fn ocr_image_path(&self, image_path: &str) -> Result<String, AutomationError> {
-        let engine = OcrEngine::new(OcrProvider::Auto).map_err(|e| {
-            AutomationError::PlatformError(format!("Failed to create OCR engine: {}", e))
-        })?;
-
-        let (text, _language, _confidence) = engine // Destructure the tuple
-            .recognize_file(image_path)
-            .await
-            .map_err(|e| {
-                AutomationError::PlatformError(format!("OCR recognition failed: {}", e))
-            })?;
-
-        Ok(text) // Return only the text
+        crate::ocr::recognize_file(image_path).await
     }
 
     async fn ocr_screenshot(
@@ -1832,18 +1828,7 @@ impl AccessibilityEngine for LinuxEngine {
         let dynamic_image = DynamicImage::ImageRgba8(img_buffer);
 
         // Directly await the OCR operation within the existing async context
-        let engine = OcrEngine::new(OcrProvider::Auto).map_err(|e| {
-            AutomationError::PlatformError(format!("Failed to create OCR engine: {}", e))
-        })?;
-
-        let (text, _language, _confidence) = engine
-            .recognize_image(&dynamic_image) // Use recognize_image
-            .await // << Directly await here
-            .map_err(|e| {
-                AutomationError::PlatformError(format!("OCR recognition failed: {}", e))
-            })?;
-
-        Ok(text)
+        crate::ocr::recognize_image(&dynamic_image).await
     }
 
     fn activate_browser_window_by_title(&self, _title: &str) -> Result<(), AutomationError> {
@@ -9,7 +9,7 @@ use accessibility::{AXAttribute, AXUIElement};
 use accessibility_sys::error_string;
 use anyhow::Result;
 use core_foundation::array::{
-    CFArrayGetCount, CFArrayGetTypeID, CFArrayGetValueAtIndex, __CFArray,
+    __CFArray, CFArrayGetCount, CFArrayGetTypeID, CFArrayGetValueAtIndex,
 };
 use core_foundation::base::{CFGetTypeID, TCFType};
 use core_foundation::boolean::CFBoolean;
@@ -27,7 +27,6 @@ use std::hash::{Hash, Hasher};
 use std::sync::Arc;
 use std::time::Duration;
 use tracing::{debug, info, instrument, warn, Level};
-use uni_ocr::{OcrEngine, OcrProvider};
 
 use super::tree_search::ElementsCollectorWithWindows;
 
@@ -2827,6 +2826,9 @@ impl AccessibilityEngine for MacOSEngine {
             Selector::Attributes(_) => Err(AutomationError::UnsupportedOperation(
                 "Attributes selector not yet supported for macOS".to_string(),
             )),
+            Selector::State { .. } => Err(AutomationError::UnsupportedOperation(
+                "State selector not yet supported for macOS".to_string(),
+            )),
             Selector::Filter(_) => Err(AutomationError::UnsupportedOperation(
                 "Filter selector not yet supported for macOS".to_string(),
             )),
@@ -2892,6 +2894,9 @@ impl AccessibilityEngine for MacOSEngine {
                 "LocalizedRole selector is not yet supported for macOS".to_string(),
             )),
 
+            Selector::Unsupported(reason) => {
+                Err(AutomationError::UnsupportedOperation(reason.clone()))
+            }
             Selector::Invalid(reason) => Err(AutomationError::InvalidArgument(reason.clone())),
             Selector::RightOf(_) | Selector::LeftOf(_) | Selector::Above(_) | Selector::Below(_) | Selector::Near(_) => Err(AutomationError::UnsupportedOperation(
                 "Relative selectors (RightOf/LeftOf/Above/Below/Near) are not yet supported for macOS".to_string(),
@@ -3157,6 +3162,9 @@ impl AccessibilityEngine for MacOSEngine {
             Selector::Attributes(_) => Err(AutomationError::UnsupportedOperation(
                 "Attributes selector not yet supported for macOS".to_string(),
             )),
+            Selector::State { .. } => Err(AutomationError::UnsupportedOperation(
+                "State selector not yet supported for macOS".to_string(),
+            )),
             Selector::Filter(_) => Err(AutomationError::UnsupportedOperation(
                 "Filter selector not yet supported for macOS".to_string(),
             )),
@@ -3210,6 +3218,9 @@ impl AccessibilityEngine for MacOSEngine {
                 "LocalizedRole selector is not yet supported for macOS".to_string(),
             )),
 
+            Selector::Unsupported(reason) => {
+                Err(AutomationError::UnsupportedOperation(reason.clone()))
+            }
             Selector::Invalid(reason) => Err(AutomationError::InvalidArgument(reason.clone())),
             Selector::RightOf(_) | Selector::LeftOf(_) | Selector::Above(_) | Selector::Below(_) | Selector::Near(_) => Err(AutomationError::UnsupportedOperation(
                 "Relative selectors (RightOf/LeftOf/Above/Below/Near) are not yet supported for macOS".to_string(),
@@ -3632,20 +3643,7 @@ impl AccessibilityEngine for MacOSEngine {
     }
 
     async fn ocr_image_path(&self, image_path: &str) -> Result<String, AutomationError> {
-        // Call the implementation from the MacOSEngine struct
-        // Directly call the implementation logic
-        let engine = OcrEngine::new(OcrProvider::Auto).map_err(|e| {
-            AutomationError::PlatformError(format!("Failed to create OCR engine: {}", e))
-        })?;
-
-        let (text, _language, _confidence) = engine // Destructure the tuple
-            .recognize_file(image_path)
-            .await
-            .map_err(|e| {
-                AutomationError::PlatformError(format!("OCR recognition failed: {}", e))
-            })?;
-
-        Ok(text) // Return only the text
+        crate::ocr::recognize_file(image_path).await
     }
 
     async fn ocr_screenshot(
@@ -3669,18 +3667,7 @@ impl AccessibilityEngine for MacOSEngine {
         // Convert to DynamicImage
         let dynamic_image = DynamicImage::ImageRgba8(img_buffer);
 
-        let engine = OcrEngine::new(OcrProvider::Auto).map_err(|e| {
-            AutomationError::PlatformError(format!("Failed to create OCR engine: {}", e))
-        })?;
-
-        let (text, _language, _confidence) = engine
-            .recognize_image(&dynamic_image) // Use recognize_image
-            .await
-            .map_err(|e| {
-                AutomationError::PlatformError(format!("OCR recognition failed: {}", e))
-            })?;
-
-        Ok(text)
+        crate::ocr::recognize_image(&dynamic_image).await
     }
 
     fn activate_browser_window_by_title(&self, title: &str) -> Result<(), AutomationError> {
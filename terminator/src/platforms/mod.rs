@@ -200,6 +200,14 @@ pub trait AccessibilityEngine: Send + Sync {
         config: TreeBuildConfig,
     ) -> Result<UINode, AutomationError>;
 
+    /// Visible top-level windows ordered front to back.
+    /// Default implementation returns an UnsupportedOperation error.
+    fn window_z_order(&self) -> Result<Vec<crate::WindowLayer>, AutomationError> {
+        Err(AutomationError::UnsupportedOperation(
+            "window_z_order is not implemented on this platform".to_string(),
+        ))
+    }
+
     /// Enable downcasting to concrete engine types
     fn as_any(&self) -> &dyn std::any::Any;
 }
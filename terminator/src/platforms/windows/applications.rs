@@ -104,6 +104,56 @@ pub fn get_process_name_by_pid(pid: i32) -> Result<String, AutomationError> {
     }
 }
 
+/// Reads the file version resource of an executable, e.g. `"10.0.22621.1"`.
+///
+/// Returns `None` if the file has no version resource or it cannot be read.
+pub fn get_file_version(path: &std::path::Path) -> Option<String> {
+    use windows::Win32::Storage::FileSystem::{
+        GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW, VS_FIXEDFILEINFO,
+    };
+
+    let path_hstring = HSTRING::from(path.as_os_str());
+    unsafe {
+        let size = GetFileVersionInfoSizeW(PCWSTR(path_hstring.as_ptr()), None);
+        if size == 0 {
+            return None;
+        }
+        let mut data = vec![0u8; size as usize];
+        GetFileVersionInfoW(
+            PCWSTR(path_hstring.as_ptr()),
+            None,
+            size,
+            data.as_mut_ptr() as *mut _,
+        )
+        .ok()?;
+
+        let root = HSTRING::from("\\");
+        let mut info_ptr: *mut std::ffi::c_void = std::ptr::null_mut();
+        let mut info_len = 0u32;
+        if !VerQueryValueW(
+            data.as_ptr() as *const _,
+            PCWSTR(root.as_ptr()),
+            &mut info_ptr,
+            &mut info_len,
+        )
+        .as_bool()
+            || info_ptr.is_null()
+            || (info_len as usize) < std::mem::size_of::<VS_FIXEDFILEINFO>()
+        {
+            return None;
+        }
+
+        let info = &*(info_ptr as *const VS_FIXEDFILEINFO);
+        Some(format!(
+            "{}.{}.{}.{}",
+            info.dwFileVersionMS >> 16,
+            info.dwFileVersionMS & 0xffff,
+            info.dwFileVersionLS >> 16,
+            info.dwFileVersionLS & 0xffff
+        ))
+    }
+}
+
 pub fn get_application_by_name(
     engine: &WindowsEngine,
     name: &str,
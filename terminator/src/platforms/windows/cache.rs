@@ -0,0 +1,61 @@
+//! UIA cache requests for batched property reads
+//!
+//! Reading properties element by element costs one cross-process call per property.
+//! A cache request fetches the properties used during tree building for a whole
+//! subtree in a single call; the element getters then read the cached values first
+//! and only fall back to live calls when a value wasn't cached.
+
+use crate::AutomationError;
+use tracing::debug;
+use uiautomation::types::{TreeScope, UIProperty};
+use uiautomation::{UIAutomation, UICacheRequest, UIElement};
+
+/// Properties read for every node while building a tree
+const TREE_PROPERTIES: &[UIProperty] = &[
+    UIProperty::ControlType,
+    UIProperty::Name,
+    UIProperty::AutomationId,
+    UIProperty::BoundingRectangle,
+    UIProperty::IsEnabled,
+    UIProperty::HasKeyboardFocus,
+    UIProperty::IsKeyboardFocusable,
+    UIProperty::IsOffscreen,
+    UIProperty::ProcessId,
+];
+
+/// Creates a cache request for the tree-building properties over `scope`.
+///
+/// The tree filter is the raw view so cached children match what the uncached
+/// `children()` fallback (a `TreeScope::Children` search with a true condition) returns.
+pub(crate) fn create_tree_cache_request(
+    automation: &UIAutomation,
+    scope: TreeScope,
+) -> Result<UICacheRequest, AutomationError> {
+    let to_err = |e: uiautomation::Error| {
+        AutomationError::PlatformError(format!("Failed to create cache request: {e}"))
+    };
+
+    let request = automation.create_cache_request().map_err(to_err)?;
+    for property in TREE_PROPERTIES {
+        request.add_property(*property).map_err(to_err)?;
+    }
+    request.set_tree_scope(scope).map_err(to_err)?;
+    let raw_view = automation.create_true_condition().map_err(to_err)?;
+    request.set_tree_filter(raw_view).map_err(to_err)?;
+    Ok(request)
+}
+
+/// Returns a copy of `element` with the tree-building properties of its whole subtree
+/// cached, so walking it via cached children needs no further round-trips.
+pub(crate) fn cache_subtree(
+    automation: &UIAutomation,
+    element: &UIElement,
+) -> Result<UIElement, AutomationError> {
+    let request = create_tree_cache_request(automation, TreeScope::Subtree)?;
+    let start = std::time::Instant::now();
+    let cached = element.build_updated_cache(&request).map_err(|e| {
+        AutomationError::PlatformError(format!("Failed to build subtree cache: {e}"))
+    })?;
+    debug!("Cached subtree properties in {:?}", start.elapsed());
+    Ok(cached)
+}
@@ -1,10 +1,12 @@
 //! Windows UI Element implementation
 
 use super::types::{FontStyle, HighlightHandle, TextPosition, ThreadSafeWinUIElement};
-use super::utils::{create_ui_automation_with_com_init, generate_element_id};
+use super::utils::{
+    create_ui_automation_with_com_init, generate_element_id, is_element_not_available,
+};
 use crate::element::UIElementImpl;
 use crate::platforms::windows::applications::get_application_by_pid;
-use crate::platforms::windows::{highlighting, WindowsEngine};
+use crate::platforms::windows::{cache, highlighting, WindowsEngine};
 use crate::{
     AutomationError, ClickResult, Locator, ScreenshotResult, Selector, UIElement,
     UIElementAttributes,
@@ -223,6 +225,16 @@ impl WindowsUIElement {
         }
     }
 
+    /// Reads a boolean property from the element's UIA cache, if it was cached
+    /// (see `platforms::windows::cache`). Returns `None` so callers fall back to a live read.
+    fn cached_bool(&self, property: UIProperty) -> Option<bool> {
+        self.element
+            .0
+            .get_cached_property_value(property)
+            .ok()
+            .and_then(|variant| variant.try_into().ok())
+    }
+
     /// Capture current element state for tracking changes
     fn capture_state(&self) -> ElementState {
         ElementState {
@@ -311,7 +323,8 @@ impl UIElementImpl for WindowsUIElement {
     fn role(&self) -> String {
         self.element
             .0
-            .get_control_type()
+            .get_cached_control_type()
+            .or_else(|_| self.element.0.get_control_type())
             .map(|ct| ct.to_string())
             .unwrap_or_else(|_| "unknown".to_string())
     }
@@ -436,6 +449,22 @@ impl UIElementImpl for WindowsUIElement {
             .collect())
     }
 
+    fn cached_subtree(&self) -> Option<UIElement> {
+        let cached = create_ui_automation_with_com_init()
+            .and_then(|automation| cache::cache_subtree(&automation, &self.element.0));
+        match cached {
+            #[allow(clippy::arc_with_non_send_sync)]
+            Ok(cached) => Some(UIElement::new(Box::new(WindowsUIElement {
+                element: ThreadSafeWinUIElement(Arc::new(cached)),
+                engine: self.engine.clone(),
+            }))),
+            Err(e) => {
+                debug!("Subtree caching failed, reading properties live: {}", e);
+                None
+            }
+        }
+    }
+
     fn parent(&self) -> Result<Option<UIElement>, AutomationError> {
         // Use TreeWalker instead of cached parent - this avoids caching setup requirements
         let temp_automation = create_ui_automation_with_com_init().map_err(|e| {
@@ -471,7 +500,8 @@ impl UIElementImpl for WindowsUIElement {
         let rect = self
             .element
             .0
-            .get_bounding_rectangle()
+            .get_cached_bounding_rectangle()
+            .or_else(|_| self.element.0.get_bounding_rectangle())
             .map_err(|e| AutomationError::ElementNotFound(e.to_string()))?;
         Ok((
             rect.get_left() as f64,
@@ -668,7 +698,11 @@ impl UIElementImpl for WindowsUIElement {
             .get_pattern::<patterns::UIInvokePattern>()
             .map_err(|e| {
                 let error_str = e.to_string();
-                if error_str.contains("not support") || error_str.contains("UIA_E_ELEMENTNOTAVAILABLE") {
+                if is_element_not_available(&error_str) {
+                    AutomationError::TreeChanged(format!(
+                        "Element left the UI tree before its InvokePattern could be read. Find it again and retry. Error: {error_str}"
+                    ))
+                } else if error_str.contains("not support") {
                     AutomationError::UnsupportedOperation(format!(
                         "Element does not support InvokePattern. This typically happens with custom controls, groups, or non-standard buttons. Try using 'click_element' instead. Error: {error_str}"
                     ))
@@ -947,7 +981,11 @@ impl UIElementImpl for WindowsUIElement {
             .get_pattern::<patterns::UIValuePattern>()
             .map_err(|e| {
                 let error_str = e.to_string();
-                if error_str.contains("not support") || error_str.contains("UIA_E_ELEMENTNOTAVAILABLE") {
+                if is_element_not_available(&error_str) {
+                    AutomationError::TreeChanged(format!(
+                        "Element left the UI tree before its ValuePattern could be read. Find it again and retry. Error: {error_str}"
+                    ))
+                } else if error_str.contains("not support") {
                     AutomationError::UnsupportedOperation(format!(
                         "Element does not support ValuePattern. This control cannot have its value set directly. Try using 'type_into_element' for text input, or 'select_option' for dropdowns. Error: {error_str}"
                     ))
@@ -962,6 +1000,9 @@ impl UIElementImpl for WindowsUIElement {
     }
 
     fn is_enabled(&self) -> Result<bool, AutomationError> {
+        if let Some(enabled) = self.cached_bool(UIProperty::IsEnabled) {
+            return Ok(enabled);
+        }
         self.element
             .0
             .is_enabled()
@@ -994,6 +1035,9 @@ impl UIElementImpl for WindowsUIElement {
     }
 
     fn is_focused(&self) -> Result<bool, AutomationError> {
+        if let Some(focused) = self.cached_bool(UIProperty::HasKeyboardFocus) {
+            return Ok(focused);
+        }
         self.element.0.has_keyboard_focus().map_err(|e| {
             AutomationError::PlatformError(format!("Failed to get keyboard focus state: {e}"))
         })
@@ -1014,7 +1058,11 @@ impl UIElementImpl for WindowsUIElement {
                     .get_pattern::<patterns::UITogglePattern>()
                     .map_err(|e| {
                         let error_str = e.to_string();
-                        if error_str.contains("not support") || error_str.contains("UIA_E_ELEMENTNOTAVAILABLE") {
+                        if is_element_not_available(&error_str) {
+                            AutomationError::TreeChanged(format!(
+                                "Element left the UI tree before its TogglePattern could be read. Find it again and retry. Error: {error_str}"
+                            ))
+                        } else if error_str.contains("not support") {
                             AutomationError::UnsupportedOperation(format!(
                                 "Element does not support TogglePattern. This is not a toggleable control (checkbox, switch, etc.). Try using 'click' instead. Error: {error_str}"
                             ))
@@ -1033,7 +1081,11 @@ impl UIElementImpl for WindowsUIElement {
                     .get_pattern::<patterns::UIExpandCollapsePattern>()
                     .map_err(|e| {
                         let error_str = e.to_string();
-                        if error_str.contains("not support") || error_str.contains("UIA_E_ELEMENTNOTAVAILABLE") {
+                        if is_element_not_available(&error_str) {
+                            AutomationError::TreeChanged(format!(
+                                "Element left the UI tree before its ExpandCollapsePattern could be read. Find it again and retry. Error: {error_str}"
+                            ))
+                        } else if error_str.contains("not support") {
                             AutomationError::UnsupportedOperation(format!(
                                 "Element does not support ExpandCollapsePattern. This is not an expandable control (tree item, dropdown, etc.). Try using 'click' to interact with it. Error: {error_str}"
                             ))
@@ -1198,6 +1250,9 @@ impl UIElementImpl for WindowsUIElement {
     }
 
     fn is_keyboard_focusable(&self) -> Result<bool, AutomationError> {
+        if let Some(focusable) = self.cached_bool(UIProperty::IsKeyboardFocusable) {
+            return Ok(focusable);
+        }
         let variant = self
             .element
             .0
@@ -1210,6 +1265,72 @@ impl UIElementImpl for WindowsUIElement {
         })
     }
 
+    fn get_value(&self) -> Result<Option<String>, AutomationError> {
+        // Same live read the state selectors use
+        Ok(crate::selector::LiveState::live_value(
+            self.element.0.as_ref(),
+        ))
+    }
+
+    fn is_required_for_form(&self) -> Result<Option<bool>, AutomationError> {
+        let variant = self
+            .element
+            .0
+            .get_property_value(UIProperty::IsRequiredForForm)
+            .map_err(|e| AutomationError::PlatformError(e.to_string()))?;
+        Ok(variant.try_into().ok())
+    }
+
+    fn is_expanded(&self) -> Result<Option<bool>, AutomationError> {
+        if self
+            .element
+            .0
+            .get_pattern::<patterns::UIExpandCollapsePattern>()
+            .is_err()
+        {
+            return Ok(None);
+        }
+        let variant = self
+            .element
+            .0
+            .get_property_value(UIProperty::ExpandCollapseExpandCollapseState)
+            .map_err(|e| AutomationError::PlatformError(e.to_string()))?;
+        let state: i32 = variant.try_into().map_err(|_| {
+            AutomationError::PlatformError(
+                "Failed to convert expand/collapse state variant to i32".to_string(),
+            )
+        })?;
+        // 0 collapsed, 1 expanded, 2 partially expanded, 3 leaf node
+        Ok(match state {
+            0 => Some(false),
+            1 | 2 => Some(true),
+            _ => None,
+        })
+    }
+
+    fn set_expanded(&self, expanded: bool) -> Result<(), AutomationError> {
+        let pattern = self
+            .element
+            .0
+            .get_pattern::<patterns::UIExpandCollapsePattern>()
+            .map_err(|e| {
+                AutomationError::UnsupportedOperation(format!(
+                    "Element does not support ExpandCollapsePattern: {e}"
+                ))
+            })?;
+        let result = if expanded {
+            pattern.expand()
+        } else {
+            pattern.collapse()
+        };
+        result.map_err(|e| {
+            AutomationError::PlatformError(format!(
+                "Failed to {} element: {e}",
+                if expanded { "expand" } else { "collapse" }
+            ))
+        })
+    }
+
     // New method for mouse drag
     fn mouse_drag(
         &self,
@@ -2051,7 +2172,11 @@ impl UIElementImpl for WindowsUIElement {
             .get_pattern::<patterns::UIRangeValuePattern>()
             .map_err(|e| {
                 let error_str = e.to_string();
-                if error_str.contains("not support") || error_str.contains("UIA_E_ELEMENTNOTAVAILABLE") {
+                if is_element_not_available(&error_str) {
+                    AutomationError::TreeChanged(format!(
+                        "Element left the UI tree before its RangeValuePattern could be read. Find it again and retry. Error: {error_str}"
+                    ))
+                } else if error_str.contains("not support") {
                     AutomationError::UnsupportedOperation(format!(
                         "Element does not support RangeValuePattern. This is not a range control (slider, progress bar, etc.). Error: {error_str}"
                     ))
@@ -2073,7 +2198,11 @@ impl UIElementImpl for WindowsUIElement {
             .get_pattern::<patterns::UIRangeValuePattern>()
             .map_err(|e| {
                 let error_str = e.to_string();
-                if error_str.contains("not support") || error_str.contains("UIA_E_ELEMENTNOTAVAILABLE") {
+                if is_element_not_available(&error_str) {
+                    AutomationError::TreeChanged(format!(
+                        "Element left the UI tree before its RangeValuePattern could be read. Find it again and retry. Error: {error_str}"
+                    ))
+                } else if error_str.contains("not support") {
                     AutomationError::UnsupportedOperation(format!(
                         "Element does not support RangeValuePattern. This is not a range control (slider, progress bar, etc.). Try using keyboard arrows or mouse drag for custom sliders. Error: {error_str}"
                     ))
@@ -1,6 +1,7 @@
 #![allow(clippy::arc_with_non_send_sync)]
 
 use crate::element::UIElementImpl;
+use crate::errors::{classify_com_error, ErrorClass};
 use crate::platforms::windows::tree_builder::{
     build_ui_node_tree_configurable, TreeBuildingConfig, TreeBuildingContext,
 };
@@ -11,7 +12,7 @@ use crate::platforms::windows::utils::{
 use crate::platforms::windows::virtual_display::{
     is_headless_environment, HeadlessConfig, VirtualDisplayConfig, VirtualDisplayManager,
 };
-use crate::platforms::windows::{applications, generate_element_id, WindowsUIElement};
+use crate::platforms::windows::{applications, cache, generate_element_id, WindowsUIElement};
 use crate::platforms::AccessibilityEngine;
 use crate::ScreenshotResult;
 use crate::{AutomationError, Selector, UIElement};
@@ -28,7 +29,6 @@ use uiautomation::filters::{ClassNameFilter, ControlTypeFilter, NameFilter, OrFi
 use uiautomation::types::{TreeScope, UIProperty};
 use uiautomation::variants::Variant;
 use uiautomation::UIAutomation;
-use uni_ocr::{OcrEngine, OcrProvider};
 
 // windows imports
 use windows::core::{HRESULT, HSTRING, PCWSTR};
@@ -727,10 +727,7 @@ impl AccessibilityEngine for WindowsEngine {
                             ),
                             com_error: Some(hresult),
                             operation: "get_root_element".to_string(),
-                            is_retryable: matches!(
-                                hresult,
-                                -2147467259 /* E_FAIL */ | -2147467262 /* E_NOINTERFACE */
-                            ),
+                            is_retryable: classify_com_error(hresult) == ErrorClass::Transient,
                         }
                     } else {
                         AutomationError::PlatformError(format!(
@@ -754,10 +751,7 @@ impl AccessibilityEngine for WindowsEngine {
                         ),
                         com_error: Some(hresult),
                         operation: "get_root_element".to_string(),
-                        is_retryable: matches!(
-                            hresult,
-                            -2147467259 /* E_FAIL */ | -2147467262 /* E_NOINTERFACE */
-                        ),
+                        is_retryable: classify_com_error(hresult) == ErrorClass::Transient,
                     }
                 } else {
                     AutomationError::PlatformError(format!(
@@ -1075,6 +1069,7 @@ impl AccessibilityEngine for WindowsEngine {
                     }
 
                     if let Selector::Nth(index) = selector {
+                        crate::ordering::sort_elements(&mut current_results);
                         let mut i = *index;
                         let len = current_results.len();
 
@@ -1142,6 +1137,37 @@ impl AccessibilityEngine for WindowsEngine {
                     })
                     .collect())
             }
+            Selector::State { role, predicates } => {
+                let mut matcher = self
+                    .automation
+                    .0
+                    .create_matcher()
+                    .from_ref(root_ele)
+                    .depth(depth.unwrap_or(50) as u32)
+                    .filter_fn({
+                        let predicates = predicates.clone();
+                        Box::new(move |e: &uiautomation::UIElement| {
+                            Ok(crate::selector::matches_state(&predicates, e))
+                        })
+                    })
+                    .timeout(timeout_ms as u64);
+                if let Some(role) = role {
+                    matcher = matcher.control_type(map_generic_role_to_win_roles(role));
+                }
+
+                let elements = matcher.find_all().map_err(|e| {
+                    AutomationError::ElementNotFound(format!("State search failed: {e}"))
+                })?;
+                Ok(elements
+                    .into_iter()
+                    .map(|ele| {
+                        UIElement::new(Box::new(WindowsUIElement {
+                            element: ThreadSafeWinUIElement(Arc::new(ele)),
+                            engine: None,
+                        }))
+                    })
+                    .collect())
+            }
             Selector::Visible(visibility) => {
                 let visibility = *visibility;
                 let matcher = self
@@ -1318,6 +1344,9 @@ impl AccessibilityEngine for WindowsEngine {
 
                 Ok(results)
             }
+            Selector::Unsupported(reason) => {
+                Err(AutomationError::UnsupportedOperation(reason.clone()))
+            }
             Selector::Invalid(reason) => Err(AutomationError::InvalidSelector(reason.clone())),
             Selector::Nth(_) => Err(AutomationError::InvalidSelector(
                 "Nth selector must be used as part of a chain (e.g. 'list >> nth=0')".to_string(),
@@ -1374,10 +1403,7 @@ impl AccessibilityEngine for WindowsEngine {
                             ),
                             com_error: Some(hresult),
                             operation: "get_root_element".to_string(),
-                            is_retryable: matches!(
-                                hresult,
-                                -2147467259 /* E_FAIL */ | -2147467262 /* E_NOINTERFACE */
-                            ),
+                            is_retryable: classify_com_error(hresult) == ErrorClass::Transient,
                         }
                     } else {
                         AutomationError::PlatformError(format!(
@@ -1401,10 +1427,7 @@ impl AccessibilityEngine for WindowsEngine {
                         ),
                         com_error: Some(hresult),
                         operation: "get_root_element".to_string(),
-                        is_retryable: matches!(
-                            hresult,
-                            -2147467259 /* E_FAIL */ | -2147467262 /* E_NOINTERFACE */
-                        ),
+                        is_retryable: classify_com_error(hresult) == ErrorClass::Transient,
                     }
                 } else {
                     AutomationError::PlatformError(format!(
@@ -1758,6 +1781,7 @@ impl AccessibilityEngine for WindowsEngine {
                         let sub_chain = Selector::Chain(collection_selectors.to_vec());
                         self.find_elements(&sub_chain, root, timeout, None)?
                     };
+                    crate::ordering::sort_elements(&mut elements);
 
                     let mut idx = *index;
                     let len = elements.len() as i32;
@@ -1846,6 +1870,17 @@ impl AccessibilityEngine for WindowsEngine {
                                         ))
                                     })?;
 
+                                let mut children: Vec<UIElement> = children
+                                    .into_iter()
+                                    .map(|child| {
+                                        UIElement::new(Box::new(WindowsUIElement {
+                                            element: ThreadSafeWinUIElement(Arc::new(child)),
+                                            engine: None,
+                                        }))
+                                    })
+                                    .collect();
+                                crate::ordering::sort_elements(&mut children);
+
                                 let mut idx = *index;
                                 let len = children.len() as i32;
 
@@ -1854,11 +1889,7 @@ impl AccessibilityEngine for WindowsEngine {
                                 }
 
                                 if idx >= 0 && idx < len {
-                                    let selected = &children[idx as usize];
-                                    current_element = UIElement::new(Box::new(WindowsUIElement {
-                                        element: ThreadSafeWinUIElement(Arc::new(selected.clone())),
-                                        engine: None,
-                                    }));
+                                    current_element = children.swap_remove(idx as usize);
                                 } else {
                                     debug!(
                                         "Nth index {} out of bounds (found {} children)",
@@ -1935,6 +1966,32 @@ impl AccessibilityEngine for WindowsEngine {
                     engine: None,
                 })))
             }
+            Selector::State { role, predicates } => {
+                let mut matcher = self
+                    .automation
+                    .0
+                    .create_matcher()
+                    .from_ref(root_ele)
+                    .depth(50)
+                    .filter_fn({
+                        let predicates = predicates.clone();
+                        Box::new(move |e: &uiautomation::UIElement| {
+                            Ok(crate::selector::matches_state(&predicates, e))
+                        })
+                    })
+                    .timeout(timeout_ms as u64);
+                if let Some(role) = role {
+                    matcher = matcher.control_type(map_generic_role_to_win_roles(role));
+                }
+
+                let element = matcher.find_first().map_err(|e| {
+                    AutomationError::ElementNotFound(format!("State search failed: {e}"))
+                })?;
+                Ok(UIElement::new(Box::new(WindowsUIElement {
+                    element: ThreadSafeWinUIElement(Arc::new(element)),
+                    engine: None,
+                })))
+            }
             Selector::Visible(visibility) => {
                 let visibility = *visibility;
                 let matcher = self
@@ -2079,6 +2136,9 @@ impl AccessibilityEngine for WindowsEngine {
                     ))
                 }
             }
+            Selector::Unsupported(reason) => {
+                Err(AutomationError::UnsupportedOperation(reason.clone()))
+            }
             Selector::Invalid(reason) => Err(AutomationError::InvalidSelector(reason.clone())),
         }
     }
@@ -2953,20 +3013,7 @@ impl AccessibilityEngine for WindowsEngine {
         })?;
 
         // Run the async code block on the runtime
-        rt.block_on(async {
-            let engine = OcrEngine::new(OcrProvider::Auto).map_err(|e| {
-                AutomationError::PlatformError(format!("Failed to create OCR engine: {e}"))
-            })?;
-
-            let (text, _language, _confidence) = engine // Destructure the tuple
-                .recognize_file(image_path)
-                .await
-                .map_err(|e| {
-                    AutomationError::PlatformError(format!("OCR recognition failed: {e}"))
-                })?;
-
-            Ok(text) // Return only the text
-        })
+        rt.block_on(crate::ocr::recognize_file(image_path))
     }
 
     async fn ocr_screenshot(
@@ -2989,16 +3036,7 @@ impl AccessibilityEngine for WindowsEngine {
         let dynamic_image = DynamicImage::ImageRgba8(img_buffer);
 
         // Directly await the OCR operation within the existing async context
-        let engine = OcrEngine::new(OcrProvider::Auto).map_err(|e| {
-            AutomationError::PlatformError(format!("Failed to create OCR engine: {e}"))
-        })?;
-
-        let (text, _language, _confidence) = engine
-            .recognize_image(&dynamic_image) // Use recognize_image
-            .await // << Directly await here
-            .map_err(|e| AutomationError::PlatformError(format!("OCR recognition failed: {e}")))?;
-
-        Ok(text)
+        crate::ocr::recognize_image(&dynamic_image).await
     }
 
     fn activate_browser_window_by_title(&self, title: &str) -> Result<(), AutomationError> {
@@ -3352,6 +3390,16 @@ impl AccessibilityEngine for WindowsEngine {
             selected_window_name, pid, title
         );
 
+        // Fetch the subtree's properties in one batched cache request. Tree building reads
+        // cached values first, so this avoids a round-trip per property per element.
+        let selected_window = match cache::cache_subtree(&self.automation.0, &selected_window) {
+            Ok(cached) => cached,
+            Err(e) => {
+                debug!("Subtree caching failed, reading properties live: {}", e);
+                selected_window
+            }
+        };
+
         // Wrap the raw OS element into our UIElement
         let window_element_wrapper = UIElement::new(Box::new(WindowsUIElement {
             element: ThreadSafeWinUIElement(Arc::new(selected_window)),
@@ -3442,6 +3490,64 @@ impl AccessibilityEngine for WindowsEngine {
         Ok(())
     }
 
+    fn window_z_order(&self) -> Result<Vec<crate::WindowLayer>, AutomationError> {
+        use windows::core::BOOL;
+        use windows::Win32::Foundation::{HWND, LPARAM, RECT};
+        use windows::Win32::UI::WindowsAndMessaging::{
+            EnumWindows, GetWindowRect, GetWindowTextW, GetWindowThreadProcessId, IsIconic,
+            IsWindowVisible,
+        };
+
+        unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+            let handles = &mut *(lparam.0 as *mut Vec<HWND>);
+            handles.push(hwnd);
+            BOOL(1)
+        }
+
+        // EnumWindows visits top-level windows in z-order, front-most first
+        let mut handles: Vec<HWND> = Vec::new();
+        unsafe {
+            EnumWindows(
+                Some(collect),
+                LPARAM(&mut handles as *mut Vec<HWND> as isize),
+            )
+            .map_err(|e| {
+                AutomationError::PlatformError(format!("Failed to enumerate windows: {e}"))
+            })?;
+        }
+
+        let mut layers = Vec::new();
+        for hwnd in handles {
+            unsafe {
+                if !IsWindowVisible(hwnd).as_bool() || IsIconic(hwnd).as_bool() {
+                    continue;
+                }
+                let mut rect = RECT::default();
+                if GetWindowRect(hwnd, &mut rect).is_err()
+                    || rect.right <= rect.left
+                    || rect.bottom <= rect.top
+                {
+                    continue;
+                }
+                let mut process_id = 0u32;
+                GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+                let mut title = [0u16; 512];
+                let len = GetWindowTextW(hwnd, &mut title).max(0) as usize;
+                layers.push(crate::WindowLayer {
+                    title: String::from_utf16_lossy(&title[..len]),
+                    process_id,
+                    bounds: (
+                        rect.left as f64,
+                        rect.top as f64,
+                        (rect.right - rect.left) as f64,
+                        (rect.bottom - rect.top) as f64,
+                    ),
+                });
+            }
+        }
+        Ok(layers)
+    }
+
     /// Enable downcasting to concrete engine types
     fn as_any(&self) -> &dyn std::any::Any {
         self
@@ -4,6 +4,7 @@
 //! the Windows UI Automation API through the uiautomation crate.
 
 pub mod applications;
+pub mod cache;
 pub mod element;
 pub mod engine;
 pub mod highlighting;
@@ -22,7 +23,7 @@ pub use types::{FontStyle, HighlightHandle, TextPosition};
 pub use utils::{convert_uiautomation_element_to_terminator, generate_element_id};
 
 // Re-export from applications module
-pub use applications::get_process_name_by_pid;
+pub use applications::{get_file_version, get_process_name_by_pid};
 
 // Re-export virtual display support
 pub use virtual_display::{
@@ -4,6 +4,7 @@ use super::types::ThreadSafeWinUIElement;
 use crate::{AutomationError, UIElement};
 use std::sync::Arc;
 use uiautomation::controls::ControlType;
+use uiautomation::patterns;
 use uiautomation::types::UIProperty;
 use uiautomation::UIAutomation;
 use windows::core::HRESULT;
@@ -97,6 +98,13 @@ pub fn convert_uiautomation_element_to_terminator(element: uiautomation::UIEleme
     }))
 }
 
+/// Whether a UI Automation error means the element is no longer in the tree
+pub(crate) fn is_element_not_available(error_str: &str) -> bool {
+    error_str.contains("UIA_E_ELEMENTNOTAVAILABLE")
+        || error_str.contains("0x80040201")
+        || error_str.contains("-2147220991")
+}
+
 /// Helper function to create UIAutomation instance with proper COM initialization
 pub(crate) fn create_ui_automation_with_com_init() -> Result<UIAutomation, AutomationError> {
     unsafe {
@@ -112,6 +120,25 @@ pub(crate) fn create_ui_automation_with_com_init() -> Result<UIAutomation, Autom
     UIAutomation::new_direct().map_err(|e| AutomationError::PlatformError(e.to_string()))
 }
 
+// State selectors read the value and toggle patterns on every match
+impl crate::selector::LiveState for uiautomation::UIElement {
+    fn live_value(&self) -> Option<String> {
+        self.get_pattern::<patterns::UIValuePattern>()
+            .ok()?
+            .get_value()
+            .ok()
+    }
+
+    fn live_toggled(&self) -> Option<bool> {
+        let state = self
+            .get_pattern::<patterns::UITogglePattern>()
+            .ok()?
+            .get_toggle_state()
+            .ok()?;
+        Some(state == uiautomation::types::ToggleState::On)
+    }
+}
+
 /// Maps generic role strings to Windows ControlType enums
 pub(crate) fn map_generic_role_to_win_roles(role: &str) -> ControlType {
     match role.to_lowercase().as_str() {
@@ -456,3 +456,78 @@ mod performance_benchmarks {
         }
     }
 }
+
+#[cfg(test)]
+mod cache_benchmarks {
+    use super::super::windows::tree_builder::{
+        build_ui_node_tree_configurable, TreeBuildingConfig, TreeBuildingContext,
+    };
+    use super::super::windows::{cache, WindowsEngine, WindowsUIElement};
+    use crate::platforms::{AccessibilityEngine, PropertyLoadingMode};
+    use crate::UIElement;
+    use std::time::{Duration, Instant};
+
+    fn build(element: &UIElement) -> (usize, Duration) {
+        let mut context = TreeBuildingContext {
+            config: TreeBuildingConfig {
+                timeout_per_operation_ms: 50,
+                yield_every_n_elements: usize::MAX,
+                batch_size: 50,
+            },
+            property_mode: PropertyLoadingMode::Fast,
+            elements_processed: 0,
+            max_depth_reached: 0,
+            cache_hits: 0,
+            fallback_calls: 0,
+            errors_encountered: 0,
+        };
+        let start = Instant::now();
+        build_ui_node_tree_configurable(element, 0, &mut context).expect("tree building failed");
+        (context.elements_processed, start.elapsed())
+    }
+
+    /// Compares per-element property reads against a single batched cache request
+    #[test]
+    #[ignore]
+    fn benchmark_batched_vs_live_tree_reads() {
+        let engine = WindowsEngine::new(false, false).expect("Failed to create Windows engine");
+        let app = engine
+            .open_application("calc")
+            .expect("Failed to open calculator");
+        std::thread::sleep(Duration::from_millis(1500));
+
+        let raw = app
+            .as_any()
+            .downcast_ref::<WindowsUIElement>()
+            .expect("expected a Windows element")
+            .get_raw_element()
+            .clone();
+
+        let (live_count, live_time) = build(&UIElement::new(Box::new(WindowsUIElement::new(
+            raw.clone(),
+        ))));
+
+        let cache_start = Instant::now();
+        let cached = cache::cache_subtree(&engine.automation.0, &raw).expect("cache failed");
+        let (batched_count, walk_time) =
+            build(&UIElement::new(Box::new(WindowsUIElement::new(cached))));
+        let batched_time = cache_start.elapsed();
+
+        println!("Live reads:    {live_count} elements in {live_time:?}");
+        println!(
+            "Batched reads: {batched_count} elements in {batched_time:?} (walk {walk_time:?})"
+        );
+        println!(
+            "Speedup: {:.2}x",
+            live_time.as_secs_f64() / batched_time.as_secs_f64().max(f64::EPSILON)
+        );
+
+        assert_eq!(live_count, batched_count);
+        assert!(
+            batched_time <= live_time,
+            "batched reads should not be slower than per-element reads"
+        );
+
+        let _ = app.close();
+    }
+}
@@ -542,3 +542,83 @@ fn test_window_transparency() {
     // Clean up
     notepad.close().unwrap();
 }
+
+fn build_tree_for_test(element: &crate::UIElement) -> crate::UINode {
+    use super::windows::tree_builder::{
+        build_ui_node_tree_configurable, TreeBuildingConfig, TreeBuildingContext,
+    };
+
+    let mut context = TreeBuildingContext {
+        config: TreeBuildingConfig {
+            timeout_per_operation_ms: 50,
+            yield_every_n_elements: 50,
+            batch_size: 50,
+        },
+        property_mode: crate::platforms::PropertyLoadingMode::Fast,
+        elements_processed: 0,
+        max_depth_reached: 0,
+        cache_hits: 0,
+        fallback_calls: 0,
+        errors_encountered: 0,
+    };
+    build_ui_node_tree_configurable(element, 0, &mut context).expect("tree building failed")
+}
+
+fn assert_same_tree(batched: &crate::UINode, live: &crate::UINode, path: &str) {
+    let (b, l) = (&batched.attributes, &live.attributes);
+    assert_eq!(b.role, l.role, "role differs at {path}");
+    assert_eq!(b.name, l.name, "name differs at {path}");
+    assert_eq!(b.properties, l.properties, "properties differ at {path}");
+    assert_eq!(b.bounds, l.bounds, "bounds differ at {path}");
+    assert_eq!(b.enabled, l.enabled, "enabled differs at {path}");
+    assert_eq!(
+        b.is_keyboard_focusable, l.is_keyboard_focusable,
+        "focusable differs at {path}"
+    );
+    assert_eq!(
+        batched.children.len(),
+        live.children.len(),
+        "child count differs at {path}"
+    );
+    for (i, (bc, lc)) in batched.children.iter().zip(&live.children).enumerate() {
+        assert_same_tree(bc, lc, &format!("{path}/{i}"));
+    }
+}
+
+#[test]
+fn test_batched_tree_matches_per_element_reads() {
+    let engine = match WindowsEngine::new(false, false) {
+        Ok(engine) => engine,
+        Err(_) => {
+            println!("Cannot create WindowsEngine, skipping batched tree test");
+            return;
+        }
+    };
+    let calculator = match engine.open_application("calc") {
+        Ok(app) => app,
+        Err(e) => {
+            println!("Cannot open calculator, skipping batched tree test: {e}");
+            return;
+        }
+    };
+    std::thread::sleep(std::time::Duration::from_millis(1000));
+
+    let raw = calculator
+        .as_any()
+        .downcast_ref::<WindowsUIElement>()
+        .expect("expected a Windows element")
+        .get_raw_element()
+        .clone();
+    let cached = super::windows::cache::cache_subtree(&engine.automation.0, &raw)
+        .expect("building the subtree cache should succeed");
+
+    let live_tree =
+        build_tree_for_test(&crate::UIElement::new(Box::new(WindowsUIElement::new(raw))));
+    let batched_tree = build_tree_for_test(&crate::UIElement::new(Box::new(
+        WindowsUIElement::new(cached),
+    )));
+
+    assert_same_tree(&batched_tree, &live_tree, "root");
+
+    let _ = calculator.close();
+}
@@ -43,10 +43,122 @@ pub enum Selector {
     Has(Box<Selector>),
     /// Navigate to parent element (Playwright-style ..)
     Parent,
+    /// Select by live control state, e.g. `checkbox[checked=true]` or `edit[value='']`
+    State {
+        role: Option<String>,
+        predicates: Vec<StatePredicate>,
+    },
+    /// A selector kind this build cannot evaluate (e.g. `ocr:` without the `ocr` feature), with a reason.
+    Unsupported(String),
     /// Represents an invalid selector string, with a reason.
     Invalid(String),
 }
 
+/// Why `image:` selectors fail: no image matching backend is compiled in
+pub const IMAGE_NOT_ENABLED: &str = "Image matching feature not enabled in this build";
+
+/// A condition on an element's current value or toggle state
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StatePredicate {
+    /// Text read through the value pattern, compared case-insensitively
+    Value(String),
+    /// Toggle state of a checkbox or switch
+    Checked(bool),
+}
+
+/// Control state read from the element at match time, never from a cached snapshot
+pub trait LiveState {
+    /// Current text of the value pattern, `None` when the element has no value pattern
+    fn live_value(&self) -> Option<String>;
+    /// Current toggle state, `None` when the element cannot be toggled
+    fn live_toggled(&self) -> Option<bool>;
+}
+
+impl StatePredicate {
+    /// Parses one `key=value` predicate from inside the brackets.
+    fn parse(predicate: &str) -> Result<Self, String> {
+        let (key, value) = predicate
+            .split_once('=')
+            .ok_or_else(|| format!("State predicate '{predicate}' must be key=value"))?;
+        let value = unquote(value.trim());
+        match key.trim().to_lowercase().as_str() {
+            "value" => Ok(StatePredicate::Value(value.to_string())),
+            "checked" | "toggled" => match value.to_lowercase().as_str() {
+                "true" => Ok(StatePredicate::Checked(true)),
+                "false" => Ok(StatePredicate::Checked(false)),
+                _ => Err(format!(
+                    "State predicate '{key}' expects true or false, got '{value}'"
+                )),
+            },
+            other => Err(format!(
+                "Unknown state predicate '{other}'. Supported: value, checked"
+            )),
+        }
+    }
+
+    /// Checks the predicate against the element's current state.
+    pub fn matches(&self, element: &impl LiveState) -> bool {
+        match self {
+            StatePredicate::Value(expected) => element
+                .live_value()
+                .is_some_and(|actual| actual.to_lowercase() == expected.to_lowercase()),
+            StatePredicate::Checked(expected) => element.live_toggled() == Some(*expected),
+        }
+    }
+}
+
+/// Checks every predicate, reading the element's state as it is right now.
+pub fn matches_state(predicates: &[StatePredicate], element: &impl LiveState) -> bool {
+    predicates
+        .iter()
+        .all(|predicate| predicate.matches(element))
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['\'', '"'] {
+        if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+            return &value[1..value.len() - 1];
+        }
+    }
+    value
+}
+
+/// Parses `role[key=value][key=value]`, or returns `None` when `s` is not a state selector.
+fn parse_state_selector(s: &str) -> Option<Selector> {
+    let open = s.find('[')?;
+    if !s.ends_with(']') {
+        return None;
+    }
+    let base = s[..open].trim();
+    let role = base.strip_prefix("role:").unwrap_or(base);
+    if !role
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return None;
+    }
+
+    let mut predicates = Vec::new();
+    let mut rest = &s[open..];
+    while !rest.is_empty() {
+        let Some(close) = rest.find(']').filter(|_| rest.starts_with('[')) else {
+            return Some(Selector::Invalid(format!(
+                "Malformed state selector: \"{s}\""
+            )));
+        };
+        match StatePredicate::parse(&rest[1..close]) {
+            Ok(predicate) => predicates.push(predicate),
+            Err(reason) => return Some(Selector::Invalid(reason)),
+        }
+        rest = rest[close + 1..].trim_start();
+    }
+
+    Some(Selector::State {
+        role: Some(role.to_string()).filter(|r| !r.is_empty()),
+        predicates,
+    })
+}
+
 impl std::fmt::Display for Selector {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{self:?}")
@@ -89,6 +201,11 @@ impl From<&str> for Selector {
             }
         }
 
+        // role[checked=true], edit[value=''] and other live state predicates
+        if let Some(selector) = parse_state_selector(s) {
+            return selector;
+        }
+
         // Make common UI roles like "window", "button", etc. default to Role selectors
         // instead of Name selectors
         match s {
@@ -181,6 +298,17 @@ impl From<&str> for Selector {
                     Selector::Invalid(format!("Invalid index for nth selector: '{index_str}'"))
                 }
             }
+            _ if s.to_lowercase().starts_with("ocr:") => Selector::Unsupported(
+                if crate::ocr::OCR_ENABLED {
+                    "ocr: selectors are not supported yet; use OCR on a screenshot instead"
+                        .to_string()
+                } else {
+                    crate::ocr::OCR_NOT_ENABLED.to_string()
+                },
+            ),
+            _ if s.to_lowercase().starts_with("image:") => {
+                Selector::Unsupported(IMAGE_NOT_ENABLED.to_string())
+            }
             _ if s.starts_with("id:") => Selector::Id(s[3..].to_string()),
             _ if s.starts_with("text:") => Selector::Text(s[5..].to_string()),
             _ if s.contains(':') => {
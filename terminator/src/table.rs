@@ -0,0 +1,153 @@
+//! Reading table and data grid cells by column header.
+//!
+//! Tables are read through the accessibility tree: the table's children are a header
+//! row (or a flat run of header items) followed by data rows, and each data row's
+//! children are its cells. The column index is resolved from the header labels, so
+//! workflows keep working when columns are reordered.
+
+use crate::{AutomationError, UIElement};
+
+const HEADER_ROW_ROLES: &[&str] = &["header", "table header"];
+const HEADER_ITEM_ROLES: &[&str] = &[
+    "headeritem",
+    "column header",
+    "table column header",
+    "axcolumnheader",
+];
+
+fn is_header_row(role: &str) -> bool {
+    HEADER_ROW_ROLES.contains(&role.to_lowercase().as_str())
+}
+
+fn is_header_item(role: &str) -> bool {
+    HEADER_ITEM_ROLES.contains(&role.to_lowercase().as_str())
+}
+
+/// The parts of an element the table reader needs.
+pub(crate) trait TableNode: Sized {
+    fn role(&self) -> String;
+    fn name(&self) -> Option<String>;
+    fn children(&self) -> Result<Vec<Self>, AutomationError>;
+    /// A copy of the element whose subtree can be read without a call per property.
+    fn cached_subtree(&self) -> Option<Self> {
+        None
+    }
+}
+
+impl TableNode for UIElement {
+    fn role(&self) -> String {
+        UIElement::role(self)
+    }
+
+    fn name(&self) -> Option<String> {
+        UIElement::name(self)
+    }
+
+    fn children(&self) -> Result<Vec<Self>, AutomationError> {
+        UIElement::children(self)
+    }
+
+    fn cached_subtree(&self) -> Option<Self> {
+        UIElement::cached_subtree(self)
+    }
+}
+
+/// Finds the column index of a header label.
+///
+/// An exact (trimmed) match wins; otherwise the label is matched case-insensitively.
+pub fn resolve_header(headers: &[String], column_header: &str) -> Result<usize, AutomationError> {
+    let wanted = column_header.trim();
+    headers
+        .iter()
+        .position(|h| h.trim() == wanted)
+        .or_else(|| {
+            headers
+                .iter()
+                .position(|h| h.trim().eq_ignore_ascii_case(wanted))
+        })
+        .ok_or_else(|| {
+            AutomationError::InvalidArgument(format!(
+                "Column header '{column_header}' not found. Available headers: {headers:?}"
+            ))
+        })
+}
+
+/// Elements paired with their index among their parent's children.
+type Indexed<T> = Vec<(usize, T)>;
+
+/// Splits a table's children into header labels and data rows.
+fn read_table<T: TableNode>(table: &T) -> Result<(Vec<String>, Indexed<T>), AutomationError> {
+    let mut headers = Vec::new();
+    let mut rows = Vec::new();
+    for (index, child) in table.children()?.into_iter().enumerate() {
+        let role = child.role();
+        if headers.is_empty() && is_header_row(&role) {
+            headers = child
+                .children()?
+                .iter()
+                .filter(|item| is_header_item(&item.role()))
+                .map(|item| item.name().unwrap_or_default())
+                .collect();
+        } else if is_header_item(&role) {
+            headers.push(child.name().unwrap_or_default());
+        } else {
+            rows.push((index, child));
+        }
+    }
+    Ok((headers, rows))
+}
+
+/// Returns the cell in data row `row` (0-based, header row excluded) under `column_header`.
+pub(crate) fn find_cell<T: TableNode>(
+    table: &T,
+    row: usize,
+    column_header: &str,
+) -> Result<T, AutomationError> {
+    // Reading the table touches every header and row, so fetch them in one batch when
+    // the platform can rather than one call per property
+    let cached = table.cached_subtree();
+    let (headers, mut rows) = read_table(cached.as_ref().unwrap_or(table))?;
+    if headers.is_empty() {
+        return Err(AutomationError::ElementNotFound(
+            "Table has no header row to resolve column names from".to_string(),
+        ));
+    }
+    let column = resolve_header(&headers, column_header)?;
+    let row_count = rows.len();
+    if row >= row_count {
+        return Err(AutomationError::ElementNotFound(format!(
+            "Row {row} is out of range; the table has {row_count} data rows"
+        )));
+    }
+    let (row_index, row_node) = rows.swap_remove(row);
+    let cells: Indexed<T> = row_node
+        .children()?
+        .into_iter()
+        .enumerate()
+        .filter(|(_, cell)| !is_header_item(&cell.role()))
+        .collect();
+    let cell_count = cells.len();
+    let (cell_index, cell) = cells.into_iter().nth(column).ok_or_else(|| {
+        AutomationError::ElementNotFound(format!(
+            "Row {row} has {cell_count} cells; no cell under column '{column_header}' (index {column})"
+        ))
+    })?;
+    if cached.is_none() {
+        return Ok(cell);
+    }
+
+    // The cached copy only answers from its snapshot, so hand back the live cell at the
+    // same position for the caller to act on
+    table
+        .children()?
+        .into_iter()
+        .nth(row_index)
+        .map(|row_node| row_node.children())
+        .transpose()?
+        .and_then(|cells| cells.into_iter().nth(cell_index))
+        .ok_or_else(|| {
+            AutomationError::ElementNotFound(format!(
+                "Row {row} changed while the table was being read; no cell under column '{column_header}'"
+            ))
+        })
+}
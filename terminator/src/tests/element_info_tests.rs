@@ -0,0 +1,88 @@
+use crate::{ElementBounds, ElementInfo, ElementStates, UIElementAttributes, UINode};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+fn submit_button() -> UIElementAttributes {
+    let mut properties = HashMap::new();
+    properties.insert("AutomationId".to_string(), Some(json!("submitBtn")));
+    UIElementAttributes {
+        role: "Button".to_string(),
+        name: Some("Submit".to_string()),
+        value: Some(String::new()),
+        properties,
+        bounds: Some((10.0, 20.0, 80.0, 24.0)),
+        enabled: Some(true),
+        is_focused: Some(true),
+        is_keyboard_focusable: Some(true),
+        ..Default::default()
+    }
+}
+
+fn keys(value: &Value) -> Vec<&str> {
+    let mut keys: Vec<&str> = value
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    keys.sort_unstable();
+    keys
+}
+
+#[test]
+fn test_info_from_attributes() {
+    let info = ElementInfo::from_attributes(&submit_button());
+    assert_eq!(
+        info,
+        ElementInfo {
+            role: "Button".to_string(),
+            name: Some("Submit".to_string()),
+            automation_id: Some("submitBtn".to_string()),
+            bounds: Some(ElementBounds {
+                x: 10.0,
+                y: 20.0,
+                width: 80.0,
+                height: 24.0
+            }),
+            // Empty strings are reported as missing
+            value: None,
+            states: ElementStates {
+                enabled: true,
+                focused: true,
+                keyboard_focusable: true,
+                selected: false,
+                toggled: false,
+            },
+        }
+    );
+}
+
+#[test]
+fn test_tree_node_info_matches_element_attributes() {
+    let node = UINode {
+        id: Some("42".to_string()),
+        attributes: submit_button(),
+        children: vec![],
+    };
+    assert_eq!(node.info(), ElementInfo::from_attributes(&submit_button()));
+}
+
+#[test]
+fn test_serialized_shape_is_fixed() {
+    let full = serde_json::to_value(ElementInfo::from_attributes(&submit_button())).unwrap();
+    let empty = serde_json::to_value(ElementInfo::from_attributes(&UIElementAttributes::default()))
+        .unwrap();
+
+    let expected = vec!["automation_id", "bounds", "name", "role", "states", "value"];
+    assert_eq!(keys(&full), expected);
+    assert_eq!(keys(&empty), expected);
+    assert_eq!(keys(&full["states"]), keys(&empty["states"]));
+    assert_eq!(
+        full["bounds"],
+        json!({"x": 10.0, "y": 20.0, "width": 80.0, "height": 24.0})
+    );
+    assert_eq!(empty["bounds"], Value::Null);
+
+    let round_trip: ElementInfo = serde_json::from_value(full.clone()).unwrap();
+    assert_eq!(serde_json::to_value(round_trip).unwrap(), full);
+}
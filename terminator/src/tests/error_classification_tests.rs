@@ -0,0 +1,116 @@
+use crate::errors::hresult::{
+    E_ACCESSDENIED, E_FAIL, E_INVALIDARG, RPC_E_CALL_REJECTED, UIA_E_ELEMENTNOTAVAILABLE,
+    UIA_E_TIMEOUT,
+};
+use crate::{classify_com_error, AutomationError, ErrorCategory, ErrorClass};
+
+fn uia_error(com_error: Option<i32>, is_retryable: bool) -> AutomationError {
+    AutomationError::UIAutomationAPIError {
+        message: "call failed".to_string(),
+        com_error,
+        operation: "find_elements".to_string(),
+        is_retryable,
+    }
+}
+
+#[test]
+fn test_transient_codes() {
+    assert_eq!(
+        classify_com_error(UIA_E_ELEMENTNOTAVAILABLE),
+        ErrorClass::Transient
+    );
+    assert_eq!(classify_com_error(UIA_E_TIMEOUT), ErrorClass::Transient);
+    assert_eq!(
+        classify_com_error(RPC_E_CALL_REJECTED),
+        ErrorClass::Transient
+    );
+    assert_eq!(classify_com_error(E_FAIL), ErrorClass::Transient);
+    // Decimal form as it appears in windows-rs debug output
+    assert_eq!(classify_com_error(-2147220991), ErrorClass::Transient);
+}
+
+#[test]
+fn test_fatal_codes() {
+    assert_eq!(classify_com_error(E_ACCESSDENIED), ErrorClass::Fatal);
+    assert_eq!(classify_com_error(E_INVALIDARG), ErrorClass::Fatal);
+    assert_eq!(classify_com_error(0x12345678), ErrorClass::Fatal);
+}
+
+#[test]
+fn test_error_exposes_code_and_classification() {
+    let transient = uia_error(Some(UIA_E_ELEMENTNOTAVAILABLE), false);
+    assert_eq!(transient.com_error_code(), Some(UIA_E_ELEMENTNOTAVAILABLE));
+    assert!(transient.is_transient());
+
+    let fatal = uia_error(Some(E_ACCESSDENIED), false);
+    assert!(!fatal.is_transient());
+
+    // An explicit retryable flag wins over the code
+    assert!(uia_error(Some(E_ACCESSDENIED), true).is_transient());
+    assert!(!uia_error(None, false).is_transient());
+    assert!(!AutomationError::Timeout("slow".to_string()).is_transient());
+    assert_eq!(
+        AutomationError::Timeout("slow".to_string()).com_error_code(),
+        None
+    );
+}
+
+#[test]
+fn test_display_includes_hresult() {
+    let err = uia_error(Some(UIA_E_ELEMENTNOTAVAILABLE), true);
+    assert_eq!(
+        err.to_string(),
+        "UI Automation API error: call failed (HRESULT 0x80040201)"
+    );
+    assert_eq!(
+        uia_error(None, true).to_string(),
+        "UI Automation API error: call failed"
+    );
+}
+
+#[test]
+fn test_authoring_mistakes_are_user_faults() {
+    for error in [
+        AutomationError::InvalidSelector("role:".to_string()),
+        AutomationError::InvalidArgument("timeout must be positive".to_string()),
+        AutomationError::ElementNotFound("role:Button|name:Submit".to_string()),
+        AutomationError::UnsupportedOperation("toggle on a Text element".to_string()),
+    ] {
+        assert_eq!(error.category(), ErrorCategory::User, "{error}");
+    }
+}
+
+#[test]
+fn test_environment_hiccups_are_transient() {
+    for error in [
+        AutomationError::Timeout("window did not appear".to_string()),
+        AutomationError::ElementObscured("covered by 'Save As'".to_string()),
+        AutomationError::TreeChanged("dialog closed".to_string()),
+        uia_error(Some(UIA_E_ELEMENTNOTAVAILABLE), false),
+        uia_error(None, true),
+    ] {
+        assert_eq!(error.category(), ErrorCategory::Transient, "{error}");
+    }
+}
+
+#[test]
+fn test_platform_failures_are_system_faults() {
+    for error in [
+        AutomationError::PlatformError("COM not initialized".to_string()),
+        AutomationError::Internal("poisoned lock".to_string()),
+        AutomationError::PermissionDenied("elevated process".to_string()),
+        AutomationError::UnsupportedPlatform("freebsd".to_string()),
+        uia_error(Some(E_ACCESSDENIED), false),
+    ] {
+        assert_eq!(error.category(), ErrorCategory::System, "{error}");
+    }
+}
+
+#[test]
+fn test_category_serializes_as_snake_case() {
+    assert_eq!(
+        serde_json::to_value(ErrorCategory::Transient).unwrap(),
+        serde_json::json!("transient")
+    );
+    assert_eq!(ErrorCategory::User.to_string(), "user");
+}
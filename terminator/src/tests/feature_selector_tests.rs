@@ -0,0 +1,49 @@
+use crate::ocr::{OCR_ENABLED, OCR_NOT_ENABLED};
+use crate::selector::IMAGE_NOT_ENABLED;
+use crate::Selector;
+
+#[test]
+fn test_image_selector_is_unsupported() {
+    assert_eq!(
+        Selector::from("image:logo.png"),
+        Selector::Unsupported(IMAGE_NOT_ENABLED.to_string())
+    );
+}
+
+#[test]
+fn test_ocr_enabled_matches_feature() {
+    assert_eq!(OCR_ENABLED, cfg!(feature = "ocr"));
+}
+
+#[cfg(not(feature = "ocr"))]
+#[test]
+fn test_ocr_selector_reports_missing_feature() {
+    assert_eq!(
+        Selector::from("ocr:Submit"),
+        Selector::Unsupported(OCR_NOT_ENABLED.to_string())
+    );
+}
+
+#[cfg(not(feature = "ocr"))]
+#[tokio::test]
+async fn test_ocr_calls_fail_without_feature() {
+    let image = image::DynamicImage::new_rgba8(4, 4);
+    let err = crate::ocr::recognize_image(&image).await.unwrap_err();
+    assert!(
+        matches!(&err, crate::AutomationError::UnsupportedOperation(msg) if msg == OCR_NOT_ENABLED),
+        "{err:?}"
+    );
+    assert!(matches!(
+        crate::ocr::recognize_file("screen.png").await,
+        Err(crate::AutomationError::UnsupportedOperation(_))
+    ));
+}
+
+#[cfg(feature = "ocr")]
+#[test]
+fn test_ocr_selector_is_recognized_but_not_evaluated() {
+    match Selector::from("ocr:Submit") {
+        Selector::Unsupported(reason) => assert_ne!(reason, OCR_NOT_ENABLED),
+        other => panic!("expected an unsupported selector, got {other:?}"),
+    }
+}
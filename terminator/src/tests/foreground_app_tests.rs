@@ -0,0 +1,50 @@
+use crate::{AppInfo, AutomationError, Desktop};
+
+#[tokio::test]
+async fn test_foreground_app_metadata_for_notepad() -> Result<(), AutomationError> {
+    let desktop = Desktop::new(false, true)?;
+    let notepad = desktop.open_application("notepad")?;
+    std::thread::sleep(std::time::Duration::from_millis(1500));
+    notepad.focus()?;
+
+    let info = desktop.foreground_app().await?;
+    let _ = notepad.close();
+
+    assert!(info.pid > 0, "pid should be set: {info:?}");
+    assert_eq!(
+        info.process_name
+            .as_deref()
+            .map(str::to_lowercase)
+            .as_deref(),
+        Some("notepad"),
+        "unexpected process name: {info:?}"
+    );
+    assert!(
+        info.window_title
+            .as_deref()
+            .is_some_and(|title| title.to_lowercase().contains("notepad")),
+        "window title should name the app: {info:?}"
+    );
+    let path = info
+        .executable_path
+        .as_deref()
+        .expect("notepad is not a protected process");
+    assert!(path.to_lowercase().ends_with("notepad.exe"), "{path}");
+    let version = info.version.as_deref().expect("notepad carries a version");
+    assert_eq!(version.split('.').count(), 4, "{version}");
+    assert!(version.split('.').all(|part| part.parse::<u16>().is_ok()));
+
+    // The serialized shape is what the MCP tool returns
+    let json = serde_json::to_value(&info).unwrap();
+    for key in [
+        "process_name",
+        "pid",
+        "window_title",
+        "executable_path",
+        "version",
+    ] {
+        assert!(json.get(key).is_some(), "missing {key} in {json}");
+    }
+    assert_eq!(serde_json::from_value::<AppInfo>(json).unwrap(), info);
+    Ok(())
+}
@@ -0,0 +1,118 @@
+use crate::identity::{find_by_identity, identity_key, IdentityNode};
+use crate::{AutomationError, ElementInfo};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_HANDLE: AtomicUsize = AtomicUsize::new(1);
+
+/// An element handle: `handle` changes on every render, like a runtime id does.
+#[derive(Debug, Clone)]
+struct Node {
+    handle: usize,
+    info: ElementInfo,
+    children: Vec<Node>,
+}
+
+impl IdentityNode for Node {
+    fn info(&self) -> ElementInfo {
+        self.info.clone()
+    }
+
+    fn children(&self) -> Result<Vec<Self>, AutomationError> {
+        Ok(self.children.clone())
+    }
+}
+
+fn node(role: &str, name: &str, automation_id: Option<&str>, children: Vec<Node>) -> Node {
+    Node {
+        handle: NEXT_HANDLE.fetch_add(1, Ordering::Relaxed),
+        info: ElementInfo {
+            role: role.to_string(),
+            name: Some(name.to_string()).filter(|n| !n.is_empty()),
+            automation_id: automation_id.map(str::to_string),
+            ..Default::default()
+        },
+        children,
+    }
+}
+
+/// Renders an order list; every call produces fresh handles.
+fn render(orders: &[(&str, &str)]) -> Node {
+    let rows = orders
+        .iter()
+        .map(|(id, label)| {
+            node(
+                "ListItem",
+                label,
+                Some(id),
+                vec![node("Text", label, None, vec![])],
+            )
+        })
+        .collect();
+    node(
+        "Window",
+        "Orders",
+        None,
+        vec![node("List", "", Some("orderList"), rows)],
+    )
+}
+
+fn find_row<'a>(tree: &'a Node, name: &str) -> &'a Node {
+    tree.children[0]
+        .children
+        .iter()
+        .find(|row| row.info.name.as_deref() == Some(name))
+        .unwrap()
+}
+
+#[test]
+fn test_identity_resolves_after_re_render() {
+    let before = render(&[("o-1", "Order 1"), ("o-2", "Order 2"), ("o-3", "Order 3")]);
+    let row = find_row(&before, "Order 2");
+    let key = identity_key(&row.info).unwrap();
+
+    // The list refreshes: rows are re-created, reordered and one is inserted
+    let after = render(&[("o-3", "Order 3"), ("o-0", "Order 0"), ("o-2", "Order 2")]);
+    let found = find_by_identity(after.clone(), &key, 10).unwrap();
+
+    assert_ne!(found.handle, row.handle);
+    assert_eq!(found.handle, find_row(&after, "Order 2").handle);
+    assert_eq!(found.info.automation_id.as_deref(), Some("o-2"));
+}
+
+#[test]
+fn test_content_identity_without_automation_id() {
+    let before = render(&[("o-1", "Order 1")]);
+    let label = &find_row(&before, "Order 1").children[0];
+    let key = identity_key(&label.info).unwrap();
+    assert_eq!(key, "content:Text:Order 1");
+
+    let after = render(&[("o-9", "Order 9"), ("o-1", "Order 1")]);
+    let found = find_by_identity(after.clone(), &key, 10).unwrap();
+    assert_ne!(found.handle, label.handle);
+    assert_eq!(found.handle, find_row(&after, "Order 1").children[0].handle);
+}
+
+#[test]
+fn test_automation_id_wins_over_content() {
+    let renamed = node("ListItem", "Order 2 (shipped)", Some("o-2"), vec![]);
+    let original = node("ListItem", "Order 2", Some("o-2"), vec![]);
+    assert_eq!(identity_key(&renamed.info), identity_key(&original.info));
+    assert_eq!(identity_key(&original.info).unwrap(), "aid:ListItem:o-2");
+}
+
+#[test]
+fn test_identity_not_found_and_invalid() {
+    let tree = render(&[("o-1", "Order 1")]);
+    assert!(identity_key(&node("Pane", "", None, vec![]).info).is_none());
+    assert!(matches!(
+        find_by_identity(tree.clone(), "aid:ListItem:o-404", 10),
+        Err(AutomationError::ElementNotFound(_))
+    ));
+    assert!(matches!(
+        find_by_identity(tree.clone(), " ", 10),
+        Err(AutomationError::InvalidArgument(_))
+    ));
+    // Rows are two levels below the window
+    assert!(find_by_identity(tree.clone(), "aid:ListItem:o-1", 1).is_err());
+    assert!(find_by_identity(tree, "aid:ListItem:o-1", 2).is_ok());
+}
@@ -1,5 +1,13 @@
 mod e2e_tests;
+#[cfg(test)]
+mod element_info_tests;
+#[cfg(test)]
+mod error_classification_tests;
+#[cfg(test)]
+mod feature_selector_tests;
 mod firefox_window_tests;
+#[cfg(all(test, target_os = "windows"))]
+mod foreground_app_tests;
 mod functional_verification_tests;
 mod get_applications_tests;
 #[cfg(test)]
@@ -7,12 +15,24 @@ mod high_level_inputs_tests;
 #[cfg(all(test, target_os = "windows"))]
 mod id_stability_tests;
 #[cfg(test)]
+mod identity_tests;
+#[cfg(test)]
+mod ocr_batch_tests;
+#[cfg(test)]
+mod ordering_tests;
+#[cfg(test)]
 mod parent_navigation_tests;
 #[cfg(test)]
 mod performance_tests;
 #[cfg(all(test, target_os = "windows"))]
 mod selector_tests;
+#[cfg(test)]
+mod state_selector_tests;
+#[cfg(test)]
+mod table_tests;
 mod test_serialization;
+#[cfg(test)]
+mod z_order_tests;
 
 // Initialize tracing for tests
 pub fn init_tracing() {
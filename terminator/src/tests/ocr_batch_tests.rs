@@ -0,0 +1,148 @@
+use crate::ocr::{ocr_regions, OcrBatchOptions, OcrRecognizer, OcrRegion};
+use crate::{AutomationError, ScreenshotResult};
+use image::{DynamicImage, GenericImageView};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const GREEN: [u8; 4] = [0, 255, 0, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+
+/// A 400x100 fixture: red, green and blue 100px panels, then white.
+fn fixture() -> ScreenshotResult {
+    let (width, height) = (400u32, 100u32);
+    let mut image_data = Vec::with_capacity((width * height * 4) as usize);
+    for _y in 0..height {
+        for x in 0..width {
+            let pixel = match x / 100 {
+                0 => RED,
+                1 => GREEN,
+                2 => BLUE,
+                _ => WHITE,
+            };
+            image_data.extend_from_slice(&pixel);
+        }
+    }
+    ScreenshotResult {
+        image_data,
+        width,
+        height,
+        monitor: None,
+    }
+}
+
+/// "Reads" the panel colour of an image and tracks how many reads overlap.
+#[derive(Default)]
+struct PanelReader {
+    in_flight: AtomicUsize,
+    max_in_flight: AtomicUsize,
+    locales: Mutex<Vec<Option<String>>>,
+}
+
+impl OcrRecognizer for PanelReader {
+    async fn recognize(
+        &self,
+        image: DynamicImage,
+        locale: Option<&str>,
+    ) -> Result<String, AutomationError> {
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+        self.locales
+            .lock()
+            .unwrap()
+            .push(locale.map(str::to_string));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+        let text = match image.get_pixel(0, 0).0 {
+            RED => "Invoice total",
+            GREEN => "Approved",
+            BLUE => "Due 2024-05-01",
+            _ => return Err(AutomationError::PlatformError("no text".to_string())),
+        };
+        Ok(format!("{text} ({}x{})", image.width(), image.height()))
+    }
+}
+
+#[tokio::test]
+async fn test_ocr_regions_returns_text_per_region() {
+    let reader = PanelReader::default();
+    let regions = vec![
+        OcrRegion::new("total", 0, 0, 100, 100),
+        OcrRegion::new("status", 100, 10, 100, 50),
+        OcrRegion::new("due", 200, 0, 100, 100),
+    ];
+    let options = OcrBatchOptions {
+        locale: Some("en-US".to_string()),
+        max_concurrency: 2,
+    };
+
+    let results = ocr_regions(&reader, &fixture(), &regions, &options)
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(
+        results["total"].as_ref().unwrap(),
+        "Invoice total (100x100)"
+    );
+    assert_eq!(results["status"].as_ref().unwrap(), "Approved (100x50)");
+    assert_eq!(results["due"].as_ref().unwrap(), "Due 2024-05-01 (100x100)");
+
+    // Every region was recognized with the per-call locale, at most two at a time
+    let locales = reader.locales.lock().unwrap();
+    assert_eq!(locales.len(), 3);
+    assert!(locales.iter().all(|l| l.as_deref() == Some("en-US")));
+    assert_eq!(reader.max_in_flight.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_failed_region_does_not_fail_the_batch() {
+    let reader = PanelReader::default();
+    let regions = vec![
+        OcrRegion::new("blank", 300, 0, 100, 100),
+        // Clipped to the screenshot: only 50px of it is on screen
+        OcrRegion::new("edge", 350, 0, 200, 100),
+        OcrRegion::new("offscreen", 500, 0, 10, 10),
+        OcrRegion::new("status", 100, 0, 100, 100),
+    ];
+
+    let results = ocr_regions(&reader, &fixture(), &regions, &OcrBatchOptions::default())
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        results["blank"],
+        Err(AutomationError::PlatformError(_))
+    ));
+    assert!(matches!(
+        results["edge"],
+        Err(AutomationError::PlatformError(_))
+    ));
+    assert!(matches!(
+        results["offscreen"],
+        Err(AutomationError::InvalidArgument(_))
+    ));
+    assert_eq!(results["status"].as_ref().unwrap(), "Approved (100x100)");
+    // The off-screen region never reached the recognizer
+    assert_eq!(reader.locales.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn test_duplicate_labels_are_rejected() {
+    let regions = vec![
+        OcrRegion::new("a", 0, 0, 10, 10),
+        OcrRegion::new("a", 100, 0, 10, 10),
+    ];
+    let err = ocr_regions(
+        &PanelReader::default(),
+        &fixture(),
+        &regions,
+        &OcrBatchOptions::default(),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, AutomationError::InvalidArgument(msg) if msg.contains("'a'")));
+}
@@ -0,0 +1,119 @@
+use crate::ordering::{sort_tree, OrderKey};
+use crate::{UIElementAttributes, UINode};
+
+fn node(name: &str, bounds: Option<(f64, f64, f64, f64)>, children: Vec<UINode>) -> UINode {
+    UINode {
+        id: Some(format!("id-{name}")),
+        attributes: UIElementAttributes {
+            role: "Button".to_string(),
+            name: Some(name.to_string()),
+            bounds,
+            ..Default::default()
+        },
+        children,
+    }
+}
+
+fn names(node: &UINode) -> Vec<String> {
+    let mut out = vec![node.attributes.name.clone().unwrap_or_default()];
+    for child in &node.children {
+        out.extend(names(child));
+    }
+    out
+}
+
+/// A form with two rows of buttons, a container without bounds and a node with no geometry
+fn sample_tree(permutation: &[usize]) -> UINode {
+    let children = [
+        node("row2-right", Some((200.0, 50.0, 80.0, 20.0)), vec![]),
+        node("row1-left", Some((10.0, 10.0, 80.0, 20.0)), vec![]),
+        node(
+            "toolbar",
+            None,
+            vec![
+                node("tool-b", Some((60.0, 30.0, 20.0, 20.0)), vec![]),
+                node("tool-a", Some((20.0, 30.0, 20.0, 20.0)), vec![]),
+            ],
+        ),
+        node("no-geometry", None, vec![]),
+        node("row1-right", Some((200.0, 10.4, 80.0, 20.0)), vec![]),
+    ];
+    let children = permutation.iter().map(|&i| children[i].clone()).collect();
+    node("window", Some((0.0, 0.0, 400.0, 300.0)), children)
+}
+
+#[test]
+fn test_sort_tree_is_reading_order() {
+    let mut tree = sample_tree(&[0, 1, 2, 3, 4]);
+    sort_tree(&mut tree);
+    assert_eq!(
+        names(&tree),
+        vec![
+            "window",
+            "row1-left",
+            "row1-right",
+            "toolbar",
+            "tool-a",
+            "tool-b",
+            "row2-right",
+            "no-geometry",
+        ]
+    );
+}
+
+#[test]
+fn test_sort_tree_is_stable_across_reads() {
+    // Each permutation stands for the platform returning the same UI in a different order
+    let reads = [
+        [0, 1, 2, 3, 4],
+        [4, 3, 2, 1, 0],
+        [2, 0, 4, 1, 3],
+        [3, 4, 0, 2, 1],
+    ];
+    let mut expected = None;
+    for permutation in reads {
+        let mut tree = sample_tree(&permutation);
+        sort_tree(&mut tree);
+        let order = names(&tree);
+        match &expected {
+            None => expected = Some(order),
+            Some(expected) => assert_eq!(&order, expected, "read {permutation:?} differs"),
+        }
+    }
+}
+
+#[test]
+fn test_order_key_tiebreaks_on_id() {
+    let bounds = Some((5.0, 5.0, 10.0, 10.0));
+    let a = OrderKey::new(bounds, Some("alpha".to_string()));
+    let b = OrderKey::new(bounds, Some("beta".to_string()));
+    assert!(a < b);
+    // Sub-pixel jitter between reads does not change the order
+    let jittered = OrderKey::new(Some((5.3, 4.8, 10.0, 10.0)), Some("beta".to_string()));
+    assert_eq!(b, jittered);
+    assert!(OrderKey::new(bounds, None) < OrderKey::new(None, Some("alpha".to_string())));
+}
+
+#[cfg(target_os = "windows")]
+#[tokio::test]
+async fn test_repeated_reads_of_live_tree_keep_order() -> Result<(), crate::AutomationError> {
+    let desktop = crate::Desktop::new(false, false)?;
+    let calculator = desktop.open_application("calc")?;
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    let pid = calculator.process_id()?;
+
+    let first = names(&desktop.get_window_tree(pid, None, None)?);
+    let second = names(&desktop.get_window_tree(pid, None, None)?);
+    assert_eq!(first, second, "tree order changed between reads");
+
+    let buttons = calculator.locator("role:Button")?;
+    let read = |elements: Vec<crate::UIElement>| -> Vec<Option<String>> {
+        elements.iter().map(|e| e.id()).collect()
+    };
+    let first = read(buttons.all(None, None).await?);
+    let second = read(buttons.all(None, None).await?);
+    assert_eq!(first, second, "find_all order changed between reads");
+
+    let _ = calculator.close();
+    Ok(())
+}
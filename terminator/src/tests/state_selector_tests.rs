@@ -0,0 +1,133 @@
+use crate::selector::{matches_state, LiveState, StatePredicate};
+use crate::Selector;
+use std::cell::{Cell, RefCell};
+
+/// A control whose state can change between matches, counting how often it is read
+#[derive(Default)]
+struct Control {
+    value: RefCell<Option<String>>,
+    toggled: Cell<Option<bool>>,
+    reads: Cell<usize>,
+}
+
+impl Control {
+    fn checkbox(checked: bool) -> Self {
+        Self {
+            toggled: Cell::new(Some(checked)),
+            ..Default::default()
+        }
+    }
+
+    fn edit(value: &str) -> Self {
+        Self {
+            value: RefCell::new(Some(value.to_string())),
+            ..Default::default()
+        }
+    }
+}
+
+impl LiveState for Control {
+    fn live_value(&self) -> Option<String> {
+        self.reads.set(self.reads.get() + 1);
+        self.value.borrow().clone()
+    }
+
+    fn live_toggled(&self) -> Option<bool> {
+        self.reads.set(self.reads.get() + 1);
+        self.toggled.get()
+    }
+}
+
+fn predicates(selector: &str) -> Vec<StatePredicate> {
+    match Selector::from(selector) {
+        Selector::State { predicates, .. } => predicates,
+        other => panic!("expected a state selector for {selector}, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_parse_state_selectors() {
+    assert_eq!(
+        Selector::from("checkbox[checked=true]"),
+        Selector::State {
+            role: Some("checkbox".to_string()),
+            predicates: vec![StatePredicate::Checked(true)],
+        }
+    );
+    assert_eq!(
+        Selector::from("role:edit[value='']"),
+        Selector::State {
+            role: Some("edit".to_string()),
+            predicates: vec![StatePredicate::Value(String::new())],
+        }
+    );
+    assert_eq!(
+        Selector::from("[value=\"hello world\"][toggled=false]"),
+        Selector::State {
+            role: None,
+            predicates: vec![
+                StatePredicate::Value("hello world".to_string()),
+                StatePredicate::Checked(false),
+            ],
+        }
+    );
+}
+
+#[test]
+fn test_invalid_state_predicates() {
+    assert!(matches!(
+        Selector::from("checkbox[checked=maybe]"),
+        Selector::Invalid(_)
+    ));
+    assert!(matches!(
+        Selector::from("checkbox[color=red]"),
+        Selector::Invalid(_)
+    ));
+    // Paths keep their own bracket syntax
+    assert!(matches!(
+        Selector::from("/Window[1]/Button[2]"),
+        Selector::Path(_)
+    ));
+}
+
+#[test]
+fn test_matches_checked_checkbox() {
+    let checked = predicates("checkbox[checked=true]");
+
+    assert!(matches_state(&checked, &Control::checkbox(true)));
+    assert!(!matches_state(&checked, &Control::checkbox(false)));
+    // Controls without a toggle pattern never match
+    assert!(!matches_state(&checked, &Control::edit("true")));
+}
+
+#[test]
+fn test_matches_empty_edit() {
+    let empty = predicates("edit[value='']");
+
+    assert!(matches_state(&empty, &Control::edit("")));
+    assert!(!matches_state(&empty, &Control::edit("typed")));
+    assert!(!matches_state(&empty, &Control::checkbox(false)));
+    assert!(matches_state(
+        &predicates("edit[value=Hello]"),
+        &Control::edit("hello")
+    ));
+}
+
+#[test]
+fn test_state_is_read_live_on_every_match() {
+    let checked = predicates("checkbox[checked=true]");
+    let control = Control::checkbox(false);
+
+    assert!(!matches_state(&checked, &control));
+    control.toggled.set(Some(true));
+    assert!(matches_state(&checked, &control));
+
+    let empty = predicates("edit[value='']");
+    let edit = Control::edit("draft");
+    assert!(!matches_state(&empty, &edit));
+    edit.value.replace(Some(String::new()));
+    assert!(matches_state(&empty, &edit));
+
+    assert_eq!(control.reads.get(), 2);
+    assert_eq!(edit.reads.get(), 2);
+}
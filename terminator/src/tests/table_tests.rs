@@ -0,0 +1,187 @@
+use crate::table::{find_cell, resolve_header, TableNode};
+use crate::AutomationError;
+use std::cell::Cell;
+use std::rc::Rc;
+
+#[derive(Debug, Clone)]
+struct Node {
+    role: &'static str,
+    name: &'static str,
+    children: Vec<Node>,
+}
+
+impl TableNode for Node {
+    fn role(&self) -> String {
+        self.role.to_string()
+    }
+
+    fn name(&self) -> Option<String> {
+        Some(self.name.to_string())
+    }
+
+    fn children(&self) -> Result<Vec<Self>, AutomationError> {
+        Ok(self.children.clone())
+    }
+}
+
+fn node(role: &'static str, name: &'static str, children: Vec<Node>) -> Node {
+    Node {
+        role,
+        name,
+        children,
+    }
+}
+
+fn row(cells: &[&'static str]) -> Node {
+    node(
+        "DataItem",
+        "",
+        cells.iter().map(|c| node("Text", c, vec![])).collect(),
+    )
+}
+
+/// A data grid shaped like UIA's: a `Header` of `HeaderItem`s, then `DataItem` rows.
+fn invoices() -> Node {
+    node(
+        "DataGrid",
+        "Invoices",
+        vec![
+            node(
+                "Header",
+                "",
+                vec![
+                    node("HeaderItem", "Number", vec![]),
+                    node("HeaderItem", "Customer", vec![]),
+                    node("HeaderItem", "Amount", vec![]),
+                ],
+            ),
+            row(&["INV-1", "Acme", "10.00"]),
+            row(&["INV-2", "Globex", "25.50"]),
+            row(&["INV-3", "Initech", "99.99"]),
+            row(&["INV-4", "Umbrella", "12.34"]),
+        ],
+    )
+}
+
+#[test]
+fn test_get_cell_by_header() {
+    let table = invoices();
+    assert_eq!(find_cell(&table, 3, "Amount").unwrap().name, "12.34");
+    assert_eq!(find_cell(&table, 0, "Customer").unwrap().name, "Acme");
+    // Header matching ignores case and surrounding whitespace
+    assert_eq!(find_cell(&table, 1, " amount ").unwrap().name, "25.50");
+}
+
+#[test]
+fn test_get_cell_with_flat_header_items() {
+    let table = node(
+        "List",
+        "",
+        vec![
+            node("HeaderItem", "Name", vec![]),
+            node("HeaderItem", "Size", vec![]),
+            row(&["a.txt", "1 KB"]),
+            row(&["b.txt", "2 KB"]),
+        ],
+    );
+    assert_eq!(find_cell(&table, 1, "Size").unwrap().name, "2 KB");
+}
+
+#[test]
+fn test_unknown_header_is_an_error() {
+    let err = find_cell(&invoices(), 0, "Total").unwrap_err();
+    match err {
+        AutomationError::InvalidArgument(msg) => {
+            assert!(msg.contains("'Total'"), "{msg}");
+            assert!(msg.contains("Amount"), "{msg}");
+        }
+        other => panic!("expected InvalidArgument, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_row_out_of_range_and_missing_header_row() {
+    assert!(matches!(
+        find_cell(&invoices(), 4, "Amount"),
+        Err(AutomationError::ElementNotFound(_))
+    ));
+    let headerless = node("DataGrid", "", vec![row(&["x"])]);
+    assert!(matches!(
+        find_cell(&headerless, 0, "Amount"),
+        Err(AutomationError::ElementNotFound(_))
+    ));
+}
+
+#[test]
+fn test_resolve_header_prefers_exact_match() {
+    let headers = vec!["id".to_string(), "ID".to_string()];
+    assert_eq!(resolve_header(&headers, "ID").unwrap(), 1);
+    assert_eq!(resolve_header(&headers, "Id").unwrap(), 0);
+}
+
+/// A table in another process: every property read is a round trip, except on the copy
+/// `cached_subtree` returns, which costs one round trip for the whole subtree and can't
+/// be acted on
+#[derive(Debug, Clone)]
+struct Remote {
+    node: Node,
+    round_trips: Rc<Cell<usize>>,
+    cached: bool,
+}
+
+impl Remote {
+    fn read(&self) -> &Node {
+        if !self.cached {
+            self.round_trips.set(self.round_trips.get() + 1);
+        }
+        &self.node
+    }
+}
+
+impl TableNode for Remote {
+    fn role(&self) -> String {
+        self.read().role()
+    }
+
+    fn name(&self) -> Option<String> {
+        self.read().name()
+    }
+
+    fn children(&self) -> Result<Vec<Self>, AutomationError> {
+        Ok(self
+            .read()
+            .children
+            .iter()
+            .map(|child| Remote {
+                node: child.clone(),
+                ..self.clone()
+            })
+            .collect())
+    }
+
+    fn cached_subtree(&self) -> Option<Self> {
+        self.round_trips.set(self.round_trips.get() + 1);
+        Some(Remote {
+            cached: true,
+            ..self.clone()
+        })
+    }
+}
+
+#[test]
+fn test_get_cell_reads_the_table_in_one_batch() {
+    let round_trips = Rc::new(Cell::new(0));
+    let table = Remote {
+        node: invoices(),
+        round_trips: round_trips.clone(),
+        cached: false,
+    };
+
+    let cell = find_cell(&table, 2, "Amount").unwrap();
+
+    assert_eq!(cell.node.name, "99.99");
+    // The cell comes from the live tree, so it can be acted on; reaching it costs the
+    // table's and the row's children, not a read per header and row
+    assert!(!cell.cached);
+    assert_eq!(round_trips.get(), 3);
+}
@@ -0,0 +1,71 @@
+use crate::z_order::{ensure_not_obscured, window_at, WindowLayer};
+use crate::AutomationError;
+
+fn layer(title: &str, process_id: u32, bounds: (f64, f64, f64, f64)) -> WindowLayer {
+    WindowLayer {
+        title: title.to_string(),
+        process_id,
+        bounds,
+    }
+}
+
+/// A dialog over an editor, which is over a full-screen browser; front to back
+fn stack() -> Vec<WindowLayer> {
+    vec![
+        layer("Save As", 30, (300.0, 200.0, 400.0, 300.0)),
+        layer("Notepad", 20, (100.0, 100.0, 800.0, 600.0)),
+        layer("Browser", 10, (0.0, 0.0, 1920.0, 1080.0)),
+    ]
+}
+
+#[test]
+fn test_window_at_returns_front_most_window() {
+    let layers = stack();
+    assert_eq!(window_at(&layers, 400.0, 300.0).unwrap().title, "Save As");
+    assert_eq!(window_at(&layers, 150.0, 150.0).unwrap().title, "Notepad");
+    assert_eq!(window_at(&layers, 10.0, 10.0).unwrap().title, "Browser");
+    assert!(window_at(&layers, 5000.0, 10.0).is_none());
+}
+
+#[test]
+fn test_right_and_bottom_edges_are_exclusive() {
+    let layers = vec![layer("Notepad", 20, (100.0, 100.0, 800.0, 600.0))];
+    assert!(window_at(&layers, 899.0, 699.0).is_some());
+    assert!(window_at(&layers, 900.0, 300.0).is_none());
+    assert!(window_at(&layers, 300.0, 700.0).is_none());
+}
+
+#[test]
+fn test_click_under_another_window_is_rejected() {
+    let notepad = (100.0, 100.0, 800.0, 600.0);
+    let err = ensure_not_obscured(&stack(), 20, notepad, 400.0, 300.0).unwrap_err();
+    match err {
+        AutomationError::ElementObscured(message) => assert!(message.contains("Save As")),
+        other => panic!("expected ElementObscured, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_click_on_visible_part_is_allowed() {
+    let notepad = (100.0, 100.0, 800.0, 600.0);
+    assert!(ensure_not_obscured(&stack(), 20, notepad, 150.0, 150.0).is_ok());
+
+    let dialog = (300.0, 200.0, 400.0, 300.0);
+    assert!(ensure_not_obscured(&stack(), 30, dialog, 400.0, 300.0).is_ok());
+}
+
+#[test]
+fn test_frame_border_differences_are_tolerated() {
+    // The accessibility frame omits the invisible resize border
+    let browser = (7.0, 0.0, 1906.0, 1073.0);
+    assert!(ensure_not_obscured(&stack(), 10, browser, 400.0, 300.0).is_err());
+    assert!(ensure_not_obscured(&stack(), 10, browser, 10.0, 10.0).is_ok());
+}
+
+#[test]
+fn test_unmatched_target_window_is_not_reported() {
+    // Same process, but no window with this frame is at the point
+    let other_window = (1000.0, 800.0, 200.0, 100.0);
+    assert!(ensure_not_obscured(&stack(), 20, other_window, 400.0, 300.0).is_ok());
+    assert!(ensure_not_obscured(&[], 20, other_window, 400.0, 300.0).is_ok());
+}
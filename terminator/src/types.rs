@@ -61,3 +61,20 @@ impl Drop for HighlightHandle {
         let _ = self.handle.take();
     }
 }
+
+/// Process and window metadata for the application in the foreground
+///
+/// Fields that cannot be read (for example because the process is protected)
+/// are `None` rather than failing the whole lookup.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppInfo {
+    /// Process name without the `.exe` extension
+    pub process_name: Option<String>,
+    pub pid: u32,
+    /// Title of the foreground window
+    pub window_title: Option<String>,
+    /// Full path of the process executable
+    pub executable_path: Option<String>,
+    /// File version of the executable (Windows only)
+    pub version: Option<String>,
+}
@@ -0,0 +1,77 @@
+//! Window stacking order and the obscured-click check.
+//!
+//! [`crate::Desktop::window_z_order`] lists the visible top-level windows front to back.
+//! Before a click at explicit coordinates, the windows containing the click point are
+//! walked in that order: if a window other than the target's own lies above it, the
+//! click would land on that window, so it is rejected with `ElementObscured`.
+//!
+//! The check only rejects what it can prove. When the target's window is not among the
+//! windows at the click point (its bounds could not be matched, or the point lies
+//! outside it), the click is allowed.
+
+use crate::AutomationError;
+use serde::{Deserialize, Serialize};
+
+/// Window frames reported by the accessibility tree and by the window manager can differ
+/// by the invisible resize borders on either side.
+const FRAME_TOLERANCE: f64 = 16.0;
+
+/// One visible top-level window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowLayer {
+    pub title: String,
+    pub process_id: u32,
+    /// `(x, y, width, height)` in screen coordinates
+    pub bounds: (f64, f64, f64, f64),
+}
+
+impl WindowLayer {
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        let (left, top, width, height) = self.bounds;
+        x >= left && x < left + width && y >= top && y < top + height
+    }
+
+    /// Whether this is the window with the given process and frame.
+    pub fn is_window(&self, process_id: u32, bounds: (f64, f64, f64, f64)) -> bool {
+        let (x, y, width, height) = self.bounds;
+        let close = |a: f64, b: f64| (a - b).abs() <= FRAME_TOLERANCE;
+        self.process_id == process_id
+            && close(x, bounds.0)
+            && close(y, bounds.1)
+            && close(width, bounds.2)
+            && close(height, bounds.3)
+    }
+}
+
+/// The front-most window containing the point, given layers ordered front to back.
+pub fn window_at(layers: &[WindowLayer], x: f64, y: f64) -> Option<&WindowLayer> {
+    layers.iter().find(|layer| layer.contains(x, y))
+}
+
+/// Fails with `ElementObscured` when another window lies above the target window at
+/// `(x, y)`.
+///
+/// `layers` must be ordered front to back; the target window is identified by its
+/// process id and frame.
+pub fn ensure_not_obscured(
+    layers: &[WindowLayer],
+    process_id: u32,
+    window_bounds: (f64, f64, f64, f64),
+    x: f64,
+    y: f64,
+) -> Result<(), AutomationError> {
+    let at_point: Vec<&WindowLayer> = layers.iter().filter(|l| l.contains(x, y)).collect();
+    let Some(target) = at_point
+        .iter()
+        .position(|l| l.is_window(process_id, window_bounds))
+    else {
+        return Ok(());
+    };
+    match at_point[..target].first() {
+        None => Ok(()),
+        Some(top) => Err(AutomationError::ElementObscured(format!(
+            "point ({x}, {y}) is covered by window '{}' (pid {})",
+            top.title, top.process_id
+        ))),
+    }
+}